@@ -0,0 +1,215 @@
+use facet_trait::{Def, Field, FieldFlags, Shape, ShapeExt as _, Variant, VariantKind};
+
+use crate::ArgsError;
+use crate::error::ArgsErrorKind;
+
+/// A command (the program, or one of its subcommands) described by the fields of a struct
+/// or of an enum variant
+pub(crate) struct Command {
+    /// The program name, followed by the names of the subcommands leading here
+    pub(crate) name: String,
+    pub(crate) doc: &'static [&'static str],
+    /// One per field, in the order of the fields
+    pub(crate) args: Vec<Arg>,
+}
+
+pub(crate) struct Arg {
+    pub(crate) field: &'static Field,
+    pub(crate) kind: ArgKind,
+}
+
+pub(crate) enum ArgKind {
+    /// A `bool` field, set by `--flag`
+    Switch,
+    /// `--flag <VALUE>`
+    Flag(Value),
+    /// A `#[facet(positional)]` field
+    Positional(Value),
+    /// An enum field, whose variants are subcommands
+    Subcommand {
+        variants: &'static [Variant],
+        required: bool,
+    },
+}
+
+/// What a flag or a positional argument holds
+pub(crate) struct Value {
+    /// The shape each value is parsed into: the item of a list, or the inside of an option
+    pub(crate) shape: &'static Shape,
+    /// The field is a list, and the argument can be repeated
+    pub(crate) multiple: bool,
+    /// The argument must be given: the field is neither a list, an option nor defaulted
+    pub(crate) required: bool,
+}
+
+impl Command {
+    /// Returns the command of the program, described by `shape`
+    pub(crate) fn root(program: &str, shape: &'static Shape) -> Self {
+        let fields = match shape.def {
+            Def::Struct(def) => def.fields,
+            _ => &[],
+        };
+        Self::new(program.to_string(), shape.doc, fields)
+    }
+
+    fn new(name: String, doc: &'static [&'static str], fields: &'static [Field]) -> Self {
+        let args = fields
+            .iter()
+            .map(|field| Arg {
+                field,
+                kind: ArgKind::of(field),
+            })
+            .collect();
+        Self { name, doc, args }
+    }
+
+    /// Returns the subcommand run by `variant`, along with the struct its arguments fill,
+    /// for newtype variants
+    pub(crate) fn subcommand(&self, variant: &'static Variant) -> (Self, Option<&'static Shape>) {
+        let name = format!("{} {}", self.name, kebab_case(variant.name));
+        match variant.kind {
+            VariantKind::Struct { fields } => (Self::new(name, variant.doc, fields), None),
+            VariantKind::Tuple { fields: [field] } => match field.shape.def {
+                Def::Struct(def) => {
+                    let doc = if variant.doc.is_empty() {
+                        field.shape.doc
+                    } else {
+                        variant.doc
+                    };
+                    (Self::new(name, doc, def.fields), Some(field.shape))
+                }
+                _ => (Self::new(name, variant.doc, &[]), Some(field.shape)),
+            },
+            _ => (Self::new(name, variant.doc, &[]), None),
+        }
+    }
+
+    /// Returns the subcommands, with their names on the command line
+    pub(crate) fn subcommands(&self) -> impl Iterator<Item = (String, &'static Variant)> {
+        self.args
+            .iter()
+            .filter_map(|arg| match arg.kind {
+                ArgKind::Subcommand { variants, .. } => Some(variants),
+                _ => None,
+            })
+            .flatten()
+            .map(|variant| (kebab_case(variant.name), variant))
+    }
+
+    /// Returns the usage line, e.g. `app build [OPTIONS] <PATH>`
+    pub(crate) fn usage(&self) -> String {
+        let mut usage = format!("{} [OPTIONS]", self.name);
+        for arg in &self.args {
+            match &arg.kind {
+                ArgKind::Flag(value) if value.required => {
+                    usage.push(' ');
+                    usage.push_str(&arg.synopsis());
+                }
+                ArgKind::Positional(_) => {
+                    usage.push(' ');
+                    usage.push_str(&arg.synopsis());
+                }
+                ArgKind::Subcommand { required: true, .. } => usage.push_str(" <COMMAND>"),
+                ArgKind::Subcommand {
+                    required: false, ..
+                } => usage.push_str(" [COMMAND]"),
+                _ => {}
+            }
+        }
+        usage
+    }
+
+    pub(crate) fn error(&self, kind: ArgsErrorKind) -> ArgsError {
+        ArgsError {
+            kind,
+            usage: self.usage(),
+        }
+    }
+}
+
+impl ArgKind {
+    fn of(field: &'static Field) -> Self {
+        let (inner, multiple, optional) = match field.shape.def {
+            Def::List(def) => (def.t, true, true),
+            Def::Option(def) => (def.t, false, true),
+            _ => (field.shape, false, false),
+        };
+        let positional = field.flags.contains(FieldFlags::POSITIONAL);
+        if let Def::Enum(def) = inner.def {
+            if !positional && !multiple && inner.vtable.parse.is_none() {
+                return ArgKind::Subcommand {
+                    variants: def.variants,
+                    required: !optional,
+                };
+            }
+        }
+        if !positional && field.shape.is_type::<bool>() {
+            return ArgKind::Switch;
+        }
+        let value = Value {
+            shape: inner,
+            multiple,
+            required: !optional && !field.flags.contains(FieldFlags::DEFAULT),
+        };
+        if positional {
+            ArgKind::Positional(value)
+        } else {
+            ArgKind::Flag(value)
+        }
+    }
+}
+
+impl Arg {
+    /// Returns the flag naming this argument, e.g. `--dry-run`
+    pub(crate) fn flag(&self) -> String {
+        format!("--{}", kebab_case(self.field.name))
+    }
+
+    /// Returns the placeholder for the argument's values, e.g. `DRY_RUN`
+    pub(crate) fn value_name(&self) -> String {
+        self.field.name.to_uppercase()
+    }
+
+    /// Returns how the argument is written in usage lines, e.g. `--name <NAME>` or
+    /// `[FILES]...`
+    pub(crate) fn synopsis(&self) -> String {
+        let name = self.value_name();
+        match &self.kind {
+            ArgKind::Switch => self.flag(),
+            ArgKind::Flag(value) => {
+                let repeat = if value.multiple { "..." } else { "" };
+                format!("{} <{name}>{repeat}", self.flag())
+            }
+            ArgKind::Positional(value) if value.multiple => format!("[{name}]..."),
+            ArgKind::Positional(value) if value.required => format!("<{name}>"),
+            ArgKind::Positional(_) => format!("[{name}]"),
+            ArgKind::Subcommand { .. } => "<COMMAND>".to_string(),
+        }
+    }
+}
+
+/// Returns the first line of a doc comment, trimmed
+pub(crate) fn summary(doc: &[&str]) -> String {
+    doc.first()
+        .map(|line| line.trim())
+        .unwrap_or_default()
+        .to_string()
+}
+
+/// Turns a field name (`dry_run`) or a variant name (`DryRun`) into `dry-run`
+pub(crate) fn kebab_case(name: &str) -> String {
+    let mut kebab = String::with_capacity(name.len());
+    for (i, c) in name.chars().enumerate() {
+        if c == '_' {
+            kebab.push('-');
+        } else if c.is_uppercase() {
+            if i > 0 && !kebab.ends_with('-') {
+                kebab.push('-');
+            }
+            kebab.extend(c.to_lowercase());
+        } else {
+            kebab.push(c);
+        }
+    }
+    kebab
+}
@@ -0,0 +1,270 @@
+use std::fmt::{self, Write as _};
+use std::str::FromStr;
+
+use crate::command::{ArgKind, Command, summary};
+
+/// A shell [`crate::completions`] can generate a script for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Shell {
+    /// Bash, through `complete -F`
+    Bash,
+    /// Zsh, through `_arguments`
+    Zsh,
+    /// Fish, through `complete -c`
+    Fish,
+}
+
+impl fmt::Display for Shell {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Shell::Bash => "bash",
+            Shell::Zsh => "zsh",
+            Shell::Fish => "fish",
+        })
+    }
+}
+
+/// Returned when parsing the name of a shell we don't generate completions for
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownShell(pub String);
+
+impl fmt::Display for UnknownShell {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unknown shell `{}`, expected bash, zsh or fish", self.0)
+    }
+}
+
+impl std::error::Error for UnknownShell {}
+
+impl FromStr for Shell {
+    type Err = UnknownShell;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "bash" => Ok(Shell::Bash),
+            "zsh" => Ok(Shell::Zsh),
+            "fish" => Ok(Shell::Fish),
+            _ => Err(UnknownShell(s.to_string())),
+        }
+    }
+}
+
+/// A command, along with the subcommands reached from it: `(word, name of the subcommand)`
+struct Node {
+    cmd: Command,
+    subcommands: Vec<(String, String)>,
+}
+
+/// Returns the command and all of its subcommands, parents first
+fn walk(cmd: Command, out: &mut Vec<Node>) {
+    let subs: Vec<(String, Command)> = cmd
+        .subcommands()
+        .map(|(word, variant)| (word, cmd.subcommand(variant).0))
+        .collect();
+    out.push(Node {
+        subcommands: subs
+            .iter()
+            .map(|(word, sub)| (word.clone(), sub.name.clone()))
+            .collect(),
+        cmd,
+    });
+    for (_, sub) in subs {
+        walk(sub, out);
+    }
+}
+
+pub(crate) fn script(root: Command, shell: Shell) -> String {
+    let program = root.name.clone();
+    let function = program
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect::<String>();
+    let mut nodes = Vec::new();
+    walk(root, &mut nodes);
+    match shell {
+        Shell::Bash => bash(&program, &function, &nodes),
+        Shell::Zsh => zsh(&program, &function, &nodes),
+        Shell::Fish => fish(&program, &function, &nodes),
+    }
+}
+
+fn bash(program: &str, function: &str, nodes: &[Node]) -> String {
+    let mut out = String::new();
+    writeln!(out, "_{function}() {{").unwrap();
+    writeln!(
+        out,
+        "    local cur=\"${{COMP_WORDS[COMP_CWORD]}}\" cmd=\"{program}\" i"
+    )
+    .unwrap();
+    writeln!(out, "    for ((i = 1; i < COMP_CWORD; i++)); do").unwrap();
+    writeln!(out, "        case \"$cmd:${{COMP_WORDS[i]}}\" in").unwrap();
+    for node in nodes {
+        for (word, name) in &node.subcommands {
+            writeln!(
+                out,
+                "            \"{}:{word}\") cmd=\"{name}\" ;;",
+                node.cmd.name
+            )
+            .unwrap();
+        }
+    }
+    writeln!(out, "        esac").unwrap();
+    writeln!(out, "    done").unwrap();
+    writeln!(out, "    case \"$cmd\" in").unwrap();
+    for node in nodes {
+        let words = node
+            .cmd
+            .args
+            .iter()
+            .filter(|arg| matches!(arg.kind, ArgKind::Switch | ArgKind::Flag(_)))
+            .map(|arg| arg.flag())
+            .chain(["--help".to_string()])
+            .chain(node.subcommands.iter().map(|(word, _)| word.clone()))
+            .collect::<Vec<_>>()
+            .join(" ");
+        writeln!(
+            out,
+            "        \"{}\") COMPREPLY=($(compgen -W \"{words}\" -- \"$cur\")) ;;",
+            node.cmd.name
+        )
+        .unwrap();
+    }
+    writeln!(out, "    esac").unwrap();
+    writeln!(out, "}}").unwrap();
+    writeln!(out).unwrap();
+    writeln!(out, "complete -o default -F _{function} {program}").unwrap();
+    out
+}
+
+fn zsh(program: &str, function: &str, nodes: &[Node]) -> String {
+    let mut out = String::new();
+    writeln!(out, "#compdef {program}").unwrap();
+    writeln!(out).unwrap();
+    writeln!(out, "_{function}() {{").unwrap();
+    writeln!(out, "    local cmd=\"{program}\" i").unwrap();
+    writeln!(out, "    for ((i = 2; i < CURRENT; i++)); do").unwrap();
+    writeln!(out, "        case \"$cmd:${{words[i]}}\" in").unwrap();
+    for node in nodes {
+        for (word, name) in &node.subcommands {
+            writeln!(
+                out,
+                "            \"{}:{word}\") cmd=\"{name}\"; words=(\"${{(@)words[i,-1]}}\"); (( CURRENT -= i - 1 )); i=1 ;;",
+                node.cmd.name
+            )
+            .unwrap();
+        }
+    }
+    writeln!(out, "        esac").unwrap();
+    writeln!(out, "    done").unwrap();
+    writeln!(out, "    case \"$cmd\" in").unwrap();
+    for node in nodes {
+        let mut specs = Vec::new();
+        for arg in &node.cmd.args {
+            let doc = zsh_escape(&summary(arg.field.doc));
+            let name = arg.value_name();
+            match &arg.kind {
+                ArgKind::Switch => specs.push(format!("'{}[{doc}]'", arg.flag())),
+                ArgKind::Flag(value) => {
+                    let repeat = if value.multiple { "*" } else { "" };
+                    specs.push(format!("'{repeat}{}[{doc}]:{name}:'", arg.flag()));
+                }
+                ArgKind::Positional(value) if value.multiple => {
+                    specs.push(format!("'*:{name}:_files'"));
+                }
+                ArgKind::Positional(value) => {
+                    let optional = if value.required { "" } else { ":" };
+                    specs.push(format!("'{optional}:{name}:_files'"));
+                }
+                ArgKind::Subcommand { .. } => {}
+            }
+        }
+        specs.push("'(-h --help)'{-h,--help}'[Print help]'".to_string());
+        if !node.subcommands.is_empty() {
+            let commands = node
+                .cmd
+                .subcommands()
+                .map(|(word, variant)| {
+                    let doc = zsh_escape(&summary(node.cmd.subcommand(variant).0.doc));
+                    format!("{word}\\:\"{doc}\"")
+                })
+                .collect::<Vec<_>>()
+                .join(" ");
+            specs.push(format!("':command:(({commands}))'"));
+        }
+        writeln!(out, "        \"{}\")", node.cmd.name).unwrap();
+        writeln!(out, "            _arguments \\").unwrap();
+        let last = specs.len() - 1;
+        for (i, spec) in specs.iter().enumerate() {
+            let continuation = if i == last { "" } else { " \\" };
+            writeln!(out, "                {spec}{continuation}").unwrap();
+        }
+        writeln!(out, "            ;;").unwrap();
+    }
+    writeln!(out, "    esac").unwrap();
+    writeln!(out, "}}").unwrap();
+    writeln!(out).unwrap();
+    writeln!(out, "_{function} \"$@\"").unwrap();
+    out
+}
+
+fn fish(program: &str, function: &str, nodes: &[Node]) -> String {
+    let mut out = String::new();
+    writeln!(out, "function __fish_{function}_command").unwrap();
+    writeln!(out, "    set -l cmd \"{program}\"").unwrap();
+    writeln!(out, "    for word in (commandline -opc)[2..-1]").unwrap();
+    writeln!(out, "        switch \"$cmd:$word\"").unwrap();
+    for node in nodes {
+        for (word, name) in &node.subcommands {
+            writeln!(out, "            case \"{}:{word}\"", node.cmd.name).unwrap();
+            writeln!(out, "                set cmd \"{name}\"").unwrap();
+        }
+    }
+    writeln!(out, "        end").unwrap();
+    writeln!(out, "    end").unwrap();
+    writeln!(out, "    echo $cmd").unwrap();
+    writeln!(out, "end").unwrap();
+    writeln!(out).unwrap();
+    for node in nodes {
+        let condition = format!(
+            "complete -c {program} -n 'test (__fish_{function}_command) = \"{}\"'",
+            node.cmd.name
+        );
+        for arg in &node.cmd.args {
+            let doc = fish_escape(&summary(arg.field.doc));
+            let long = arg.flag();
+            let long = &long[2..];
+            match arg.kind {
+                ArgKind::Switch => writeln!(out, "{condition} -l {long} -d '{doc}'").unwrap(),
+                ArgKind::Flag(_) => writeln!(out, "{condition} -l {long} -r -d '{doc}'").unwrap(),
+                _ => {}
+            }
+        }
+        writeln!(out, "{condition} -s h -l help -d 'Print help'").unwrap();
+        for (word, variant) in node.cmd.subcommands() {
+            let doc = fish_escape(&summary(node.cmd.subcommand(variant).0.doc));
+            writeln!(out, "{condition} -f -a {word} -d '{doc}'").unwrap();
+        }
+    }
+    out
+}
+
+/// Escapes text for a single-quoted `_arguments` spec
+fn zsh_escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '\'' => escaped.push_str("'\\''"),
+            '\\' | '[' | ']' | ':' | '"' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Escapes text for a single-quoted fish string
+fn fish_escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('\'', "\\'")
+}
@@ -0,0 +1,184 @@
+use std::fmt;
+
+use facet_trait::{ParseError, Shape};
+
+/// Returned when the arguments can't be parsed, or when they ask for help
+///
+/// Its `Display` implementation is what a command-line tool prints: the help text, or the
+/// error followed by the usage of the command. [`ArgsError::exit`] does just that.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArgsError {
+    /// What went wrong
+    pub kind: ArgsErrorKind,
+    /// The usage line of the command being parsed
+    pub(crate) usage: String,
+}
+
+/// What went wrong while parsing arguments
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum ArgsErrorKind {
+    /// `--help` or `-h` was given: this is the help text of the command
+    Help(String),
+    /// A flag the command doesn't have
+    UnknownFlag {
+        /// The flag, as given
+        flag: String,
+        /// The command's flag that's spelled the closest, if any is close enough
+        suggestion: Option<String>,
+    },
+    /// A subcommand the command doesn't have
+    UnknownSubcommand {
+        /// The subcommand, as given
+        name: String,
+        /// The command's subcommand that's spelled the closest, if any is close enough
+        suggestion: Option<String>,
+    },
+    /// A flag that takes a value was last on the command line
+    MissingValue {
+        /// The flag
+        flag: String,
+    },
+    /// A required flag or positional argument wasn't given
+    Missing {
+        /// The flag or the argument, e.g. `--name <NAME>` or `<PATH>`
+        arg: String,
+    },
+    /// The command has subcommands, and none was given
+    MissingSubcommand,
+    /// A value doesn't parse
+    Invalid {
+        /// The flag or the argument the value was given to
+        arg: String,
+        /// The value, unless the field is sensitive
+        value: Option<String>,
+        /// Why it doesn't parse
+        error: ParseError,
+    },
+    /// A positional argument the command has no room for
+    UnexpectedArgument(String),
+    /// The field behind an argument has a type that can't be parsed from the command line
+    Unsupported {
+        /// The flag or the argument
+        arg: String,
+        /// The type of its values
+        shape: &'static Shape,
+    },
+}
+
+impl ArgsError {
+    /// Returns true if this is a request for help rather than an error
+    pub fn is_help(&self) -> bool {
+        matches!(self.kind, ArgsErrorKind::Help(_))
+    }
+
+    /// Prints the help text to stdout and exits successfully, or prints the error to stderr
+    /// and exits with status 2
+    pub fn exit(&self) -> ! {
+        if self.is_help() {
+            print!("{self}");
+            std::process::exit(0)
+        }
+        eprint!("{self}");
+        std::process::exit(2)
+    }
+}
+
+impl fmt::Display for ArgsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let tip = match &self.kind {
+            ArgsErrorKind::Help(help) => return write!(f, "{help}"),
+            ArgsErrorKind::UnknownFlag {
+                suggestion: Some(suggestion),
+                ..
+            } => Some(("flag", suggestion)),
+            ArgsErrorKind::UnknownSubcommand {
+                suggestion: Some(suggestion),
+                ..
+            } => Some(("subcommand", suggestion)),
+            _ => None,
+        };
+        writeln!(f, "error: {}", self.kind)?;
+        if let Some((what, suggestion)) = tip {
+            writeln!(f, "\n  tip: a similar {what} exists: `{suggestion}`")?;
+        }
+        writeln!(f, "\nUsage: {}", self.usage)?;
+        writeln!(f, "\nFor more information, try `--help`.")
+    }
+}
+
+impl fmt::Display for ArgsErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ArgsErrorKind::Help(help) => write!(f, "{help}"),
+            ArgsErrorKind::UnknownFlag { flag, .. } => write!(f, "unexpected flag `{flag}`"),
+            ArgsErrorKind::UnknownSubcommand { name, .. } => {
+                write!(f, "unrecognized subcommand `{name}`")
+            }
+            ArgsErrorKind::MissingValue { flag } => write!(f, "`{flag}` needs a value"),
+            ArgsErrorKind::Missing { arg } => {
+                write!(f, "the argument `{arg}` is required but wasn't given")
+            }
+            ArgsErrorKind::MissingSubcommand => write!(f, "a subcommand is required"),
+            ArgsErrorKind::Invalid {
+                arg,
+                value: Some(value),
+                error,
+            } => write!(f, "invalid value {value:?} for `{arg}`: {error}"),
+            ArgsErrorKind::Invalid {
+                arg,
+                value: None,
+                error,
+            } => write!(f, "invalid value for `{arg}`: {error}"),
+            ArgsErrorKind::UnexpectedArgument(arg) => write!(f, "unexpected argument `{arg}`"),
+            ArgsErrorKind::Unsupported { arg, shape } => {
+                write!(
+                    f,
+                    "`{arg}` takes a {shape}, which can't be parsed from an argument"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for ArgsError {}
+
+/// Returns the candidate closest to `given`, if it's close enough to be a typo
+pub(crate) fn suggest<'a>(
+    given: &str,
+    candidates: impl IntoIterator<Item = &'a str>,
+) -> Option<String> {
+    candidates
+        .into_iter()
+        .map(|candidate| (distance(given, candidate), candidate))
+        .filter(|(distance, candidate)| *distance <= (candidate.len() / 3).max(1))
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate.to_string())
+}
+
+/// Returns the edit distance between `a` and `b`, where swapping two neighbouring
+/// characters counts as a single edit
+fn distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    // d[i][j] is the distance between the first i characters of a and the first j of b
+    let mut d = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in d.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for j in 0..=b.len() {
+        d[0][j] = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            d[i][j] = (d[i - 1][j] + 1)
+                .min(d[i][j - 1] + 1)
+                .min(d[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                d[i][j] = d[i][j].min(d[i - 2][j - 2] + 1);
+            }
+        }
+    }
+    d[a.len()][b.len()]
+}
@@ -0,0 +1,62 @@
+use std::fmt::Write as _;
+
+use crate::command::{ArgKind, Command, summary};
+
+/// Returns the `--help` text of a command
+pub(crate) fn help(cmd: &Command) -> String {
+    let mut out = String::new();
+    if !cmd.doc.is_empty() {
+        for line in cmd.doc {
+            writeln!(out, "{}", line.trim()).unwrap();
+        }
+        out.push('\n');
+    }
+    writeln!(out, "Usage: {}", cmd.usage()).unwrap();
+
+    let commands: Vec<(String, String)> = cmd
+        .subcommands()
+        .map(|(name, variant)| {
+            let (sub, _) = cmd.subcommand(variant);
+            (name, summary(sub.doc))
+        })
+        .collect();
+    section(&mut out, "Commands", &commands);
+
+    let arguments: Vec<(String, String)> = cmd
+        .args
+        .iter()
+        .filter(|arg| matches!(arg.kind, ArgKind::Positional(_)))
+        .map(|arg| (arg.synopsis(), summary(arg.field.doc)))
+        .collect();
+    section(&mut out, "Arguments", &arguments);
+
+    let options: Vec<(String, String)> = cmd
+        .args
+        .iter()
+        .filter(|arg| matches!(arg.kind, ArgKind::Switch | ArgKind::Flag(_)))
+        .map(|arg| (format!("    {}", arg.synopsis()), summary(arg.field.doc)))
+        .chain([("-h, --help".to_string(), "Print help".to_string())])
+        .collect();
+    section(&mut out, "Options", &options);
+    out
+}
+
+/// Writes a titled list of entries, with their descriptions lined up
+fn section(out: &mut String, title: &str, entries: &[(String, String)]) {
+    if entries.is_empty() {
+        return;
+    }
+    let width = entries
+        .iter()
+        .map(|(name, _)| name.len())
+        .max()
+        .unwrap_or(0);
+    write!(out, "\n{title}:\n").unwrap();
+    for (name, description) in entries {
+        if description.is_empty() {
+            writeln!(out, "  {name}").unwrap();
+        } else {
+            writeln!(out, "  {name:width$}  {description}").unwrap();
+        }
+    }
+}
@@ -0,0 +1,113 @@
+#![warn(missing_docs)]
+#![doc = include_str!("../README.md")]
+//!
+//! The fields of a struct become the command's arguments:
+//!
+//!   * a field is a `--flag` named after it, in kebab case: `dry_run` is `--dry-run`, and
+//!     its value is given as `--dry-run value` or `--dry-run=value`
+//!   * a `#[facet(positional)]` field is a positional argument instead
+//!   * a `bool` field is a switch: `--verbose` sets it
+//!   * a `Vec<T>` field can be given any number of times, and an `Option<T>` or a
+//!     `#[facet(default)]` field can be left out
+//!   * an enum field holds a subcommand: each variant is one, named in kebab case, and its
+//!     fields (or those of the struct it wraps) are the subcommand's arguments
+//!   * doc comments on the struct, its fields and the variants are the help text
+//!
+//! Values are parsed with the `parse` entry of their shape's vtable, that is with
+//! `FromStr`.
+
+use facet_poke::Poke;
+use facet_trait::{Def, Facet};
+
+mod command;
+mod completions;
+mod error;
+mod help;
+mod parse;
+
+pub use completions::{Shell, UnknownShell};
+pub use error::{ArgsError, ArgsErrorKind};
+
+use command::Command;
+use parse::{Fields, parse_command};
+
+/// Parses a `T` out of `args`, which don't include the program name
+///
+/// `program` is used in the help text and in errors.
+///
+/// ```rust
+/// use facet_derive::Facet;
+/// use facet_trait::{self as facet, Facet};
+///
+/// /// Copies files around
+/// #[derive(Debug, Facet)]
+/// struct Cp {
+///     /// Print each file as it's copied
+///     verbose: bool,
+///     /// How many files to copy at once
+///     #[facet(default)]
+///     jobs: u32,
+///     /// Where to copy the files to
+///     #[facet(positional)]
+///     to: String,
+///     /// The files to copy
+///     #[facet(positional)]
+///     files: Vec<String>,
+/// }
+///
+/// let cp: Cp = facet_args::from_args("cp", ["--jobs=4", "/tmp", "a.txt", "b.txt"]).unwrap();
+/// assert!(!cp.verbose);
+/// assert_eq!(cp.jobs, 4);
+/// assert_eq!(cp.to, "/tmp");
+/// assert_eq!(cp.files, ["a.txt", "b.txt"]);
+///
+/// let err = facet_args::from_args::<Cp>("cp", ["--verbsoe", "/tmp"]).unwrap_err();
+/// assert!(err.to_string().contains("a similar flag exists: `--verbose`"));
+/// ```
+///
+/// # Errors
+///
+/// Returns an error if the arguments don't match the command, or if they ask for help:
+/// see [`ArgsError::exit`].
+pub fn from_args<T: Facet>(
+    program: &str,
+    args: impl IntoIterator<Item = impl Into<String>>,
+) -> Result<T, ArgsError> {
+    let args: Vec<String> = args.into_iter().map(Into::into).collect();
+    let cmd = Command::root(program, T::SHAPE);
+    let (poke, _guard) = Poke::alloc::<T>();
+    let Def::Struct(_) = T::SHAPE.def else {
+        return Err(cmd.error(ArgsErrorKind::Unsupported {
+            arg: program.to_string(),
+            shape: T::SHAPE,
+        }));
+    };
+    let opaque = parse_command(&cmd, Fields::Struct(poke.into_struct()), &args)?;
+    Ok(unsafe { opaque.read::<T>() })
+}
+
+/// Parses a `T` out of the process's arguments, printing help or errors and exiting if
+/// they don't describe one
+pub fn from_env<T: Facet>() -> T {
+    let mut args = std::env::args();
+    let program = args
+        .next()
+        .as_deref()
+        .and_then(|path| std::path::Path::new(path).file_name()?.to_str())
+        .map(str::to_string)
+        .unwrap_or_else(|| "app".to_string());
+    from_args(&program, args).unwrap_or_else(|err| err.exit())
+}
+
+/// Returns the `--help` text of `T` as a command named `program`
+pub fn help<T: Facet>(program: &str) -> String {
+    help::help(&Command::root(program, T::SHAPE))
+}
+
+/// Returns a script that completes the arguments of `T` as a command named `program`
+///
+/// Installing it is up to the shell: source it from `.bashrc`, save it as `_program`
+/// somewhere in `$fpath`, or as `program.fish` in fish's `completions` directory.
+pub fn completions<T: Facet>(program: &str, shell: Shell) -> String {
+    completions::script(Command::root(program, T::SHAPE), shell)
+}
@@ -0,0 +1,290 @@
+use facet_poke::{Poke, PokeEnum, PokeStruct, Scratch};
+use facet_trait::{Def, FieldFlags, Opaque, Shape, Variant};
+
+use crate::ArgsError;
+use crate::command::{ArgKind, Command, Value};
+use crate::error::{ArgsErrorKind, suggest};
+use crate::help::help;
+
+/// The fields being filled: those of a struct, or of an enum's selected variant
+pub(crate) enum Fields<'mem> {
+    Struct(PokeStruct<'mem>),
+    Variant(PokeEnum<'mem>),
+}
+
+impl<'mem> Fields<'mem> {
+    fn field(&mut self, index: usize) -> Poke<'mem> {
+        match self {
+            Fields::Struct(ps) => ps.field(index),
+            Fields::Variant(pe) => pe.variant_field(index),
+        }
+        .expect("the command has one argument per field")
+    }
+
+    /// # Safety
+    ///
+    /// The field must have just been initialized.
+    unsafe fn mark_initialized(&mut self, index: usize) {
+        match self {
+            Fields::Struct(ps) => unsafe { ps.mark_initialized(index) },
+            Fields::Variant(pe) => unsafe { pe.mark_field_as_initialized(index) },
+        }
+    }
+
+    fn build_in_place(self) -> Opaque<'mem> {
+        match self {
+            Fields::Struct(ps) => ps.build_in_place(),
+            Fields::Variant(pe) => pe.build_in_place(),
+        }
+    }
+}
+
+/// What was given on the command line for one argument
+#[derive(Default)]
+struct Given {
+    values: Vec<String>,
+}
+
+/// Fills `fields` from `args`; on error, whatever was built is dropped along with `fields`
+pub(crate) fn parse_command<'mem>(
+    cmd: &Command,
+    mut fields: Fields<'mem>,
+    args: &[String],
+) -> Result<Opaque<'mem>, ArgsError> {
+    let mut given: Vec<Given> = cmd.args.iter().map(|_| Given::default()).collect();
+    let mut subcommand = None;
+    let mut positionals = cmd
+        .args
+        .iter()
+        .enumerate()
+        .filter(|(_, arg)| matches!(arg.kind, ArgKind::Positional(_)))
+        .map(|(index, _)| index)
+        .peekable();
+    let mut only_positionals = false;
+
+    let mut rest = args.iter().enumerate();
+    while let Some((position, raw)) = rest.next() {
+        if !only_positionals && raw == "--" {
+            only_positionals = true;
+            continue;
+        }
+        if !only_positionals && (raw == "--help" || raw == "-h") {
+            return Err(cmd.error(ArgsErrorKind::Help(help(cmd))));
+        }
+        if !only_positionals && raw.starts_with('-') && raw.len() > 1 && raw.parse::<f64>().is_err()
+        {
+            let (flag, inline) = match raw.split_once('=') {
+                Some((flag, value)) => (flag, Some(value.to_string())),
+                None => (raw.as_str(), None),
+            };
+            let Some(index) = cmd.args.iter().position(|arg| {
+                matches!(arg.kind, ArgKind::Switch | ArgKind::Flag(_)) && arg.flag() == flag
+            }) else {
+                let flags: Vec<String> = cmd
+                    .args
+                    .iter()
+                    .filter(|arg| matches!(arg.kind, ArgKind::Switch | ArgKind::Flag(_)))
+                    .map(|arg| arg.flag())
+                    .chain(["--help".to_string()])
+                    .collect();
+                return Err(cmd.error(ArgsErrorKind::UnknownFlag {
+                    flag: flag.to_string(),
+                    suggestion: suggest(flag, flags.iter().map(String::as_str)),
+                }));
+            };
+            let value = match (&cmd.args[index].kind, inline) {
+                (_, Some(value)) => value,
+                (ArgKind::Switch, None) => "true".to_string(),
+                (_, None) => match rest.next() {
+                    Some((_, value)) => value.clone(),
+                    None => {
+                        return Err(cmd.error(ArgsErrorKind::MissingValue {
+                            flag: flag.to_string(),
+                        }));
+                    }
+                },
+            };
+            given[index].values.push(value);
+            continue;
+        }
+
+        if let Some(&index) = positionals.peek() {
+            given[index].values.push(raw.clone());
+            let multiple = matches!(
+                &cmd.args[index].kind,
+                ArgKind::Positional(Value { multiple: true, .. })
+            );
+            if !multiple {
+                positionals.next();
+            }
+            continue;
+        }
+
+        let names: Vec<(String, _)> = cmd.subcommands().collect();
+        if names.is_empty() {
+            return Err(cmd.error(ArgsErrorKind::UnexpectedArgument(raw.clone())));
+        }
+        match names.iter().find(|(name, _)| name == raw) {
+            Some((_, variant)) => {
+                subcommand = Some((*variant, &args[position + 1..]));
+                break;
+            }
+            None => {
+                return Err(cmd.error(ArgsErrorKind::UnknownSubcommand {
+                    name: raw.clone(),
+                    suggestion: suggest(raw, names.iter().map(|(name, _)| name.as_str())),
+                }));
+            }
+        }
+    }
+
+    for (index, (arg, given)) in cmd.args.iter().zip(given).enumerate() {
+        let poke = fields.field(index);
+        match &arg.kind {
+            ArgKind::Switch => {
+                let value = given.values.last().map_or("false", String::as_str);
+                parse_value(poke, value, cmd, &arg.flag(), arg.field.flags)?;
+            }
+            ArgKind::Flag(value) | ArgKind::Positional(value) => {
+                let name = arg.synopsis();
+                build_value(poke, value, &given.values, cmd, &name, arg.field.flags)?;
+            }
+            ArgKind::Subcommand { .. } => {
+                build_subcommand(poke, subcommand, cmd)?;
+            }
+        }
+        unsafe { fields.mark_initialized(index) };
+    }
+    Ok(fields.build_in_place())
+}
+
+/// Builds a flag's or a positional argument's field out of the values given to it
+fn build_value<'mem>(
+    poke: Poke<'mem>,
+    value: &Value,
+    given: &[String],
+    cmd: &Command,
+    name: &str,
+    flags: FieldFlags,
+) -> Result<Opaque<'mem>, ArgsError> {
+    let shape = poke.shape();
+    match shape.def {
+        Def::List(_) => {
+            let mut items = Vec::with_capacity(given.len());
+            for raw in given {
+                items.push(parse_scratch(value.shape, raw, cmd, name, flags)?);
+            }
+            let Ok(mut pl) = poke.into_list().init(Some(items.len())) else {
+                return Err(unsupported(cmd, name, shape));
+            };
+            for mut item in items {
+                unsafe { pl.push(item.take()) };
+            }
+            Ok(pl.build_in_place())
+        }
+        Def::Option(_) => {
+            let po = poke.into_option();
+            match given.last() {
+                Some(raw) => {
+                    let mut inner = parse_scratch(value.shape, raw, cmd, name, flags)?;
+                    Ok(unsafe { po.init_some(inner.take()) })
+                }
+                None => Ok(po.init_none()),
+            }
+        }
+        _ => match given.last() {
+            Some(raw) => parse_value(poke, raw, cmd, name, flags),
+            None if flags.contains(FieldFlags::DEFAULT) => poke
+                .into_value()
+                .default_in_place()
+                .map_err(|_| unsupported(cmd, name, shape)),
+            None => Err(cmd.error(ArgsErrorKind::Missing {
+                arg: name.to_string(),
+            })),
+        },
+    }
+}
+
+/// Builds a subcommand field, out of the subcommand that was given and its arguments
+fn build_subcommand<'mem>(
+    poke: Poke<'mem>,
+    subcommand: Option<(&'static Variant, &[String])>,
+    cmd: &Command,
+) -> Result<Opaque<'mem>, ArgsError> {
+    let shape = poke.shape();
+    match (shape.def, subcommand) {
+        (Def::Option(_), None) => Ok(poke.into_option().init_none()),
+        (_, None) => Err(cmd.error(ArgsErrorKind::MissingSubcommand)),
+        (Def::Option(def), Some((variant, args))) => {
+            let (inner, mut scratch) = Scratch::alloc(def.t);
+            build_variant(inner, variant, args, cmd)?;
+            unsafe { scratch.mark_initialized() };
+            Ok(unsafe { poke.into_option().init_some(scratch.take()) })
+        }
+        (_, Some((variant, args))) => build_variant(poke, variant, args, cmd),
+    }
+}
+
+fn build_variant<'mem>(
+    poke: Poke<'mem>,
+    variant: &'static Variant,
+    args: &[String],
+    parent: &Command,
+) -> Result<Opaque<'mem>, ArgsError> {
+    let (cmd, newtype) = parent.subcommand(variant);
+    let mut pe = poke
+        .into_enum()
+        .set_variant_by_name(variant.name)
+        .expect("the variant exists");
+    match newtype {
+        Some(shape) if matches!(shape.def, Def::Struct(_)) => {
+            let inner = pe.variant_field(0).expect("newtype variants have a field");
+            parse_command(&cmd, Fields::Struct(inner.into_struct()), args)?;
+            unsafe { pe.mark_field_as_initialized(0) };
+            Ok(pe.build_in_place())
+        }
+        Some(shape) => Err(unsupported(parent, &cmd.name, shape)),
+        None => parse_command(&cmd, Fields::Variant(pe), args),
+    }
+}
+
+fn parse_value<'mem>(
+    poke: Poke<'mem>,
+    raw: &str,
+    cmd: &Command,
+    name: &str,
+    flags: FieldFlags,
+) -> Result<Opaque<'mem>, ArgsError> {
+    let shape = poke.shape();
+    if shape.vtable.parse.is_none() {
+        return Err(unsupported(cmd, name, shape));
+    }
+    poke.into_value().parse(raw).map_err(|(_, error)| {
+        cmd.error(ArgsErrorKind::Invalid {
+            arg: name.to_string(),
+            value: (!flags.contains(FieldFlags::SENSITIVE)).then(|| raw.to_string()),
+            error,
+        })
+    })
+}
+
+fn unsupported(cmd: &Command, name: &str, shape: &'static Shape) -> ArgsError {
+    cmd.error(ArgsErrorKind::Unsupported {
+        arg: name.to_string(),
+        shape,
+    })
+}
+
+/// Parses a value on the side, to be moved into its container
+fn parse_scratch(
+    shape: &'static Shape,
+    raw: &str,
+    cmd: &Command,
+    name: &str,
+    flags: FieldFlags,
+) -> Result<Scratch, ArgsError> {
+    let (poke, mut scratch) = Scratch::alloc(shape);
+    parse_value(poke, raw, cmd, name, flags)?;
+    unsafe { scratch.mark_initialized() };
+    Ok(scratch)
+}
@@ -0,0 +1,402 @@
+use facet_args::{ArgsErrorKind, Shell, completions, from_args, help};
+use facet_derive::Facet;
+use facet_trait::{Def, EnumDef, EnumRepr, Facet, Field, FieldFlags, Shape, Variant, VariantKind};
+
+use facet_trait as facet;
+
+/// Builds and runs projects
+#[derive(Debug, PartialEq, Facet)]
+struct Cli {
+    /// Print more output
+    verbose: bool,
+    /// Path to the project's manifest
+    #[facet(default)]
+    manifest_path: String,
+    /// Features to enable
+    features: Vec<String>,
+    /// How many jobs to run at once
+    jobs: Option<u32>,
+    command: Subcommand,
+}
+
+/// Runs a binary of the project
+#[derive(Debug, PartialEq, Facet)]
+struct Run {
+    /// The binary to run
+    #[facet(default)]
+    bin: String,
+    /// Arguments passed to the binary
+    #[facet(positional)]
+    args: Vec<String>,
+}
+
+/// The derive macro doesn't handle enums with fields yet, so the shape is written by hand.
+#[derive(Debug, PartialEq)]
+#[repr(u8)]
+enum Subcommand {
+    Build {
+        release: bool,
+        target: Option<String>,
+    },
+    Run(Run),
+    Clean,
+}
+
+// A `#[repr(u8)]` enum is laid out as a union of `#[repr(C)]` structs, one per variant,
+// each starting with the tag.
+#[repr(C)]
+struct BuildLayout {
+    _tag: u8,
+    release: bool,
+    target: Option<String>,
+}
+
+#[repr(C)]
+struct RunLayout {
+    _tag: u8,
+    run: Run,
+}
+
+unsafe impl Facet for Subcommand {
+    const ARCHETYPE: Self = Subcommand::Clean;
+    const SHAPE: &'static Shape = &const {
+        Shape::builder()
+            .layout(core::alloc::Layout::new::<Self>())
+            .vtable(facet::value_vtable!(Subcommand, |f, _opts| {
+                core::fmt::Write::write_str(f, "Subcommand")
+            }))
+            .def(Def::Enum(
+                EnumDef::builder()
+                    .repr(EnumRepr::U8)
+                    .variants(
+                        &const {
+                            [
+                                Variant::builder()
+                                    .name("Build")
+                                    .discriminant(Some(0))
+                                    .kind(VariantKind::Struct {
+                                        fields: &const {
+                                            [
+                                                Field::builder()
+                                                    .name("release")
+                                                    .shape(bool::SHAPE)
+                                                    .offset(core::mem::offset_of!(
+                                                        BuildLayout,
+                                                        release
+                                                    ))
+                                                    .flags(FieldFlags::EMPTY)
+                                                    .doc(&[" Build with optimizations"])
+                                                    .build(),
+                                                Field::builder()
+                                                    .name("target")
+                                                    .shape(<Option<String>>::SHAPE)
+                                                    .offset(core::mem::offset_of!(
+                                                        BuildLayout,
+                                                        target
+                                                    ))
+                                                    .flags(FieldFlags::POSITIONAL)
+                                                    .doc(&[" The target to build"])
+                                                    .build(),
+                                            ]
+                                        },
+                                    })
+                                    .doc(&[" Builds the project"])
+                                    .build(),
+                                Variant::builder()
+                                    .name("Run")
+                                    .discriminant(Some(1))
+                                    .kind(VariantKind::Tuple {
+                                        fields: &const {
+                                            [Field::builder()
+                                                .name("0")
+                                                .shape(Run::SHAPE)
+                                                .offset(core::mem::offset_of!(RunLayout, run))
+                                                .flags(FieldFlags::EMPTY)
+                                                .build()]
+                                        },
+                                    })
+                                    .build(),
+                                Variant::builder()
+                                    .name("Clean")
+                                    .discriminant(Some(2))
+                                    .kind(VariantKind::Unit)
+                                    .doc(&[" Removes build artifacts"])
+                                    .build(),
+                            ]
+                        },
+                    )
+                    .build(),
+            ))
+            .build()
+    };
+}
+
+fn parse(args: &[&str]) -> Result<Cli, facet_args::ArgsError> {
+    from_args("cargo", args.iter().copied())
+}
+
+#[test]
+fn flags_positionals_and_subcommands() {
+    let cli = parse(&[
+        "--verbose",
+        "--features",
+        "a",
+        "--features=b",
+        "--jobs",
+        "4",
+        "build",
+        "--release",
+        "wasm",
+    ])
+    .unwrap();
+    assert_eq!(
+        cli,
+        Cli {
+            verbose: true,
+            manifest_path: String::new(),
+            features: vec!["a".to_string(), "b".to_string()],
+            jobs: Some(4),
+            command: Subcommand::Build {
+                release: true,
+                target: Some("wasm".to_string()),
+            },
+        }
+    );
+
+    let cli = parse(&[
+        "--manifest-path",
+        "x/Cargo.toml",
+        "run",
+        "--bin",
+        "app",
+        "--",
+        "--port",
+        "-1",
+    ])
+    .unwrap();
+    assert_eq!(cli.manifest_path, "x/Cargo.toml");
+    assert!(!cli.verbose);
+    assert_eq!(cli.jobs, None);
+    assert_eq!(
+        cli.command,
+        Subcommand::Run(Run {
+            bin: "app".to_string(),
+            args: vec!["--port".to_string(), "-1".to_string()],
+        })
+    );
+
+    let cli = parse(&["clean"]).unwrap();
+    assert_eq!(cli.command, Subcommand::Clean);
+}
+
+#[test]
+fn help_comes_from_doc_comments() {
+    let err = parse(&["--help"]).unwrap_err();
+    assert!(err.is_help());
+    assert_eq!(err.to_string(), help::<Cli>("cargo"));
+    assert_eq!(
+        help::<Cli>("cargo"),
+        "\
+Builds and runs projects
+
+Usage: cargo [OPTIONS] <COMMAND>
+
+Commands:
+  build  Builds the project
+  run    Runs a binary of the project
+  clean  Removes build artifacts
+
+Options:
+      --verbose                        Print more output
+      --manifest-path <MANIFEST_PATH>  Path to the project's manifest
+      --features <FEATURES>...         Features to enable
+      --jobs <JOBS>                    How many jobs to run at once
+  -h, --help                           Print help
+"
+    );
+
+    let err = parse(&["--jobs", "2", "build", "-h"]).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "\
+Builds the project
+
+Usage: cargo build [OPTIONS] [TARGET]
+
+Arguments:
+  [TARGET]  The target to build
+
+Options:
+      --release  Build with optimizations
+  -h, --help     Print help
+"
+    );
+}
+
+#[test]
+fn usage_errors_suggest_fixes() {
+    let err = parse(&["--verbsoe", "clean"]).unwrap_err();
+    assert_eq!(
+        err.kind,
+        ArgsErrorKind::UnknownFlag {
+            flag: "--verbsoe".to_string(),
+            suggestion: Some("--verbose".to_string()),
+        }
+    );
+    assert_eq!(
+        err.to_string(),
+        "\
+error: unexpected flag `--verbsoe`
+
+  tip: a similar flag exists: `--verbose`
+
+Usage: cargo [OPTIONS] <COMMAND>
+
+For more information, try `--help`.
+"
+    );
+
+    let err = parse(&["biuld"]).unwrap_err();
+    assert_eq!(
+        err.kind,
+        ArgsErrorKind::UnknownSubcommand {
+            name: "biuld".to_string(),
+            suggestion: Some("build".to_string()),
+        }
+    );
+
+    let err = parse(&["build", "--frobnicate"]).unwrap_err();
+    assert!(
+        matches!(
+            &err.kind,
+            ArgsErrorKind::UnknownFlag {
+                suggestion: None,
+                ..
+            }
+        ),
+        "{err}"
+    );
+    assert!(
+        err.to_string()
+            .contains("Usage: cargo build [OPTIONS] [TARGET]"),
+        "{err}"
+    );
+
+    let err = parse(&["clean", "--jobs"]).unwrap_err();
+    assert!(
+        matches!(err.kind, ArgsErrorKind::UnknownFlag { .. }),
+        "{err}"
+    );
+
+    let err = parse(&["--jobs"]).unwrap_err();
+    assert_eq!(
+        err.kind,
+        ArgsErrorKind::MissingValue {
+            flag: "--jobs".to_string()
+        }
+    );
+
+    let err = parse(&["--jobs", "many", "clean"]).unwrap_err();
+    assert!(
+        err.to_string()
+            .starts_with("error: invalid value \"many\" for `--jobs <JOBS>`"),
+        "{err}"
+    );
+
+    let err = parse(&["--verbose"]).unwrap_err();
+    assert_eq!(err.kind, ArgsErrorKind::MissingSubcommand);
+
+    let err = parse(&["build", "a", "b"]).unwrap_err();
+    assert_eq!(err.kind, ArgsErrorKind::UnexpectedArgument("b".to_string()));
+}
+
+#[test]
+fn required_arguments_must_be_given() {
+    #[derive(Debug, Facet)]
+    struct Serve {
+        #[facet(positional)]
+        root: String,
+        port: u16,
+        #[facet(sensitive)]
+        token: Option<String>,
+    }
+
+    let err = from_args::<Serve>("serve", ["--port", "80"]).unwrap_err();
+    assert_eq!(
+        err.kind,
+        ArgsErrorKind::Missing {
+            arg: "<ROOT>".to_string()
+        }
+    );
+    assert!(
+        err.to_string()
+            .contains("Usage: serve [OPTIONS] <ROOT> --port <PORT>"),
+        "{err}"
+    );
+
+    let err = from_args::<Serve>("serve", ["www"]).unwrap_err();
+    assert_eq!(
+        err.kind,
+        ArgsErrorKind::Missing {
+            arg: "--port <PORT>".to_string()
+        }
+    );
+
+    let serve = from_args::<Serve>("serve", ["--token", "hunter2", "www", "--port=8080"]).unwrap();
+    assert_eq!((serve.root.as_str(), serve.port), ("www", 8080));
+    assert_eq!(serve.token.as_deref(), Some("hunter2"));
+}
+
+#[test]
+fn completion_scripts_cover_every_command() {
+    let bash = completions::<Cli>("cargo", Shell::Bash);
+    assert!(
+        bash.contains("\"cargo:build\") cmd=\"cargo build\" ;;"),
+        "{bash}"
+    );
+    assert!(
+        bash.contains("--verbose --manifest-path --features --jobs --help build run clean"),
+        "{bash}"
+    );
+    assert!(
+        bash.contains("\"cargo build\") COMPREPLY=($(compgen -W \"--release --help\""),
+        "{bash}"
+    );
+    assert!(
+        bash.ends_with("complete -o default -F _cargo cargo\n"),
+        "{bash}"
+    );
+
+    let zsh = completions::<Cli>("cargo", Shell::Zsh);
+    assert!(zsh.starts_with("#compdef cargo\n"), "{zsh}");
+    assert!(
+        zsh.contains("'*--features[Features to enable]:FEATURES:'"),
+        "{zsh}"
+    );
+    assert!(
+        zsh.contains("'--manifest-path[Path to the project'\\''s manifest]:MANIFEST_PATH:'"),
+        "{zsh}"
+    );
+    assert!(
+        zsh.contains("':command:((build\\:\"Builds the project\" run\\:"),
+        "{zsh}"
+    );
+    assert!(zsh.contains("'*:ARGS:_files'"), "{zsh}");
+
+    let fish = completions::<Cli>("cargo", Shell::Fish);
+    assert!(
+        fish.contains("complete -c cargo -n 'test (__fish_cargo_command) = \"cargo\"' -l jobs -r -d 'How many jobs to run at once'"),
+        "{fish}"
+    );
+    assert!(
+        fish.contains("complete -c cargo -n 'test (__fish_cargo_command) = \"cargo\"' -f -a clean -d 'Removes build artifacts'"),
+        "{fish}"
+    );
+    assert!(
+        fish.contains("complete -c cargo -n 'test (__fish_cargo_command) = \"cargo run\"' -l bin -r -d 'The binary to run'"),
+        "{fish}"
+    );
+
+    assert_eq!("zsh".parse::<Shell>(), Ok(Shell::Zsh));
+    assert!("powershell".parse::<Shell>().is_err());
+}
@@ -0,0 +1,94 @@
+#![warn(missing_docs)]
+#![doc = include_str!("../README.md")]
+
+//! Assertions that compare values by their structure
+//!
+//! [`assert_same!`] compares two values with [`facet_diff`], so the compared type doesn't
+//! need to implement `PartialEq`, and [`DiffOptions`] can rule out the differences a test
+//! doesn't care about. When the values differ, the panic message lists every difference
+//! with its path, as rendered by [`facet_pretty`].
+
+use std::fmt;
+
+use facet_diff::diff_with;
+use facet_peek::{Path, Peek};
+use facet_pretty::PrettyPrinter;
+use facet_trait::Facet;
+
+pub use facet_diff::{DiffOptions, FloatTolerance};
+pub use facet_peek::PathPattern;
+
+/// Asserts that two values have no differences, according to [`DiffOptions`] if given
+///
+/// ```rust
+/// use facet_assert::{DiffOptions, FloatTolerance, assert_same};
+/// use facet_derive::Facet;
+/// use facet_trait::{self as facet, Facet};
+///
+/// #[derive(Facet)]
+/// struct Sample {
+///     id: u64,
+///     mean: f64,
+/// }
+///
+/// let options = DiffOptions::new()
+///     .ignore("id".parse().unwrap())
+///     .float_tolerance(FloatTolerance::Epsilon(1e-9));
+/// assert_same!(
+///     Sample { id: 1, mean: 0.1 + 0.2 },
+///     Sample { id: 2, mean: 0.3 },
+///     &options
+/// );
+/// ```
+#[macro_export]
+macro_rules! assert_same {
+    ($left:expr, $right:expr $(,)?) => {
+        $crate::assert_same!($left, $right, &$crate::DiffOptions::default())
+    };
+    ($left:expr, $right:expr, $options:expr $(,)?) => {
+        if let Err(mismatch) = $crate::check_same(&$left, &$right, $options) {
+            panic!("assertion `left == right` failed: {mismatch}");
+        }
+    };
+}
+
+/// Returns an error describing the differences between `left` and `right`, if any
+pub fn check_same<T: Facet>(left: &T, right: &T, options: &DiffOptions) -> Result<(), Mismatch> {
+    let changes = diff_with(Peek::new(left), Peek::new(right), options);
+    if changes.is_empty() {
+        return Ok(());
+    }
+    // honors https://no-color.org, since the report usually ends up in test output
+    let printer = PrettyPrinter::new().with_colors(std::env::var_os("NO_COLOR").is_none());
+    Err(Mismatch {
+        paths: changes.iter().map(|change| change.path.clone()).collect(),
+        diff: printer.format_changes(&changes),
+    })
+}
+
+/// The differences found by [`check_same`]
+///
+/// Its [`Display`](fmt::Display) implementation renders them as a diff, where `-` lines
+/// are the left value and `+` lines the right value.
+#[derive(Debug, Clone)]
+pub struct Mismatch {
+    paths: Vec<Path>,
+    diff: String,
+}
+
+impl Mismatch {
+    /// Returns the paths of the differences, in the order they were found
+    pub fn paths(&self) -> &[Path] {
+        &self.paths
+    }
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let count = self.paths.len();
+        let plural = if count == 1 { "" } else { "s" };
+        write!(f, "{count} difference{plural}\n{}", self.diff)
+    }
+}
+
+impl std::error::Error for Mismatch {}
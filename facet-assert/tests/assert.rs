@@ -0,0 +1,105 @@
+use facet_assert::{DiffOptions, FloatTolerance, assert_same, check_same};
+use facet_derive::Facet;
+use facet_testhelpers::Handle;
+use facet_trait::Facet;
+
+use facet_trait as facet;
+
+/// Implements neither `PartialEq` nor `Debug`
+#[derive(Facet)]
+struct Snapshot {
+    id: String,
+    created_at: u64,
+    users: Vec<User>,
+    score: f64,
+}
+
+#[derive(Facet)]
+struct User {
+    name: String,
+    created_at: u64,
+    roles: Vec<String>,
+}
+
+fn snapshot(seed: u64) -> Snapshot {
+    Snapshot {
+        id: format!("run-{seed}"),
+        created_at: seed,
+        users: vec![
+            User {
+                name: "ada".to_string(),
+                created_at: seed + 1,
+                roles: vec!["admin".to_string(), "dev".to_string()],
+            },
+            User {
+                name: "bob".to_string(),
+                created_at: seed + 2,
+                roles: vec![],
+            },
+        ],
+        score: 0.1 + 0.2,
+    }
+}
+
+fn lenient() -> DiffOptions {
+    DiffOptions::new()
+        .ignore("id".parse().unwrap())
+        .ignore("**.created_at".parse().unwrap())
+        .float_tolerance(FloatTolerance::Epsilon(1e-9))
+}
+
+#[test]
+fn equal_values_pass() {
+    assert_same!(snapshot(1), snapshot(1));
+}
+
+#[test]
+fn ignored_paths_and_float_noise_pass() {
+    let mut expected = snapshot(2);
+    expected.score = 0.3;
+    assert_same!(snapshot(1), expected, &lenient());
+}
+
+#[test]
+fn unordered_lists_pass() {
+    let mut expected = snapshot(1);
+    expected.users.reverse();
+    expected.users[1].roles.reverse();
+    assert!(check_same(&snapshot(1), &expected, &DiffOptions::new()).is_err());
+    assert_same!(
+        snapshot(1),
+        expected,
+        &DiffOptions::new().unordered_lists(true)
+    );
+}
+
+#[test]
+fn mismatches_list_their_paths() {
+    let mut expected = snapshot(2);
+    expected.users[0].name = "ada lovelace".to_string();
+    expected.users[1].roles.push("ops".to_string());
+
+    let mismatch = check_same(&snapshot(1), &expected, &lenient()).unwrap_err();
+    let paths: Vec<_> = mismatch.paths().iter().map(ToString::to_string).collect();
+    assert_eq!(paths, ["users[0].name", "users[1].roles[0]"]);
+
+    let report = mismatch.to_string();
+    assert!(report.starts_with("2 differences\n"), "{report}");
+    assert!(report.contains("users[0].name"), "{report}");
+    assert!(report.contains("ada lovelace"), "{report}");
+}
+
+#[test]
+#[should_panic(expected = "assertion `left == right` failed: 1 difference")]
+fn mismatches_panic() {
+    let mut expected = snapshot(1);
+    expected.score = 0.3;
+    assert_same!(snapshot(1), expected);
+}
+
+#[test]
+fn incomparable_values_never_pass() {
+    let mismatch = check_same(&Handle(1), &Handle(1), &DiffOptions::new()).unwrap_err();
+    assert_eq!(mismatch.paths().len(), 1);
+    assert!(mismatch.paths()[0].is_root());
+}
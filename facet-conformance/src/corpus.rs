@@ -0,0 +1,586 @@
+//! The values every format is checked against
+//!
+//! Every case is a struct, since some formats (like urlencoded) only take structs at the root.
+
+use std::collections::HashMap;
+
+use facet::{Def, EnumDef, EnumRepr, Facet, Field, FieldFlags, Shape, Variant, VariantKind};
+
+use crate::{Format, Report, round_trip};
+
+/// Round-trips every case of the corpus through `format`
+pub fn run<F: Format>(format: &F) -> Report {
+    let mut report = Report {
+        format: format.name(),
+        outcomes: Vec::new(),
+    };
+
+    check(
+        &mut report,
+        format,
+        "unit",
+        &Units {
+            unit: (),
+            marker: Marker,
+        },
+    );
+    check(
+        &mut report,
+        format,
+        "bool",
+        &Bools {
+            yes: true,
+            no: false,
+        },
+    );
+    check(
+        &mut report,
+        format,
+        "unsigned integers",
+        &Unsigned {
+            byte: 200,
+            short: 60_000,
+            int: 4_000_000_000,
+            long: 1 << 60,
+            wide: 1 << 100,
+        },
+    );
+    check(
+        &mut report,
+        format,
+        "signed integers",
+        &Signed {
+            byte: -100,
+            short: -30_000,
+            int: -2_000_000_000,
+            long: -(1 << 60),
+            wide: -(1 << 100),
+        },
+    );
+    check(
+        &mut report,
+        format,
+        "integer extremes",
+        &IntegerExtremes {
+            u64_max: u64::MAX,
+            i64_min: i64::MIN,
+            u128_max: u128::MAX,
+            i128_min: i128::MIN,
+        },
+    );
+    check(
+        &mut report,
+        format,
+        "floats",
+        &Floats {
+            single: 1.5,
+            double: -0.1,
+        },
+    );
+    check(
+        &mut report,
+        format,
+        "float extremes",
+        &FloatExtremes {
+            negative_zero: -0.0,
+            tiny: f64::MIN_POSITIVE,
+            huge: f64::MAX,
+            whole: 3.0,
+        },
+    );
+    check(
+        &mut report,
+        format,
+        "non-finite floats",
+        &NonFinite {
+            infinity: f64::INFINITY,
+            negative_infinity: f64::NEG_INFINITY,
+        },
+    );
+    check(
+        &mut report,
+        format,
+        "strings",
+        &Strings {
+            empty: String::new(),
+            plain: "hello".to_string(),
+            escapes: "quote \" backslash \\ newline \n tab \t".to_string(),
+            control: "bell \u{7} nul \u{0}".to_string(),
+            reserved: "a=1&b[c]=2: #3, {4}".to_string(),
+        },
+    );
+    check(
+        &mut report,
+        format,
+        "unicode strings",
+        &Unicode {
+            accented: "crème brûlée".to_string(),
+            cjk: "日本語".to_string(),
+            emoji: "🦀 ❤\u{fe0f}".to_string(),
+            combining: "e\u{301}".to_string(),
+            right_to_left: "مرحبا".to_string(),
+        },
+    );
+    check(
+        &mut report,
+        format,
+        "nested structs",
+        &Outer {
+            name: "outer".to_string(),
+            inner: Inner {
+                id: 7,
+                position: Position { x: 1, y: -1 },
+            },
+        },
+    );
+    check(
+        &mut report,
+        format,
+        "flattened structs",
+        &Flattened {
+            name: "outer".to_string(),
+            position: Position { x: 2, y: -3 },
+        },
+    );
+    check(
+        &mut report,
+        format,
+        "tuple structs",
+        &TupleStructs {
+            newtype: Meters(2.5),
+            pair: Pair(-4, 9),
+        },
+    );
+    check(
+        &mut report,
+        format,
+        "tuples",
+        &Tuples {
+            pair: (1, "one".to_string()),
+            triple: (true, -2, 0.5),
+        },
+    );
+    check(
+        &mut report,
+        format,
+        "options",
+        &Options {
+            some: Some(3),
+            none: None,
+            some_struct: Some(Position { x: 0, y: 5 }),
+        },
+    );
+    check(
+        &mut report,
+        format,
+        "lists",
+        &Lists {
+            numbers: vec![1, 2, 3],
+            empty: vec![],
+            nested: vec![vec![1], vec![], vec![2, 3]],
+            structs: vec![Position { x: 1, y: 2 }, Position { x: 3, y: 4 }],
+        },
+    );
+    check(
+        &mut report,
+        format,
+        "maps",
+        &Maps {
+            scores: HashMap::from([("alice".to_string(), 3), ("bob".to_string(), 5)]),
+            empty: HashMap::new(),
+            nested: HashMap::from([("origin".to_string(), Position { x: 0, y: 0 })]),
+        },
+    );
+    for (name, choice) in [
+        ("unit variant", Choice::Unit),
+        ("newtype variant", Choice::Newtype("one".to_string())),
+        ("tuple variant", Choice::Tuple(2, -2)),
+        (
+            "struct variant",
+            Choice::Struct {
+                id: 3,
+                label: "three".to_string(),
+            },
+        ),
+    ] {
+        check(&mut report, format, name, &Chosen { choice });
+    }
+
+    report
+}
+
+/// Round-trips `value` through `format`, recording the outcome as case `name`
+fn check<F: Format, T: Facet + PartialEq + core::fmt::Debug>(
+    report: &mut Report,
+    format: &F,
+    name: &'static str,
+    value: &T,
+) {
+    report
+        .outcomes
+        .push((name, round_trip(format, name, value)));
+}
+
+/// Zero-sized values
+#[derive(Debug, PartialEq, Facet)]
+pub struct Units {
+    /// The unit type
+    pub unit: (),
+    /// A unit struct
+    pub marker: Marker,
+}
+
+/// A unit struct
+#[derive(Debug, PartialEq, Facet)]
+pub struct Marker;
+
+/// Booleans
+#[derive(Debug, PartialEq, Facet)]
+pub struct Bools {
+    /// `true`
+    pub yes: bool,
+    /// `false`
+    pub no: bool,
+}
+
+/// One of each unsigned integer type
+#[derive(Debug, PartialEq, Facet)]
+pub struct Unsigned {
+    /// A `u8`
+    pub byte: u8,
+    /// A `u16`
+    pub short: u16,
+    /// A `u32`
+    pub int: u32,
+    /// A `u64`
+    pub long: u64,
+    /// A `u128`
+    pub wide: u128,
+}
+
+/// One of each signed integer type
+#[derive(Debug, PartialEq, Facet)]
+pub struct Signed {
+    /// An `i8`
+    pub byte: i8,
+    /// An `i16`
+    pub short: i16,
+    /// An `i32`
+    pub int: i32,
+    /// An `i64`
+    pub long: i64,
+    /// An `i128`
+    pub wide: i128,
+}
+
+/// The largest and smallest 64 and 128-bit integers
+#[derive(Debug, PartialEq, Facet)]
+pub struct IntegerExtremes {
+    /// `u64::MAX`
+    pub u64_max: u64,
+    /// `i64::MIN`
+    pub i64_min: i64,
+    /// `u128::MAX`
+    pub u128_max: u128,
+    /// `i128::MIN`
+    pub i128_min: i128,
+}
+
+/// One of each float type
+#[derive(Debug, PartialEq, Facet)]
+pub struct Floats {
+    /// An `f32`
+    pub single: f32,
+    /// An `f64`
+    pub double: f64,
+}
+
+/// Floats that are easy to get wrong when printing or parsing
+#[derive(Debug, PartialEq, Facet)]
+pub struct FloatExtremes {
+    /// `-0.0`
+    pub negative_zero: f64,
+    /// The smallest positive normal `f64`
+    pub tiny: f64,
+    /// The largest `f64`
+    pub huge: f64,
+    /// A float with no fractional part
+    pub whole: f64,
+}
+
+/// Infinities, which not every format can represent
+#[derive(Debug, PartialEq, Facet)]
+pub struct NonFinite {
+    /// Positive infinity
+    pub infinity: f64,
+    /// Negative infinity
+    pub negative_infinity: f64,
+}
+
+/// Strings that need escaping, or careful quoting
+#[derive(Debug, PartialEq, Facet)]
+pub struct Strings {
+    /// The empty string
+    pub empty: String,
+    /// A string with nothing special in it
+    pub plain: String,
+    /// Quotes, backslashes and whitespace escapes
+    pub escapes: String,
+    /// Control characters
+    pub control: String,
+    /// Characters that mean something in some formats
+    pub reserved: String,
+}
+
+/// Strings outside of ASCII
+#[derive(Debug, PartialEq, Facet)]
+pub struct Unicode {
+    /// Latin letters with diacritics
+    pub accented: String,
+    /// CJK ideographs
+    pub cjk: String,
+    /// An emoji, and one with a variation selector
+    pub emoji: String,
+    /// A combining character
+    pub combining: String,
+    /// Right-to-left script
+    pub right_to_left: String,
+}
+
+/// A struct holding a struct holding a struct
+#[derive(Debug, PartialEq, Facet)]
+pub struct Outer {
+    /// A field before the nested struct
+    pub name: String,
+    /// The nested struct
+    pub inner: Inner,
+}
+
+/// The middle struct of [`Outer`]
+#[derive(Debug, PartialEq, Facet)]
+pub struct Inner {
+    /// A field before the nested struct
+    pub id: u64,
+    /// The innermost struct
+    pub position: Position,
+}
+
+/// A struct whose fields are written along with those of the struct holding it
+#[derive(Debug, PartialEq, Facet)]
+pub struct Flattened {
+    /// A field before the flattened struct
+    pub name: String,
+    /// The flattened struct
+    #[facet(flatten)]
+    pub position: Position,
+}
+
+/// A small struct, used inside other cases
+#[derive(Debug, PartialEq, Facet)]
+pub struct Position {
+    /// Horizontal position
+    pub x: i32,
+    /// Vertical position
+    pub y: i32,
+}
+
+/// Tuple structs
+#[derive(Debug, PartialEq, Facet)]
+pub struct TupleStructs {
+    /// A tuple struct with a single field
+    pub newtype: Meters,
+    /// A tuple struct with two fields
+    pub pair: Pair,
+}
+
+/// A newtype around a float
+#[derive(Debug, PartialEq, Facet)]
+pub struct Meters(pub f64);
+
+/// A tuple struct with two fields
+#[derive(Debug, PartialEq, Facet)]
+pub struct Pair(pub i32, pub i32);
+
+/// Tuples
+#[derive(Debug, PartialEq, Facet)]
+pub struct Tuples {
+    /// A pair
+    pub pair: (u32, String),
+    /// A triple of different types
+    pub triple: (bool, i64, f64),
+}
+
+/// Present and absent options
+#[derive(Debug, PartialEq, Facet)]
+pub struct Options {
+    /// `Some` scalar
+    pub some: Option<u32>,
+    /// `None`
+    pub none: Option<u32>,
+    /// `Some` struct
+    pub some_struct: Option<Position>,
+}
+
+/// Lists
+#[derive(Debug, PartialEq, Facet)]
+pub struct Lists {
+    /// A list of numbers
+    pub numbers: Vec<u64>,
+    /// An empty list
+    pub empty: Vec<String>,
+    /// A list of lists
+    pub nested: Vec<Vec<u8>>,
+    /// A list of structs
+    pub structs: Vec<Position>,
+}
+
+/// Maps with string keys
+#[derive(Debug, PartialEq, Facet)]
+pub struct Maps {
+    /// A map of numbers
+    pub scores: HashMap<String, u64>,
+    /// An empty map
+    pub empty: HashMap<String, String>,
+    /// A map of structs
+    pub nested: HashMap<String, Position>,
+}
+
+/// Holds a [`Choice`]
+#[derive(Debug, PartialEq, Facet)]
+pub struct Chosen {
+    /// The enum
+    pub choice: Choice,
+}
+
+/// An enum with a variant of each kind
+///
+/// The derive macro doesn't handle enums with fields yet, so the shape is written by hand.
+#[derive(Debug, PartialEq)]
+#[repr(u8)]
+pub enum Choice {
+    /// A unit variant
+    Unit,
+    /// A variant with a single unnamed field
+    Newtype(String),
+    /// A variant with several unnamed fields
+    Tuple(u32, i64),
+    /// A variant with named fields
+    Struct {
+        /// A number
+        id: u64,
+        /// A string
+        label: String,
+    },
+}
+
+// A `#[repr(u8)]` enum is laid out as a union of `#[repr(C)]` structs, one per variant,
+// each starting with the tag.
+#[repr(C)]
+struct NewtypeLayout {
+    _tag: u8,
+    value: String,
+}
+
+#[repr(C)]
+struct TupleLayout {
+    _tag: u8,
+    first: u32,
+    second: i64,
+}
+
+#[repr(C)]
+struct StructLayout {
+    _tag: u8,
+    id: u64,
+    label: String,
+}
+
+unsafe impl Facet for Choice {
+    const ARCHETYPE: Self = Choice::Unit;
+    const SHAPE: &'static Shape = &const {
+        const fn field(name: &'static str, shape: &'static Shape, offset: usize) -> Field {
+            Field::builder()
+                .name(name)
+                .shape(shape)
+                .offset(offset)
+                .flags(FieldFlags::EMPTY)
+                .build()
+        }
+
+        Shape::builder()
+            .layout(core::alloc::Layout::new::<Self>())
+            .vtable(facet::value_vtable!(Choice, |f, _opts| {
+                core::fmt::Write::write_str(f, "Choice")
+            }))
+            .def(Def::Enum(
+                EnumDef::builder()
+                    .repr(EnumRepr::U8)
+                    .variants(
+                        &const {
+                            [
+                                Variant::builder()
+                                    .name("Unit")
+                                    .discriminant(Some(0))
+                                    .kind(VariantKind::Unit)
+                                    .build(),
+                                Variant::builder()
+                                    .name("Newtype")
+                                    .discriminant(Some(1))
+                                    .kind(VariantKind::Tuple {
+                                        fields: &const {
+                                            [field(
+                                                "0",
+                                                String::SHAPE,
+                                                core::mem::offset_of!(NewtypeLayout, value),
+                                            )]
+                                        },
+                                    })
+                                    .build(),
+                                Variant::builder()
+                                    .name("Tuple")
+                                    .discriminant(Some(2))
+                                    .kind(VariantKind::Tuple {
+                                        fields: &const {
+                                            [
+                                                field(
+                                                    "0",
+                                                    u32::SHAPE,
+                                                    core::mem::offset_of!(TupleLayout, first),
+                                                ),
+                                                field(
+                                                    "1",
+                                                    i64::SHAPE,
+                                                    core::mem::offset_of!(TupleLayout, second),
+                                                ),
+                                            ]
+                                        },
+                                    })
+                                    .build(),
+                                Variant::builder()
+                                    .name("Struct")
+                                    .discriminant(Some(3))
+                                    .kind(VariantKind::Struct {
+                                        fields: &const {
+                                            [
+                                                field(
+                                                    "id",
+                                                    u64::SHAPE,
+                                                    core::mem::offset_of!(StructLayout, id),
+                                                ),
+                                                field(
+                                                    "label",
+                                                    String::SHAPE,
+                                                    core::mem::offset_of!(StructLayout, label),
+                                                ),
+                                            ]
+                                        },
+                                    })
+                                    .build(),
+                            ]
+                        },
+                    )
+                    .build(),
+            ))
+            .build()
+    };
+}
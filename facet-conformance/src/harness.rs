@@ -0,0 +1,211 @@
+use core::fmt;
+use std::panic::{AssertUnwindSafe, catch_unwind};
+
+use facet::Facet;
+
+/// A format under test
+pub trait Format {
+    /// The name of the format, as shown in the matrix
+    fn name(&self) -> &'static str;
+
+    /// Writes a value, or returns `None` if the format has no writer
+    fn serialize<T: Facet>(&self, value: &T) -> Option<Result<Vec<u8>, String>>;
+
+    /// Reads back a value written by [`Format::serialize`], or given by [`Format::input`]
+    fn deserialize<T: Facet>(&self, input: &[u8]) -> Result<T, String>;
+
+    /// Returns a hand-written encoding of the case named `case`, which is read instead of
+    /// the output of [`Format::serialize`] when the format has no writer
+    fn input(&self, case: &str) -> Option<Vec<u8>> {
+        let _ = case;
+        None
+    }
+}
+
+/// What happened to a value sent through a format
+#[derive(Debug, Clone, PartialEq)]
+pub enum Outcome {
+    /// The value came back unchanged
+    Pass,
+    /// The format has no writer, nor a hand-written input for the case, so nothing was tried
+    NoWriter,
+    /// The value couldn't be written
+    SerializeError(String),
+    /// The output couldn't be read back
+    DeserializeError {
+        /// What the format wrote
+        output: Vec<u8>,
+        /// The error reading it back
+        error: String,
+    },
+    /// The output was read back as a different value
+    Mismatch {
+        /// What the format wrote
+        output: Vec<u8>,
+        /// The value read back, debug-formatted
+        got: String,
+    },
+    /// The format panicked, either writing or reading
+    Panicked(String),
+}
+
+impl Outcome {
+    /// Returns the short label used in the matrix
+    pub fn label(&self) -> &'static str {
+        match self {
+            Outcome::Pass => "pass",
+            Outcome::NoWriter => "no writer",
+            Outcome::SerializeError(_) => "write error",
+            Outcome::DeserializeError { .. } => "read error",
+            Outcome::Mismatch { .. } => "mismatch",
+            Outcome::Panicked(_) => "panic",
+        }
+    }
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let output = |output: &[u8]| match core::str::from_utf8(output) {
+            Ok(s) => format!("{s:?}"),
+            Err(_) => format!("{output:02x?}"),
+        };
+        match self {
+            Outcome::Pass | Outcome::NoWriter => write!(f, "{}", self.label()),
+            Outcome::SerializeError(error) => write!(f, "write error: {error}"),
+            Outcome::DeserializeError { output: o, error } => {
+                write!(f, "read error: {error} (reading {})", output(o))
+            }
+            Outcome::Mismatch { output: o, got } => {
+                write!(f, "mismatch: got {got} (reading {})", output(o))
+            }
+            Outcome::Panicked(message) => write!(f, "panic: {message}"),
+        }
+    }
+}
+
+/// Sends `value` through `format` and back
+///
+/// If `format` has no writer, its hand-written input for `case` is read instead, and
+/// should give back `value`.
+pub fn round_trip<F: Format, T: Facet + PartialEq + fmt::Debug>(
+    format: &F,
+    case: &str,
+    value: &T,
+) -> Outcome {
+    let output = match catch(|| format.serialize(value)) {
+        Ok(None) => match format.input(case) {
+            Some(input) => input,
+            None => return Outcome::NoWriter,
+        },
+        Ok(Some(Ok(output))) => output,
+        Ok(Some(Err(error))) => return Outcome::SerializeError(error),
+        Err(message) => return Outcome::Panicked(format!("writing: {message}")),
+    };
+    match catch(|| format.deserialize::<T>(&output)) {
+        Ok(Ok(back)) if back == *value => Outcome::Pass,
+        Ok(Ok(back)) => Outcome::Mismatch {
+            output,
+            got: format!("{back:?}"),
+        },
+        Ok(Err(error)) => Outcome::DeserializeError { output, error },
+        Err(message) => Outcome::Panicked(format!("reading: {message}")),
+    }
+}
+
+/// Runs `f`, turning a panic into its message
+fn catch<R>(f: impl FnOnce() -> R) -> Result<R, String> {
+    catch_unwind(AssertUnwindSafe(f)).map_err(|payload| {
+        if let Some(s) = payload.downcast_ref::<&str>() {
+            s.to_string()
+        } else if let Some(s) = payload.downcast_ref::<String>() {
+            s.clone()
+        } else {
+            "(non-string panic payload)".to_string()
+        }
+    })
+}
+
+/// The outcome of every case of the corpus, for one format
+#[derive(Debug, Clone)]
+pub struct Report {
+    /// The name of the format
+    pub format: &'static str,
+    /// Each case's name and outcome, in corpus order
+    pub outcomes: Vec<(&'static str, Outcome)>,
+}
+
+impl Report {
+    /// Returns the outcome of the case named `case`
+    pub fn outcome(&self, case: &str) -> Option<&Outcome> {
+        self.outcomes
+            .iter()
+            .find(|(name, _)| *name == case)
+            .map(|(_, outcome)| outcome)
+    }
+
+    /// Returns the cases that didn't pass
+    pub fn failures(&self) -> impl Iterator<Item = &(&'static str, Outcome)> {
+        self.outcomes
+            .iter()
+            .filter(|(_, outcome)| *outcome != Outcome::Pass)
+    }
+
+    /// Panics, listing every failure, unless all cases passed
+    pub fn assert_all_pass(&self) {
+        let failures: Vec<_> = self
+            .failures()
+            .map(|(case, outcome)| format!("  {case}: {outcome}"))
+            .collect();
+        assert!(
+            failures.is_empty(),
+            "{} failed {} case(s):\n{}",
+            self.format,
+            failures.len(),
+            failures.join("\n")
+        );
+    }
+}
+
+/// The reports of several formats, displayed as a markdown table with one row per case
+/// and one column per format
+#[derive(Debug, Clone, Default)]
+pub struct Matrix {
+    /// One report per format
+    pub reports: Vec<Report>,
+}
+
+impl Matrix {
+    /// Adds a format's report as a new column
+    pub fn push(&mut self, report: Report) {
+        self.reports.push(report);
+    }
+}
+
+impl fmt::Display for Matrix {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Some(first) = self.reports.first() else {
+            return Ok(());
+        };
+
+        write!(f, "| case |")?;
+        for report in &self.reports {
+            write!(f, " {} |", report.format)?;
+        }
+        writeln!(f)?;
+        write!(f, "|------|")?;
+        for _ in &self.reports {
+            write!(f, "------|")?;
+        }
+        writeln!(f)?;
+
+        for (case, _) in &first.outcomes {
+            write!(f, "| {case} |")?;
+            for report in &self.reports {
+                let label = report.outcome(case).map_or("", Outcome::label);
+                write!(f, " {label} |")?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,14 @@
+#![warn(missing_docs)]
+#![doc = include_str!("../README.md")]
+
+//! Round-trip conformance suite for format crates
+//!
+//! A format crate implements [`Format`] in its tests (or here, in `tests/formats.rs`), then
+//! [`run`] round-trips every value of the corpus through it. The resulting [`Report`]s are
+//! gathered in a [`Matrix`], which renders as a markdown table of what each format supports.
+
+mod corpus;
+pub use corpus::*;
+
+mod harness;
+pub use harness::*;
@@ -0,0 +1,193 @@
+use std::path::Path;
+
+use facet::Facet;
+use facet_conformance::{Format, Matrix, Outcome, run};
+use facet_poke::Peek;
+
+struct Json;
+
+impl Format for Json {
+    fn name(&self) -> &'static str {
+        "json"
+    }
+
+    fn serialize<T: Facet>(&self, value: &T) -> Option<Result<Vec<u8>, String>> {
+        Some(Ok(
+            facet_json::to_json_string(Peek::new(value), false).into_bytes()
+        ))
+    }
+
+    fn deserialize<T: Facet>(&self, input: &[u8]) -> Result<T, String> {
+        let input = core::str::from_utf8(input).map_err(|e| e.to_string())?;
+        facet_json::from_str(input).map_err(|e| e.to_string())
+    }
+}
+
+struct Msgpack;
+
+impl Format for Msgpack {
+    fn name(&self) -> &'static str {
+        "msgpack"
+    }
+
+    fn serialize<T: Facet>(&self, value: &T) -> Option<Result<Vec<u8>, String>> {
+        Some(Ok(facet_msgpack::to_vec(value)))
+    }
+
+    fn deserialize<T: Facet>(&self, input: &[u8]) -> Result<T, String> {
+        facet_msgpack::from_str(input).map_err(|e| e.to_string())
+    }
+}
+
+struct Yaml;
+
+impl Format for Yaml {
+    fn name(&self) -> &'static str {
+        "yaml"
+    }
+
+    fn serialize<T: Facet>(&self, value: &T) -> Option<Result<Vec<u8>, String>> {
+        Some(
+            facet_yaml::to_string(value)
+                .map(String::into_bytes)
+                .map_err(|e| e.to_string()),
+        )
+    }
+
+    fn deserialize<T: Facet>(&self, input: &[u8]) -> Result<T, String> {
+        let input = core::str::from_utf8(input).map_err(|e| e.to_string())?;
+        facet_yaml::from_str(input).map_err(|e| e.to_string())
+    }
+}
+
+struct Toml;
+
+impl Format for Toml {
+    fn name(&self) -> &'static str {
+        "toml"
+    }
+
+    fn serialize<T: Facet>(&self, value: &T) -> Option<Result<Vec<u8>, String>> {
+        Some(
+            facet_toml::to_string(value)
+                .map(String::into_bytes)
+                .map_err(|e| e.to_string()),
+        )
+    }
+
+    fn deserialize<T: Facet>(&self, input: &[u8]) -> Result<T, String> {
+        let input = core::str::from_utf8(input).map_err(|e| e.to_string())?;
+        facet_toml::from_str(input).map_err(|e| e.to_string())
+    }
+}
+
+struct UrlEncoded;
+
+impl Format for UrlEncoded {
+    fn name(&self) -> &'static str {
+        "urlencoded"
+    }
+
+    fn serialize<T: Facet>(&self, _value: &T) -> Option<Result<Vec<u8>, String>> {
+        None
+    }
+
+    fn deserialize<T: Facet>(&self, input: &[u8]) -> Result<T, String> {
+        let input = core::str::from_utf8(input).map_err(|e| e.to_string())?;
+        facet_urlencoded::from_str(input).map_err(|e| e.to_string())
+    }
+
+    /// Nested values use the bracket notation: `outer[inner]=value` for struct fields and
+    /// map entries, `list[0]=value` for list items, and `enum[Variant]...` for variants
+    /// with fields
+    fn input(&self, case: &str) -> Option<Vec<u8>> {
+        let input = match case {
+            "unit" => "unit=&marker=",
+            "bool" => "yes=true&no=false",
+            "unsigned integers" => {
+                "byte=200&short=60000&int=4000000000&long=1152921504606846976\
+                 &wide=1267650600228229401496703205376"
+            }
+            "signed integers" => {
+                "byte=-100&short=-30000&int=-2000000000&long=-1152921504606846976\
+                 &wide=-1267650600228229401496703205376"
+            }
+            "integer extremes" => {
+                "u64_max=18446744073709551615&i64_min=-9223372036854775808\
+                 &u128_max=340282366920938463463374607431768211455\
+                 &i128_min=-170141183460469231731687303715884105728"
+            }
+            "floats" => "single=1.5&double=-0.1",
+            "float extremes" => {
+                "negative_zero=-0&tiny=2.2250738585072014e-308\
+                 &huge=1.7976931348623157e308&whole=3"
+            }
+            "non-finite floats" => "infinity=inf&negative_infinity=-inf",
+            "strings" => {
+                "empty=&plain=hello&escapes=quote+%22+backslash+%5C+newline+%0A+tab+%09\
+                 &control=bell+%07+nul+%00&reserved=a%3D1%26b%5Bc%5D%3D2%3A+%233%2C+%7B4%7D"
+            }
+            "unicode strings" => {
+                "accented=cr%C3%A8me+br%C3%BBl%C3%A9e&cjk=%E6%97%A5%E6%9C%AC%E8%AA%9E\
+                 &emoji=%F0%9F%A6%80+%E2%9D%A4%EF%B8%8F&combining=e%CC%81\
+                 &right_to_left=%D9%85%D8%B1%D8%AD%D8%A8%D8%A7"
+            }
+            "nested structs" => "name=outer&inner[id]=7&inner[position][x]=1&inner[position][y]=-1",
+            "flattened structs" => "name=outer&x=2&y=-3",
+            "tuple structs" => "newtype[0]=2.5&pair[0]=-4&pair[1]=9",
+            "tuples" => "pair[0]=1&pair[1]=one&triple[0]=true&triple[1]=-2&triple[2]=0.5",
+            "options" => "some=3&some_struct[x]=0&some_struct[y]=5",
+            "lists" => {
+                "numbers[0]=1&numbers[1]=2&numbers[2]=3&nested[0][0]=1&nested[2][0]=2\
+                 &nested[2][1]=3&structs[0][x]=1&structs[0][y]=2&structs[1][x]=3&structs[1][y]=4"
+            }
+            "maps" => "scores[alice]=3&scores[bob]=5&nested[origin][x]=0&nested[origin][y]=0",
+            "unit variant" => "choice=Unit",
+            "newtype variant" => "choice[Newtype]=one",
+            "tuple variant" => "choice[Tuple][0]=2&choice[Tuple][1]=-2",
+            "struct variant" => "choice[Struct][id]=3&choice[Struct][label]=three",
+            _ => return None,
+        };
+        Some(input.as_bytes().to_vec())
+    }
+}
+
+/// Checks the capability matrix against `CAPABILITIES.md`, or rewrites it if
+/// `FACET_CONFORMANCE_BLESS` is set
+#[test]
+fn capabilities() {
+    let mut matrix = Matrix::default();
+    matrix.push(run(&Json));
+    matrix.push(run(&Msgpack));
+    matrix.push(run(&Yaml));
+    let urlencoded = run(&UrlEncoded);
+    assert!(
+        !urlencoded
+            .outcomes
+            .iter()
+            .any(|(_, outcome)| *outcome == Outcome::NoWriter),
+        "urlencoded has no writer, so every case needs a hand-written input"
+    );
+    matrix.push(urlencoded);
+    matrix.push(run(&Toml));
+
+    let rendered = format!(
+        "# Format capabilities\n\n\
+         Whether each case of the corpus survives a round trip through each format. Formats \
+         without a writer read a hand-written encoding of each case instead.\n\
+         Generated by `tests/formats.rs`: run `FACET_CONFORMANCE_BLESS=1 cargo test -p \
+         facet-conformance` to update it.\n\n\
+         {matrix}"
+    );
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("CAPABILITIES.md");
+    if std::env::var_os("FACET_CONFORMANCE_BLESS").is_some() {
+        std::fs::write(&path, rendered).unwrap();
+        return;
+    }
+    let expected = std::fs::read_to_string(&path).unwrap_or_default();
+    assert_eq!(
+        expected, rendered,
+        "the capability matrix changed: if that's expected, run the tests with \
+         FACET_CONFORMANCE_BLESS=1 to update CAPABILITIES.md"
+    );
+}
@@ -0,0 +1,180 @@
+//! The corpus round-trips through facet-serialize's data model, with a trivial encoding of
+//! its events: a format failing a case is the format's fault, not the corpus'.
+
+use std::borrow::Cow;
+
+use facet::Facet;
+use facet_conformance::{Format, run};
+use facet_poke::Peek;
+use facet_serialize::{Deserializer, Event, Serializer, deserialize, serialize};
+
+/// Encodes every event as a tag byte, followed by its payload if it has one
+struct Events;
+
+impl Format for Events {
+    fn name(&self) -> &'static str {
+        "events"
+    }
+
+    fn serialize<T: Facet>(&self, value: &T) -> Option<Result<Vec<u8>, String>> {
+        let mut writer = Writer(Vec::new());
+        Some(
+            serialize(Peek::new(value), &mut writer)
+                .map(|()| writer.0)
+                .map_err(|e| e.to_string()),
+        )
+    }
+
+    fn deserialize<T: Facet>(&self, input: &[u8]) -> Result<T, String> {
+        let mut reader = Reader(input);
+        let value = deserialize(&mut reader).map_err(|e| e.to_string())?;
+        if !reader.0.is_empty() {
+            return Err(format!("{} trailing bytes", reader.0.len()));
+        }
+        Ok(value)
+    }
+}
+
+const UNIT: u8 = 0;
+const BOOL: u8 = 1;
+const U64: u8 = 2;
+const I64: u8 = 3;
+const F64: u8 = 4;
+const STR: u8 = 5;
+const START_SEQ: u8 = 6;
+const END_SEQ: u8 = 7;
+const START_MAP: u8 = 8;
+const KEY: u8 = 9;
+const END_MAP: u8 = 10;
+
+struct Writer(Vec<u8>);
+
+impl Writer {
+    fn tag(&mut self, tag: u8) -> Result<(), core::convert::Infallible> {
+        self.0.push(tag);
+        Ok(())
+    }
+
+    fn payload(&mut self, tag: u8, bytes: &[u8]) -> Result<(), core::convert::Infallible> {
+        self.0.push(tag);
+        self.0.extend_from_slice(bytes);
+        Ok(())
+    }
+
+    fn string(&mut self, tag: u8, s: &str) -> Result<(), core::convert::Infallible> {
+        self.payload(tag, &(s.len() as u64).to_le_bytes())?;
+        self.0.extend_from_slice(s.as_bytes());
+        Ok(())
+    }
+}
+
+impl Serializer for Writer {
+    type Error = core::convert::Infallible;
+
+    fn serialize_bool(&mut self, value: bool) -> Result<(), Self::Error> {
+        self.payload(BOOL, &[value as u8])
+    }
+
+    fn serialize_u64(&mut self, value: u64) -> Result<(), Self::Error> {
+        self.payload(U64, &value.to_le_bytes())
+    }
+
+    fn serialize_i64(&mut self, value: i64) -> Result<(), Self::Error> {
+        self.payload(I64, &value.to_le_bytes())
+    }
+
+    fn serialize_f64(&mut self, value: f64) -> Result<(), Self::Error> {
+        self.payload(F64, &value.to_le_bytes())
+    }
+
+    fn serialize_str(&mut self, value: &str) -> Result<(), Self::Error> {
+        self.string(STR, value)
+    }
+
+    fn serialize_unit(&mut self) -> Result<(), Self::Error> {
+        self.tag(UNIT)
+    }
+
+    fn start_struct(&mut self, _len: usize) -> Result<(), Self::Error> {
+        self.tag(START_MAP)
+    }
+
+    fn field(&mut self, name: &'static str) -> Result<(), Self::Error> {
+        self.string(KEY, name)
+    }
+
+    fn end_struct(&mut self) -> Result<(), Self::Error> {
+        self.tag(END_MAP)
+    }
+
+    fn start_seq(&mut self, _len: usize) -> Result<(), Self::Error> {
+        self.tag(START_SEQ)
+    }
+
+    fn end_seq(&mut self) -> Result<(), Self::Error> {
+        self.tag(END_SEQ)
+    }
+
+    fn start_map(&mut self, _len: usize) -> Result<(), Self::Error> {
+        self.tag(START_MAP)
+    }
+
+    fn map_key(&mut self, key: &str) -> Result<(), Self::Error> {
+        self.string(KEY, key)
+    }
+
+    fn end_map(&mut self) -> Result<(), Self::Error> {
+        self.tag(END_MAP)
+    }
+}
+
+struct Reader<'de>(&'de [u8]);
+
+impl<'de> Reader<'de> {
+    fn take(&mut self, len: usize) -> Result<&'de [u8], &'static str> {
+        if self.0.len() < len {
+            return Err("unexpected end of input");
+        }
+        let (taken, rest) = self.0.split_at(len);
+        self.0 = rest;
+        Ok(taken)
+    }
+
+    fn word(&mut self) -> Result<[u8; 8], &'static str> {
+        Ok(self.take(8)?.try_into().unwrap())
+    }
+
+    fn string(&mut self) -> Result<Cow<'de, str>, &'static str> {
+        let len = u64::from_le_bytes(self.word()?) as usize;
+        let bytes = self.take(len)?;
+        core::str::from_utf8(bytes)
+            .map(Cow::Borrowed)
+            .map_err(|_| "invalid UTF-8")
+    }
+}
+
+impl<'de> Deserializer<'de> for Reader<'de> {
+    type Error = &'static str;
+
+    fn next(&mut self) -> Result<Event<'de>, Self::Error> {
+        Ok(match self.take(1)?[0] {
+            UNIT => Event::Unit,
+            BOOL => Event::Bool(self.take(1)?[0] != 0),
+            U64 => Event::U64(u64::from_le_bytes(self.word()?)),
+            I64 => Event::I64(i64::from_le_bytes(self.word()?)),
+            F64 => Event::F64(f64::from_le_bytes(self.word()?)),
+            STR => Event::Str(self.string()?),
+            START_SEQ => Event::StartSeq,
+            END_SEQ => Event::EndSeq,
+            START_MAP => Event::StartMap,
+            KEY => Event::Key(self.string()?),
+            END_MAP => Event::EndMap,
+            _ => return Err("unknown tag"),
+        })
+    }
+}
+
+#[test]
+fn corpus_round_trips_through_the_data_model() {
+    run(&Events).assert_all_pass();
+}
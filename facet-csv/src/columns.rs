@@ -0,0 +1,77 @@
+use facet_trait::{Def, FieldFlags, Shape, StructDef, StructKind, VariantKind};
+
+/// Returns the columns of the rows of `shape`, which must be a struct: the names of its
+/// fields, with the fields of nested structs joined with dots (`address.city`), except
+/// for `#[facet(flatten)]` ones
+///
+/// Returns the shape that doesn't fit in columns if there's one.
+pub(crate) fn headers(shape: &'static Shape) -> Result<Vec<String>, &'static Shape> {
+    let Def::Struct(def) = shape.def else {
+        return Err(shape);
+    };
+    if def.kind != StructKind::Struct {
+        return Err(shape);
+    }
+    let mut headers = Vec::new();
+    fields(def, "", &mut headers)?;
+    Ok(headers)
+}
+
+/// Returns the number of columns a value of `shape` takes
+pub(crate) fn count(shape: &'static Shape) -> usize {
+    let mut headers = Vec::new();
+    let _ = columns(shape, String::new(), &mut headers);
+    headers.len()
+}
+
+/// Returns true if values of `shape` fit in a single cell
+pub(crate) fn is_cell(shape: &'static Shape) -> bool {
+    match shape.def {
+        Def::Scalar { .. } => true,
+        Def::Enum(def) => def
+            .variants
+            .iter()
+            .all(|variant| matches!(variant.kind, VariantKind::Unit)),
+        Def::Struct(_) => shape.vtable.parse.is_some(),
+        _ => false,
+    }
+}
+
+fn fields(def: StructDef, prefix: &str, out: &mut Vec<String>) -> Result<(), &'static Shape> {
+    for field in def.fields {
+        match field.shape.def {
+            Def::Struct(
+                def @ StructDef {
+                    kind: StructKind::Struct,
+                    ..
+                },
+            ) if field.flags.contains(FieldFlags::FLATTEN) => fields(def, prefix, out)?,
+            _ => columns(field.shape, format!("{prefix}{}", field.name), out)?,
+        }
+    }
+    Ok(())
+}
+
+/// Adds the columns of a value named `name`
+fn columns(
+    shape: &'static Shape,
+    name: String,
+    out: &mut Vec<String>,
+) -> Result<(), &'static Shape> {
+    if is_cell(shape) {
+        out.push(name);
+        return Ok(());
+    }
+    match shape.def {
+        Def::Option(def) => columns(def.t, name, out),
+        Def::Struct(def) => match def.kind {
+            StructKind::Unit => Ok(()),
+            StructKind::TupleStruct if def.fields.len() == 1 => {
+                columns(def.fields[0].shape, name, out)
+            }
+            StructKind::Struct => fields(def, &format!("{name}."), out),
+            _ => Err(shape),
+        },
+        _ => Err(shape),
+    }
+}
@@ -0,0 +1,85 @@
+use std::fmt;
+
+use facet_trait::{ParseError, Shape};
+
+/// Returned when reading or writing CSV
+#[derive(Debug)]
+pub struct CsvError {
+    /// The line of the record, counting from 1 (the header is line 1)
+    pub line: usize,
+    /// The column of the problem, if it's about a single cell
+    pub column: Option<String>,
+    /// What went wrong
+    pub kind: CsvErrorKind,
+}
+
+/// What went wrong while reading or writing CSV
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum CsvErrorKind {
+    /// Reading or writing failed
+    Io(std::io::Error),
+    /// The input isn't valid CSV
+    Syntax(&'static str),
+    /// The record doesn't have as many cells as the header
+    CellCount {
+        /// The number of columns of the header
+        expected: usize,
+        /// The number of cells of the record
+        got: usize,
+    },
+    /// The header has no column for a field that's neither optional nor defaulted
+    MissingColumn,
+    /// The cell doesn't parse
+    Invalid {
+        /// The cell, unless the field is sensitive
+        value: Option<String>,
+        /// Why it doesn't parse
+        error: ParseError,
+    },
+    /// Values of this shape don't fit in columns: rows are structs, and their fields are
+    /// scalars, unit enums, options, or structs made of those
+    Unsupported(&'static Shape),
+}
+
+impl CsvError {
+    pub(crate) fn new(line: usize, column: Option<&str>, kind: CsvErrorKind) -> Self {
+        Self {
+            line,
+            column: column.map(str::to_string),
+            kind,
+        }
+    }
+}
+
+impl fmt::Display for CsvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}", self.line)?;
+        if let Some(column) = &self.column {
+            write!(f, ", column `{column}`")?;
+        }
+        match &self.kind {
+            CsvErrorKind::Io(err) => write!(f, ": {err}"),
+            CsvErrorKind::Syntax(message) => write!(f, ": {message}"),
+            CsvErrorKind::CellCount { expected, got } => {
+                write!(f, ": expected {expected} cells, got {got}")
+            }
+            CsvErrorKind::MissingColumn => write!(f, ": missing"),
+            CsvErrorKind::Invalid {
+                value: Some(value),
+                error,
+            } => write!(f, ": invalid value {value:?}: {error}"),
+            CsvErrorKind::Invalid { value: None, error } => write!(f, ": invalid value: {error}"),
+            CsvErrorKind::Unsupported(shape) => write!(f, ": can't fit a {shape} in columns"),
+        }
+    }
+}
+
+impl std::error::Error for CsvError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match &self.kind {
+            CsvErrorKind::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
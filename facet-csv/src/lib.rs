@@ -0,0 +1,218 @@
+#![warn(missing_docs)]
+#![doc = include_str!("../README.md")]
+
+use std::collections::HashMap;
+use std::io::{BufReader, Read, Write};
+use std::marker::PhantomData;
+
+use facet_poke::{Peek, Poke};
+use facet_trait::Facet;
+
+mod columns;
+
+mod error;
+pub use error::*;
+
+mod read;
+use read::{Records, Row};
+
+mod write;
+
+/// Reads the rows of a CSV file as `T`s, one at a time
+///
+/// The first record is the header. Each column is read into the field of `T` named like
+/// it (after `#[facet(rename = "...")]`), and the fields of nested structs are read from
+/// dotted columns: `address.city` for the `city` field of the `address` field, unless
+/// `address` is `#[facet(flatten)]`. Columns that aren't the name of a field are ignored.
+///
+///   * cells are parsed with their field's `parse` vtable entry (typically its `FromStr`
+///     impl), and unit variants of enums are read by name
+///   * an `Option` field is `None` when its cell is empty, or when all the cells of its
+///     columns are, for a struct
+///   * a `#[facet(default)]` field takes its default value when its column is missing or
+///     its cell is empty
+///
+/// ```rust
+/// use facet_derive::Facet;
+/// use facet_trait::{self as facet, Facet};
+///
+/// #[derive(Facet)]
+/// struct Sale {
+///     #[facet(rename = "Item")]
+///     item: String,
+///     quantity: u32,
+///     discount: Option<f64>,
+/// }
+///
+/// let csv = "Item,quantity,discount\nwidget,3,0.1\ngadget,1,\n";
+/// let sales = facet_csv::from_reader::<Sale, _>(csv.as_bytes())
+///     .unwrap()
+///     .collect::<Result<Vec<_>, _>>()
+///     .unwrap();
+/// assert_eq!(sales[0].item, "widget");
+/// assert_eq!(sales[1].discount, None);
+/// ```
+///
+/// # Errors
+///
+/// Returns an error if the header can't be read, or if `T` isn't a struct that fits in
+/// columns. Each row is an error if it can't be read into a `T`: a syntax error or a
+/// failed read ends the rows, other errors only skip their row.
+pub fn from_reader<T: Facet, R: Read>(reader: R) -> Result<Rows<T, R>, CsvError> {
+    columns::headers(T::SHAPE)
+        .map_err(|shape| CsvError::new(1, None, CsvErrorKind::Unsupported(shape)))?;
+    let mut records = Records::new(BufReader::new(reader));
+    let mut columns = HashMap::new();
+    let mut width = 0;
+    if let Some((_, header)) = records.next_record()? {
+        width = header.len();
+        for (index, name) in header.into_iter().enumerate() {
+            // spreadsheets like to start their exports with a byte order mark
+            let name = match index {
+                0 => name.trim_start_matches('\u{feff}').to_string(),
+                _ => name,
+            };
+            columns.entry(name).or_insert(index);
+        }
+    }
+    Ok(Rows {
+        records,
+        columns,
+        width,
+        done: false,
+        _t: PhantomData,
+    })
+}
+
+/// Reads all the rows of a CSV string as `T`s
+///
+/// # Errors
+///
+/// Returns the first error, see [`from_reader`].
+pub fn from_str<T: Facet>(csv: &str) -> Result<Vec<T>, CsvError> {
+    from_reader(csv.as_bytes())?.collect()
+}
+
+/// The rows of a CSV file, returned by [`from_reader`]
+pub struct Rows<T, R> {
+    records: Records<BufReader<R>>,
+    /// The index of each column, by name
+    columns: HashMap<String, usize>,
+    /// The number of cells of each record
+    width: usize,
+    done: bool,
+    _t: PhantomData<fn() -> T>,
+}
+
+impl<T: Facet, R: Read> Iterator for Rows<T, R> {
+    type Item = Result<T, CsvError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let (line, cells) = match self.records.next_record() {
+            Ok(Some(record)) => record,
+            Ok(None) => {
+                self.done = true;
+                return None;
+            }
+            Err(err) => {
+                self.done = true;
+                return Some(Err(err));
+            }
+        };
+        if cells.len() != self.width {
+            let kind = CsvErrorKind::CellCount {
+                expected: self.width,
+                got: cells.len(),
+            };
+            return Some(Err(CsvError::new(line, None, kind)));
+        }
+        let row = Row {
+            columns: &self.columns,
+            cells: &cells,
+            line,
+        };
+        let (poke, _guard) = Poke::alloc::<T>();
+        Some(
+            row.structure(poke, "")
+                .map(|opaque| unsafe { opaque.read::<T>() }),
+        )
+    }
+}
+
+/// Writes `rows` as CSV: a header with a column per field of `T`, then a record per row
+///
+/// Nested structs are flattened into dotted columns, like [`from_reader`] reads them,
+/// and `None` is written as empty cells. Cells holding commas, quotes or line breaks are
+/// quoted.
+///
+/// ```rust
+/// use facet_derive::Facet;
+/// use facet_trait::{self as facet, Facet};
+///
+/// #[derive(Facet)]
+/// struct Point {
+///     x: i32,
+///     y: i32,
+/// }
+///
+/// #[derive(Facet)]
+/// struct Marker {
+///     label: String,
+///     at: Point,
+/// }
+///
+/// let markers = [Marker {
+///     label: "home, sweet home".to_string(),
+///     at: Point { x: 1, y: -2 },
+/// }];
+/// let mut out = Vec::new();
+/// facet_csv::to_writer(&mut out, &markers).unwrap();
+/// assert_eq!(out, b"label,at.x,at.y\n\"home, sweet home\",1,-2\n");
+/// ```
+///
+/// # Errors
+///
+/// Returns an error if `T` isn't a struct that fits in columns, if a cell has no
+/// `Display` impl, or if writing fails.
+pub fn to_writer<'a, T, W>(
+    mut writer: W,
+    rows: impl IntoIterator<Item = &'a T>,
+) -> Result<(), CsvError>
+where
+    T: Facet + 'a,
+    W: Write,
+{
+    let headers = columns::headers(T::SHAPE)
+        .map_err(|shape| CsvError::new(1, None, CsvErrorKind::Unsupported(shape)))?;
+    write::write_record(&mut writer, headers.iter().map(String::as_str), 1)?;
+    let mut cells = Vec::with_capacity(headers.len());
+    let mut line = 2;
+    for row in rows {
+        cells.clear();
+        write::cells(Peek::new(row), "", &mut cells)
+            .map_err(|(column, kind)| CsvError::new(line, Some(&column), kind))?;
+        write::write_record(&mut writer, cells.iter().map(String::as_str), line)?;
+        // quoted cells can span lines
+        line += 1 + cells
+            .iter()
+            .map(|cell| cell.matches('\n').count())
+            .sum::<usize>();
+    }
+    Ok(())
+}
+
+/// Writes `rows` as a CSV string
+///
+/// # Errors
+///
+/// See [`to_writer`].
+pub fn to_string<'a, T: Facet + 'a>(
+    rows: impl IntoIterator<Item = &'a T>,
+) -> Result<String, CsvError> {
+    let mut out = Vec::new();
+    to_writer(&mut out, rows)?;
+    Ok(String::from_utf8(out).expect("cells are strings"))
+}
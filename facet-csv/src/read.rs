@@ -0,0 +1,245 @@
+use std::collections::HashMap;
+use std::io::BufRead;
+
+use facet_poke::Poke;
+use facet_trait::{Def, Field, FieldFlags, Opaque, ParseError, StructKind, VariantKind};
+
+use crate::{CsvError, CsvErrorKind};
+
+/// Splits CSV input into records, as described by RFC 4180: cells are separated by
+/// commas, and quoted when they hold commas, quotes (doubled) or line breaks
+pub(crate) struct Records<R> {
+    reader: R,
+    /// The number of lines read so far
+    line: usize,
+    buf: Vec<u8>,
+}
+
+impl<R: BufRead> Records<R> {
+    pub(crate) fn new(reader: R) -> Self {
+        Self {
+            reader,
+            line: 0,
+            buf: Vec::new(),
+        }
+    }
+
+    /// Reads the next record and the line it starts on, or `None` at the end of the
+    /// input. Blank lines are skipped.
+    pub(crate) fn next_record(&mut self) -> Result<Option<(usize, Vec<String>)>, CsvError> {
+        let mut cells = Vec::new();
+        let mut cell = Vec::new();
+        let mut in_quotes = false;
+        let mut start = self.line + 1;
+        loop {
+            self.buf.clear();
+            let read = self
+                .reader
+                .read_until(b'\n', &mut self.buf)
+                .map_err(|err| CsvError::new(self.line + 1, None, CsvErrorKind::Io(err)))?;
+            if read == 0 {
+                if in_quotes {
+                    let kind = CsvErrorKind::Syntax("unterminated quoted cell");
+                    return Err(CsvError::new(start, None, kind));
+                }
+                if cells.is_empty() && cell.is_empty() {
+                    return Ok(None);
+                }
+                cells.push(self.finish_cell(&mut cell, start)?);
+                return Ok(Some((start, cells)));
+            }
+            self.line += 1;
+            if !in_quotes && cells.is_empty() && cell.is_empty() {
+                if matches!(self.buf.as_slice(), b"\n" | b"\r\n") {
+                    start = self.line + 1;
+                    continue;
+                }
+                start = self.line;
+            }
+
+            let mut bytes = self.buf.iter().copied().peekable();
+            while let Some(b) = bytes.next() {
+                match b {
+                    b'"' if in_quotes => {
+                        if bytes.peek() == Some(&b'"') {
+                            bytes.next();
+                            cell.push(b'"');
+                        } else {
+                            in_quotes = false;
+                        }
+                    }
+                    _ if in_quotes => cell.push(b),
+                    // quotes only open a quoted cell at its start
+                    b'"' if cell.is_empty() => in_quotes = true,
+                    b',' => cells.push(self.finish_cell(&mut cell, start)?),
+                    b'\r' if matches!(bytes.peek(), Some(b'\n') | None) => {}
+                    b'\n' => {
+                        cells.push(self.finish_cell(&mut cell, start)?);
+                        return Ok(Some((start, cells)));
+                    }
+                    _ => cell.push(b),
+                }
+            }
+        }
+    }
+
+    fn finish_cell(&self, cell: &mut Vec<u8>, line: usize) -> Result<String, CsvError> {
+        String::from_utf8(core::mem::take(cell)).map_err(|_| {
+            CsvError::new(line, None, CsvErrorKind::Syntax("a cell isn't valid UTF-8"))
+        })
+    }
+}
+
+/// A record, read into the fields of a struct by column name
+pub(crate) struct Row<'r> {
+    /// The index of each column, by name
+    pub(crate) columns: &'r HashMap<String, usize>,
+    pub(crate) cells: &'r [String],
+    pub(crate) line: usize,
+}
+
+impl Row<'_> {
+    fn error(&self, column: &str, kind: CsvErrorKind) -> CsvError {
+        CsvError::new(self.line, Some(column), kind)
+    }
+
+    fn cell(&self, column: &str) -> Option<&str> {
+        self.columns
+            .get(column)
+            .map(|&index| self.cells[index].as_str())
+    }
+
+    /// Returns true if `column`, or a column nested under it, has a non-empty cell
+    fn is_set(&self, column: &str) -> bool {
+        let nested = format!("{column}.");
+        self.columns.iter().any(|(name, &index)| {
+            (name == column || name.starts_with(&nested)) && !self.cells[index].is_empty()
+        })
+    }
+
+    /// Builds the fields of a struct, whose columns start with `prefix`
+    pub(crate) fn structure<'mem>(
+        &self,
+        poke: Poke<'mem>,
+        prefix: &str,
+    ) -> Result<Opaque<'mem>, CsvError> {
+        let mut ps = poke.into_struct();
+        for (index, field) in ps.def().fields.iter().enumerate() {
+            let field_poke = ps.field(index).expect("the index is in bounds");
+            // on failure, dropping `ps` drops the fields that were built
+            match field_poke.shape().def {
+                Def::Struct(def)
+                    if def.kind == StructKind::Struct
+                        && field.flags.contains(FieldFlags::FLATTEN) =>
+                {
+                    self.structure(field_poke, prefix)?
+                }
+                _ => {
+                    let column = format!("{prefix}{}", field.name);
+                    self.value(field_poke, &column, Some(field))?
+                }
+            };
+            unsafe { ps.mark_initialized(index) };
+        }
+        Ok(ps.build_in_place())
+    }
+
+    /// Builds the value of `column`; on failure, leaves `poke` uninitialized
+    fn value<'mem>(
+        &self,
+        poke: Poke<'mem>,
+        column: &str,
+        field: Option<&Field>,
+    ) -> Result<Opaque<'mem>, CsvError> {
+        let shape = poke.shape();
+        let sensitive = field.is_some_and(|f| f.flags.contains(FieldFlags::SENSITIVE));
+        match shape.def {
+            Def::Option(def) => {
+                let po = poke.into_option();
+                if !self.is_set(column) {
+                    return Ok(po.init_none());
+                }
+                let (inner, _guard) = Poke::alloc_shape(def.t);
+                let value = self.value(inner, column, field)?;
+                Ok(unsafe { po.init_some(value) })
+            }
+            _ if shape.vtable.parse.is_some() => {
+                let raw = match self.cell(column) {
+                    Some("") if is_defaulted(field) => None,
+                    raw => raw,
+                };
+                let Some(raw) = raw else {
+                    return self.missing(poke, column, field);
+                };
+                poke.into_value().parse(raw).map_err(|(_, error)| {
+                    let value = (!sensitive).then(|| raw.to_string());
+                    self.error(column, CsvErrorKind::Invalid { value, error })
+                })
+            }
+            Def::Struct(def) => match def.kind {
+                StructKind::Unit => Ok(poke.into_struct().build_in_place()),
+                StructKind::TupleStruct if def.fields.len() == 1 => {
+                    let mut ps = poke.into_struct();
+                    let inner = ps.field(0).expect("the struct has a field");
+                    self.value(inner, column, Some(&def.fields[0]))?;
+                    unsafe { ps.mark_initialized(0) };
+                    Ok(ps.build_in_place())
+                }
+                StructKind::Struct if !self.is_set(column) && is_defaulted(field) => {
+                    self.missing(poke, column, field)
+                }
+                StructKind::Struct => self.structure(poke, &format!("{column}.")),
+                _ => Err(self.error(column, CsvErrorKind::Unsupported(shape))),
+            },
+            Def::Enum(def) => {
+                let raw = match self.cell(column) {
+                    Some("") if is_defaulted(field) => None,
+                    raw => raw,
+                };
+                let Some(raw) = raw else {
+                    return self.missing(poke, column, field);
+                };
+                let variant = def.variants.iter().find(|variant| {
+                    matches!(variant.kind, VariantKind::Unit) && variant.name == raw
+                });
+                match variant {
+                    Some(variant) => {
+                        let pe = poke
+                            .into_enum()
+                            .set_variant_by_name(variant.name)
+                            .expect("the variant exists");
+                        Ok(pe.build_in_place())
+                    }
+                    None => {
+                        let error =
+                            ParseError::new("not the name of a unit variant").with_shape(shape);
+                        let value = (!sensitive).then(|| raw.to_string());
+                        Err(self.error(column, CsvErrorKind::Invalid { value, error }))
+                    }
+                }
+            }
+            _ => Err(self.error(column, CsvErrorKind::Unsupported(shape))),
+        }
+    }
+
+    /// Handles a missing column, or an empty cell of a defaulted field: the field's
+    /// default, or an error
+    fn missing<'mem>(
+        &self,
+        poke: Poke<'mem>,
+        column: &str,
+        field: Option<&Field>,
+    ) -> Result<Opaque<'mem>, CsvError> {
+        if !is_defaulted(field) {
+            return Err(self.error(column, CsvErrorKind::MissingColumn));
+        }
+        let shape = poke.shape();
+        poke.into_value()
+            .default_in_place()
+            .map_err(|_| self.error(column, CsvErrorKind::Unsupported(shape)))
+    }
+}
+
+fn is_defaulted(field: Option<&Field>) -> bool {
+    field.is_some_and(|f| f.flags.contains(FieldFlags::DEFAULT))
+}
@@ -0,0 +1,84 @@
+use std::io::Write;
+
+use facet_poke::Peek;
+use facet_trait::{FieldFlags, StructKind};
+
+use crate::columns;
+use crate::{CsvError, CsvErrorKind};
+
+/// Adds the cells of a value to `out`, in the order of its columns
+///
+/// Returns the column of the value that can't be written if there's one.
+pub(crate) fn cells(
+    peek: Peek<'_>,
+    column: &str,
+    out: &mut Vec<String>,
+) -> Result<(), (String, CsvErrorKind)> {
+    let shape = peek.shape();
+    if columns::is_cell(shape) {
+        let cell = match peek {
+            Peek::Enum(pe) => pe.variant().name.to_string(),
+            peek if peek.vtable().display.is_some() => peek.to_string(),
+            _ => return Err((column.to_string(), CsvErrorKind::Unsupported(shape))),
+        };
+        out.push(cell);
+        return Ok(());
+    }
+    match peek {
+        Peek::Option(po) => match po.value() {
+            Some(value) => cells(value, column, out),
+            None => {
+                // an empty cell for each of the columns of the value
+                let count = columns::count(po.def().t);
+                out.extend(core::iter::repeat_n(String::new(), count));
+                Ok(())
+            }
+        },
+        Peek::Struct(ps) => match ps.def().kind {
+            StructKind::Unit => Ok(()),
+            StructKind::TupleStruct if ps.field_count() == 1 => {
+                let inner = ps.field_value(0).expect("the struct has a field");
+                cells(inner, column, out)
+            }
+            StructKind::Struct => {
+                for (index, field) in ps.def().fields.iter().enumerate() {
+                    let value = ps.field_value(index).expect("the index is in bounds");
+                    let column = match column {
+                        _ if field.flags.contains(FieldFlags::FLATTEN) => column.to_string(),
+                        "" => field.name.to_string(),
+                        column => format!("{column}.{}", field.name),
+                    };
+                    cells(value, &column, out)?;
+                }
+                Ok(())
+            }
+            _ => Err((column.to_string(), CsvErrorKind::Unsupported(shape))),
+        },
+        _ => Err((column.to_string(), CsvErrorKind::Unsupported(shape))),
+    }
+}
+
+/// Writes a record, quoting the cells that hold commas, quotes or line breaks
+pub(crate) fn write_record<'c>(
+    writer: &mut impl Write,
+    cells: impl IntoIterator<Item = &'c str>,
+    line: usize,
+) -> Result<(), CsvError> {
+    let mut record = String::new();
+    for (i, cell) in cells.into_iter().enumerate() {
+        if i > 0 {
+            record.push(',');
+        }
+        if cell.contains([',', '"', '\n', '\r']) {
+            record.push('"');
+            record.push_str(&cell.replace('"', "\"\""));
+            record.push('"');
+        } else {
+            record.push_str(cell);
+        }
+    }
+    record.push('\n');
+    writer
+        .write_all(record.as_bytes())
+        .map_err(|err| CsvError::new(line, None, CsvErrorKind::Io(err)))
+}
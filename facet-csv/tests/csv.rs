@@ -0,0 +1,232 @@
+use std::io::Read;
+
+use facet_csv::CsvErrorKind;
+use facet_derive::Facet;
+use facet_trait::{self as facet, Facet};
+
+#[derive(Debug, PartialEq, Facet)]
+struct Address {
+    street: String,
+    city: String,
+}
+
+#[derive(Debug, PartialEq, Facet)]
+struct Person {
+    #[facet(rename = "Name")]
+    name: String,
+    age: u8,
+    email: Option<String>,
+    address: Address,
+}
+
+#[derive(Debug, PartialEq, Facet)]
+struct Meta {
+    id: u32,
+    tag: String,
+}
+
+#[derive(Debug, PartialEq, Facet)]
+struct Entry {
+    #[facet(flatten)]
+    meta: Meta,
+    score: f64,
+    #[facet(default)]
+    retries: u32,
+    location: Option<Address>,
+}
+
+fn people() -> Vec<Person> {
+    vec![
+        Person {
+            name: "Ada".to_string(),
+            age: 36,
+            email: Some("ada@example.com".to_string()),
+            address: Address {
+                street: "12 St James's Square".to_string(),
+                city: "London".to_string(),
+            },
+        },
+        Person {
+            name: "Grace, \"Amazing\"".to_string(),
+            age: 85,
+            email: None,
+            address: Address {
+                street: "1 Navy Way\nSuite 2".to_string(),
+                city: "Arlington".to_string(),
+            },
+        },
+    ]
+}
+
+#[test]
+fn test_read_by_column_name() {
+    // columns in any order, with an unknown one
+    let csv = "address.city,notes,age,Name,address.street,email\n\
+               London,first,36,Ada,12 St James's Square,ada@example.com\n";
+    let rows = facet_csv::from_str::<Person>(csv).unwrap();
+    assert_eq!(rows, people()[..1]);
+}
+
+#[test]
+fn test_empty_cells_are_none() {
+    let csv = "id,tag,score,location.street,location.city\n\
+               1,a,0.5,,\n\
+               2,b,1.5,Main St,\n";
+    let rows = facet_csv::from_str::<Entry>(csv).unwrap();
+    assert_eq!(rows[0].location, None);
+    assert_eq!(
+        rows[1],
+        Entry {
+            meta: Meta {
+                id: 2,
+                tag: "b".to_string(),
+            },
+            score: 1.5,
+            retries: 0,
+            location: Some(Address {
+                street: "Main St".to_string(),
+                city: String::new(),
+            }),
+        }
+    );
+}
+
+#[test]
+fn test_defaults() {
+    let csv = "id,tag,score,retries\n1,a,2,\n2,b,3,4\n";
+    let rows = facet_csv::from_str::<Entry>(csv).unwrap();
+    assert_eq!(rows[0].retries, 0);
+    assert_eq!(rows[1].retries, 4);
+    assert_eq!(rows[1].location, None);
+}
+
+#[test]
+fn test_quoting() {
+    let csv = "Name,age,email,address.street,address.city\r\n\
+               \"Grace, \"\"Amazing\"\"\",85,,\"1 Navy Way\r\nSuite 2\",Arlington\r\n";
+    let rows = facet_csv::from_str::<Person>(csv).unwrap();
+    assert_eq!(rows[0].name, "Grace, \"Amazing\"");
+    assert_eq!(rows[0].address.street, "1 Navy Way\r\nSuite 2");
+
+    // blank lines, a byte order mark and a missing final line break
+    let csv = "\u{feff}id,tag,score\n\n1,a,2\n\n2,\"b\",3";
+    let rows = facet_csv::from_str::<Entry>(csv).unwrap();
+    assert_eq!(rows.len(), 2);
+    assert_eq!(rows[1].meta.tag, "b");
+}
+
+/// Hands out its input a few bytes at a time
+struct Trickle<'a>(&'a [u8]);
+
+impl Read for Trickle<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = buf.len().min(self.0.len()).min(3);
+        buf[..n].copy_from_slice(&self.0[..n]);
+        self.0 = &self.0[n..];
+        Ok(n)
+    }
+}
+
+#[test]
+fn test_streaming() {
+    let csv = facet_csv::to_string(&people()).unwrap();
+    let mut rows = facet_csv::from_reader::<Person, _>(Trickle(csv.as_bytes())).unwrap();
+    assert_eq!(rows.next().unwrap().unwrap(), people()[0]);
+    assert_eq!(rows.next().unwrap().unwrap(), people()[1]);
+    assert!(rows.next().is_none());
+}
+
+#[test]
+fn test_write() {
+    let csv = facet_csv::to_string(&people()).unwrap();
+    assert_eq!(
+        csv,
+        "Name,age,email,address.street,address.city\n\
+         Ada,36,ada@example.com,12 St James's Square,London\n\
+         \"Grace, \"\"Amazing\"\"\",85,,\"1 Navy Way\nSuite 2\",Arlington\n"
+    );
+    assert_eq!(facet_csv::from_str::<Person>(&csv).unwrap(), people());
+
+    let entries = [Entry {
+        meta: Meta {
+            id: 7,
+            tag: "x".to_string(),
+        },
+        score: 0.25,
+        retries: 1,
+        location: None,
+    }];
+    let csv = facet_csv::to_string(&entries).unwrap();
+    assert_eq!(
+        csv,
+        "id,tag,score,retries,location.street,location.city\n7,x,0.25,1,,\n"
+    );
+    assert_eq!(facet_csv::from_str::<Entry>(&csv).unwrap(), entries);
+
+    // no rows still gets a header
+    assert_eq!(facet_csv::to_string::<Meta>(&[]).unwrap(), "id,tag\n");
+}
+
+#[test]
+fn test_errors() {
+    let csv = "id,tag,score\n1,a,2\nx,b,3\n3,c\n4,d,4\n";
+    let rows = facet_csv::from_reader::<Entry, _>(csv.as_bytes())
+        .unwrap()
+        .collect::<Vec<_>>();
+    assert_eq!(rows.len(), 4);
+    assert!(rows[0].is_ok());
+    let err = rows[1].as_ref().unwrap_err();
+    assert_eq!(err.line, 3);
+    assert_eq!(err.column.as_deref(), Some("id"));
+    assert!(matches!(&err.kind, CsvErrorKind::Invalid { value: Some(v), .. } if v == "x"));
+    assert!(
+        err.to_string()
+            .starts_with("line 3, column `id`: invalid value \"x\"")
+    );
+    let err = rows[2].as_ref().unwrap_err();
+    assert_eq!(err.line, 4);
+    assert!(matches!(
+        err.kind,
+        CsvErrorKind::CellCount {
+            expected: 3,
+            got: 2
+        }
+    ));
+    assert!(rows[3].is_ok());
+
+    let err = facet_csv::from_str::<Entry>("id,score\n1,2\n").unwrap_err();
+    assert_eq!(err.line, 2);
+    assert_eq!(err.column.as_deref(), Some("tag"));
+    assert!(matches!(err.kind, CsvErrorKind::MissingColumn));
+
+    // a syntax error ends the rows
+    let csv = "id,tag,score\n1,\"a,2\n2,b,3\n";
+    let rows = facet_csv::from_reader::<Entry, _>(csv.as_bytes())
+        .unwrap()
+        .collect::<Vec<_>>();
+    assert_eq!(rows.len(), 1);
+    let err = rows[0].as_ref().unwrap_err();
+    assert_eq!(err.line, 2);
+    assert!(matches!(err.kind, CsvErrorKind::Syntax(_)));
+
+    // rows are structs
+    let err = facet_csv::from_str::<u32>("a\n1\n").unwrap_err();
+    assert!(matches!(err.kind, CsvErrorKind::Unsupported(_)));
+}
+
+#[derive(Debug, Facet)]
+struct Login {
+    user: String,
+    #[facet(sensitive)]
+    pin: u16,
+}
+
+#[test]
+fn test_sensitive_cells_are_not_shown() {
+    let err = facet_csv::from_str::<Login>("user,pin\nroot,hunter2\n").unwrap_err();
+    assert!(matches!(
+        err.kind,
+        CsvErrorKind::Invalid { value: None, .. }
+    ));
+    assert!(!err.to_string().contains("hunter2"));
+}
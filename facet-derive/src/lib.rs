@@ -4,6 +4,7 @@
 mod process_enum;
 mod process_struct;
 mod process_tuple_struct;
+mod process_unit_struct;
 
 use unsynn::*;
 
@@ -18,6 +19,12 @@ keyword! {
     KMut = "mut";
     KFacet = "facet";
     KSensitive = "sensitive";
+    KTryFrom = "try_from";
+    KRename = "rename";
+    KDefault = "default";
+    KFlatten = "flatten";
+    KMerge = "merge";
+    KPositional = "positional";
 }
 
 operator! {
@@ -52,9 +59,33 @@ unsynn! {
 
     enum FacetInner {
         Sensitive(KSensitive),
+        TryFrom(TryFromInner),
+        Rename(RenameInner),
+        Default(KDefault),
+        Flatten(KFlatten),
+        Merge(MergeInner),
+        Positional(KPositional),
         Other(Vec<TokenTree>)
     }
 
+    struct RenameInner {
+        _kw_rename: KRename,
+        _eq: Eq,
+        value: LiteralString,
+    }
+
+    struct MergeInner {
+        _kw_merge: KMerge,
+        _eq: Eq,
+        value: LiteralString,
+    }
+
+    struct TryFromInner {
+        _kw_try_from: KTryFrom,
+        _eq: Eq,
+        source: Vec<TokenTree>,
+    }
+
     struct DocInner {
         _kw_doc: KDoc,
         _eq: Eq,
@@ -130,6 +161,15 @@ unsynn! {
         body: ParenthesisGroupContaining<CommaDelimitedVec<TupleField>>,
     }
 
+    struct UnitStruct {
+        // Skip any doc attributes by consuming them
+        attributes: Vec<Attribute>,
+        _vis: Option<Vis>,
+        _kw_struct: KStruct,
+        name: Ident,
+        _semi: Semi,
+    }
+
     struct TupleField {
         attributes: Vec<Attribute>,
         vis: Option<Vis>,
@@ -171,7 +211,7 @@ unsynn! {
     }
 }
 
-/// Derive the Facet trait for structs, tuple structs, and enums.
+/// Derive the Facet trait for structs, tuple structs, unit structs, and enums.
 ///
 /// This uses unsynn, so it's light, but it _will_ choke on some Rust syntax because...
 /// there's a lot of Rust syntax.
@@ -193,6 +233,12 @@ pub fn facet_derive(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     }
     let tuple_struct_tokens_left = i.count();
 
+    // Try to parse as unit struct
+    i = input.to_token_iter(); // Reset iterator
+    if let Ok(parsed) = i.parse::<UnitStruct>() {
+        return process_unit_struct::process_unit_struct(parsed);
+    }
+
     // Try to parse as enum
     i = input.to_token_iter(); // Reset iterator
     if let Ok(parsed) = i.parse::<Enum>() {
@@ -244,6 +290,107 @@ pub fn facet_derive(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     panic!("{msg}");
 }
 
+/// Builds the `value_vtable!` invocation for a struct, forwarding the source types
+/// of any `#[facet(try_from = Type)]` attributes.
+pub(crate) fn value_vtable_for(struct_name: &str, attributes: &[Attribute]) -> String {
+    let sources = attributes
+        .iter()
+        .filter_map(|attr| match &attr.body.content {
+            AttributeInner::Facet(facet_attr) => match &facet_attr._sensitive.content {
+                FacetInner::TryFrom(try_from) => Some(
+                    try_from
+                        .source
+                        .iter()
+                        .cloned()
+                        .collect::<TokenStream>()
+                        .to_string(),
+                ),
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect::<Vec<String>>();
+
+    let try_from = if sources.is_empty() {
+        String::new()
+    } else {
+        format!(", try_from = [{}]", sources.join(", "))
+    };
+
+    format!(
+        "facet::value_vtable!(
+                {struct_name},
+                |f, _opts| core::fmt::Write::write_str(f, \"{struct_name}\"){try_from}
+            )"
+    )
+}
+
+/// Returns a `.doc(&[...])` builder call holding the lines of the doc comments among
+/// `attributes`, or nothing if there are none.
+pub(crate) fn doc_for(attributes: &[Attribute]) -> String {
+    let lines = attributes
+        .iter()
+        .filter_map(|attr| match &attr.body.content {
+            AttributeInner::Doc(doc) => Some(doc.value.value().to_string()),
+            _ => None,
+        })
+        .collect::<Vec<String>>();
+    if lines.is_empty() {
+        String::new()
+    } else {
+        format!(".doc(&[{}])", lines.join(", "))
+    }
+}
+
+/// Returns the name (a string literal, quotes included), the flags (a `FieldFlags`
+/// expression) and the `.merge_key(...)` builder call, if any, of a field, from its
+/// `#[facet(...)]` attributes.
+pub(crate) fn field_name_and_flags(
+    name: &str,
+    attributes: &[Attribute],
+) -> (String, String, String) {
+    let mut literal = format!("{name:?}");
+    let mut flags = Vec::new();
+    let mut merge_key = String::new();
+    for attr in attributes {
+        if let AttributeInner::Facet(facet_attr) = &attr.body.content {
+            match &facet_attr._sensitive.content {
+                FacetInner::Sensitive(_) => flags.push("SENSITIVE"),
+                FacetInner::Default(_) => flags.push("DEFAULT"),
+                FacetInner::Flatten(_) => flags.push("FLATTEN"),
+                FacetInner::Positional(_) => flags.push("POSITIONAL"),
+                FacetInner::Merge(merge) => {
+                    let strategy = merge.value.value().trim_matches('"');
+                    let key = strategy
+                        .strip_prefix("by_key(")
+                        .and_then(|rest| rest.strip_suffix(')'));
+                    flags.push(match (strategy, key) {
+                        ("replace", _) => "MERGE_REPLACE",
+                        ("append", _) => "MERGE_APPEND",
+                        ("by_key", _) => "MERGE_BY_KEY",
+                        (_, Some(key)) => {
+                            merge_key = format!(".merge_key({key:?})");
+                            "MERGE_BY_KEY"
+                        }
+                        _ => panic!(
+                            "Unknown merge strategy \"{strategy}\" on field `{name}`, expected \"replace\", \"append\", \"by_key\" or \"by_key(<item field>)\""
+                        ),
+                    })
+                }
+                FacetInner::Rename(rename) => literal = rename.value.value().to_string(),
+                _ => {}
+            }
+        }
+    }
+
+    let flags = flags
+        .iter()
+        .map(|flag| format!("facet::FieldFlags::{flag}"))
+        .reduce(|a, b| format!("{a}.union({b})"))
+        .unwrap_or_else(|| "facet::FieldFlags::EMPTY".to_string());
+    (literal, flags, merge_key)
+}
+
 impl core::fmt::Display for Type {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         match self {
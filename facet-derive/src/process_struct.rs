@@ -32,22 +32,20 @@ pub(crate) fn process_struct(parsed: Struct) -> proc_macro::TokenStream {
         .map(|field| {
             let field_name = field.value.name.to_string();
 
-            // Determine field flags
-            let mut flags = "facet::FieldFlags::EMPTY";
-            for attr in &field.value.attributes {
-                if let AttributeInner::Facet(_) = &attr.body.content {
-                    // Since FacetInner only has Sensitive variant, we can directly set flags
-                    flags = "facet::FieldFlags::SENSITIVE";
-                }
-            }
+            // Determine the name, flags and merge key, which attributes can change
+            let (name, flags, merge_key) =
+                field_name_and_flags(&field_name, &field.value.attributes);
+            let doc = doc_for(&field.value.attributes);
 
             // Generate each field definition
             format!(
                 "facet::Field::builder()
-                .name(\"{field_name}\")
+                .name({name})
                 .shape(facet::shape_of(&|s: {struct_name}| s.{field_name}))
                 .offset(::core::mem::offset_of!({struct_name}, {field_name}))
                 .flags({flags})
+                {merge_key}
+                {doc}
                 .build()"
             )
         })
@@ -57,6 +55,9 @@ pub(crate) fn process_struct(parsed: Struct) -> proc_macro::TokenStream {
             ",
         );
 
+    let vtable = value_vtable_for(&struct_name, &parsed.attributes);
+    let doc = doc_for(&parsed.attributes);
+
     // Generate the impl
     let output = format!(
         r#"
@@ -73,14 +74,12 @@ unsafe impl facet::Facet for {struct_name} {{
 
         facet::Shape::builder()
             .layout(core::alloc::Layout::new::<Self>())
-            .vtable(facet::value_vtable!(
-                {struct_name},
-                |f, _opts| core::fmt::Write::write_str(f, "{struct_name}")
-            ))
+            .vtable({vtable})
             .def(facet::Def::Struct(facet::StructDef::builder()
                 .kind(facet::StructKind::Struct)
                 .fields(FIELDS)
                 .build()))
+            {doc}
             .build()
     }};
 }}
@@ -28,6 +28,9 @@ pub(crate) fn process_tuple_struct(parsed: TupleStruct) -> proc_macro::TokenStre
         .collect::<Vec<String>>()
         .join(", ");
 
+    let vtable = value_vtable_for(&struct_name, &parsed.attributes);
+    let doc = doc_for(&parsed.attributes);
+
     // Generate the impl
     let output = format!(
         r#"
@@ -37,15 +40,13 @@ unsafe impl facet::Facet for {struct_name} {{
     const SHAPE: &'static facet::Shape = &const {{
         facet::Shape::builder()
             .layout(core::alloc::Layout::new::<Self>())
-            .vtable(facet::value_vtable!(
-                {struct_name},
-                |f, _opts| core::fmt::Write::write_str(f, "{struct_name}")
-            ))
+            .vtable({vtable})
             .def(facet::Def::Struct(facet::StructDef::builder()
                 .kind(facet::StructKind::TupleStruct)
                 .fields(facet::struct_fields!({struct_name}, ({fields_str})))
                 .build()
             ))
+            {doc}
             .build()
     }};
 }}
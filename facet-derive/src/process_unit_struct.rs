@@ -0,0 +1,37 @@
+use super::*;
+
+/// Processes a unit struct to implement Facet
+///
+/// Example input:
+/// ```rust
+/// struct Marker;
+/// ```
+pub(crate) fn process_unit_struct(parsed: UnitStruct) -> proc_macro::TokenStream {
+    let struct_name = parsed.name.to_string();
+
+    let vtable = value_vtable_for(&struct_name, &parsed.attributes);
+    let doc = doc_for(&parsed.attributes);
+
+    // Generate the impl
+    let output = format!(
+        r#"
+#[automatically_derived]
+unsafe impl facet::Facet for {struct_name} {{
+    const ARCHETYPE: Self = Self;
+    const SHAPE: &'static facet::Shape = &const {{
+        facet::Shape::builder()
+            .layout(core::alloc::Layout::new::<Self>())
+            .vtable({vtable})
+            .def(facet::Def::Struct(facet::StructDef::builder()
+                .kind(facet::StructKind::Unit)
+                .fields(&[])
+                .build()
+            ))
+            {doc}
+            .build()
+    }};
+}}
+    "#
+    );
+    output.into_token_stream().into()
+}
@@ -0,0 +1,63 @@
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// Lists whose unmatched middles are larger than this many item pairs aren't aligned:
+/// their middles are compared item by item instead
+const MAX_TABLE_SIZE: usize = 1 << 20;
+
+/// Returns the pairs of indices of the items of two lists that are matched to each other,
+/// in increasing order, along the longest common subsequence of equal items
+pub(crate) fn align(
+    old_len: usize,
+    new_len: usize,
+    mut eq: impl FnMut(usize, usize) -> bool,
+) -> Vec<(usize, usize)> {
+    // common prefixes and suffixes are matched without building a table
+    let mut prefix = 0;
+    while prefix < old_len.min(new_len) && eq(prefix, prefix) {
+        prefix += 1;
+    }
+    let mut suffix = 0;
+    while suffix < (old_len - prefix).min(new_len - prefix)
+        && eq(old_len - 1 - suffix, new_len - 1 - suffix)
+    {
+        suffix += 1;
+    }
+
+    let mut pairs: Vec<_> = (0..prefix).map(|i| (i, i)).collect();
+
+    let (n, m) = (old_len - prefix - suffix, new_len - prefix - suffix);
+    if n > 0 && m > 0 && n.saturating_mul(m) <= MAX_TABLE_SIZE {
+        // lengths[i][j] is the length of the longest common subsequence of the middles
+        // starting at i and j
+        let mut lengths = vec![0u32; (n + 1) * (m + 1)];
+        let at = |i: usize, j: usize| i * (m + 1) + j;
+        for i in (0..n).rev() {
+            for j in (0..m).rev() {
+                lengths[at(i, j)] = if eq(prefix + i, prefix + j) {
+                    lengths[at(i + 1, j + 1)] + 1
+                } else {
+                    lengths[at(i + 1, j)].max(lengths[at(i, j + 1)])
+                };
+            }
+        }
+
+        let (mut i, mut j) = (0, 0);
+        while i < n && j < m {
+            if lengths[at(i, j)] == lengths[at(i + 1, j + 1)] + 1
+                && lengths[at(i, j)] > lengths[at(i + 1, j)].max(lengths[at(i, j + 1)])
+            {
+                pairs.push((prefix + i, prefix + j));
+                i += 1;
+                j += 1;
+            } else if lengths[at(i + 1, j)] >= lengths[at(i, j + 1)] {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+    }
+
+    pairs.extend((0..suffix).map(|k| (old_len - suffix + k, new_len - suffix + k)));
+    pairs
+}
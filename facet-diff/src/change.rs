@@ -0,0 +1,64 @@
+use core::fmt;
+
+use facet_peek::{Path, Peek};
+
+/// One difference between two values
+#[derive(Clone, Debug)]
+pub struct Change<'mem> {
+    /// Where the difference is
+    ///
+    /// Removed list items are at their index in the old list, everything else is at its
+    /// index in the new list.
+    pub path: Path,
+    /// What changed
+    pub kind: ChangeKind<'mem>,
+    /// True if the change is inside a field marked `#[facet(sensitive)]`: its values
+    /// shouldn't be shown
+    pub sensitive: bool,
+}
+
+/// What changed at a [`Change`]'s path
+#[derive(Clone, Copy, Debug)]
+pub enum ChangeKind<'mem> {
+    /// The value was replaced: a scalar changed, an enum switched variants, or an option
+    /// switched between `None` and `Some`
+    Modified {
+        /// The value before
+        old: Peek<'mem>,
+        /// The value after
+        new: Peek<'mem>,
+    },
+    /// A list item or a map entry was added
+    Added(Peek<'mem>),
+    /// A list item or a map entry was removed
+    Removed(Peek<'mem>),
+}
+
+impl fmt::Display for Change<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.path.is_root() {
+            f.write_str("(root)")?;
+        } else {
+            write!(f, "{}", self.path)?;
+        }
+        let value = |peek| Value(peek, self.sensitive);
+        match self.kind {
+            ChangeKind::Modified { old, new } => write!(f, ": {} -> {}", value(old), value(new)),
+            ChangeKind::Added(new) => write!(f, ": added {}", value(new)),
+            ChangeKind::Removed(old) => write!(f, ": removed {}", value(old)),
+        }
+    }
+}
+
+/// Debug-formats a value, unless it's sensitive
+struct Value<'mem>(Peek<'mem>, bool);
+
+impl fmt::Display for Value<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.1 {
+            f.write_str("[REDACTED]")
+        } else {
+            write!(f, "{:?}", self.0)
+        }
+    }
+}
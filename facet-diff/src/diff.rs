@@ -0,0 +1,271 @@
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+use core::ops::ControlFlow;
+
+use facet_peek::{Path, PathSegment, Peek, PeekList, PeekMap, PeekValue};
+use facet_trait::{Field, FieldFlags, ShapeExt as _};
+
+use crate::align::align;
+use crate::options::Float;
+use crate::{Change, ChangeKind, DiffOptions};
+
+/// Returns every difference between `old` and `new`
+///
+/// Changes come in field order, list order, and map key order. Values of different
+/// shapes are reported as a single modification of the whole value.
+pub fn diff<'mem>(old: Peek<'mem>, new: Peek<'mem>) -> Vec<Change<'mem>> {
+    diff_with(old, new, &DiffOptions::default())
+}
+
+/// Returns every difference between `old` and `new` that `options` doesn't rule out
+pub fn diff_with<'mem>(
+    old: Peek<'mem>,
+    new: Peek<'mem>,
+    options: &DiffOptions,
+) -> Vec<Change<'mem>> {
+    let mut changes = Vec::new();
+    let _ = Differ {
+        path: Path::root(),
+        sensitive: 0,
+        options,
+        emit: |path: &Path, kind, sensitive| {
+            changes.push(Change {
+                path: path.clone(),
+                kind,
+                sensitive,
+            });
+            ControlFlow::Continue(())
+        },
+    }
+    .root(old, new);
+    changes
+}
+
+/// Returns true if `old` and `new`, found at `path`, have no differences
+fn same(path: Path, options: &DiffOptions, old: Peek<'_>, new: Peek<'_>) -> bool {
+    Differ {
+        path,
+        sensitive: 0,
+        options,
+        emit: |_: &Path, _, _| ControlFlow::Break(()),
+    }
+    .values(old, new)
+    .is_continue()
+}
+
+/// Walks two values side by side, calling `emit` for each difference until it breaks
+struct Differ<'o, F> {
+    path: Path,
+    /// How many sensitive fields the walk is inside of
+    sensitive: usize,
+    options: &'o DiffOptions,
+    emit: F,
+}
+
+impl<'mem, F: FnMut(&Path, ChangeKind<'mem>, bool) -> ControlFlow<()>> Differ<'_, F> {
+    fn root(&mut self, old: Peek<'mem>, new: Peek<'mem>) -> ControlFlow<()> {
+        // below the root, both sides have the same shape by construction: enums and
+        // options are checked for a matching variant before their fields are compared
+        if !core::ptr::eq(old.shape(), new.shape()) && old.shape() != new.shape() {
+            return self.emit(ChangeKind::Modified { old, new });
+        }
+        self.values(old, new)
+    }
+
+    fn values(&mut self, old: Peek<'mem>, new: Peek<'mem>) -> ControlFlow<()> {
+        if self.options.is_ignored(&self.path) {
+            return ControlFlow::Continue(());
+        }
+        match (old, new) {
+            (Peek::Value(a), Peek::Value(b)) => {
+                if !self.scalars_equal(a, b) {
+                    return self.emit(ChangeKind::Modified { old, new });
+                }
+            }
+            (Peek::Struct(a), Peek::Struct(b)) => {
+                for (field, ((name, a), (_, b))) in
+                    a.def().fields.iter().zip(a.fields().zip(b.fields()))
+                {
+                    self.field(field, name, a, b)?;
+                }
+            }
+            (Peek::Enum(a), Peek::Enum(b)) => {
+                if a.variant_index() != b.variant_index() {
+                    return self.emit(ChangeKind::Modified { old, new });
+                }
+                for (field, ((name, a), (_, b))) in
+                    a.variant_fields().iter().zip(a.fields().zip(b.fields()))
+                {
+                    self.field(field, name, a, b)?;
+                }
+            }
+            (Peek::Option(a), Peek::Option(b)) => match (a.value(), b.value()) {
+                (Some(a), Some(b)) => self.values(a, b)?,
+                (None, None) => {}
+                _ => return self.emit(ChangeKind::Modified { old, new }),
+            },
+            (Peek::List(a), Peek::List(b)) => self.lists(a, b)?,
+            (Peek::Map(a), Peek::Map(b)) => self.maps(a, b)?,
+            _ => return self.emit(ChangeKind::Modified { old, new }),
+        }
+        ControlFlow::Continue(())
+    }
+
+    /// Reports a change to the current value
+    fn emit(&mut self, kind: ChangeKind<'mem>) -> ControlFlow<()> {
+        if self.options.is_ignored(&self.path) {
+            return ControlFlow::Continue(());
+        }
+        (self.emit)(&self.path, kind, self.sensitive > 0)
+    }
+
+    /// Returns true if `old` and `new`, found at `segment`, have no differences
+    fn same(&self, segment: PathSegment, old: Peek<'_>, new: Peek<'_>) -> bool {
+        same(self.path.join(segment), self.options, old, new)
+    }
+
+    /// Compares two values of a struct or enum field
+    fn field(
+        &mut self,
+        field: &Field,
+        name: &str,
+        old: Peek<'mem>,
+        new: Peek<'mem>,
+    ) -> ControlFlow<()> {
+        let sensitive = field.flags.contains(FieldFlags::SENSITIVE);
+        self.sensitive += sensitive as usize;
+        let flow = self.at(PathSegment::Field(name.to_string()), old, new);
+        self.sensitive -= sensitive as usize;
+        flow
+    }
+
+    /// Compares two children found at `segment`
+    fn at(&mut self, segment: PathSegment, old: Peek<'mem>, new: Peek<'mem>) -> ControlFlow<()> {
+        self.path.push(segment);
+        let flow = self.values(old, new);
+        self.path.pop();
+        flow
+    }
+
+    /// Reports a change to the child at `segment`
+    fn emit_at(&mut self, segment: PathSegment, kind: ChangeKind<'mem>) -> ControlFlow<()> {
+        self.path.push(segment);
+        let flow = self.emit(kind);
+        self.path.pop();
+        flow
+    }
+
+    fn lists(&mut self, old: PeekList<'mem>, new: PeekList<'mem>) -> ControlFlow<()> {
+        let old: Vec<_> = old.iter().collect();
+        let new: Vec<_> = new.iter().collect();
+        if self.options.lists_are_unordered() {
+            return self.multisets(&old, &new);
+        }
+        let anchors = align(old.len(), new.len(), |i, j| {
+            self.same(PathSegment::Index(j), old[i], new[j])
+        });
+
+        // between two anchors, removed items are paired with added ones and compared,
+        // the rest are reported as removed or added
+        let (mut i, mut j) = (0, 0);
+        for (next_i, next_j) in anchors.into_iter().chain([(old.len(), new.len())]) {
+            let paired = (next_i - i).min(next_j - j);
+            for k in 0..paired {
+                self.at(PathSegment::Index(j + k), old[i + k], new[j + k])?;
+            }
+            for (k, &item) in old.iter().enumerate().take(next_i).skip(i + paired) {
+                self.emit_at(PathSegment::Index(k), ChangeKind::Removed(item))?;
+            }
+            for (k, &item) in new.iter().enumerate().take(next_j).skip(j + paired) {
+                self.emit_at(PathSegment::Index(k), ChangeKind::Added(item))?;
+            }
+            (i, j) = (next_i + 1, next_j + 1);
+        }
+        ControlFlow::Continue(())
+    }
+
+    /// Matches each old item with an equal new item, wherever they are in their lists
+    fn multisets(&mut self, old: &[Peek<'mem>], new: &[Peek<'mem>]) -> ControlFlow<()> {
+        let mut matched = vec![false; new.len()];
+        let mut removed = Vec::new();
+        for (i, &item) in old.iter().enumerate() {
+            let found = (0..new.len())
+                .find(|&j| !matched[j] && self.same(PathSegment::Index(j), item, new[j]));
+            match found {
+                Some(j) => matched[j] = true,
+                None => removed.push(i),
+            }
+        }
+        for i in removed {
+            self.emit_at(PathSegment::Index(i), ChangeKind::Removed(old[i]))?;
+        }
+        for (j, &item) in new.iter().enumerate() {
+            if !matched[j] {
+                self.emit_at(PathSegment::Index(j), ChangeKind::Added(item))?;
+            }
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn maps(&mut self, old: PeekMap<'mem>, new: PeekMap<'mem>) -> ControlFlow<()> {
+        let mut entries: Vec<(Peek<'mem>, Option<Peek<'mem>>, Option<Peek<'mem>>)> = old
+            .iter()
+            .map(|(key, value)| (key, Some(value), new.get_peek(key.as_value())))
+            .collect();
+        entries.extend(
+            new.iter()
+                .filter(|(key, _)| old.get_peek(key.as_value()).is_none())
+                .map(|(key, value)| (key, None, Some(value))),
+        );
+        entries.sort_by(|(a, _, _), (b, _, _)| compare_keys(a.as_value(), b.as_value()));
+
+        for (key, old, new) in entries {
+            let segment = PathSegment::Key(key.to_string());
+            match (old, new) {
+                (Some(old), Some(new)) => self.at(segment, old, new)?,
+                (Some(old), None) => self.emit_at(segment, ChangeKind::Removed(old))?,
+                (None, Some(new)) => self.emit_at(segment, ChangeKind::Added(new))?,
+                (None, None) => unreachable!("every entry comes from one of the maps"),
+            }
+        }
+        ControlFlow::Continue(())
+    }
+
+    /// Compares two floats with the options' tolerance, other scalars with their `eq`
+    /// vtable entry, or their debug representation if they have none; scalars that have
+    /// neither can't be compared, so they're reported as modified
+    fn scalars_equal(&self, a: PeekValue<'_>, b: PeekValue<'_>) -> bool {
+        if let (Some(a), Some(b)) = (float(a), float(b)) {
+            return self.options.floats_equal(a, b);
+        }
+        if let Some(eq) = a.eq(&b) {
+            return eq;
+        }
+        if a.vtable().debug.is_some() {
+            return alloc::format!("{a:?}") == alloc::format!("{b:?}");
+        }
+        false
+    }
+}
+
+fn float(value: PeekValue<'_>) -> Option<Float> {
+    let shape = value.shape();
+    if shape.is_type::<f64>() {
+        Some(Float::F64(unsafe { *value.data().as_ref::<f64>() }))
+    } else if shape.is_type::<f32>() {
+        Some(Float::F32(unsafe { *value.data().as_ref::<f32>() }))
+    } else {
+        None
+    }
+}
+
+/// Orders map keys with their `partial_ord` vtable entry, or by their display
+/// representation if they have none
+fn compare_keys(a: PeekValue<'_>, b: PeekValue<'_>) -> Ordering {
+    a.partial_cmp(&b).unwrap_or_else(|| {
+        let (a, b): (String, String) = (a.to_string(), b.to_string());
+        a.cmp(&b)
+    })
+}
@@ -0,0 +1,28 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+#![warn(missing_docs)]
+#![doc = include_str!("../README.md")]
+
+//! Structural diffs between two values
+//!
+//! [`diff`] walks two [`Peek`](facet_peek::Peek)s of the same shape side by side:
+//!
+//!   * structs and enum variants are compared field by field
+//!   * lists are aligned on their longest common subsequence of equal items, so that an
+//!     insertion shows up as one added item rather than every item after it changing
+//!   * maps are compared key by key
+//!   * scalars are compared with their `eq` vtable entry
+//!
+//! Each difference is reported as a [`Change`] with its [`Path`](facet_peek::Path).
+
+extern crate alloc;
+
+mod change;
+pub use change::*;
+
+mod diff;
+pub use diff::*;
+
+mod options;
+pub use options::*;
+
+mod align;
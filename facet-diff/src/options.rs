@@ -0,0 +1,111 @@
+use alloc::vec::Vec;
+
+use facet_peek::{Path, PathPattern};
+
+/// Tunes what [`diff_with`](crate::diff_with) considers a difference
+///
+/// The default options are those of [`diff`](crate::diff): nothing is ignored, floats
+/// must be equal, and lists are ordered. Whatever the options, NaN is equal to NaN, since
+/// a value that stays NaN hasn't changed.
+#[derive(Debug, Clone, Default)]
+pub struct DiffOptions {
+    ignored: Vec<PathPattern>,
+    tolerance: Option<FloatTolerance>,
+    unordered_lists: bool,
+}
+
+/// How far apart two floats can be while still being considered equal
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FloatTolerance {
+    /// The absolute difference between the floats is at most this much
+    Epsilon(f64),
+    /// At most this many representable floats lie between the floats, counting the
+    /// larger one: `1` lets adjacent floats through
+    Ulps(u64),
+}
+
+impl DiffOptions {
+    /// Returns the default options
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Ignores the values at paths matching `pattern`, and everything inside them
+    pub fn ignore(mut self, pattern: PathPattern) -> Self {
+        self.ignored.push(pattern);
+        self
+    }
+
+    /// Considers floats equal if they're within `tolerance` of each other
+    pub fn float_tolerance(mut self, tolerance: FloatTolerance) -> Self {
+        self.tolerance = Some(tolerance);
+        self
+    }
+
+    /// Compares lists as multisets: items are matched regardless of their position, and
+    /// only the items left without a match are reported, as removed or added
+    pub fn unordered_lists(mut self, unordered: bool) -> Self {
+        self.unordered_lists = unordered;
+        self
+    }
+
+    pub(crate) fn is_ignored(&self, path: &Path) -> bool {
+        self.ignored.iter().any(|pattern| pattern.matches(path))
+    }
+
+    pub(crate) fn lists_are_unordered(&self) -> bool {
+        self.unordered_lists
+    }
+
+    /// Compares two floats, widened to `f64`; `f32` distances in ULPs are measured
+    /// between the original `f32`s
+    pub(crate) fn floats_equal(&self, a: Float, b: Float) -> bool {
+        let (wide_a, wide_b) = (a.wide(), b.wide());
+        if wide_a == wide_b || (wide_a.is_nan() && wide_b.is_nan()) {
+            return true;
+        }
+        match self.tolerance {
+            None => false,
+            Some(FloatTolerance::Epsilon(epsilon)) => (wide_a - wide_b).abs() <= epsilon,
+            Some(FloatTolerance::Ulps(ulps)) => {
+                let distance = match (a, b) {
+                    (Float::F32(a), Float::F32(b)) => {
+                        ulps_between(a.to_bits() as u64, b.to_bits() as u64, 31)
+                    }
+                    _ => ulps_between(wide_a.to_bits(), wide_b.to_bits(), 63),
+                };
+                distance <= ulps as u128
+            }
+        }
+    }
+}
+
+/// A float read from a value
+#[derive(Clone, Copy)]
+pub(crate) enum Float {
+    F32(f32),
+    F64(f64),
+}
+
+impl Float {
+    fn wide(self) -> f64 {
+        match self {
+            Float::F32(f) => f as f64,
+            Float::F64(f) => f,
+        }
+    }
+}
+
+/// Counts the floats between two floats' bits, `sign` being the position of the sign bit
+fn ulps_between(a: u64, b: u64, sign: u32) -> u128 {
+    // maps the bits to integers that order like the floats do
+    let ordered = |bits: u64| {
+        let magnitude = (bits & ((1 << sign) - 1)) as i128;
+        if bits >> sign & 1 == 1 {
+            -magnitude
+        } else {
+            magnitude
+        }
+    };
+    ordered(a).abs_diff(ordered(b))
+}
@@ -0,0 +1,315 @@
+use std::collections::HashMap;
+
+use facet_derive::Facet;
+use facet_diff::{Change, ChangeKind, DiffOptions, FloatTolerance, diff, diff_with};
+use facet_peek::Peek;
+use facet_testhelpers::{Handle, Source};
+use facet_trait::Facet;
+
+use facet_trait as facet;
+
+#[derive(Debug, Facet)]
+struct Deployment {
+    name: String,
+    replicas: u32,
+    image: Image,
+    ports: Vec<u16>,
+    labels: HashMap<String, String>,
+    proxy: Option<String>,
+    source: Source,
+}
+
+#[derive(Debug, Facet)]
+struct Image {
+    repository: String,
+    tag: String,
+}
+
+#[derive(Debug, Facet)]
+struct Credentials {
+    user: String,
+    #[facet(sensitive)]
+    password: String,
+}
+
+fn deployment() -> Deployment {
+    Deployment {
+        name: "api".to_string(),
+        replicas: 3,
+        image: Image {
+            repository: "registry/api".to_string(),
+            tag: "1.0".to_string(),
+        },
+        ports: vec![80, 443],
+        labels: HashMap::from([
+            ("team".to_string(), "core".to_string()),
+            ("tier".to_string(), "web".to_string()),
+        ]),
+        proxy: None,
+        source: Source::File("api.toml".to_string()),
+    }
+}
+
+/// Renders changes to strings, so they can be compared
+fn changes<T: Facet>(old: &T, new: &T) -> Vec<String> {
+    diff(Peek::new(old), Peek::new(new))
+        .iter()
+        .map(Change::to_string)
+        .collect()
+}
+
+#[test]
+fn equal_values_have_no_changes() {
+    assert!(changes(&deployment(), &deployment()).is_empty());
+}
+
+#[test]
+fn fields_are_compared_one_by_one() {
+    let mut new = deployment();
+    new.replicas = 5;
+    new.image.tag = "1.1".to_string();
+
+    assert_eq!(
+        changes(&deployment(), &new),
+        ["replicas: 3 -> 5", r#"image.tag: "1.0" -> "1.1""#]
+    );
+
+    let old = deployment();
+    let changes = diff(Peek::new(&old), Peek::new(&new));
+    let ChangeKind::Modified { old, new } = changes[0].kind else {
+        panic!("expected a modification, got {:?}", changes[0]);
+    };
+    assert_eq!(old.as_value(), Peek::new(&3u32).as_value());
+    assert_eq!(new.as_value(), Peek::new(&5u32).as_value());
+}
+
+#[test]
+fn lists_are_aligned() {
+    let list = |items: &[u16]| Deployment {
+        ports: items.to_vec(),
+        ..deployment()
+    };
+
+    // an insertion is a single change, not a change to every item after it
+    assert_eq!(
+        changes(&list(&[80, 443, 8080]), &list(&[22, 80, 443, 8080])),
+        ["ports[0]: added 22"]
+    );
+    assert_eq!(
+        changes(&list(&[80, 443, 8080]), &list(&[80, 8080])),
+        ["ports[1]: removed 443"]
+    );
+    // replaced items are compared
+    assert_eq!(
+        changes(&list(&[80, 443, 8080]), &list(&[80, 8443, 8080, 9000])),
+        ["ports[1]: 443 -> 8443", "ports[3]: added 9000"]
+    );
+    // removed items are at their old index, added items at their new one
+    assert_eq!(
+        changes(&list(&[1, 2, 3, 4]), &list(&[4, 3, 2, 1])),
+        [
+            "ports[0]: removed 1",
+            "ports[1]: removed 2",
+            "ports[2]: removed 3",
+            "ports[1]: added 3",
+            "ports[2]: added 2",
+            "ports[3]: added 1",
+        ]
+    );
+}
+
+#[test]
+fn changes_inside_list_items_have_paths() {
+    let old = vec![
+        Image {
+            repository: "a".to_string(),
+            tag: "1".to_string(),
+        },
+        Image {
+            repository: "b".to_string(),
+            tag: "1".to_string(),
+        },
+    ];
+    let new = vec![
+        Image {
+            repository: "a".to_string(),
+            tag: "1".to_string(),
+        },
+        Image {
+            repository: "b".to_string(),
+            tag: "2".to_string(),
+        },
+    ];
+    assert_eq!(changes(&old, &new), [r#"[1].tag: "1" -> "2""#]);
+}
+
+#[test]
+fn maps_are_compared_by_key() {
+    let mut new = deployment();
+    new.labels.remove("team");
+    new.labels.insert("tier".to_string(), "backend".to_string());
+    new.labels.insert("owner".to_string(), "ops".to_string());
+
+    assert_eq!(
+        changes(&deployment(), &new),
+        [
+            r#"labels["owner"]: added "ops""#,
+            r#"labels["team"]: removed "core""#,
+            r#"labels["tier"]: "web" -> "backend""#,
+        ]
+    );
+}
+
+#[test]
+fn options_and_enums() {
+    let mut new = deployment();
+    new.proxy = Some("http://proxy".to_string());
+    new.source = Source::File("api.yaml".to_string());
+    assert_eq!(
+        changes(&deployment(), &new),
+        [
+            r#"proxy: None -> Some("http://proxy")"#,
+            r#"source.0: "api.toml" -> "api.yaml""#,
+        ]
+    );
+
+    // inside a `Some`, the option is transparent
+    let mut newer = deployment();
+    newer.proxy = Some("http://other".to_string());
+    assert_eq!(
+        changes(&new, &newer),
+        [
+            r#"proxy: "http://proxy" -> "http://other""#,
+            r#"source.0: "api.yaml" -> "api.toml""#,
+        ]
+    );
+
+    // a different variant replaces the whole enum
+    let mut remote = deployment();
+    remote.source = Source::Remote {
+        url: "https://config".to_string(),
+        retries: 2,
+    };
+    assert_eq!(
+        changes(&deployment(), &remote),
+        [r#"source: File("api.toml") -> Remote { url: "https://config", retries: 2 }"#]
+    );
+
+    let mut disabled = deployment();
+    disabled.source = Source::Disabled;
+    assert_eq!(
+        changes(&remote, &disabled),
+        [r#"source: Remote { url: "https://config", retries: 2 } -> Disabled"#]
+    );
+}
+
+#[test]
+fn different_shapes_are_replaced_whole() {
+    let one = "one".to_string();
+    let changes = diff(Peek::new(&1u32), Peek::new(&one));
+    assert_eq!(changes.len(), 1);
+    assert_eq!(changes[0].to_string(), r#"(root): 1 -> "one""#);
+}
+
+#[test]
+fn sensitive_changes_are_flagged() {
+    let old = Credentials {
+        user: "admin".to_string(),
+        password: "hunter2".to_string(),
+    };
+    let new = Credentials {
+        user: "root".to_string(),
+        password: "correct horse".to_string(),
+    };
+    let changes = diff(Peek::new(&old), Peek::new(&new));
+    assert!(!changes[0].sensitive);
+    assert!(changes[1].sensitive);
+    assert_eq!(changes[1].to_string(), "password: [REDACTED] -> [REDACTED]");
+}
+
+#[test]
+fn options_rule_out_differences() {
+    let paths = |old: &Deployment, new: &Deployment, options: &DiffOptions| {
+        diff_with(Peek::new(old), Peek::new(new), options)
+            .iter()
+            .map(|change| change.path.to_string())
+            .collect::<Vec<_>>()
+    };
+
+    let mut new = deployment();
+    new.replicas = 4;
+    new.image.tag = "1.1".to_string();
+    new.labels.insert("team".to_string(), "infra".to_string());
+    let options = DiffOptions::new()
+        .ignore("replicas".parse().unwrap())
+        .ignore("*.tag".parse().unwrap())
+        .ignore("labels[*]".parse().unwrap());
+    assert_eq!(paths(&deployment(), &new, &options), [] as [&str; 0]);
+
+    new.ports = vec![443, 80];
+    assert_eq!(
+        paths(&deployment(), &new, &options),
+        ["ports[0]", "ports[1]"]
+    );
+    let options = options.unordered_lists(true);
+    assert_eq!(paths(&deployment(), &new, &options), [] as [&str; 0]);
+    new.ports = vec![443, 8080, 80, 80];
+    assert_eq!(
+        paths(&deployment(), &new, &options),
+        ["ports[1]", "ports[3]"]
+    );
+}
+
+#[test]
+fn floats_can_be_compared_with_a_tolerance() {
+    let equal = |a: f64, b: f64, tolerance| {
+        let options = DiffOptions::new().float_tolerance(tolerance);
+        diff_with(Peek::new(&a), Peek::new(&b), &options).is_empty()
+    };
+
+    assert!(equal(0.1 + 0.2, 0.3, FloatTolerance::Epsilon(1e-12)));
+    assert!(!equal(0.1, 0.2, FloatTolerance::Epsilon(1e-12)));
+    assert!(equal(f64::NAN, f64::NAN, FloatTolerance::Epsilon(0.0)));
+
+    let next = f64::from_bits(1.0f64.to_bits() + 1);
+    assert!(equal(1.0, next, FloatTolerance::Ulps(1)));
+    assert!(!equal(
+        1.0,
+        f64::from_bits(1.0f64.to_bits() + 2),
+        FloatTolerance::Ulps(1)
+    ));
+    assert!(equal(-0.0, f64::from_bits(1), FloatTolerance::Ulps(1)));
+    assert!(equal(
+        -f64::from_bits(1),
+        f64::from_bits(1),
+        FloatTolerance::Ulps(2)
+    ));
+
+    let options = DiffOptions::new().float_tolerance(FloatTolerance::Ulps(1));
+    let next = f32::from_bits(1.0f32.to_bits() + 1);
+    assert!(diff_with(Peek::new(&1.0f32), Peek::new(&next), &options).is_empty());
+
+    assert!(!diff(Peek::new(&(0.1 + 0.2)), Peek::new(&0.3)).is_empty());
+}
+
+#[derive(Facet)]
+struct Connection {
+    name: String,
+    handle: Handle,
+}
+
+#[test]
+fn incomparable_scalars_are_modified() {
+    let old = Connection {
+        name: "db".to_string(),
+        handle: Handle(1),
+    };
+    let new = Connection {
+        name: "db".to_string(),
+        handle: Handle(1),
+    };
+    let changes = diff(Peek::new(&old), Peek::new(&new));
+    assert_eq!(changes.len(), 1);
+    assert_eq!(changes[0].path.to_string(), "handle");
+    assert!(matches!(changes[0].kind, ChangeKind::Modified { .. }));
+}
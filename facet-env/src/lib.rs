@@ -0,0 +1,451 @@
+#![warn(missing_docs)]
+#![doc = include_str!("../README.md")]
+
+use std::collections::HashMap;
+use std::fmt;
+
+use facet_poke::{Poke, Scratch};
+use facet_trait::{
+    Def, Facet, Field, FieldFlags, Opaque, ParseError, Shape, StructKind, VariantKind,
+};
+
+#[cfg(test)]
+mod tests;
+
+/// Deserializes a `T` from the process environment, with the default options for `prefix`
+///
+/// See [`EnvConfig`] for how variables are named.
+pub fn from_env<T: Facet>(prefix: &str) -> Result<T, EnvErrors> {
+    EnvConfig::new(prefix).from_env()
+}
+
+/// Says where a value lives in the environment
+///
+/// A field's variable is the prefix followed by the names of the fields leading to it,
+/// uppercased and joined with the separator: with the prefix `APP_` and the default `__`
+/// separator, `database.url` is read from `APP_DATABASE__URL`.
+///
+///   * scalars are parsed with their shape's `parse` vtable entry, and unit variants of
+///     enums are read by name
+///   * lists are split on the list delimiter, `,` by default: `APP_FEATURES=a,b,c`
+///   * maps are read from every variable under theirs: `APP_LABELS__TIER=web` sets the
+///     key `TIER`
+///   * an `Option` field is `None` when none of its variables are set, and a
+///     `#[facet(default)]` field takes its default value
+///
+/// ```rust
+/// use facet_derive::Facet;
+/// use facet_env::EnvConfig;
+/// use facet_trait::{self as facet, Facet};
+///
+/// #[derive(Facet)]
+/// struct Config {
+///     workers: u32,
+///     features: Vec<String>,
+///     database: Database,
+/// }
+///
+/// #[derive(Facet)]
+/// struct Database {
+///     url: String,
+///     pool: Option<u32>,
+/// }
+///
+/// let config: Config = EnvConfig::new("APP_")
+///     .from_vars([
+///         ("APP_WORKERS", "4"),
+///         ("APP_FEATURES", "a,b,c"),
+///         ("APP_DATABASE__URL", "postgres://localhost"),
+///     ])
+///     .unwrap();
+/// assert_eq!(config.workers, 4);
+/// assert_eq!(config.features, ["a", "b", "c"]);
+/// assert_eq!(config.database.pool, None);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EnvConfig {
+    prefix: String,
+    separator: String,
+    list_delimiter: char,
+}
+
+impl EnvConfig {
+    /// Returns the default options for variables starting with `prefix`
+    pub fn new(prefix: impl Into<String>) -> Self {
+        Self {
+            prefix: prefix.into(),
+            separator: "__".to_string(),
+            list_delimiter: ',',
+        }
+    }
+
+    /// Sets what separates the names of nested fields, `__` by default
+    pub fn separator(mut self, separator: impl Into<String>) -> Self {
+        self.separator = separator.into();
+        self
+    }
+
+    /// Sets what separates the items of lists, `,` by default
+    pub fn list_delimiter(mut self, delimiter: char) -> Self {
+        self.list_delimiter = delimiter;
+        self
+    }
+
+    /// Deserializes a `T` from the process environment
+    ///
+    /// Variables whose name or value isn't valid unicode are ignored.
+    pub fn from_env<T: Facet>(&self) -> Result<T, EnvErrors> {
+        self.from_vars(
+            std::env::vars_os().filter_map(|(key, value)| {
+                Some((key.into_string().ok()?, value.into_string().ok()?))
+            }),
+        )
+    }
+
+    /// Deserializes a `T` from the given variables, e.g. a map built by a test
+    ///
+    /// # Errors
+    ///
+    /// Returns every variable that's missing or invalid, not just the first one.
+    pub fn from_vars<T, K, V>(&self, vars: impl IntoIterator<Item = (K, V)>) -> Result<T, EnvErrors>
+    where
+        T: Facet,
+        K: Into<String>,
+        V: Into<String>,
+    {
+        let mut reader = Reader {
+            config: self,
+            vars: vars
+                .into_iter()
+                .map(|(key, value)| (key.into(), value.into()))
+                .collect(),
+            errors: Vec::new(),
+        };
+        let (poke, _guard) = Poke::alloc::<T>();
+        let built = match T::SHAPE.def {
+            Def::Struct(_) => reader.structure(poke, &self.prefix),
+            _ => {
+                reader.error(&self.prefix, EnvErrorKind::Unsupported(T::SHAPE));
+                None
+            }
+        };
+        match built {
+            Some(opaque) if reader.errors.is_empty() => Ok(unsafe { opaque.read::<T>() }),
+            Some(opaque) => {
+                if let Some(drop_fn) = T::SHAPE.vtable.drop_in_place {
+                    unsafe { drop_fn(opaque) };
+                }
+                Err(EnvErrors(reader.errors))
+            }
+            None => Err(EnvErrors(reader.errors)),
+        }
+    }
+}
+
+/// Every variable that was missing or invalid, returned by [`EnvConfig::from_vars`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct EnvErrors(Vec<EnvError>);
+
+impl EnvErrors {
+    /// Returns the errors, in the order of the fields
+    pub fn errors(&self) -> &[EnvError] {
+        &self.0
+    }
+}
+
+impl fmt::Display for EnvErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let count = self.0.len();
+        let plural = if count == 1 { "" } else { "s" };
+        write!(f, "{count} invalid environment variable{plural}")?;
+        for error in &self.0 {
+            write!(f, "\n  {error}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for EnvErrors {}
+
+/// A variable that was missing or invalid
+#[derive(Debug, Clone, PartialEq)]
+pub struct EnvError {
+    /// The name of the variable
+    pub variable: String,
+    /// What's wrong with it
+    pub kind: EnvErrorKind,
+}
+
+/// What's wrong with a variable
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum EnvErrorKind {
+    /// The variable isn't set, and the field is neither optional nor defaulted
+    Missing,
+    /// The variable's value (or one of its items, for a list) doesn't parse
+    Invalid {
+        /// The value, unless the field is sensitive
+        value: Option<String>,
+        /// Why it doesn't parse
+        error: ParseError,
+    },
+    /// The field's type can't be read from the environment
+    Unsupported(&'static Shape),
+}
+
+impl fmt::Display for EnvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let variable = &self.variable;
+        match &self.kind {
+            EnvErrorKind::Missing => write!(f, "{variable}: missing"),
+            EnvErrorKind::Invalid {
+                value: Some(value),
+                error,
+            } => write!(f, "{variable}: invalid value {value:?}: {error}"),
+            EnvErrorKind::Invalid { value: None, error } => {
+                write!(f, "{variable}: invalid value: {error}")
+            }
+            EnvErrorKind::Unsupported(shape) => {
+                write!(f, "{variable}: can't read a {shape} from the environment")
+            }
+        }
+    }
+}
+
+impl std::error::Error for EnvError {}
+
+struct Reader<'c> {
+    config: &'c EnvConfig,
+    vars: HashMap<String, String>,
+    errors: Vec<EnvError>,
+}
+
+impl Reader<'_> {
+    fn error(&mut self, variable: &str, kind: EnvErrorKind) {
+        self.errors.push(EnvError {
+            variable: variable.to_string(),
+            kind,
+        });
+    }
+
+    /// Returns true if `var`, or a variable nested under it, is set
+    fn is_set(&self, var: &str) -> bool {
+        let nested = format!("{var}{}", self.config.separator);
+        self.vars
+            .keys()
+            .any(|key| key == var || key.starts_with(&nested))
+    }
+
+    /// Builds the fields of a struct, whose variables start with `prefix`
+    fn structure<'mem>(&mut self, poke: Poke<'mem>, prefix: &str) -> Option<Opaque<'mem>> {
+        let mut ps = poke.into_struct();
+        let fields = ps.def().fields;
+        let mut complete = true;
+        for (index, field) in fields.iter().enumerate() {
+            let field_poke = ps.field(index).expect("the index is in bounds");
+            let value = if field.flags.contains(FieldFlags::FLATTEN) {
+                self.structure(field_poke, prefix)
+            } else {
+                let var = format!("{prefix}{}", field.name.to_uppercase());
+                self.value(field_poke, &var, Some(field))
+            };
+            match value {
+                Some(_) => unsafe { ps.mark_initialized(index) },
+                None => complete = false,
+            }
+        }
+        // on failure, dropping `ps` drops the fields that were built
+        complete.then(|| ps.build_in_place())
+    }
+
+    /// Builds the value of `var`; on failure, records why and leaves `poke` uninitialized
+    fn value<'mem>(
+        &mut self,
+        poke: Poke<'mem>,
+        var: &str,
+        field: Option<&Field>,
+    ) -> Option<Opaque<'mem>> {
+        let shape = poke.shape();
+        let sensitive = field.is_some_and(|f| f.flags.contains(FieldFlags::SENSITIVE));
+        match shape.def {
+            Def::Option(def) => {
+                let po = poke.into_option();
+                if !self.is_set(var) {
+                    return Some(po.init_none());
+                }
+                let (inner, _guard) = Poke::alloc_shape(def.t);
+                let value = self.value(inner, var, field)?;
+                Some(unsafe { po.init_some(value) })
+            }
+            Def::List(def) => {
+                let Some(raw) = self.vars.get(var).cloned() else {
+                    return self.missing(poke, var, field);
+                };
+                let items = if raw.is_empty() {
+                    Vec::new()
+                } else {
+                    raw.split(self.config.list_delimiter).collect()
+                };
+                let mut parsed = Vec::with_capacity(items.len());
+                for item in items {
+                    parsed.push(self.parse_on_side(def.t, item, var, sensitive)?);
+                }
+                let Ok(mut pl) = poke.into_list().init(Some(parsed.len())) else {
+                    self.error(var, EnvErrorKind::Unsupported(shape));
+                    return None;
+                };
+                for mut item in parsed {
+                    unsafe { pl.push(item.take()) };
+                }
+                Some(pl.build_in_place())
+            }
+            Def::Map(def) => {
+                let nested = format!("{var}{}", self.config.separator);
+                let mut entries: Vec<(String, String)> = self
+                    .vars
+                    .iter()
+                    .filter_map(|(key, value)| {
+                        Some((key.strip_prefix(&nested)?.to_string(), value.clone()))
+                    })
+                    .collect();
+                entries.sort();
+                let mut parsed = Vec::with_capacity(entries.len());
+                let mut complete = true;
+                for (key, value) in &entries {
+                    let var = format!("{nested}{key}");
+                    let key = self.parse_on_side(def.k, key, &var, false);
+                    let value = self.parse_on_side(def.v, value, &var, sensitive);
+                    match (key, value) {
+                        (Some(key), Some(value)) => parsed.push((key, value)),
+                        _ => complete = false,
+                    }
+                }
+                if !complete {
+                    return None;
+                }
+                let Ok(mut pm) = poke.into_map().init(Some(parsed.len())) else {
+                    self.error(var, EnvErrorKind::Unsupported(shape));
+                    return None;
+                };
+                for (mut key, mut value) in parsed {
+                    unsafe { pm.insert(key.take(), value.take()) };
+                }
+                Some(pm.build_in_place())
+            }
+            _ if shape.vtable.parse.is_some() => {
+                let Some(raw) = self.vars.get(var) else {
+                    return self.missing(poke, var, field);
+                };
+                match poke.into_value().parse(raw) {
+                    Ok(value) => Some(value),
+                    Err((_, error)) => {
+                        let value = (!sensitive).then(|| raw.clone());
+                        self.error(var, EnvErrorKind::Invalid { value, error });
+                        None
+                    }
+                }
+            }
+            Def::Struct(def) => match def.kind {
+                StructKind::Unit => Some(poke.into_struct().build_in_place()),
+                StructKind::TupleStruct if def.fields.len() == 1 => {
+                    let mut ps = poke.into_struct();
+                    let inner = ps.field(0).expect("the struct has a field");
+                    self.value(inner, var, Some(&def.fields[0]))?;
+                    unsafe { ps.mark_initialized(0) };
+                    Some(ps.build_in_place())
+                }
+                StructKind::Struct if !self.is_set(var) && is_defaulted(field) => {
+                    self.missing(poke, var, field)
+                }
+                StructKind::Struct => {
+                    let prefix = format!("{var}{}", self.config.separator);
+                    self.structure(poke, &prefix)
+                }
+                _ => {
+                    self.error(var, EnvErrorKind::Unsupported(shape));
+                    None
+                }
+            },
+            Def::Enum(def) => {
+                let Some(raw) = self.vars.get(var).cloned() else {
+                    return self.missing(poke, var, field);
+                };
+                let variant = def.variants.iter().find(|variant| {
+                    matches!(variant.kind, VariantKind::Unit)
+                        && variant.name.eq_ignore_ascii_case(&raw)
+                });
+                match variant {
+                    Some(variant) => {
+                        let pe = poke
+                            .into_enum()
+                            .set_variant_by_name(variant.name)
+                            .expect("the variant exists");
+                        Some(pe.build_in_place())
+                    }
+                    None => {
+                        let error =
+                            ParseError::new("not the name of a unit variant").with_shape(shape);
+                        let value = (!sensitive).then_some(raw);
+                        self.error(var, EnvErrorKind::Invalid { value, error });
+                        None
+                    }
+                }
+            }
+            _ => {
+                self.error(var, EnvErrorKind::Unsupported(shape));
+                None
+            }
+        }
+    }
+
+    /// Parses an item of a list or a map, to be moved into it once they all parse
+    fn parse_on_side(
+        &mut self,
+        shape: &'static Shape,
+        s: &str,
+        var: &str,
+        sensitive: bool,
+    ) -> Option<Scratch> {
+        if shape.vtable.parse.is_none() {
+            self.error(var, EnvErrorKind::Unsupported(shape));
+            return None;
+        }
+        let (poke, mut scratch) = Scratch::alloc(shape);
+        match poke.into_value().parse(s) {
+            Ok(_) => {
+                unsafe { scratch.mark_initialized() };
+                Some(scratch)
+            }
+            Err((_, error)) => {
+                let value = (!sensitive).then(|| s.to_string());
+                self.error(var, EnvErrorKind::Invalid { value, error });
+                None
+            }
+        }
+    }
+
+    /// Handles an unset variable: the field's default, or an error
+    fn missing<'mem>(
+        &mut self,
+        poke: Poke<'mem>,
+        var: &str,
+        field: Option<&Field>,
+    ) -> Option<Opaque<'mem>> {
+        if !is_defaulted(field) {
+            self.error(var, EnvErrorKind::Missing);
+            return None;
+        }
+        let shape = poke.shape();
+        match poke.into_value().default_in_place() {
+            Ok(value) => Some(value),
+            Err(_) => {
+                self.error(var, EnvErrorKind::Unsupported(shape));
+                None
+            }
+        }
+    }
+}
+
+fn is_defaulted(field: Option<&Field>) -> bool {
+    field.is_some_and(|f| f.flags.contains(FieldFlags::DEFAULT))
+}
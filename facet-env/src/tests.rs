@@ -0,0 +1,149 @@
+use std::collections::HashMap;
+
+use crate::{EnvConfig, EnvErrorKind};
+use facet_derive::Facet;
+use facet_trait::{self as facet, Facet};
+
+#[derive(Debug, Facet, PartialEq)]
+struct Config {
+    workers: u32,
+    features: Vec<String>,
+    database: Database,
+    #[facet(default)]
+    verbose: bool,
+    timeout: Option<u64>,
+    labels: HashMap<String, String>,
+}
+
+#[derive(Debug, Facet, PartialEq)]
+struct Database {
+    url: String,
+    #[facet(sensitive)]
+    password: Option<String>,
+    ports: Vec<u16>,
+}
+
+fn vars(vars: &[(&str, &str)]) -> Vec<(String, String)> {
+    vars.iter()
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect()
+}
+
+#[test]
+fn test_nested_fields_and_lists() {
+    let config: Config = EnvConfig::new("APP_")
+        .from_vars(vars(&[
+            ("APP_WORKERS", "4"),
+            ("APP_FEATURES", "a,b,c"),
+            ("APP_DATABASE__URL", "postgres://localhost"),
+            ("APP_DATABASE__PASSWORD", "hunter2"),
+            ("APP_DATABASE__PORTS", "5432,5433"),
+            ("APP_TIMEOUT", "30"),
+            ("APP_LABELS__TIER", "web"),
+            ("APP_LABELS__TEAM", "core"),
+            ("OTHER_WORKERS", "8"),
+        ]))
+        .unwrap();
+    assert_eq!(
+        config,
+        Config {
+            workers: 4,
+            features: vec!["a".to_string(), "b".to_string(), "c".to_string()],
+            database: Database {
+                url: "postgres://localhost".to_string(),
+                password: Some("hunter2".to_string()),
+                ports: vec![5432, 5433],
+            },
+            verbose: false,
+            timeout: Some(30),
+            labels: HashMap::from([
+                ("TIER".to_string(), "web".to_string()),
+                ("TEAM".to_string(), "core".to_string()),
+            ]),
+        }
+    );
+}
+
+#[test]
+fn test_options_and_defaults() {
+    let config: Config = EnvConfig::new("APP_")
+        .from_vars(vars(&[
+            ("APP_WORKERS", "1"),
+            ("APP_FEATURES", ""),
+            ("APP_DATABASE__URL", "sqlite://"),
+            ("APP_DATABASE__PORTS", ""),
+        ]))
+        .unwrap();
+    assert!(config.features.is_empty());
+    assert!(!config.verbose);
+    assert_eq!(config.timeout, None);
+    assert_eq!(config.database.password, None);
+    assert!(config.labels.is_empty());
+}
+
+#[test]
+fn test_custom_separator_and_delimiter() {
+    let config: Config = EnvConfig::new("MY.")
+        .separator(".")
+        .list_delimiter(';')
+        .from_vars(vars(&[
+            ("MY.WORKERS", "2"),
+            ("MY.FEATURES", "x;y"),
+            ("MY.DATABASE.URL", "mysql://"),
+            ("MY.DATABASE.PORTS", "3306"),
+            ("MY.VERBOSE", "true"),
+        ]))
+        .unwrap();
+    assert_eq!(config.features, ["x", "y"]);
+    assert_eq!(config.database.ports, [3306]);
+    assert!(config.verbose);
+}
+
+#[test]
+fn test_every_error_is_reported() {
+    let errors = EnvConfig::new("APP_")
+        .from_vars::<Config, _, _>(vars(&[
+            ("APP_WORKERS", "many"),
+            ("APP_DATABASE__PASSWORD", "hunter2"),
+            ("APP_DATABASE__PORTS", "5432,http"),
+            ("APP_TIMEOUT", "soon"),
+        ]))
+        .unwrap_err();
+    let summary: Vec<(&str, bool)> = errors
+        .errors()
+        .iter()
+        .map(|e| (e.variable.as_str(), e.kind == EnvErrorKind::Missing))
+        .collect();
+    assert_eq!(
+        summary,
+        [
+            ("APP_WORKERS", false),
+            ("APP_FEATURES", true),
+            ("APP_DATABASE__URL", true),
+            ("APP_DATABASE__PORTS", false),
+            ("APP_TIMEOUT", false),
+        ]
+    );
+
+    let message = errors.to_string();
+    assert!(message.starts_with("5 invalid environment variables\n"));
+    assert!(message.contains("APP_WORKERS: invalid value \"many\""));
+    assert!(message.contains("APP_FEATURES: missing"));
+}
+
+#[test]
+fn test_sensitive_values_are_not_reported() {
+    #[derive(Debug, Facet, PartialEq)]
+    struct Secret {
+        #[facet(sensitive)]
+        pin: u32,
+    }
+    let errors = EnvConfig::new("APP_")
+        .from_vars::<Secret, _, _>(vars(&[("APP_PIN", "hunter2")]))
+        .unwrap_err();
+    assert!(matches!(
+        errors.errors()[0].kind,
+        EnvErrorKind::Invalid { value: None, .. }
+    ));
+    assert!(!errors.to_string().contains("hunter2"));
+}
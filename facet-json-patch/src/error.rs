@@ -0,0 +1,79 @@
+use alloc::string::String;
+use core::fmt;
+
+use crate::{Pointer, SyntaxError, Value};
+
+/// Returned when a patch can't be applied
+///
+/// Whatever the error, the patched value is left as it was before the patch.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PatchError {
+    /// The index of the failing operation, for JSON Patch documents
+    pub operation: Option<usize>,
+    /// The pointer that failed to resolve, or whose value was rejected
+    pub pointer: Pointer,
+    /// What went wrong
+    pub kind: PatchErrorKind,
+}
+
+/// What went wrong while applying a patch
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum PatchErrorKind {
+    /// The patch isn't JSON
+    Syntax(SyntaxError),
+    /// The patch is JSON, but not a valid patch, e.g. an operation without `"op"`
+    Invalid(String),
+    /// Nothing is at the pointer
+    NotFound,
+    /// The pointer goes through a list, with a token that isn't an index into it
+    InvalidIndex(String),
+    /// The new value doesn't fit the type of the value at the pointer
+    Mismatch(String),
+    /// A `test` operation found something else than the expected value
+    TestFailed {
+        /// The value that was found
+        actual: Value,
+    },
+    /// The operation can't be done on this type, e.g. removing a field that isn't an
+    /// `Option`
+    Unsupported(&'static str),
+}
+
+impl PatchError {
+    pub(crate) fn new(pointer: Pointer, kind: PatchErrorKind) -> Self {
+        Self {
+            operation: None,
+            pointer,
+            kind,
+        }
+    }
+}
+
+impl fmt::Display for PatchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            PatchErrorKind::Syntax(err) => write!(f, "Invalid JSON: {err}")?,
+            PatchErrorKind::Invalid(message) => write!(f, "Invalid patch: {message}")?,
+            PatchErrorKind::NotFound => write!(f, "Nothing at `{}`", self.pointer)?,
+            PatchErrorKind::InvalidIndex(token) => {
+                write!(f, "Invalid index `{token}` in `{}`", self.pointer)?
+            }
+            PatchErrorKind::Mismatch(message) => {
+                write!(f, "Mismatched value at `{}`: {message}", self.pointer)?
+            }
+            PatchErrorKind::TestFailed { actual } => {
+                write!(f, "Test failed at `{}`: found {actual}", self.pointer)?
+            }
+            PatchErrorKind::Unsupported(message) => {
+                write!(f, "Can't patch `{}`: {message}", self.pointer)?
+            }
+        }
+        if let Some(operation) = self.operation {
+            write!(f, " (operation {operation})")?;
+        }
+        Ok(())
+    }
+}
+
+impl core::error::Error for PatchError {}
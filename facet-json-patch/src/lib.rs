@@ -0,0 +1,49 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+#![warn(missing_docs)]
+#![doc = include_str!("../README.md")]
+
+//! JSON Patch and JSON Merge Patch, applied to typed values
+//!
+//! [`Patch`] applies a list of operations at [`Pointer`]s, [`MergePatch`] merges a
+//! document into the value. Either way, values are checked against the type they're
+//! written into, using the data model of [`facet_serialize`], and an error leaves the
+//! value as it was before the patch.
+
+extern crate alloc;
+
+use facet_trait::Facet;
+
+mod error;
+pub use error::*;
+
+mod merge;
+pub use merge::*;
+
+mod patch;
+pub use patch::*;
+
+mod pointer;
+pub use pointer::{Pointer, PointerParseError};
+
+mod target;
+
+mod value;
+pub use value::{SyntaxError, Value};
+
+/// Parses `patch` as a JSON Patch document and applies it to `value`
+///
+/// # Errors
+///
+/// See [`Patch::apply`].
+pub fn apply_patch<T: Facet>(value: &mut T, patch: &str) -> Result<(), PatchError> {
+    patch.parse::<Patch>()?.apply(value)
+}
+
+/// Parses `patch` as a JSON Merge Patch document and merges it into `value`
+///
+/// # Errors
+///
+/// See [`MergePatch::apply`].
+pub fn apply_merge_patch<T: Facet>(value: &mut T, patch: &str) -> Result<(), PatchError> {
+    patch.parse::<MergePatch>()?.apply(value)
+}
@@ -0,0 +1,146 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use facet_trait::{Facet, Opaque};
+
+use crate::target::{MergeKind, Target};
+use crate::{PatchError, PatchErrorKind, Pointer, Value};
+
+/// A JSON Merge Patch document, as defined by
+/// [RFC 7386](https://www.rfc-editor.org/rfc/rfc7386)
+///
+/// An object patches the struct or map it's applied to member by member: `null` removes
+/// a map entry or sets an optional field to `None`, another object patches the value
+/// recursively, and anything else replaces the value. Any other patch replaces the whole
+/// value.
+///
+/// ```rust
+/// use std::collections::HashMap;
+///
+/// use facet_derive::Facet;
+/// use facet_json_patch::MergePatch;
+/// use facet_trait::{self as facet, Facet};
+///
+/// #[derive(Facet)]
+/// struct Config {
+///     name: String,
+///     token: Option<String>,
+///     labels: HashMap<String, String>,
+/// }
+///
+/// let mut config = Config {
+///     name: "web".to_string(),
+///     token: Some("hunter2".to_string()),
+///     labels: HashMap::from([("tier".to_string(), "front".to_string())]),
+/// };
+/// let patch: MergePatch = r#"{ "token": null, "labels": { "tier": null, "team": "core" } }"#
+///     .parse()
+///     .unwrap();
+/// patch.apply(&mut config).unwrap();
+/// assert_eq!(config.token, None);
+/// assert_eq!(config.labels, HashMap::from([("team".to_string(), "core".to_string())]));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct MergePatch {
+    patch: Value,
+}
+
+impl MergePatch {
+    /// Returns a merge patch made of `patch`
+    pub fn new(patch: Value) -> Self {
+        Self { patch }
+    }
+
+    /// Returns the patch document
+    pub fn value(&self) -> &Value {
+        &self.patch
+    }
+
+    /// Merges the patch into `value`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the patch names a field `value` doesn't have, or a value
+    /// doesn't fit, in which case `value` is left untouched.
+    pub fn apply<T: Facet>(&self, value: &mut T) -> Result<(), PatchError> {
+        Target::new(Opaque::from_ref(value), T::SHAPE)
+            .run(|target| merge(target, &mut Vec::new(), &self.patch))
+    }
+}
+
+impl core::str::FromStr for MergePatch {
+    type Err = PatchError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.parse()
+            .map(Self::new)
+            .map_err(|err| PatchError::new(Pointer::root(), PatchErrorKind::Syntax(err)))
+    }
+}
+
+fn merge(
+    target: &mut Target<'_>,
+    tokens: &mut Vec<String>,
+    patch: &Value,
+) -> Result<(), PatchError> {
+    let at = |tokens: &[String]| {
+        let pointer = Pointer::from(tokens.to_vec());
+        move |kind| PatchError::new(pointer, kind)
+    };
+    let Value::Object(members) = patch else {
+        return target.replace(tokens, patch).map_err(at(tokens));
+    };
+    match target.merge_kind(tokens).map_err(at(tokens))? {
+        MergeKind::Fields => {
+            for (key, value) in members {
+                tokens.push(key.clone());
+                match value {
+                    Value::Null => {
+                        if !target.is_option(tokens).map_err(at(tokens))? {
+                            return Err(PatchError::new(
+                                Pointer::from(tokens.clone()),
+                                PatchErrorKind::Unsupported("only optional fields can be removed"),
+                            ));
+                        }
+                        if !target.is_none(tokens).map_err(at(tokens))? {
+                            target.remove(tokens).map_err(at(tokens))?;
+                        }
+                    }
+                    value => merge(target, tokens, value)?,
+                }
+                tokens.pop();
+            }
+            Ok(())
+        }
+        MergeKind::Entries => {
+            for (key, value) in members {
+                tokens.push(key.clone());
+                let exists = target.exists(tokens);
+                match value {
+                    Value::Null if exists => target.remove(tokens).map_err(at(tokens))?,
+                    Value::Null => {}
+                    Value::Object(_) if exists => merge(target, tokens, value)?,
+                    value => target
+                        .add(tokens, &value.without_nulls())
+                        .map_err(at(tokens))?,
+                }
+                tokens.pop();
+            }
+            Ok(())
+        }
+        MergeKind::Variant(name) => match members.as_slice() {
+            [(key, value @ Value::Object(_))] if key == name => {
+                tokens.push(key.clone());
+                merge(target, tokens, value)?;
+                tokens.pop();
+                Ok(())
+            }
+            _ => target
+                .replace(tokens, &patch.without_nulls())
+                .map_err(at(tokens)),
+        },
+        MergeKind::Value => target
+            .replace(tokens, &patch.without_nulls())
+            .map_err(at(tokens)),
+    }
+}
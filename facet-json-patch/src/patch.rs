@@ -0,0 +1,234 @@
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use facet_trait::{Facet, Opaque};
+
+use crate::target::Target;
+use crate::{PatchError, PatchErrorKind, Pointer, Value};
+
+/// A JSON Patch document, as defined by [RFC 6902](https://www.rfc-editor.org/rfc/rfc6902)
+///
+/// ```rust
+/// use facet_derive::Facet;
+/// use facet_json_patch::Patch;
+/// use facet_trait::{self as facet, Facet};
+///
+/// #[derive(Facet)]
+/// struct Config {
+///     name: String,
+///     ports: Vec<u16>,
+/// }
+///
+/// let mut config = Config { name: "web".to_string(), ports: vec![80] };
+/// let patch: Patch = r#"[
+///     { "op": "replace", "path": "/name", "value": "api" },
+///     { "op": "add", "path": "/ports/-", "value": 443 }
+/// ]"#
+/// .parse()
+/// .unwrap();
+/// patch.apply(&mut config).unwrap();
+/// assert_eq!(config.name, "api");
+/// assert_eq!(config.ports, [80, 443]);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Patch {
+    operations: Vec<Operation>,
+}
+
+/// One operation of a [`Patch`]
+#[derive(Debug, Clone, PartialEq)]
+pub enum Operation {
+    /// Inserts into a list or a map, or replaces a field
+    Add {
+        /// Where to add
+        path: Pointer,
+        /// What to add
+        value: Value,
+    },
+    /// Removes from a list or a map, or sets an optional field to `None`
+    Remove {
+        /// What to remove
+        path: Pointer,
+    },
+    /// Replaces an existing value
+    Replace {
+        /// What to replace
+        path: Pointer,
+        /// The new value
+        value: Value,
+    },
+    /// Removes a value and adds it elsewhere
+    Move {
+        /// What to move
+        from: Pointer,
+        /// Where to add it
+        path: Pointer,
+    },
+    /// Adds a copy of a value elsewhere
+    Copy {
+        /// What to copy
+        from: Pointer,
+        /// Where to add it
+        path: Pointer,
+    },
+    /// Checks that a value is equal to the given one
+    Test {
+        /// What to check
+        path: Pointer,
+        /// The expected value
+        value: Value,
+    },
+}
+
+impl Patch {
+    /// Returns a patch made of `operations`
+    pub fn new(operations: Vec<Operation>) -> Self {
+        Self { operations }
+    }
+
+    /// Returns the operations of the patch, in order
+    pub fn operations(&self) -> &[Operation] {
+        &self.operations
+    }
+
+    /// Reads a patch out of a JSON value: an array of operation objects
+    pub fn from_value(value: &Value) -> Result<Self, PatchError> {
+        let Value::Array(items) = value else {
+            return Err(invalid(None, "expected an array of operations"));
+        };
+        items
+            .iter()
+            .enumerate()
+            .map(|(index, item)| {
+                parse_operation(item).map_err(|message| invalid(Some(index), message))
+            })
+            .collect::<Result<_, _>>()
+            .map(Self::new)
+    }
+
+    /// Applies the operations to `value`, in order
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of the first operation that fails, in which case `value` is left
+    /// untouched.
+    pub fn apply<T: Facet>(&self, value: &mut T) -> Result<(), PatchError> {
+        Target::new(Opaque::from_ref(value), T::SHAPE).run(|target| {
+            for (index, operation) in self.operations.iter().enumerate() {
+                apply(target, operation).map_err(|err| PatchError {
+                    operation: Some(index),
+                    ..err
+                })?;
+            }
+            Ok(())
+        })
+    }
+}
+
+impl core::str::FromStr for Patch {
+    type Err = PatchError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let value = s
+            .parse()
+            .map_err(|err| PatchError::new(Pointer::root(), PatchErrorKind::Syntax(err)))?;
+        Self::from_value(&value)
+    }
+}
+
+fn invalid(operation: Option<usize>, message: impl Into<String>) -> PatchError {
+    PatchError {
+        operation,
+        pointer: Pointer::root(),
+        kind: PatchErrorKind::Invalid(message.into()),
+    }
+}
+
+fn parse_operation(item: &Value) -> Result<Operation, String> {
+    let Value::Object(_) = item else {
+        return Err("expected an operation object".into());
+    };
+    let pointer = |member: &str| match item.get(member) {
+        Some(Value::String(s)) => s
+            .parse::<Pointer>()
+            .map_err(|err| format!("`{member}` isn't a JSON Pointer: {err}")),
+        Some(_) => Err(format!("`{member}` must be a string")),
+        None => Err(format!("missing `{member}`")),
+    };
+    let value = || item.get("value").cloned().ok_or("missing `value`");
+    Ok(match item.get("op") {
+        Some(Value::String(op)) => match op.as_str() {
+            "add" => Operation::Add {
+                path: pointer("path")?,
+                value: value()?,
+            },
+            "remove" => Operation::Remove {
+                path: pointer("path")?,
+            },
+            "replace" => Operation::Replace {
+                path: pointer("path")?,
+                value: value()?,
+            },
+            "move" => Operation::Move {
+                from: pointer("from")?,
+                path: pointer("path")?,
+            },
+            "copy" => Operation::Copy {
+                from: pointer("from")?,
+                path: pointer("path")?,
+            },
+            "test" => Operation::Test {
+                path: pointer("path")?,
+                value: value()?,
+            },
+            op => return Err(format!("unknown operation `{op}`")),
+        },
+        Some(_) => return Err("`op` must be a string".into()),
+        None => return Err("missing `op`".into()),
+    })
+}
+
+fn apply(target: &mut Target<'_>, operation: &Operation) -> Result<(), PatchError> {
+    let at = |pointer: &Pointer| {
+        let pointer = pointer.clone();
+        move |kind| PatchError::new(pointer, kind)
+    };
+    match operation {
+        Operation::Add { path, value } => target.add(path.tokens(), value).map_err(at(path)),
+        Operation::Remove { path } => target.remove(path.tokens()).map_err(at(path)),
+        Operation::Replace { path, value } => {
+            target.replace(path.tokens(), value).map_err(at(path))
+        }
+        Operation::Move { from, path } => {
+            if path.is_inside(from) {
+                return Err(PatchError::new(
+                    path.clone(),
+                    PatchErrorKind::Invalid("a value can't be moved into itself".into()),
+                ));
+            }
+            if path == from {
+                // still fails if there's nothing to move
+                return target.read(from.tokens()).map(drop).map_err(at(from));
+            }
+            let value = target.read(from.tokens()).map_err(at(from))?;
+            target.remove(from.tokens()).map_err(at(from))?;
+            target.add(path.tokens(), &value).map_err(at(path))
+        }
+        Operation::Copy { from, path } => {
+            let value = target.read(from.tokens()).map_err(at(from))?;
+            target.add(path.tokens(), &value).map_err(at(path))
+        }
+        Operation::Test { path, value } => {
+            let actual = target.read(path.tokens()).map_err(at(path))?;
+            if actual.json_eq(value) {
+                Ok(())
+            } else {
+                Err(PatchError::new(
+                    path.clone(),
+                    PatchErrorKind::TestFailed { actual },
+                ))
+            }
+        }
+    }
+}
@@ -0,0 +1,136 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+
+/// A JSON Pointer, as defined by [RFC 6901](https://www.rfc-editor.org/rfc/rfc6901)
+///
+/// A pointer is a list of reference tokens, written with a leading `/` each: `""` points
+/// to the whole value, `/users/0/name` to the name of its first user. In tokens, `~1`
+/// stands for `/` and `~0` for `~`.
+///
+/// Against a typed value, tokens name struct fields, list indexes and map keys. An enum
+/// is entered through the name of its current variant, like in its JSON form, while
+/// options and single-field tuple structs are transparent.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Pointer {
+    tokens: Vec<String>,
+}
+
+impl Pointer {
+    /// Returns the pointer to the whole value
+    pub fn root() -> Self {
+        Self::default()
+    }
+
+    /// Returns the unescaped reference tokens
+    pub fn tokens(&self) -> &[String] {
+        &self.tokens
+    }
+
+    /// Returns true if this pointer is `prefix` followed by at least one more token
+    pub fn is_inside(&self, prefix: &Pointer) -> bool {
+        self.tokens.len() > prefix.tokens.len() && self.tokens.starts_with(&prefix.tokens)
+    }
+}
+
+impl From<Vec<String>> for Pointer {
+    fn from(tokens: Vec<String>) -> Self {
+        Self { tokens }
+    }
+}
+
+impl fmt::Display for Pointer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for token in &self.tokens {
+            f.write_str("/")?;
+            for c in token.chars() {
+                match c {
+                    '~' => f.write_str("~0")?,
+                    '/' => f.write_str("~1")?,
+                    c => write!(f, "{c}")?,
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Error returned when a string isn't a JSON Pointer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PointerParseError {
+    /// Byte offset of the problem in the input
+    pub position: usize,
+    /// What was expected there
+    pub message: &'static str,
+}
+
+impl fmt::Display for PointerParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at byte {}", self.message, self.position)
+    }
+}
+
+impl core::error::Error for PointerParseError {}
+
+impl core::str::FromStr for Pointer {
+    type Err = PointerParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.is_empty() {
+            return Ok(Self::root());
+        }
+        if !s.starts_with('/') {
+            return Err(PointerParseError {
+                position: 0,
+                message: "expected `/`",
+            });
+        }
+
+        let mut tokens = Vec::new();
+        let mut pos = 1;
+        for raw in s[1..].split('/') {
+            let mut token = String::with_capacity(raw.len());
+            let mut chars = raw.char_indices();
+            while let Some((i, c)) = chars.next() {
+                if c != '~' {
+                    token.push(c);
+                    continue;
+                }
+                match chars.next() {
+                    Some((_, '0')) => token.push('~'),
+                    Some((_, '1')) => token.push('/'),
+                    _ => {
+                        return Err(PointerParseError {
+                            position: pos + i,
+                            message: "expected `0` or `1` after `~`",
+                        });
+                    }
+                }
+            }
+            tokens.push(token);
+            pos += raw.len() + 1;
+        }
+        Ok(Self { tokens })
+    }
+}
+
+/// Where an array index token points
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Index {
+    /// An existing item, or the end of the list when adding
+    At(usize),
+    /// `-`, past the last item
+    End,
+}
+
+/// Reads an array index token: digits without leading zeros, or `-`
+pub(crate) fn parse_index(token: &str) -> Option<Index> {
+    if token == "-" {
+        return Some(Index::End);
+    }
+    let digits = token.bytes().all(|b| b.is_ascii_digit());
+    if !digits || token.is_empty() || (token.len() > 1 && token.starts_with('0')) {
+        return None;
+    }
+    token.parse().ok().map(Index::At)
+}
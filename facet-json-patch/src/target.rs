@@ -0,0 +1,555 @@
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use facet_poke::{Peek, Scratch, read_variant_index};
+use facet_serialize::{deserialize_opaque, serialize};
+use facet_trait::{
+    Def, Field, FieldFlags, ListDef, MapDef, Opaque, Shape, StructKind, VariantKind,
+};
+
+use crate::PatchErrorKind;
+use crate::pointer::{Index, parse_index};
+use crate::value::{Builder, Events, Value};
+
+/// The value being patched, along with what it takes to undo the changes made so far
+///
+/// Changes are made in place, one at a time, and logged: if an operation fails, the log
+/// is replayed backwards, so a patch is applied either entirely or not at all. The log
+/// keeps the tokens leading to each change rather than pointers, since later changes
+/// may move things around, e.g. by growing a list.
+pub(crate) struct Target<'mem> {
+    root: Opaque<'mem>,
+    shape: &'static Shape,
+    log: Vec<Undo>,
+}
+
+/// A value, and where it is
+#[derive(Clone, Copy)]
+struct Place<'mem> {
+    data: Opaque<'mem>,
+    shape: &'static Shape,
+}
+
+/// What a pointer can lead to
+#[derive(Clone, Copy)]
+enum Node<'mem> {
+    Value(Place<'mem>),
+    /// The fields of an enum variant with more than one, which only exist one by one
+    Fields {
+        base: Opaque<'mem>,
+        fields: &'static [Field],
+    },
+}
+
+/// What holds the value a pointer's last token names
+enum Container<'mem> {
+    List(Place<'mem>, ListDef),
+    Map(Place<'mem>, MapDef),
+    /// A struct or a variant, whose fields are always there
+    Fields,
+}
+
+enum Undo {
+    Replaced {
+        at: Vec<String>,
+        old: Scratch,
+    },
+    InsertedItem {
+        list: Vec<String>,
+        index: usize,
+    },
+    RemovedItem {
+        list: Vec<String>,
+        index: usize,
+        item: Scratch,
+    },
+    InsertedEntry {
+        map: Vec<String>,
+        key: String,
+    },
+    RemovedEntry {
+        map: Vec<String>,
+        key: String,
+        value: Scratch,
+    },
+}
+
+/// Builds a value of `shape` out of `value`, or describes why it doesn't fit
+fn build(shape: &'static Shape, value: &Value) -> Result<Scratch, PatchErrorKind> {
+    let (poke, mut scratch) = Scratch::alloc(shape);
+    deserialize_opaque(poke, &mut Events::new(value))
+        .map_err(|err| PatchErrorKind::Mismatch(err.to_string()))?;
+    unsafe { scratch.mark_initialized() };
+    Ok(scratch)
+}
+
+/// Exchanges the bytes of two values of the same shape
+///
+/// # Safety
+///
+/// Both values must be of `shape`, and not overlap.
+unsafe fn swap(a: Opaque<'_>, b: Opaque<'_>, shape: &'static Shape) {
+    unsafe {
+        core::ptr::swap_nonoverlapping(
+            a.as_mut_byte_ptr(),
+            b.as_mut_byte_ptr(),
+            shape.layout.size(),
+        )
+    };
+}
+
+/// Finds the field named `token`, looking into flattened fields
+fn field<'mem>(base: Opaque<'mem>, fields: &'static [Field], token: &str) -> Option<Place<'mem>> {
+    fields.iter().find_map(|field| {
+        let data = unsafe { Opaque::new_unchecked(base.as_mut_byte_ptr().add(field.offset)) };
+        match field.shape.def {
+            Def::Struct(def) if field.flags.contains(FieldFlags::FLATTEN) => {
+                self::field(data, def.fields, token)
+            }
+            _ if field.name == token => Some(Place {
+                data,
+                shape: field.shape,
+            }),
+            _ => None,
+        }
+    })
+}
+
+/// Goes through options and single-field tuple structs, which have no token of their own;
+/// returns `None` for an option that holds nothing
+fn unwrap(mut place: Place<'_>) -> Option<Place<'_>> {
+    loop {
+        place = match place.shape.def {
+            Def::Option(def) => {
+                let value = unsafe { (def.vtable.get_value)(place.data.as_const()) }?;
+                Place {
+                    data: unsafe { Opaque::new_unchecked(value.as_byte_ptr() as *mut u8) },
+                    shape: def.t,
+                }
+            }
+            Def::Struct(def) if def.kind == StructKind::TupleStruct && def.fields.len() == 1 => {
+                Place {
+                    data: unsafe {
+                        Opaque::new_unchecked(
+                            place.data.as_mut_byte_ptr().add(def.fields[0].offset),
+                        )
+                    },
+                    shape: def.fields[0].shape,
+                }
+            }
+            _ => return Some(place),
+        }
+    }
+}
+
+fn list_len(place: Place<'_>, def: &ListDef) -> usize {
+    unsafe { (def.vtable.len)(place.data.as_const()) }
+}
+
+impl<'mem> Target<'mem> {
+    pub(crate) fn new(root: Opaque<'mem>, shape: &'static Shape) -> Self {
+        Self {
+            root,
+            shape,
+            log: Vec::new(),
+        }
+    }
+
+    /// Runs `patch` against the value, undoing all its changes if it fails
+    pub(crate) fn run<E>(
+        mut self,
+        patch: impl FnOnce(&mut Self) -> Result<(), E>,
+    ) -> Result<(), E> {
+        let result = patch(&mut self);
+        if result.is_err() {
+            self.rollback();
+        }
+        result
+    }
+
+    fn node(&self, tokens: &[String]) -> Result<Node<'mem>, PatchErrorKind> {
+        let root = Node::Value(Place {
+            data: self.root,
+            shape: self.shape,
+        });
+        tokens
+            .iter()
+            .try_fold(root, |node, token| self.child(node, token))
+    }
+
+    fn child(&self, node: Node<'mem>, token: &str) -> Result<Node<'mem>, PatchErrorKind> {
+        let place = match node {
+            Node::Fields { base, fields } => {
+                return field(base, fields, token)
+                    .map(Node::Value)
+                    .ok_or(PatchErrorKind::NotFound);
+            }
+            Node::Value(place) => unwrap(place).ok_or(PatchErrorKind::NotFound)?,
+        };
+        let at = |offset: usize| unsafe {
+            Opaque::new_unchecked(place.data.as_mut_byte_ptr().add(offset))
+        };
+        match place.shape.def {
+            Def::Struct(def) => field(place.data, def.fields, token)
+                .map(Node::Value)
+                .ok_or(PatchErrorKind::NotFound),
+            Def::Enum(def) => {
+                let index = unsafe { read_variant_index(place.data.as_const(), &def) }
+                    .ok_or(PatchErrorKind::Unsupported("the enum's variant is unknown"))?;
+                let variant = &def.variants[index];
+                if variant.name != token {
+                    return Err(PatchErrorKind::NotFound);
+                }
+                match variant.kind {
+                    VariantKind::Unit => Err(PatchErrorKind::NotFound),
+                    VariantKind::Tuple { fields } if fields.len() == 1 => Ok(Node::Value(Place {
+                        data: at(fields[0].offset),
+                        shape: fields[0].shape,
+                    })),
+                    VariantKind::Tuple { fields } | VariantKind::Struct { fields } => {
+                        Ok(Node::Fields {
+                            base: place.data,
+                            fields,
+                        })
+                    }
+                    _ => Err(PatchErrorKind::Unsupported("unknown kind of variant")),
+                }
+            }
+            Def::List(def) => {
+                let index = match parse_index(token) {
+                    Some(Index::At(index)) if index < list_len(place, &def) => index,
+                    Some(_) => return Err(PatchErrorKind::NotFound),
+                    None => return Err(PatchErrorKind::InvalidIndex(token.into())),
+                };
+                let get_item_ptr_mut = def
+                    .vtable
+                    .get_item_ptr_mut
+                    .ok_or(PatchErrorKind::Unsupported("the list can't be modified"))?;
+                Ok(Node::Value(Place {
+                    data: unsafe { get_item_ptr_mut(place.data, index) },
+                    shape: def.t,
+                }))
+            }
+            Def::Map(def) => {
+                let key = map_key(&def, token)?;
+                let get_value_ptr_mut = def
+                    .vtable
+                    .get_value_ptr_mut_fn
+                    .ok_or(PatchErrorKind::Unsupported("the map can't be modified"))?;
+                let value = unsafe { get_value_ptr_mut(place.data, key.get()) }
+                    .ok_or(PatchErrorKind::NotFound)?;
+                Ok(Node::Value(Place {
+                    data: value,
+                    shape: def.v,
+                }))
+            }
+            _ => Err(PatchErrorKind::NotFound),
+        }
+    }
+
+    fn place(&self, tokens: &[String]) -> Result<Place<'mem>, PatchErrorKind> {
+        match self.node(tokens)? {
+            Node::Value(place) => Ok(place),
+            Node::Fields { .. } => Err(PatchErrorKind::Unsupported(
+                "the fields of this variant can only be patched one by one",
+            )),
+        }
+    }
+
+    fn container(&self, tokens: &[String]) -> Result<Container<'mem>, PatchErrorKind> {
+        let place = match self.node(tokens)? {
+            Node::Value(place) => unwrap(place).ok_or(PatchErrorKind::NotFound)?,
+            Node::Fields { .. } => return Ok(Container::Fields),
+        };
+        Ok(match place.shape.def {
+            Def::List(def) => Container::List(place, def),
+            Def::Map(def) => Container::Map(place, def),
+            _ => Container::Fields,
+        })
+    }
+
+    /// Returns true if something is at `tokens`
+    pub(crate) fn exists(&self, tokens: &[String]) -> bool {
+        self.node(tokens).is_ok()
+    }
+
+    /// Returns true if `tokens` points to an option
+    pub(crate) fn is_option(&self, tokens: &[String]) -> Result<bool, PatchErrorKind> {
+        Ok(matches!(self.place(tokens)?.shape.def, Def::Option(_)))
+    }
+
+    /// Returns true if `tokens` points to an option that holds nothing
+    pub(crate) fn is_none(&self, tokens: &[String]) -> Result<bool, PatchErrorKind> {
+        let place = self.place(tokens)?;
+        Ok(match place.shape.def {
+            Def::Option(def) => !unsafe { (def.vtable.is_some)(place.data.as_const()) },
+            _ => false,
+        })
+    }
+
+    /// Returns what a merge patch sees at `tokens`: the fields of a struct, the entries
+    /// of a map, the name of an enum's variant when it has fields, or a plain value
+    pub(crate) fn merge_kind(&self, tokens: &[String]) -> Result<MergeKind, PatchErrorKind> {
+        let place = match self.node(tokens)? {
+            Node::Value(place) => match unwrap(place) {
+                Some(place) => place,
+                None => return Ok(MergeKind::Value),
+            },
+            Node::Fields { .. } => return Ok(MergeKind::Fields),
+        };
+        Ok(match place.shape.def {
+            Def::Struct(def) if def.kind == StructKind::Struct => MergeKind::Fields,
+            Def::Map(_) => MergeKind::Entries,
+            Def::Enum(def) => match unsafe { read_variant_index(place.data.as_const(), &def) } {
+                Some(index) if !matches!(def.variants[index].kind, VariantKind::Unit) => {
+                    MergeKind::Variant(def.variants[index].name)
+                }
+                _ => MergeKind::Value,
+            },
+            _ => MergeKind::Value,
+        })
+    }
+
+    /// Returns the value at `tokens`, as JSON
+    pub(crate) fn read(&self, tokens: &[String]) -> Result<Value, PatchErrorKind> {
+        let place = self.place(tokens)?;
+        let peek = unsafe { Peek::unchecked_new(place.data.as_const(), place.shape) };
+        let mut builder = Builder::default();
+        serialize(peek, &mut builder)
+            .map_err(|_| PatchErrorKind::Unsupported("the value can't be read as JSON"))?;
+        Ok(builder.finish())
+    }
+
+    /// Replaces the value at `tokens` with `value`
+    pub(crate) fn replace(
+        &mut self,
+        tokens: &[String],
+        value: &Value,
+    ) -> Result<(), PatchErrorKind> {
+        let place = self.place(tokens)?;
+        let mut new = build(place.shape, value)?;
+        unsafe { swap(place.data, new.get_mut(), place.shape) };
+        self.log.push(Undo::Replaced {
+            at: tokens.to_vec(),
+            old: new,
+        });
+        Ok(())
+    }
+
+    /// Inserts `value` into a list or a map, or replaces a field, as `add` does
+    pub(crate) fn add(&mut self, tokens: &[String], value: &Value) -> Result<(), PatchErrorKind> {
+        let Some((last, parent)) = tokens.split_last() else {
+            return self.replace(tokens, value);
+        };
+        match self.container(parent)? {
+            Container::List(place, def) => {
+                let len = list_len(place, &def);
+                let index = match parse_index(last) {
+                    Some(Index::End) => len,
+                    Some(Index::At(index)) if index <= len => index,
+                    Some(Index::At(_)) => return Err(PatchErrorKind::NotFound),
+                    None => return Err(PatchErrorKind::InvalidIndex(last.clone())),
+                };
+                let item = build(def.t, value)?;
+                insert_item(place, &def, index, item)?;
+                self.log.push(Undo::InsertedItem {
+                    list: parent.to_vec(),
+                    index,
+                });
+                Ok(())
+            }
+            Container::Map(place, def) => {
+                let mut key = map_key(&def, last)?;
+                if unsafe { (def.vtable.contains_key_fn)(place.data.as_const(), key.get()) } {
+                    return self.replace(tokens, value);
+                }
+                if def.vtable.remove_fn.is_none() {
+                    return Err(PatchErrorKind::Unsupported(
+                        "entries can't be removed from the map",
+                    ));
+                }
+                let mut value = build(def.v, value)?;
+                unsafe { (def.vtable.insert_fn)(place.data, key.take(), value.take()) };
+                self.log.push(Undo::InsertedEntry {
+                    map: parent.to_vec(),
+                    key: last.clone(),
+                });
+                Ok(())
+            }
+            Container::Fields => self.replace(tokens, value),
+        }
+    }
+
+    /// Removes the value at `tokens` from its list or map, or sets an optional field to
+    /// `None`
+    pub(crate) fn remove(&mut self, tokens: &[String]) -> Result<(), PatchErrorKind> {
+        let Some((last, parent)) = tokens.split_last() else {
+            return Err(PatchErrorKind::Unsupported(
+                "the whole value can't be removed",
+            ));
+        };
+        match self.container(parent)? {
+            Container::List(place, def) => {
+                let index = match parse_index(last) {
+                    Some(Index::At(index)) if index < list_len(place, &def) => index,
+                    Some(_) => return Err(PatchErrorKind::NotFound),
+                    None => return Err(PatchErrorKind::InvalidIndex(last.clone())),
+                };
+                let item = remove_item(place, &def, index)?;
+                self.log.push(Undo::RemovedItem {
+                    list: parent.to_vec(),
+                    index,
+                    item,
+                });
+                Ok(())
+            }
+            Container::Map(place, def) => {
+                let key = map_key(&def, last)?;
+                let value = remove_entry(place, &def, &key)?.ok_or(PatchErrorKind::NotFound)?;
+                self.log.push(Undo::RemovedEntry {
+                    map: parent.to_vec(),
+                    key: last.clone(),
+                    value,
+                });
+                Ok(())
+            }
+            Container::Fields => {
+                let place = self.place(tokens)?;
+                let Def::Option(def) = place.shape.def else {
+                    return Err(PatchErrorKind::Unsupported(
+                        "only optional fields can be removed",
+                    ));
+                };
+                if !unsafe { (def.vtable.is_some)(place.data.as_const()) } {
+                    return Err(PatchErrorKind::NotFound);
+                }
+                let mut none = Scratch::new(place.shape);
+                unsafe {
+                    (def.vtable.init_none)(none.as_uninit());
+                    none.mark_initialized();
+                    swap(place.data, none.get_mut(), place.shape);
+                }
+                self.log.push(Undo::Replaced {
+                    at: tokens.to_vec(),
+                    old: none,
+                });
+                Ok(())
+            }
+        }
+    }
+
+    /// Undoes every logged change, most recent first
+    fn rollback(&mut self) {
+        const UNDO: &str = "undoing a change finds what the change left";
+        for undo in core::mem::take(&mut self.log).into_iter().rev() {
+            match undo {
+                Undo::Replaced { at, mut old } => {
+                    let place = self.place(&at).expect(UNDO);
+                    unsafe { swap(place.data, old.get_mut(), place.shape) };
+                }
+                Undo::InsertedItem { list, index } => {
+                    let Ok(Container::List(place, def)) = self.container(&list) else {
+                        unreachable!("{UNDO}");
+                    };
+                    drop(remove_item(place, &def, index).expect(UNDO));
+                }
+                Undo::RemovedItem { list, index, item } => {
+                    let Ok(Container::List(place, def)) = self.container(&list) else {
+                        unreachable!("{UNDO}");
+                    };
+                    insert_item(place, &def, index, item).expect(UNDO);
+                }
+                Undo::InsertedEntry { map, key } => {
+                    let Ok(Container::Map(place, def)) = self.container(&map) else {
+                        unreachable!("{UNDO}");
+                    };
+                    let key = map_key(&def, &key).expect(UNDO);
+                    drop(remove_entry(place, &def, &key).expect(UNDO));
+                }
+                Undo::RemovedEntry {
+                    map,
+                    key,
+                    mut value,
+                } => {
+                    let Ok(Container::Map(place, def)) = self.container(&map) else {
+                        unreachable!("{UNDO}");
+                    };
+                    let mut key = map_key(&def, &key).expect(UNDO);
+                    unsafe { (def.vtable.insert_fn)(place.data, key.take(), value.take()) };
+                }
+            }
+        }
+    }
+}
+
+/// How a merge patch applies to a value
+pub(crate) enum MergeKind {
+    /// Members name fields
+    Fields,
+    /// Members name map keys
+    Entries,
+    /// A member named after the current variant patches its fields
+    Variant(&'static str),
+    /// The value is replaced
+    Value,
+}
+
+/// Builds a map key out of a token, the way map keys are deserialized
+fn map_key(def: &MapDef, token: &str) -> Result<Scratch, PatchErrorKind> {
+    build(def.k, &Value::String(token.into()))
+}
+
+/// Moves `item` to `index` in the list, by pushing it then swapping it into place
+fn insert_item(
+    place: Place<'_>,
+    def: &ListDef,
+    index: usize,
+    mut item: Scratch,
+) -> Result<(), PatchErrorKind> {
+    let (Some(get_item_ptr_mut), Some(_)) = (def.vtable.get_item_ptr_mut, def.vtable.remove_at)
+    else {
+        return Err(PatchErrorKind::Unsupported(
+            "items can't be added to the list",
+        ));
+    };
+    unsafe { (def.vtable.push)(place.data, item.take()) };
+    for i in (index + 1..list_len(place, def)).rev() {
+        unsafe {
+            swap(
+                get_item_ptr_mut(place.data, i - 1),
+                get_item_ptr_mut(place.data, i),
+                def.t,
+            )
+        };
+    }
+    Ok(())
+}
+
+fn remove_item(place: Place<'_>, def: &ListDef, index: usize) -> Result<Scratch, PatchErrorKind> {
+    let remove_at = def.vtable.remove_at.ok_or(PatchErrorKind::Unsupported(
+        "items can't be removed from the list",
+    ))?;
+    let mut item = Scratch::new(def.t);
+    unsafe {
+        remove_at(place.data, index, item.as_uninit());
+        item.mark_initialized();
+    }
+    Ok(item)
+}
+
+/// Removes the entry for `key`, returning its value if there was one
+fn remove_entry(
+    place: Place<'_>,
+    def: &MapDef,
+    key: &Scratch,
+) -> Result<Option<Scratch>, PatchErrorKind> {
+    let remove_fn = def.vtable.remove_fn.ok_or(PatchErrorKind::Unsupported(
+        "entries can't be removed from the map",
+    ))?;
+    let mut value = Scratch::new(def.v);
+    if unsafe { remove_fn(place.data, key.get(), value.as_uninit()) }.is_none() {
+        return Ok(None);
+    }
+    unsafe { value.mark_initialized() };
+    Ok(Some(value))
+}
@@ -0,0 +1,427 @@
+use alloc::borrow::Cow;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::convert::Infallible;
+use core::fmt::{self, Write};
+
+use facet_json_read::{JsonParseError, JsonParseErrorKind, JsonParser};
+use facet_serialize::{Deserializer, Event, Serializer};
+
+/// A JSON value, as found in patch documents
+///
+/// Objects keep their members in document order.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    /// `null`
+    Null,
+    /// `true` or `false`
+    Bool(bool),
+    /// A non-negative integer
+    U64(u64),
+    /// A negative integer
+    I64(i64),
+    /// A number with a fraction or an exponent, or an integer too large for the above
+    F64(f64),
+    /// A string
+    String(String),
+    /// An array
+    Array(Vec<Value>),
+    /// An object
+    Object(Vec<(String, Value)>),
+}
+
+impl Value {
+    /// Returns the value of the object member named `key`, if this is an object that has one
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        match self {
+            Value::Object(members) => members
+                .iter()
+                .rev()
+                .find(|(name, _)| name == key)
+                .map(|(_, value)| value),
+            _ => None,
+        }
+    }
+
+    /// Compares two values like JSON does: numbers are equal if they have the same
+    /// numeric value, whatever their representation, and objects are equal if they have
+    /// the same members, in any order
+    pub fn json_eq(&self, other: &Value) -> bool {
+        match (self, other) {
+            (Value::Array(a), Value::Array(b)) => {
+                a.len() == b.len() && a.iter().zip(b).all(|(a, b)| a.json_eq(b))
+            }
+            (Value::Object(a), Value::Object(b)) => {
+                a.len() == b.len()
+                    && a.iter()
+                        .all(|(key, a)| other.get(key).is_some_and(|b| a.json_eq(b)))
+            }
+            (a, b) => match (a.as_f64(), b.as_f64()) {
+                (Some(a), Some(b)) => match (self, other) {
+                    (Value::F64(_), _) | (_, Value::F64(_)) => a == b,
+                    _ => self == other,
+                },
+                _ => self == other,
+            },
+        }
+    }
+
+    fn as_f64(&self) -> Option<f64> {
+        match *self {
+            Value::U64(n) => Some(n as f64),
+            Value::I64(n) => Some(n as f64),
+            Value::F64(n) => Some(n),
+            _ => None,
+        }
+    }
+
+    /// Returns a copy of this value without the members of its objects that are `null`,
+    /// at any depth, which is how merge patches describe new values
+    pub(crate) fn without_nulls(&self) -> Value {
+        match self {
+            Value::Array(items) => Value::Array(items.iter().map(Value::without_nulls).collect()),
+            Value::Object(members) => Value::Object(
+                members
+                    .iter()
+                    .filter(|(_, value)| *value != Value::Null)
+                    .map(|(key, value)| (key.clone(), value.without_nulls()))
+                    .collect(),
+            ),
+            value => value.clone(),
+        }
+    }
+}
+
+/// Writes the value as compact JSON
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Null => f.write_str("null"),
+            Value::Bool(b) => write!(f, "{b}"),
+            Value::U64(n) => write!(f, "{n}"),
+            Value::I64(n) => write!(f, "{n}"),
+            Value::F64(n) => write!(f, "{n:?}"),
+            Value::String(s) => write_string(f, s),
+            Value::Array(items) => {
+                f.write_char('[')?;
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        f.write_char(',')?;
+                    }
+                    write!(f, "{item}")?;
+                }
+                f.write_char(']')
+            }
+            Value::Object(members) => {
+                f.write_char('{')?;
+                for (i, (key, value)) in members.iter().enumerate() {
+                    if i > 0 {
+                        f.write_char(',')?;
+                    }
+                    write_string(f, key)?;
+                    write!(f, ":{value}")?;
+                }
+                f.write_char('}')
+            }
+        }
+    }
+}
+
+fn write_string(f: &mut fmt::Formatter<'_>, s: &str) -> fmt::Result {
+    f.write_char('"')?;
+    for c in s.chars() {
+        match c {
+            '"' => f.write_str("\\\"")?,
+            '\\' => f.write_str("\\\\")?,
+            '\n' => f.write_str("\\n")?,
+            '\r' => f.write_str("\\r")?,
+            '\t' => f.write_str("\\t")?,
+            c if (c as u32) < 0x20 => write!(f, "\\u{:04x}", c as u32)?,
+            c => f.write_char(c)?,
+        }
+    }
+    f.write_char('"')
+}
+
+/// Error returned when a string isn't valid JSON
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SyntaxError {
+    /// Byte offset of the problem in the input
+    pub position: usize,
+    /// What was expected there
+    pub message: &'static str,
+}
+
+impl fmt::Display for SyntaxError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at byte {}", self.message, self.position)
+    }
+}
+
+impl core::error::Error for SyntaxError {}
+
+impl core::str::FromStr for Value {
+    type Err = SyntaxError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = JsonParser::new(s);
+        let mut builder = Builder::default();
+        let mut depth = 0usize;
+        loop {
+            let event = parser.next().map_err(syntax_error)?;
+            let Ok(()) = match event {
+                Event::Unit => builder.serialize_unit(),
+                Event::Bool(b) => builder.serialize_bool(b),
+                Event::U64(n) => builder.serialize_u64(n),
+                Event::I64(n) => builder.serialize_i64(n),
+                Event::F64(n) => builder.serialize_f64(n),
+                // integers too wide for 64 bits come as their digits, string literals
+                // end with their closing quote
+                Event::Str(text) if !s[..parser.position].ends_with('"') => {
+                    builder.serialize_f64(text.parse().expect("digits read as a float"))
+                }
+                Event::Str(text) => builder.serialize_str(&text),
+                Event::StartSeq => {
+                    depth += 1;
+                    builder.start_seq(0)
+                }
+                Event::StartMap => {
+                    depth += 1;
+                    builder.start_map(0)
+                }
+                Event::Key(key) => builder.map_key(&key),
+                Event::EndSeq => {
+                    depth -= 1;
+                    builder.end_seq()
+                }
+                Event::EndMap => {
+                    depth -= 1;
+                    builder.end_map()
+                }
+            };
+            if depth == 0 {
+                break;
+            }
+        }
+        parser.skip_whitespace();
+        if parser.position != s.len() {
+            return Err(SyntaxError {
+                position: parser.position,
+                message: "expected the end of the input",
+            });
+        }
+        Ok(builder.finish())
+    }
+}
+
+fn syntax_error(err: JsonParseError) -> SyntaxError {
+    let message = match err.kind {
+        JsonParseErrorKind::ExpectedOpeningQuote => "expected a key",
+        JsonParseErrorKind::UnterminatedString => "unterminated string",
+        JsonParseErrorKind::InvalidEscapeSequence(_) => "invalid escape sequence",
+        JsonParseErrorKind::IncompleteUnicodeEscape => "incomplete unicode escape",
+        JsonParseErrorKind::InvalidUnicodeEscape => "invalid unicode escape",
+        JsonParseErrorKind::ExpectedNumber | JsonParseErrorKind::InvalidNumberFormat => {
+            "expected a digit"
+        }
+        JsonParseErrorKind::ExpectedColon => "expected `:`",
+        JsonParseErrorKind::ExpectedClosingBrace => "expected `,` or `}`",
+        JsonParseErrorKind::ExpectedClosingBracket => "expected `,` or `]`",
+        JsonParseErrorKind::UnexpectedEndOfInput => "unexpected end of input",
+        _ => "expected a value",
+    };
+    SyntaxError {
+        position: err.position,
+        message,
+    }
+}
+
+/// Reads a [`Value`] as a stream of events, to deserialize it into a typed value
+pub(crate) struct Events<'v> {
+    pending: Vec<Pending<'v>>,
+}
+
+enum Pending<'v> {
+    Value(&'v Value),
+    Items(core::slice::Iter<'v, Value>),
+    Members(core::slice::Iter<'v, (String, Value)>),
+}
+
+impl<'v> Events<'v> {
+    pub(crate) fn new(value: &'v Value) -> Self {
+        Self {
+            pending: Vec::from([Pending::Value(value)]),
+        }
+    }
+}
+
+/// Returned by [`Events`] when asked for more than its value
+#[derive(Debug)]
+pub(crate) struct EndOfValue;
+
+impl fmt::Display for EndOfValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Unexpected end of value")
+    }
+}
+
+impl<'v> Deserializer<'v> for Events<'v> {
+    type Error = EndOfValue;
+
+    fn next(&mut self) -> Result<Event<'v>, Self::Error> {
+        loop {
+            match self.pending.pop().ok_or(EndOfValue)? {
+                Pending::Value(value) => {
+                    return Ok(match value {
+                        Value::Null => Event::Unit,
+                        Value::Bool(b) => Event::Bool(*b),
+                        Value::U64(n) => Event::U64(*n),
+                        Value::I64(n) => Event::I64(*n),
+                        Value::F64(n) => Event::F64(*n),
+                        Value::String(s) => Event::Str(Cow::Borrowed(s)),
+                        Value::Array(items) => {
+                            self.pending.push(Pending::Items(items.iter()));
+                            Event::StartSeq
+                        }
+                        Value::Object(members) => {
+                            self.pending.push(Pending::Members(members.iter()));
+                            Event::StartMap
+                        }
+                    });
+                }
+                Pending::Items(mut items) => match items.next() {
+                    Some(item) => {
+                        self.pending.push(Pending::Items(items));
+                        self.pending.push(Pending::Value(item));
+                    }
+                    None => return Ok(Event::EndSeq),
+                },
+                Pending::Members(mut members) => match members.next() {
+                    Some((key, value)) => {
+                        self.pending.push(Pending::Members(members));
+                        self.pending.push(Pending::Value(value));
+                        return Ok(Event::Key(Cow::Borrowed(key)));
+                    }
+                    None => return Ok(Event::EndMap),
+                },
+            }
+        }
+    }
+}
+
+/// Builds a [`Value`] out of a typed value being serialized
+#[derive(Default)]
+pub(crate) struct Builder {
+    /// The arrays and objects being built, with the key of the next member of objects
+    open: Vec<(Value, Option<String>)>,
+    done: Option<Value>,
+}
+
+impl Builder {
+    pub(crate) fn finish(self) -> Value {
+        self.done.expect("the serializer emitted a whole value")
+    }
+
+    fn emit(&mut self, value: Value) {
+        match self.open.last_mut() {
+            Some((Value::Array(items), _)) => items.push(value),
+            Some((Value::Object(members), key)) => {
+                members.push((key.take().unwrap_or_default(), value));
+            }
+            _ => self.done = Some(value),
+        }
+    }
+
+    fn open(&mut self, value: Value) {
+        self.open.push((value, None));
+    }
+
+    fn close(&mut self) {
+        if let Some((value, _)) = self.open.pop() {
+            self.emit(value);
+        }
+    }
+
+    fn key(&mut self, key: &str) {
+        if let Some((_, next)) = self.open.last_mut() {
+            *next = Some(key.into());
+        }
+    }
+}
+
+impl Serializer for Builder {
+    type Error = Infallible;
+
+    fn serialize_bool(&mut self, value: bool) -> Result<(), Self::Error> {
+        self.emit(Value::Bool(value));
+        Ok(())
+    }
+
+    fn serialize_u64(&mut self, value: u64) -> Result<(), Self::Error> {
+        self.emit(Value::U64(value));
+        Ok(())
+    }
+
+    fn serialize_i64(&mut self, value: i64) -> Result<(), Self::Error> {
+        self.emit(match u64::try_from(value) {
+            Ok(value) => Value::U64(value),
+            Err(_) => Value::I64(value),
+        });
+        Ok(())
+    }
+
+    fn serialize_f64(&mut self, value: f64) -> Result<(), Self::Error> {
+        self.emit(Value::F64(value));
+        Ok(())
+    }
+
+    fn serialize_str(&mut self, value: &str) -> Result<(), Self::Error> {
+        self.emit(Value::String(value.into()));
+        Ok(())
+    }
+
+    fn serialize_unit(&mut self) -> Result<(), Self::Error> {
+        self.emit(Value::Null);
+        Ok(())
+    }
+
+    fn start_struct(&mut self, _len: usize) -> Result<(), Self::Error> {
+        self.open(Value::Object(Vec::new()));
+        Ok(())
+    }
+
+    fn field(&mut self, name: &'static str) -> Result<(), Self::Error> {
+        self.key(name);
+        Ok(())
+    }
+
+    fn end_struct(&mut self) -> Result<(), Self::Error> {
+        self.close();
+        Ok(())
+    }
+
+    fn start_seq(&mut self, _len: usize) -> Result<(), Self::Error> {
+        self.open(Value::Array(Vec::new()));
+        Ok(())
+    }
+
+    fn end_seq(&mut self) -> Result<(), Self::Error> {
+        self.close();
+        Ok(())
+    }
+
+    fn start_map(&mut self, _len: usize) -> Result<(), Self::Error> {
+        self.open(Value::Object(Vec::new()));
+        Ok(())
+    }
+
+    fn map_key(&mut self, key: &str) -> Result<(), Self::Error> {
+        self.key(key);
+        Ok(())
+    }
+
+    fn end_map(&mut self) -> Result<(), Self::Error> {
+        self.close();
+        Ok(())
+    }
+}
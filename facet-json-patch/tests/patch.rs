@@ -0,0 +1,237 @@
+use std::collections::HashMap;
+
+use facet_derive::Facet;
+use facet_json_patch::{PatchErrorKind, Pointer, Value, apply_merge_patch, apply_patch};
+use facet_testhelpers::Source;
+use facet_trait::Facet;
+
+use facet_trait as facet;
+
+#[derive(Facet, Debug, PartialEq)]
+struct Config {
+    name: String,
+    port: u16,
+    token: Option<String>,
+    tags: Vec<String>,
+    limits: HashMap<String, u32>,
+    sources: Vec<Source>,
+    retry: Option<Retry>,
+}
+
+#[derive(Facet, Debug, PartialEq)]
+struct Retry {
+    attempts: u32,
+    backoff: f64,
+}
+
+fn config() -> Config {
+    Config {
+        name: "web".to_string(),
+        port: 80,
+        token: None,
+        tags: vec!["a".to_string(), "b".to_string()],
+        limits: HashMap::from([("cpu".to_string(), 2)]),
+        sources: vec![
+            Source::Disabled,
+            Source::File("app.toml".to_string()),
+            Source::Remote {
+                url: "https://example.com".to_string(),
+                retries: 3,
+            },
+        ],
+        retry: Some(Retry {
+            attempts: 3,
+            backoff: 0.5,
+        }),
+    }
+}
+
+#[test]
+fn operations_follow_rfc_6902() {
+    let mut value = config();
+    apply_patch(
+        &mut value,
+        r#"[
+            { "op": "test", "path": "/name", "value": "web" },
+            { "op": "replace", "path": "/port", "value": 8080 },
+            { "op": "add", "path": "/token", "value": "hunter2" },
+            { "op": "add", "path": "/tags/1", "value": "between" },
+            { "op": "add", "path": "/tags/-", "value": "last" },
+            { "op": "remove", "path": "/tags/0" },
+            { "op": "add", "path": "/limits/memory", "value": 512 },
+            { "op": "move", "from": "/limits/cpu", "path": "/limits/threads" },
+            { "op": "copy", "from": "/tags/0", "path": "/name" },
+            { "op": "replace", "path": "/retry/attempts", "value": 5 },
+            { "op": "remove", "path": "/sources/0" },
+            { "op": "replace", "path": "/sources/0/File", "value": "other.toml" },
+            { "op": "replace", "path": "/sources/1/Remote/retries", "value": 1 }
+        ]"#,
+    )
+    .unwrap();
+
+    let mut expected = config();
+    expected.name = "between".to_string();
+    expected.port = 8080;
+    expected.token = Some("hunter2".to_string());
+    expected.tags = vec!["between".to_string(), "b".to_string(), "last".to_string()];
+    expected.limits = HashMap::from([("memory".to_string(), 512), ("threads".to_string(), 2)]);
+    expected.sources = vec![
+        Source::File("other.toml".to_string()),
+        Source::Remote {
+            url: "https://example.com".to_string(),
+            retries: 1,
+        },
+    ];
+    expected.retry.as_mut().unwrap().attempts = 5;
+    assert_eq!(value, expected);
+}
+
+#[test]
+fn merge_patches_follow_rfc_7386() {
+    let mut value = config();
+    value.token = Some("hunter2".to_string());
+    apply_merge_patch(
+        &mut value,
+        r#"{
+            "port": 443,
+            "token": null,
+            "tags": ["only"],
+            "limits": { "cpu": null, "memory": 256 },
+            "retry": { "backoff": 1.5 }
+        }"#,
+    )
+    .unwrap();
+
+    let mut expected = config();
+    expected.port = 443;
+    expected.tags = vec!["only".to_string()];
+    expected.limits = HashMap::from([("memory".to_string(), 256)]);
+    expected.retry.as_mut().unwrap().backoff = 1.5;
+    assert_eq!(value, expected);
+
+    // a missing optional struct is built from the patch
+    value.retry = None;
+    apply_merge_patch(
+        &mut value,
+        r#"{ "retry": { "attempts": 1, "backoff": 0.0 } }"#,
+    )
+    .unwrap();
+    assert_eq!(
+        value.retry,
+        Some(Retry {
+            attempts: 1,
+            backoff: 0.0
+        })
+    );
+}
+
+#[test]
+fn errors_name_the_failing_pointer() {
+    let cases = [
+        (
+            r#"[{ "op": "replace", "path": "/retry/tries", "value": 1 }]"#,
+            "/retry/tries",
+        ),
+        (r#"[{ "op": "remove", "path": "/tags/7" }]"#, "/tags/7"),
+        (
+            r#"[{ "op": "add", "path": "/tags/01", "value": "x" }]"#,
+            "/tags/01",
+        ),
+        (
+            r#"[{ "op": "replace", "path": "/port", "value": 70000 }]"#,
+            "/port",
+        ),
+        (
+            r#"[{ "op": "replace", "path": "/sources/2/File", "value": "x" }]"#,
+            "/sources/2/File",
+        ),
+        (r#"[{ "op": "remove", "path": "/name" }]"#, "/name"),
+    ];
+    for (patch, pointer) in cases {
+        let err = apply_patch(&mut config(), patch).unwrap_err();
+        assert_eq!(err.pointer.to_string(), pointer, "{err}");
+        assert_eq!(err.operation, Some(0));
+        assert!(err.to_string().contains(pointer), "{err}");
+    }
+
+    let err = apply_patch(
+        &mut config(),
+        r#"[{ "op": "replace", "path": "/port", "value": true }]"#,
+    )
+    .unwrap_err();
+    assert!(matches!(err.kind, PatchErrorKind::Mismatch(_)), "{err}");
+
+    let err = apply_merge_patch(&mut config(), r#"{ "limits": { "gpu": -1 } }"#).unwrap_err();
+    assert_eq!(err.pointer.to_string(), "/limits/gpu");
+    assert_eq!(err.operation, None);
+}
+
+#[test]
+fn failed_patches_leave_the_value_untouched() {
+    let mut value = config();
+    let err = apply_patch(
+        &mut value,
+        r#"[
+            { "op": "replace", "path": "/name", "value": "api" },
+            { "op": "add", "path": "/tags/0", "value": "first" },
+            { "op": "remove", "path": "/tags/2" },
+            { "op": "remove", "path": "/limits/cpu" },
+            { "op": "add", "path": "/limits/disk", "value": 10 },
+            { "op": "remove", "path": "/retry" },
+            { "op": "move", "from": "/sources/0", "path": "/sources/-" },
+            { "op": "test", "path": "/port", "value": 81 }
+        ]"#,
+    )
+    .unwrap_err();
+    assert_eq!(err.operation, Some(7));
+    assert_eq!(
+        err.kind,
+        PatchErrorKind::TestFailed {
+            actual: Value::U64(80)
+        }
+    );
+    assert_eq!(value, config());
+
+    let err =
+        apply_merge_patch(&mut value, r#"{ "name": "api", "tags": [], "port": [] }"#).unwrap_err();
+    assert_eq!(err.pointer.to_string(), "/port");
+    assert_eq!(value, config());
+}
+
+#[test]
+fn tests_compare_json_values() {
+    let mut value = config();
+    apply_patch(
+        &mut value,
+        r#"[
+            { "op": "test", "path": "/retry", "value": { "backoff": 0.5, "attempts": 3.0 } },
+            { "op": "test", "path": "/token", "value": null },
+            { "op": "test", "path": "/sources/1", "value": { "File": "app.toml" } },
+            { "op": "test", "path": "", "value": {
+                "name": "web", "port": 80, "token": null, "tags": ["a", "b"],
+                "limits": { "cpu": 2 },
+                "sources": ["Disabled", { "File": "app.toml" },
+                    { "Remote": { "url": "https://example.com", "retries": 3 } }],
+                "retry": { "attempts": 3, "backoff": 0.5 }
+            } }
+        ]"#,
+    )
+    .unwrap();
+}
+
+#[test]
+fn pointers_escape_their_tokens() {
+    let pointer: Pointer = "/a~1b/m~0n/".parse().unwrap();
+    assert_eq!(pointer.tokens(), ["a/b", "m~n", ""]);
+    assert_eq!(pointer.to_string(), "/a~1b/m~0n/");
+    assert!("a".parse::<Pointer>().is_err());
+    assert!("/~2".parse::<Pointer>().is_err());
+
+    let mut value = config();
+    apply_patch(
+        &mut value,
+        r#"[{ "op": "add", "path": "/limits/a~1b", "value": 1 }]"#,
+    )
+    .unwrap();
+    assert_eq!(value.limits["a/b"], 1);
+}
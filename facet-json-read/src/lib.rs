@@ -1,7 +1,11 @@
+#![cfg_attr(not(feature = "std"), no_std)]
 #![warn(missing_docs)]
 #![doc = include_str!("../README.md")]
 
+extern crate alloc;
+
 mod parser;
+pub use parser::{JsonParseError, JsonParseErrorKind, JsonParseErrorWithContext, JsonParser};
 
 mod deserialize;
 pub use deserialize::*;
@@ -1,50 +1,84 @@
 //! The facet-json parser.
 //!
 //! For now it is extremely naive, it's just a proof of concept, it doesn't use SIMD or anything,
-//! it's not fast, it's nothing, it's just proving that we can use facet types to deserialize something.
+//! it's not fast, it's nothing: it reads the input as a stream of facet-serialize events, which
+//! are then built into a value.
 
 #![allow(dead_code)]
 
+use alloc::borrow::Cow;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use facet_serialize::{Deserializer, Event};
+
+/// Error returned when the input isn't valid JSON, or doesn't fit the value being read
 #[derive(Debug)]
 pub struct JsonParseError {
+    /// What went wrong
     pub kind: JsonParseErrorKind,
+    /// Byte offset of the problem in the input
     pub position: usize,
 }
 
+/// What went wrong while reading JSON
 #[derive(Debug)]
 #[non_exhaustive]
 pub enum JsonParseErrorKind {
+    /// An object key doesn't start with `"`
     ExpectedOpeningQuote,
+    /// A string has no closing `"`
     UnterminatedString,
+    /// A `\` is followed by something that isn't an escape
     InvalidEscapeSequence(char),
+    /// A `\u` escape has fewer than 4 digits
     IncompleteUnicodeEscape,
+    /// A `\u` escape has digits that aren't hexadecimal
     InvalidUnicodeEscape,
+    /// A number has no digits
     ExpectedNumber,
+    /// A number's fraction or exponent has no digits
     InvalidNumberFormat,
+    /// An object was expected
     ExpectedOpeningBrace,
+    /// An array was expected
     ExpectedOpeningBracket,
+    /// An object key isn't followed by `:`
     ExpectedColon,
+    /// The input ends in the middle of a value
     UnexpectedEndOfInput,
+    /// Something that doesn't start a value
     InvalidValue,
+    /// An object member isn't followed by `,` or `}`
     ExpectedClosingBrace,
+    /// An array item isn't followed by `,` or `]`
     ExpectedClosingBracket,
+    /// An object has a member that isn't a field of the struct being read
     UnknownField(String),
+    /// An object lacks a field of the struct being read
+    MissingField(&'static str),
+    /// The value doesn't fit the type being read
     Custom(String),
 }
 
 impl JsonParseError {
+    /// Creates an error of the given kind, at a byte offset
     pub fn new(kind: JsonParseErrorKind, position: usize) -> Self {
         JsonParseError { kind, position }
     }
 }
 
+/// A [`JsonParseError`], along with the input it was found in, to show where it is
 #[derive(Debug)]
 pub struct JsonParseErrorWithContext<'input> {
+    /// The error
     pub error: JsonParseError,
+    /// The whole input
     pub input: &'input str,
 }
 
 impl JsonParseErrorWithContext<'_> {
+    /// Returns the error without its input
     pub fn strip_context(self) -> JsonParseError {
         self.error
     }
@@ -72,6 +106,9 @@ impl core::fmt::Display for JsonParseError {
             JsonParseErrorKind::UnknownField(field) => {
                 return write!(f, "Unknown field: {}", field);
             }
+            JsonParseErrorKind::MissingField(field) => {
+                return write!(f, "Missing field: {}", field);
+            }
             JsonParseErrorKind::Custom(msg) => msg,
         };
 
@@ -81,10 +118,17 @@ impl core::fmt::Display for JsonParseError {
 
 impl core::fmt::Display for JsonParseErrorWithContext<'_> {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-        let context_start = self.error.position.saturating_sub(20);
-        let context_end = (self.error.position + 20).min(self.input.len());
+        let position = self.error.position.min(self.input.len());
+        let mut context_start = position.saturating_sub(20);
+        while !self.input.is_char_boundary(context_start) {
+            context_start -= 1;
+        }
+        let mut context_end = (position + 20).min(self.input.len());
+        while !self.input.is_char_boundary(context_end) {
+            context_end += 1;
+        }
         let context = &self.input[context_start..context_end];
-        let arrow_position = self.error.position - context_start;
+        let arrow_position = self.input[context_start..position].chars().count();
 
         writeln!(f, "{}", self.error)?;
         writeln!(f, "\x1b[36m{}\x1b[0m", context)?;
@@ -94,305 +138,305 @@ impl core::fmt::Display for JsonParseErrorWithContext<'_> {
 
 impl core::error::Error for JsonParseError {}
 
+/// An array or object being read
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Container {
+    Array,
+    Object,
+}
+
+/// Reads JSON as a stream of [`Event`]s, which [`facet_serialize::deserialize`] builds into
+/// a value
+///
+/// The parser reads a single value: once it's read, [`JsonParser::position`] is the offset
+/// of what follows it.
 pub struct JsonParser<'input> {
+    /// The JSON being read
     pub input: &'input str,
+    /// Byte offset of the next thing to read
     pub position: usize,
+    /// The arrays and objects being read, innermost last, and whether they have an item yet
+    containers: Vec<(Container, bool)>,
+    /// Set after reading an object key, until its value is read
+    after_key: bool,
 }
 
 impl<'a> JsonParser<'a> {
+    /// Creates a parser reading `input` from its start
     pub fn new(input: &'a str) -> Self {
-        JsonParser { input, position: 0 }
+        JsonParser {
+            input,
+            position: 0,
+            containers: Vec::new(),
+            after_key: false,
+        }
     }
 
+    /// Returns an error of the given kind at the current position, with the input
     pub fn make_error(&self, kind: JsonParseErrorKind) -> JsonParseErrorWithContext<'a> {
         JsonParseErrorWithContext {
-            error: JsonParseError::new(kind, self.position),
+            error: self.error(kind),
             input: self.input,
         }
     }
 
-    pub fn parse_string(&mut self) -> Result<String, JsonParseErrorWithContext<'a>> {
-        self.skip_whitespace();
-        if self.position >= self.input.len() || self.input.as_bytes()[self.position] != b'"' {
-            return Err(self.make_error(JsonParseErrorKind::ExpectedOpeningQuote));
-        }
-        self.position += 1;
+    fn error(&self, kind: JsonParseErrorKind) -> JsonParseError {
+        JsonParseError::new(kind, self.position)
+    }
 
-        let mut result = String::new();
-        let mut escaped = false;
+    fn peek(&self) -> Option<u8> {
+        self.input.as_bytes().get(self.position).copied()
+    }
 
-        while self.position < self.input.len() {
-            let ch = self.input.as_bytes()[self.position];
+    /// Moves past whitespace
+    pub fn skip_whitespace(&mut self) {
+        while let Some(b' ' | b'\t' | b'\n' | b'\r') = self.peek() {
             self.position += 1;
+        }
+    }
+
+    /// Reads a string, which must start at the current position, give or take whitespace
+    pub fn parse_string(&mut self) -> Result<Cow<'a, str>, JsonParseError> {
+        self.skip_whitespace();
+        if self.peek() != Some(b'"') {
+            return Err(self.error(JsonParseErrorKind::ExpectedOpeningQuote));
+        }
+        self.position += 1;
 
-            if escaped {
-                match ch {
-                    b'"' | b'\\' | b'/' => result.push(ch as char),
-                    b'b' => result.push('\x08'),
-                    b'f' => result.push('\x0C'),
-                    b'n' => result.push('\n'),
-                    b'r' => result.push('\r'),
-                    b't' => result.push('\t'),
-                    b'u' => {
-                        // Parse 4-digit hex code
-                        if self.position + 4 > self.input.len() {
-                            return Err(
-                                self.make_error(JsonParseErrorKind::IncompleteUnicodeEscape)
-                            );
+        let bytes = self.input.as_bytes();
+        let mut owned: Option<String> = None;
+        let mut run_start = self.position;
+        while let Some(&byte) = bytes.get(self.position) {
+            match byte {
+                b'"' => {
+                    let run = &self.input[run_start..self.position];
+                    self.position += 1;
+                    return Ok(match owned {
+                        Some(mut s) => {
+                            s.push_str(run);
+                            Cow::Owned(s)
                         }
-                        let hex = &self.input[self.position..self.position + 4];
-                        self.position += 4;
-                        if let Ok(code) = u16::from_str_radix(hex, 16) {
-                            result.push(char::from_u32(code as u32).unwrap_or('\u{FFFD}'));
-                        } else {
-                            return Err(self.make_error(JsonParseErrorKind::InvalidUnicodeEscape));
+                        None => Cow::Borrowed(run),
+                    });
+                }
+                b'\\' => {
+                    let s = owned.get_or_insert_with(String::new);
+                    s.push_str(&self.input[run_start..self.position]);
+                    self.position += 1;
+                    let Some(&escape) = bytes.get(self.position) else {
+                        break;
+                    };
+                    self.position += 1;
+                    match escape {
+                        b'"' | b'\\' | b'/' => s.push(escape as char),
+                        b'b' => s.push('\x08'),
+                        b'f' => s.push('\x0C'),
+                        b'n' => s.push('\n'),
+                        b'r' => s.push('\r'),
+                        b't' => s.push('\t'),
+                        b'u' => s.push(self.parse_unicode_escape()?),
+                        _ => {
+                            self.position -= 1;
+                            return Err(self.error(JsonParseErrorKind::InvalidEscapeSequence(
+                                self.input[self.position..].chars().next().unwrap(),
+                            )));
                         }
                     }
-                    _ => {
-                        return Err(
-                            self.make_error(JsonParseErrorKind::InvalidEscapeSequence(ch as char))
-                        );
-                    }
+                    run_start = self.position;
                 }
-                escaped = false;
-            } else if ch == b'\\' {
-                escaped = true;
-            } else if ch == b'"' {
-                return Ok(result);
-            } else {
-                result.push(ch as char);
+                _ => self.position += 1,
             }
         }
 
-        Err(self.make_error(JsonParseErrorKind::UnterminatedString))
+        Err(self.error(JsonParseErrorKind::UnterminatedString))
     }
 
-    pub fn parse_u64(&mut self) -> Result<u64, JsonParseErrorWithContext<'a>> {
-        self.skip_whitespace();
-        let start = self.position;
-        while self.position < self.input.len()
-            && self.input.as_bytes()[self.position].is_ascii_digit()
-        {
-            self.position += 1;
+    /// Parses the 4 hex digits of a `\u` escape, and the low surrogate that follows a high one
+    fn parse_unicode_escape(&mut self) -> Result<char, JsonParseError> {
+        let high = self.parse_hex4()?;
+        if !(0xD800..0xDC00).contains(&high) {
+            return Ok(char::from_u32(high as u32).unwrap_or('\u{FFFD}'));
+        }
+        if !self.input[self.position..].starts_with("\\u") {
+            return Ok('\u{FFFD}');
         }
-        if start == self.position {
-            return Err(self.make_error(JsonParseErrorKind::ExpectedNumber));
+        let before = self.position;
+        self.position += 2;
+        let low = self.parse_hex4()?;
+        if !(0xDC00..0xE000).contains(&low) {
+            // not a pair: the second escape is read on its own
+            self.position = before;
+            return Ok('\u{FFFD}');
         }
-        let num_str = &self.input[start..self.position];
-        num_str
-            .parse::<u64>()
-            .map_err(|_| self.make_error(JsonParseErrorKind::InvalidNumberFormat))
+        let code = 0x10000 + (((high as u32) - 0xD800) << 10) + ((low as u32) - 0xDC00);
+        Ok(char::from_u32(code).unwrap_or('\u{FFFD}'))
     }
 
-    // Generic number parsing helper
-    fn parse_number<T>(&mut self) -> Result<T, JsonParseErrorWithContext<'a>>
-    where
-        T: core::str::FromStr,
-        <T as core::str::FromStr>::Err: core::fmt::Debug, // Ensure the error type can be debug printed
-    {
-        self.skip_whitespace();
+    fn parse_hex4(&mut self) -> Result<u16, JsonParseError> {
+        let Some(hex) = self.input.get(self.position..self.position + 4) else {
+            return Err(self.error(JsonParseErrorKind::IncompleteUnicodeEscape));
+        };
+        let code = u16::from_str_radix(hex, 16)
+            .map_err(|_| self.error(JsonParseErrorKind::InvalidUnicodeEscape))?;
+        self.position += 4;
+        Ok(code)
+    }
+
+    /// Parses a number: an integer if it has no fraction nor exponent, or a float.
+    /// Integers too large for 64 bits are read as strings, which wider integer types
+    /// parse from.
+    fn parse_number(&mut self) -> Result<Event<'a>, JsonParseError> {
         let start = self.position;
-        // Allow leading minus sign
-        if self.position < self.input.len() && self.input.as_bytes()[self.position] == b'-' {
+        let digits = |parser: &mut Self| {
+            let before = parser.position;
+            while parser.peek().is_some_and(|b| b.is_ascii_digit()) {
+                parser.position += 1;
+            }
+            parser.position > before
+        };
+
+        if self.peek() == Some(b'-') {
+            self.position += 1;
+        }
+        if !digits(self) {
+            return Err(self.error(JsonParseErrorKind::ExpectedNumber));
+        }
+        let mut float = false;
+        if self.peek() == Some(b'.') {
+            float = true;
             self.position += 1;
+            if !digits(self) {
+                return Err(self.error(JsonParseErrorKind::InvalidNumberFormat));
+            }
         }
-        // Allow digits and decimal point
-        while self.position < self.input.len() {
-            let byte = self.input.as_bytes()[self.position];
-            if byte.is_ascii_digit() || byte == b'.' {
+        if let Some(b'e' | b'E') = self.peek() {
+            float = true;
+            self.position += 1;
+            if let Some(b'+' | b'-') = self.peek() {
                 self.position += 1;
-            } else {
-                break;
+            }
+            if !digits(self) {
+                return Err(self.error(JsonParseErrorKind::InvalidNumberFormat));
             }
         }
 
-        if start == self.position
-            || (self.position == start + 1 && self.input.as_bytes()[start] == b'-')
-        {
-            // Handle case where only '-' was found or nothing was parsed
-            return Err(self.make_error(JsonParseErrorKind::ExpectedNumber));
+        let text = &self.input[start..self.position];
+        let invalid = |_| JsonParseError::new(JsonParseErrorKind::InvalidNumberFormat, start);
+        if float {
+            text.parse().map(Event::F64).map_err(invalid)
+        } else if text.starts_with('-') {
+            Ok(text
+                .parse()
+                .map_or(Event::Str(Cow::Borrowed(text)), Event::I64))
+        } else {
+            Ok(text
+                .parse()
+                .map_or(Event::Str(Cow::Borrowed(text)), Event::U64))
         }
-
-        let num_str = &self.input[start..self.position];
-        num_str
-            .parse::<T>()
-            .map_err(|_| self.make_error(JsonParseErrorKind::InvalidNumberFormat))
-    }
-
-    // Implement specific number parsers using the helper
-    pub fn parse_u8(&mut self) -> Result<u8, JsonParseErrorWithContext<'a>> {
-        self.parse_number()
-    }
-    pub fn parse_u16(&mut self) -> Result<u16, JsonParseErrorWithContext<'a>> {
-        self.parse_number()
-    }
-    pub fn parse_u32(&mut self) -> Result<u32, JsonParseErrorWithContext<'a>> {
-        self.parse_number()
-    }
-    // u64 is already implemented, keep it as is or refactor to use parse_number
-    // pub fn parse_u64(&mut self) -> Result<u64, JsonParseErrorWithContext<'a>> {
-    //     self.parse_number()
-    // }
-
-    pub fn parse_i8(&mut self) -> Result<i8, JsonParseErrorWithContext<'a>> {
-        self.parse_number()
-    }
-    pub fn parse_i16(&mut self) -> Result<i16, JsonParseErrorWithContext<'a>> {
-        self.parse_number()
-    }
-    pub fn parse_i32(&mut self) -> Result<i32, JsonParseErrorWithContext<'a>> {
-        self.parse_number()
-    }
-    pub fn parse_i64(&mut self) -> Result<i64, JsonParseErrorWithContext<'a>> {
-        self.parse_number()
-    }
-
-    pub fn parse_f32(&mut self) -> Result<f32, JsonParseErrorWithContext<'a>> {
-        self.parse_number()
-    }
-    pub fn parse_f64(&mut self) -> Result<f64, JsonParseErrorWithContext<'a>> {
-        self.parse_number()
     }
 
-    pub fn parse_bool(&mut self) -> Result<bool, JsonParseErrorWithContext<'a>> {
-        self.skip_whitespace();
-        if self.position + 4 <= self.input.len()
-            && &self.input[self.position..self.position + 4] == "true"
-        {
-            self.position += 4;
-            return Ok(true);
-        }
-        if self.position + 5 <= self.input.len()
-            && &self.input[self.position..self.position + 5] == "false"
-        {
-            self.position += 5;
-            return Ok(false);
+    fn parse_literal(
+        &mut self,
+        literal: &str,
+        event: Event<'a>,
+    ) -> Result<Event<'a>, JsonParseError> {
+        if self.input[self.position..].starts_with(literal) {
+            self.position += literal.len();
+            Ok(event)
+        } else {
+            Err(self.error(JsonParseErrorKind::InvalidValue))
         }
-        Err(self.make_error(JsonParseErrorKind::InvalidValue))
     }
 
-    pub fn skip_whitespace(&mut self) {
-        while self.position < self.input.len() {
-            match self.input.as_bytes()[self.position] {
-                b' ' | b'\t' | b'\n' | b'\r' => self.position += 1,
-                _ => break,
+    /// Reads the value that starts at the current position
+    fn parse_value(&mut self) -> Result<Event<'a>, JsonParseError> {
+        match self.peek() {
+            None => Err(self.error(JsonParseErrorKind::UnexpectedEndOfInput)),
+            Some(b'{') => {
+                self.position += 1;
+                self.containers.push((Container::Object, false));
+                Ok(Event::StartMap)
             }
+            Some(b'[') => {
+                self.position += 1;
+                self.containers.push((Container::Array, false));
+                Ok(Event::StartSeq)
+            }
+            Some(b'"') => self.parse_string().map(Event::Str),
+            Some(b't') => self.parse_literal("true", Event::Bool(true)),
+            Some(b'f') => self.parse_literal("false", Event::Bool(false)),
+            Some(b'n') => self.parse_literal("null", Event::Unit),
+            Some(b'-' | b'0'..=b'9') => self.parse_number(),
+            Some(_) => Err(self.error(JsonParseErrorKind::InvalidValue)),
         }
     }
 
-    /// Expects the start of an array and returns whether there's a first element.
-    /// Returns true if the array has an element, false if the array is empty.
-    pub fn expect_array_start(&mut self) -> Result<bool, JsonParseErrorWithContext<'a>> {
-        self.skip_whitespace();
-        if self.position >= self.input.len() || self.input.as_bytes()[self.position] != b'[' {
-            return Err(self.make_error(JsonParseErrorKind::ExpectedOpeningBracket));
-        }
-        self.position += 1;
-        self.skip_whitespace();
-
-        // Check if array is immediately closed
-        if self.position < self.input.len() && self.input.as_bytes()[self.position] == b']' {
-            self.position += 1;
-            return Ok(false); // Empty array
-        }
-
-        Ok(true) // Array has at least one element
-    }
-
-    /// Expects the end of an array or a comma followed by the next element.
-    /// Returns Some(true) if there's another element, Some(false) if the array has ended,
-    /// or an error if the JSON is malformed.
-    pub fn parse_array_element(&mut self) -> Result<Option<bool>, JsonParseErrorWithContext<'a>> {
-        self.skip_whitespace();
-        if self.position >= self.input.len() {
-            return Err(self.make_error(JsonParseErrorKind::UnexpectedEndOfInput));
-        }
-
-        match self.input.as_bytes()[self.position] {
-            b',' => {
+    /// Reads the next key of the current object, or its end
+    fn parse_object_key(&mut self, has_entries: bool) -> Result<Event<'a>, JsonParseError> {
+        match self.peek() {
+            None => return Err(self.error(JsonParseErrorKind::UnexpectedEndOfInput)),
+            Some(b'}') => {
                 self.position += 1;
-                self.skip_whitespace();
-                Ok(Some(true)) // There's another element
+                self.containers.pop();
+                return Ok(Event::EndMap);
             }
-            b']' => {
+            Some(b',') if has_entries => {
                 self.position += 1;
-                Ok(Some(false)) // End of array
+                self.skip_whitespace();
             }
-            _ => {
-                // First element doesn't need a comma
-                Ok(Some(true))
+            Some(_) if has_entries => {
+                return Err(self.error(JsonParseErrorKind::ExpectedClosingBrace));
             }
+            Some(_) => {}
         }
-    }
 
-    /// Expects the start of an object and returns the first key if present.
-    /// Returns None if the object is empty.
-    pub fn expect_object_start(&mut self) -> Result<Option<String>, JsonParseErrorWithContext<'a>> {
-        self.skip_whitespace();
-        if self.position >= self.input.len() || self.input.as_bytes()[self.position] != b'{' {
-            return Err(self.make_error(JsonParseErrorKind::ExpectedOpeningBrace));
+        if let Some(last) = self.containers.last_mut() {
+            last.1 = true;
         }
-        self.position += 1;
+        let key = self.parse_string()?;
         self.skip_whitespace();
-
-        if self.position < self.input.len() && self.input.as_bytes()[self.position] == b'"' {
-            let key = self.parse_string()?;
-            self.skip_whitespace();
-            if self.position < self.input.len() && self.input.as_bytes()[self.position] == b':' {
-                self.position += 1;
-                Ok(Some(key))
-            } else {
-                Err(self.make_error(JsonParseErrorKind::ExpectedColon))
-            }
-        } else if self.position < self.input.len() && self.input.as_bytes()[self.position] == b'}' {
-            self.position += 1;
-            Ok(None)
-        } else {
-            Err(self.make_error(JsonParseErrorKind::InvalidValue))
+        if self.peek() != Some(b':') {
+            return Err(self.error(JsonParseErrorKind::ExpectedColon));
         }
+        self.position += 1;
+        self.after_key = true;
+        Ok(Event::Key(key))
     }
+}
+
+impl<'a> Deserializer<'a> for JsonParser<'a> {
+    type Error = JsonParseError;
 
-    /// Expects the end of an object or a comma followed by the next key.
-    /// Returns None if the object has ended, or Some(key) if there's another key-value pair.
-    ///
-    /// This function is used to parse the end of an object or to move to the next key-value pair.
-    /// It handles three cases:
-    /// 1. If it encounters a comma, it expects the next key-value pair and returns Some(key).
-    /// 2. If it encounters a closing brace, it returns None to indicate the end of the object.
-    /// 3. If it encounters anything else, it returns an error.
-    ///
-    /// The function also takes care of skipping whitespace before and after tokens.
-    /// If it reaches the end of input unexpectedly, it returns an appropriate error.
-    pub fn parse_object_key(&mut self) -> Result<Option<String>, JsonParseErrorWithContext<'a>> {
+    fn next(&mut self) -> Result<Event<'a>, Self::Error> {
         self.skip_whitespace();
-        if self.position >= self.input.len() {
-            return Err(self.make_error(JsonParseErrorKind::UnexpectedEndOfInput));
-        }
-        match self.input.as_bytes()[self.position] {
-            b',' => {
-                self.position += 1;
-                self.skip_whitespace();
-                if self.position < self.input.len() && self.input.as_bytes()[self.position] == b'"'
-                {
-                    let key = self.parse_string()?;
-                    self.skip_whitespace();
-                    if self.position < self.input.len()
-                        && self.input.as_bytes()[self.position] == b':'
-                    {
+        match self.containers.last().copied() {
+            Some((Container::Object, has_entries)) if !self.after_key => {
+                return self.parse_object_key(has_entries);
+            }
+            Some((Container::Array, has_items)) => {
+                match self.peek() {
+                    None => return Err(self.error(JsonParseErrorKind::UnexpectedEndOfInput)),
+                    Some(b']') => {
                         self.position += 1;
-                        Ok(Some(key))
-                    } else {
-                        Err(self.make_error(JsonParseErrorKind::ExpectedColon))
+                        self.containers.pop();
+                        return Ok(Event::EndSeq);
                     }
-                } else {
-                    Err(self.make_error(JsonParseErrorKind::InvalidValue))
+                    Some(b',') if has_items => {
+                        self.position += 1;
+                        self.skip_whitespace();
+                    }
+                    Some(_) if has_items => {
+                        return Err(self.error(JsonParseErrorKind::ExpectedClosingBracket));
+                    }
+                    Some(_) => {}
+                }
+                if let Some(last) = self.containers.last_mut() {
+                    last.1 = true;
                 }
             }
-            b'}' => {
-                self.position += 1;
-                Ok(None)
-            }
-            _ => Err(self.make_error(JsonParseErrorKind::InvalidValue)),
+            _ => {}
         }
+        self.after_key = false;
+        self.parse_value()
     }
 }
@@ -0,0 +1,129 @@
+//! Deserialization errors at any point of the input must drop whatever was already built,
+//! without leaking it and without dropping anything twice.
+//!
+//! Leaks are detected by counting live allocations on the current thread; under Miri,
+//! double drops and use-after-frees are reported as well.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::collections::HashMap;
+
+use facet_derive::Facet;
+use facet_json_read::from_str;
+use facet_trait::Facet;
+
+use facet_trait as facet;
+
+struct CountingAlloc;
+
+thread_local! {
+    static LIVE: Cell<isize> = const { Cell::new(0) };
+}
+
+fn track(delta: isize) {
+    // the thread-local may already be gone while the thread shuts down
+    let _ = LIVE.try_with(|live| live.set(live.get() + delta));
+}
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        track(1);
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        track(-1);
+        unsafe { System.dealloc(ptr, layout) }
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+fn live_allocations() -> isize {
+    LIVE.with(|live| live.get())
+}
+
+#[derive(Facet)]
+struct Inner {
+    label: String,
+    tags: Vec<String>,
+}
+
+#[derive(Facet)]
+struct Doc {
+    name: String,
+    id: u64,
+    inner: Inner,
+    items: Vec<Inner>,
+    extra: HashMap<String, String>,
+    motto: String,
+}
+
+const DOC: &str = r#"{"name":"facet","id":7,"inner":{"label":"first","tags":["a","bb"]},"items":[{"label":"x","tags":[]},{"label":"y","tags":["z"]}],"extra":{"k1":"v1","k2":"v2"},"motto":"reflect"}"#;
+
+/// Runs `from_str` on `input` and checks that nothing it allocated outlives the result.
+fn assert_no_leak<T: Facet>(input: &str) {
+    let before = live_allocations();
+    let result = from_str::<T>(input);
+    drop(result);
+    assert_eq!(
+        live_allocations(),
+        before,
+        "allocations leaked while deserializing {input:?}"
+    );
+}
+
+#[test]
+fn valid_document_round_trips() {
+    let doc: Doc = from_str(DOC).unwrap();
+    assert_eq!(doc.name, "facet");
+    assert_eq!(doc.id, 7);
+    assert_eq!(doc.inner.tags, ["a", "bb"]);
+    assert_eq!(doc.items.len(), 2);
+    assert_eq!(doc.items[1].tags, ["z"]);
+    assert_eq!(doc.extra["k2"], "v2");
+    assert_eq!(doc.motto, "reflect");
+
+    assert_no_leak::<Doc>(DOC);
+}
+
+#[test]
+fn truncated_input_drops_partial_value() {
+    for end in 0..DOC.len() {
+        let input = &DOC[..end];
+        assert!(
+            from_str::<Doc>(input).is_err(),
+            "{input:?} should not parse"
+        );
+        assert_no_leak::<Doc>(input);
+    }
+}
+
+#[test]
+fn corrupted_input_drops_partial_value() {
+    for pos in 0..DOC.len() {
+        let mut input = DOC.to_string();
+        input.replace_range(pos..pos + 1, "#");
+        assert_no_leak::<Doc>(&input);
+    }
+}
+
+#[test]
+fn missing_field_is_an_error() {
+    let input =
+        r#"{"name":"facet","id":7,"inner":{"label":"first","tags":["a"]},"items":[],"extra":{}}"#;
+    let err = from_str::<Doc>(input).err().expect("motto is missing");
+    assert!(err.to_string().contains("Missing field: motto"), "{err}");
+    assert_no_leak::<Doc>(input);
+}
+
+#[test]
+fn repeated_field_drops_previous_value() {
+    let input = r#"{"label":"one","tags":["a"],"label":"two","tags":["b","c"]}"#;
+    let inner: Inner = from_str(input).unwrap();
+    assert_eq!(inner.label, "two");
+    assert_eq!(inner.tags, ["b", "c"]);
+    drop(inner);
+    assert_no_leak::<Inner>(input);
+}
@@ -133,6 +133,46 @@ fn test_from_json_with_nested_structs() {
     assert_eq!(test_struct.inner.value, 42);
 }
 
+#[test]
+fn test_from_json_with_zero_sized_types() {
+    #[derive(Debug, PartialEq, Facet)]
+    struct Marker;
+
+    #[derive(Debug, PartialEq, Facet)]
+    struct Empty {}
+
+    #[derive(Facet)]
+    struct Holder {
+        marker: Marker,
+        empty: Empty,
+        nothing: (),
+        markers: Vec<Marker>,
+        units: std::collections::HashMap<String, ()>,
+    }
+
+    let () = from_str::<()>("null").unwrap();
+    assert_eq!(from_str::<Marker>("null").unwrap(), Marker);
+    assert_eq!(from_str::<Empty>("{}").unwrap(), Empty {});
+    assert!(from_str::<Marker>("{}").is_err());
+
+    let json = r#"{
+        "marker": null,
+        "empty": {},
+        "nothing": null,
+        "markers": [null, null, null],
+        "units": {"a": null, "b": null}
+    }"#;
+    let holder: Holder = match from_str(json) {
+        Ok(s) => s,
+        Err(e) => panic!("Error deserializing JSON: {}", e),
+    };
+    assert_eq!(holder.marker, Marker);
+    assert_eq!(holder.empty, Empty {});
+    assert_eq!(holder.markers, [Marker, Marker, Marker]);
+    assert_eq!(holder.units.len(), 2);
+    assert_eq!(holder.units.get("b"), Some(&()));
+}
+
 // #[test]
 // fn test_from_json_with_simple_tuples() {
 //     type Tuple = (i32, String);
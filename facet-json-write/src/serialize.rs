@@ -1,294 +1,232 @@
-use facet_poke::{Peek, PeekValue};
-use facet_trait::ShapeExt as _;
-use std::collections::VecDeque;
-use std::io::{self, Write};
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::{self, Write};
+use facet_poke::Peek;
+use facet_serialize::{Serializer, serialize};
+
+/// Serializes any Facet type to JSON, writing into a [`core::fmt::Write`] sink
+///
+/// This is available without `std`: use it to write into a `String`, a fixed-size
+/// buffer, or a UART.
+///
+/// Values are written with [`facet_serialize`]'s data model: `None` is `null`,
+/// `Some(value)` is just the value, and enums are externally tagged. Floats that JSON
+/// can't represent (infinities and NaN) are written as `null`.
+///
+/// # Errors
+///
+/// Returns an error if the sink does, or if the value can't be represented: a scalar
+/// the data model doesn't know about that doesn't implement `Display`, or a value that
+/// contains itself.
+pub fn to_json_fmt<W: Write>(peek: Peek<'_>, writer: &mut W, indent: bool) -> fmt::Result {
+    let mut json = JsonWriter {
+        writer,
+        indent,
+        containers: Vec::new(),
+    };
+    serialize(peek, &mut json).map_err(|_| fmt::Error)
+}
 
-/// Serializes any Facet type to JSON
-pub fn to_json<W: Write>(peek: Peek<'_>, writer: &mut W, indent: bool) -> io::Result<()> {
-    #[derive(Debug)]
-    enum StackItem<'mem> {
-        Value {
-            peek: Peek<'mem>,
-            level: usize,
-        },
-        StructField {
-            field_name: String,
-            peek: Peek<'mem>,
-            level: usize,
-            is_first: bool,
-        },
-        StructEnd {
-            level: usize,
-            had_fields: bool,
-        },
-        ListItem {
-            peek: Peek<'mem>,
-            level: usize,
-            is_first: bool,
-        },
-        ListEnd {
-            level: usize,
-            had_items: bool,
-        },
-        MapEntry {
-            key: Peek<'mem>,
-            value: Peek<'mem>,
-            level: usize,
-            is_first: bool,
-        },
-        MapEnd {
-            level: usize,
-            had_entries: bool,
-        },
+/// Serializes any Facet type to JSON, writing into a [`std::io::Write`] sink
+#[cfg(feature = "std")]
+pub fn to_json<W: std::io::Write>(
+    peek: Peek<'_>,
+    writer: &mut W,
+    indent: bool,
+) -> std::io::Result<()> {
+    /// Forwards formatted output to an `io::Write`, remembering the first I/O error
+    struct IoAdapter<'w, W> {
+        inner: &'w mut W,
+        error: std::io::Result<()>,
     }
 
-    let mut stack: VecDeque<StackItem> = VecDeque::new();
-    stack.push_back(StackItem::Value { peek, level: 0 });
-
-    while let Some(item) = stack.pop_front() {
-        match item {
-            StackItem::Value { peek, level } => {
-                match peek {
-                    Peek::Value(pv) => {
-                        if pv.shape().is_type::<()>() {
-                            write!(writer, "null")?;
-                        } else if pv.shape().is_type::<bool>() {
-                            let value = unsafe { pv.data().as_ref::<bool>() };
-                            write!(writer, "{}", value)?;
-                        } else if pv.shape().is_type::<u64>() {
-                            let value = unsafe { pv.data().as_ref::<u64>() };
-                            write!(writer, "{}", value)?;
-                        } else if pv.shape().is_type::<String>() {
-                            let value = unsafe { pv.data().as_ref::<String>() };
-                            write!(writer, "\"{}\"", value.escape_debug())?;
-                        } else {
-                            // For other types, we'll use a placeholder
-                            write!(writer, "\"<unsupported type>\"")?;
-                        }
-                    }
-                    Peek::Struct(ps) => {
-                        write!(writer, "{{")?;
-                        if indent {
-                            writeln!(writer)?;
-                        }
+    impl<W: std::io::Write> Write for IoAdapter<'_, W> {
+        fn write_str(&mut self, s: &str) -> fmt::Result {
+            self.inner.write_all(s.as_bytes()).map_err(|e| {
+                self.error = Err(e);
+                fmt::Error
+            })
+        }
+    }
 
-                        let fields: Vec<_> = ps.fields().collect();
-                        stack.push_front(StackItem::StructEnd {
-                            level,
-                            had_fields: !fields.is_empty(),
-                        });
+    let mut adapter = IoAdapter {
+        inner: writer,
+        error: Ok(()),
+    };
+    match to_json_fmt(peek, &mut adapter, indent) {
+        Ok(()) => Ok(()),
+        Err(fmt::Error) => adapter
+            .error
+            .and(Err(std::io::Error::other("formatter error"))),
+    }
+}
 
-                        // Push fields in reverse order so they'll be processed in the correct order
-                        for (i, field) in fields.into_iter().enumerate().rev() {
-                            stack.push_front(StackItem::StructField {
-                                field_name: field.0.to_string(),
-                                peek: field.1,
-                                level,
-                                is_first: i == 0,
-                            });
-                        }
-                    }
-                    Peek::List(pl) => {
-                        write!(writer, "[")?;
-                        if indent {
-                            writeln!(writer)?;
-                        }
+/// Serializes any Facet type to JSON and returns it as a String
+///
+/// # Panics
+///
+/// Panics if the value can't be represented, see [`to_json_fmt`].
+pub fn to_json_string(peek: Peek<'_>, indent: bool) -> String {
+    let mut buffer = String::new();
+    to_json_fmt(peek, &mut buffer, indent).unwrap();
+    buffer
+}
 
-                        let mut items = Vec::new();
-                        let mut index = 0;
-                        while let Some(item) = pl.item_at(index) {
-                            items.push(item);
-                            index += 1;
-                        }
+/// Writes the calls of [`serialize`] as JSON
+struct JsonWriter<'w, W> {
+    writer: &'w mut W,
+    indent: bool,
+    /// The arrays and objects being written, innermost last: whether they're arrays, and
+    /// whether they have an item yet
+    containers: Vec<(bool, bool)>,
+}
 
-                        stack.push_front(StackItem::ListEnd {
-                            level,
-                            had_items: !items.is_empty(),
-                        });
+impl<W: Write> JsonWriter<'_, W> {
+    /// Writes what comes before an item of the current array or object: a comma if it's
+    /// not the first one, and the indentation
+    fn item(&mut self) -> fmt::Result {
+        let level = self.containers.len();
+        let Some((_, has_items)) = self.containers.last_mut() else {
+            return Ok(());
+        };
+        if core::mem::replace(has_items, true) {
+            self.writer.write_char(',')?;
+        }
+        if self.indent {
+            write!(self.writer, "\n{:indent$}", "", indent = level * 2)?;
+        }
+        Ok(())
+    }
 
-                        // Push items in reverse order
-                        for (i, item) in items.into_iter().enumerate().rev() {
-                            stack.push_front(StackItem::ListItem {
-                                peek: item,
-                                level,
-                                is_first: i == 0,
-                            });
-                        }
-                    }
-                    Peek::Map(pm) => {
-                        write!(writer, "{{")?;
-                        if indent {
-                            writeln!(writer)?;
-                        }
+    /// Writes what comes before a value: inside an array, the value is an item of it
+    fn value(&mut self) -> fmt::Result {
+        match self.containers.last() {
+            Some((true, _)) => self.item(),
+            _ => Ok(()),
+        }
+    }
 
-                        // Collect entries using the iterator and convert them to the format expected by MapEntry
-                        let entries: Vec<(PeekValue<'_>, Peek<'_>)> = pm
-                            .iter()
-                            .map(|(key, value)| (key.as_value(), value))
-                            .collect();
+    fn open(&mut self, array: bool) -> fmt::Result {
+        self.value()?;
+        self.writer.write_char(if array { '[' } else { '{' })?;
+        self.containers.push((array, false));
+        Ok(())
+    }
 
-                        stack.push_front(StackItem::MapEnd {
-                            level,
-                            had_entries: !entries.is_empty(),
-                        });
+    fn close(&mut self) -> fmt::Result {
+        let (array, has_items) = self.containers.pop().expect("closing a container");
+        if has_items && self.indent {
+            write!(
+                self.writer,
+                "\n{:indent$}",
+                "",
+                indent = self.containers.len() * 2
+            )?;
+        }
+        self.writer.write_char(if array { ']' } else { '}' })
+    }
 
-                        // Push entries in reverse order
-                        for (i, (key, value)) in entries.into_iter().enumerate().rev() {
-                            stack.push_front(StackItem::MapEntry {
-                                key: Peek::Value(key),
-                                value,
-                                level,
-                                is_first: i == 0,
-                            });
-                        }
-                    }
-                    _ => todo!("unsupported peek type: {:?}", peek),
-                }
+    fn string(&mut self, s: &str) -> fmt::Result {
+        self.writer.write_char('"')?;
+        let mut run_start = 0;
+        for (i, c) in s.char_indices() {
+            let escape = match c {
+                '"' => "\\\"",
+                '\\' => "\\\\",
+                '\n' => "\\n",
+                '\r' => "\\r",
+                '\t' => "\\t",
+                '\x08' => "\\b",
+                '\x0C' => "\\f",
+                c if c < ' ' => "",
+                _ => continue,
+            };
+            self.writer.write_str(&s[run_start..i])?;
+            if escape.is_empty() {
+                write!(self.writer, "\\u{:04x}", c as u32)?;
+            } else {
+                self.writer.write_str(escape)?;
             }
-            StackItem::StructField {
-                field_name,
-                peek,
-                level,
-                is_first,
-            } => {
-                if !is_first {
-                    write!(writer, ",")?;
-                    if indent {
-                        writeln!(writer)?;
-                    }
-                }
+            run_start = i + c.len_utf8();
+        }
+        self.writer.write_str(&s[run_start..])?;
+        self.writer.write_char('"')
+    }
+}
 
-                if indent {
-                    write!(writer, "{:indent$}", "", indent = (level + 1) * 2)?;
-                }
-                write!(writer, "\"{}\":", field_name)?;
-                if indent {
-                    write!(writer, " ")?;
-                }
+impl<W: Write> Serializer for JsonWriter<'_, W> {
+    type Error = fmt::Error;
 
-                stack.push_front(StackItem::Value {
-                    peek,
-                    level: level + 1,
-                });
-            }
-            StackItem::StructEnd { level, had_fields } => {
-                if had_fields && indent {
-                    writeln!(writer)?;
-                    write!(writer, "{:indent$}", "", indent = level * 2)?;
-                }
-                write!(writer, "}}")?;
-            }
-            StackItem::ListItem {
-                peek,
-                level,
-                is_first,
-            } => {
-                if !is_first {
-                    write!(writer, ",")?;
-                    if indent {
-                        writeln!(writer)?;
-                    }
-                }
+    fn serialize_bool(&mut self, value: bool) -> fmt::Result {
+        self.value()?;
+        write!(self.writer, "{value}")
+    }
 
-                if indent {
-                    write!(writer, "{:indent$}", "", indent = (level + 1) * 2)?;
-                }
+    fn serialize_u64(&mut self, value: u64) -> fmt::Result {
+        self.value()?;
+        write!(self.writer, "{value}")
+    }
 
-                stack.push_front(StackItem::Value {
-                    peek,
-                    level: level + 1,
-                });
-            }
-            StackItem::ListEnd { level, had_items } => {
-                if had_items && indent {
-                    writeln!(writer)?;
-                    write!(writer, "{:indent$}", "", indent = level * 2)?;
-                }
-                write!(writer, "]")?;
-            }
-            StackItem::MapEntry {
-                key,
-                value,
-                level,
-                is_first,
-            } => {
-                if !is_first {
-                    write!(writer, ",")?;
-                    if indent {
-                        writeln!(writer)?;
-                    }
-                }
+    fn serialize_i64(&mut self, value: i64) -> fmt::Result {
+        self.value()?;
+        write!(self.writer, "{value}")
+    }
 
-                if indent {
-                    write!(writer, "{:indent$}", "", indent = (level + 1) * 2)?;
-                }
+    fn serialize_f64(&mut self, value: f64) -> fmt::Result {
+        self.value()?;
+        if value.is_finite() {
+            // `Debug` keeps the fraction of whole floats, and uses exponents for extremes
+            write!(self.writer, "{value:?}")
+        } else {
+            self.writer.write_str("null")
+        }
+    }
 
-                // Process key first (inline with no indentation)
-                let mut temp_writer = Vec::new();
-                let mut temp_stack = VecDeque::new();
-                temp_stack.push_back(StackItem::Value {
-                    peek: key,
-                    level: 0,
-                });
+    fn serialize_str(&mut self, value: &str) -> fmt::Result {
+        self.value()?;
+        self.string(value)
+    }
 
-                while let Some(temp_item) = temp_stack.pop_front() {
-                    match temp_item {
-                        StackItem::Value { peek, level: _ } => match peek {
-                            Peek::Value(pv) => {
-                                if pv.shape().is_type::<()>() {
-                                    write!(&mut temp_writer, "null")?;
-                                } else if pv.shape().is_type::<bool>() {
-                                    let value = unsafe { pv.data().as_ref::<bool>() };
-                                    write!(&mut temp_writer, "{}", value)?;
-                                } else if pv.shape().is_type::<u64>() {
-                                    let value = unsafe { pv.data().as_ref::<u64>() };
-                                    write!(&mut temp_writer, "{}", value)?;
-                                } else if pv.shape().is_type::<String>() {
-                                    let value = unsafe { pv.data().as_ref::<String>() };
-                                    write!(&mut temp_writer, "\"{}\"", value.escape_debug())?;
-                                } else {
-                                    write!(&mut temp_writer, "\"<unsupported type>\"")?;
-                                }
-                            }
-                            _ => {
-                                write!(&mut temp_writer, "\"<complex_key>\"")?;
-                            }
-                        },
-                        _ => {
-                            // This should not happen for simple key serialization
-                            write!(&mut temp_writer, "\"<invalid_key>\"")?;
-                        }
-                    }
-                }
+    fn serialize_unit(&mut self) -> fmt::Result {
+        self.value()?;
+        self.writer.write_str("null")
+    }
 
-                let key_string = String::from_utf8(temp_writer).unwrap();
-                write!(writer, "{}:", key_string)?;
+    fn start_struct(&mut self, _len: usize) -> fmt::Result {
+        self.open(false)
+    }
 
-                if indent {
-                    write!(writer, " ")?;
-                }
+    fn field(&mut self, name: &'static str) -> fmt::Result {
+        self.map_key(name)
+    }
 
-                stack.push_front(StackItem::Value {
-                    peek: value,
-                    level: level + 1,
-                });
-            }
-            StackItem::MapEnd { level, had_entries } => {
-                if had_entries && indent {
-                    writeln!(writer)?;
-                    write!(writer, "{:indent$}", "", indent = level * 2)?;
-                }
-                write!(writer, "}}")?;
-            }
-        }
+    fn end_struct(&mut self) -> fmt::Result {
+        self.close()
     }
 
-    Ok(())
-}
+    fn start_seq(&mut self, _len: usize) -> fmt::Result {
+        self.open(true)
+    }
 
-/// Serializes any Facet type to JSON and returns it as a String
-pub fn to_json_string(peek: Peek<'_>, indent: bool) -> String {
-    let mut buffer = Vec::new();
-    to_json(peek, &mut buffer, indent).unwrap();
-    String::from_utf8(buffer).unwrap()
+    fn end_seq(&mut self) -> fmt::Result {
+        self.close()
+    }
+
+    fn start_map(&mut self, _len: usize) -> fmt::Result {
+        self.open(false)
+    }
+
+    fn map_key(&mut self, key: &str) -> fmt::Result {
+        self.item()?;
+        self.string(key)?;
+        self.writer.write_char(':')?;
+        if self.indent {
+            self.writer.write_char(' ')?;
+        }
+        Ok(())
+    }
+
+    fn end_map(&mut self) -> fmt::Result {
+        self.close()
+    }
 }
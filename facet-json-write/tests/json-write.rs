@@ -1,5 +1,5 @@
 use facet_derive::Facet;
-use facet_json_write::to_json;
+use facet_json_write::{to_json, to_json_fmt, to_json_string};
 use facet_poke::Peek;
 use facet_trait::Facet;
 
@@ -43,3 +43,143 @@ fn test_to_json() {
     let json = String::from_utf8(buffer).unwrap();
     assert_eq!(json, expected_json_indented);
 }
+
+#[test]
+fn test_to_json_zero_sized_types() {
+    #[derive(Facet)]
+    struct Marker;
+
+    #[derive(Facet)]
+    struct Empty {}
+
+    #[derive(Facet)]
+    struct Holder {
+        marker: Marker,
+        empty: Empty,
+        nothing: (),
+        markers: Vec<Marker>,
+    }
+
+    assert_eq!(to_json_string(Peek::new(&()), false), "null");
+    assert_eq!(to_json_string(Peek::new(&Marker), false), "null");
+    assert_eq!(to_json_string(Peek::new(&Empty {}), false), "{}");
+
+    let holder = Holder {
+        marker: Marker,
+        empty: Empty {},
+        nothing: (),
+        markers: vec![Marker, Marker],
+    };
+    assert_eq!(
+        to_json_string(Peek::new(&holder), false),
+        r#"{"marker":null,"empty":{},"nothing":null,"markers":[null,null]}"#
+    );
+}
+
+#[test]
+fn test_to_json_fmt_fixed_buffer() {
+    /// A `core::fmt::Write` sink backed by a fixed-size array, as found on embedded targets
+    struct FixedBuf<const N: usize> {
+        buf: [u8; N],
+        len: usize,
+    }
+
+    impl<const N: usize> core::fmt::Write for FixedBuf<N> {
+        fn write_str(&mut self, s: &str) -> core::fmt::Result {
+            let end = self.len + s.len();
+            if end > N {
+                return Err(core::fmt::Error);
+            }
+            self.buf[self.len..end].copy_from_slice(s.as_bytes());
+            self.len = end;
+            Ok(())
+        }
+    }
+
+    #[derive(Debug, PartialEq, Clone, Facet)]
+    struct Reading {
+        sensor: String,
+        value: u64,
+    }
+
+    let reading = Reading {
+        sensor: "temp".to_string(),
+        value: 21,
+    };
+
+    let mut sink = FixedBuf::<64> {
+        buf: [0; 64],
+        len: 0,
+    };
+    to_json_fmt(Peek::new(&reading), &mut sink, false).unwrap();
+    assert_eq!(
+        core::str::from_utf8(&sink.buf[..sink.len]).unwrap(),
+        r#"{"sensor":"temp","value":21}"#
+    );
+
+    let mut small = FixedBuf::<8> {
+        buf: [0; 8],
+        len: 0,
+    };
+    assert!(to_json_fmt(Peek::new(&reading), &mut small, false).is_err());
+}
+
+#[test]
+fn test_to_json_io_error() {
+    struct FailingWriter;
+
+    impl std::io::Write for FailingWriter {
+        fn write(&mut self, _buf: &[u8]) -> std::io::Result<usize> {
+            Err(std::io::Error::new(
+                std::io::ErrorKind::BrokenPipe,
+                "pipe closed",
+            ))
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    let value = 42u64;
+    let err = to_json(Peek::new(&value), &mut FailingWriter, false).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::BrokenPipe);
+}
+
+#[test]
+fn test_options_round_trip() {
+    #[derive(Debug, PartialEq, Facet)]
+    struct Point {
+        x: i32,
+        y: i32,
+    }
+
+    #[derive(Debug, PartialEq, Facet)]
+    struct Profile {
+        nickname: Option<String>,
+        age: Option<u8>,
+        home: Option<Point>,
+        scores: Vec<Option<u64>>,
+    }
+
+    let profile = Profile {
+        nickname: Some("\"ada\"\n🦀".to_string()),
+        age: None,
+        home: Some(Point { x: 1, y: -2 }),
+        scores: vec![Some(3), None],
+    };
+    let json = to_json_string(Peek::new(&profile), false);
+    assert_eq!(
+        json,
+        r#"{"nickname":"\"ada\"\n🦀","age":null,"home":{"x":1,"y":-2},"scores":[3,null]}"#
+    );
+    assert_eq!(
+        facet_json_read::from_str::<Profile>(&json).unwrap(),
+        profile
+    );
+
+    // a missing option is `None` too
+    let profile: Profile = facet_json_read::from_str(r#"{"scores":[]}"#).unwrap();
+    assert_eq!(profile.nickname, None);
+    assert_eq!(profile.home, None);
+}
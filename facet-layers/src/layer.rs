@@ -0,0 +1,367 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+
+use facet_peek::{Path, PathSegment, Peek};
+use facet_serialize::{Deserializer, Event, Serializer, serialize};
+use facet_trait::Facet;
+
+/// The values one source of configuration sets, e.g. a file or the environment
+///
+/// A layer only holds what its source sets: fields it doesn't mention are left to the
+/// layers below it. It's a tree in the data model of [`facet_serialize`], so it can be
+/// read from any format's [`Deserializer`], or built key by key with [`Layer::set`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Layer {
+    name: String,
+    root: Option<Node>,
+}
+
+/// A scalar value set by a [`Layer`]
+#[derive(Debug, Clone, PartialEq)]
+pub enum Scalar {
+    /// `None`, or `()`
+    Unit,
+    /// A boolean
+    Bool(bool),
+    /// An unsigned integer
+    U64(u64),
+    /// A signed integer
+    I64(i64),
+    /// A float
+    F64(f64),
+    /// A string, from which any scalar that parses can be built
+    Str(String),
+}
+
+macro_rules! scalar_from {
+    ($($ty:ty => $variant:ident),*) => {
+        $(
+            impl From<$ty> for Scalar {
+                fn from(value: $ty) -> Self {
+                    Scalar::$variant(value.into())
+                }
+            }
+        )*
+    };
+}
+
+scalar_from!(
+    bool => Bool,
+    u8 => U64, u16 => U64, u32 => U64, u64 => U64,
+    i8 => I64, i16 => I64, i32 => I64, i64 => I64,
+    f32 => F64, f64 => F64,
+    String => Str, &str => Str
+);
+
+/// A value of a layer, along with the index of the layer that supplied it once layers
+/// are merged
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Node {
+    pub(crate) kind: NodeKind,
+    pub(crate) layer: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum NodeKind {
+    Scalar(Scalar),
+    Seq(Vec<Node>),
+    Map(Vec<(String, Node)>),
+}
+
+impl Node {
+    fn new(kind: NodeKind) -> Self {
+        Self { kind, layer: 0 }
+    }
+
+    /// Attributes this node and everything in it to `layer`
+    pub(crate) fn stamp(&mut self, layer: usize) {
+        self.layer = layer;
+        match &mut self.kind {
+            NodeKind::Scalar(_) => {}
+            NodeKind::Seq(items) => items.iter_mut().for_each(|item| item.stamp(layer)),
+            NodeKind::Map(members) => members.iter_mut().for_each(|(_, value)| value.stamp(layer)),
+        }
+    }
+}
+
+/// Returned by [`Layer::from_deserializer`]
+#[derive(Debug, Clone, PartialEq)]
+pub enum ReadError<E> {
+    /// The deserializer returned an error
+    Format(E),
+    /// The events don't describe a value, e.g. a key outside of a map
+    Unexpected {
+        /// What was read, see [`Event::describe`]
+        got: &'static str,
+    },
+}
+
+impl<E: fmt::Display> fmt::Display for ReadError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReadError::Format(err) => write!(f, "{err}"),
+            ReadError::Unexpected { got } => write!(f, "Expected a value, got {got}"),
+        }
+    }
+}
+
+impl<E: fmt::Debug + fmt::Display> core::error::Error for ReadError<E> {}
+
+impl Layer {
+    /// Returns a layer named `name` that sets nothing
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            root: None,
+        }
+    }
+
+    /// Returns a layer named `name` that sets every field of `value`, typically the
+    /// compiled defaults at the bottom of the stack
+    pub fn from_value<T: Facet>(name: impl Into<String>, value: &T) -> Self {
+        let mut builder = Builder::default();
+        // the builder never fails, but values the data model can't represent are left out
+        let root = match serialize(Peek::new(value), &mut builder) {
+            Ok(()) => builder.done,
+            Err(_) => None,
+        };
+        Self {
+            name: name.into(),
+            root,
+        }
+    }
+
+    /// Returns a layer named `name` that sets the value read from `deserializer`
+    ///
+    /// # Errors
+    ///
+    /// Returns the first error returned by the deserializer, or an error if the events
+    /// don't describe a value.
+    pub fn from_deserializer<'de, D: Deserializer<'de>>(
+        name: impl Into<String>,
+        deserializer: &mut D,
+    ) -> Result<Self, ReadError<D::Error>> {
+        let first = deserializer.next().map_err(ReadError::Format)?;
+        let root = read(first, deserializer)?;
+        Ok(Self {
+            name: name.into(),
+            root: Some(root),
+        })
+    }
+
+    /// Sets the value at `path`, creating the maps and lists that lead to it
+    ///
+    /// Field and key segments name map members, index segments list items.
+    ///
+    /// # Panics
+    ///
+    /// Panics if an index is past the end of its list: items are added one after the other.
+    pub fn set(mut self, path: &Path, value: impl Into<Scalar>) -> Self {
+        let mut node = self
+            .root
+            .get_or_insert_with(|| Node::new(NodeKind::Map(Vec::new())));
+        for segment in path.segments() {
+            node = match segment {
+                PathSegment::Field(key) | PathSegment::Key(key) => {
+                    if !matches!(node.kind, NodeKind::Map(_)) {
+                        node.kind = NodeKind::Map(Vec::new());
+                    }
+                    let NodeKind::Map(members) = &mut node.kind else {
+                        unreachable!()
+                    };
+                    let index = match members.iter().position(|(name, _)| name == key) {
+                        Some(index) => index,
+                        None => {
+                            let empty = Node::new(NodeKind::Map(Vec::new()));
+                            members.push((key.clone(), empty));
+                            members.len() - 1
+                        }
+                    };
+                    &mut members[index].1
+                }
+                PathSegment::Index(index) => {
+                    if !matches!(node.kind, NodeKind::Seq(_)) {
+                        node.kind = NodeKind::Seq(Vec::new());
+                    }
+                    let NodeKind::Seq(items) = &mut node.kind else {
+                        unreachable!()
+                    };
+                    assert!(
+                        *index <= items.len(),
+                        "index {index} is past the end of the list at `{path}`"
+                    );
+                    if *index == items.len() {
+                        items.push(Node::new(NodeKind::Map(Vec::new())));
+                    }
+                    &mut items[*index]
+                }
+            };
+        }
+        *node = Node::new(NodeKind::Scalar(value.into()));
+        self
+    }
+
+    /// Returns the name of the layer
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub(crate) fn root(&self) -> Option<&Node> {
+        self.root.as_ref()
+    }
+}
+
+/// Reads the value that starts with `first`
+fn read<'de, D: Deserializer<'de>>(
+    first: Event<'de>,
+    de: &mut D,
+) -> Result<Node, ReadError<D::Error>> {
+    let kind = match first {
+        Event::Unit => NodeKind::Scalar(Scalar::Unit),
+        Event::Bool(b) => NodeKind::Scalar(Scalar::Bool(b)),
+        Event::U64(n) => NodeKind::Scalar(Scalar::U64(n)),
+        Event::I64(n) => NodeKind::Scalar(Scalar::I64(n)),
+        Event::F64(n) => NodeKind::Scalar(Scalar::F64(n)),
+        Event::Str(s) => NodeKind::Scalar(Scalar::Str(s.into_owned())),
+        Event::StartSeq => {
+            let mut items = Vec::new();
+            loop {
+                match de.next().map_err(ReadError::Format)? {
+                    Event::EndSeq => break,
+                    event => items.push(read(event, de)?),
+                }
+            }
+            NodeKind::Seq(items)
+        }
+        Event::StartMap => {
+            let mut members = Vec::new();
+            loop {
+                match de.next().map_err(ReadError::Format)? {
+                    Event::EndMap => break,
+                    Event::Key(key) => {
+                        let first = de.next().map_err(ReadError::Format)?;
+                        members.push((key.into_owned(), read(first, de)?));
+                    }
+                    event => {
+                        return Err(ReadError::Unexpected {
+                            got: event.describe(),
+                        });
+                    }
+                }
+            }
+            NodeKind::Map(members)
+        }
+        event => {
+            return Err(ReadError::Unexpected {
+                got: event.describe(),
+            });
+        }
+    };
+    Ok(Node::new(kind))
+}
+
+/// Builds a [`Node`] out of a value being serialized
+#[derive(Default)]
+struct Builder {
+    /// The maps and lists being built, with the key of the next member of maps
+    open: Vec<(NodeKind, Option<String>)>,
+    done: Option<Node>,
+}
+
+impl Builder {
+    fn emit(&mut self, kind: NodeKind) {
+        let node = Node::new(kind);
+        match self.open.last_mut() {
+            Some((NodeKind::Seq(items), _)) => items.push(node),
+            Some((NodeKind::Map(members), key)) => {
+                members.push((key.take().unwrap_or_default(), node));
+            }
+            _ => self.done = Some(node),
+        }
+    }
+
+    fn scalar(&mut self, scalar: Scalar) -> Result<(), core::convert::Infallible> {
+        self.emit(NodeKind::Scalar(scalar));
+        Ok(())
+    }
+
+    fn open(&mut self, kind: NodeKind) -> Result<(), core::convert::Infallible> {
+        self.open.push((kind, None));
+        Ok(())
+    }
+
+    fn close(&mut self) -> Result<(), core::convert::Infallible> {
+        if let Some((kind, _)) = self.open.pop() {
+            self.emit(kind);
+        }
+        Ok(())
+    }
+
+    fn key(&mut self, key: &str) -> Result<(), core::convert::Infallible> {
+        if let Some((_, next)) = self.open.last_mut() {
+            *next = Some(key.into());
+        }
+        Ok(())
+    }
+}
+
+impl Serializer for Builder {
+    type Error = core::convert::Infallible;
+
+    fn serialize_bool(&mut self, value: bool) -> Result<(), Self::Error> {
+        self.scalar(Scalar::Bool(value))
+    }
+
+    fn serialize_u64(&mut self, value: u64) -> Result<(), Self::Error> {
+        self.scalar(Scalar::U64(value))
+    }
+
+    fn serialize_i64(&mut self, value: i64) -> Result<(), Self::Error> {
+        self.scalar(Scalar::I64(value))
+    }
+
+    fn serialize_f64(&mut self, value: f64) -> Result<(), Self::Error> {
+        self.scalar(Scalar::F64(value))
+    }
+
+    fn serialize_str(&mut self, value: &str) -> Result<(), Self::Error> {
+        self.scalar(Scalar::Str(value.into()))
+    }
+
+    fn serialize_unit(&mut self) -> Result<(), Self::Error> {
+        self.scalar(Scalar::Unit)
+    }
+
+    fn start_struct(&mut self, _len: usize) -> Result<(), Self::Error> {
+        self.open(NodeKind::Map(Vec::new()))
+    }
+
+    fn field(&mut self, name: &'static str) -> Result<(), Self::Error> {
+        self.key(name)
+    }
+
+    fn end_struct(&mut self) -> Result<(), Self::Error> {
+        self.close()
+    }
+
+    fn start_seq(&mut self, _len: usize) -> Result<(), Self::Error> {
+        self.open(NodeKind::Seq(Vec::new()))
+    }
+
+    fn end_seq(&mut self) -> Result<(), Self::Error> {
+        self.close()
+    }
+
+    fn start_map(&mut self, _len: usize) -> Result<(), Self::Error> {
+        self.open(NodeKind::Map(Vec::new()))
+    }
+
+    fn map_key(&mut self, key: &str) -> Result<(), Self::Error> {
+        self.key(key)
+    }
+
+    fn end_map(&mut self) -> Result<(), Self::Error> {
+        self.close()
+    }
+}
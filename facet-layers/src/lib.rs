@@ -0,0 +1,17 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+#![warn(missing_docs)]
+#![doc = include_str!("../README.md")]
+
+//! Layered configuration
+//!
+//! Each [`Layer`] holds the values one source sets; [`Layers`] stacks them, merges them
+//! following the shape of the target type, and builds it with [`facet_serialize`]. The
+//! [`Merged`] result tells which layer supplied each leaf.
+
+extern crate alloc;
+
+mod layer;
+pub use layer::{Layer, ReadError, Scalar};
+
+mod merge;
+pub use merge::*;
@@ -0,0 +1,584 @@
+use alloc::borrow::Cow;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::convert::Infallible;
+use core::fmt::{self, Write};
+
+use facet_peek::{Path, PathSegment};
+use facet_serialize::{DeserializeError, Deserializer, Event, deserialize};
+use facet_trait::{Def, Facet, Field, FieldFlags, Shape, StructKind, VariantKind};
+
+use crate::layer::{Layer, Node, NodeKind, Scalar};
+
+/// A stack of [`Layer`]s, from lowest to highest priority
+///
+/// ```rust
+/// use facet_derive::Facet;
+/// use facet_layers::{Layer, Layers};
+/// use facet_trait::{self as facet, Facet};
+///
+/// #[derive(Facet, Default)]
+/// struct Config {
+///     workers: u64,
+///     #[facet(merge = "append")]
+///     features: Vec<String>,
+/// }
+///
+/// let defaults = Config { workers: 1, features: vec!["metrics".to_string()] };
+/// let merged = Layers::new()
+///     .layer(Layer::from_value("defaults", &defaults))
+///     .layer(Layer::new("env").set(&"workers".parse().unwrap(), 4))
+///     .layer(Layer::new("cli").set(&"features[0]".parse().unwrap(), "tracing"))
+///     .merge::<Config>()
+///     .unwrap();
+/// assert_eq!(merged.value().workers, 4);
+/// assert_eq!(merged.value().features, ["metrics", "tracing"]);
+/// assert_eq!(merged.source_of(&"workers".parse().unwrap()), Some("env"));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Layers {
+    layers: Vec<Layer>,
+}
+
+/// How a later layer's list or map combines with the one below it, chosen per field with
+/// `#[facet(merge = "...")]`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Strategy {
+    /// Replace lists, merge maps by key
+    Default,
+    Replace,
+    /// Append to lists, add entries to maps, replacing those with the same key
+    Append,
+    /// Merge map entries with the same key, and list items with the same value of the
+    /// named item field
+    ByKey(Option<&'static str>),
+}
+
+impl Strategy {
+    fn of(field: &Field) -> Self {
+        if field.flags.contains(FieldFlags::MERGE_REPLACE) {
+            Strategy::Replace
+        } else if field.flags.contains(FieldFlags::MERGE_APPEND) {
+            Strategy::Append
+        } else if field.flags.contains(FieldFlags::MERGE_BY_KEY) {
+            Strategy::ByKey(field.merge_key)
+        } else {
+            Strategy::Default
+        }
+    }
+}
+
+impl Layers {
+    /// Returns an empty stack
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds `layer` on top of the stack: its values win over those of the layers below
+    pub fn layer(mut self, layer: Layer) -> Self {
+        self.layers.push(layer);
+        self
+    }
+
+    /// Returns the layers, from lowest to highest priority
+    pub fn layers(&self) -> &[Layer] {
+        &self.layers
+    }
+
+    /// Merges the layers and builds a `T` out of the result
+    ///
+    /// Struct fields merge recursively, while lists and maps follow the strategy of the
+    /// field that holds them:
+    ///
+    /// - `replace`: the later list or map replaces the earlier one. The default for lists.
+    /// - `append`: the later items are appended to the list, and the later entries are
+    ///   added to the map, replacing whole entries with the same key.
+    /// - `by_key`: entries with the same key merge recursively. The default for maps.
+    /// - `by_key(<item field>)`: list items with the same value of the item field, e.g.
+    ///   `by_key(id)`, merge recursively, and other items are appended. On a list,
+    ///   `by_key` without an item field replaces, like the default.
+    ///
+    /// Scalars are always replaced.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the merged values don't make a `T`, naming the layer that
+    /// supplied the offending value.
+    pub fn merge<T: Facet>(&self) -> Result<Merged<T>, MergeError> {
+        let mut merged: Option<Node> = None;
+        for (index, layer) in self.layers.iter().enumerate() {
+            let Some(root) = layer.root() else {
+                continue;
+            };
+            let mut next = root.clone();
+            next.stamp(index);
+            match &mut merged {
+                Some(base) => merge(T::SHAPE, Strategy::Default, base, next),
+                None => merged = Some(next),
+            }
+        }
+        let root = merged.unwrap_or(Node {
+            kind: NodeKind::Map(Vec::new()),
+            layer: 0,
+        });
+
+        let mut provenance = Vec::new();
+        record(
+            T::SHAPE,
+            &root,
+            &mut Path::root(),
+            false,
+            &self.layers,
+            &mut provenance,
+        );
+        match deserialize::<T, _>(&mut Events::new(&root)) {
+            Ok(value) => Ok(Merged { value, provenance }),
+            Err(error) => {
+                let layer = source_of(&provenance, &error.path).map(String::from);
+                Err(MergeError { error, layer })
+            }
+        }
+    }
+}
+
+/// Merges `next` into `base`, both values of `shape`
+fn merge(shape: &'static Shape, strategy: Strategy, base: &mut Node, next: Node) {
+    let shape = unwrap(shape);
+    match (&mut base.kind, next.kind) {
+        (NodeKind::Map(members), NodeKind::Map(next_members)) => match shape.def {
+            Def::Struct(def) => merge_fields(def.fields, members, next_members),
+            Def::Map(_) if strategy == Strategy::Replace => {
+                replace(base, NodeKind::Map(next_members), next.layer)
+            }
+            Def::Map(def) => {
+                for (key, value) in next_members {
+                    match members.iter_mut().find(|(name, _)| *name == key) {
+                        Some((_, existing)) if strategy == Strategy::Append => *existing = value,
+                        Some((_, existing)) => merge(def.v, Strategy::Default, existing, value),
+                        None => members.push((key, value)),
+                    }
+                }
+            }
+            Def::Enum(def) => {
+                // only the fields of the same variant merge, a new variant replaces the old
+                let same_variant = matches!(
+                    (members.as_slice(), next_members.as_slice()),
+                    ([(a, _)], [(b, _)]) if a == b
+                );
+                let variant = members
+                    .first()
+                    .and_then(|(name, _)| def.variants.iter().find(|v| v.name == name));
+                match (same_variant, variant.map(|v| v.kind)) {
+                    (true, Some(VariantKind::Tuple { fields })) if fields.len() == 1 => {
+                        let value = next_members.into_iter().next().unwrap().1;
+                        merge(
+                            fields[0].shape,
+                            Strategy::of(&fields[0]),
+                            &mut members[0].1,
+                            value,
+                        );
+                    }
+                    (true, Some(VariantKind::Struct { fields })) => {
+                        let value = next_members.into_iter().next().unwrap().1;
+                        match (&mut members[0].1.kind, value.kind) {
+                            (NodeKind::Map(members), NodeKind::Map(next_members)) => {
+                                merge_fields(fields, members, next_members)
+                            }
+                            (_, kind) => {
+                                members[0].1 = Node {
+                                    kind,
+                                    layer: value.layer,
+                                }
+                            }
+                        }
+                    }
+                    _ => replace(base, NodeKind::Map(next_members), next.layer),
+                }
+            }
+            _ => replace(base, NodeKind::Map(next_members), next.layer),
+        },
+        (NodeKind::Seq(items), NodeKind::Seq(next_items)) => match shape.def {
+            Def::List(def) => match strategy {
+                Strategy::Append => items.extend(next_items),
+                Strategy::ByKey(Some(key)) => {
+                    for item in next_items {
+                        let existing = item_key(&item, key).and_then(|value| {
+                            items
+                                .iter_mut()
+                                .find(|existing| item_key(existing, key) == Some(value))
+                        });
+                        match existing {
+                            Some(existing) => merge(def.t, Strategy::Default, existing, item),
+                            None => items.push(item),
+                        }
+                    }
+                }
+                _ => replace(base, NodeKind::Seq(next_items), next.layer),
+            },
+            _ => replace(base, NodeKind::Seq(next_items), next.layer),
+        },
+        (_, kind) => replace(base, kind, next.layer),
+    }
+}
+
+fn replace(base: &mut Node, kind: NodeKind, layer: usize) {
+    *base = Node { kind, layer };
+}
+
+/// Returns the value of the member named `key`, which identifies list items merged by key
+fn item_key<'n>(item: &'n Node, key: &str) -> Option<&'n NodeKind> {
+    match &item.kind {
+        NodeKind::Map(members) => members
+            .iter()
+            .find(|(name, _)| name == key)
+            .map(|(_, value)| &value.kind),
+        _ => None,
+    }
+}
+
+/// Merges the members of a struct or a struct variant, member by member
+fn merge_fields(
+    fields: &'static [Field],
+    members: &mut Vec<(String, Node)>,
+    next_members: Vec<(String, Node)>,
+) {
+    for (key, value) in next_members {
+        let existing = members.iter_mut().find(|(name, _)| *name == key);
+        match (existing, find_field(fields, &key)) {
+            (Some((_, existing)), Some(chain)) => {
+                let field = chain[chain.len() - 1];
+                merge(field.shape, Strategy::of(field), existing, value);
+            }
+            (Some((_, existing)), None) => *existing = value,
+            (None, _) => members.push((key, value)),
+        }
+    }
+}
+
+/// Goes through options and single-field tuple structs, which are transparent in layers:
+/// the strategy of the field holding them applies to their value
+fn unwrap(mut shape: &'static Shape) -> &'static Shape {
+    loop {
+        shape = match shape.def {
+            Def::Option(def) => def.t,
+            Def::Struct(def) if def.kind == StructKind::TupleStruct && def.fields.len() == 1 => {
+                def.fields[0].shape
+            }
+            _ => return shape,
+        }
+    }
+}
+
+/// Finds the field named `name`, along with the flattened fields leading to it
+fn find_field(fields: &'static [Field], name: &str) -> Option<Vec<&'static Field>> {
+    fields.iter().find_map(|field| match field.shape.def {
+        Def::Struct(def) if field.flags.contains(FieldFlags::FLATTEN) => {
+            let mut chain = find_field(def.fields, name)?;
+            chain.insert(0, field);
+            Some(chain)
+        }
+        _ if field.name == name => Some(alloc::vec![field]),
+        _ => None,
+    })
+}
+
+/// Records where each leaf of `node` comes from: scalars, empty lists and empty maps
+fn record(
+    shape: &'static Shape,
+    node: &Node,
+    path: &mut Path,
+    sensitive: bool,
+    layers: &[Layer],
+    out: &mut Vec<Provenance>,
+) {
+    let is_option = matches!(shape.def, Def::Option(_));
+    let shape = unwrap(shape);
+    let leaf = |value: String, out: &mut Vec<Provenance>| {
+        out.push(Provenance {
+            path: path.clone(),
+            value: if sensitive {
+                "[REDACTED]".to_string()
+            } else {
+                value
+            },
+            layer: layers[node.layer].name().to_string(),
+        })
+    };
+    match &node.kind {
+        NodeKind::Scalar(scalar) => leaf(render(scalar, is_option), out),
+        NodeKind::Seq(items) if items.is_empty() => leaf("[]".to_string(), out),
+        NodeKind::Map(members) if members.is_empty() => leaf("{}".to_string(), out),
+        NodeKind::Seq(items) => {
+            let item_shape = match shape.def {
+                Def::List(def) => Some(def.t),
+                _ => None,
+            };
+            for (index, item) in items.iter().enumerate() {
+                let item_shape = match (item_shape, shape.def) {
+                    (Some(shape), _) => shape,
+                    (None, Def::Struct(def)) => match def.fields.get(index) {
+                        Some(field) => field.shape,
+                        None => continue,
+                    },
+                    _ => continue,
+                };
+                path.push(PathSegment::Index(index));
+                record(item_shape, item, path, sensitive, layers, out);
+                path.pop();
+            }
+        }
+        NodeKind::Map(members) => {
+            for (key, value) in members {
+                let pushed = match shape.def {
+                    Def::Struct(def) => {
+                        let Some(chain) = find_field(def.fields, key) else {
+                            continue;
+                        };
+                        fields_into(path, &chain);
+                        let field = chain[chain.len() - 1];
+                        let sensitive = sensitive
+                            || chain
+                                .iter()
+                                .any(|f| f.flags.contains(FieldFlags::SENSITIVE));
+                        record(field.shape, value, path, sensitive, layers, out);
+                        chain.len()
+                    }
+                    Def::Map(def) => {
+                        path.push(PathSegment::Key(key.clone()));
+                        record(def.v, value, path, sensitive, layers, out);
+                        1
+                    }
+                    Def::Enum(def) => {
+                        let Some(variant) = def.variants.iter().find(|v| v.name == key) else {
+                            continue;
+                        };
+                        match (variant.kind, &value.kind) {
+                            (VariantKind::Tuple { fields }, _) if fields.len() == 1 => {
+                                fields_into(path, &[&fields[0]]);
+                                record(fields[0].shape, value, path, sensitive, layers, out);
+                                1
+                            }
+                            (
+                                VariantKind::Tuple { fields } | VariantKind::Struct { fields },
+                                NodeKind::Map(_) | NodeKind::Seq(_),
+                            ) => {
+                                record_variant(fields, value, path, sensitive, layers, out);
+                                0
+                            }
+                            _ => continue,
+                        }
+                    }
+                    _ => continue,
+                };
+                for _ in 0..pushed {
+                    path.pop();
+                }
+            }
+        }
+    }
+}
+
+/// Records the fields of a variant with more than one, which are children of the enum
+fn record_variant(
+    fields: &'static [Field],
+    node: &Node,
+    path: &mut Path,
+    sensitive: bool,
+    layers: &[Layer],
+    out: &mut Vec<Provenance>,
+) {
+    let children: Vec<(&'static Field, &Node)> = match &node.kind {
+        NodeKind::Map(members) => members
+            .iter()
+            .filter_map(|(key, value)| Some((fields.iter().find(|f| f.name == key)?, value)))
+            .collect(),
+        NodeKind::Seq(items) => fields.iter().zip(items).collect(),
+        NodeKind::Scalar(_) => Vec::new(),
+    };
+    for (field, value) in children {
+        fields_into(path, &[field]);
+        let sensitive = sensitive || field.flags.contains(FieldFlags::SENSITIVE);
+        record(field.shape, value, path, sensitive, layers, out);
+        path.pop();
+    }
+}
+
+fn fields_into(path: &mut Path, chain: &[&'static Field]) {
+    for field in chain {
+        path.push(PathSegment::Field(field.name.to_string()));
+    }
+}
+
+fn render(scalar: &Scalar, is_option: bool) -> String {
+    match scalar {
+        Scalar::Unit if is_option => "None".to_string(),
+        Scalar::Unit => "()".to_string(),
+        Scalar::Bool(b) => b.to_string(),
+        Scalar::U64(n) => n.to_string(),
+        Scalar::I64(n) => n.to_string(),
+        Scalar::F64(n) => format!("{n:?}"),
+        Scalar::Str(s) => format!("{s:?}"),
+    }
+}
+
+/// Returns the layer that supplied the value at `path`, or the value holding it
+fn source_of<'p>(provenance: &'p [Provenance], path: &Path) -> Option<&'p str> {
+    let segments = path.segments();
+    provenance
+        .iter()
+        .filter(|p| {
+            let other = p.path.segments();
+            other.starts_with(segments) || segments.starts_with(other)
+        })
+        .max_by_key(|p| p.path.segments().len().min(segments.len()))
+        .map(|p| p.layer.as_str())
+}
+
+/// A value merged out of [`Layers`], along with where each of its leaves came from
+#[derive(Debug, Clone)]
+pub struct Merged<T> {
+    value: T,
+    provenance: Vec<Provenance>,
+}
+
+/// Where a leaf of a [`Merged`] value came from
+///
+/// Leaves are scalars, and lists and maps left empty.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Provenance {
+    /// Where the leaf is in the value
+    pub path: Path,
+    /// The leaf, rendered for humans: `[REDACTED]` for sensitive fields
+    pub value: String,
+    /// The name of the layer that supplied it
+    pub layer: String,
+}
+
+impl<T> Merged<T> {
+    /// Returns the merged value
+    pub fn value(&self) -> &T {
+        &self.value
+    }
+
+    /// Returns the merged value, dropping where it came from
+    pub fn into_value(self) -> T {
+        self.value
+    }
+
+    /// Returns where each leaf came from, in the order the leaves appear in the layers
+    pub fn provenance(&self) -> &[Provenance] {
+        &self.provenance
+    }
+
+    /// Returns the name of the layer that supplied the value at `path`
+    ///
+    /// For a list or a map that several layers contributed to, that's the layer of its
+    /// first leaf; use [`Merged::provenance`] for the details.
+    pub fn source_of(&self, path: &Path) -> Option<&str> {
+        source_of(&self.provenance, path)
+    }
+
+    /// Lists every leaf of the value, with the layer that supplied it, one per line:
+    /// what an `--explain-config` flag would print
+    pub fn explain(&self) -> String {
+        let mut out = String::new();
+        for leaf in &self.provenance {
+            let path = if leaf.path.is_root() {
+                "(root)".to_string()
+            } else {
+                leaf.path.to_string()
+            };
+            let _ = writeln!(out, "{path} = {}  ({})", leaf.value, leaf.layer);
+        }
+        out
+    }
+}
+
+/// Returned by [`Layers::merge`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct MergeError {
+    /// Why the merged values don't make the value
+    pub error: DeserializeError<Infallible>,
+    /// The layer that supplied the offending value, if it's known
+    pub layer: Option<String>,
+}
+
+impl fmt::Display for MergeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.error)?;
+        if let Some(layer) = &self.layer {
+            write!(f, " (from {layer})")?;
+        }
+        Ok(())
+    }
+}
+
+impl core::error::Error for MergeError {}
+
+/// Reads a merged [`Node`] as a stream of events
+struct Events<'n> {
+    pending: Vec<Pending<'n>>,
+}
+
+enum Pending<'n> {
+    Node(&'n Node),
+    Items(core::slice::Iter<'n, Node>),
+    Members(core::slice::Iter<'n, (String, Node)>),
+}
+
+impl<'n> Events<'n> {
+    fn new(root: &'n Node) -> Self {
+        Self {
+            pending: alloc::vec![Pending::Node(root)],
+        }
+    }
+}
+
+impl<'n> Deserializer<'n> for Events<'n> {
+    type Error = Infallible;
+
+    fn next(&mut self) -> Result<Event<'n>, Self::Error> {
+        loop {
+            let pending = self
+                .pending
+                .pop()
+                .expect("the deserializer reads a single value");
+            match pending {
+                Pending::Node(node) => {
+                    return Ok(match &node.kind {
+                        NodeKind::Scalar(Scalar::Unit) => Event::Unit,
+                        NodeKind::Scalar(Scalar::Bool(b)) => Event::Bool(*b),
+                        NodeKind::Scalar(Scalar::U64(n)) => Event::U64(*n),
+                        NodeKind::Scalar(Scalar::I64(n)) => Event::I64(*n),
+                        NodeKind::Scalar(Scalar::F64(n)) => Event::F64(*n),
+                        NodeKind::Scalar(Scalar::Str(s)) => Event::Str(Cow::Borrowed(s)),
+                        NodeKind::Seq(items) => {
+                            self.pending.push(Pending::Items(items.iter()));
+                            Event::StartSeq
+                        }
+                        NodeKind::Map(members) => {
+                            self.pending.push(Pending::Members(members.iter()));
+                            Event::StartMap
+                        }
+                    });
+                }
+                Pending::Items(mut items) => match items.next() {
+                    Some(item) => {
+                        self.pending.push(Pending::Items(items));
+                        self.pending.push(Pending::Node(item));
+                    }
+                    None => return Ok(Event::EndSeq),
+                },
+                Pending::Members(mut members) => match members.next() {
+                    Some((key, value)) => {
+                        self.pending.push(Pending::Members(members));
+                        self.pending.push(Pending::Node(value));
+                        return Ok(Event::Key(Cow::Borrowed(key)));
+                    }
+                    None => return Ok(Event::EndMap),
+                },
+            }
+        }
+    }
+}
@@ -0,0 +1,368 @@
+use std::borrow::Cow;
+use std::collections::{HashMap, VecDeque};
+
+use facet_derive::Facet;
+use facet_layers::{Layer, Layers, ReadError};
+use facet_peek::Path;
+use facet_serialize::{Deserializer, Event};
+use facet_testhelpers::Source;
+use facet_trait::Facet;
+
+use facet_trait as facet;
+
+/// Replays a list of events, like a file format would produce them
+struct Events(VecDeque<Event<'static>>);
+
+impl Deserializer<'static> for Events {
+    type Error = &'static str;
+
+    fn next(&mut self) -> Result<Event<'static>, Self::Error> {
+        self.0.pop_front().ok_or("unexpected end of input")
+    }
+}
+
+fn events(events: impl IntoIterator<Item = Event<'static>>) -> Events {
+    Events(events.into_iter().collect())
+}
+
+fn key(key: &'static str) -> Event<'static> {
+    Event::Key(Cow::Borrowed(key))
+}
+
+fn path(path: &str) -> Path {
+    path.parse().unwrap()
+}
+
+#[derive(Facet, Debug, PartialEq)]
+struct Config {
+    name: String,
+    workers: u64,
+    database: Database,
+    #[facet(merge = "append")]
+    features: Vec<String>,
+    hosts: Vec<String>,
+    labels: HashMap<String, String>,
+    #[facet(merge = "replace")]
+    limits: HashMap<String, u64>,
+    source: Source,
+}
+
+#[derive(Facet, Debug, PartialEq)]
+struct Database {
+    url: String,
+    #[facet(sensitive)]
+    password: Option<String>,
+    pool: u64,
+}
+
+fn defaults() -> Config {
+    Config {
+        name: "app".to_string(),
+        workers: 1,
+        database: Database {
+            url: "postgres://localhost".to_string(),
+            password: None,
+            pool: 5,
+        },
+        features: vec!["metrics".to_string()],
+        hosts: vec!["localhost".to_string()],
+        labels: HashMap::from([("tier".to_string(), "web".to_string())]),
+        limits: HashMap::from([("cpu".to_string(), 1), ("memory".to_string(), 256)]),
+        source: Source::Remote {
+            url: "https://config.example.com".to_string(),
+            retries: 3,
+        },
+    }
+}
+
+fn file() -> Layer {
+    Layer::from_deserializer(
+        "app.toml",
+        &mut events([
+            Event::StartMap,
+            key("database"),
+            Event::StartMap,
+            key("url"),
+            Event::Str(Cow::Borrowed("postgres://db.internal")),
+            key("pool"),
+            Event::U64(20),
+            Event::EndMap,
+            key("features"),
+            Event::StartSeq,
+            Event::Str(Cow::Borrowed("tracing")),
+            Event::EndSeq,
+            key("hosts"),
+            Event::StartSeq,
+            Event::Str(Cow::Borrowed("a.internal")),
+            Event::Str(Cow::Borrowed("b.internal")),
+            Event::EndSeq,
+            key("labels"),
+            Event::StartMap,
+            key("team"),
+            Event::Str(Cow::Borrowed("core")),
+            Event::EndMap,
+            key("limits"),
+            Event::StartMap,
+            key("cpu"),
+            Event::U64(4),
+            Event::EndMap,
+            key("source"),
+            Event::StartMap,
+            key("Remote"),
+            Event::StartMap,
+            key("retries"),
+            Event::U64(5),
+            Event::EndMap,
+            Event::EndMap,
+            Event::EndMap,
+        ]),
+    )
+    .unwrap()
+}
+
+fn env() -> Layer {
+    Layer::new("env")
+        .set(&path("workers"), "4")
+        .set(&path("database.password"), "hunter2")
+        .set(&path("features[0]"), "profiling")
+}
+
+#[test]
+fn later_layers_win_and_strategies_apply() {
+    let merged = Layers::new()
+        .layer(Layer::from_value("defaults", &defaults()))
+        .layer(file())
+        .layer(env())
+        .merge::<Config>()
+        .unwrap();
+
+    let mut expected = defaults();
+    expected.workers = 4;
+    expected.database = Database {
+        url: "postgres://db.internal".to_string(),
+        password: Some("hunter2".to_string()),
+        pool: 20,
+    };
+    expected.features = vec![
+        "metrics".to_string(),
+        "tracing".to_string(),
+        "profiling".to_string(),
+    ];
+    expected.hosts = vec!["a.internal".to_string(), "b.internal".to_string()];
+    expected
+        .labels
+        .insert("team".to_string(), "core".to_string());
+    expected.limits = HashMap::from([("cpu".to_string(), 4)]);
+    expected.source = Source::Remote {
+        url: "https://config.example.com".to_string(),
+        retries: 5,
+    };
+    assert_eq!(merged.value(), &expected);
+}
+
+#[test]
+fn leaves_remember_their_layer() {
+    let merged = Layers::new()
+        .layer(Layer::from_value("defaults", &defaults()))
+        .layer(file())
+        .layer(env())
+        .merge::<Config>()
+        .unwrap();
+
+    let source_of = |p: &str| merged.source_of(&path(p));
+    assert_eq!(source_of("name"), Some("defaults"));
+    assert_eq!(source_of("workers"), Some("env"));
+    assert_eq!(source_of("database.url"), Some("app.toml"));
+    assert_eq!(source_of("database.password"), Some("env"));
+    assert_eq!(source_of("features[0]"), Some("defaults"));
+    assert_eq!(source_of("features[1]"), Some("app.toml"));
+    assert_eq!(source_of("features[2]"), Some("env"));
+    assert_eq!(source_of("labels[\"tier\"]"), Some("defaults"));
+    assert_eq!(source_of("labels[\"team\"]"), Some("app.toml"));
+    assert_eq!(source_of("source.url"), Some("defaults"));
+    assert_eq!(source_of("source.retries"), Some("app.toml"));
+    assert_eq!(source_of("missing"), None);
+
+    let explain = merged.explain();
+    assert!(explain.contains("workers = \"4\"  (env)\n"), "{explain}");
+    assert!(
+        explain.contains("database.password = [REDACTED]  (env)\n"),
+        "{explain}"
+    );
+    assert!(
+        explain.contains("database.url = \"postgres://db.internal\"  (app.toml)\n"),
+        "{explain}"
+    );
+    assert!(!explain.contains("hunter2"), "{explain}");
+}
+
+#[test]
+fn list_items_merge_by_their_key_field() {
+    #[derive(Facet, Debug, PartialEq)]
+    struct Deployment {
+        #[facet(merge = "by_key(name)")]
+        services: Vec<Service>,
+    }
+
+    #[derive(Facet, Debug, PartialEq)]
+    struct Service {
+        name: String,
+        port: u16,
+        replicas: u64,
+    }
+
+    let service = |name: &str, port, replicas| Service {
+        name: name.to_string(),
+        port,
+        replicas,
+    };
+    let defaults = Deployment {
+        services: vec![service("api", 80, 1), service("worker", 81, 1)],
+    };
+    // items are matched by name, not by position
+    let file = Layer::new("file")
+        .set(&path("services[0].name"), "jobs")
+        .set(&path("services[0].port"), 90u64)
+        .set(&path("services[0].replicas"), 1u64)
+        .set(&path("services[1].name"), "worker")
+        .set(&path("services[1].replicas"), 3u64);
+    let merged = Layers::new()
+        .layer(Layer::from_value("defaults", &defaults))
+        .layer(file)
+        .merge::<Deployment>()
+        .unwrap();
+    assert_eq!(
+        merged.value().services,
+        [
+            service("api", 80, 1),
+            service("worker", 81, 3),
+            service("jobs", 90, 1)
+        ]
+    );
+    assert_eq!(
+        merged.source_of(&path("services[1].port")),
+        Some("defaults")
+    );
+    assert_eq!(
+        merged.source_of(&path("services[1].replicas")),
+        Some("file")
+    );
+}
+
+#[test]
+fn map_entries_are_replaced_by_append_and_merged_by_default() {
+    #[derive(Facet, Debug, PartialEq)]
+    struct Routes {
+        #[facet(merge = "append")]
+        appended: HashMap<String, Route>,
+        merged: HashMap<String, Route>,
+    }
+
+    #[derive(Facet, Debug, PartialEq)]
+    struct Route {
+        host: String,
+        port: Option<u16>,
+    }
+
+    let route = |host: &str, port| Route {
+        host: host.to_string(),
+        port,
+    };
+    let defaults = Routes {
+        appended: HashMap::from([("api".to_string(), route("localhost", Some(8080)))]),
+        merged: HashMap::from([("api".to_string(), route("localhost", Some(8080)))]),
+    };
+    let file = Layer::new("file")
+        .set(&path("appended[\"api\"].host"), "api.internal")
+        .set(&path("appended[\"web\"].host"), "web.internal")
+        .set(&path("merged[\"api\"].host"), "api.internal")
+        .set(&path("merged[\"web\"].host"), "web.internal");
+    let merged = Layers::new()
+        .layer(Layer::from_value("defaults", &defaults))
+        .layer(file)
+        .merge::<Routes>()
+        .unwrap();
+
+    // `append` replaces the whole entry, dropping the port set below
+    assert_eq!(
+        merged.value().appended,
+        HashMap::from([
+            ("api".to_string(), route("api.internal", None)),
+            ("web".to_string(), route("web.internal", None)),
+        ])
+    );
+    // the default merges the entry with the one below
+    assert_eq!(
+        merged.value().merged,
+        HashMap::from([
+            ("api".to_string(), route("api.internal", Some(8080))),
+            ("web".to_string(), route("web.internal", None)),
+        ])
+    );
+}
+
+#[test]
+fn explain_lists_every_leaf() {
+    #[derive(Facet, Debug, PartialEq)]
+    struct Small {
+        port: u16,
+        token: Option<String>,
+        tags: Vec<String>,
+    }
+
+    let merged = Layers::new()
+        .layer(Layer::from_value(
+            "defaults",
+            &Small {
+                port: 80,
+                token: None,
+                tags: vec![],
+            },
+        ))
+        .layer(Layer::new("cli").set(&path("port"), 8080u64))
+        .merge::<Small>()
+        .unwrap();
+    assert_eq!(
+        merged.explain(),
+        "port = 8080  (cli)\ntoken = None  (defaults)\ntags = []  (defaults)\n"
+    );
+}
+
+#[test]
+fn variants_merge_only_with_themselves() {
+    let file = Layer::new("file").set(&path("source.File"), "app.toml");
+    let merged = Layers::new()
+        .layer(Layer::from_value("defaults", &defaults()))
+        .layer(file)
+        .merge::<Config>()
+        .unwrap();
+    assert_eq!(merged.value().source, Source::File("app.toml".to_string()));
+
+    let cli = Layer::new("cli").set(&path("source"), "Disabled");
+    let merged = Layers::new()
+        .layer(Layer::from_value("defaults", &defaults()))
+        .layer(cli)
+        .merge::<Config>()
+        .unwrap();
+    assert_eq!(merged.value().source, Source::Disabled);
+    assert_eq!(merged.source_of(&path("source")), Some("cli"));
+}
+
+#[test]
+fn errors_name_the_layer() {
+    let err = Layers::new()
+        .layer(Layer::from_value("defaults", &defaults()))
+        .layer(Layer::new("env").set(&path("database.pool"), "many"))
+        .merge::<Config>()
+        .unwrap_err();
+    assert_eq!(err.layer.as_deref(), Some("env"));
+    assert_eq!(err.error.path, path("database.pool"));
+    assert!(err.to_string().ends_with("(from env)"), "{err}");
+
+    // without defaults, required fields are missing
+    let err = Layers::new().layer(env()).merge::<Config>().unwrap_err();
+    assert!(err.to_string().contains("Missing field"), "{err}");
+
+    let err = Layer::from_deserializer("file", &mut events([key("oops")])).unwrap_err();
+    assert_eq!(err, ReadError::Unexpected { got: "a map key" });
+}
@@ -12,6 +12,12 @@ pub enum Error {
     InvalidData,
     /// Encountered a field name that isn't recognized
     UnknownField(String),
+    /// A field wasn't in the data, and has no default
+    MissingField(String),
+    /// Encountered a variant name that isn't recognized
+    UnknownVariant(String),
+    /// The data doesn't describe the type being deserialized
+    Mismatch(String),
 }
 
 impl fmt::Display for Error {
@@ -21,6 +27,9 @@ impl fmt::Display for Error {
             Error::InsufficientData => write!(f, "Insufficient data to decode"),
             Error::InvalidData => write!(f, "Invalid MessagePack data"),
             Error::UnknownField(field) => write!(f, "Unknown field: {}", field),
+            Error::MissingField(field) => write!(f, "Missing field: {}", field),
+            Error::UnknownVariant(variant) => write!(f, "Unknown variant: {}", variant),
+            Error::Mismatch(message) => write!(f, "{}", message),
         }
     }
 }
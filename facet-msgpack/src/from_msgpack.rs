@@ -1,9 +1,9 @@
 use crate::constants::*;
 use crate::errors::Error as DecodeError;
 use facet_poke::Poke;
-use facet_trait::ShapeExt as _;
-use facet_trait::{Facet, Opaque, OpaqueConst};
-use log::trace;
+use facet_serialize::{DeserializeErrorKind, Deserializer, Event, deserialize_opaque};
+use facet_trait::{Facet, Opaque};
+use std::borrow::Cow;
 
 /// Deserializes MessagePack-encoded data into a type that implements `Facet`.
 ///
@@ -80,69 +80,55 @@ pub fn from_str<T: Facet>(msgpack: &[u8]) -> Result<T, DecodeError> {
 /// # MessagePack Format
 /// This implementation follows the MessagePack specification:
 /// <https://github.com/msgpack/msgpack/blob/master/spec.md>
-#[allow(clippy::needless_lifetimes)]
 pub fn from_slice_opaque<'mem>(
     poke: Poke<'mem>,
     msgpack: &[u8],
 ) -> Result<Opaque<'mem>, DecodeError> {
     let mut decoder = Decoder::new(msgpack);
+    deserialize_opaque(poke, &mut decoder).map_err(|err| {
+        let message = err.to_string();
+        match err.kind {
+            DeserializeErrorKind::Format(err) => err,
+            DeserializeErrorKind::UnknownField(name) => DecodeError::UnknownField(name),
+            DeserializeErrorKind::MissingField(name) => DecodeError::MissingField(name.into()),
+            DeserializeErrorKind::UnknownVariant(name) => DecodeError::UnknownVariant(name),
+            _ => DecodeError::Mismatch(message),
+        }
+    })
+}
 
-    fn deserialize_value<'mem>(
-        decoder: &mut Decoder,
-        poke: Poke<'mem>,
-    ) -> Result<Opaque<'mem>, DecodeError> {
-        let shape = poke.shape();
-        trace!("Deserializing {:?}", shape);
-
-        let opaque = match poke {
-            Poke::Scalar(pv) => {
-                trace!("Deserializing scalar");
-                if pv.shape().is_type::<String>() {
-                    let s = decoder.decode_string()?;
-                    let data = unsafe { pv.put(OpaqueConst::from_ref(&s)) };
-                    core::mem::forget(s);
-                    data
-                } else if pv.shape().is_type::<u64>() {
-                    let n = decoder.decode_u64()?;
-                    unsafe { pv.put(OpaqueConst::from_ref(&n)) }
-                } else {
-                    todo!("Unsupported scalar type: {}", pv.shape())
-                }
-            }
-            Poke::Struct(mut ps) => {
-                trace!("Deserializing struct");
-                let map_len = decoder.decode_map_len()?;
-
-                for _ in 0..map_len {
-                    let key = decoder.decode_string()?;
-                    let (index, field_poke) = ps
-                        .field_by_name(&key)
-                        .map_err(|_| DecodeError::UnknownField(key))?;
-
-                    deserialize_value(decoder, field_poke)?;
-                    unsafe { ps.mark_initialized(index) };
-                }
-                ps.build_in_place()
-            }
-            _ => {
-                todo!("Unsupported shape: {:?}", shape)
-            }
-        };
-
-        Ok(opaque)
-    }
-
-    deserialize_value(&mut decoder, poke)
+/// A map or array being read: whether it's a map, how many of its entries are left, and
+/// for a map, whether a key comes next
+struct Container {
+    map: bool,
+    remaining: usize,
+    key_next: bool,
 }
 
 struct Decoder<'input> {
     input: &'input [u8],
     offset: usize,
+    /// The maps and arrays being read, innermost last
+    containers: Vec<Container>,
 }
 
 impl<'input> Decoder<'input> {
     fn new(input: &'input [u8]) -> Self {
-        Decoder { input, offset: 0 }
+        Decoder {
+            input,
+            offset: 0,
+            containers: Vec::new(),
+        }
+    }
+
+    /// Takes the next `len` bytes of the input
+    fn take(&mut self, len: usize) -> Result<&'input [u8], DecodeError> {
+        if self.offset + len > self.input.len() {
+            return Err(DecodeError::InsufficientData);
+        }
+        let bytes = &self.input[self.offset..self.offset + len];
+        self.offset += len;
+        Ok(bytes)
     }
 
     /// Decodes a single byte from the input.
@@ -180,79 +166,122 @@ impl<'input> Decoder<'input> {
         Ok(value)
     }
 
-    /// Decodes a MessagePack-encoded unsigned 64-bit integer.
-    /// Handles the following MessagePack types:
-    /// - positive fixint (0x00 - 0x7f): single-byte positive integer
-    /// - uint8 (0xcc): 8-bit unsigned integer
-    /// - uint16 (0xcd): 16-bit unsigned integer (big-endian)
-    /// - uint32 (0xce): 32-bit unsigned integer (big-endian)
-    /// - uint64 (0xcf): 64-bit unsigned integer (big-endian)
-    ///
-    /// Ref: <https://github.com/msgpack/msgpack/blob/master/spec.md#int-format-family>
+    /// Decodes a 64-bit unsigned integer in big-endian byte order.
+    /// This is a low-level method used by other decoders.
     fn decode_u64(&mut self) -> Result<u64, DecodeError> {
-        match self.decode_u8()? {
-            MSGPACK_UINT8 => Ok(self.decode_u8()? as u64),
-            MSGPACK_UINT16 => Ok(self.decode_u16()? as u64),
-            MSGPACK_UINT32 => Ok(self.decode_u32()? as u64),
-            MSGPACK_UINT64 => {
-                if self.offset + 8 > self.input.len() {
-                    return Err(DecodeError::InsufficientData);
-                }
-                let value = u64::from_be_bytes(
-                    self.input[self.offset..self.offset + 8].try_into().unwrap(),
-                );
-                self.offset += 8;
-                Ok(value)
-            }
-            prefix @ MSGPACK_POSFIXINT_MIN..=MSGPACK_POSFIXINT_MAX => Ok(prefix as u64),
-            _ => Err(DecodeError::UnexpectedType),
-        }
+        Ok(u64::from_be_bytes(self.take(8)?.try_into().unwrap()))
     }
 
-    /// Decodes a MessagePack-encoded string.
-    /// Handles the following MessagePack types:
-    /// - fixstr (0xa0 - 0xbf): string up to 31 bytes
-    /// - str8 (0xd9): string up to 255 bytes
-    /// - str16 (0xda): string up to 65535 bytes
-    /// - str32 (0xdb): string up to 4294967295 bytes
+    /// Decodes a string of `len` bytes, borrowed from the input.
     ///
     /// Ref: <https://github.com/msgpack/msgpack/blob/master/spec.md#formats-str>
-    fn decode_string(&mut self) -> Result<String, DecodeError> {
-        let prefix = self.decode_u8()?;
-
-        let len = match prefix {
-            prefix @ MSGPACK_FIXSTR_MIN..=MSGPACK_FIXSTR_MAX => (prefix & 0x1f) as usize,
-            MSGPACK_STR8 => self.decode_u8()? as usize,
-            MSGPACK_STR16 => self.decode_u16()? as usize,
-            MSGPACK_STR32 => self.decode_u32()? as usize,
-            _ => return Err(DecodeError::UnexpectedType),
-        };
+    fn decode_str(&mut self, len: usize) -> Result<&'input str, DecodeError> {
+        core::str::from_utf8(self.take(len)?).map_err(|_| DecodeError::InvalidData)
+    }
 
-        if self.offset + len > self.input.len() {
-            return Err(DecodeError::InsufficientData);
+    /// Starts a map or an array of `len` entries
+    fn open(&mut self, map: bool, len: usize) -> Event<'input> {
+        self.containers.push(Container {
+            map,
+            remaining: len,
+            key_next: map,
+        });
+        if map {
+            Event::StartMap
+        } else {
+            Event::StartSeq
         }
-
-        let value = String::from_utf8(self.input[self.offset..self.offset + len].to_vec())
-            .map_err(|_| DecodeError::InvalidData)?;
-        self.offset += len;
-        Ok(value)
     }
 
-    /// Decodes a MessagePack-encoded map length.
-    /// Handles the following MessagePack types:
-    /// - fixmap (0x80 - 0x8f): map with up to 15 elements
-    /// - map16 (0xde): map with up to 65535 elements
-    /// - map32 (0xdf): map with up to 4294967295 elements
+    /// Decodes a MessagePack-encoded value, or the start of one for maps and arrays.
+    /// Handles nil, booleans, the int, float and str format families, maps and arrays:
+    /// bin and ext values can't be decoded.
     ///
-    /// Ref: <https://github.com/msgpack/msgpack/blob/master/spec.md#formats-map>
-    fn decode_map_len(&mut self) -> Result<usize, DecodeError> {
-        let prefix = self.decode_u8()?;
+    /// Ref: <https://github.com/msgpack/msgpack/blob/master/spec.md#formats>
+    fn decode_value(&mut self) -> Result<Event<'input>, DecodeError> {
+        let event = match self.decode_u8()? {
+            MSGPACK_NIL => Event::Unit,
+            MSGPACK_FALSE => Event::Bool(false),
+            MSGPACK_TRUE => Event::Bool(true),
+            prefix @ MSGPACK_POSFIXINT_MIN..=MSGPACK_POSFIXINT_MAX => Event::U64(prefix as u64),
+            MSGPACK_UINT8 => Event::U64(self.decode_u8()? as u64),
+            MSGPACK_UINT16 => Event::U64(self.decode_u16()? as u64),
+            MSGPACK_UINT32 => Event::U64(self.decode_u32()? as u64),
+            MSGPACK_UINT64 => Event::U64(self.decode_u64()?),
+            MSGPACK_INT8 => Event::I64(self.decode_u8()? as i8 as i64),
+            MSGPACK_INT16 => Event::I64(self.decode_u16()? as i16 as i64),
+            MSGPACK_INT32 => Event::I64(self.decode_u32()? as i32 as i64),
+            MSGPACK_INT64 => Event::I64(self.decode_u64()? as i64),
+            prefix if (prefix as i8) >= MSGPACK_NEGFIXINT_MIN => Event::I64(prefix as i8 as i64),
+            MSGPACK_FLOAT32 => Event::F64(f32::from_bits(self.decode_u32()?) as f64),
+            MSGPACK_FLOAT64 => Event::F64(f64::from_bits(self.decode_u64()?)),
+            prefix @ MSGPACK_FIXSTR_MIN..=MSGPACK_FIXSTR_MAX => {
+                Event::Str(Cow::Borrowed(self.decode_str((prefix & 0x1f) as usize)?))
+            }
+            MSGPACK_STR8 => {
+                let len = self.decode_u8()? as usize;
+                Event::Str(Cow::Borrowed(self.decode_str(len)?))
+            }
+            MSGPACK_STR16 => {
+                let len = self.decode_u16()? as usize;
+                Event::Str(Cow::Borrowed(self.decode_str(len)?))
+            }
+            MSGPACK_STR32 => {
+                let len = self.decode_u32()? as usize;
+                Event::Str(Cow::Borrowed(self.decode_str(len)?))
+            }
+            prefix @ MSGPACK_FIXMAP_MIN..=MSGPACK_FIXMAP_MAX => {
+                self.open(true, (prefix & 0x0f) as usize)
+            }
+            MSGPACK_MAP16 => {
+                let len = self.decode_u16()? as usize;
+                self.open(true, len)
+            }
+            MSGPACK_MAP32 => {
+                let len = self.decode_u32()? as usize;
+                self.open(true, len)
+            }
+            prefix @ MSGPACK_FIXARRAY_MIN..=MSGPACK_FIXARRAY_MAX => {
+                self.open(false, (prefix & 0x0f) as usize)
+            }
+            MSGPACK_ARRAY16 => {
+                let len = self.decode_u16()? as usize;
+                self.open(false, len)
+            }
+            MSGPACK_ARRAY32 => {
+                let len = self.decode_u32()? as usize;
+                self.open(false, len)
+            }
+            _ => return Err(DecodeError::UnexpectedType),
+        };
+        Ok(event)
+    }
+}
 
-        match prefix {
-            prefix @ MSGPACK_FIXMAP_MIN..=MSGPACK_FIXMAP_MAX => Ok((prefix & 0x0f) as usize),
-            MSGPACK_MAP16 => Ok(self.decode_u16()? as usize),
-            MSGPACK_MAP32 => Ok(self.decode_u32()? as usize),
-            _ => Err(DecodeError::UnexpectedType),
+impl<'input> Deserializer<'input> for Decoder<'input> {
+    type Error = DecodeError;
+
+    fn next(&mut self) -> Result<Event<'input>, DecodeError> {
+        if let Some(container) = self.containers.last_mut() {
+            if container.map && !container.key_next {
+                // The value of the entry whose key was just read
+                container.key_next = true;
+            } else if container.remaining == 0 {
+                let map = container.map;
+                self.containers.pop();
+                return Ok(if map { Event::EndMap } else { Event::EndSeq });
+            } else {
+                container.remaining -= 1;
+                if container.map {
+                    container.key_next = false;
+                    return match self.decode_value()? {
+                        Event::Str(key) => Ok(Event::Key(key)),
+                        // Only string keys are supported
+                        _ => Err(DecodeError::UnexpectedType),
+                    };
+                }
+            }
         }
+        self.decode_value()
     }
 }
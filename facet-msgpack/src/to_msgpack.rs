@@ -1,70 +1,102 @@
+use crate::constants::*;
 use facet_peek::Peek;
-use facet_trait::{Facet, ShapeExt as _};
-use log::trace;
+use facet_serialize::{SerializeErrorKind, Serializer, serialize};
+use facet_trait::Facet;
 use std::io::{self, Write};
 
 /// Serializes any Facet type to MessagePack bytes
+///
+/// Values are written with [`facet_serialize`]'s data model: `None` is nil, `Some(value)`
+/// is just the value, structs are maps keyed by field name, and enums are externally
+/// tagged.
+///
+/// # Panics
+///
+/// Panics if the value can't be represented: a scalar the data model doesn't know about
+/// that doesn't implement `Display`, or a value that contains itself.
 pub fn to_vec<T: Facet>(value: &T) -> Vec<u8> {
     let mut buffer = Vec::new();
-    let peek = Peek::new(value);
-    serialize(peek, &mut buffer).unwrap();
+    to_writer(Peek::new(value), &mut buffer).unwrap();
     buffer
 }
 
 /// Serializes any Facet type to a writer in MessagePack format
-fn serialize<W: Write>(peek: Peek<'_>, writer: &mut W) -> io::Result<()> {
-    match peek {
-        Peek::Value(pv) => {
-            trace!("Serializing scalar");
-            if pv.shape().is_type::<String>() {
-                let value = unsafe { pv.data().as_ref::<String>() };
-                write_str(writer, value)
-            } else if pv.shape().is_type::<u64>() {
-                let value = unsafe { pv.data().as_ref::<u64>() };
-                write_u64(writer, *value)
-            } else if pv.shape().is_type::<u32>() {
-                let value = unsafe { pv.data().as_ref::<u32>() };
-                write_u32(writer, *value)
-            } else if pv.shape().is_type::<u16>() {
-                let value = unsafe { pv.data().as_ref::<u16>() };
-                write_u16(writer, *value)
-            } else if pv.shape().is_type::<u8>() {
-                let value = unsafe { pv.data().as_ref::<u8>() };
-                write_u8(writer, *value)
-            } else if pv.shape().is_type::<i64>() {
-                let value = unsafe { pv.data().as_ref::<i64>() };
-                write_i64(writer, *value)
-            } else if pv.shape().is_type::<i32>() {
-                let value = unsafe { pv.data().as_ref::<i32>() };
-                write_i32(writer, *value)
-            } else if pv.shape().is_type::<i16>() {
-                let value = unsafe { pv.data().as_ref::<i16>() };
-                write_i16(writer, *value)
-            } else if pv.shape().is_type::<i8>() {
-                let value = unsafe { pv.data().as_ref::<i8>() };
-                write_i8(writer, *value)
-            } else {
-                todo!("Unsupported scalar type: {}", pv.shape())
-            }
-        }
-        Peek::Struct(ps) => {
-            trace!("Serializing struct");
+fn to_writer<W: Write>(peek: Peek<'_>, writer: &mut W) -> io::Result<()> {
+    serialize(peek, &mut MsgpackWriter { writer }).map_err(|err| match err.kind {
+        SerializeErrorKind::Format(err) => err,
+        _ => io::Error::other(err.to_string()),
+    })
+}
 
-            // Write map header
-            let fields: Vec<_> = ps.fields().collect();
-            write_map_len(writer, fields.len())?;
+/// Writes the calls of [`serialize`] as MessagePack
+struct MsgpackWriter<'w, W> {
+    writer: &'w mut W,
+}
 
-            // Write fields
-            for (name, field_peek) in fields {
-                write_str(writer, name)?;
-                serialize(field_peek, writer)?;
-            }
-            Ok(())
-        }
-        _ => {
-            todo!("Unsupported type: {:?}", peek)
-        }
+impl<W: Write> Serializer for MsgpackWriter<'_, W> {
+    type Error = io::Error;
+
+    fn serialize_bool(&mut self, value: bool) -> io::Result<()> {
+        self.writer
+            .write_all(&[if value { MSGPACK_TRUE } else { MSGPACK_FALSE }])
+    }
+
+    fn serialize_u64(&mut self, value: u64) -> io::Result<()> {
+        write_u64(self.writer, value)
+    }
+
+    fn serialize_i64(&mut self, value: i64) -> io::Result<()> {
+        write_i64(self.writer, value)
+    }
+
+    fn serialize_f64(&mut self, value: f64) -> io::Result<()> {
+        self.writer.write_all(&[MSGPACK_FLOAT64])?;
+        self.writer.write_all(&value.to_be_bytes())
+    }
+
+    fn serialize_str(&mut self, value: &str) -> io::Result<()> {
+        write_str(self.writer, value)
+    }
+
+    fn serialize_unit(&mut self) -> io::Result<()> {
+        write_nil(self.writer)
+    }
+
+    fn start_struct(&mut self, len: usize) -> io::Result<()> {
+        write_map_len(self.writer, len)
+    }
+
+    fn field(&mut self, name: &'static str) -> io::Result<()> {
+        write_str(self.writer, name)
+    }
+
+    fn end_struct(&mut self) -> io::Result<()> {
+        Ok(())
     }
+
+    fn start_seq(&mut self, len: usize) -> io::Result<()> {
+        write_array_len(self.writer, len)
+    }
+
+    fn end_seq(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn start_map(&mut self, len: usize) -> io::Result<()> {
+        write_map_len(self.writer, len)
+    }
+
+    fn map_key(&mut self, key: &str) -> io::Result<()> {
+        write_str(self.writer, key)
+    }
+
+    fn end_map(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+fn write_nil<W: Write>(writer: &mut W) -> io::Result<()> {
+    writer.write_all(&[0xc0])
 }
 
 fn write_str<W: Write>(writer: &mut W, s: &str) -> io::Result<()> {
@@ -94,60 +126,6 @@ fn write_str<W: Write>(writer: &mut W, s: &str) -> io::Result<()> {
     writer.write_all(bytes)
 }
 
-fn write_u8<W: Write>(writer: &mut W, n: u8) -> io::Result<()> {
-    match n {
-        0..=127 => {
-            // positive fixint
-            writer.write_all(&[n])
-        }
-        _ => {
-            // uint8
-            writer.write_all(&[0xcc, n])
-        }
-    }
-}
-
-fn write_u16<W: Write>(writer: &mut W, n: u16) -> io::Result<()> {
-    match n {
-        0..=127 => {
-            // positive fixint
-            writer.write_all(&[n as u8])
-        }
-        128..=255 => {
-            // uint8
-            writer.write_all(&[0xcc, n as u8])
-        }
-        _ => {
-            // uint16
-            writer.write_all(&[0xcd])?;
-            writer.write_all(&n.to_be_bytes())
-        }
-    }
-}
-
-fn write_u32<W: Write>(writer: &mut W, n: u32) -> io::Result<()> {
-    match n {
-        0..=127 => {
-            // positive fixint
-            writer.write_all(&[n as u8])
-        }
-        128..=255 => {
-            // uint8
-            writer.write_all(&[0xcc, n as u8])
-        }
-        256..=65535 => {
-            // uint16
-            writer.write_all(&[0xcd])?;
-            writer.write_all(&(n as u16).to_be_bytes())
-        }
-        _ => {
-            // uint32
-            writer.write_all(&[0xce])?;
-            writer.write_all(&n.to_be_bytes())
-        }
-    }
-}
-
 fn write_u64<W: Write>(writer: &mut W, n: u64) -> io::Result<()> {
     match n {
         0..=127 => {
@@ -176,95 +154,6 @@ fn write_u64<W: Write>(writer: &mut W, n: u64) -> io::Result<()> {
     }
 }
 
-fn write_i8<W: Write>(writer: &mut W, n: i8) -> io::Result<()> {
-    match n {
-        -32..=-1 => {
-            // negative fixint
-            writer.write_all(&[n as u8])
-        }
-        -128..=-33 => {
-            // int8
-            writer.write_all(&[0xd0, n as u8])
-        }
-        0..=127 => {
-            // positive fixint
-            writer.write_all(&[n as u8])
-        }
-    }
-}
-
-fn write_i16<W: Write>(writer: &mut W, n: i16) -> io::Result<()> {
-    match n {
-        -32..=-1 => {
-            // negative fixint
-            writer.write_all(&[n as u8])
-        }
-        -128..=-33 => {
-            // int8
-            writer.write_all(&[0xd0, n as u8])
-        }
-        -32768..=-129 => {
-            // int16
-            writer.write_all(&[0xd1])?;
-            writer.write_all(&n.to_be_bytes())
-        }
-        0..=127 => {
-            // positive fixint
-            writer.write_all(&[n as u8])
-        }
-        128..=255 => {
-            // uint8
-            writer.write_all(&[0xcc, n as u8])
-        }
-        256..=32767 => {
-            // uint16
-            writer.write_all(&[0xcd])?;
-            writer.write_all(&(n as u16).to_be_bytes())
-        }
-    }
-}
-
-fn write_i32<W: Write>(writer: &mut W, n: i32) -> io::Result<()> {
-    match n {
-        -32..=-1 => {
-            // negative fixint
-            writer.write_all(&[n as u8])
-        }
-        -128..=-33 => {
-            // int8
-            writer.write_all(&[0xd0, n as u8])
-        }
-        -32768..=-129 => {
-            // int16
-            writer.write_all(&[0xd1])?;
-            writer.write_all(&(n as i16).to_be_bytes())
-        }
-        -2147483648..=-32769 => {
-            // int32
-            writer.write_all(&[0xd2])?;
-            writer.write_all(&n.to_be_bytes())
-        }
-        0..=127 => {
-            // positive fixint
-            writer.write_all(&[n as u8])
-        }
-        128..=255 => {
-            // uint8
-            writer.write_all(&[0xcc, n as u8])
-        }
-        256..=65535 => {
-            // uint16
-            writer.write_all(&[0xcd])?;
-            writer.write_all(&(n as u16).to_be_bytes())
-        }
-        65536..=2147483647 => {
-            // uint32
-            writer.write_all(&[0xce])?;
-            writer.write_all(&(n as u32).to_be_bytes())
-        }
-    }
-}
-
 fn write_i64<W: Write>(writer: &mut W, n: i64) -> io::Result<()> {
     match n {
         -32..=-1 => {
@@ -334,3 +223,22 @@ fn write_map_len<W: Write>(writer: &mut W, len: usize) -> io::Result<()> {
         }
     }
 }
+
+fn write_array_len<W: Write>(writer: &mut W, len: usize) -> io::Result<()> {
+    match len {
+        0..=15 => {
+            // fixarray
+            writer.write_all(&[(0x90 | len as u8)])
+        }
+        16..=65535 => {
+            // array16
+            writer.write_all(&[0xdc])?;
+            writer.write_all(&(len as u16).to_be_bytes())
+        }
+        _ => {
+            // array32
+            writer.write_all(&[0xdd])?;
+            writer.write_all(&(len as u32).to_be_bytes())
+        }
+    }
+}
@@ -6,7 +6,7 @@ fn init_backtrace() {
 }
 
 use facet_derive::Facet;
-use facet_msgpack::to_vec;
+use facet_msgpack::{from_str, to_vec};
 use facet_trait::Facet;
 
 use facet_trait as facet;
@@ -126,3 +126,87 @@ fn test_struct() {
 
     assert_eq!(msgpack, expected);
 }
+
+#[test]
+fn test_zero_sized_types_round_trip() {
+    #[derive(Debug, PartialEq, Facet)]
+    struct Marker;
+
+    #[derive(Debug, PartialEq, Facet)]
+    struct Empty {}
+
+    #[derive(Debug, PartialEq, Facet)]
+    struct Holder {
+        marker: Marker,
+        empty: Empty,
+        nothing: (),
+        id: u64,
+    }
+
+    assert_eq!(to_vec(&()), [0xc0]);
+    assert_eq!(to_vec(&Marker), [0xc0]);
+    assert_eq!(to_vec(&Empty {}), [0x80]);
+    from_str::<()>(&[0xc0]).unwrap();
+    assert_eq!(from_str::<Marker>(&[0xc0]).unwrap(), Marker);
+    assert_eq!(from_str::<Empty>(&[0x80]).unwrap(), Empty {});
+
+    let holder = Holder {
+        marker: Marker,
+        empty: Empty {},
+        nothing: (),
+        id: 7,
+    };
+    let msgpack = to_vec(&holder);
+    assert_eq!(from_str::<Holder>(&msgpack).unwrap(), holder);
+}
+
+#[test]
+fn test_options_round_trip() {
+    #[derive(Debug, PartialEq, Facet)]
+    struct Point {
+        x: i32,
+        y: i32,
+    }
+
+    #[derive(Debug, PartialEq, Facet)]
+    struct Profile {
+        nickname: Option<String>,
+        age: Option<u8>,
+        home: Option<Point>,
+        scores: Vec<Option<u64>>,
+    }
+
+    let profile = Profile {
+        nickname: Some("ada".to_string()),
+        age: None,
+        home: Some(Point { x: 1, y: -2 }),
+        scores: vec![Some(3), None],
+    };
+    let msgpack = to_vec(&profile);
+
+    let expected = [
+        0x84, // map with 4 elements
+        // nickname: "ada"
+        0xa8, 0x6e, 0x69, 0x63, 0x6b, 0x6e, 0x61, 0x6d, 0x65, 0xa3, 0x61, 0x64, 0x61,
+        // age: nil
+        0xa3, 0x61, 0x67, 0x65, 0xc0, //
+        // home: {x: 1, y: -2}
+        0xa4, 0x68, 0x6f, 0x6d, 0x65, 0x82, 0xa1, 0x78, 0x01, 0xa1, 0x79, 0xfe,
+        // scores: [3, nil]
+        0xa6, 0x73, 0x63, 0x6f, 0x72, 0x65, 0x73, 0x92, 0x03, 0xc0,
+    ];
+    assert_eq!(msgpack, expected);
+    assert_eq!(from_str::<Profile>(&msgpack).unwrap(), profile);
+
+    // Options missing from the data are `None`
+    let empty = from_str::<Profile>(&[0x81, 0xa6, 0x73, 0x63, 0x6f, 0x72, 0x65, 0x73, 0x90]);
+    assert_eq!(
+        empty.unwrap(),
+        Profile {
+            nickname: None,
+            age: None,
+            home: None,
+            scores: vec![],
+        }
+    );
+}
@@ -0,0 +1,158 @@
+use facet_trait::{EnumDef, EnumRepr, Field, OpaqueConst, Variant, VariantKind};
+
+use crate::{Peek, PeekValue};
+
+/// Lets you read from an enum: which variant it holds, and that variant's fields
+#[derive(Clone, Copy)]
+pub struct PeekEnum<'mem> {
+    value: PeekValue<'mem>,
+    def: EnumDef,
+}
+
+impl<'mem> core::ops::Deref for PeekEnum<'mem> {
+    type Target = PeekValue<'mem>;
+
+    #[inline(always)]
+    fn deref(&self) -> &Self::Target {
+        &self.value
+    }
+}
+
+impl<'mem> PeekEnum<'mem> {
+    /// Create a new peek enum
+    pub(crate) fn new(value: PeekValue<'mem>, def: EnumDef) -> Self {
+        Self { value, def }
+    }
+
+    /// Returns the enum definition
+    #[inline(always)]
+    pub fn def(&self) -> &EnumDef {
+        &self.def
+    }
+
+    /// Returns the index of the variant this value holds
+    ///
+    /// # Panics
+    ///
+    /// Panics if the discriminant doesn't match any variant, which means the shape
+    /// doesn't describe the value.
+    pub fn variant_index(&self) -> usize {
+        unsafe { read_variant_index(self.data(), &self.def) }
+            .expect("enum discriminant doesn't match any variant")
+    }
+
+    /// Returns the variant this value holds
+    pub fn variant(&self) -> &'static Variant {
+        &self.def.variants[self.variant_index()]
+    }
+
+    /// Returns the number of fields of the current variant
+    #[inline]
+    pub fn field_count(&self) -> usize {
+        self.variant_fields().len()
+    }
+
+    /// Returns the name of the field of the current variant at the given index
+    #[inline]
+    pub fn field_name(&self, index: usize) -> Option<&'static str> {
+        self.variant_fields().get(index).map(|field| field.name)
+    }
+
+    /// Returns the value of the field of the current variant at the given index
+    #[inline]
+    pub fn field_value(&self, index: usize) -> Option<Peek<'mem>> {
+        self.variant_fields().get(index).map(|field| unsafe {
+            let field_data = self.data().field(field.offset);
+            Peek::unchecked_new(field_data, field.shape)
+        })
+    }
+
+    /// Returns the value of the field of the current variant with the given name
+    #[inline]
+    pub fn get_field(&self, name: &str) -> Option<Peek<'mem>> {
+        self.variant_fields()
+            .iter()
+            .position(|field| field.name == name)
+            .and_then(|index| self.field_value(index))
+    }
+
+    /// Iterates over the fields of the current variant, providing both name and value
+    #[inline]
+    pub fn fields(&self) -> impl Iterator<Item = (&'static str, Peek<'mem>)> + '_ {
+        (0..self.field_count()).filter_map(|i| {
+            let name = self.field_name(i)?;
+            let value = self.field_value(i)?;
+            Some((name, value))
+        })
+    }
+
+    /// Returns the fields of the current variant
+    pub fn variant_fields(&self) -> &'static [Field] {
+        match self.variant().kind {
+            VariantKind::Tuple { fields } | VariantKind::Struct { fields } => fields,
+            _ => &[],
+        }
+    }
+}
+
+/// Reads the discriminant of an initialized enum and returns the index of its variant.
+///
+/// Returns `None` if the discriminant doesn't match any variant.
+///
+/// # Safety
+///
+/// `data` must point to an initialized value of the enum described by `def`.
+pub unsafe fn read_variant_index(data: OpaqueConst<'_>, def: &EnumDef) -> Option<usize> {
+    let ptr = data.as_byte_ptr();
+    let discriminant = unsafe {
+        match def.repr {
+            EnumRepr::U8 => *ptr as i64,
+            EnumRepr::U16 => *(ptr as *const u16) as i64,
+            EnumRepr::U32 => *(ptr as *const u32) as i64,
+            EnumRepr::U64 => *(ptr as *const u64) as i64,
+            EnumRepr::USize => *(ptr as *const usize) as i64,
+            EnumRepr::I8 => *(ptr as *const i8) as i64,
+            EnumRepr::I16 => *(ptr as *const i16) as i64,
+            EnumRepr::I32 => *(ptr as *const i32) as i64,
+            EnumRepr::I64 => *(ptr as *const i64),
+            EnumRepr::ISize => *(ptr as *const isize) as i64,
+            EnumRepr::Default => {
+                if def.variants.len() <= 256 {
+                    *ptr as i64
+                } else if def.variants.len() <= 65536 {
+                    *(ptr as *const u16) as i64
+                } else {
+                    *(ptr as *const u32) as i64
+                }
+            }
+            _ => {
+                panic!("Unsupported enum representation: {:?}", def.repr);
+            }
+        }
+    };
+
+    def.variants
+        .iter()
+        .enumerate()
+        .find(|(index, variant)| {
+            // the tag was written truncated to the repr, so compare truncated values too
+            let expected = variant.discriminant.unwrap_or(*index as i64);
+            match def.repr {
+                EnumRepr::U8 => expected as u8 as i64 == discriminant,
+                EnumRepr::U16 => expected as u16 as i64 == discriminant,
+                EnumRepr::U32 => expected as u32 as i64 == discriminant,
+                EnumRepr::I8 => expected as i8 as i64 == discriminant,
+                EnumRepr::I16 => expected as i16 as i64 == discriminant,
+                EnumRepr::I32 => expected as i32 as i64 == discriminant,
+                EnumRepr::Default if def.variants.len() <= 256 => {
+                    expected as u8 as i64 == discriminant
+                }
+                EnumRepr::Default if def.variants.len() <= 65536 => {
+                    expected as u16 as i64 == discriminant
+                }
+                EnumRepr::Default => expected as u32 as i64 == discriminant,
+                _ => expected == discriminant,
+            }
+        })
+        .map(|(index, _)| index)
+}
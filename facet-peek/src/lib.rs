@@ -1,8 +1,12 @@
+#![cfg_attr(not(feature = "std"), no_std)]
 #![warn(missing_docs)]
 #![doc = include_str!("../README.md")]
 
 //! Allows peeking (reading from) shapes
 
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
 use facet_trait::{Facet, TypeNameOpts};
 
 mod value;
@@ -17,6 +21,32 @@ pub use list::*;
 mod map;
 pub use map::*;
 
+mod enum_;
+pub use enum_::*;
+
+mod option;
+pub use option::*;
+
+#[cfg(feature = "alloc")]
+mod path;
+#[cfg(feature = "alloc")]
+pub use path::*;
+
+#[cfg(feature = "alloc")]
+mod pattern;
+#[cfg(feature = "alloc")]
+pub use pattern::*;
+
+#[cfg(feature = "alloc")]
+mod visit;
+#[cfg(feature = "alloc")]
+pub use visit::*;
+
+#[cfg(feature = "alloc")]
+mod structural;
+#[cfg(feature = "alloc")]
+pub use structural::*;
+
 use facet_trait::{Def, OpaqueConst, Shape};
 
 /// Lets you peek at the innards of a value
@@ -37,6 +67,12 @@ pub enum Peek<'mem> {
 
     /// cf. [`PeekStruct`]
     Struct(PeekStruct<'mem>),
+
+    /// cf. [`PeekEnum`]
+    Enum(PeekEnum<'mem>),
+
+    /// cf. [`PeekOption`]
+    Option(PeekOption<'mem>),
 }
 
 impl<'mem> core::ops::Deref for Peek<'mem> {
@@ -48,6 +84,8 @@ impl<'mem> core::ops::Deref for Peek<'mem> {
             Peek::List(list) => list,
             Peek::Map(map) => map,
             Peek::Struct(struct_) => struct_,
+            Peek::Enum(enum_) => enum_,
+            Peek::Option(option) => option,
         }
     }
 }
@@ -74,7 +112,8 @@ impl<'mem> Peek<'mem> {
             Def::Map(def) => Peek::Map(PeekMap::new(value, def)),
             Def::List(def) => Peek::List(PeekList::new(value, def)),
             Def::Scalar { .. } => Peek::Value(value),
-            Def::Enum { .. } => todo!(),
+            Def::Enum(def) => Peek::Enum(PeekEnum::new(value, def)),
+            Def::Option(def) => Peek::Option(PeekOption::new(value, def)),
             _ => todo!("unsupported def: {:?}", shape.def),
         }
     }
@@ -86,6 +125,8 @@ impl<'mem> Peek<'mem> {
             Self::List(l) => *l,
             Self::Map(m) => *m,
             Self::Struct(s) => *s,
+            Self::Enum(e) => *e,
+            Self::Option(o) => *o,
         }
     }
 }
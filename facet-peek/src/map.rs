@@ -84,6 +84,19 @@ impl<'mem> PeekMap<'mem> {
         }
     }
 
+    /// Get a value from the map for a key that's only known by its shape
+    ///
+    /// Returns `None` if the key isn't in the map, or isn't of the map's key type.
+    pub fn get_peek(&self, key: PeekValue<'_>) -> Option<Peek<'mem>> {
+        if key.shape() != self.def.k {
+            return None;
+        }
+        unsafe {
+            let value_ptr = (self.def.vtable.get_value_ptr_fn)(self.value.data(), key.data())?;
+            Some(Peek::unchecked_new(value_ptr, self.def.v))
+        }
+    }
+
     /// Returns an iterator over the key-value pairs in the map
     pub fn iter(self) -> PeekMapIter<'mem> {
         let iter = unsafe { (self.def.vtable.iter_fn)(self.value.data()) };
@@ -0,0 +1,55 @@
+use facet_trait::OptionDef;
+
+use crate::{Peek, PeekValue};
+
+/// Lets you read from an option (implements read-only [`OptionVTable`] proxies)
+///
+/// [`OptionVTable`]: facet_trait::OptionVTable
+#[derive(Clone, Copy)]
+pub struct PeekOption<'mem> {
+    value: PeekValue<'mem>,
+    def: OptionDef,
+}
+
+impl<'mem> core::ops::Deref for PeekOption<'mem> {
+    type Target = PeekValue<'mem>;
+
+    #[inline(always)]
+    fn deref(&self) -> &Self::Target {
+        &self.value
+    }
+}
+
+impl<'mem> PeekOption<'mem> {
+    /// Create a new peek option
+    pub(crate) fn new(value: PeekValue<'mem>, def: OptionDef) -> Self {
+        Self { value, def }
+    }
+
+    /// Returns the option definition
+    #[inline(always)]
+    pub fn def(&self) -> &OptionDef {
+        &self.def
+    }
+
+    /// Returns true if the option holds a value
+    #[inline]
+    pub fn is_some(&self) -> bool {
+        unsafe { (self.def.vtable.is_some)(self.data()) }
+    }
+
+    /// Returns true if the option holds no value
+    #[inline]
+    pub fn is_none(&self) -> bool {
+        !self.is_some()
+    }
+
+    /// Returns the value held by the option, if any
+    #[inline]
+    pub fn value(&self) -> Option<Peek<'mem>> {
+        unsafe {
+            let data = (self.def.vtable.get_value)(self.data())?;
+            Some(Peek::unchecked_new(data, self.def.t))
+        }
+    }
+}
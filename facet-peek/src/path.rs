@@ -0,0 +1,392 @@
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt::{self, Write};
+
+use facet_trait::Shape;
+
+use crate::{Peek, PeekValue};
+
+/// A location inside a value, like `users[3].address.city` or `settings["theme"]`
+///
+/// Parse one with [`str::parse`]; its [`Display`](core::fmt::Display) implementation
+/// writes it back in the same syntax. The empty path is the value itself.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Path {
+    segments: Vec<PathSegment>,
+}
+
+/// One step of a [`Path`]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum PathSegment {
+    /// A struct field or a field of an enum's current variant, written `.name` — tuple
+    /// fields are named `0`, `1`, etc.
+    Field(String),
+    /// A list index, written `[3]` — on a map, the key that displays as `3`
+    Index(usize),
+    /// A map key, written `["theme"]`: matches the key that displays as `theme`
+    Key(String),
+}
+
+impl Path {
+    /// Returns the empty path, which designates the value itself
+    pub fn root() -> Self {
+        Self::default()
+    }
+
+    /// Returns the segments of this path
+    pub fn segments(&self) -> &[PathSegment] {
+        &self.segments
+    }
+
+    /// Returns true if this is the empty path
+    pub fn is_root(&self) -> bool {
+        self.segments.is_empty()
+    }
+
+    /// Appends a segment
+    pub fn push(&mut self, segment: PathSegment) {
+        self.segments.push(segment);
+    }
+
+    /// Removes the last segment
+    pub fn pop(&mut self) -> Option<PathSegment> {
+        self.segments.pop()
+    }
+
+    /// Returns a copy of this path with `segment` appended
+    pub fn join(&self, segment: PathSegment) -> Self {
+        let mut path = self.clone();
+        path.push(segment);
+        path
+    }
+}
+
+impl From<Vec<PathSegment>> for Path {
+    fn from(segments: Vec<PathSegment>) -> Self {
+        Self { segments }
+    }
+}
+
+impl fmt::Display for Path {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, segment) in self.segments.iter().enumerate() {
+            match segment {
+                PathSegment::Field(name) if i == 0 => write!(f, "{name}")?,
+                PathSegment::Field(name) => write!(f, ".{name}")?,
+                PathSegment::Index(index) => write!(f, "[{index}]")?,
+                PathSegment::Key(key) => {
+                    f.write_str("[\"")?;
+                    for c in key.chars() {
+                        if c == '"' || c == '\\' {
+                            f.write_char('\\')?;
+                        }
+                        f.write_char(c)?;
+                    }
+                    f.write_str("\"]")?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl core::str::FromStr for Path {
+    type Err = PathParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut segments = Vec::new();
+        let mut pos = 0;
+        while pos < s.len() {
+            let (segment, next) = parse_segment(s, pos, segments.is_empty())?;
+            segments.push(segment);
+            pos = next;
+        }
+        Ok(Self { segments })
+    }
+}
+
+/// Parses the segment starting at byte `pos` of `s`, returning it with the position
+/// right after it; `first` allows a field name without a leading dot
+pub(crate) fn parse_segment(
+    s: &str,
+    pos: usize,
+    first: bool,
+) -> Result<(PathSegment, usize), PathParseError> {
+    let bytes = s.as_bytes();
+    let error = |position, message| PathParseError { position, message };
+
+    match bytes[pos] {
+        b'[' if bytes.get(pos + 1) == Some(&b'"') => {
+            let mut key = String::new();
+            let mut chars = s[pos + 2..].char_indices();
+            let end = loop {
+                match chars.next() {
+                    Some((i, '"')) => break pos + 2 + i,
+                    Some((_, '\\')) => match chars.next() {
+                        Some((_, c)) => key.push(c),
+                        None => return Err(error(s.len(), "unterminated key")),
+                    },
+                    Some((_, c)) => key.push(c),
+                    None => return Err(error(s.len(), "unterminated key")),
+                }
+            };
+            if bytes.get(end + 1) != Some(&b']') {
+                return Err(error(end + 1, "expected `]` after key"));
+            }
+            Ok((PathSegment::Key(key), end + 2))
+        }
+        b'[' => {
+            let len = s[pos + 1..]
+                .bytes()
+                .take_while(|b| b.is_ascii_digit())
+                .count();
+            if len == 0 {
+                return Err(error(pos + 1, "expected an index or a quoted key"));
+            }
+            if bytes.get(pos + 1 + len) != Some(&b']') {
+                return Err(error(pos + 1 + len, "expected `]` after index"));
+            }
+            let index = s[pos + 1..pos + 1 + len]
+                .parse()
+                .map_err(|_| error(pos + 1, "index is too large"))?;
+            Ok((PathSegment::Index(index), pos + len + 2))
+        }
+        b'.' => {
+            // a leading dot is allowed too, so a tuple's fields read `.0`
+            let len = field_name_len(&s[pos + 1..]);
+            if len == 0 {
+                return Err(error(pos + 1, "expected a field name"));
+            }
+            let name = s[pos + 1..pos + 1 + len].to_string();
+            Ok((PathSegment::Field(name), pos + 1 + len))
+        }
+        _ if first && field_name_len(&s[pos..]) > 0 => {
+            let len = field_name_len(&s[pos..]);
+            Ok((PathSegment::Field(s[pos..pos + len].to_string()), pos + len))
+        }
+        _ => Err(error(pos, "expected `.` or `[`")),
+    }
+}
+
+fn field_name_len(s: &str) -> usize {
+    s.bytes()
+        .take_while(|b| b.is_ascii_alphanumeric() || *b == b'_')
+        .count()
+}
+
+/// Error returned when a string isn't a valid [`Path`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PathParseError {
+    /// Byte offset of the problem in the input
+    pub position: usize,
+    /// What was expected there
+    pub message: &'static str,
+}
+
+impl fmt::Display for PathParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invalid path at byte {}: {}",
+            self.position, self.message
+        )
+    }
+}
+
+impl core::error::Error for PathParseError {}
+
+/// Error returned by [`Peek::at_path`] when the path doesn't lead anywhere
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PathError {
+    /// The path up to (and including) the segment that failed
+    pub path: Path,
+    /// Why that segment failed
+    pub kind: PathErrorKind,
+}
+
+/// Why a segment of a [`Path`] failed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum PathErrorKind {
+    /// The segment doesn't apply to this kind of value (e.g. a field of a list)
+    WrongKind {
+        /// What the segment needs ("a struct or an enum", "a list or a map", "a map")
+        expected: &'static str,
+        /// The shape that was found instead
+        found: &'static Shape,
+    },
+    /// The struct (or the enum's current variant) has no field with this name
+    NoSuchField,
+    /// The list is shorter than that
+    IndexOutOfBounds {
+        /// The index that was requested
+        index: usize,
+        /// The length of the list
+        len: usize,
+    },
+    /// The map has no entry for this key
+    NoSuchKey,
+    /// An option along the way holds no value
+    IsNone,
+}
+
+impl fmt::Display for PathError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "at `{}`: ", self.path)?;
+        match self.kind {
+            PathErrorKind::WrongKind { expected, found } => {
+                write!(f, "expected {expected}, found {found}")
+            }
+            PathErrorKind::NoSuchField => write!(f, "no such field"),
+            PathErrorKind::IndexOutOfBounds { index, len } => {
+                write!(f, "index {index} is out of bounds (len is {len})")
+            }
+            PathErrorKind::NoSuchKey => write!(f, "no such key"),
+            PathErrorKind::IsNone => write!(f, "the value is `None`"),
+        }
+    }
+}
+
+impl core::error::Error for PathError {}
+
+impl<'mem> Peek<'mem> {
+    /// Returns the value at `path`
+    ///
+    /// Options are transparent: a segment applies to the value an option holds.
+    ///
+    /// # Errors
+    ///
+    /// Returns which segment of the path failed, and why.
+    pub fn at_path(self, path: &Path) -> Result<Peek<'mem>, PathError> {
+        let mut current = self;
+        for (i, segment) in path.segments.iter().enumerate() {
+            let error = |kind| PathError {
+                path: Path::from(path.segments[..=i].to_vec()),
+                kind,
+            };
+            while let Peek::Option(po) = current {
+                current = po.value().ok_or_else(|| error(PathErrorKind::IsNone))?;
+            }
+            current = match (segment, current) {
+                (PathSegment::Field(name), Peek::Struct(ps)) => ps
+                    .get_field(name)
+                    .ok_or_else(|| error(PathErrorKind::NoSuchField))?,
+                (PathSegment::Field(name), Peek::Enum(pe)) => pe
+                    .get_field(name)
+                    .ok_or_else(|| error(PathErrorKind::NoSuchField))?,
+                (PathSegment::Index(index), Peek::List(pl)) => {
+                    pl.item_at(*index).ok_or_else(|| {
+                        error(PathErrorKind::IndexOutOfBounds {
+                            index: *index,
+                            len: pl.len(),
+                        })
+                    })?
+                }
+                (PathSegment::Index(index), Peek::Map(pm)) => map_get(pm, &index.to_string())
+                    .ok_or_else(|| error(PathErrorKind::NoSuchKey))?,
+                (PathSegment::Key(key), Peek::Map(pm)) => {
+                    map_get(pm, key).ok_or_else(|| error(PathErrorKind::NoSuchKey))?
+                }
+                (segment, peek) => {
+                    let expected = match segment {
+                        PathSegment::Field(_) => "a struct or an enum",
+                        PathSegment::Index(_) => "a list or a map",
+                        PathSegment::Key(_) => "a map",
+                    };
+                    return Err(error(PathErrorKind::WrongKind {
+                        expected,
+                        found: peek.shape(),
+                    }));
+                }
+            };
+        }
+        Ok(current)
+    }
+
+    /// Returns every leaf of this value with its path, depth-first, in field, list and
+    /// map iteration order.
+    ///
+    /// Leaves are values without children: scalars, but also empty lists, maps and
+    /// structs, unit enum variants and `None`, so that no part of the value goes
+    /// unreported. An option that holds a value is reported through that value.
+    pub fn walk_paths(self) -> Vec<(Path, Peek<'mem>)> {
+        let mut leaves = Vec::new();
+        walk(self, &mut Path::root(), &mut leaves);
+        leaves
+    }
+}
+
+fn walk<'mem>(peek: Peek<'mem>, path: &mut Path, leaves: &mut Vec<(Path, Peek<'mem>)>) {
+    let before = leaves.len();
+    match peek {
+        Peek::Value(_) => {}
+        Peek::Struct(ps) => {
+            for (name, field) in ps.fields() {
+                path.push(PathSegment::Field(name.to_string()));
+                walk(field, path, leaves);
+                path.pop();
+            }
+        }
+        Peek::List(pl) => {
+            for (index, item) in pl.iter().enumerate() {
+                path.push(PathSegment::Index(index));
+                walk(item, path, leaves);
+                path.pop();
+            }
+        }
+        Peek::Map(pm) => {
+            for (key, value) in pm.iter() {
+                path.push(PathSegment::Key(key.to_string()));
+                walk(value, path, leaves);
+                path.pop();
+            }
+        }
+        Peek::Enum(pe) => {
+            for (name, field) in pe.fields() {
+                path.push(PathSegment::Field(name.to_string()));
+                walk(field, path, leaves);
+                path.pop();
+            }
+        }
+        Peek::Option(po) => {
+            if let Some(value) = po.value() {
+                walk(value, path, leaves);
+            }
+        }
+    }
+    if leaves.len() == before {
+        leaves.push((path.clone(), peek));
+    }
+}
+
+/// Finds the entry whose key displays as `key`
+fn map_get<'mem>(pm: crate::PeekMap<'mem>, key: &str) -> Option<Peek<'mem>> {
+    pm.iter()
+        .find(|(k, _)| displays_as(k.as_value(), key))
+        .map(|(_, v)| v)
+}
+
+fn displays_as(value: PeekValue<'_>, expected: &str) -> bool {
+    /// Compares formatted output against `expected` without allocating
+    struct Matcher<'a> {
+        rest: &'a str,
+    }
+
+    impl Write for Matcher<'_> {
+        fn write_str(&mut self, s: &str) -> fmt::Result {
+            match self.rest.strip_prefix(s) {
+                Some(rest) => {
+                    self.rest = rest;
+                    Ok(())
+                }
+                None => Err(fmt::Error),
+            }
+        }
+    }
+
+    if value.vtable().display.is_none() {
+        return false;
+    }
+    let mut matcher = Matcher { rest: expected };
+    write!(matcher, "{value}").is_ok() && matcher.rest.is_empty()
+}
@@ -0,0 +1,130 @@
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::{Path, PathParseError, PathSegment, path::parse_segment};
+
+/// A pattern matching [`Path`]s, like `users[*].created_at` or `**.id`
+///
+/// Patterns use the path syntax, plus three wildcards:
+///
+///   * `*` in place of a field name matches any one field
+///   * `[*]` matches any one list index or map key
+///   * `**` in place of a field name matches any number of segments, including none
+///
+/// So `*.created_at` matches `user.created_at` but not `users[0].created_at`, which
+/// `**.created_at` matches.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PathPattern {
+    segments: Vec<PatternSegment>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum PatternSegment {
+    Exact(PathSegment),
+    AnyField,
+    AnyItem,
+    AnyDepth,
+}
+
+impl PathPattern {
+    /// Returns true if `path` matches this pattern
+    pub fn matches(&self, path: &Path) -> bool {
+        matches(&self.segments, path.segments())
+    }
+}
+
+fn matches(pattern: &[PatternSegment], path: &[PathSegment]) -> bool {
+    let Some((first, rest)) = pattern.split_first() else {
+        return path.is_empty();
+    };
+    match first {
+        PatternSegment::AnyDepth => (0..=path.len()).any(|skip| matches(rest, &path[skip..])),
+        _ => {
+            let Some((segment, path)) = path.split_first() else {
+                return false;
+            };
+            let matched = match first {
+                PatternSegment::Exact(expected) => expected == segment,
+                PatternSegment::AnyField => matches!(segment, PathSegment::Field(_)),
+                PatternSegment::AnyItem => {
+                    matches!(segment, PathSegment::Index(_) | PathSegment::Key(_))
+                }
+                PatternSegment::AnyDepth => unreachable!(),
+            };
+            matched && matches(rest, path)
+        }
+    }
+}
+
+impl From<Path> for PathPattern {
+    fn from(path: Path) -> Self {
+        Self {
+            segments: path
+                .segments()
+                .iter()
+                .cloned()
+                .map(PatternSegment::Exact)
+                .collect(),
+        }
+    }
+}
+
+impl fmt::Display for PathPattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, segment) in self.segments.iter().enumerate() {
+            let dot = if i == 0 { "" } else { "." };
+            match segment {
+                PatternSegment::Exact(segment) => {
+                    let path = Path::from(alloc::vec![segment.clone()]);
+                    match segment {
+                        PathSegment::Field(_) if i > 0 => write!(f, ".{path}")?,
+                        _ => write!(f, "{path}")?,
+                    }
+                }
+                PatternSegment::AnyField => write!(f, "{dot}*")?,
+                PatternSegment::AnyItem => f.write_str("[*]")?,
+                PatternSegment::AnyDepth => write!(f, "{dot}**")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl core::str::FromStr for PathPattern {
+    type Err = PathParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut segments = Vec::new();
+        let mut pos = 0;
+        while pos < s.len() {
+            let rest = &s[pos..];
+            let first = segments.is_empty();
+            // like field names, wildcards only need a leading dot after the first segment
+            let (body, dot) = match rest.strip_prefix('.') {
+                Some(body) => (body, 1),
+                None => (rest, 0),
+            };
+            let (segment, len) = if rest.starts_with("[*]") {
+                (PatternSegment::AnyItem, 3)
+            } else if (first || dot == 1) && body.starts_with("**") {
+                (PatternSegment::AnyDepth, dot + 2)
+            } else if (first || dot == 1) && body.starts_with('*') {
+                (PatternSegment::AnyField, dot + 1)
+            } else {
+                let (segment, next) = parse_segment(s, pos, first)?;
+                segments.push(PatternSegment::Exact(segment));
+                pos = next;
+                continue;
+            };
+            pos += len;
+            if !matches!(s.as_bytes().get(pos), None | Some(b'.' | b'[')) {
+                return Err(PathParseError {
+                    position: pos,
+                    message: "expected `.` or `[` after a wildcard",
+                });
+            }
+            segments.push(segment);
+        }
+        Ok(Self { segments })
+    }
+}
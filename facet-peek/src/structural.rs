@@ -0,0 +1,399 @@
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+use core::hash::{Hash, Hasher};
+
+use facet_trait::{Facet, Shape, ShapeExt as _};
+
+use crate::{Peek, PeekList, PeekMap, PeekValue};
+
+/// How floats are compared and hashed by structural comparisons
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum FloatMode {
+    /// Floats are compared with `total_cmp`: every NaN is equal to itself, and
+    /// `-0.0` is less than `0.0`
+    ///
+    /// This is the only mode in which [`Structural`]'s `Eq` and `Ord` implementations
+    /// are lawful for every value.
+    #[default]
+    Total,
+    /// Floats are compared as IEEE 754 says: NaN is equal to nothing, not even itself,
+    /// and `-0.0` is equal to `0.0`
+    ///
+    /// NaN is ordered after every other number, and equal to itself.
+    Ieee,
+    /// Like [`FloatMode::Ieee`], except that every NaN is equal to every other NaN
+    NanEqual,
+}
+
+impl FloatMode {
+    fn eq(self, a: f64, b: f64) -> bool {
+        match self {
+            FloatMode::Total => a.total_cmp(&b).is_eq(),
+            FloatMode::Ieee => a == b,
+            FloatMode::NanEqual => a == b || (a.is_nan() && b.is_nan()),
+        }
+    }
+
+    fn cmp(self, a: f64, b: f64) -> Ordering {
+        match self {
+            FloatMode::Total => a.total_cmp(&b),
+            FloatMode::Ieee | FloatMode::NanEqual => a
+                .partial_cmp(&b)
+                .unwrap_or_else(|| a.is_nan().cmp(&b.is_nan())),
+        }
+    }
+
+    /// Returns bits that are equal for floats that are equal in this mode
+    fn bits(self, a: f64) -> u64 {
+        match self {
+            FloatMode::Total => a.to_bits(),
+            FloatMode::Ieee | FloatMode::NanEqual => {
+                if a.is_nan() {
+                    f64::NAN.to_bits()
+                } else if a == 0.0 {
+                    0
+                } else {
+                    a.to_bits()
+                }
+            }
+        }
+    }
+}
+
+impl Peek<'_> {
+    /// Compares two values by their structure: structs and enum variants field by field,
+    /// lists item by item, maps entry by entry, ignoring any `eq` vtable entries they have
+    ///
+    /// Scalars are compared with their `eq` vtable entry, except for floats, which follow
+    /// `floats`.
+    ///
+    /// # Returns
+    ///
+    /// `None` if a scalar that needs comparing doesn't support equality
+    pub fn structural_eq(&self, other: &Peek<'_>, floats: FloatMode) -> Option<bool> {
+        if !same_shape(self.shape(), other.shape()) {
+            return Some(false);
+        }
+        match (self, other) {
+            (Peek::Value(a), Peek::Value(b)) => match float(a) {
+                Some(a) => Some(floats.eq(a, float(b)?)),
+                None => a.eq(b),
+            },
+            (Peek::Struct(a), Peek::Struct(b)) => {
+                for ((_, a), (_, b)) in a.fields().zip(b.fields()) {
+                    if !a.structural_eq(&b, floats)? {
+                        return Some(false);
+                    }
+                }
+                Some(true)
+            }
+            (Peek::Enum(a), Peek::Enum(b)) => {
+                if a.variant_index() != b.variant_index() {
+                    return Some(false);
+                }
+                for ((_, a), (_, b)) in a.fields().zip(b.fields()) {
+                    if !a.structural_eq(&b, floats)? {
+                        return Some(false);
+                    }
+                }
+                Some(true)
+            }
+            (Peek::Option(a), Peek::Option(b)) => match (a.value(), b.value()) {
+                (Some(a), Some(b)) => a.structural_eq(&b, floats),
+                (None, None) => Some(true),
+                _ => Some(false),
+            },
+            (Peek::List(a), Peek::List(b)) => {
+                if a.len() != b.len() {
+                    return Some(false);
+                }
+                for (a, b) in a.iter().zip(b.iter()) {
+                    if !a.structural_eq(&b, floats)? {
+                        return Some(false);
+                    }
+                }
+                Some(true)
+            }
+            (Peek::Map(a), Peek::Map(b)) => {
+                if a.len() != b.len() {
+                    return Some(false);
+                }
+                for (key, a) in a.iter() {
+                    let Some(b) = b.get_peek(key.as_value()) else {
+                        return Some(false);
+                    };
+                    if !a.structural_eq(&b, floats)? {
+                        return Some(false);
+                    }
+                }
+                Some(true)
+            }
+            _ => Some(false),
+        }
+    }
+
+    /// Orders two values by their structure, ignoring any `ord` vtable entries they have
+    ///
+    /// Structs and enum variants are ordered by their fields, in declaration order, and
+    /// enum variants by their declaration order. `None` comes before `Some`, lists are
+    /// ordered lexicographically, and maps are ordered lexicographically by their entries,
+    /// sorted by key. Scalars are ordered with their `ord` vtable entry, except for floats,
+    /// which follow `floats`.
+    ///
+    /// # Returns
+    ///
+    /// `None` if the values have different shapes, or if a scalar that needs ordering
+    /// doesn't support total ordering
+    pub fn structural_cmp(&self, other: &Peek<'_>, floats: FloatMode) -> Option<Ordering> {
+        if !same_shape(self.shape(), other.shape()) {
+            return None;
+        }
+        match (self, other) {
+            (Peek::Value(a), Peek::Value(b)) => match float(a) {
+                Some(a) => Some(floats.cmp(a, float(b)?)),
+                None => a.cmp(b),
+            },
+            (Peek::Struct(a), Peek::Struct(b)) => cmp_all(
+                a.fields().map(|(_, peek)| peek),
+                b.fields().map(|(_, peek)| peek),
+                floats,
+            ),
+            (Peek::Enum(a), Peek::Enum(b)) => match a.variant_index().cmp(&b.variant_index()) {
+                Ordering::Equal => cmp_all(
+                    a.fields().map(|(_, peek)| peek),
+                    b.fields().map(|(_, peek)| peek),
+                    floats,
+                ),
+                ordering => Some(ordering),
+            },
+            (Peek::Option(a), Peek::Option(b)) => match (a.value(), b.value()) {
+                (Some(a), Some(b)) => a.structural_cmp(&b, floats),
+                (a, b) => Some(a.is_some().cmp(&b.is_some())),
+            },
+            (Peek::List(a), Peek::List(b)) => cmp_all(a.iter(), b.iter(), floats),
+            (Peek::Map(a), Peek::Map(b)) => {
+                let a = sorted_entries(*a, floats)?;
+                let b = sorted_entries(*b, floats)?;
+                cmp_all(
+                    a.into_iter().flat_map(|(key, value)| [key, value]),
+                    b.into_iter().flat_map(|(key, value)| [key, value]),
+                    floats,
+                )
+            }
+            _ => None,
+        }
+    }
+
+    /// Hashes a value by its structure, ignoring any `hash` vtable entries it has
+    ///
+    /// Values that are equal according to [`Peek::structural_eq`] with the same `floats`
+    /// have the same hash. Map entries are hashed in an order-independent way.
+    ///
+    /// # Returns
+    ///
+    /// `false` if a scalar inside the value doesn't support hashing, `true` otherwise
+    pub fn structural_hash<H: Hasher>(&self, hasher: &mut H, floats: FloatMode) -> bool {
+        match self {
+            Peek::Value(value) => match float(value) {
+                Some(value) => {
+                    hasher.write_u64(floats.bits(value));
+                    true
+                }
+                None => value.hash(hasher),
+            },
+            Peek::Struct(struct_) => struct_
+                .fields()
+                .all(|(_, field)| field.structural_hash(hasher, floats)),
+            Peek::Enum(enum_) => {
+                hasher.write_usize(enum_.variant_index());
+                enum_
+                    .fields()
+                    .all(|(_, field)| field.structural_hash(hasher, floats))
+            }
+            Peek::Option(option) => match option.value() {
+                Some(value) => {
+                    hasher.write_u8(1);
+                    value.structural_hash(hasher, floats)
+                }
+                None => {
+                    hasher.write_u8(0);
+                    true
+                }
+            },
+            Peek::List(list) => hash_list(*list, hasher, floats),
+            Peek::Map(map) => hash_map(*map, hasher, floats),
+        }
+    }
+}
+
+/// Compares and hashes a value by its structure rather than by its trait implementations
+///
+/// `T` doesn't need to implement `PartialEq`, `Ord` or `Hash`: the wrapper implements them
+/// with [`Peek::structural_eq`], [`Peek::structural_cmp`] and [`Peek::structural_hash`],
+/// so that values can be compared, sorted, or used as keys of a map or set.
+///
+/// # Panics
+///
+/// Comparing or hashing panics if a scalar inside the value doesn't support it. Two
+/// wrappers are compared with the float mode of the left-hand side.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Structural<T> {
+    value: T,
+    floats: FloatMode,
+}
+
+impl<T: Facet> Structural<T> {
+    /// Wraps a value, comparing its floats with [`FloatMode::Total`]
+    pub fn new(value: T) -> Self {
+        Self {
+            value,
+            floats: FloatMode::Total,
+        }
+    }
+
+    /// Sets how floats inside the value are compared and hashed
+    pub fn with_floats(mut self, floats: FloatMode) -> Self {
+        self.floats = floats;
+        self
+    }
+
+    /// Returns the wrapped value
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
+
+impl<T> core::ops::Deref for Structural<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+impl<T: Facet> PartialEq for Structural<T> {
+    fn eq(&self, other: &Self) -> bool {
+        Peek::new(&self.value)
+            .structural_eq(&Peek::new(&other.value), self.floats)
+            .unwrap_or_else(|| panic!("{} can't be compared for equality", T::SHAPE))
+    }
+}
+
+impl<T: Facet> Eq for Structural<T> {}
+
+impl<T: Facet> PartialOrd for Structural<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T: Facet> Ord for Structural<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        Peek::new(&self.value)
+            .structural_cmp(&Peek::new(&other.value), self.floats)
+            .unwrap_or_else(|| panic!("{} can't be ordered", T::SHAPE))
+    }
+}
+
+impl<T: Facet> Hash for Structural<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        if !Peek::new(&self.value).structural_hash(state, self.floats) {
+            panic!("{} can't be hashed", T::SHAPE);
+        }
+    }
+}
+
+fn same_shape(a: &'static Shape, b: &'static Shape) -> bool {
+    core::ptr::eq(a, b) || a == b
+}
+
+/// Reads a float scalar as an `f64`
+fn float(value: &PeekValue<'_>) -> Option<f64> {
+    let shape = value.shape();
+    if shape.is_type::<f64>() {
+        Some(unsafe { *value.data().as_ref::<f64>() })
+    } else if shape.is_type::<f32>() {
+        Some(unsafe { *value.data().as_ref::<f32>() } as f64)
+    } else {
+        None
+    }
+}
+
+/// Orders two sequences lexicographically
+fn cmp_all<'a, 'b>(
+    a: impl IntoIterator<Item = Peek<'a>>,
+    b: impl IntoIterator<Item = Peek<'b>>,
+    floats: FloatMode,
+) -> Option<Ordering> {
+    let mut b = b.into_iter();
+    for a in a {
+        let Some(b) = b.next() else {
+            return Some(Ordering::Greater);
+        };
+        match a.structural_cmp(&b, floats)? {
+            Ordering::Equal => {}
+            ordering => return Some(ordering),
+        }
+    }
+    Some(if b.next().is_some() {
+        Ordering::Less
+    } else {
+        Ordering::Equal
+    })
+}
+
+/// Returns the entries of a map sorted by key, or `None` if its keys can't be ordered
+fn sorted_entries(map: PeekMap<'_>, floats: FloatMode) -> Option<Vec<(Peek<'_>, Peek<'_>)>> {
+    let mut entries: Vec<_> = map.iter().collect();
+    let mut orderable = true;
+    entries.sort_by(|(a, _), (b, _)| {
+        a.structural_cmp(b, floats).unwrap_or_else(|| {
+            orderable = false;
+            Ordering::Equal
+        })
+    });
+    orderable.then_some(entries)
+}
+
+fn hash_list<H: Hasher>(list: PeekList<'_>, hasher: &mut H, floats: FloatMode) -> bool {
+    hasher.write_usize(list.len());
+    list.iter().all(|item| item.structural_hash(hasher, floats))
+}
+
+/// Hashes each entry on its own and combines the hashes with a commutative operation,
+/// since map iteration order isn't meaningful
+fn hash_map<H: Hasher>(map: PeekMap<'_>, hasher: &mut H, floats: FloatMode) -> bool {
+    let mut combined = 0u64;
+    for (key, value) in map.iter() {
+        let mut entry = Fnv1a::default();
+        if !key.structural_hash(&mut entry, floats) || !value.structural_hash(&mut entry, floats) {
+            return false;
+        }
+        combined = combined.wrapping_add(entry.finish());
+    }
+    hasher.write_usize(map.len());
+    hasher.write_u64(combined);
+    true
+}
+
+/// The FNV-1a hash function, which needs no allocation or random state
+struct Fnv1a(u64);
+
+impl Default for Fnv1a {
+    fn default() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Hasher for Fnv1a {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 ^= byte as u64;
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+}
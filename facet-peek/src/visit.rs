@@ -0,0 +1,379 @@
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use facet_trait::Field;
+
+use crate::{
+    Path, PathSegment, Peek, PeekEnum, PeekList, PeekMap, PeekMapIter, PeekOption, PeekStruct,
+    PeekValue,
+};
+
+/// What to do after entering a node, returned by the `enter_*` methods of a [`Visitor`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VisitFlow {
+    /// Visit the children of the node
+    Continue,
+    /// Don't visit the children of the node: the matching `leave_*` method is still called
+    Skip,
+}
+
+/// Callbacks for a depth-first traversal of a [`Peek`] tree, started by [`Peek::visit`]
+///
+/// Every method has a default implementation that does nothing, so a visitor only
+/// implements what it needs. Each `enter_*` call is matched by the corresponding
+/// `leave_*` call once the children (if any) have been visited, even when
+/// [`VisitFlow::Skip`] was returned. The `path` argument is the location of the node
+/// being entered or left, relative to where the traversal started.
+///
+/// Returning an error from any method stops the traversal, and [`Peek::visit`] returns
+/// that error.
+#[allow(unused_variables)]
+pub trait Visitor<'mem> {
+    /// The error that stops the traversal, e.g. [`core::fmt::Error`] for a writer
+    type Error;
+
+    /// Called for values without any structure (numbers, strings, etc.)
+    fn scalar(&mut self, path: &Path, value: PeekValue<'mem>) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    /// Called before the fields of a struct
+    fn enter_struct(
+        &mut self,
+        path: &Path,
+        value: PeekStruct<'mem>,
+    ) -> Result<VisitFlow, Self::Error> {
+        Ok(VisitFlow::Continue)
+    }
+
+    /// Called after the fields of a struct
+    fn leave_struct(&mut self, path: &Path, value: PeekStruct<'mem>) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    /// Called before the value of a field, of a struct or of an enum's current variant
+    fn enter_field(
+        &mut self,
+        path: &Path,
+        field: &'static Field,
+        value: Peek<'mem>,
+    ) -> Result<VisitFlow, Self::Error> {
+        Ok(VisitFlow::Continue)
+    }
+
+    /// Called after the value of a field
+    fn leave_field(
+        &mut self,
+        path: &Path,
+        field: &'static Field,
+        value: Peek<'mem>,
+    ) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    /// Called before the items of a list
+    fn enter_list(&mut self, path: &Path, value: PeekList<'mem>) -> Result<VisitFlow, Self::Error> {
+        Ok(VisitFlow::Continue)
+    }
+
+    /// Called after the items of a list
+    fn leave_list(&mut self, path: &Path, value: PeekList<'mem>) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    /// Called before an item of a list
+    fn enter_list_item(
+        &mut self,
+        path: &Path,
+        index: usize,
+        item: Peek<'mem>,
+    ) -> Result<VisitFlow, Self::Error> {
+        Ok(VisitFlow::Continue)
+    }
+
+    /// Called after an item of a list
+    fn leave_list_item(
+        &mut self,
+        path: &Path,
+        index: usize,
+        item: Peek<'mem>,
+    ) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    /// Called before the entries of a map
+    fn enter_map(&mut self, path: &Path, value: PeekMap<'mem>) -> Result<VisitFlow, Self::Error> {
+        Ok(VisitFlow::Continue)
+    }
+
+    /// Called after the entries of a map
+    fn leave_map(&mut self, path: &Path, value: PeekMap<'mem>) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    /// Called before the value of a map entry. Keys aren't visited: they're only
+    /// passed here.
+    fn enter_map_entry(
+        &mut self,
+        path: &Path,
+        key: Peek<'mem>,
+        value: Peek<'mem>,
+    ) -> Result<VisitFlow, Self::Error> {
+        Ok(VisitFlow::Continue)
+    }
+
+    /// Called after the value of a map entry
+    fn leave_map_entry(
+        &mut self,
+        path: &Path,
+        key: Peek<'mem>,
+        value: Peek<'mem>,
+    ) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    /// Called before the fields of an enum's current variant, see [`PeekEnum::variant`]
+    fn enter_variant(
+        &mut self,
+        path: &Path,
+        value: PeekEnum<'mem>,
+    ) -> Result<VisitFlow, Self::Error> {
+        Ok(VisitFlow::Continue)
+    }
+
+    /// Called after the fields of an enum's current variant
+    fn leave_variant(&mut self, path: &Path, value: PeekEnum<'mem>) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    /// Called before the value held by an option, if any. The value is at the same path
+    /// as the option itself.
+    fn enter_option(
+        &mut self,
+        path: &Path,
+        value: PeekOption<'mem>,
+    ) -> Result<VisitFlow, Self::Error> {
+        Ok(VisitFlow::Continue)
+    }
+
+    /// Called after the value held by an option, if any
+    fn leave_option(&mut self, path: &Path, value: PeekOption<'mem>) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    /// Called instead of `enter_*`/`leave_*` when a value is one of its own ancestors
+    /// (same address, same shape): its children aren't visited again.
+    fn cycle(&mut self, path: &Path, value: Peek<'mem>) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+impl<'mem> Peek<'mem> {
+    /// Walks this value depth-first, calling `visitor` for every node.
+    ///
+    /// The traversal uses an explicit stack rather than recursion, so deeply nested
+    /// values don't overflow the call stack.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first error returned by the visitor.
+    pub fn visit<V: Visitor<'mem>>(self, visitor: &mut V) -> Result<(), V::Error> {
+        let mut traversal = Traversal {
+            visitor,
+            path: Path::root(),
+            stack: Vec::new(),
+        };
+        traversal.start(self)?;
+        traversal.run()
+    }
+}
+
+/// A container whose children are being visited
+enum Frame<'mem> {
+    Struct(PeekStruct<'mem>, usize),
+    List(PeekList<'mem>, usize),
+    Map(PeekMap<'mem>, PeekMapIter<'mem>),
+    Enum(PeekEnum<'mem>, usize),
+    Option(PeekOption<'mem>, bool),
+}
+
+impl<'mem> Frame<'mem> {
+    fn peek(&self) -> Peek<'mem> {
+        match self {
+            Frame::Struct(ps, _) => Peek::Struct(*ps),
+            Frame::List(pl, _) => Peek::List(*pl),
+            Frame::Map(pm, _) => Peek::Map(*pm),
+            Frame::Enum(pe, _) => Peek::Enum(*pe),
+            Frame::Option(po, _) => Peek::Option(*po),
+        }
+    }
+
+    fn next_child(&mut self) -> Option<Child<'mem>> {
+        match self {
+            Frame::Struct(ps, next) => {
+                let field = ps.def().fields.get(*next)?;
+                let value = ps.field_value(*next)?;
+                *next += 1;
+                Some(Child::Field(field, value))
+            }
+            Frame::List(pl, next) => {
+                let item = pl.item_at(*next)?;
+                *next += 1;
+                Some(Child::Item(*next - 1, item))
+            }
+            Frame::Map(_, iter) => iter.next().map(|(key, value)| Child::Entry(key, value)),
+            Frame::Enum(pe, next) => {
+                let field = pe.variant_fields().get(*next)?;
+                let value = pe.field_value(*next)?;
+                *next += 1;
+                Some(Child::Field(field, value))
+            }
+            Frame::Option(po, done) => {
+                let value = po.value().filter(|_| !*done)?;
+                *done = true;
+                Some(Child::Some(value))
+            }
+        }
+    }
+}
+
+/// A child of a [`Frame`], with what the visitor needs to enter and leave it
+#[derive(Clone, Copy)]
+enum Child<'mem> {
+    Field(&'static Field, Peek<'mem>),
+    Item(usize, Peek<'mem>),
+    Entry(Peek<'mem>, Peek<'mem>),
+    /// The value held by an option, which has no callbacks or path segment of its own
+    Some(Peek<'mem>),
+}
+
+impl<'mem> Child<'mem> {
+    fn segment(&self) -> Option<PathSegment> {
+        match self {
+            Child::Field(field, _) => Some(PathSegment::Field(String::from(field.name))),
+            Child::Item(index, _) => Some(PathSegment::Index(*index)),
+            Child::Entry(key, _) => Some(PathSegment::Key(key.to_string())),
+            Child::Some(_) => None,
+        }
+    }
+
+    fn value(&self) -> Peek<'mem> {
+        match self {
+            Child::Field(_, value)
+            | Child::Item(_, value)
+            | Child::Entry(_, value)
+            | Child::Some(value) => *value,
+        }
+    }
+}
+
+struct Traversal<'v, 'mem, V> {
+    visitor: &'v mut V,
+    path: Path,
+    /// Containers being visited, each with the child whose `leave_*` call is pending
+    stack: Vec<(Frame<'mem>, Option<Child<'mem>>)>,
+}
+
+impl<'mem, V: Visitor<'mem>> Traversal<'_, 'mem, V> {
+    fn run(&mut self) -> Result<(), V::Error> {
+        while let Some((frame, open)) = self.stack.last_mut() {
+            if let Some(child) = open.take() {
+                self.leave_child(child)?;
+                if child.segment().is_some() {
+                    self.path.pop();
+                }
+                continue;
+            }
+
+            match frame.next_child() {
+                Some(child) => {
+                    *open = Some(child);
+                    if let Some(segment) = child.segment() {
+                        self.path.push(segment);
+                    }
+                    if self.enter_child(child)? == VisitFlow::Continue {
+                        self.start(child.value())?;
+                    }
+                }
+                None => {
+                    let (frame, _) = self.stack.pop().unwrap();
+                    self.leave(frame)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Enters a node: scalars are visited right away, containers are pushed on the stack
+    fn start(&mut self, peek: Peek<'mem>) -> Result<(), V::Error> {
+        let (frame, flow) = match peek {
+            Peek::Value(pv) => return self.visitor.scalar(&self.path, pv),
+            _ if self.is_ancestor(peek) => return self.visitor.cycle(&self.path, peek),
+            Peek::Struct(ps) => (
+                Frame::Struct(ps, 0),
+                self.visitor.enter_struct(&self.path, ps)?,
+            ),
+            Peek::List(pl) => (Frame::List(pl, 0), self.visitor.enter_list(&self.path, pl)?),
+            Peek::Map(pm) => (
+                Frame::Map(pm, pm.iter()),
+                self.visitor.enter_map(&self.path, pm)?,
+            ),
+            Peek::Enum(pe) => (
+                Frame::Enum(pe, 0),
+                self.visitor.enter_variant(&self.path, pe)?,
+            ),
+            Peek::Option(po) => (
+                Frame::Option(po, false),
+                self.visitor.enter_option(&self.path, po)?,
+            ),
+        };
+        match flow {
+            VisitFlow::Continue => self.stack.push((frame, None)),
+            VisitFlow::Skip => self.leave(frame)?,
+        }
+        Ok(())
+    }
+
+    /// Returns true if `peek` is already being visited further up the stack
+    fn is_ancestor(&self, peek: Peek<'mem>) -> bool {
+        // zero-sized values share dangling addresses, so they can't be part of a cycle
+        if peek.shape().layout.size() == 0 {
+            return false;
+        }
+        // shapes are compared by address: comparing them structurally would recurse
+        // forever on the self-referential shapes that make cycles possible
+        self.stack.iter().any(|(frame, _)| {
+            let ancestor = frame.peek();
+            core::ptr::eq(ancestor.shape(), peek.shape())
+                && ancestor.data().as_byte_ptr() == peek.data().as_byte_ptr()
+        })
+    }
+
+    fn leave(&mut self, frame: Frame<'mem>) -> Result<(), V::Error> {
+        match frame {
+            Frame::Struct(ps, _) => self.visitor.leave_struct(&self.path, ps),
+            Frame::List(pl, _) => self.visitor.leave_list(&self.path, pl),
+            Frame::Map(pm, _) => self.visitor.leave_map(&self.path, pm),
+            Frame::Enum(pe, _) => self.visitor.leave_variant(&self.path, pe),
+            Frame::Option(po, _) => self.visitor.leave_option(&self.path, po),
+        }
+    }
+
+    fn enter_child(&mut self, child: Child<'mem>) -> Result<VisitFlow, V::Error> {
+        match child {
+            Child::Field(field, value) => self.visitor.enter_field(&self.path, field, value),
+            Child::Item(index, item) => self.visitor.enter_list_item(&self.path, index, item),
+            Child::Entry(key, value) => self.visitor.enter_map_entry(&self.path, key, value),
+            Child::Some(_) => Ok(VisitFlow::Continue),
+        }
+    }
+
+    fn leave_child(&mut self, child: Child<'mem>) -> Result<(), V::Error> {
+        match child {
+            Child::Field(field, value) => self.visitor.leave_field(&self.path, field, value),
+            Child::Item(index, item) => self.visitor.leave_list_item(&self.path, index, item),
+            Child::Entry(key, value) => self.visitor.leave_map_entry(&self.path, key, value),
+            Child::Some(_) => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,264 @@
+use std::collections::HashMap;
+
+use facet_derive::Facet;
+use facet_peek::{Path, PathErrorKind, PathPattern, PathSegment, Peek};
+use facet_testhelpers::Source;
+use facet_trait::Facet;
+
+use facet_trait as facet;
+
+#[derive(Facet)]
+struct Address {
+    city: String,
+}
+
+#[derive(Facet)]
+struct User {
+    name: String,
+    address: Address,
+}
+
+#[derive(Facet)]
+struct Point(u64, u64);
+
+#[derive(Facet)]
+struct Db {
+    users: Vec<User>,
+    settings: HashMap<String, String>,
+    origin: Point,
+    pair: (u64, String),
+    empty: Vec<u64>,
+}
+
+fn db() -> Db {
+    Db {
+        users: vec![
+            User {
+                name: "ada".to_string(),
+                address: Address {
+                    city: "London".to_string(),
+                },
+            },
+            User {
+                name: "grace".to_string(),
+                address: Address {
+                    city: "Arlington".to_string(),
+                },
+            },
+        ],
+        settings: HashMap::from([("theme".to_string(), "dark".to_string())]),
+        origin: Point(3, 4),
+        pair: (7, "seven".to_string()),
+        empty: vec![],
+    }
+}
+
+fn at<'mem>(peek: Peek<'mem>, path: &str) -> Peek<'mem> {
+    peek.at_path(&path.parse().unwrap()).unwrap()
+}
+
+#[test]
+fn parse_and_display_round_trip() {
+    for path in [
+        "",
+        "users[1].address.city",
+        "settings[\"theme\"]",
+        "settings[\"a \\\"quoted\\\" key\"]",
+        "origin.0",
+        "users[0]",
+        "[3][4]",
+    ] {
+        let parsed: Path = path.parse().unwrap();
+        assert_eq!(parsed.to_string(), path);
+    }
+
+    let parsed: Path = ".0.name[2]".parse().unwrap();
+    assert_eq!(
+        parsed.segments(),
+        [
+            PathSegment::Field("0".to_string()),
+            PathSegment::Field("name".to_string()),
+            PathSegment::Index(2),
+        ]
+    );
+}
+
+#[test]
+fn parse_errors() {
+    for (path, position) in [
+        ("users[", 6),
+        ("users[x]", 6),
+        ("users[1", 7),
+        ("settings[\"theme", 15),
+        ("settings[\"theme\"", 16),
+        ("users.", 6),
+        ("users..name", 6),
+        ("users name", 5),
+    ] {
+        let err = path.parse::<Path>().unwrap_err();
+        assert_eq!(err.position, position, "{path}: {err}");
+    }
+}
+
+#[test]
+fn patterns_match_paths() {
+    let matches = |pattern: &str, path: &str| {
+        let pattern: PathPattern = pattern.parse().unwrap();
+        assert_eq!(
+            pattern.to_string(),
+            pattern
+                .to_string()
+                .parse::<PathPattern>()
+                .unwrap()
+                .to_string()
+        );
+        pattern.matches(&path.parse().unwrap())
+    };
+
+    assert!(matches("users[0].name", "users[0].name"));
+    assert!(!matches("users[0].name", "users[1].name"));
+    assert!(matches("users[*].name", "users[1].name"));
+    assert!(matches("settings[*]", r#"settings["theme"]"#));
+    assert!(!matches("users[*]", "users.name"));
+
+    assert!(matches("*.created_at", "user.created_at"));
+    assert!(!matches("*.created_at", "users[0].created_at"));
+    assert!(matches("**.created_at", "users[0].created_at"));
+    assert!(matches("**.created_at", "created_at"));
+    assert!(matches("users.**", "users[0].address.city"));
+    assert!(!matches("users.**.id", "users[0].name"));
+
+    for (pattern, position) in [("*x", 1), ("users.*[", 8), ("users[*", 6)] {
+        let err = pattern.parse::<PathPattern>().unwrap_err();
+        assert_eq!(err.position, position, "{pattern}: {err}");
+    }
+}
+
+#[test]
+fn at_path_reaches_nested_values() {
+    let db = db();
+    let peek = Peek::new(&db);
+
+    assert_eq!(at(peek, "users[1].address.city").to_string(), "Arlington");
+    assert_eq!(at(peek, "settings[\"theme\"]").to_string(), "dark");
+    assert_eq!(at(peek, "origin.1").to_string(), "4");
+    assert_eq!(at(peek, "pair.1").to_string(), "seven");
+    assert_eq!(at(peek, "").shape(), Db::SHAPE);
+}
+
+#[test]
+fn at_path_errors_name_the_failing_segment() {
+    let db = db();
+    let peek = Peek::new(&db);
+    let fail = |path: &str| peek.at_path(&path.parse().unwrap()).unwrap_err();
+
+    let err = fail("users[5].name");
+    assert_eq!(err.path.to_string(), "users[5]");
+    assert_eq!(
+        err.kind,
+        PathErrorKind::IndexOutOfBounds { index: 5, len: 2 }
+    );
+    assert_eq!(
+        err.to_string(),
+        "at `users[5]`: index 5 is out of bounds (len is 2)"
+    );
+
+    let err = fail("users[0].adress.city");
+    assert_eq!(err.path.to_string(), "users[0].adress");
+    assert_eq!(err.kind, PathErrorKind::NoSuchField);
+
+    let err = fail("settings[\"font\"]");
+    assert_eq!(err.kind, PathErrorKind::NoSuchKey);
+
+    let err = fail("users.name");
+    assert_eq!(err.path.to_string(), "users.name");
+    assert!(matches!(
+        err.kind,
+        PathErrorKind::WrongKind {
+            expected: "a struct or an enum",
+            ..
+        }
+    ));
+    assert_eq!(
+        err.to_string(),
+        "at `users.name`: expected a struct or an enum, found Vec<User>"
+    );
+
+    let err = fail("origin[\"x\"]");
+    assert!(matches!(
+        err.kind,
+        PathErrorKind::WrongKind {
+            expected: "a map",
+            ..
+        }
+    ));
+}
+
+#[test]
+fn map_keys_can_be_indices() {
+    let map = HashMap::from([(42u64, "answer".to_string())]);
+    let peek = Peek::new(&map);
+    assert_eq!(at(peek, "[42]").to_string(), "answer");
+    assert_eq!(at(peek, "[\"42\"]").to_string(), "answer");
+}
+
+#[test]
+fn walk_paths_lists_every_leaf() {
+    let db = db();
+    let leaves: Vec<(String, String)> = Peek::new(&db)
+        .walk_paths()
+        .into_iter()
+        .map(|(path, peek)| (path.to_string(), format!("{peek:?}")))
+        .collect();
+    let leaves: Vec<(&str, &str)> = leaves
+        .iter()
+        .map(|(path, value)| (path.as_str(), value.as_str()))
+        .collect();
+
+    assert_eq!(
+        leaves,
+        [
+            ("users[0].name", "\"ada\""),
+            ("users[0].address.city", "\"London\""),
+            ("users[1].name", "\"grace\""),
+            ("users[1].address.city", "\"Arlington\""),
+            ("settings[\"theme\"]", "\"dark\""),
+            ("origin.0", "3"),
+            ("origin.1", "4"),
+            ("pair.0", "7"),
+            ("pair.1", "\"seven\""),
+            ("empty", "[]"),
+        ]
+    );
+
+    // every path leads back to its leaf
+    for (path, leaf) in Peek::new(&db).walk_paths() {
+        let found = Peek::new(&db).at_path(&path).unwrap();
+        assert_eq!(found.data().as_byte_ptr(), leaf.data().as_byte_ptr());
+    }
+}
+
+#[test]
+fn enums_expose_the_fields_of_their_variant() {
+    let sources = vec![
+        Source::File("/etc/app.toml".to_string()),
+        Source::Remote {
+            url: "https://example.com".to_string(),
+            retries: 3,
+        },
+        Source::Disabled,
+    ];
+    let peek = Peek::new(&sources);
+
+    assert_eq!(at(peek, "[0].0").to_string(), "/etc/app.toml");
+    assert_eq!(at(peek, "[1].retries").to_string(), "3");
+    let err = peek.at_path(&"[1].0".parse().unwrap()).unwrap_err();
+    assert_eq!(err.kind, PathErrorKind::NoSuchField);
+
+    let leaves: Vec<String> = peek
+        .walk_paths()
+        .into_iter()
+        .map(|(path, _)| path.to_string())
+        .collect();
+    assert_eq!(leaves, ["[0].0", "[1].url", "[1].retries", "[2]"]);
+}
@@ -0,0 +1,158 @@
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use facet_derive::Facet;
+use facet_peek::{FloatMode, Peek, Structural};
+use facet_testhelpers::Source;
+use facet_trait::Facet;
+
+use facet_trait as facet;
+
+/// Implements none of `PartialEq`, `Ord` or `Hash`
+#[derive(Debug, Facet)]
+struct Reading {
+    sensor: String,
+    value: f64,
+    tags: Vec<String>,
+    limits: HashMap<String, u32>,
+    source: Source,
+    note: Option<String>,
+}
+
+fn reading(sensor: &str, value: f64) -> Reading {
+    Reading {
+        sensor: sensor.to_string(),
+        value,
+        tags: vec!["outdoor".to_string()],
+        limits: HashMap::from([("low".to_string(), 1), ("high".to_string(), 9)]),
+        source: Source::File("sensors.toml".to_string()),
+        note: None,
+    }
+}
+
+#[test]
+fn values_are_compared_field_by_field() {
+    assert_eq!(
+        Structural::new(reading("a", 1.0)),
+        Structural::new(reading("a", 1.0))
+    );
+    assert_ne!(
+        Structural::new(reading("a", 1.0)),
+        Structural::new(reading("b", 1.0))
+    );
+
+    let mut other = reading("a", 1.0);
+    other.limits.insert("low".to_string(), 2);
+    assert_ne!(Structural::new(reading("a", 1.0)), Structural::new(other));
+
+    let mut other = reading("a", 1.0);
+    other.source = Source::Remote {
+        url: "https://example.com".to_string(),
+        retries: 3,
+    };
+    assert_ne!(Structural::new(reading("a", 1.0)), Structural::new(other));
+
+    let mut other = reading("a", 1.0);
+    other.note = Some("calibrated".to_string());
+    assert_ne!(Structural::new(reading("a", 1.0)), Structural::new(other));
+}
+
+#[test]
+fn float_modes() {
+    let eq = |a: f64, b: f64, floats| Peek::new(&a).structural_eq(&Peek::new(&b), floats).unwrap();
+
+    assert!(eq(f64::NAN, f64::NAN, FloatMode::Total));
+    assert!(!eq(-0.0, 0.0, FloatMode::Total));
+
+    assert!(!eq(f64::NAN, f64::NAN, FloatMode::Ieee));
+    assert!(eq(-0.0, 0.0, FloatMode::Ieee));
+
+    assert!(eq(f64::NAN, -f64::NAN, FloatMode::NanEqual));
+    assert!(eq(-0.0, 0.0, FloatMode::NanEqual));
+
+    let nan = Structural::new(reading("a", f64::NAN));
+    assert_eq!(nan, Structural::new(reading("a", f64::NAN)));
+    let nan = nan.with_floats(FloatMode::Ieee);
+    assert_ne!(nan, Structural::new(reading("a", f64::NAN)));
+}
+
+#[test]
+fn values_are_ordered_by_structure() {
+    let mut readings: Vec<_> = [
+        reading("b", 1.0),
+        reading("a", f64::NAN),
+        reading("a", -2.5),
+        reading("a", 3.0),
+    ]
+    .into_iter()
+    .map(Structural::new)
+    .collect();
+    readings.sort();
+    let sorted: Vec<_> = readings.iter().map(|r| (&r.sensor[..], r.value)).collect();
+    assert_eq!(
+        format!("{sorted:?}"),
+        r#"[("a", -2.5), ("a", 3.0), ("a", NaN), ("b", 1.0)]"#
+    );
+
+    let sources: BTreeSet<_> = [
+        Source::Remote {
+            url: "x".to_string(),
+            retries: 1,
+        },
+        Source::File("b".to_string()),
+        Source::Disabled,
+        Source::File("a".to_string()),
+    ]
+    .into_iter()
+    .map(Structural::new)
+    .collect();
+    let sources: Vec<_> = sources.into_iter().map(Structural::into_inner).collect();
+    assert_eq!(
+        sources,
+        [
+            Source::Disabled,
+            Source::File("a".to_string()),
+            Source::File("b".to_string()),
+            Source::Remote {
+                url: "x".to_string(),
+                retries: 1,
+            },
+        ]
+    );
+
+    let cmp = |a: &Vec<u32>, b: &Vec<u32>| {
+        Peek::new(a)
+            .structural_cmp(&Peek::new(b), FloatMode::Total)
+            .unwrap()
+    };
+    assert!(cmp(&vec![1, 2], &vec![1, 2, 0]).is_lt());
+    assert!(cmp(&vec![1, 3], &vec![1, 2, 0]).is_gt());
+}
+
+#[test]
+fn equal_values_hash_the_same() {
+    // insertion order doesn't matter for maps
+    let mut other = reading("a", 1.0);
+    other.limits = (0..32).map(|i| (i.to_string(), i)).collect();
+    let mut same = reading("a", 1.0);
+    same.limits = (0..32).rev().map(|i| (i.to_string(), i)).collect();
+
+    let set: HashSet<_> = [reading("a", 1.0), reading("a", 1.0), other, same]
+        .into_iter()
+        .map(Structural::new)
+        .collect();
+    assert_eq!(set.len(), 2);
+
+    let set: HashSet<_> = [0.0, -0.0, f64::NAN, -f64::NAN]
+        .into_iter()
+        .map(|value| Structural::new(value).with_floats(FloatMode::NanEqual))
+        .collect();
+    assert_eq!(set.len(), 2);
+}
+
+#[test]
+fn different_shapes_are_not_equal() {
+    let a = Peek::new(&1u32);
+    let b = Peek::new(&1u64);
+    assert_eq!(a.structural_eq(&b, FloatMode::Total), Some(false));
+    assert_eq!(a.structural_cmp(&b, FloatMode::Total), None);
+}
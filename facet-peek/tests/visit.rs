@@ -0,0 +1,361 @@
+use std::collections::HashMap;
+
+use facet_derive::Facet;
+use facet_peek::{
+    Path, Peek, PeekEnum, PeekList, PeekMap, PeekStruct, PeekValue, VisitFlow, Visitor,
+};
+use facet_testhelpers::Source;
+use facet_trait::{Def, Facet, Field, ListDef, ListVTable, OpaqueConst, Shape, ShapeExt as _};
+
+use facet_trait as facet;
+
+#[derive(Facet)]
+struct Job {
+    name: String,
+    retries: Vec<u64>,
+    env: HashMap<String, u64>,
+    source: Source,
+    fallback: Source,
+}
+
+fn job() -> Job {
+    Job {
+        name: "build".to_string(),
+        retries: vec![1, 2],
+        env: HashMap::from([("JOBS".to_string(), 4)]),
+        source: Source::Remote {
+            url: "https://example.com".to_string(),
+            retries: 3,
+        },
+        fallback: Source::Disabled,
+    }
+}
+
+/// Records every callback as a line, with the path it was called at
+#[derive(Default)]
+struct Recorder {
+    events: Vec<String>,
+    skip: Option<&'static str>,
+}
+
+impl Recorder {
+    fn log(&mut self, path: &Path, event: impl std::fmt::Display) {
+        self.events.push(format!("{path}: {event}"));
+    }
+
+    fn flow(&self, path: &Path) -> VisitFlow {
+        if self.skip == Some(path.to_string().as_str()) {
+            VisitFlow::Skip
+        } else {
+            VisitFlow::Continue
+        }
+    }
+}
+
+impl<'mem> Visitor<'mem> for Recorder {
+    type Error = std::convert::Infallible;
+
+    fn scalar(&mut self, path: &Path, value: PeekValue<'mem>) -> Result<(), Self::Error> {
+        self.log(path, format_args!("scalar {value:?}"));
+        Ok(())
+    }
+
+    fn enter_struct(
+        &mut self,
+        path: &Path,
+        value: PeekStruct<'mem>,
+    ) -> Result<VisitFlow, Self::Error> {
+        self.log(path, format_args!("enter struct {}", value.shape()));
+        Ok(self.flow(path))
+    }
+
+    fn leave_struct(&mut self, path: &Path, _value: PeekStruct<'mem>) -> Result<(), Self::Error> {
+        self.log(path, "leave struct");
+        Ok(())
+    }
+
+    fn enter_field(
+        &mut self,
+        path: &Path,
+        field: &'static Field,
+        _value: Peek<'mem>,
+    ) -> Result<VisitFlow, Self::Error> {
+        self.log(path, format_args!("enter field {}", field.name));
+        Ok(self.flow(path))
+    }
+
+    fn leave_field(
+        &mut self,
+        path: &Path,
+        field: &'static Field,
+        _value: Peek<'mem>,
+    ) -> Result<(), Self::Error> {
+        self.log(path, format_args!("leave field {}", field.name));
+        Ok(())
+    }
+
+    fn enter_list(&mut self, path: &Path, value: PeekList<'mem>) -> Result<VisitFlow, Self::Error> {
+        self.log(path, format_args!("enter list of {}", value.len()));
+        Ok(self.flow(path))
+    }
+
+    fn leave_list(&mut self, path: &Path, _value: PeekList<'mem>) -> Result<(), Self::Error> {
+        self.log(path, "leave list");
+        Ok(())
+    }
+
+    fn enter_list_item(
+        &mut self,
+        path: &Path,
+        index: usize,
+        _item: Peek<'mem>,
+    ) -> Result<VisitFlow, Self::Error> {
+        self.log(path, format_args!("enter item {index}"));
+        Ok(self.flow(path))
+    }
+
+    fn leave_list_item(
+        &mut self,
+        path: &Path,
+        index: usize,
+        _item: Peek<'mem>,
+    ) -> Result<(), Self::Error> {
+        self.log(path, format_args!("leave item {index}"));
+        Ok(())
+    }
+
+    fn enter_map(&mut self, path: &Path, value: PeekMap<'mem>) -> Result<VisitFlow, Self::Error> {
+        self.log(path, format_args!("enter map of {}", value.len()));
+        Ok(self.flow(path))
+    }
+
+    fn leave_map(&mut self, path: &Path, _value: PeekMap<'mem>) -> Result<(), Self::Error> {
+        self.log(path, "leave map");
+        Ok(())
+    }
+
+    fn enter_map_entry(
+        &mut self,
+        path: &Path,
+        key: Peek<'mem>,
+        _value: Peek<'mem>,
+    ) -> Result<VisitFlow, Self::Error> {
+        self.log(path, format_args!("enter entry {key:?}"));
+        Ok(self.flow(path))
+    }
+
+    fn leave_map_entry(
+        &mut self,
+        path: &Path,
+        key: Peek<'mem>,
+        _value: Peek<'mem>,
+    ) -> Result<(), Self::Error> {
+        self.log(path, format_args!("leave entry {key:?}"));
+        Ok(())
+    }
+
+    fn enter_variant(
+        &mut self,
+        path: &Path,
+        value: PeekEnum<'mem>,
+    ) -> Result<VisitFlow, Self::Error> {
+        self.log(path, format_args!("enter variant {}", value.variant().name));
+        Ok(self.flow(path))
+    }
+
+    fn leave_variant(&mut self, path: &Path, value: PeekEnum<'mem>) -> Result<(), Self::Error> {
+        self.log(path, format_args!("leave variant {}", value.variant().name));
+        Ok(())
+    }
+
+    fn cycle(&mut self, path: &Path, value: Peek<'mem>) -> Result<(), Self::Error> {
+        self.log(path, format_args!("cycle {}", value.shape()));
+        Ok(())
+    }
+}
+
+fn record(peek: Peek<'_>, skip: Option<&'static str>) -> Vec<String> {
+    let mut recorder = Recorder {
+        skip,
+        ..Default::default()
+    };
+    peek.visit(&mut recorder).unwrap();
+    recorder.events
+}
+
+#[test]
+fn visits_every_node_in_order() {
+    let job = job();
+    assert_eq!(
+        record(Peek::new(&job), None),
+        [
+            ": enter struct Job",
+            "name: enter field name",
+            "name: scalar \"build\"",
+            "name: leave field name",
+            "retries: enter field retries",
+            "retries: enter list of 2",
+            "retries[0]: enter item 0",
+            "retries[0]: scalar 1",
+            "retries[0]: leave item 0",
+            "retries[1]: enter item 1",
+            "retries[1]: scalar 2",
+            "retries[1]: leave item 1",
+            "retries: leave list",
+            "retries: leave field retries",
+            "env: enter field env",
+            "env: enter map of 1",
+            "env[\"JOBS\"]: enter entry \"JOBS\"",
+            "env[\"JOBS\"]: scalar 4",
+            "env[\"JOBS\"]: leave entry \"JOBS\"",
+            "env: leave map",
+            "env: leave field env",
+            "source: enter field source",
+            "source: enter variant Remote",
+            "source.url: enter field url",
+            "source.url: scalar \"https://example.com\"",
+            "source.url: leave field url",
+            "source.retries: enter field retries",
+            "source.retries: scalar 3",
+            "source.retries: leave field retries",
+            "source: leave variant Remote",
+            "source: leave field source",
+            "fallback: enter field fallback",
+            "fallback: enter variant Disabled",
+            "fallback: leave variant Disabled",
+            "fallback: leave field fallback",
+            ": leave struct",
+        ]
+    );
+}
+
+#[test]
+fn skipped_nodes_are_still_left() {
+    let job = job();
+
+    let events = record(Peek::new(&job), Some("retries"));
+    assert!(!events.contains(&"retries: enter list of 2".to_string()));
+    assert_eq!(
+        events[4..7],
+        [
+            "retries: enter field retries",
+            "retries: leave field retries",
+            "env: enter field env",
+        ]
+    );
+
+    let events = record(Peek::new(&job), Some("source"));
+    let source: Vec<_> = events.iter().filter(|e| e.starts_with("source")).collect();
+    assert_eq!(
+        source,
+        ["source: enter field source", "source: leave field source"]
+    );
+
+    let events = record(Peek::new(&job), Some(""));
+    assert_eq!(events, [": enter struct Job", ": leave struct"]);
+}
+
+#[test]
+fn roots_that_arent_structs() {
+    assert_eq!(record(Peek::new(&7u64), None), [": scalar 7"]);
+
+    let source = Source::File("/etc/app.toml".to_string());
+    assert_eq!(
+        record(Peek::new(&source), None),
+        [
+            ": enter variant File",
+            "0: enter field 0",
+            "0: scalar \"/etc/app.toml\"",
+            "0: leave field 0",
+            ": leave variant File",
+        ]
+    );
+}
+
+/// Stops at the first scalar that isn't a string
+struct FirstNumber(Vec<String>);
+
+impl<'mem> Visitor<'mem> for FirstNumber {
+    type Error = String;
+
+    fn scalar(&mut self, path: &Path, value: PeekValue<'mem>) -> Result<(), Self::Error> {
+        if value.shape().is_type::<String>() {
+            self.0.push(path.to_string());
+            Ok(())
+        } else {
+            Err(format!("{path} = {value}"))
+        }
+    }
+}
+
+#[test]
+fn errors_stop_the_traversal() {
+    let job = job();
+    let mut visitor = FirstNumber(Vec::new());
+    assert_eq!(
+        Peek::new(&job).visit(&mut visitor),
+        Err("retries[0] = 1".to_string())
+    );
+    assert_eq!(visitor.0, ["name"]);
+}
+
+/// A list that contains itself: its only item is at its own address
+#[derive(Debug)]
+struct Ouroboros {
+    _byte: u8,
+}
+
+static OUROBOROS_SHAPE: Shape = Shape::builder()
+    .layout(core::alloc::Layout::new::<Ouroboros>())
+    .vtable(facet::value_vtable!(Ouroboros, |f, _opts| {
+        core::fmt::Write::write_str(f, "Ouroboros")
+    }))
+    .def(Def::List(
+        ListDef::builder()
+            .vtable(
+                &const {
+                    ListVTable::builder()
+                        .init_in_place_with_capacity(|_, _| Err(()))
+                        .push(|_, _| panic!("cannot push to an Ouroboros"))
+                        .len(|_| 1)
+                        .get_item_ptr(|ptr, _| unsafe {
+                            OpaqueConst::new_unchecked(ptr.as_byte_ptr())
+                        })
+                        .build()
+                },
+            )
+            .t(&OUROBOROS_SHAPE)
+            .build(),
+    ))
+    .build();
+
+unsafe impl Facet for Ouroboros {
+    const ARCHETYPE: Self = Ouroboros { _byte: 0 };
+    const SHAPE: &'static Shape = &OUROBOROS_SHAPE;
+}
+
+#[derive(Facet)]
+struct Holder {
+    snake: Ouroboros,
+}
+
+#[test]
+fn cycles_are_reported_once() {
+    let holder = Holder {
+        snake: Ouroboros { _byte: 0 },
+    };
+    assert_eq!(
+        record(Peek::new(&holder), None),
+        [
+            ": enter struct Holder",
+            "snake: enter field snake",
+            "snake: enter list of 1",
+            "snake[0]: enter item 0",
+            "snake[0]: cycle Ouroboros",
+            "snake[0]: leave item 0",
+            "snake: leave list",
+            "snake: leave field snake",
+            ": leave struct",
+        ]
+    );
+}
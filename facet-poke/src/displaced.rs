@@ -0,0 +1,74 @@
+use alloc::vec::Vec;
+use facet_trait::{Field, OpaqueUninit};
+
+use crate::ISet;
+
+/// The previous values of fields that are being written again
+///
+/// Getting a writer for an initialized field moves its value out of the way instead of
+/// dropping it: it's only dropped once the new value is committed, and it's moved back
+/// if the write is abandoned, so a failed write never leaves the field uninitialized.
+///
+/// A field has a displaced value only while it's marked uninitialized.
+#[derive(Default)]
+pub(crate) struct Displaced {
+    values: Vec<(usize, OpaqueUninit<'static>)>,
+}
+
+impl Displaced {
+    /// Returns true if the previous value of field `index` is kept aside
+    pub(crate) fn has(&self, index: usize) -> bool {
+        self.values.iter().any(|&(i, _)| i == index)
+    }
+
+    /// Moves the value of `field` out of `data`, keeping it aside
+    ///
+    /// # Safety
+    ///
+    /// `field` must be initialized in `data`, and be marked uninitialized afterwards.
+    pub(crate) unsafe fn displace(&mut self, index: usize, field: &Field, data: OpaqueUninit<'_>) {
+        debug_assert!(!self.has(index), "field {index} was displaced twice");
+        let aside = field.shape.allocate();
+        unsafe {
+            core::ptr::copy_nonoverlapping(
+                data.field_uninit(field.offset).as_ptr(),
+                aside.as_mut_ptr(),
+                field.shape.layout.size(),
+            );
+        }
+        self.values.push((index, aside));
+    }
+
+    /// Drops the previous value of field `index`, if any, now that a new one is committed
+    pub(crate) fn commit(&mut self, index: usize, field: &Field) {
+        let Some(position) = self.values.iter().position(|&(i, _)| i == index) else {
+            return;
+        };
+        let (_, aside) = self.values.swap_remove(position);
+        unsafe {
+            if let Some(drop_fn) = field.shape.vtable.drop_in_place {
+                drop_fn(aside.assume_init());
+            }
+            field.shape.deallocate(aside);
+        }
+    }
+
+    /// Moves the previous values back into the fields that weren't written again, and
+    /// marks them initialized
+    ///
+    /// This also frees the bookkeeping, so pokes can be forgotten right after.
+    pub(crate) fn restore(&mut self, fields: &[Field], data: OpaqueUninit<'_>, iset: &mut ISet) {
+        for (index, aside) in core::mem::take(&mut self.values) {
+            let field = &fields[index];
+            unsafe {
+                core::ptr::copy_nonoverlapping(
+                    aside.as_ptr(),
+                    data.field_uninit(field.offset).as_mut_ptr(),
+                    field.shape.layout.size(),
+                );
+                field.shape.deallocate(aside);
+            }
+            iset.set(index);
+        }
+    }
+}
@@ -1,9 +1,12 @@
+use alloc::boxed::Box;
 use core::ptr::NonNull;
 use facet_trait::{
-    EnumDef, EnumRepr, Facet, FieldError, Opaque, OpaqueUninit, Shape, ShapeExt as _, VariantKind,
+    EnumDef, EnumRepr, Facet, Field, FieldError, Opaque, OpaqueUninit, Shape, ShapeExt as _,
+    VariantKind,
 };
 
 use super::{ISet, Poke, PokeValue};
+use crate::displaced::Displaced;
 
 /// Represents an enum before a variant has been selected
 pub struct PokeEnumNoVariant<'mem> {
@@ -148,13 +151,19 @@ impl<'mem> PokeEnumNoVariant<'mem> {
             }
         }
 
+        let field_count = match &variant.kind {
+            VariantKind::Tuple { fields } | VariantKind::Struct { fields } => fields.len(),
+            _ => 0,
+        };
+
         // Create PokeEnum with the selected variant
         Ok(PokeEnum {
             data: self.data,
-            iset: Default::default(),
+            iset: ISet::new(field_count),
             shape: self.shape,
             def: self.def,
             selected_variant: variant_index,
+            displaced: Displaced::default(),
         })
     }
 }
@@ -166,67 +175,110 @@ pub struct PokeEnum<'mem> {
     shape: &'static Shape,
     def: EnumDef,
     selected_variant: usize,
+    displaced: Displaced,
 }
 
 impl<'mem> PokeEnum<'mem> {
+    /// Shape getter
+    #[inline(always)]
+    pub fn shape(&self) -> &'static Shape {
+        self.shape
+    }
+
     /// Returns the currently selected variant index
     pub fn selected_variant_index(&self) -> usize {
         self.selected_variant
     }
 
+    /// Returns the fields of the selected variant
+    fn fields(&self) -> &'static [Field] {
+        match &self.def.variants[self.selected_variant].kind {
+            VariantKind::Tuple { fields } | VariantKind::Struct { fields } => fields,
+            _ => &[],
+        }
+    }
+
+    /// Returns true if the field at `index` holds a value: either it was initialized, or
+    /// it's being written again and still has its previous value to fall back on
+    fn has_value(&self, index: usize) -> bool {
+        self.iset.has(index) || self.displaced.has(index)
+    }
+
+    /// Puts back the previous values of the fields that were not written again
+    fn restore_displaced(&mut self) {
+        let fields = self.fields();
+        self.displaced.restore(fields, self.data, &mut self.iset);
+    }
+
     /// Get a field writer for a field in the currently selected variant.
     ///
+    /// If the field was already initialized, its previous value is kept aside until the
+    /// field is marked initialized again, and only dropped then. If the write is abandoned
+    /// instead, the previous value is put back when the enum is built or dropped.
+    ///
     /// # Errors
     ///
     /// Returns an error if:
     /// - The field name doesn't exist in the selected variant.
     /// - The selected variant is a unit variant (which has no fields).
     pub fn variant_field_by_name<'s>(&'s mut self, name: &str) -> Result<Poke<'s>, FieldError> {
-        let variant = &self.def.variants[self.selected_variant];
+        let index = self
+            .fields()
+            .iter()
+            .position(|f| f.name == name)
+            .ok_or(FieldError::NoSuchStaticField)?;
+        self.variant_field(index)
+    }
 
-        // Find the field in the variant
-        match &variant.kind {
-            VariantKind::Unit => {
-                // Unit variants have no fields
-                Err(FieldError::NoSuchStaticField)
-            }
-            VariantKind::Tuple { fields } => {
-                // For tuple variants, find the field by name
-                let field = fields
-                    .iter()
-                    .find(|f| f.name == name)
-                    .ok_or(FieldError::NoSuchStaticField)?;
-
-                // Get the field's address
-                let field_data = unsafe { self.data.field_uninit(field.offset) };
-                let poke = unsafe { Poke::unchecked_new(field_data, field.shape) };
-                Ok(poke)
-            }
-            VariantKind::Struct { fields } => {
-                // For struct variants, find the field by name
-                let field = fields
-                    .iter()
-                    .find(|f| f.name == name)
-                    .ok_or(FieldError::NoSuchStaticField)?;
-
-                // Get the field's address
-                let field_data = unsafe { self.data.field_uninit(field.offset) };
-                let poke = unsafe { Poke::unchecked_new(field_data, field.shape) };
-                Ok(poke)
-            }
-            _ => {
-                panic!("Unsupported enum variant kind: {:?}", variant.kind);
-            }
+    /// Get a field writer for a field of the currently selected variant, by index.
+    ///
+    /// If the field was already initialized, its previous value is kept aside until the
+    /// field is marked initialized again, and only dropped then. If the write is abandoned
+    /// instead, the previous value is put back when the enum is built or dropped.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the index is out of bounds for the selected variant (unit
+    /// variants have no fields).
+    pub fn variant_field(&mut self, index: usize) -> Result<Poke<'mem>, FieldError> {
+        let field = self
+            .fields()
+            .get(index)
+            .ok_or(FieldError::IndexOutOfBounds)?;
+
+        if self.iset.has(index) {
+            unsafe { self.displaced.displace(index, field, self.data) };
+            self.iset.unset(index);
         }
+
+        let field_data = unsafe { self.data.field_uninit(field.offset) };
+        Ok(unsafe { Poke::unchecked_new(field_data, field.shape) })
     }
 
     /// Marks a field in the current variant as initialized.
     ///
+    /// If the field had a value before, it's dropped now.
+    ///
     /// # Safety
     ///
     /// The caller must ensure that the field is not already initialized.
     pub unsafe fn mark_field_as_initialized(&mut self, field_index: usize) {
         self.iset.set(field_index);
+        if let Some(field) = self.fields().get(field_index) {
+            self.displaced.commit(field_index, field);
+        }
+    }
+
+    /// Returns the first field of the selected variant that hasn't been initialized yet, if any.
+    pub fn first_uninitialized_field(&self) -> Option<&'static Field> {
+        match &self.def.variants[self.selected_variant].kind {
+            VariantKind::Tuple { fields } | VariantKind::Struct { fields } => fields
+                .iter()
+                .enumerate()
+                .find(|(index, _)| !self.has_value(*index))
+                .map(|(_, field)| field),
+            _ => None,
+        }
     }
 
     /// Checks if all required fields in the enum are initialized.
@@ -245,7 +297,7 @@ impl<'mem> PokeEnum<'mem> {
             VariantKind::Tuple { fields } | VariantKind::Struct { fields } => {
                 // Check each field
                 for (field_index, field) in fields.iter().enumerate() {
-                    if !self.iset.has(field_index) {
+                    if !self.has_value(field_index) {
                         panic!(
                             "Field '{}' of variant '{}' was not initialized. Complete schema:\n{}",
                             field.name, variant.name, self.shape
@@ -277,8 +329,9 @@ impl<'mem> PokeEnum<'mem> {
     /// # Panics
     ///
     /// This function will panic if any required field is not initialized.
-    pub fn build_in_place(self) -> Opaque<'mem> {
+    pub fn build_in_place(mut self) -> Opaque<'mem> {
         // ensure all fields are initialized
+        self.restore_displaced();
         self.assert_all_fields_initialized();
         let data = unsafe { self.data.assume_init() };
         // prevent field drops when the PokeEnum is dropped
@@ -293,7 +346,8 @@ impl<'mem> PokeEnum<'mem> {
     /// This function will panic if:
     /// - Not all fields in the selected variant have been initialized.
     /// - The generic type parameter T does not match the shape that this PokeEnum is building.
-    pub fn build<T: Facet>(self) -> T {
+    pub fn build<T: Facet>(mut self) -> T {
+        self.restore_displaced();
         self.assert_all_fields_initialized();
         self.assert_matching_shape::<T>();
 
@@ -312,7 +366,8 @@ impl<'mem> PokeEnum<'mem> {
     /// This function will panic if:
     /// - Not all fields in the selected variant have been initialized.
     /// - The generic type parameter T does not match the shape that this PokeEnum is building.
-    pub fn build_boxed<T: Facet>(self) -> Box<T> {
+    pub fn build_boxed<T: Facet>(mut self) -> Box<T> {
+        self.restore_displaced();
         self.assert_all_fields_initialized();
         self.assert_matching_shape::<T>();
 
@@ -329,7 +384,8 @@ impl<'mem> PokeEnum<'mem> {
     /// and must be large enough to hold the value.
     /// The caller is responsible for ensuring that the target memory is properly deallocated
     /// when it's no longer needed.
-    pub unsafe fn move_into(self, target: NonNull<u8>) {
+    pub unsafe fn move_into(mut self, target: NonNull<u8>) {
+        self.restore_displaced();
         self.assert_all_fields_initialized();
         unsafe {
             core::ptr::copy_nonoverlapping(
@@ -344,6 +400,7 @@ impl<'mem> PokeEnum<'mem> {
 
 impl Drop for PokeEnum<'_> {
     fn drop(&mut self) {
+        self.restore_displaced();
         let variant = &self.def.variants[self.selected_variant];
 
         // Drop fields based on the variant kind
@@ -384,7 +441,7 @@ pub enum VariantError {
     NoSuchVariant,
 }
 
-impl std::error::Error for VariantError {}
+impl core::error::Error for VariantError {}
 
 impl core::fmt::Display for VariantError {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
@@ -1,13 +1,16 @@
+#![cfg_attr(not(feature = "std"), no_std)]
 #![warn(missing_docs)]
 #![doc = include_str!("../README.md")]
 
 //! Allows poking (writing to) shapes
 
-use core::alloc::Layout;
+extern crate alloc;
+
+use alloc::boxed::Box;
 
 pub use facet_peek::*;
 
-use facet_trait::{Def, Facet, OpaqueUninit, Shape};
+use facet_trait::{Def, Facet, Opaque, OpaqueConst, OpaqueUninit, Shape};
 
 mod value;
 pub use value::*;
@@ -24,6 +27,17 @@ pub use struct_::*;
 mod enum_;
 pub use enum_::*;
 
+mod option;
+pub use option::*;
+
+mod displaced;
+
+mod peek_mut;
+pub use peek_mut::*;
+
+mod partial;
+pub use partial::*;
+
 /// Allows writing values of different kinds.
 #[non_exhaustive]
 pub enum Poke<'mem> {
@@ -37,19 +51,124 @@ pub enum Poke<'mem> {
     Struct(PokeStruct<'mem>),
     /// An enum variant. See [`PokeEnum`].
     Enum(PokeEnumNoVariant<'mem>),
+    /// An option. See [`PokeOption`].
+    Option(PokeOption<'mem>),
 }
 
-/// Ensures a value is dropped when the guard is dropped.
+/// Ensures the memory backing a poke is deallocated when the guard is dropped.
+///
+/// Zero-sized shapes are never actually allocated, so their guards have nothing to free.
+///
+/// The guard never drops the value itself: whatever was partially initialized is
+/// dropped by the poke that initialized it ([`PokeStruct`], [`PokeEnum`], [`PokeList`],
+/// [`PokeMap`]), so drop the poke before the guard.
 pub struct Guard {
     ptr: *mut u8,
-    layout: Layout,
     shape: &'static Shape,
 }
 
+impl Guard {
+    /// Returns the guarded allocation
+    pub fn as_uninit(&self) -> OpaqueUninit<'_> {
+        OpaqueUninit::new(self.ptr)
+    }
+
+    /// Returns the shape the guarded allocation was made for
+    pub fn shape(&self) -> &'static Shape {
+        self.shape
+    }
+}
+
 impl Drop for Guard {
     fn drop(&mut self) {
         unsafe {
-            std::alloc::dealloc(self.ptr, self.layout);
+            self.shape.deallocate(OpaqueUninit::new(self.ptr));
+        }
+    }
+}
+
+/// A value built on the side, before it's moved into its container.
+///
+/// Unlike a [`Guard`], a scratch drops its value, unless the value was moved out of
+/// with [`Scratch::take`].
+pub struct Scratch {
+    guard: Guard,
+    initialized: bool,
+}
+
+impl Scratch {
+    /// Allocates room for a value of the given shape, which starts out uninitialized
+    pub fn new(shape: &'static Shape) -> Self {
+        let (_, guard) = Poke::alloc_shape(shape);
+        Self {
+            guard,
+            initialized: false,
+        }
+    }
+
+    /// Allocates room for a value of the given shape, along with a poke to build it.
+    ///
+    /// Once the poke has built the value, call [`Scratch::mark_initialized`].
+    pub fn alloc<'mem>(shape: &'static Shape) -> (Poke<'mem>, Self) {
+        let (poke, guard) = Poke::alloc_shape(shape);
+        let scratch = Self {
+            guard,
+            initialized: false,
+        };
+        (poke, scratch)
+    }
+
+    /// Returns the allocation, to build the value in place
+    pub fn as_uninit(&self) -> OpaqueUninit<'_> {
+        self.guard.as_uninit()
+    }
+
+    /// Returns the shape of the value
+    pub fn shape(&self) -> &'static Shape {
+        self.guard.shape()
+    }
+
+    /// Returns true if the scratch holds a value
+    pub fn is_initialized(&self) -> bool {
+        self.initialized
+    }
+
+    /// Records that the value was built, so that it's dropped along with the scratch
+    ///
+    /// # Safety
+    ///
+    /// The allocation must hold a fully initialized value of the scratch's shape.
+    pub unsafe fn mark_initialized(&mut self) {
+        self.initialized = true;
+    }
+
+    /// Borrows the value
+    pub fn get(&self) -> OpaqueConst<'_> {
+        assert!(self.initialized, "borrowing a value that isn't there");
+        unsafe { self.guard.as_uninit().assume_init() }.as_const()
+    }
+
+    /// Borrows the value mutably
+    pub fn get_mut(&mut self) -> Opaque<'_> {
+        assert!(self.initialized, "borrowing a value that isn't there");
+        unsafe { self.guard.as_uninit().assume_init() }
+    }
+
+    /// Returns the value, which the caller must move out of
+    pub fn take(&mut self) -> Opaque<'_> {
+        assert!(self.initialized, "taking a value that isn't there");
+        self.initialized = false;
+        unsafe { self.guard.as_uninit().assume_init() }
+    }
+}
+
+impl Drop for Scratch {
+    fn drop(&mut self) {
+        if !self.initialized {
+            return;
+        }
+        if let Some(drop_fn) = self.guard.shape().vtable.drop_in_place {
+            unsafe { drop_fn(self.guard.as_uninit().assume_init()) };
         }
     }
 }
@@ -57,14 +176,17 @@ impl Drop for Guard {
 impl<'mem> Poke<'mem> {
     /// Allocates a new poke of a type that implements facet
     pub fn alloc<S: Facet>() -> (Self, Guard) {
-        let data = S::SHAPE.allocate();
-        let layout = Layout::new::<S>();
+        Self::alloc_shape(S::SHAPE)
+    }
+
+    /// Allocates a new poke for a value of the given shape
+    pub fn alloc_shape(shape: &'static Shape) -> (Self, Guard) {
+        let data = shape.allocate();
         let guard = Guard {
             ptr: data.as_mut_ptr(),
-            layout,
-            shape: S::SHAPE,
+            shape,
         };
-        let poke = unsafe { Self::unchecked_new(data, S::SHAPE) };
+        let poke = unsafe { Self::unchecked_new(data, shape) };
         (poke, guard)
     }
 
@@ -91,6 +213,9 @@ impl<'mem> Poke<'mem> {
             Def::Enum(enum_def) => {
                 Poke::Enum(unsafe { PokeEnumNoVariant::new(data, shape, enum_def) })
             }
+            Def::Option(option_def) => {
+                Poke::Option(unsafe { PokeOption::new(data, shape, option_def) })
+            }
             _ => todo!("unsupported def: {:?}", shape.def),
         }
     }
@@ -135,6 +260,14 @@ impl<'mem> Poke<'mem> {
         }
     }
 
+    /// Converts this Poke into a PokeOption, panicking if it's not an Option variant
+    pub fn into_option(self) -> PokeOption<'mem> {
+        match self {
+            Poke::Option(o) => o,
+            _ => panic!("expected Option variant"),
+        }
+    }
+
     /// Converts into a value, so we can manipulate it
     #[inline(always)]
     pub fn into_value(self) -> PokeValue<'mem> {
@@ -144,6 +277,7 @@ impl<'mem> Poke<'mem> {
             Poke::Map(m) => m.into_value(),
             Poke::Struct(s) => s.into_value(),
             Poke::Enum(e) => e.into_value(),
+            Poke::Option(o) => o.into_value(),
         }
     }
 
@@ -156,45 +290,110 @@ impl<'mem> Poke<'mem> {
             Poke::Map(poke_map_uninit) => poke_map_uninit.shape(),
             Poke::Struct(poke_struct) => poke_struct.shape(),
             Poke::Enum(poke_enum_no_variant) => poke_enum_no_variant.shape(),
+            Poke::Option(poke_option) => poke_option.shape(),
         }
     }
 }
 
-/// Keeps track of which fields were initialized, up to 64 fields
-#[derive(Clone, Copy, Default)]
-pub struct ISet(u64);
+/// Keeps track of which fields were initialized.
+///
+/// Up to 64 fields are tracked inline; wider structs and variants spill to the heap.
+#[derive(Clone)]
+pub struct ISet {
+    count: usize,
+    bits: ISetBits,
+}
+
+#[derive(Clone)]
+enum ISetBits {
+    Inline(u64),
+    Heap(Box<[u64]>),
+}
+
+impl Default for ISet {
+    fn default() -> Self {
+        Self::new(64)
+    }
+}
 
 impl ISet {
+    /// Creates a set able to track `count` fields, all unset.
+    pub fn new(count: usize) -> Self {
+        let bits = if count <= 64 {
+            ISetBits::Inline(0)
+        } else {
+            ISetBits::Heap(alloc::vec![0; count.div_ceil(64)].into_boxed_slice())
+        };
+        Self { count, bits }
+    }
+
+    /// Returns the number of fields this set can track.
+    pub fn capacity(&self) -> usize {
+        self.count
+    }
+
+    #[inline]
+    fn word_and_mask(&self, index: usize) -> (usize, u64) {
+        if index >= self.count {
+            panic!(
+                "ISet tracks {} fields. Index {index} is out of bounds.",
+                self.count
+            );
+        }
+        (index / 64, 1 << (index % 64))
+    }
+
+    #[inline]
+    fn words(&self) -> &[u64] {
+        match &self.bits {
+            ISetBits::Inline(word) => core::slice::from_ref(word),
+            ISetBits::Heap(words) => words,
+        }
+    }
+
+    #[inline]
+    fn words_mut(&mut self) -> &mut [u64] {
+        match &mut self.bits {
+            ISetBits::Inline(word) => core::slice::from_mut(word),
+            ISetBits::Heap(words) => words,
+        }
+    }
+
     /// Sets the bit at the given index.
     pub fn set(&mut self, index: usize) {
-        if index >= 64 {
-            panic!("ISet can only track up to 64 fields. Index {index} is out of bounds.");
-        }
-        self.0 |= 1 << index;
+        let (word, mask) = self.word_and_mask(index);
+        self.words_mut()[word] |= mask;
     }
 
     /// Unsets the bit at the given index.
     pub fn unset(&mut self, index: usize) {
-        if index >= 64 {
-            panic!("ISet can only track up to 64 fields. Index {index} is out of bounds.");
-        }
-        self.0 &= !(1 << index);
+        let (word, mask) = self.word_and_mask(index);
+        self.words_mut()[word] &= !mask;
     }
 
     /// Checks if the bit at the given index is set.
     pub fn has(&self, index: usize) -> bool {
-        if index >= 64 {
-            panic!("ISet can only track up to 64 fields. Index {index} is out of bounds.");
-        }
-        (self.0 & (1 << index)) != 0
+        let (word, mask) = self.word_and_mask(index);
+        self.words()[word] & mask != 0
     }
 
     /// Checks if all bits up to the given count are set.
     pub fn all_set(&self, count: usize) -> bool {
-        if count > 64 {
-            panic!("ISet can only track up to 64 fields. Count {count} is out of bounds.");
+        if count > self.count {
+            panic!(
+                "ISet tracks {} fields. Count {count} is out of bounds.",
+                self.count
+            );
+        }
+        let words = self.words();
+        let (full, rest) = (count / 64, count % 64);
+        if words[..full].iter().any(|&w| w != u64::MAX) {
+            return false;
+        }
+        if rest == 0 {
+            return true;
         }
-        let mask = (1 << count) - 1;
-        self.0 & mask == mask
+        let mask = (1 << rest) - 1;
+        words[full] & mask == mask
     }
 }
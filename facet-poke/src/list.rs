@@ -50,7 +50,6 @@ impl<'mem> PokeListUninit<'mem> {
 /// Allows poking a list (appending, etc.)
 pub struct PokeList<'mem> {
     data: Opaque<'mem>,
-    #[allow(dead_code)]
     shape: &'static Shape,
     def: ListDef,
 }
@@ -65,6 +64,12 @@ impl<'mem> PokeList<'mem> {
         Self { data, shape, def }
     }
 
+    /// Shape getter
+    #[inline(always)]
+    pub fn shape(&self) -> &'static Shape {
+        self.shape
+    }
+
     /// Gets the vtable for the list
     #[inline(always)]
     fn list_vtable(&self) -> &'static ListVTable {
@@ -102,7 +107,10 @@ impl<'mem> PokeList<'mem> {
 
     /// Takes ownership of this `PokeList` and returns the underlying data.
     pub fn build_in_place(self) -> Opaque<'mem> {
-        self.data
+        let data = self.data;
+        // the caller now owns the collection, don't drop it
+        core::mem::forget(self);
+        data
     }
 
     /// Gets the def for that list
@@ -110,3 +118,13 @@ impl<'mem> PokeList<'mem> {
         &self.def
     }
 }
+
+impl Drop for PokeList<'_> {
+    fn drop(&mut self) {
+        // The collection was initialized but never built: drop it along with
+        // every item it already holds.
+        if let Some(drop_fn) = self.shape.vtable.drop_in_place {
+            unsafe { drop_fn(self.data) };
+        }
+    }
+}
@@ -46,7 +46,6 @@ impl<'mem> PokeMapUninit<'mem> {
 /// Allows poking a map (inserting, etc.)
 pub struct PokeMap<'mem> {
     data: Opaque<'mem>,
-    #[allow(dead_code)]
     shape: &'static Shape,
     def: MapDef,
 }
@@ -111,9 +110,12 @@ impl<'mem> PokeMap<'mem> {
         unsafe { (self.map_vtable().get_value_ptr_fn)(self.data.as_const(), key) }
     }
 
-    /// Takes ownership of this `PokeList` and returns the underlying data.
+    /// Takes ownership of this `PokeMap` and returns the underlying data.
     pub fn build_in_place(self) -> Opaque<'mem> {
-        self.data
+        let data = self.data;
+        // the caller now owns the collection, don't drop it
+        core::mem::forget(self);
+        data
     }
 
     /// Returns a reference to the `MapDef` of this `PokeMap`.
@@ -122,3 +124,13 @@ impl<'mem> PokeMap<'mem> {
         &self.def
     }
 }
+
+impl Drop for PokeMap<'_> {
+    fn drop(&mut self) {
+        // The collection was initialized but never built: drop it along with
+        // every item it already holds.
+        if let Some(drop_fn) = self.shape.vtable.drop_in_place {
+            unsafe { drop_fn(self.data) };
+        }
+    }
+}
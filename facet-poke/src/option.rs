@@ -0,0 +1,58 @@
+use facet_trait::{Opaque, OpaqueUninit, OptionDef, Shape};
+
+use crate::PokeValue;
+
+/// Lets you initialize an option, with either `None` or `Some(value)`
+pub struct PokeOption<'mem> {
+    data: OpaqueUninit<'mem>,
+    shape: &'static Shape,
+    def: OptionDef,
+}
+
+impl<'mem> PokeOption<'mem> {
+    /// Creates a new option write-proxy
+    ///
+    /// # Safety
+    ///
+    /// The data buffer must match the size and alignment of the option shape described by shape
+    pub(crate) unsafe fn new(
+        data: OpaqueUninit<'mem>,
+        shape: &'static Shape,
+        def: OptionDef,
+    ) -> Self {
+        Self { data, shape, def }
+    }
+
+    /// Coerce back into a `PokeValue`
+    #[inline(always)]
+    pub fn into_value(self) -> PokeValue<'mem> {
+        unsafe { PokeValue::new(self.data, self.shape) }
+    }
+
+    /// Shape getter
+    #[inline(always)]
+    pub fn shape(&self) -> &'static Shape {
+        self.shape
+    }
+
+    /// Returns the option definition
+    #[inline(always)]
+    pub fn def(&self) -> &OptionDef {
+        &self.def
+    }
+
+    /// Initializes the option with `None`
+    pub fn init_none(self) -> Opaque<'mem> {
+        unsafe { (self.def.vtable.init_none)(self.data) }
+    }
+
+    /// Initializes the option with `Some(value)`
+    ///
+    /// # Safety
+    ///
+    /// `value` must point to an initialized value of the type described by
+    /// `self.def().t`. It is moved out of — it should be deallocated, but not dropped.
+    pub unsafe fn init_some(self, value: Opaque<'_>) -> Opaque<'mem> {
+        unsafe { (self.def.vtable.init_some)(self.data, value) }
+    }
+}
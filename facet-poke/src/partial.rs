@@ -0,0 +1,763 @@
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::marker::PhantomData;
+use core::mem::ManuallyDrop;
+
+use facet_trait::{
+    Def, EnumDef, Facet, Field, MapDef, Opaque, OpaqueConst, OpaqueUninit, Shape, TryFromError,
+    VariantKind,
+};
+
+use crate::{Guard, Poke, PokeEnumNoVariant, Scratch};
+use facet_peek::read_variant_index;
+
+/// An owned, partially initialized `T`, built by setting values at paths.
+///
+/// A path is a list of segments: struct field names, list indices, map keys (parsed with
+/// the key type's `FromStr`), and enum variant names, which select the variant to build.
+/// [`Partial::set`] takes them joined with dots: `"address.city"`, `"items.3.qty"`.
+///
+/// Values are moved in as-is when their type matches, and converted with the target's
+/// `try_from` vtable entry otherwise (e.g. a `&str` into a `String` field).
+///
+/// Dropping a `Partial` drops whatever was initialized so far.
+///
+/// ```rust
+/// use facet_derive::Facet;
+/// use facet_poke::Partial;
+/// use facet_trait::{self as facet, Facet};
+///
+/// #[derive(Debug, PartialEq, Facet)]
+/// struct Address {
+///     city: String,
+/// }
+///
+/// #[derive(Debug, PartialEq, Facet)]
+/// struct Person {
+///     name: String,
+///     address: Address,
+///     tags: Vec<String>,
+/// }
+///
+/// let mut partial = Partial::<Person>::new();
+/// partial.set("name", "Ada")?.set("address.city", "Paris")?;
+/// partial.push("tags", "math")?;
+/// let person = partial.build()?;
+/// assert_eq!(person.address.city, "Paris");
+/// assert_eq!(person.tags, ["math"]);
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub struct Partial<T: Facet> {
+    slot: Slot,
+    guard: Guard,
+    _phantom: PhantomData<T>,
+}
+
+/// Tracks what is initialized in a value being built
+#[derive(Clone)]
+enum Slot {
+    Uninit,
+    Init,
+    /// A struct whose fields are being set one by one
+    Struct(Vec<Slot>),
+    /// An enum whose variant was selected, and whose fields are being set one by one
+    Enum {
+        variant: usize,
+        fields: Vec<Slot>,
+    },
+}
+
+/// Where a path leads
+enum Target<'s> {
+    /// A value tracked by a slot, possibly uninitialized
+    Slot {
+        data: *mut u8,
+        shape: &'static Shape,
+        slot: &'s mut Slot,
+    },
+    /// A value inside an initialized value
+    Value {
+        data: *mut u8,
+        shape: &'static Shape,
+    },
+    /// A key that isn't in the map yet
+    MapEntry {
+        map: *mut u8,
+        def: MapDef,
+        key: Scratch,
+    },
+}
+
+impl<T: Facet> Default for Partial<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Facet> Partial<T> {
+    /// Allocates a new, fully uninitialized `T`
+    pub fn new() -> Self {
+        let (_, guard) = Poke::alloc::<T>();
+        Self {
+            slot: Slot::Uninit,
+            guard,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Sets the value at a dot-separated path. The empty path is the whole value.
+    ///
+    /// Whatever was at the path before is dropped.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the path doesn't lead anywhere, or if `value` can't be
+    /// converted to the type found there.
+    pub fn set<V: Facet>(&mut self, path: &str, value: V) -> Result<&mut Self, PartialError> {
+        self.set_at(&split_path(path), value)
+    }
+
+    /// Sets the value at a path given as segments
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the path doesn't lead anywhere, or if `value` can't be
+    /// converted to the type found there.
+    pub fn set_at<V: Facet>(&mut self, path: &[&str], value: V) -> Result<&mut Self, PartialError> {
+        let target = self.resolve(path)?;
+        match target {
+            Target::Slot { data, shape, slot } => move_in(value, shape, |src| unsafe {
+                drop_slot(slot, data, shape);
+                copy_value(src, data, shape);
+                *slot = Slot::Init;
+            }),
+            Target::Value { data, shape } => move_in(value, shape, |src| unsafe {
+                if let Some(drop_fn) = shape.vtable.drop_in_place {
+                    drop_fn(Opaque::new_unchecked(data));
+                }
+                copy_value(src, data, shape);
+            }),
+            Target::MapEntry { map, def, mut key } => move_in(value, def.v, |src| unsafe {
+                (def.vtable.insert_fn)(Opaque::new_unchecked(map), key.take(), src);
+            }),
+        }
+        .map_err(|kind| PartialError::new(path, path.len(), kind))?;
+        Ok(self)
+    }
+
+    /// Selects the variant of the enum at a dot-separated path.
+    ///
+    /// Unit variants are complete once selected. Selecting another variant drops
+    /// whatever was set for the previous one.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the path doesn't lead to an enum with such a variant.
+    pub fn set_variant(&mut self, path: &str, variant: &str) -> Result<&mut Self, PartialError> {
+        let mut path = split_path(path);
+        let shape = match self.resolve(&path)? {
+            Target::Slot { shape, .. } | Target::Value { shape, .. } => shape,
+            Target::MapEntry { .. } => {
+                return Err(PartialError::new(
+                    &path,
+                    path.len(),
+                    PartialErrorKind::NoSuchKey,
+                ));
+            }
+        };
+        path.push(variant);
+        if !matches!(shape.def, Def::Enum(_)) {
+            return Err(PartialError::new(
+                &path,
+                path.len() - 1,
+                PartialErrorKind::NotAnEnum,
+            ));
+        }
+        self.resolve(&path)?;
+        Ok(self)
+    }
+
+    /// Pushes an item to the list at a dot-separated path, initializing it if needed
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the path doesn't lead to a list, or if `item` can't be
+    /// converted to its item type.
+    pub fn push<V: Facet>(&mut self, path: &str, item: V) -> Result<&mut Self, PartialError> {
+        let path = split_path(path);
+        let error = |kind| PartialError::new(&path, path.len(), kind);
+        let (data, shape) = match self.resolve(&path)? {
+            Target::Slot { data, shape, slot } => {
+                if let (Def::List(def), Slot::Uninit) = (shape.def, &slot) {
+                    unsafe { init_empty_list(data, def.vtable.init_in_place_with_capacity) }
+                        .map_err(error)?;
+                    *slot = Slot::Init;
+                }
+                (data, shape)
+            }
+            Target::Value { data, shape } => (data, shape),
+            Target::MapEntry { .. } => return Err(error(PartialErrorKind::NoSuchKey)),
+        };
+        let Def::List(def) = shape.def else {
+            return Err(error(PartialErrorKind::NotAList));
+        };
+
+        move_in(item, def.t, |src| unsafe {
+            (def.vtable.push)(Opaque::new_unchecked(data), src);
+        })
+        .map_err(error)?;
+        Ok(self)
+    }
+
+    /// Returns the path of every field that hasn't been set yet
+    pub fn missing_fields(&self) -> Vec<String> {
+        let mut missing = Vec::new();
+        collect_missing(&self.slot, T::SHAPE, &mut Vec::new(), &mut missing);
+        missing
+    }
+
+    /// Builds the value, if every field was set
+    ///
+    /// # Errors
+    ///
+    /// Returns the path of every field that wasn't set. The partial value is dropped.
+    pub fn build(mut self) -> Result<T, MissingFields> {
+        let missing = self.missing_fields();
+        if !missing.is_empty() {
+            return Err(MissingFields { paths: missing });
+        }
+
+        let value = unsafe { core::ptr::read(self.guard.as_uninit().as_ptr() as *const T) };
+        // the value was moved out: only free the allocation
+        self.slot = Slot::Uninit;
+        Ok(value)
+    }
+
+    fn resolve(&mut self, path: &[&str]) -> Result<Target<'_>, PartialError> {
+        let data = self.guard.as_uninit().as_mut_ptr();
+        unsafe { resolve_slot(&mut self.slot, data, T::SHAPE, path, 0) }
+            .map_err(|(index, kind)| PartialError::new(path, index + 1, kind))
+    }
+}
+
+impl<T: Facet> Drop for Partial<T> {
+    fn drop(&mut self) {
+        let data = self.guard.as_uninit().as_mut_ptr();
+        unsafe { drop_slot(&self.slot, data, T::SHAPE) };
+    }
+}
+
+fn split_path(path: &str) -> Vec<&str> {
+    if path.is_empty() {
+        Vec::new()
+    } else {
+        path.split('.').collect()
+    }
+}
+
+/// Hands `f` a value of shape `shape` to move out of, converting `value` if needed
+fn move_in<V: Facet>(
+    value: V,
+    shape: &'static Shape,
+    f: impl FnOnce(Opaque<'_>),
+) -> Result<(), PartialErrorKind> {
+    let mut value = ManuallyDrop::new(value);
+    if V::SHAPE == shape {
+        f(Opaque::from_ref(&mut *value));
+        return Ok(());
+    }
+
+    let mismatch = PartialErrorKind::TypeMismatch {
+        expected: shape,
+        actual: V::SHAPE,
+    };
+    let Some(try_from) = shape.vtable.try_from else {
+        unsafe { ManuallyDrop::drop(&mut value) };
+        return Err(mismatch);
+    };
+    let mut scratch = Scratch::new(shape);
+    let converted = unsafe {
+        try_from(
+            OpaqueConst::from_ref(&*value),
+            V::SHAPE,
+            scratch.as_uninit(),
+        )
+    };
+    match converted {
+        Ok(_) => {
+            unsafe { scratch.mark_initialized() };
+            f(scratch.take());
+            Ok(())
+        }
+        Err(TryFromError::Incompatible { .. } | TryFromError::Unimplemented(_)) => {
+            // the source wasn't moved out of
+            unsafe { ManuallyDrop::drop(&mut value) };
+            Err(mismatch)
+        }
+        Err(err) => Err(PartialErrorKind::Conversion(err)),
+    }
+}
+
+/// # Safety
+///
+/// `src` must point to an initialized value of `shape`, which is moved out of;
+/// `dst` must be valid for writes of `shape`.
+unsafe fn copy_value(src: Opaque<'_>, dst: *mut u8, shape: &'static Shape) {
+    unsafe { core::ptr::copy_nonoverlapping(src.as_byte_ptr(), dst, shape.layout.size()) };
+}
+
+/// # Safety
+///
+/// `data` must be valid for writes of a list of the shape `init` belongs to.
+unsafe fn init_empty_list(
+    data: *mut u8,
+    init: facet_trait::ListInitInPlaceWithCapacityFn,
+) -> Result<(), PartialErrorKind> {
+    unsafe { init(OpaqueUninit::new(data), 0) }
+        .map(|_| ())
+        .map_err(|_| PartialErrorKind::Unsupported {
+            operation: "initialize",
+        })
+}
+
+fn struct_fields(shape: &'static Shape) -> &'static [Field] {
+    match shape.def {
+        Def::Struct(def) => def.fields,
+        _ => &[],
+    }
+}
+
+fn variant_fields(def: &EnumDef, variant: usize) -> &'static [Field] {
+    match &def.variants[variant].kind {
+        VariantKind::Tuple { fields } | VariantKind::Struct { fields } => fields,
+        _ => &[],
+    }
+}
+
+/// Drops whatever `slot` says is initialized
+///
+/// # Safety
+///
+/// `data` must point to a value of `shape`, initialized as `slot` says.
+unsafe fn drop_slot(slot: &Slot, data: *mut u8, shape: &'static Shape) {
+    match slot {
+        Slot::Uninit => {}
+        Slot::Init => {
+            if let Some(drop_fn) = shape.vtable.drop_in_place {
+                unsafe { drop_fn(Opaque::new_unchecked(data)) };
+            }
+        }
+        Slot::Struct(children) => {
+            for (field, child) in struct_fields(shape).iter().zip(children) {
+                unsafe { drop_slot(child, data.add(field.offset), field.shape) };
+            }
+        }
+        Slot::Enum { variant, fields } => {
+            let Def::Enum(def) = shape.def else {
+                unreachable!()
+            };
+            for (field, child) in variant_fields(&def, *variant).iter().zip(fields) {
+                unsafe { drop_slot(child, data.add(field.offset), field.shape) };
+            }
+        }
+    }
+}
+
+/// Drops whatever was built at `slot` and starts building variant `variant` instead,
+/// unless it's already the one being built.
+///
+/// # Safety
+///
+/// `data` must point to a value of `shape`, initialized as `slot` says.
+unsafe fn select_variant(
+    slot: &mut Slot,
+    data: *mut u8,
+    shape: &'static Shape,
+    def: EnumDef,
+    variant: usize,
+) {
+    match slot {
+        Slot::Enum {
+            variant: current, ..
+        } if *current == variant => return,
+        Slot::Init
+            if unsafe { read_variant_index(OpaqueConst::new_unchecked(data), &def) }
+                == Some(variant) =>
+        {
+            return;
+        }
+        _ => {}
+    }
+
+    unsafe { drop_slot(slot, data, shape) };
+    *slot = Slot::Uninit;
+    let poke = unsafe { PokeEnumNoVariant::new(OpaqueUninit::new(data), shape, def) };
+    // writes the discriminant: no field is initialized yet, so dropping the poke is a no-op
+    drop(poke.set_variant_by_index(variant));
+    *slot = Slot::Enum {
+        variant,
+        fields: vec![Slot::Uninit; variant_fields(&def, variant).len()],
+    };
+}
+
+type ResolveError = (usize, PartialErrorKind);
+
+/// Follows `path[index..]` from a value tracked by `slot`
+///
+/// # Safety
+///
+/// `data` must point to a value of `shape`, initialized as `slot` says.
+unsafe fn resolve_slot<'s>(
+    slot: &'s mut Slot,
+    data: *mut u8,
+    shape: &'static Shape,
+    path: &[&str],
+    index: usize,
+) -> Result<Target<'s>, ResolveError> {
+    let Some(&segment) = path.get(index) else {
+        return Ok(Target::Slot { data, shape, slot });
+    };
+    let selects_variant = match shape.def {
+        Def::Enum(def) => def.variants.iter().any(|v| v.name == segment),
+        _ => false,
+    };
+    // an initialized enum can still switch variants, since its slot tracks it
+    if matches!(slot, Slot::Init) && !selects_variant {
+        return unsafe { resolve_value(data, shape, path, index) };
+    }
+
+    match shape.def {
+        Def::Struct(def) => {
+            let field_index = def
+                .fields
+                .iter()
+                .position(|f| f.name == segment)
+                .ok_or((index, PartialErrorKind::NoSuchField))?;
+            if matches!(slot, Slot::Uninit) {
+                *slot = Slot::Struct(vec![Slot::Uninit; def.fields.len()]);
+            }
+            let Slot::Struct(children) = slot else {
+                unreachable!()
+            };
+            let field = &def.fields[field_index];
+            unsafe {
+                resolve_slot(
+                    &mut children[field_index],
+                    data.add(field.offset),
+                    field.shape,
+                    path,
+                    index + 1,
+                )
+            }
+        }
+        Def::Enum(def) => {
+            if let Some(variant) = def.variants.iter().position(|v| v.name == segment) {
+                unsafe {
+                    select_variant(slot, data, shape, def, variant);
+                    return resolve_slot(slot, data, shape, path, index + 1);
+                }
+            }
+            let Slot::Enum { variant, fields } = slot else {
+                return Err((index, PartialErrorKind::NoVariantSelected));
+            };
+            let variant_fields = variant_fields(&def, *variant);
+            let field_index = variant_fields
+                .iter()
+                .position(|f| f.name == segment)
+                .ok_or((index, PartialErrorKind::NoSuchField))?;
+            let field = &variant_fields[field_index];
+            unsafe {
+                resolve_slot(
+                    &mut fields[field_index],
+                    data.add(field.offset),
+                    field.shape,
+                    path,
+                    index + 1,
+                )
+            }
+        }
+        Def::List(def) => {
+            // lists and maps are built empty, then edited in place
+            unsafe { init_empty_list(data, def.vtable.init_in_place_with_capacity) }
+                .map_err(|kind| (index, kind))?;
+            *slot = Slot::Init;
+            unsafe { resolve_value(data, shape, path, index) }
+        }
+        Def::Map(def) => {
+            unsafe { (def.vtable.init_in_place_with_capacity_fn)(OpaqueUninit::new(data), 0) }
+                .map_err(|_| {
+                    (
+                        index,
+                        PartialErrorKind::Unsupported {
+                            operation: "initialize",
+                        },
+                    )
+                })?;
+            *slot = Slot::Init;
+            unsafe { resolve_value(data, shape, path, index) }
+        }
+        _ => Err((index, PartialErrorKind::NoSuchField)),
+    }
+}
+
+/// Follows `path[index..]` inside an initialized value
+///
+/// # Safety
+///
+/// `data` must point to an initialized value of `shape`.
+unsafe fn resolve_value<'s>(
+    mut data: *mut u8,
+    mut shape: &'static Shape,
+    path: &[&str],
+    index: usize,
+) -> Result<Target<'s>, ResolveError> {
+    for (index, &segment) in path.iter().enumerate().skip(index) {
+        let err = |kind| (index, kind);
+        match shape.def {
+            Def::Struct(def) => {
+                let field = def
+                    .fields
+                    .iter()
+                    .find(|f| f.name == segment)
+                    .ok_or(err(PartialErrorKind::NoSuchField))?;
+                data = unsafe { data.add(field.offset) };
+                shape = field.shape;
+            }
+            Def::Enum(def) => {
+                let current = unsafe { read_variant_index(OpaqueConst::new_unchecked(data), &def) }
+                    .ok_or(err(PartialErrorKind::NoVariantSelected))?;
+                if def.variants.iter().any(|v| v.name == segment) {
+                    if def.variants[current].name != segment {
+                        return Err(err(PartialErrorKind::VariantAlreadySet {
+                            current: def.variants[current].name,
+                        }));
+                    }
+                    continue;
+                }
+                let field = variant_fields(&def, current)
+                    .iter()
+                    .find(|f| f.name == segment)
+                    .ok_or(err(PartialErrorKind::NoSuchField))?;
+                data = unsafe { data.add(field.offset) };
+                shape = field.shape;
+            }
+            Def::List(def) => {
+                let item_index: usize = segment
+                    .parse()
+                    .map_err(|_| err(PartialErrorKind::InvalidKey))?;
+                let list = unsafe { Opaque::new_unchecked(data) };
+                let len = unsafe { (def.vtable.len)(list.as_const()) };
+                if item_index >= len {
+                    return Err(err(PartialErrorKind::IndexOutOfBounds {
+                        index: item_index,
+                        len,
+                    }));
+                }
+                let get_item_ptr_mut =
+                    def.vtable
+                        .get_item_ptr_mut
+                        .ok_or(err(PartialErrorKind::Unsupported {
+                            operation: "get_item_mut",
+                        }))?;
+                data = unsafe { get_item_ptr_mut(list, item_index) }.as_mut_byte_ptr();
+                shape = def.t;
+            }
+            Def::Map(def) => {
+                let key = parse_key(segment, def.k).map_err(err)?;
+                let get_value_ptr_mut =
+                    def.vtable
+                        .get_value_ptr_mut_fn
+                        .ok_or(err(PartialErrorKind::Unsupported {
+                            operation: "get_mut",
+                        }))?;
+                let map = unsafe { Opaque::new_unchecked(data) };
+                match unsafe { get_value_ptr_mut(map, key.get()) } {
+                    Some(value) => {
+                        data = value.as_mut_byte_ptr();
+                        shape = def.v;
+                    }
+                    None if index + 1 == path.len() => {
+                        return Ok(Target::MapEntry {
+                            map: data,
+                            def,
+                            key,
+                        });
+                    }
+                    None => return Err(err(PartialErrorKind::NoSuchKey)),
+                }
+            }
+            _ => return Err(err(PartialErrorKind::NoSuchField)),
+        }
+    }
+    Ok(Target::Value { data, shape })
+}
+
+fn parse_key(segment: &str, shape: &'static Shape) -> Result<Scratch, PartialErrorKind> {
+    let parse = shape.vtable.parse.ok_or(PartialErrorKind::InvalidKey)?;
+    let mut scratch = Scratch::new(shape);
+    unsafe { parse(segment, scratch.as_uninit()) }.map_err(|_| PartialErrorKind::InvalidKey)?;
+    unsafe { scratch.mark_initialized() };
+    Ok(scratch)
+}
+
+fn collect_missing(
+    slot: &Slot,
+    shape: &'static Shape,
+    path: &mut Vec<&'static str>,
+    missing: &mut Vec<String>,
+) {
+    let mut visit = |name: &'static str, slot: &Slot, shape: &'static Shape| {
+        path.push(name);
+        collect_missing(slot, shape, path, missing);
+        path.pop();
+    };
+    match slot {
+        Slot::Init => {}
+        // report the leaves of structs nobody touched, not the structs themselves
+        Slot::Uninit if matches!(shape.def, Def::Struct(_)) => {
+            for field in struct_fields(shape) {
+                visit(field.name, &Slot::Uninit, field.shape);
+            }
+        }
+        Slot::Uninit => missing.push(path.join(".")),
+        Slot::Struct(children) => {
+            for (field, child) in struct_fields(shape).iter().zip(children) {
+                visit(field.name, child, field.shape);
+            }
+        }
+        Slot::Enum { variant, fields } => {
+            let Def::Enum(def) = shape.def else {
+                unreachable!()
+            };
+            path.push(def.variants[*variant].name);
+            for (field, child) in variant_fields(&def, *variant).iter().zip(fields) {
+                path.push(field.name);
+                collect_missing(child, field.shape, path, missing);
+                path.pop();
+            }
+            path.pop();
+        }
+    }
+}
+
+/// Errors returned when a [`Partial`] can't set a value at a path
+#[derive(Debug, Clone, PartialEq)]
+pub struct PartialError {
+    /// The path up to (and including) the segment that failed
+    pub path: String,
+    /// What went wrong
+    pub kind: PartialErrorKind,
+}
+
+impl PartialError {
+    fn new(path: &[&str], len: usize, kind: PartialErrorKind) -> Self {
+        Self {
+            path: path[..len.min(path.len())].join("."),
+            kind,
+        }
+    }
+}
+
+/// What went wrong while setting a value at a path
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum PartialErrorKind {
+    /// No field with this name exists.
+    NoSuchField,
+
+    /// The map has no entry for this key.
+    NoSuchKey,
+
+    /// The segment can't be parsed as a list index or a map key.
+    InvalidKey,
+
+    /// The list index is past the end of the list.
+    IndexOutOfBounds {
+        /// The index that was requested
+        index: usize,
+        /// The length of the list
+        len: usize,
+    },
+
+    /// A field of an enum was set before selecting its variant.
+    NoVariantSelected,
+
+    /// The enum is already initialized with another variant.
+    VariantAlreadySet {
+        /// The current variant
+        current: &'static str,
+    },
+
+    /// A variant was selected on something that isn't an enum.
+    NotAnEnum,
+
+    /// An item was pushed to something that isn't a list.
+    NotAList,
+
+    /// The value doesn't have the type found at the path, and can't be converted to it.
+    TypeMismatch {
+        /// The shape found at the path
+        expected: &'static Shape,
+        /// The shape of the value
+        actual: &'static Shape,
+    },
+
+    /// The value was rejected by the conversion to the type found at the path.
+    Conversion(TryFromError),
+
+    /// The value's vtable doesn't support the operation.
+    Unsupported {
+        /// The operation that was attempted
+        operation: &'static str,
+    },
+}
+
+impl core::error::Error for PartialError {}
+
+impl core::fmt::Display for PartialError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{} at `{}`", self.kind, self.path)
+    }
+}
+
+impl core::fmt::Display for PartialErrorKind {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            PartialErrorKind::NoSuchField => write!(f, "No such field"),
+            PartialErrorKind::NoSuchKey => write!(f, "No such key"),
+            PartialErrorKind::InvalidKey => write!(f, "Invalid index or key"),
+            PartialErrorKind::IndexOutOfBounds { index, len } => {
+                write!(f, "Index {index} out of bounds (len is {len})")
+            }
+            PartialErrorKind::NoVariantSelected => write!(f, "No variant selected"),
+            PartialErrorKind::VariantAlreadySet { current } => {
+                write!(f, "Variant already set to {current}")
+            }
+            PartialErrorKind::NotAnEnum => write!(f, "Not an enum"),
+            PartialErrorKind::NotAList => write!(f, "Not a list"),
+            PartialErrorKind::TypeMismatch { expected, actual } => {
+                write!(f, "Type mismatch: expected {expected}, got {actual}")
+            }
+            PartialErrorKind::Conversion(err) => write!(f, "{err}"),
+            PartialErrorKind::Unsupported { operation } => {
+                write!(f, "Operation not supported: {operation}")
+            }
+        }
+    }
+}
+
+/// Returned by [`Partial::build`] when some fields were never set
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MissingFields {
+    /// The path of every field that wasn't set
+    pub paths: Vec<String>,
+}
+
+impl core::error::Error for MissingFields {}
+
+impl core::fmt::Display for MissingFields {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "Missing fields: {}", self.paths.join(", "))
+    }
+}
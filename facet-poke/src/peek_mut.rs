@@ -0,0 +1,895 @@
+use core::marker::PhantomData;
+use core::mem::MaybeUninit;
+
+use facet_trait::{
+    Def, EnumDef, Facet, Field, ListDef, MapDef, Opaque, OpaqueConst, OpaqueUninit, OptionDef,
+    Shape, ShapeExt as _, StructDef, Variant, VariantKind,
+};
+
+use crate::{Guard, Poke, PokeEnum};
+use facet_peek::read_variant_index;
+
+/// Allows mutating an initialized value in place.
+///
+/// Where [`Poke`] builds values from scratch, `PeekMut` edits values that already exist:
+/// every write drops the value it replaces, and the value stays fully initialized at all
+/// times, so it can be handed back to its owner once the handle is gone.
+#[non_exhaustive]
+pub enum PeekMut<'mem> {
+    /// A scalar value (or anything that can only be replaced wholesale). See [`PeekValueMut`].
+    Value(PeekValueMut<'mem>),
+    /// A struct, tuple struct, or tuple. See [`PeekStructMut`].
+    Struct(PeekStructMut<'mem>),
+    /// A list (array/vec/etc). See [`PeekListMut`].
+    List(PeekListMut<'mem>),
+    /// A map (HashMap/BTreeMap/etc). See [`PeekMapMut`].
+    Map(PeekMapMut<'mem>),
+    /// An enum. See [`PeekEnumMut`].
+    Enum(PeekEnumMut<'mem>),
+    /// An option. See [`PeekOptionMut`].
+    Option(PeekOptionMut<'mem>),
+}
+
+impl<'mem> core::ops::Deref for PeekMut<'mem> {
+    type Target = PeekValueMut<'mem>;
+
+    fn deref(&self) -> &Self::Target {
+        match self {
+            PeekMut::Value(value) => value,
+            PeekMut::Struct(struct_) => struct_,
+            PeekMut::List(list) => list,
+            PeekMut::Map(map) => map,
+            PeekMut::Enum(enum_) => enum_,
+            PeekMut::Option(option) => option,
+        }
+    }
+}
+
+impl core::ops::DerefMut for PeekMut<'_> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        match self {
+            PeekMut::Value(value) => value,
+            PeekMut::Struct(struct_) => struct_,
+            PeekMut::List(list) => list,
+            PeekMut::Map(map) => map,
+            PeekMut::Enum(enum_) => enum_,
+            PeekMut::Option(option) => option,
+        }
+    }
+}
+
+impl<'mem> PeekMut<'mem> {
+    /// Creates a new mutable peek from a mutable reference to some initialized value
+    pub fn new<T: Facet>(value: &'mem mut T) -> Self {
+        unsafe { Self::unchecked_new(Opaque::from_ref(value), T::SHAPE) }
+    }
+
+    /// Creates a new mutable peek, for easy manipulation of some opaque data.
+    ///
+    /// # Safety
+    ///
+    /// `data` must be initialized and well-aligned, point to a value of the type
+    /// described by `shape`, and not be aliased for `'mem`.
+    pub unsafe fn unchecked_new(data: Opaque<'mem>, shape: &'static Shape) -> Self {
+        let value = PeekValueMut { data, shape };
+        match shape.def {
+            Def::Struct(def) => PeekMut::Struct(PeekStructMut { value, def }),
+            Def::List(def) => PeekMut::List(PeekListMut { value, def }),
+            Def::Map(def) => PeekMut::Map(PeekMapMut { value, def }),
+            Def::Enum(def) => PeekMut::Enum(PeekEnumMut { value, def }),
+            Def::Option(def) => PeekMut::Option(PeekOptionMut { value, def }),
+            // anything else can still be replaced wholesale
+            _ => PeekMut::Value(value),
+        }
+    }
+
+    /// Converts this PeekMut into a PeekStructMut, panicking if it's not a Struct variant
+    pub fn into_struct(self) -> PeekStructMut<'mem> {
+        match self {
+            PeekMut::Struct(s) => s,
+            _ => panic!("expected Struct variant"),
+        }
+    }
+
+    /// Converts this PeekMut into a PeekListMut, panicking if it's not a List variant
+    pub fn into_list(self) -> PeekListMut<'mem> {
+        match self {
+            PeekMut::List(l) => l,
+            _ => panic!("expected List variant"),
+        }
+    }
+
+    /// Converts this PeekMut into a PeekMapMut, panicking if it's not a Map variant
+    pub fn into_map(self) -> PeekMapMut<'mem> {
+        match self {
+            PeekMut::Map(m) => m,
+            _ => panic!("expected Map variant"),
+        }
+    }
+
+    /// Converts this PeekMut into a PeekEnumMut, panicking if it's not an Enum variant
+    pub fn into_enum(self) -> PeekEnumMut<'mem> {
+        match self {
+            PeekMut::Enum(e) => e,
+            _ => panic!("expected Enum variant"),
+        }
+    }
+
+    /// Converts this PeekMut into a PeekOptionMut, panicking if it's not an Option variant
+    pub fn into_option(self) -> PeekOptionMut<'mem> {
+        match self {
+            PeekMut::Option(o) => o,
+            _ => panic!("expected Option variant"),
+        }
+    }
+}
+
+/// Lets you replace an initialized value wholesale
+pub struct PeekValueMut<'mem> {
+    data: Opaque<'mem>,
+    shape: &'static Shape,
+}
+
+impl PeekValueMut<'_> {
+    /// Shape getter
+    #[inline(always)]
+    pub fn shape(&self) -> &'static Shape {
+        self.shape
+    }
+
+    /// Returns a pointer to the value
+    #[inline(always)]
+    pub fn data(&self) -> OpaqueConst<'_> {
+        self.data.as_const()
+    }
+
+    /// Replaces the value, dropping the previous one.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PeekMutError::TypeMismatch`] if `T` isn't the type of the value.
+    pub fn replace<T: Facet>(&mut self, value: T) -> Result<(), PeekMutError> {
+        expect_shape::<T>(self.shape)?;
+        *unsafe { self.data.as_mut::<T>() } = value;
+        Ok(())
+    }
+
+    /// Replaces the value by moving `value` in, dropping the previous one.
+    ///
+    /// # Safety
+    ///
+    /// `value` must point to an initialized value of this shape, which must not be used
+    /// (or dropped) afterwards.
+    pub unsafe fn replace_opaque(&mut self, value: Opaque<'_>) {
+        unsafe {
+            if let Some(drop_fn) = self.shape.vtable.drop_in_place {
+                drop_fn(self.data);
+            }
+            core::ptr::copy_nonoverlapping(
+                value.as_byte_ptr(),
+                self.data.as_mut_byte_ptr(),
+                self.shape.layout.size(),
+            );
+        }
+    }
+}
+
+/// Lets you mutate the fields of an initialized struct
+pub struct PeekStructMut<'mem> {
+    value: PeekValueMut<'mem>,
+    def: StructDef,
+}
+
+impl<'mem> core::ops::Deref for PeekStructMut<'mem> {
+    type Target = PeekValueMut<'mem>;
+
+    #[inline(always)]
+    fn deref(&self) -> &Self::Target {
+        &self.value
+    }
+}
+
+impl core::ops::DerefMut for PeekStructMut<'_> {
+    #[inline(always)]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.value
+    }
+}
+
+impl PeekStructMut<'_> {
+    /// Returns the struct definition
+    #[inline(always)]
+    pub fn def(&self) -> &StructDef {
+        &self.def
+    }
+
+    /// Returns the number of fields in this struct
+    #[inline(always)]
+    pub fn field_count(&self) -> usize {
+        self.def.fields.len()
+    }
+
+    /// Gets a mutable peek at the field with the given index
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PeekMutError::IndexOutOfBounds`] if there is no such field.
+    pub fn field_mut_at(&mut self, index: usize) -> Result<PeekMut<'_>, PeekMutError> {
+        let field = self
+            .def
+            .fields
+            .get(index)
+            .ok_or(PeekMutError::IndexOutOfBounds {
+                index,
+                len: self.def.fields.len(),
+            })?;
+        Ok(unsafe { field_of(self.value.data, field) })
+    }
+
+    /// Gets a mutable peek at the field with the given name
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PeekMutError::NoSuchField`] if there is no such field.
+    pub fn field_mut(&mut self, name: &str) -> Result<PeekMut<'_>, PeekMutError> {
+        let index = self.field_index(name)?;
+        self.field_mut_at(index)
+    }
+
+    /// Replaces the value of the field with the given name, dropping the previous one.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there is no such field, or if `T` isn't the type of the field.
+    pub fn set_field<T: Facet>(&mut self, name: &str, value: T) -> Result<(), PeekMutError> {
+        self.field_mut(name)?.replace(value)
+    }
+
+    fn field_index(&self, name: &str) -> Result<usize, PeekMutError> {
+        self.def
+            .fields
+            .iter()
+            .position(|f| f.name == name)
+            .ok_or(PeekMutError::NoSuchField)
+    }
+}
+
+/// Lets you mutate an initialized list: access items, push, pop, remove, clear
+pub struct PeekListMut<'mem> {
+    value: PeekValueMut<'mem>,
+    def: ListDef,
+}
+
+impl<'mem> core::ops::Deref for PeekListMut<'mem> {
+    type Target = PeekValueMut<'mem>;
+
+    #[inline(always)]
+    fn deref(&self) -> &Self::Target {
+        &self.value
+    }
+}
+
+impl core::ops::DerefMut for PeekListMut<'_> {
+    #[inline(always)]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.value
+    }
+}
+
+impl PeekListMut<'_> {
+    /// Returns the list definition
+    #[inline(always)]
+    pub fn def(&self) -> &ListDef {
+        &self.def
+    }
+
+    /// Returns the number of items in the list
+    pub fn len(&self) -> usize {
+        unsafe { (self.def.vtable.len)(self.value.data.as_const()) }
+    }
+
+    /// Returns true if the list is empty
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Gets a mutable peek at the item with the given index
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the index is out of bounds, or if the list doesn't allow
+    /// mutating its items in place.
+    pub fn item_mut(&mut self, index: usize) -> Result<PeekMut<'_>, PeekMutError> {
+        let get_item_ptr_mut = self.supports(self.def.vtable.get_item_ptr_mut, "get_item_mut")?;
+        self.check_index(index)?;
+        unsafe {
+            let item = get_item_ptr_mut(self.value.data, index);
+            Ok(PeekMut::unchecked_new(item, self.def.t))
+        }
+    }
+
+    /// Appends an item to the end of the list
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PeekMutError::TypeMismatch`] if `T` isn't the item type of the list.
+    pub fn push<T: Facet>(&mut self, item: T) -> Result<(), PeekMutError> {
+        expect_shape::<T>(self.def.t)?;
+        let mut item = core::mem::ManuallyDrop::new(item);
+        unsafe {
+            (self.def.vtable.push)(self.value.data, Opaque::from_ref(&mut *item));
+        }
+        Ok(())
+    }
+
+    /// Removes the last item of the list and returns it, or `None` if the list is empty
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `T` isn't the item type of the list, or if the list doesn't
+    /// support removing items.
+    pub fn pop<T: Facet>(&mut self) -> Result<Option<T>, PeekMutError> {
+        expect_shape::<T>(self.def.t)?;
+        let pop = self.supports(self.def.vtable.pop, "pop")?;
+        let mut item = MaybeUninit::<T>::uninit();
+        let popped =
+            unsafe { pop(self.value.data, OpaqueUninit::from_maybe_uninit(&mut item)) }.is_some();
+        Ok(popped.then(|| unsafe { item.assume_init() }))
+    }
+
+    /// Removes the item with the given index, shifting the following items down, and returns it
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `T` isn't the item type of the list, if the index is out of
+    /// bounds, or if the list doesn't support removing items.
+    pub fn remove<T: Facet>(&mut self, index: usize) -> Result<T, PeekMutError> {
+        expect_shape::<T>(self.def.t)?;
+        let remove_at = self.supports(self.def.vtable.remove_at, "remove")?;
+        self.check_index(index)?;
+        let mut item = MaybeUninit::<T>::uninit();
+        unsafe {
+            remove_at(
+                self.value.data,
+                index,
+                OpaqueUninit::from_maybe_uninit(&mut item),
+            );
+            Ok(item.assume_init())
+        }
+    }
+
+    /// Removes (and drops) every item of the list
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PeekMutError::Unsupported`] if the list doesn't support removing items.
+    pub fn clear(&mut self) -> Result<(), PeekMutError> {
+        let clear = self.supports(self.def.vtable.clear, "clear")?;
+        unsafe { clear(self.value.data) };
+        Ok(())
+    }
+
+    /// Shortens the list to `len` items, dropping the rest
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PeekMutError::Unsupported`] if the list doesn't support removing items.
+    pub fn truncate(&mut self, len: usize) -> Result<(), PeekMutError> {
+        let truncate = self.supports(self.def.vtable.truncate, "truncate")?;
+        unsafe { truncate(self.value.data, len) };
+        Ok(())
+    }
+
+    /// Reserves capacity for at least `additional` more items
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PeekMutError::Unsupported`] if the list has no notion of capacity.
+    pub fn reserve(&mut self, additional: usize) -> Result<(), PeekMutError> {
+        let reserve = self.supports(self.def.vtable.reserve, "reserve")?;
+        unsafe { reserve(self.value.data, additional) };
+        Ok(())
+    }
+
+    fn check_index(&self, index: usize) -> Result<(), PeekMutError> {
+        let len = self.len();
+        if index >= len {
+            return Err(PeekMutError::IndexOutOfBounds { index, len });
+        }
+        Ok(())
+    }
+
+    fn supports<F>(&self, f: Option<F>, operation: &'static str) -> Result<F, PeekMutError> {
+        f.ok_or(PeekMutError::Unsupported {
+            shape: self.value.shape,
+            operation,
+        })
+    }
+}
+
+/// Lets you mutate an initialized map: access values, insert, remove, clear
+pub struct PeekMapMut<'mem> {
+    value: PeekValueMut<'mem>,
+    def: MapDef,
+}
+
+impl<'mem> core::ops::Deref for PeekMapMut<'mem> {
+    type Target = PeekValueMut<'mem>;
+
+    #[inline(always)]
+    fn deref(&self) -> &Self::Target {
+        &self.value
+    }
+}
+
+impl core::ops::DerefMut for PeekMapMut<'_> {
+    #[inline(always)]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.value
+    }
+}
+
+impl PeekMapMut<'_> {
+    /// Returns the map definition
+    #[inline(always)]
+    pub fn def(&self) -> &MapDef {
+        &self.def
+    }
+
+    /// Returns the number of entries in the map
+    pub fn len(&self) -> usize {
+        unsafe { (self.def.vtable.len_fn)(self.value.data.as_const()) }
+    }
+
+    /// Returns true if the map is empty
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns true if the map contains the given key
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PeekMutError::TypeMismatch`] if `K` isn't the key type of the map.
+    pub fn contains_key<K: Facet>(&self, key: &K) -> Result<bool, PeekMutError> {
+        expect_shape::<K>(self.def.k)?;
+        Ok(unsafe {
+            (self.def.vtable.contains_key_fn)(
+                self.value.data.as_const(),
+                OpaqueConst::from_ref(key),
+            )
+        })
+    }
+
+    /// Gets a mutable peek at the value for the given key, or `None` if the key isn't in the map
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `K` isn't the key type of the map, or if the map doesn't allow
+    /// mutating its values in place.
+    pub fn get_mut<K: Facet>(&mut self, key: &K) -> Result<Option<PeekMut<'_>>, PeekMutError> {
+        expect_shape::<K>(self.def.k)?;
+        let get_value_ptr_mut = self.supports(self.def.vtable.get_value_ptr_mut_fn, "get_mut")?;
+        unsafe {
+            let value = get_value_ptr_mut(self.value.data, OpaqueConst::from_ref(key));
+            Ok(value.map(|value| PeekMut::unchecked_new(value, self.def.v)))
+        }
+    }
+
+    /// Inserts an entry, dropping the value previously stored under the same key, if any
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PeekMutError::TypeMismatch`] if `K` or `V` don't match the map.
+    pub fn insert<K: Facet, V: Facet>(&mut self, key: K, value: V) -> Result<(), PeekMutError> {
+        expect_shape::<K>(self.def.k)?;
+        expect_shape::<V>(self.def.v)?;
+        let mut key = core::mem::ManuallyDrop::new(key);
+        let mut value = core::mem::ManuallyDrop::new(value);
+        unsafe {
+            (self.def.vtable.insert_fn)(
+                self.value.data,
+                Opaque::from_ref(&mut *key),
+                Opaque::from_ref(&mut *value),
+            );
+        }
+        Ok(())
+    }
+
+    /// Removes (and drops) the entry for the given key. Returns true if there was one.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `K` isn't the key type of the map, or if the map doesn't
+    /// support removing entries.
+    pub fn remove<K: Facet>(&mut self, key: &K) -> Result<bool, PeekMutError> {
+        expect_shape::<K>(self.def.k)?;
+        let remove = self.supports(self.def.vtable.remove_fn, "remove")?;
+        let (_, guard) = Poke::alloc_shape(self.def.v);
+        unsafe {
+            let removed = remove(
+                self.value.data,
+                OpaqueConst::from_ref(key),
+                guard.as_uninit(),
+            );
+            match removed {
+                Some(value) => {
+                    if let Some(drop_fn) = self.def.v.vtable.drop_in_place {
+                        drop_fn(value);
+                    }
+                    Ok(true)
+                }
+                None => Ok(false),
+            }
+        }
+    }
+
+    /// Removes (and drops) every entry of the map
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PeekMutError::Unsupported`] if the map doesn't support removing entries.
+    pub fn clear(&mut self) -> Result<(), PeekMutError> {
+        let clear = self.supports(self.def.vtable.clear_fn, "clear")?;
+        unsafe { clear(self.value.data) };
+        Ok(())
+    }
+
+    fn supports<F>(&self, f: Option<F>, operation: &'static str) -> Result<F, PeekMutError> {
+        f.ok_or(PeekMutError::Unsupported {
+            shape: self.value.shape,
+            operation,
+        })
+    }
+}
+
+/// Lets you mutate an initialized enum: edit the fields of the current variant, or
+/// switch to another variant
+pub struct PeekEnumMut<'mem> {
+    value: PeekValueMut<'mem>,
+    def: EnumDef,
+}
+
+impl<'mem> core::ops::Deref for PeekEnumMut<'mem> {
+    type Target = PeekValueMut<'mem>;
+
+    #[inline(always)]
+    fn deref(&self) -> &Self::Target {
+        &self.value
+    }
+}
+
+impl core::ops::DerefMut for PeekEnumMut<'_> {
+    #[inline(always)]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.value
+    }
+}
+
+impl PeekEnumMut<'_> {
+    /// Returns the enum definition
+    #[inline(always)]
+    pub fn def(&self) -> &EnumDef {
+        &self.def
+    }
+
+    /// Returns the index of the current variant
+    ///
+    /// # Panics
+    ///
+    /// Panics if the discriminant doesn't match any variant of the enum definition.
+    pub fn variant_index(&self) -> usize {
+        unsafe { read_variant_index(self.value.data.as_const(), &self.def) }.unwrap_or_else(|| {
+            panic!(
+                "Discriminant of {} doesn't match any of its variants",
+                self.value.shape
+            )
+        })
+    }
+
+    /// Returns the current variant
+    pub fn variant(&self) -> &'static Variant {
+        &self.def.variants[self.variant_index()]
+    }
+
+    /// Gets a mutable peek at a field of the current variant
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PeekMutError::NoSuchField`] if the current variant has no such field.
+    pub fn field_mut(&mut self, name: &str) -> Result<PeekMut<'_>, PeekMutError> {
+        let field = variant_fields(self.variant())
+            .iter()
+            .find(|f| f.name == name)
+            .ok_or(PeekMutError::NoSuchField)?;
+        Ok(unsafe { field_of(self.value.data, field) })
+    }
+
+    /// Replaces the value of a field of the current variant, dropping the previous one.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the current variant has no such field, or if `T` isn't the
+    /// type of the field.
+    pub fn set_field<T: Facet>(&mut self, name: &str, value: T) -> Result<(), PeekMutError> {
+        self.field_mut(name)?.replace(value)
+    }
+
+    /// Starts switching the enum to the variant with the given name.
+    ///
+    /// The new variant is built on the side; the enum keeps its current value until
+    /// [`VariantSwitch::commit`] succeeds. Dropping the switch abandons it.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PeekMutError::NoSuchVariant`] if there is no such variant.
+    pub fn switch_variant(&mut self, name: &str) -> Result<VariantSwitch<'_>, PeekMutError> {
+        let (poke, guard) = Poke::alloc_shape(self.value.shape);
+        // dropping the guard on error is fine: nothing was initialized yet
+        let poke = poke
+            .into_enum()
+            .set_variant_by_name(name)
+            .map_err(|_| PeekMutError::NoSuchVariant)?;
+        Ok(VariantSwitch {
+            poke,
+            guard,
+            target: self.value.data,
+            _borrow: PhantomData,
+        })
+    }
+}
+
+/// A variant being built to replace the current value of an enum.
+///
+/// See [`PeekEnumMut::switch_variant`].
+pub struct VariantSwitch<'a> {
+    // drops whatever fields were set before the guard frees the scratch allocation
+    poke: PokeEnum<'a>,
+    guard: Guard,
+    target: Opaque<'a>,
+    _borrow: PhantomData<&'a mut ()>,
+}
+
+impl VariantSwitch<'_> {
+    /// Returns the variant being built
+    pub fn variant(&self) -> &'static Variant {
+        let Def::Enum(def) = self.guard.shape().def else {
+            unreachable!()
+        };
+        &def.variants[self.poke.selected_variant_index()]
+    }
+
+    /// Sets a field of the new variant. Setting a field twice drops the previous value.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the new variant has no such field, or if `T` isn't the
+    /// type of the field.
+    pub fn set_field<T: Facet>(&mut self, name: &str, value: T) -> Result<(), PeekMutError> {
+        let fields = variant_fields(self.variant());
+        let index = fields
+            .iter()
+            .position(|f| f.name == name)
+            .ok_or(PeekMutError::NoSuchField)?;
+        expect_shape::<T>(fields[index].shape)?;
+
+        let field = self
+            .poke
+            .variant_field_by_name(name)
+            .map_err(|_| PeekMutError::NoSuchField)?;
+        let value = core::mem::ManuallyDrop::new(value);
+        unsafe {
+            field.into_value().put(OpaqueConst::from_ref(&*value));
+            self.poke.mark_field_as_initialized(index);
+        }
+        Ok(())
+    }
+
+    /// Replaces the enum with the new variant, dropping its previous value.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PeekMutError::MissingField`] if a field of the new variant wasn't set,
+    /// in which case the enum is left untouched.
+    pub fn commit(self) -> Result<(), PeekMutError> {
+        if let Some(field) = self.poke.first_uninitialized_field() {
+            return Err(PeekMutError::MissingField(field.name));
+        }
+
+        let VariantSwitch {
+            poke,
+            guard,
+            target,
+            _borrow,
+        } = self;
+        let shape = guard.shape();
+        let built = poke.build_in_place();
+        unsafe {
+            if let Some(drop_fn) = shape.vtable.drop_in_place {
+                drop_fn(target);
+            }
+            core::ptr::copy_nonoverlapping(
+                built.as_byte_ptr(),
+                target.as_mut_byte_ptr(),
+                shape.layout.size(),
+            );
+        }
+        // the value was moved out: only free the scratch allocation
+        drop(guard);
+        Ok(())
+    }
+}
+
+/// Lets you mutate an initialized option: the value it holds, or whether it holds one
+pub struct PeekOptionMut<'mem> {
+    value: PeekValueMut<'mem>,
+    def: OptionDef,
+}
+
+impl<'mem> core::ops::Deref for PeekOptionMut<'mem> {
+    type Target = PeekValueMut<'mem>;
+
+    #[inline(always)]
+    fn deref(&self) -> &Self::Target {
+        &self.value
+    }
+}
+
+impl core::ops::DerefMut for PeekOptionMut<'_> {
+    #[inline(always)]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.value
+    }
+}
+
+impl PeekOptionMut<'_> {
+    /// Returns the option definition
+    #[inline(always)]
+    pub fn def(&self) -> &OptionDef {
+        &self.def
+    }
+
+    /// Returns true if the option holds a value
+    pub fn is_some(&self) -> bool {
+        unsafe { (self.def.vtable.is_some)(self.value.data.as_const()) }
+    }
+
+    /// Returns true if the option holds no value
+    pub fn is_none(&self) -> bool {
+        !self.is_some()
+    }
+
+    /// Gets a mutable peek at the value held by the option, if any
+    pub fn value_mut(&mut self) -> Option<PeekMut<'_>> {
+        unsafe {
+            let inner = (self.def.vtable.get_value)(self.value.data.as_const())?;
+            // the option is borrowed mutably, so is what it holds
+            let inner = Opaque::new_unchecked(inner.as_byte_ptr() as *mut u8);
+            Some(PeekMut::unchecked_new(inner, self.def.t))
+        }
+    }
+
+    /// Makes the option hold `value`, dropping the previous value if any
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PeekMutError::TypeMismatch`] if `T` isn't the type held by the option.
+    pub fn set_some<T: Facet>(&mut self, value: T) -> Result<(), PeekMutError> {
+        expect_shape::<T>(self.def.t)?;
+        let mut value = core::mem::ManuallyDrop::new(value);
+        let init_some = self.def.vtable.init_some;
+        unsafe {
+            init_some(self.clear(), Opaque::from_ref(&mut *value));
+        }
+        Ok(())
+    }
+
+    /// Makes the option hold no value, dropping the previous value if any
+    pub fn set_none(&mut self) {
+        let init_none = self.def.vtable.init_none;
+        unsafe {
+            init_none(self.clear());
+        }
+    }
+
+    /// Drops the option, leaving its memory uninitialized
+    ///
+    /// # Safety
+    ///
+    /// The option must be initialized again before the borrow ends.
+    unsafe fn clear(&mut self) -> OpaqueUninit<'_> {
+        unsafe {
+            if let Some(drop_fn) = self.value.shape.vtable.drop_in_place {
+                drop_fn(self.value.data);
+            }
+            OpaqueUninit::new(self.value.data.as_mut_byte_ptr())
+        }
+    }
+}
+
+/// Errors returned when mutating a value through a [`PeekMut`]
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum PeekMutError {
+    /// No field with the given name exists.
+    NoSuchField,
+
+    /// No variant with the given name exists.
+    NoSuchVariant,
+
+    /// The index is past the end of the struct or list.
+    IndexOutOfBounds {
+        /// The index that was requested
+        index: usize,
+        /// The number of fields or items
+        len: usize,
+    },
+
+    /// The value has a different type than the one being written or read.
+    TypeMismatch {
+        /// The shape of the value being mutated
+        expected: &'static Shape,
+        /// The shape of the type that was passed
+        actual: &'static Shape,
+    },
+
+    /// The value's vtable doesn't support the operation.
+    Unsupported {
+        /// The shape of the value being mutated
+        shape: &'static Shape,
+        /// The operation that was attempted
+        operation: &'static str,
+    },
+
+    /// A field of the new variant was never set.
+    MissingField(&'static str),
+}
+
+impl core::error::Error for PeekMutError {}
+
+impl core::fmt::Display for PeekMutError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            PeekMutError::NoSuchField => write!(f, "No such field"),
+            PeekMutError::NoSuchVariant => write!(f, "No such variant"),
+            PeekMutError::IndexOutOfBounds { index, len } => {
+                write!(f, "Index {index} out of bounds (len is {len})")
+            }
+            PeekMutError::TypeMismatch { expected, actual } => {
+                write!(f, "Type mismatch: expected {expected}, got {actual}")
+            }
+            PeekMutError::Unsupported { shape, operation } => {
+                write!(f, "{shape} does not support {operation}")
+            }
+            PeekMutError::MissingField(name) => write!(f, "Missing field: {name}"),
+        }
+    }
+}
+
+fn expect_shape<T: Facet>(expected: &'static Shape) -> Result<(), PeekMutError> {
+    if expected.is_type::<T>() {
+        Ok(())
+    } else {
+        Err(PeekMutError::TypeMismatch {
+            expected,
+            actual: T::SHAPE,
+        })
+    }
+}
+
+fn variant_fields(variant: &'static Variant) -> &'static [Field] {
+    match &variant.kind {
+        VariantKind::Tuple { fields } | VariantKind::Struct { fields } => fields,
+        _ => &[],
+    }
+}
+
+/// # Safety
+///
+/// `data` must point to an initialized value that has `field` at `field.offset`.
+unsafe fn field_of<'mem>(data: Opaque<'mem>, field: &Field) -> PeekMut<'mem> {
+    unsafe {
+        let field_data = Opaque::new_unchecked(data.as_mut_byte_ptr().add(field.offset));
+        PeekMut::unchecked_new(field_data, field.shape)
+    }
+}
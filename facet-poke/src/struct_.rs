@@ -1,7 +1,11 @@
+use alloc::boxed::Box;
 use core::ptr::NonNull;
-use facet_trait::{FieldError, Opaque, OpaqueConst, OpaqueUninit, Shape, ShapeExt as _, StructDef};
+use facet_trait::{
+    Field, FieldError, Opaque, OpaqueConst, OpaqueUninit, Shape, ShapeExt as _, StructDef,
+};
 
 use super::{Guard, ISet, PokeValue};
+use crate::displaced::Displaced;
 
 /// Allows poking a struct (setting fields, etc.)
 pub struct PokeStruct<'mem> {
@@ -9,6 +13,7 @@ pub struct PokeStruct<'mem> {
     shape: &'static Shape,
     def: StructDef,
     iset: ISet,
+    displaced: Displaced,
 }
 
 impl<'mem> PokeStruct<'mem> {
@@ -23,6 +28,12 @@ impl<'mem> PokeStruct<'mem> {
     pub fn shape(&self) -> &'static Shape {
         self.shape
     }
+
+    /// Returns the struct definition
+    #[inline(always)]
+    pub fn def(&self) -> &StructDef {
+        &self.def
+    }
     /// Creates a new PokeStruct
     ///
     /// # Safety
@@ -31,17 +42,38 @@ impl<'mem> PokeStruct<'mem> {
     pub unsafe fn new(data: OpaqueUninit<'mem>, shape: &'static Shape, def: StructDef) -> Self {
         Self {
             data,
-            iset: Default::default(),
+            iset: ISet::new(def.fields.len()),
             shape,
             def,
+            displaced: Displaced::default(),
         }
     }
 
+    /// Returns true if the field at `index` holds a value: either it was initialized, or
+    /// it's being written again and still has its previous value to fall back on
+    fn has_value(&self, index: usize) -> bool {
+        self.iset.has(index) || self.displaced.has(index)
+    }
+
+    /// Puts back the previous values of the fields that were not written again
+    fn restore_displaced(&mut self) {
+        self.displaced
+            .restore(self.def.fields, self.data, &mut self.iset);
+    }
+
+    /// Returns the first field that hasn't been initialized yet, if any.
+    pub fn first_uninitialized_field(&self) -> Option<&'static Field> {
+        let fields = self.def.fields;
+        (0..fields.len())
+            .find(|&i| !self.has_value(i))
+            .map(|i| &fields[i])
+    }
+
     /// Checks if all fields in the struct have been initialized.
     /// Panics if any field is not initialized, providing details about the uninitialized field.
     pub fn assert_all_fields_initialized(&self) {
         for (i, field) in self.def.fields.iter().enumerate() {
-            if !self.iset.has(i) {
+            if !self.has_value(i) {
                 panic!(
                     "Field '{}' was not initialized. Complete schema:\n{:?}",
                     field.name, self.shape
@@ -58,8 +90,9 @@ impl<'mem> PokeStruct<'mem> {
     /// # Panics
     ///
     /// This function will panic if any field is not initialized.
-    pub fn build_in_place(self) -> Opaque<'mem> {
+    pub fn build_in_place(mut self) -> Opaque<'mem> {
         // ensure all fields are initialized
+        self.restore_displaced();
         self.assert_all_fields_initialized();
 
         let data = unsafe { self.data.assume_init() };
@@ -80,9 +113,10 @@ impl<'mem> PokeStruct<'mem> {
     /// - The generic type parameter T does not match the shape that this PokeStruct is building.
     pub fn build<T: crate::Facet>(self, guard: Option<Guard>) -> T {
         let mut guard = guard;
-        let this = self;
+        let mut this = self;
         // this changes drop order: guard must be dropped _after_ this.
 
+        this.restore_displaced();
         this.assert_all_fields_initialized();
         this.shape.assert_type::<T>();
         if let Some(guard) = &guard {
@@ -105,7 +139,8 @@ impl<'mem> PokeStruct<'mem> {
     /// This function will panic if:
     /// - Not all the fields have been initialized.
     /// - The generic type parameter T does not match the shape that this PokeStruct is building.
-    pub fn build_boxed<T: crate::Facet>(self) -> Box<T> {
+    pub fn build_boxed<T: crate::Facet>(mut self) -> Box<T> {
+        self.restore_displaced();
         self.assert_all_fields_initialized();
         self.shape.assert_type::<T>();
 
@@ -122,7 +157,8 @@ impl<'mem> PokeStruct<'mem> {
     /// and must be large enough to hold the value.
     /// The caller is responsible for ensuring that the target memory is properly deallocated
     /// when it's no longer needed.
-    pub unsafe fn move_into(self, target: NonNull<u8>, guard: Option<Guard>) {
+    pub unsafe fn move_into(mut self, target: NonNull<u8>, guard: Option<Guard>) {
+        self.restore_displaced();
         self.assert_all_fields_initialized();
         if let Some(guard) = &guard {
             guard.shape.assert_shape(self.shape);
@@ -151,6 +187,10 @@ impl<'mem> PokeStruct<'mem> {
 
     /// Get a field writer for a field by index.
     ///
+    /// If the field was already initialized, its previous value is kept aside until the
+    /// field is marked initialized again, and only dropped then. If the write is abandoned
+    /// instead, the previous value is put back when the struct is built or dropped.
+    ///
     /// # Errors
     ///
     /// Returns an error if:
@@ -162,6 +202,10 @@ impl<'mem> PokeStruct<'mem> {
         }
 
         let field = &self.def.fields[index];
+        if self.iset.has(index) {
+            unsafe { self.displaced.displace(index, field, self.data) };
+            self.iset.unset(index);
+        }
 
         // Get the field's address
         let field_addr = unsafe { self.data.field_uninit(field.offset) };
@@ -182,7 +226,9 @@ impl<'mem> PokeStruct<'mem> {
         if index >= self.def.fields.len() {
             return Err(FieldError::IndexOutOfBounds);
         }
+        self.drop_field(index);
         let field = &self.def.fields[index];
+        self.displaced.commit(index, field);
         let field_shape = field.shape;
 
         unsafe {
@@ -220,13 +266,29 @@ impl<'mem> PokeStruct<'mem> {
     ///
     /// The caller must ensure that the field is initialized. Only call this after writing to
     /// an address gotten through [`Self::field`] or [`Self::field_by_name`].
+    ///
+    /// If the field had a value before, it's dropped now.
     pub unsafe fn mark_initialized(&mut self, index: usize) {
         self.iset.set(index);
+        self.displaced.commit(index, &self.def.fields[index]);
+    }
+
+    /// Drops the field at `index` if it was initialized, and marks it uninitialized.
+    fn drop_field(&mut self, index: usize) {
+        if !self.iset.has(index) {
+            return;
+        }
+        let field = &self.def.fields[index];
+        if let Some(drop_fn) = field.shape.vtable.drop_in_place {
+            unsafe { drop_fn(self.data.field_init(field.offset)) };
+        }
+        self.iset.unset(index);
     }
 }
 
 impl Drop for PokeStruct<'_> {
     fn drop(&mut self) {
+        self.restore_displaced();
         self.def
             .fields
             .iter()
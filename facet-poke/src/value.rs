@@ -1,5 +1,7 @@
 use facet_peek::Peek;
-use facet_trait::{Opaque, OpaqueConst, OpaqueUninit, Shape, TryFromError, ValueVTable};
+use facet_trait::{
+    Opaque, OpaqueConst, OpaqueUninit, ParseError, Shape, TryFromError, ValueVTable,
+};
 
 /// Lets you write to a value (implements write-only [`ValueVTable`] proxies)
 pub struct PokeValue<'mem> {
@@ -45,12 +47,19 @@ impl<'mem> PokeValue<'mem> {
     /// Attempts to convert a value from another type into this one
     ///
     /// Returns `Ok(Opaque)` if the conversion was successful, `Err((Self, TryFromError))` otherwise.
-    pub fn try_from<'src>(
+    ///
+    /// # Safety
+    ///
+    /// `source` must point to an initialized value of the type described by `source_shape`.
+    /// Unless the error is [`TryFromError::Incompatible`] or [`TryFromError::Unimplemented`],
+    /// `source` has been moved out of — it should be deallocated, but not dropped.
+    pub unsafe fn try_from<'src>(
         self,
         source: OpaqueConst<'src>,
+        source_shape: &'static Shape,
     ) -> Result<Opaque<'mem>, (Self, TryFromError)> {
         if let Some(try_from_fn) = self.vtable().try_from {
-            match unsafe { try_from_fn(source, self.data) } {
+            match unsafe { try_from_fn(source, source_shape, self.data) } {
                 Ok(built_val) => Ok(built_val),
                 Err(err) => Err((self, err)),
             }
@@ -62,15 +71,20 @@ impl<'mem> PokeValue<'mem> {
 
     /// Attempts to parse a string into this value
     ///
-    /// Returns `Ok(Opaque)` if parsing was successful, `Err(Self)` otherwise.
-    pub fn parse(self, s: &str) -> Result<Opaque<'mem>, Self> {
+    /// Returns `Ok(Opaque)` if parsing was successful, `Err((Self, ParseError))` otherwise.
+    /// The error always carries this value's shape.
+    pub fn parse(self, s: &str) -> Result<Opaque<'mem>, (Self, ParseError)> {
+        let shape = self.shape;
         if let Some(parse_fn) = self.vtable().parse {
             match unsafe { parse_fn(s, self.data) } {
                 Ok(parsed_val) => Ok(parsed_val),
-                Err(_) => Err(self),
+                Err(err) => Err((self, err.or_shape(shape))),
             }
         } else {
-            Err(self)
+            Err((
+                self,
+                ParseError::new("type does not implement parsing from a string").with_shape(shape),
+            ))
         }
     }
 
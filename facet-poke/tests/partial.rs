@@ -0,0 +1,300 @@
+use std::collections::HashMap;
+
+use facet_derive::Facet;
+use facet_poke::{MissingFields, Partial, PartialErrorKind};
+use facet_testhelpers::Source;
+use facet_trait::Facet;
+
+use facet_trait as facet;
+
+#[derive(Debug, PartialEq, Facet)]
+struct Address {
+    city: String,
+    zip: String,
+}
+
+#[derive(Debug, PartialEq, Facet)]
+struct Item {
+    name: String,
+    qty: u64,
+}
+
+#[derive(Debug, PartialEq, Facet)]
+struct Order {
+    customer: String,
+    address: Address,
+    tags: Vec<String>,
+    items: Vec<Item>,
+    notes: HashMap<String, String>,
+}
+
+fn item(name: &str, qty: u64) -> Item {
+    Item {
+        name: name.to_string(),
+        qty,
+    }
+}
+
+#[test]
+fn build_by_path() {
+    let mut partial = Partial::<Order>::new();
+    partial
+        .set("customer", "Ada")
+        .unwrap()
+        .set("address.city", "Paris")
+        .unwrap()
+        .set("address.zip", "75001")
+        .unwrap()
+        .set("items", vec![item("pen", 1), item("ink", 2)])
+        .unwrap();
+    partial.push("tags", "urgent").unwrap();
+    partial.push("tags", "gift".to_string()).unwrap();
+    partial.set_at(&["items", "1", "qty"], 5u32).unwrap();
+    partial.set("notes.door", "blue").unwrap();
+
+    let order = partial.build().unwrap();
+    assert_eq!(
+        order,
+        Order {
+            customer: "Ada".to_string(),
+            address: Address {
+                city: "Paris".to_string(),
+                zip: "75001".to_string(),
+            },
+            tags: vec!["urgent".to_string(), "gift".to_string()],
+            items: vec![item("pen", 1), item("ink", 5)],
+            notes: HashMap::from([("door".to_string(), "blue".to_string())]),
+        }
+    );
+}
+
+#[test]
+fn reports_every_missing_field() {
+    let mut partial = Partial::<Order>::new();
+    partial.set("address.zip", "75001").unwrap();
+    partial.push("items", item("pen", 1)).unwrap();
+
+    assert_eq!(
+        partial.build().err(),
+        Some(MissingFields {
+            paths: vec![
+                "customer".to_string(),
+                "address.city".to_string(),
+                "tags".to_string(),
+                "notes".to_string(),
+            ],
+        })
+    );
+}
+
+#[test]
+fn setting_twice_replaces() {
+    let mut partial = Partial::<Address>::new();
+    partial.set("city", "Lyon").unwrap();
+    partial.set("city", "Paris").unwrap();
+    partial
+        .set(
+            "",
+            Address {
+                city: "Nice".to_string(),
+                zip: "06000".to_string(),
+            },
+        )
+        .unwrap();
+    partial.set("zip", "06100").unwrap();
+
+    let address = partial.build().unwrap();
+    assert_eq!(address.city, "Nice");
+    assert_eq!(address.zip, "06100");
+}
+
+#[test]
+fn path_errors() {
+    let mut partial = Partial::<Order>::new();
+
+    let err = partial.set("address.country", "FR").err().unwrap();
+    assert_eq!(err.path, "address.country");
+    assert_eq!(err.kind, PartialErrorKind::NoSuchField);
+
+    let err = partial.set("customer", 42u64).err().unwrap();
+    assert_eq!(err.path, "customer");
+    assert_eq!(
+        err.kind,
+        PartialErrorKind::TypeMismatch {
+            expected: String::SHAPE,
+            actual: u64::SHAPE,
+        }
+    );
+
+    let err = partial.set("items.0.qty", 1u64).err().unwrap();
+    assert_eq!(err.path, "items.0");
+    assert_eq!(
+        err.kind,
+        PartialErrorKind::IndexOutOfBounds { index: 0, len: 0 }
+    );
+
+    let err = partial.set("items.first", 1u64).err().unwrap();
+    assert_eq!(err.kind, PartialErrorKind::InvalidKey);
+
+    let err = partial.set("notes.door.color", "red").err().unwrap();
+    assert_eq!(err.path, "notes.door");
+    assert_eq!(err.kind, PartialErrorKind::NoSuchKey);
+
+    let err = partial.push("customer", "x").err().unwrap();
+    assert_eq!(err.kind, PartialErrorKind::NotAList);
+
+    let err = partial.set("items", vec![-1i64]).err().unwrap();
+    assert!(matches!(err.kind, PartialErrorKind::TypeMismatch { .. }));
+}
+
+#[test]
+fn failed_conversions_are_reported() {
+    let mut partial = Partial::<Item>::new();
+    let err = partial.set("qty", -1i32).err().unwrap();
+    assert!(matches!(err.kind, PartialErrorKind::Conversion(_)));
+    partial.set("qty", 3i32).unwrap();
+    partial.set("name", "pen").unwrap();
+    assert_eq!(partial.build().unwrap(), item("pen", 3));
+}
+
+#[derive(Debug, PartialEq, Facet)]
+struct Config {
+    name: String,
+    source: Source,
+}
+
+#[test]
+fn build_enum_variants() {
+    let mut partial = Partial::<Config>::new();
+    partial.set("name", "app").unwrap();
+
+    let err = partial
+        .set("source.url", "https://example.com")
+        .err()
+        .unwrap();
+    assert_eq!(err.kind, PartialErrorKind::NoVariantSelected);
+
+    partial
+        .set("source.Remote.url", "https://example.com")
+        .unwrap();
+    assert_eq!(
+        partial.missing_fields(),
+        vec!["source.Remote.retries".to_string()]
+    );
+    partial.set("source.retries", 3u8).unwrap();
+
+    let config = partial.build().unwrap();
+    assert_eq!(
+        config.source,
+        Source::Remote {
+            url: "https://example.com".to_string(),
+            retries: 3,
+        }
+    );
+}
+
+#[test]
+fn switching_variants_drops_the_previous_one() {
+    let mut partial = Partial::<Config>::new();
+    partial.set("name", "app").unwrap();
+    partial.set("source.File.0", "/etc/app.toml").unwrap();
+    partial.set_variant("source", "Disabled").unwrap();
+    assert_eq!(partial.build().unwrap().source, Source::Disabled);
+
+    let mut partial = Partial::<Config>::new();
+    partial
+        .set("source", Source::File("a".to_string()))
+        .unwrap();
+    partial.set("source.File.0", "b").unwrap();
+    partial.set("source.Remote.retries", 1u64).unwrap();
+    partial.set("name", "app").unwrap();
+    assert_eq!(
+        partial.missing_fields(),
+        vec!["source.Remote.url".to_string()]
+    );
+    partial.set("source.url", "https://example.com").unwrap();
+    assert_eq!(
+        partial.build().unwrap().source,
+        Source::Remote {
+            url: "https://example.com".to_string(),
+            retries: 1,
+        }
+    );
+
+    // inside an initialized list, nothing tracks the items: they can't switch variants
+    let mut partial = Partial::<Vec<Source>>::new();
+    partial.push("", Source::Disabled).unwrap();
+    let err = partial.set("0.Remote.retries", 1u64).err().unwrap();
+    assert_eq!(err.path, "0.Remote");
+    assert_eq!(
+        err.kind,
+        PartialErrorKind::VariantAlreadySet {
+            current: "Disabled"
+        }
+    );
+    partial.set("0", Source::File("c".to_string())).unwrap();
+    partial.set("0.File.0", "d").unwrap();
+    assert_eq!(partial.build().unwrap(), [Source::File("d".to_string())]);
+
+    let err = Partial::<Config>::new()
+        .set_variant("name", "Remote")
+        .err()
+        .unwrap();
+    assert_eq!(err.kind, PartialErrorKind::NotAnEnum);
+}
+
+#[test]
+fn scalars_and_maps_at_the_root() {
+    let mut partial = Partial::<u64>::new();
+    assert_eq!(partial.missing_fields(), vec![String::new()]);
+    partial.set("", 7u8).unwrap();
+    assert_eq!(partial.build().unwrap(), 7);
+
+    let mut partial = Partial::<HashMap<u64, Vec<String>>>::new();
+    partial.set("1", vec!["one".to_string()]).unwrap();
+    partial.push("1", "uno").unwrap();
+    let err = partial.push("2", "dos").err().unwrap();
+    assert_eq!(err.kind, PartialErrorKind::NoSuchKey);
+    let map = partial.build().unwrap();
+    assert_eq!(map[&1], ["one", "uno"]);
+}
+
+thread_local! {
+    static DROPS: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+}
+
+#[derive(Facet)]
+struct Tracked {
+    id: u64,
+}
+
+impl Drop for Tracked {
+    fn drop(&mut self) {
+        DROPS.with(|d| d.set(d.get() + 1));
+    }
+}
+
+#[derive(Facet)]
+struct Pair {
+    left: Tracked,
+    right: Tracked,
+    all: Vec<Tracked>,
+}
+
+#[test]
+fn abandoned_partial_drops_what_was_set() {
+    let drops = || DROPS.with(|d| d.get());
+    let before = drops();
+
+    let mut partial = Partial::<Pair>::new();
+    partial.set("left", Tracked { id: 1 }).unwrap();
+    partial.set("left.id", 2u64).unwrap();
+    partial.push("all", Tracked { id: 3 }).unwrap();
+    partial.push("all", Tracked { id: 4 }).unwrap();
+    assert_eq!(drops() - before, 0);
+    partial.set("left", Tracked { id: 5 }).unwrap();
+    assert_eq!(drops() - before, 1);
+
+    assert!(partial.build().is_err());
+    assert_eq!(drops() - before, 4);
+}
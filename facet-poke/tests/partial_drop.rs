@@ -0,0 +1,171 @@
+use std::cell::Cell;
+
+use facet_derive::Facet;
+use facet_poke::Poke;
+use facet_trait::{Facet, Opaque, OpaqueConst};
+
+use facet_trait as facet;
+
+thread_local! {
+    static DROPS: Cell<usize> = const { Cell::new(0) };
+}
+
+fn drops() -> usize {
+    DROPS.with(|d| d.get())
+}
+
+#[derive(Facet)]
+struct Tracked {
+    id: u64,
+}
+
+impl Drop for Tracked {
+    fn drop(&mut self) {
+        DROPS.with(|d| d.set(d.get() + 1));
+    }
+}
+
+#[derive(Facet)]
+struct Triple {
+    a: Tracked,
+    b: Tracked,
+    c: Tracked,
+}
+
+/// Moves a `Tracked` into field `index` of the struct being poked
+fn set_tracked(ps: &mut facet_poke::PokeStruct<'_>, index: usize, id: u64) {
+    let value = Tracked { id };
+    ps.set(index, OpaqueConst::from_ref(&value)).unwrap();
+    core::mem::forget(value);
+}
+
+#[test]
+fn partial_struct_drops_initialized_fields_only() {
+    // abandon the struct after 0, 1, 2 and 3 fields
+    for filled in 0..=3 {
+        let before = drops();
+        let (poke, guard) = Poke::alloc::<Triple>();
+        let mut ps = poke.into_struct();
+        for index in 0..filled {
+            set_tracked(&mut ps, index, index as u64);
+        }
+        drop(ps);
+        drop(guard);
+        assert_eq!(drops() - before, filled);
+    }
+}
+
+#[test]
+fn setting_a_field_twice_drops_the_previous_value() {
+    let before = drops();
+    let (poke, guard) = Poke::alloc::<Triple>();
+    let mut ps = poke.into_struct();
+    set_tracked(&mut ps, 1, 1);
+    set_tracked(&mut ps, 1, 2);
+    assert_eq!(drops() - before, 1);
+
+    // re-opening the field keeps what was there until a new value is committed
+    let Poke::Struct(field) = ps.field(1).unwrap() else {
+        panic!("expected a struct poke");
+    };
+    assert_eq!(drops() - before, 1);
+    drop(field);
+
+    set_tracked(&mut ps, 0, 0);
+    set_tracked(&mut ps, 1, 1);
+    set_tracked(&mut ps, 2, 2);
+    let triple = ps.build::<Triple>(Some(guard));
+    assert_eq!(drops() - before, 2);
+    assert_eq!([triple.a.id, triple.b.id, triple.c.id], [0, 1, 2]);
+    drop(triple);
+    assert_eq!(drops() - before, 5);
+}
+
+#[test]
+fn abandoned_field_writes_keep_the_previous_value() {
+    let before = drops();
+    let (poke, guard) = Poke::alloc::<Triple>();
+    let mut ps = poke.into_struct();
+    set_tracked(&mut ps, 0, 0);
+    set_tracked(&mut ps, 1, 1);
+    set_tracked(&mut ps, 2, 2);
+
+    // bail out of a write, as a deserializer would on a parse error
+    let Poke::Struct(field) = ps.field(1).unwrap() else {
+        panic!("expected a struct poke");
+    };
+    drop(field);
+    assert_eq!(drops() - before, 0);
+    assert!(ps.first_uninitialized_field().is_none());
+
+    // a committed write drops the previous value then, and only then
+    let Poke::Struct(mut field) = ps.field(2).unwrap() else {
+        panic!("expected a struct poke");
+    };
+    field
+        .set_by_name("id", OpaqueConst::from_ref(&20u64))
+        .unwrap();
+    field.build_in_place();
+    assert_eq!(drops() - before, 0);
+    unsafe { ps.mark_initialized(2) };
+    assert_eq!(drops() - before, 1);
+
+    let triple = ps.build::<Triple>(Some(guard));
+    assert_eq!([triple.a.id, triple.b.id, triple.c.id], [0, 1, 20]);
+    drop(triple);
+    assert_eq!(drops() - before, 4);
+}
+
+#[test]
+fn abandoned_struct_drops_the_previous_value_of_reopened_fields() {
+    let before = drops();
+    let (poke, guard) = Poke::alloc::<Triple>();
+    let mut ps = poke.into_struct();
+    set_tracked(&mut ps, 0, 0);
+    set_tracked(&mut ps, 1, 1);
+    drop(ps.field(1).unwrap());
+    drop(ps.field(0).unwrap());
+    assert_eq!(drops() - before, 0);
+
+    drop(ps);
+    drop(guard);
+    assert_eq!(drops() - before, 2);
+}
+
+#[test]
+fn abandoned_list_drops_its_items() {
+    for pushed in 0..=3 {
+        let before = drops();
+        let (poke, guard) = Poke::alloc::<Vec<Tracked>>();
+        let mut pl = poke.into_list().init(None).ok().unwrap();
+        for id in 0..pushed {
+            let mut item = Tracked { id };
+            unsafe { pl.push(Opaque::from_ref(&mut item)) };
+            core::mem::forget(item);
+        }
+        assert_eq!(pl.len(), pushed as usize);
+        drop(pl);
+        drop(guard);
+        assert_eq!(drops() - before, pushed as usize);
+    }
+}
+
+#[test]
+fn built_list_is_not_dropped_by_the_poke() {
+    let before = drops();
+    let mut items: Vec<Tracked> = Vec::new();
+    let (poke, _guard) = Poke::alloc::<Vec<Tracked>>();
+    let mut pl = poke.into_list().init(None).ok().unwrap();
+    let mut item = Tracked { id: 7 };
+    unsafe { pl.push(Opaque::from_ref(&mut item)) };
+    core::mem::forget(item);
+    let built = pl.build_in_place();
+    assert_eq!(drops() - before, 0);
+
+    core::mem::swap(&mut items, unsafe { built.as_mut::<Vec<Tracked>>() });
+    unsafe { built.drop_in_place::<Vec<Tracked>>() };
+    assert_eq!(items.len(), 1);
+    assert_eq!(items[0].id, 7);
+    drop(items);
+    assert_eq!(drops() - before, 1);
+}
@@ -0,0 +1,449 @@
+use std::cell::Cell;
+use std::collections::HashMap;
+
+use facet_derive::Facet;
+use facet_poke::{PeekMut, PeekMutError};
+use facet_testhelpers::Source;
+use facet_trait::Facet;
+
+use facet_trait as facet;
+
+thread_local! {
+    static DROPS: Cell<usize> = const { Cell::new(0) };
+}
+
+fn drops() -> usize {
+    DROPS.with(|d| d.get())
+}
+
+#[derive(Facet)]
+struct Tracked {
+    id: u64,
+}
+
+impl Drop for Tracked {
+    fn drop(&mut self) {
+        DROPS.with(|d| d.set(d.get() + 1));
+    }
+}
+
+#[derive(Facet)]
+struct Limits {
+    max_connections: u64,
+}
+
+#[derive(Facet)]
+struct Server {
+    name: String,
+    owner: Tracked,
+    limits: Limits,
+    tags: Vec<String>,
+    env: HashMap<String, String>,
+}
+
+fn server() -> Server {
+    Server {
+        name: "alpha".to_string(),
+        owner: Tracked { id: 1 },
+        limits: Limits {
+            max_connections: 10,
+        },
+        tags: vec!["a".to_string(), "b".to_string(), "c".to_string()],
+        env: HashMap::from([("HOME".to_string(), "/root".to_string())]),
+    }
+}
+
+#[test]
+fn set_field_drops_the_previous_value() {
+    let mut server = server();
+    let before = drops();
+
+    let mut ps = PeekMut::new(&mut server).into_struct();
+    ps.set_field("owner", Tracked { id: 2 }).unwrap();
+    assert_eq!(drops() - before, 1);
+    ps.set_field("name", "beta".to_string()).unwrap();
+
+    assert_eq!(server.owner.id, 2);
+    assert_eq!(server.name, "beta");
+    assert_eq!(drops() - before, 1);
+}
+
+#[test]
+fn nested_fields_are_reachable() {
+    let mut server = server();
+
+    let mut ps = PeekMut::new(&mut server).into_struct();
+    ps.field_mut("limits")
+        .unwrap()
+        .into_struct()
+        .set_field("max_connections", 64u64)
+        .unwrap();
+    ps.field_mut_at(0)
+        .unwrap()
+        .replace("gamma".to_string())
+        .unwrap();
+
+    assert_eq!(server.limits.max_connections, 64);
+    assert_eq!(server.name, "gamma");
+}
+
+#[test]
+fn field_errors() {
+    let mut server = server();
+    let mut ps = PeekMut::new(&mut server).into_struct();
+
+    assert_eq!(
+        ps.set_field("nope", 1u64).err(),
+        Some(PeekMutError::NoSuchField)
+    );
+    assert_eq!(
+        ps.set_field("name", 1u64).err(),
+        Some(PeekMutError::TypeMismatch {
+            expected: String::SHAPE,
+            actual: u64::SHAPE,
+        })
+    );
+    assert!(matches!(
+        ps.field_mut_at(42).err(),
+        Some(PeekMutError::IndexOutOfBounds { index: 42, len: 5 })
+    ));
+}
+
+#[test]
+fn list_operations() {
+    let mut server = server();
+
+    {
+        let mut ps = PeekMut::new(&mut server).into_struct();
+        let mut tags = ps.field_mut("tags").unwrap().into_list();
+        assert_eq!(tags.len(), 3);
+
+        tags.push("d".to_string()).unwrap();
+        tags.item_mut(0).unwrap().replace("A".to_string()).unwrap();
+        assert_eq!(tags.pop::<String>().unwrap().as_deref(), Some("d"));
+        assert_eq!(tags.remove::<String>(1).unwrap(), "b");
+        assert_eq!(
+            tags.remove::<String>(5).err(),
+            Some(PeekMutError::IndexOutOfBounds { index: 5, len: 2 })
+        );
+        assert!(tags.push(1u64).is_err());
+        assert!(tags.item_mut(2).is_err());
+    }
+    assert_eq!(server.tags, ["A", "c"]);
+
+    let mut ps = PeekMut::new(&mut server).into_struct();
+    let mut tags = ps.field_mut("tags").unwrap().into_list();
+    tags.clear().unwrap();
+    assert!(tags.is_empty());
+    assert_eq!(tags.pop::<String>().unwrap(), None);
+}
+
+#[test]
+fn list_items_can_be_dropped_in_place() {
+    let mut items = vec![Tracked { id: 1 }, Tracked { id: 2 }, Tracked { id: 3 }];
+    let before = drops();
+
+    let mut pl = PeekMut::new(&mut items).into_list();
+    pl.item_mut(1)
+        .unwrap()
+        .into_struct()
+        .set_field("id", 20u64)
+        .unwrap();
+    pl.item_mut(0).unwrap().replace(Tracked { id: 10 }).unwrap();
+    assert_eq!(drops() - before, 1);
+    pl.clear().unwrap();
+    assert_eq!(drops() - before, 4);
+}
+
+#[test]
+fn truncate_and_reserve() {
+    let mut items = vec![Tracked { id: 1 }, Tracked { id: 2 }, Tracked { id: 3 }];
+    let before = drops();
+
+    let mut pl = PeekMut::new(&mut items).into_list();
+    pl.reserve(100).unwrap();
+    pl.truncate(5).unwrap();
+    assert_eq!(drops() - before, 0);
+    pl.truncate(1).unwrap();
+    assert_eq!(drops() - before, 2);
+    assert_eq!(pl.len(), 1);
+
+    assert!(items.capacity() >= 100);
+    assert_eq!(items[0].id, 1);
+}
+
+#[test]
+fn fixed_size_lists_cannot_shrink() {
+    let mut array = [3u64];
+    let mut pinned = PeekMut::new(&mut array).into_list();
+    pinned.item_mut(0).unwrap().replace(4u64).unwrap();
+    assert!(pinned.item_mut(1).is_err());
+
+    assert!(matches!(
+        pinned.pop::<u64>(),
+        Err(PeekMutError::Unsupported {
+            operation: "pop",
+            ..
+        })
+    ));
+    assert!(matches!(
+        pinned.clear(),
+        Err(PeekMutError::Unsupported {
+            operation: "clear",
+            ..
+        })
+    ));
+    assert!(pinned.truncate(0).is_err());
+    assert!(pinned.reserve(1).is_err());
+    assert_eq!(array, [4]);
+}
+
+#[test]
+fn shared_slices_are_read_only() {
+    let items = [1u64, 2];
+    let mut slice: &[u64] = &items;
+    let mut pl = PeekMut::new(&mut slice).into_list();
+    assert_eq!(pl.len(), 2);
+    assert!(matches!(
+        pl.item_mut(0),
+        Err(PeekMutError::Unsupported {
+            operation: "get_item_mut",
+            ..
+        })
+    ));
+    assert!(pl.pop::<u64>().is_err());
+    assert!(pl.clear().is_err());
+    assert!(pl.truncate(0).is_err());
+    assert!(pl.reserve(1).is_err());
+    assert_eq!(slice, [1, 2]);
+}
+
+/// A hasher with state, so a `HashMap` using it doesn't have the layout of one
+/// using `RandomState`
+#[derive(Clone, Default, Facet)]
+struct Seeded {
+    seed: u64,
+    salt: u64,
+}
+
+impl std::hash::BuildHasher for Seeded {
+    type Hasher = std::hash::DefaultHasher;
+
+    fn build_hasher(&self) -> Self::Hasher {
+        use std::hash::Hasher;
+        let mut hasher = std::hash::DefaultHasher::new();
+        hasher.write_u64(self.seed ^ self.salt);
+        hasher
+    }
+}
+
+#[test]
+fn maps_with_a_custom_hasher() {
+    let mut map = HashMap::with_hasher(Seeded { seed: 7, salt: 9 });
+    map.insert(1u64, Tracked { id: 1 });
+    let before = drops();
+
+    let mut pm = PeekMut::new(&mut map).into_map();
+    pm.insert(2u64, Tracked { id: 2 }).unwrap();
+    assert_eq!(pm.len(), 2);
+    assert!(pm.contains_key(&1u64).unwrap());
+    pm.get_mut(&1u64)
+        .unwrap()
+        .unwrap()
+        .replace(Tracked { id: 10 })
+        .unwrap();
+    assert_eq!(drops() - before, 1);
+    assert!(pm.remove(&2u64).unwrap());
+    assert_eq!(drops() - before, 2);
+    assert_eq!(map[&1].id, 10);
+    assert_eq!(map.hasher().seed, 7);
+
+    let mut pm = PeekMut::new(&mut map).into_map();
+    pm.clear().unwrap();
+    assert_eq!(drops() - before, 3);
+    assert!(map.is_empty());
+}
+
+#[test]
+fn map_operations() {
+    let mut server = server();
+
+    {
+        let mut ps = PeekMut::new(&mut server).into_struct();
+        let mut env = ps.field_mut("env").unwrap().into_map();
+        assert_eq!(env.len(), 1);
+
+        env.insert("USER".to_string(), "root".to_string()).unwrap();
+        env.insert("HOME".to_string(), "/home/root".to_string())
+            .unwrap();
+        assert_eq!(env.len(), 2);
+        assert!(env.contains_key(&"USER".to_string()).unwrap());
+
+        env.get_mut(&"USER".to_string())
+            .unwrap()
+            .unwrap()
+            .replace("admin".to_string())
+            .unwrap();
+        assert!(env.get_mut(&"SHELL".to_string()).unwrap().is_none());
+
+        assert!(env.insert(1u64, "x".to_string()).is_err());
+        assert!(env.contains_key(&1u64).is_err());
+    }
+    assert_eq!(server.env["HOME"], "/home/root");
+    assert_eq!(server.env["USER"], "admin");
+
+    let mut ps = PeekMut::new(&mut server).into_struct();
+    let mut env = ps.field_mut("env").unwrap().into_map();
+    assert!(env.remove(&"HOME".to_string()).unwrap());
+    assert!(!env.remove(&"HOME".to_string()).unwrap());
+    assert_eq!(env.len(), 1);
+    env.clear().unwrap();
+    assert!(env.is_empty());
+}
+
+#[test]
+fn map_values_are_dropped_on_remove() {
+    let mut map = HashMap::from([(1u64, Tracked { id: 1 }), (2u64, Tracked { id: 2 })]);
+    let before = drops();
+
+    let mut pm = PeekMut::new(&mut map).into_map();
+    pm.insert(1u64, Tracked { id: 10 }).unwrap();
+    assert_eq!(drops() - before, 1);
+    assert!(pm.remove(&2u64).unwrap());
+    assert_eq!(drops() - before, 2);
+    pm.clear().unwrap();
+    assert_eq!(drops() - before, 3);
+    assert!(map.is_empty());
+}
+
+#[test]
+fn enum_fields_of_the_current_variant() {
+    let mut source = Source::Remote {
+        url: "https://example.com".to_string(),
+        retries: 3,
+    };
+
+    let mut pe = PeekMut::new(&mut source).into_enum();
+    assert_eq!(pe.variant().name, "Remote");
+    pe.set_field("retries", 5u64).unwrap();
+    assert_eq!(
+        pe.set_field("0", 5u64).err(),
+        Some(PeekMutError::NoSuchField)
+    );
+    assert_eq!(
+        source,
+        Source::Remote {
+            url: "https://example.com".to_string(),
+            retries: 5
+        }
+    );
+}
+
+#[test]
+fn switch_enum_variant() {
+    let mut source = Source::File("/etc/app.toml".to_string());
+
+    let mut pe = PeekMut::new(&mut source).into_enum();
+    assert_eq!(pe.variant_index(), 1);
+
+    let mut switch = pe.switch_variant("Remote").unwrap();
+    switch
+        .set_field("url", "https://example.com".to_string())
+        .unwrap();
+    assert_eq!(
+        switch.set_field("retries", "3".to_string()).err(),
+        Some(PeekMutError::TypeMismatch {
+            expected: u64::SHAPE,
+            actual: String::SHAPE,
+        })
+    );
+    switch.set_field("retries", 3u64).unwrap();
+    switch.commit().unwrap();
+    assert_eq!(pe.variant().name, "Remote");
+
+    pe.switch_variant("Disabled").unwrap().commit().unwrap();
+    assert_eq!(source, Source::Disabled);
+
+    let mut pe = PeekMut::new(&mut source).into_enum();
+    assert_eq!(
+        pe.switch_variant("Missing").err(),
+        Some(PeekMutError::NoSuchVariant)
+    );
+    let mut switch = pe.switch_variant("File").unwrap();
+    switch.set_field("0", "a".to_string()).unwrap();
+    switch.set_field("0", "b".to_string()).unwrap();
+    switch.commit().unwrap();
+    assert_eq!(source, Source::File("b".to_string()));
+}
+
+#[test]
+fn incomplete_or_abandoned_switch_leaves_the_enum_untouched() {
+    let mut source = Source::File("/etc/app.toml".to_string());
+
+    let mut pe = PeekMut::new(&mut source).into_enum();
+    let mut switch = pe.switch_variant("Remote").unwrap();
+    switch
+        .set_field("url", "https://example.com".to_string())
+        .unwrap();
+    assert_eq!(
+        switch.commit().err(),
+        Some(PeekMutError::MissingField("retries"))
+    );
+
+    let mut switch = pe.switch_variant("Remote").unwrap();
+    switch
+        .set_field("url", "https://example.com".to_string())
+        .unwrap();
+    drop(switch);
+
+    assert_eq!(source, Source::File("/etc/app.toml".to_string()));
+}
+
+#[derive(Facet)]
+struct Profile {
+    nickname: Option<String>,
+    badge: Option<Tracked>,
+}
+
+#[test]
+fn option_fields() {
+    let mut profile = Profile {
+        nickname: None,
+        badge: Some(Tracked { id: 3 }),
+    };
+    let before = drops();
+
+    let mut ps = PeekMut::new(&mut profile).into_struct();
+    ps.set_field("nickname", Some("ace".to_string())).unwrap();
+
+    let mut badge = ps.field_mut("badge").unwrap().into_option();
+    assert!(badge.is_some());
+    badge
+        .value_mut()
+        .unwrap()
+        .into_struct()
+        .set_field("id", 4u64)
+        .unwrap();
+    assert_eq!(
+        badge.set_some(4u64),
+        Err(PeekMutError::TypeMismatch {
+            expected: Tracked::SHAPE,
+            actual: u64::SHAPE,
+        })
+    );
+    badge.set_none();
+    assert!(badge.is_none());
+    assert!(badge.value_mut().is_none());
+    assert_eq!(drops() - before, 1);
+    badge.set_some(Tracked { id: 5 }).unwrap();
+
+    let mut nickname = ps.field_mut("nickname").unwrap().into_option();
+    let PeekMut::Value(mut inner) = nickname.value_mut().unwrap() else {
+        panic!("expected a value");
+    };
+    inner.replace("deuce".to_string()).unwrap();
+
+    assert_eq!(profile.nickname.as_deref(), Some("deuce"));
+    assert_eq!(profile.badge.as_ref().map(|b| b.id), Some(5));
+    assert_eq!(drops() - before, 1);
+}
@@ -878,3 +878,23 @@ fn build_u64_properly() {
     // Verify the value was set correctly
     assert_eq!(value, 42);
 }
+
+#[test]
+fn integer_parse_errors_point_at_the_offending_byte() {
+    fn parse_error<T: Facet>(s: &str) -> facet_trait::ParseError {
+        let (poke, _guard) = Poke::alloc::<T>();
+        match poke.into_scalar().parse(s) {
+            Ok(_) => panic!("{s:?} parsed"),
+            Err((_, err)) => err,
+        }
+    }
+
+    let err = parse_error::<u32>("12x");
+    assert_eq!(err.offset, Some(2));
+    assert_eq!(err.shape, Some(u32::SHAPE));
+    assert_eq!(parse_error::<i16>("-4.5").offset, Some(2));
+    assert_eq!(parse_error::<u8>("-1").offset, Some(0));
+    // nothing to point at
+    assert_eq!(parse_error::<u8>("256").offset, None);
+    assert_eq!(parse_error::<i64>("").offset, None);
+}
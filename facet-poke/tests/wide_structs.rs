@@ -0,0 +1,213 @@
+use core::alloc::Layout;
+use core::mem::MaybeUninit;
+
+use facet_peek::Peek;
+use facet_poke::{ISet, Poke};
+use facet_trait::{
+    Def, DynamicShapeBuilder, EnumDef, EnumRepr, Facet, Field, FieldFlags, OpaqueConst,
+    OpaqueUninit, Shape, ValueVTable, Variant, VariantKind,
+};
+
+/// Builds a struct shape with `count` `u32` fields named `f0`, `f1`, ...
+fn wide_shape(count: usize) -> &'static Shape {
+    let names: Vec<String> = (0..count).map(|i| format!("f{i}")).collect();
+    let mut builder = DynamicShapeBuilder::new().struct_();
+    for name in &names {
+        builder = builder.field(name, u32::SHAPE);
+    }
+    builder.build().unwrap()
+}
+
+/// Fills every field of a `count`-wide struct (skipping `skip`, if any), then builds it.
+fn fill_wide_struct(count: usize, skip: Option<usize>) -> Vec<u32> {
+    let shape = wide_shape(count);
+    assert_eq!(shape.layout.size(), count * 4);
+
+    let mut buffer = vec![0u32; count];
+    let data = OpaqueUninit::new(buffer.as_mut_ptr());
+    let Poke::Struct(mut ps) = (unsafe { Poke::unchecked_new(data, shape) }) else {
+        panic!("expected a struct poke");
+    };
+
+    for i in (0..count).filter(|&i| Some(i) != skip) {
+        let value = i as u32 * 10;
+        ps.set(i, OpaqueConst::from_ref(&value)).unwrap();
+    }
+    let opaque = ps.build_in_place();
+
+    let Peek::Struct(peek) = (unsafe { Peek::unchecked_new(opaque.as_const(), shape) }) else {
+        panic!("expected a struct peek");
+    };
+    assert_eq!(peek.field_count(), count);
+    assert_eq!(
+        format!("{}", peek.get_field(&format!("f{}", count - 1)).unwrap()),
+        format!("{}", (count - 1) * 10)
+    );
+
+    buffer
+}
+
+#[test]
+fn wide_struct_64_fields() {
+    let values = fill_wide_struct(64, None);
+    assert_eq!(values[63], 630);
+}
+
+#[test]
+fn wide_struct_65_fields() {
+    let values = fill_wide_struct(65, None);
+    assert_eq!(values[64], 640);
+}
+
+#[test]
+fn wide_struct_500_fields() {
+    let values = fill_wide_struct(500, None);
+    assert!(values.iter().enumerate().all(|(i, &v)| v == i as u32 * 10));
+}
+
+#[test]
+#[should_panic(expected = "Field 'f499' was not initialized")]
+fn wide_struct_500_fields_missing_last() {
+    fill_wide_struct(500, Some(499));
+}
+
+#[test]
+#[should_panic(expected = "Field 'f64' was not initialized")]
+fn wide_struct_65_fields_missing_spilled() {
+    fill_wide_struct(65, Some(64));
+}
+
+#[test]
+fn iset_boundaries() {
+    for count in [0, 1, 63, 64, 65, 128, 500] {
+        let mut iset = ISet::new(count);
+        assert_eq!(iset.capacity(), count);
+        assert!(iset.all_set(0));
+        for i in 0..count {
+            assert!(!iset.all_set(i + 1));
+            iset.set(i);
+            assert!(iset.has(i));
+            assert!(iset.all_set(i + 1));
+        }
+        if count > 0 {
+            iset.unset(count / 2);
+            assert!(!iset.has(count / 2));
+            assert!(!iset.all_set(count));
+            assert!(iset.all_set(count / 2));
+        }
+    }
+}
+
+#[test]
+#[should_panic(expected = "ISet tracks 65 fields. Index 65 is out of bounds.")]
+fn iset_out_of_bounds() {
+    ISet::new(65).set(65);
+}
+
+/// An enum whose second variant has more fields than fit in a single word of an `ISet`
+// only ever built through reflection, and boxing `Many` would defeat the point
+#[allow(dead_code, clippy::large_enum_variant)]
+#[repr(u8)]
+enum Wide {
+    Empty,
+    Many([u32; 70]),
+}
+
+#[repr(C)]
+struct ManyLayout {
+    _tag: u8,
+    values: [u32; 70],
+}
+
+/// Describes `Wide::Many` as a tuple variant with 70 `u32` fields, one per array element
+fn wide_enum_shape() -> &'static Shape {
+    static VTABLE: ValueVTable = ValueVTable::builder()
+        .type_name(|f, _opts| write!(f, "Wide"))
+        .build();
+
+    let base = core::mem::offset_of!(ManyLayout, values);
+    let fields: Vec<Field> = (0..70)
+        .map(|i: usize| {
+            Field::builder()
+                .name(Box::leak(i.to_string().into_boxed_str()))
+                .shape(u32::SHAPE)
+                .offset(base + i * 4)
+                .flags(FieldFlags::EMPTY)
+                .build()
+        })
+        .collect();
+    let variants = vec![
+        Variant::builder()
+            .name("Empty")
+            .discriminant(Some(0))
+            .kind(VariantKind::Unit)
+            .build(),
+        Variant::builder()
+            .name("Many")
+            .discriminant(Some(1))
+            .kind(VariantKind::Tuple {
+                fields: Box::leak(fields.into_boxed_slice()),
+            })
+            .build(),
+    ];
+    let def = EnumDef::builder()
+        .repr(EnumRepr::U8)
+        .variants(Box::leak(variants.into_boxed_slice()))
+        .build();
+    Box::leak(Box::new(
+        Shape::builder()
+            .layout(Layout::new::<Wide>())
+            .vtable(&VTABLE)
+            .def(Def::Enum(def))
+            .build(),
+    ))
+}
+
+/// Selects `Wide::Many` and fills every field but `skip`, in reverse order
+fn fill_wide_variant(
+    slot: &mut MaybeUninit<Wide>,
+    skip: Option<usize>,
+) -> facet_poke::PokeEnum<'_> {
+    let data = OpaqueUninit::new(slot.as_mut_ptr());
+    let Poke::Enum(pe) = (unsafe { Poke::unchecked_new(data, wide_enum_shape()) }) else {
+        panic!("expected an enum poke");
+    };
+    let mut pe = pe.set_variant_by_name("Many").unwrap();
+    for i in (0..70).rev().filter(|&i| Some(i) != skip) {
+        let Poke::Scalar(pv) = pe.variant_field(i).unwrap() else {
+            panic!("expected a scalar poke");
+        };
+        unsafe {
+            pv.put(OpaqueConst::from_ref(&(i as u32 * 10)));
+            pe.mark_field_as_initialized(i);
+        }
+    }
+    pe
+}
+
+#[test]
+fn wide_variant_70_fields() {
+    let mut slot = MaybeUninit::uninit();
+    let pe = fill_wide_variant(&mut slot, None);
+    assert!(pe.first_uninitialized_field().is_none());
+    pe.build_in_place();
+
+    let Wide::Many(values) = (unsafe { slot.assume_init() }) else {
+        panic!("expected the Many variant");
+    };
+    assert!(values.iter().enumerate().all(|(i, &v)| v == i as u32 * 10));
+}
+
+#[test]
+fn wide_variant_missing_spilled_field() {
+    let mut slot = MaybeUninit::uninit();
+    let pe = fill_wide_variant(&mut slot, Some(66));
+    assert_eq!(pe.first_uninitialized_field().unwrap().name, "66");
+}
+
+#[test]
+#[should_panic(expected = "Field '69' of variant 'Many' was not initialized")]
+fn wide_variant_missing_last_field() {
+    let mut slot = MaybeUninit::uninit();
+    fill_wide_variant(&mut slot, Some(69)).build_in_place();
+}
@@ -0,0 +1,114 @@
+use std::collections::HashMap;
+
+use facet_derive::Facet;
+use facet_poke::Poke;
+use facet_trait::{Facet, Opaque, OpaqueConst, StructKind};
+
+use facet_trait as facet;
+
+#[derive(Debug, PartialEq, Facet)]
+struct Unit;
+
+#[derive(Debug, PartialEq, Facet)]
+struct Empty {}
+
+#[derive(Debug, PartialEq, Facet)]
+struct Holder {
+    unit: Unit,
+    nothing: (),
+    value: u64,
+}
+
+#[test]
+fn unit_struct_shape() {
+    let facet::Def::Struct(def) = Unit::SHAPE.def else {
+        panic!("expected a struct def");
+    };
+    assert_eq!(def.kind, StructKind::Unit);
+    assert!(def.fields.is_empty());
+    assert_eq!(Unit::SHAPE.layout.size(), 0);
+}
+
+#[test]
+fn zst_allocations_are_dangling_and_aligned() {
+    for shape in [<()>::SHAPE, Unit::SHAPE, Empty::SHAPE] {
+        let data = shape.allocate();
+        assert!(!data.as_ptr().is_null());
+        assert_eq!(data.as_ptr() as usize % shape.layout.align(), 0);
+        unsafe { shape.deallocate(data) };
+    }
+}
+
+#[test]
+fn build_zero_sized_structs() {
+    let (poke, guard) = Poke::alloc::<Unit>();
+    assert_eq!(poke.into_struct().build::<Unit>(Some(guard)), Unit);
+
+    let (poke, guard) = Poke::alloc::<Empty>();
+    assert_eq!(poke.into_struct().build::<Empty>(Some(guard)), Empty {});
+
+    let (poke, _guard) = Poke::alloc::<Unit>();
+    assert_eq!(*poke.into_struct().build_boxed::<Unit>(), Unit);
+}
+
+#[test]
+fn build_unit_scalar() {
+    let (poke, guard) = Poke::alloc::<()>();
+    let opaque = unsafe { poke.into_scalar().put(OpaqueConst::from_ref(&())) };
+    let () = unsafe { opaque.read::<()>() };
+    drop(guard);
+}
+
+#[test]
+fn build_struct_with_zst_fields() {
+    let (poke, guard) = Poke::alloc::<Holder>();
+    let mut ps = poke.into_struct();
+    ps.set_by_name("unit", OpaqueConst::from_ref(&Unit))
+        .unwrap();
+    ps.set_by_name("nothing", OpaqueConst::from_ref(&()))
+        .unwrap();
+    ps.set_by_name("value", OpaqueConst::from_ref(&7u64))
+        .unwrap();
+    assert_eq!(
+        ps.build::<Holder>(Some(guard)),
+        Holder {
+            unit: Unit,
+            nothing: (),
+            value: 7
+        }
+    );
+}
+
+#[test]
+fn zsts_in_lists() {
+    let (poke, guard) = Poke::alloc::<Vec<Unit>>();
+    let mut pl = poke.into_list().init(None).ok().unwrap();
+    for _ in 0..3 {
+        let (item, item_guard) = Poke::alloc::<Unit>();
+        let item = item.into_struct().build_in_place();
+        unsafe { pl.push(item) };
+        drop(item_guard);
+    }
+    assert_eq!(pl.len(), 3);
+    let list = unsafe { pl.build_in_place().read::<Vec<Unit>>() };
+    drop(guard);
+    assert_eq!(list, [Unit, Unit, Unit]);
+}
+
+#[test]
+fn zsts_in_maps() {
+    let (poke, guard) = Poke::alloc::<HashMap<String, ()>>();
+    let mut pm = poke.into_map().init(None).ok().unwrap();
+    for key in ["a", "b"] {
+        let mut key = key.to_string();
+        let (value, value_guard) = Poke::alloc::<()>();
+        let value = unsafe { value.into_scalar().put(OpaqueConst::from_ref(&())) };
+        unsafe { pm.insert(Opaque::from_ref(&mut key), value) };
+        core::mem::forget(key);
+        drop(value_guard);
+    }
+    let map = unsafe { pm.build_in_place().read::<HashMap<String, ()>>() };
+    drop(guard);
+    assert_eq!(map.len(), 2);
+    assert!(map.contains_key("a") && map.contains_key("b"));
+}
@@ -0,0 +1,91 @@
+//! Rendering of structural diffs
+
+use std::fmt::{self, Write};
+
+use facet_diff::{Change, ChangeKind, diff};
+use facet_peek::Peek;
+use facet_trait::Facet;
+
+use crate::{ansi, printer::PrettyPrinter};
+
+impl PrettyPrinter {
+    /// Formats the differences between two values: one entry per change, with its path,
+    /// followed by the old value on a `-` line and the new value on a `+` line
+    pub fn format_diff<T: Facet>(&self, old: &T, new: &T) -> String {
+        self.format_changes(&diff(Peek::new(old), Peek::new(new)))
+    }
+
+    /// Formats changes as returned by [`facet_diff::diff`]
+    pub fn format_changes(&self, changes: &[Change<'_>]) -> String {
+        let mut output = String::new();
+        for change in changes {
+            self.write_change(&mut output, change)
+                .expect("Formatting failed");
+        }
+        output
+    }
+
+    fn write_change(&self, f: &mut String, change: &Change<'_>) -> fmt::Result {
+        if self.use_colors {
+            ansi::write_bold(f)?;
+        }
+        if change.path.is_root() {
+            write!(f, "(root)")?;
+        } else {
+            write!(f, "{}", change.path)?;
+        }
+        if self.use_colors {
+            ansi::write_reset(f)?;
+        }
+        writeln!(f)?;
+
+        match change.kind {
+            ChangeKind::Modified { old, new } => {
+                self.write_side(f, Side::Removed, old, change.sensitive)?;
+                self.write_side(f, Side::Added, new, change.sensitive)
+            }
+            ChangeKind::Added(new) => self.write_side(f, Side::Added, new, change.sensitive),
+            ChangeKind::Removed(old) => self.write_side(f, Side::Removed, old, change.sensitive),
+        }
+    }
+
+    /// Writes a value, prefixing each of its lines with a colored `-` or `+`
+    fn write_side(
+        &self,
+        f: &mut String,
+        side: Side,
+        peek: Peek<'_>,
+        sensitive: bool,
+    ) -> fmt::Result {
+        let value = if sensitive {
+            let mut redacted = String::new();
+            self.write_redacted(&mut redacted, "[REDACTED]")?;
+            redacted
+        } else {
+            self.format_peek(peek)
+        };
+
+        let (marker, (r, g, b)) = match side {
+            Side::Removed => ("-", (224, 49, 49)),
+            Side::Added => ("+", (64, 192, 87)),
+        };
+        for line in value.lines() {
+            write!(f, "{:indent$}", "", indent = self.indent_size)?;
+            if self.use_colors {
+                ansi::write_rgb(f, r, g, b)?;
+                write!(f, "{marker}")?;
+                ansi::write_reset(f)?;
+            } else {
+                write!(f, "{marker}")?;
+            }
+            writeln!(f, " {line}")?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Side {
+    Removed,
+    Added,
+}
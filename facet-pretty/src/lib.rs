@@ -3,6 +3,7 @@
 
 mod ansi;
 mod color;
+mod diff;
 mod display;
 mod printer;
 
@@ -1,23 +1,24 @@
 //! Pretty printer implementation for Facet types
 
 use std::{
-    collections::{HashMap, VecDeque},
     fmt::{self, Write},
     hash::{DefaultHasher, Hash, Hasher},
     str,
 };
 
-use facet_peek::Peek;
-use facet_trait::Facet;
+use facet_peek::{
+    Path, Peek, PeekEnum, PeekList, PeekMap, PeekOption, PeekStruct, PeekValue, VisitFlow, Visitor,
+};
+use facet_trait::{Facet, Field, FieldFlags};
 
 use crate::{ansi, color::ColorGenerator};
 
 /// A formatter for pretty-printing Facet types
 pub struct PrettyPrinter {
-    indent_size: usize,
+    pub(crate) indent_size: usize,
     max_depth: Option<usize>,
     color_generator: ColorGenerator,
-    use_colors: bool,
+    pub(crate) use_colors: bool,
 }
 
 impl Default for PrettyPrinter {
@@ -31,23 +32,6 @@ impl Default for PrettyPrinter {
     }
 }
 
-/// Stack state for iterative formatting
-enum StackState {
-    Start,
-    ProcessStructField { field_index: usize },
-    ProcessListItem { item_index: usize },
-    ProcessMapEntry,
-    Finish,
-}
-
-/// Stack item for iterative traversal
-struct StackItem<'a> {
-    peek: Peek<'a>,
-    format_depth: usize,
-    type_depth: usize,
-    state: StackState,
-}
-
 impl PrettyPrinter {
     /// Create a new PrettyPrinter with default settings
     pub fn new() -> Self {
@@ -83,7 +67,7 @@ impl PrettyPrinter {
         let peek = Peek::new(value);
 
         let mut output = String::new();
-        self.format_peek_internal(peek, &mut output, 0, 0, &mut HashMap::new())
+        self.write_peek(peek, &mut output)
             .expect("Formatting failed");
 
         output
@@ -92,302 +76,40 @@ impl PrettyPrinter {
     /// Format a value to a formatter
     pub fn format_to<T: Facet>(&self, value: &T, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let peek = Peek::new(value);
-        self.format_peek_internal(peek, f, 0, 0, &mut HashMap::new())
+        self.write_peek(peek, f)
     }
 
     /// Format a Peek value to a string
     pub fn format_peek(&self, peek: Peek<'_>) -> String {
         let mut output = String::new();
-        self.format_peek_internal(peek, &mut output, 0, 0, &mut HashMap::new())
+        self.write_peek(peek, &mut output)
             .expect("Formatting failed");
         output
     }
 
-    /// Internal method to format a Peek value
-    pub(crate) fn format_peek_internal(
-        &self,
-        peek: Peek<'_>,
-        f: &mut impl Write,
-        format_depth: usize,
-        type_depth: usize,
-        visited: &mut HashMap<*const (), usize>,
-    ) -> fmt::Result {
-        // Create a queue for our stack items
-        let mut stack = VecDeque::new();
-
-        // Push the initial item
-        stack.push_back(StackItem {
-            peek,
-            format_depth,
-            type_depth,
-            state: StackState::Start,
-        });
-
-        // Process items until the stack is empty
-        while let Some(mut item) = stack.pop_back() {
-            match item.state {
-                StackState::Start => {
-                    // Check if we've reached the maximum depth
-                    if let Some(max_depth) = self.max_depth {
-                        if item.format_depth > max_depth {
-                            self.write_punctuation(f, "[")?;
-                            write!(f, "...")?;
-                            continue;
-                        }
-                    }
+    /// Writes a Peek value, walking it with [`Peek::visit`]
+    fn write_peek(&self, peek: Peek<'_>, f: &mut impl Write) -> fmt::Result {
+        peek.visit(&mut PeekWriter {
+            printer: self,
+            f,
+            depth: 0,
+        })
+    }
 
-                    // Get the data pointer for cycle detection
-                    let ptr = unsafe { item.peek.data().as_ptr() };
-
-                    // Check for cycles - if we've seen this pointer before at a different type_depth
-                    if let Some(&ptr_type_depth) = visited.get(&ptr) {
-                        // If the current type_depth is significantly deeper than when we first saw this pointer,
-                        // we have a true cycle, not just a transparent wrapper
-                        if item.type_depth > ptr_type_depth + 1 {
-                            self.write_type_name(f, &item.peek)?;
-                            self.write_punctuation(f, " { ")?;
-                            self.write_comment(
-                                f,
-                                &format!(
-                                    "/* cycle detected at {:p} (first seen at type_depth {}) */",
-                                    ptr, ptr_type_depth
-                                ),
-                            )?;
-                            self.write_punctuation(f, " }")?;
-                            continue;
-                        }
-                    } else {
-                        // First time seeing this pointer, record its type_depth
-                        visited.insert(ptr, item.type_depth);
-                    }
+    /// Writes the indentation of a line at `depth`
+    fn write_indent(&self, f: &mut impl Write, depth: usize) -> fmt::Result {
+        write!(f, "{:width$}", "", width = depth * self.indent_size)
+    }
 
-                    // Process based on the peek variant
-                    match item.peek {
-                        Peek::Value(value) => {
-                            self.format_value(value, f)?;
-                        }
-                        Peek::Struct(struct_) => {
-                            // When recursing into a struct, always increment format_depth
-                            // Only increment type_depth if we're moving to a different address
-                            let new_type_depth =
-                                if core::ptr::eq(unsafe { struct_.data().as_ptr() }, ptr) {
-                                    item.type_depth // Same pointer, don't increment type_depth
-                                } else {
-                                    item.type_depth + 1 // Different pointer, increment type_depth
-                                };
-
-                            // Print the struct name
-                            self.write_type_name(f, &struct_)?;
-                            self.write_punctuation(f, " {")?;
-
-                            if struct_.field_count() == 0 {
-                                self.write_punctuation(f, " }")?;
-                                continue;
-                            }
-
-                            writeln!(f)?;
-
-                            // Push back the item with the next state to continue processing fields
-                            item.state = StackState::ProcessStructField { field_index: 0 };
-                            item.format_depth += 1;
-                            item.type_depth = new_type_depth;
-                            stack.push_back(item);
-                        }
-                        Peek::List(list) => {
-                            // When recursing into a list, always increment format_depth
-                            // Only increment type_depth if we're moving to a different address
-                            let new_type_depth =
-                                if core::ptr::eq(unsafe { list.data().as_ptr() }, ptr) {
-                                    item.type_depth // Same pointer, don't increment type_depth
-                                } else {
-                                    item.type_depth + 1 // Different pointer, increment type_depth
-                                };
-
-                            // Print the list name
-                            self.write_type_name(f, &list)?;
-                            self.write_punctuation(f, " [")?;
-                            writeln!(f)?;
-
-                            // Push back the item with the next state to continue processing list items
-                            item.state = StackState::ProcessListItem { item_index: 0 };
-                            item.format_depth += 1;
-                            item.type_depth = new_type_depth;
-                            stack.push_back(item);
-                        }
-                        Peek::Map(map) => {
-                            // Print the map name
-                            self.write_type_name(f, &map)?;
-                            self.write_punctuation(f, " {")?;
-                            writeln!(f)?;
-
-                            // Push back the item with the next state to continue processing map
-                            item.state = StackState::ProcessMapEntry;
-                            item.format_depth += 1;
-                            // When recursing into a map, always increment format_depth
-                            // Only increment type_depth if we're moving to a different address
-                            item.type_depth = if core::ptr::eq(unsafe { map.data().as_ptr() }, ptr)
-                            {
-                                item.type_depth // Same pointer, don't increment type_depth
-                            } else {
-                                item.type_depth + 1 // Different pointer, increment type_depth
-                            };
-                            stack.push_back(item);
-                        }
-                        _ => {
-                            writeln!(f, "unsupported peek variant: {:?}", item.peek)?;
-                        }
-                    }
-                }
-                StackState::ProcessStructField { field_index } => {
-                    if let Peek::Struct(struct_) = item.peek {
-                        let fields: Vec<_> = struct_.fields_with_metadata().collect();
-
-                        if field_index >= fields.len() {
-                            // All fields processed, write closing brace
-                            write!(
-                                f,
-                                "{:width$}{}",
-                                "",
-                                self.style_punctuation("}"),
-                                width = (item.format_depth - 1) * self.indent_size
-                            )?;
-                            continue;
-                        }
-
-                        let (_, field_name, field_value, flags) = &fields[field_index];
-
-                        // Indent
-                        write!(
-                            f,
-                            "{:width$}",
-                            "",
-                            width = item.format_depth * self.indent_size
-                        )?;
-
-                        // Field name
-                        self.write_field_name(f, field_name)?;
-                        self.write_punctuation(f, ": ")?;
-
-                        // Check if field is sensitive
-                        if flags.contains(facet_trait::FieldFlags::SENSITIVE) {
-                            // Field value is sensitive, use write_redacted
-                            self.write_redacted(f, "[REDACTED]")?;
-                            self.write_punctuation(f, ",")?;
-                            writeln!(f)?;
-
-                            // Process next field
-                            item.state = StackState::ProcessStructField {
-                                field_index: field_index + 1,
-                            };
-                            stack.push_back(item);
-                        } else {
-                            // Field value is not sensitive, format normally
-                            // Push back current item to continue after formatting field value
-                            item.state = StackState::ProcessStructField {
-                                field_index: field_index + 1,
-                            };
-
-                            let finish_item = StackItem {
-                                peek: *field_value,
-                                format_depth: item.format_depth,
-                                type_depth: item.type_depth + 1,
-                                state: StackState::Finish,
-                            };
-                            let start_item = StackItem {
-                                peek: *field_value,
-                                format_depth: item.format_depth,
-                                type_depth: item.type_depth + 1,
-                                state: StackState::Start,
-                            };
-
-                            stack.push_back(item);
-                            stack.push_back(finish_item);
-                            stack.push_back(start_item);
-                        }
-                    }
-                }
-                StackState::ProcessListItem { item_index } => {
-                    if let Peek::List(list) = item.peek {
-                        if item_index >= list.len() {
-                            // All items processed, write closing bracket
-                            write!(
-                                f,
-                                "{:width$}",
-                                "",
-                                width = (item.format_depth - 1) * self.indent_size
-                            )?;
-                            self.write_punctuation(f, "]")?;
-                            continue;
-                        }
-
-                        // Indent
-                        write!(
-                            f,
-                            "{:width$}",
-                            "",
-                            width = item.format_depth * self.indent_size
-                        )?;
-
-                        // Push back current item to continue after formatting list item
-                        item.state = StackState::ProcessListItem {
-                            item_index: item_index + 1,
-                        };
-                        let next_format_depth = item.format_depth;
-                        let next_type_depth = item.type_depth + 1;
-                        stack.push_back(item);
-
-                        // Push list item to format first
-                        let list_item = list.iter().nth(item_index).unwrap();
-                        stack.push_back(StackItem {
-                            peek: list_item,
-                            format_depth: next_format_depth,
-                            type_depth: next_type_depth,
-                            state: StackState::Finish,
-                        });
-
-                        // When we push a list item to format, we need to process it from the beginning
-                        stack.push_back(StackItem {
-                            peek: list_item,
-                            format_depth: next_format_depth,
-                            type_depth: next_type_depth,
-                            state: StackState::Start, // Use Start state to properly process the item
-                        });
-                    }
-                }
-                StackState::ProcessMapEntry => {
-                    if let Peek::Map(_) = item.peek {
-                        // TODO: Implement proper map iteration when available in facet_peek
-
-                        // Indent
-                        write!(
-                            f,
-                            "{:width$}",
-                            "",
-                            width = item.format_depth * self.indent_size
-                        )?;
-                        write!(f, "{}", self.style_comment("/* Map contents */"))?;
-                        writeln!(f)?;
-
-                        // Closing brace with proper indentation
-                        write!(
-                            f,
-                            "{:width$}{}",
-                            "",
-                            self.style_punctuation("}"),
-                            width = (item.format_depth - 1) * self.indent_size
-                        )?;
-                    }
-                }
-                StackState::Finish => {
-                    // This state is reached after processing a field or list item
-                    // Add comma and newline for struct fields and list items
-                    self.write_punctuation(f, ",")?;
-                    writeln!(f)?;
-                }
-            }
+    /// Writes the placeholder of a value deeper than the maximum depth, and returns true,
+    /// if `depth` is too deep
+    fn write_elided(&self, f: &mut impl Write, depth: usize) -> Result<bool, fmt::Error> {
+        if self.max_depth.is_none_or(|max_depth| depth <= max_depth) {
+            return Ok(false);
         }
-
-        Ok(())
+        self.write_punctuation(f, "[")?;
+        write!(f, "...")?;
+        Ok(true)
     }
 
     /// Format a scalar value
@@ -484,13 +206,6 @@ impl PrettyPrinter {
         }
     }
 
-    /// Style punctuation and return it as a string
-    fn style_punctuation(&self, text: &str) -> String {
-        let mut result = String::new();
-        self.write_punctuation(&mut result, text).unwrap();
-        result
-    }
-
     /// Write styled comment to formatter
     fn write_comment<W: fmt::Write>(&self, f: &mut W, text: &str) -> fmt::Result {
         if self.use_colors {
@@ -502,15 +217,8 @@ impl PrettyPrinter {
         }
     }
 
-    /// Style a comment and return it as a string
-    fn style_comment(&self, text: &str) -> String {
-        let mut result = String::new();
-        self.write_comment(&mut result, text).unwrap();
-        result
-    }
-
     /// Write styled redacted value to formatter
-    fn write_redacted<W: fmt::Write>(&self, f: &mut W, text: &str) -> fmt::Result {
+    pub(crate) fn write_redacted<W: fmt::Write>(&self, f: &mut W, text: &str) -> fmt::Result {
         if self.use_colors {
             ansi::write_rgb(f, 224, 49, 49)?; // Use bright red for redacted values
             ansi::write_bold(f)?;
@@ -530,6 +238,160 @@ impl PrettyPrinter {
     }
 }
 
+/// Writes each node of a value as the traversal reaches it
+struct PeekWriter<'p, W> {
+    printer: &'p PrettyPrinter,
+    f: &'p mut W,
+    /// How many structs and lists are open, which is how deep their children are
+    depth: usize,
+}
+
+impl<'mem, W: Write> Visitor<'mem> for PeekWriter<'_, W> {
+    type Error = fmt::Error;
+
+    fn scalar(&mut self, _path: &Path, value: PeekValue<'mem>) -> fmt::Result {
+        self.printer.format_value(value, self.f)
+    }
+
+    fn enter_struct(
+        &mut self,
+        _path: &Path,
+        value: PeekStruct<'mem>,
+    ) -> Result<VisitFlow, fmt::Error> {
+        self.printer.write_type_name(self.f, &value)?;
+        self.printer.write_punctuation(self.f, " {")?;
+        if value.field_count() == 0 {
+            self.printer.write_punctuation(self.f, " }")?;
+            return Ok(VisitFlow::Skip);
+        }
+        writeln!(self.f)?;
+        self.depth += 1;
+        Ok(VisitFlow::Continue)
+    }
+
+    fn leave_struct(&mut self, _path: &Path, value: PeekStruct<'mem>) -> fmt::Result {
+        if value.field_count() == 0 {
+            return Ok(());
+        }
+        self.depth -= 1;
+        self.printer.write_indent(self.f, self.depth)?;
+        self.printer.write_punctuation(self.f, "}")
+    }
+
+    fn enter_field(
+        &mut self,
+        _path: &Path,
+        field: &'static Field,
+        _value: Peek<'mem>,
+    ) -> Result<VisitFlow, fmt::Error> {
+        self.printer.write_indent(self.f, self.depth)?;
+        self.printer.write_field_name(self.f, field.name)?;
+        self.printer.write_punctuation(self.f, ": ")?;
+        if field.flags.contains(FieldFlags::SENSITIVE) {
+            self.printer.write_redacted(self.f, "[REDACTED]")?;
+            return Ok(VisitFlow::Skip);
+        }
+        if self.printer.write_elided(self.f, self.depth)? {
+            return Ok(VisitFlow::Skip);
+        }
+        Ok(VisitFlow::Continue)
+    }
+
+    fn leave_field(
+        &mut self,
+        _path: &Path,
+        _field: &'static Field,
+        _value: Peek<'mem>,
+    ) -> fmt::Result {
+        self.printer.write_punctuation(self.f, ",")?;
+        writeln!(self.f)
+    }
+
+    fn enter_list(&mut self, _path: &Path, value: PeekList<'mem>) -> Result<VisitFlow, fmt::Error> {
+        self.printer.write_type_name(self.f, &value)?;
+        self.printer.write_punctuation(self.f, " [")?;
+        writeln!(self.f)?;
+        self.depth += 1;
+        Ok(VisitFlow::Continue)
+    }
+
+    fn leave_list(&mut self, _path: &Path, _value: PeekList<'mem>) -> fmt::Result {
+        self.depth -= 1;
+        self.printer.write_indent(self.f, self.depth)?;
+        self.printer.write_punctuation(self.f, "]")
+    }
+
+    fn enter_list_item(
+        &mut self,
+        _path: &Path,
+        _index: usize,
+        _item: Peek<'mem>,
+    ) -> Result<VisitFlow, fmt::Error> {
+        self.printer.write_indent(self.f, self.depth)?;
+        if self.printer.write_elided(self.f, self.depth)? {
+            return Ok(VisitFlow::Skip);
+        }
+        Ok(VisitFlow::Continue)
+    }
+
+    fn leave_list_item(&mut self, _path: &Path, _index: usize, _item: Peek<'mem>) -> fmt::Result {
+        self.printer.write_punctuation(self.f, ",")?;
+        writeln!(self.f)
+    }
+
+    fn enter_map(&mut self, _path: &Path, value: PeekMap<'mem>) -> Result<VisitFlow, fmt::Error> {
+        // TODO: print the entries
+        self.printer.write_type_name(self.f, &value)?;
+        self.printer.write_punctuation(self.f, " {")?;
+        writeln!(self.f)?;
+        self.printer.write_indent(self.f, self.depth + 1)?;
+        self.printer.write_comment(self.f, "/* Map contents */")?;
+        writeln!(self.f)?;
+        self.printer.write_indent(self.f, self.depth)?;
+        self.printer.write_punctuation(self.f, "}")?;
+        Ok(VisitFlow::Skip)
+    }
+
+    fn enter_variant(
+        &mut self,
+        _path: &Path,
+        value: PeekEnum<'mem>,
+    ) -> Result<VisitFlow, fmt::Error> {
+        writeln!(self.f, "unsupported peek variant: {:?}", Peek::Enum(value))?;
+        Ok(VisitFlow::Skip)
+    }
+
+    fn enter_option(
+        &mut self,
+        _path: &Path,
+        value: PeekOption<'mem>,
+    ) -> Result<VisitFlow, fmt::Error> {
+        if value.value().is_none() {
+            write!(self.f, "None")?;
+        } else {
+            write!(self.f, "Some")?;
+            self.printer.write_punctuation(self.f, "(")?;
+        }
+        Ok(VisitFlow::Continue)
+    }
+
+    fn leave_option(&mut self, _path: &Path, value: PeekOption<'mem>) -> fmt::Result {
+        if value.value().is_some() {
+            self.printer.write_punctuation(self.f, ")")?;
+        }
+        Ok(())
+    }
+
+    fn cycle(&mut self, _path: &Path, value: Peek<'mem>) -> fmt::Result {
+        let ptr = value.data().as_byte_ptr();
+        self.printer.write_type_name(self.f, &value)?;
+        self.printer.write_punctuation(self.f, " { ")?;
+        self.printer
+            .write_comment(self.f, &format!("/* cycle detected at {ptr:p} */"))?;
+        self.printer.write_punctuation(self.f, " }")
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
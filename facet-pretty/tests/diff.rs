@@ -0,0 +1,54 @@
+use facet_derive::Facet;
+use facet_pretty::PrettyPrinter;
+use facet_trait as facet;
+use facet_trait::Facet;
+
+#[derive(Debug, Facet)]
+struct Server {
+    host: String,
+    ports: Vec<u16>,
+    #[facet(sensitive)]
+    token: String,
+}
+
+fn server(ports: Vec<u16>, token: &str) -> Server {
+    Server {
+        host: "localhost".to_string(),
+        ports,
+        token: token.to_string(),
+    }
+}
+
+#[test]
+fn changes_are_listed_by_path() {
+    let old = server(vec![80, 443], "a");
+    let new = server(vec![80, 8443, 9000], "b");
+
+    let output = PrettyPrinter::new()
+        .with_colors(false)
+        .format_diff(&old, &new);
+    assert_eq!(
+        output,
+        "ports[1]\n  - 443\n  + 8443\n\
+         ports[2]\n  + 9000\n\
+         token\n  - [REDACTED]\n  + [REDACTED]\n"
+    );
+}
+
+#[test]
+fn equal_values_format_to_nothing() {
+    let output = PrettyPrinter::new()
+        .with_colors(false)
+        .format_diff(&server(vec![80], "a"), &server(vec![80], "a"));
+    assert_eq!(output, "");
+}
+
+#[test]
+fn colors_mark_old_and_new_values() {
+    let output = PrettyPrinter::new()
+        .with_colors(true)
+        .format_diff(&server(vec![80], "a"), &server(vec![81], "a"));
+    assert!(output.contains("\x1b["));
+    assert!(output.contains("80"));
+    assert!(output.contains("81"));
+}
@@ -0,0 +1,189 @@
+use facet_peek::Peek;
+use facet_pretty::PrettyPrinter;
+use facet_trait::{
+    Def, DynamicShapeBuilder, DynamicShapeError, Facet, OpaqueConst, ShapeArena, ShapeExt as _,
+};
+
+#[repr(C)]
+struct Point {
+    x: i32,
+    y: i32,
+}
+
+#[repr(C)]
+struct Record {
+    id: u32,
+    score: f64,
+    flag: bool,
+    origin: Point,
+}
+
+#[test]
+fn dynamic_struct_matches_repr_c() {
+    let builder = DynamicShapeBuilder::new();
+    let point = builder
+        .struct_()
+        .field("x", i32::SHAPE)
+        .field("y", i32::SHAPE)
+        .build()
+        .unwrap();
+    let record = builder
+        .struct_()
+        .field("id", u32::SHAPE)
+        .field("score", f64::SHAPE)
+        .field("flag", bool::SHAPE)
+        .field("origin", point)
+        .build()
+        .unwrap();
+
+    assert_eq!(record.layout, core::alloc::Layout::new::<Record>());
+    let Def::Struct(sd) = record.def else {
+        panic!("expected a struct def");
+    };
+    let offsets: Vec<_> = sd.fields.iter().map(|f| (f.name, f.offset)).collect();
+    assert_eq!(
+        offsets,
+        [
+            ("id", core::mem::offset_of!(Record, id)),
+            ("score", core::mem::offset_of!(Record, score)),
+            ("flag", core::mem::offset_of!(Record, flag)),
+            ("origin", core::mem::offset_of!(Record, origin)),
+        ]
+    );
+
+    let value = Record {
+        id: 7,
+        score: 1.5,
+        flag: true,
+        origin: Point { x: -3, y: 4 },
+    };
+    let peek = unsafe { Peek::unchecked_new(OpaqueConst::from_ref(&value), record) };
+    let Peek::Struct(ps) = peek else {
+        panic!("expected a struct peek");
+    };
+    let id = ps.get_field("id").unwrap();
+    assert!(id.shape().is_type::<u32>());
+    assert_eq!(format!("{}", id), "7");
+
+    let Peek::Struct(origin) = ps.get_field("origin").unwrap() else {
+        panic!("expected a struct peek");
+    };
+    assert_eq!(format!("{}", origin.get_field("x").unwrap()), "-3");
+
+    let output = PrettyPrinter::new().with_colors(false).format_peek(peek);
+    assert!(output.contains("score: 1.5"));
+    assert!(output.contains("y: 4"));
+}
+
+#[test]
+fn dynamic_struct_explicit_offsets() {
+    let buffer: [u32; 4] = [0, 10, 0, 20];
+    let shape = DynamicShapeBuilder::new()
+        .struct_()
+        .field_at("b", u32::SHAPE, 12)
+        .field_at("a", u32::SHAPE, 4)
+        .build()
+        .unwrap();
+    assert_eq!(shape.layout.size(), 16);
+
+    let peek = unsafe { Peek::unchecked_new(OpaqueConst::from_ref(&buffer), shape) };
+    let Peek::Struct(ps) = peek else {
+        panic!("expected a struct peek");
+    };
+    assert_eq!(format!("{}", ps.get_field("a").unwrap()), "10");
+    assert_eq!(format!("{}", ps.get_field("b").unwrap()), "20");
+}
+
+#[test]
+fn dynamic_struct_errors() {
+    let builder = DynamicShapeBuilder::new();
+
+    let err = builder
+        .struct_()
+        .field("a", u8::SHAPE)
+        .field("a", u8::SHAPE)
+        .build()
+        .unwrap_err();
+    assert_eq!(err, DynamicShapeError::DuplicateField("a".to_string()));
+
+    let err = builder
+        .struct_()
+        .field_at("a", u32::SHAPE, 2)
+        .build()
+        .unwrap_err();
+    assert!(matches!(err, DynamicShapeError::MisalignedField { .. }));
+
+    let err = builder
+        .struct_()
+        .field_at("a", u64::SHAPE, 0)
+        .field_at("b", u32::SHAPE, 4)
+        .build()
+        .unwrap_err();
+    assert!(matches!(err, DynamicShapeError::OverlappingFields { .. }));
+
+    let err = builder
+        .struct_()
+        .field("name", String::SHAPE)
+        .build()
+        .unwrap_err();
+    assert!(matches!(err, DynamicShapeError::FieldNeedsDrop { .. }));
+}
+
+#[test]
+fn dynamic_lists_and_maps() {
+    let builder = DynamicShapeBuilder::new();
+
+    let list = builder.list(u16::SHAPE).unwrap();
+    assert!(matches!(list.def, Def::List(ld) if ld.t.is_type::<u16>()));
+
+    let map = builder.map(String::SHAPE, f64::SHAPE).unwrap();
+    assert!(matches!(map.def, Def::Map(md) if md.k.is_type::<String>() && md.v.is_type::<f64>()));
+
+    let point = builder.struct_().field("x", i32::SHAPE).build().unwrap();
+    assert_eq!(
+        builder.list(point),
+        Err(DynamicShapeError::UnsupportedShape(point))
+    );
+    assert_eq!(
+        builder.map(f32::SHAPE, u8::SHAPE),
+        Err(DynamicShapeError::UnsupportedShape(f32::SHAPE))
+    );
+    assert_eq!(
+        builder.map(String::SHAPE, list),
+        Err(DynamicShapeError::UnsupportedShape(list))
+    );
+
+    // lists and maps need to be dropped, so they can't be fields either
+    let err = builder.struct_().field("scores", list).build().unwrap_err();
+    assert!(matches!(err, DynamicShapeError::FieldNeedsDrop { shape, .. } if shape == list));
+
+    let values: Vec<u16> = vec![1, 2, 3];
+    let peek = unsafe { Peek::unchecked_new(OpaqueConst::from_ref(&values), list) };
+    let Peek::List(pl) = peek else {
+        panic!("expected a list peek");
+    };
+    assert_eq!(pl.len(), 3);
+}
+
+#[test]
+fn arena_interns_shapes() {
+    static ARENA: ShapeArena = ShapeArena::new();
+    let builder = DynamicShapeBuilder::with_arena(&ARENA);
+
+    let build = || {
+        builder
+            .struct_()
+            .field("lat", f32::SHAPE)
+            .field("lon", f32::SHAPE)
+            .build()
+            .unwrap()
+    };
+    let a = build();
+    let b = build();
+    assert!(core::ptr::eq(a, b));
+    assert_eq!(ARENA.len(), 1);
+
+    let c = builder.tuple_struct().element(f32::SHAPE).build().unwrap();
+    assert!(!core::ptr::eq(a, c));
+    assert_eq!(ARENA.len(), 2);
+}
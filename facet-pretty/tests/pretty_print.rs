@@ -138,3 +138,79 @@ fn test_sensitive_fields() {
     assert!(buffer.contains("[REDACTED]"));
     assert!(!buffer.contains("TOP SECRET PASSWORD"));
 }
+
+#[test]
+fn test_zero_sized_types() {
+    #[derive(Debug, Facet)]
+    struct Marker;
+
+    #[derive(Debug, Facet)]
+    struct Nested {
+        inner: Marker,
+    }
+
+    #[derive(Debug, Facet)]
+    struct Holder {
+        markers: Vec<Nested>,
+        nothing: (),
+    }
+
+    let holder = Holder {
+        markers: vec![Nested { inner: Marker }, Nested { inner: Marker }],
+        nothing: (),
+    };
+
+    let output = PrettyPrinter::new().with_colors(false).format(&holder);
+    assert!(!output.contains("cycle detected"), "{output}");
+    assert_eq!(output.matches("Marker { }").count(), 2, "{output}");
+}
+
+#[test]
+fn test_options() {
+    #[derive(Debug, Facet)]
+    struct Profile {
+        nickname: Option<String>,
+        age: Option<u8>,
+        home: Option<Address>,
+    }
+
+    let profile = Profile {
+        nickname: Some("ada".to_string()),
+        age: None,
+        home: Some(Address {
+            street: "1 Main St".to_string(),
+            city: "Anytown".to_string(),
+            country: "Nowhere".to_string(),
+        }),
+    };
+
+    let output = PrettyPrinter::new().with_colors(false).format(&profile);
+    assert!(!output.contains("unsupported"), "{output}");
+    assert!(!output.contains("cycle detected"), "{output}");
+    assert!(output.contains("nickname: Some(ada),"), "{output}");
+    assert!(output.contains("age: None,"), "{output}");
+    assert!(output.contains("home: Some(Address {"), "{output}");
+    assert!(output.contains("city: Anytown,"), "{output}");
+}
+
+#[test]
+fn test_max_depth() {
+    let person = Person {
+        name: "Alice".to_string(),
+        age: 30,
+        address: Address {
+            street: "123 Main St".to_string(),
+            city: "Wonderland".to_string(),
+            country: "Imagination".to_string(),
+        },
+    };
+
+    let output = PrettyPrinter::new()
+        .with_colors(false)
+        .with_max_depth(1)
+        .format(&person);
+    assert_eq!(
+        output,
+        "Person {\n  name: Alice,\n  age: 30,\n  address: Address {\n    street: [...,\n    city: [...,\n    country: [...,\n  },\n}"
+    );
+}
@@ -0,0 +1,844 @@
+use alloc::borrow::Cow;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use facet_poke::{
+    Path, PathSegment, Poke, PokeEnum, PokeEnumNoVariant, PokeList, PokeMap, PokeOption,
+    PokeStruct, PokeValue, Scratch,
+};
+use facet_trait::{
+    Def, Facet, Field, FieldFlags, Opaque, OpaqueConst, Shape, ShapeExt as _, StructKind,
+    TryFromError, VariantKind,
+};
+
+/// A token of the data model, read by a [`Deserializer`]
+///
+/// Compound values are bracketed by start and end events: in a map, each value is
+/// preceded by a [`Event::Key`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum Event<'de> {
+    /// `null`, `()` or a unit struct — and `None`, when deserializing an option
+    Unit,
+    /// A boolean
+    Bool(bool),
+    /// An unsigned integer
+    U64(u64),
+    /// A signed integer
+    I64(i64),
+    /// A floating-point number
+    F64(f64),
+    /// A string: besides strings, any scalar or struct that parses from one can be built
+    /// from it
+    Str(Cow<'de, str>),
+    /// The start of a list, tuple or tuple struct
+    StartSeq,
+    /// The end of the current sequence
+    EndSeq,
+    /// The start of a map or struct
+    StartMap,
+    /// The key of the next entry of the current map
+    Key(Cow<'de, str>),
+    /// The end of the current map
+    EndMap,
+}
+
+impl Event<'_> {
+    /// Describes the kind of event, for error messages
+    pub fn describe(&self) -> &'static str {
+        match self {
+            Event::Unit => "a unit value",
+            Event::Bool(_) => "a boolean",
+            Event::U64(_) | Event::I64(_) => "an integer",
+            Event::F64(_) => "a float",
+            Event::Str(_) => "a string",
+            Event::StartSeq => "a sequence",
+            Event::EndSeq => "the end of a sequence",
+            Event::StartMap => "a map",
+            Event::Key(_) => "a map key",
+            Event::EndMap => "the end of a map",
+        }
+    }
+}
+
+/// The token-level half of a format: reads the input as a stream of [`Event`]s, which
+/// [`deserialize`] builds a value out of.
+///
+/// Structs are maps keyed by field name, and enums are externally tagged: a unit variant
+/// is a string, other variants are maps with a single key, the variant name, whose value
+/// is the payload (the value of its only field for a newtype variant, a sequence for a
+/// tuple variant, a map for a struct variant).
+pub trait Deserializer<'de> {
+    /// The error returned by the format, e.g. a syntax error
+    type Error;
+
+    /// Reads the next event
+    fn next(&mut self) -> Result<Event<'de>, Self::Error>;
+}
+
+/// Returned by [`deserialize`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeserializeError<E> {
+    /// Where in the value being built the error happened
+    pub path: Path,
+    /// The shape of the value that was being built
+    pub shape: &'static Shape,
+    /// What went wrong
+    pub kind: DeserializeErrorKind<E>,
+}
+
+/// What went wrong while deserializing a value
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum DeserializeErrorKind<E> {
+    /// The deserializer returned an error
+    Format(E),
+    /// The event doesn't fit the value being built
+    Unexpected {
+        /// What would have fit
+        expected: &'static str,
+        /// What was read, see [`Event::describe`]
+        got: &'static str,
+    },
+    /// The scalar being built can't be built from this kind of event
+    Mismatch {
+        /// What was read, see [`Event::describe`]
+        got: &'static str,
+    },
+    /// The event has the right kind, but its value was rejected, e.g. a number that's
+    /// out of range
+    InvalidValue(String),
+    /// The struct has no field with this name
+    UnknownField(String),
+    /// The struct's field wasn't in the input, and has no default
+    MissingField(&'static str),
+    /// The enum has no variant with this name
+    UnknownVariant(String),
+    /// Values of this shape can't be deserialized
+    Unsupported,
+}
+
+impl<E: core::fmt::Debug + core::fmt::Display> core::error::Error for DeserializeError<E> {}
+
+impl<E: core::fmt::Display> core::fmt::Display for DeserializeError<E> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let shape = self.shape;
+        match &self.kind {
+            DeserializeErrorKind::Format(err) => write!(f, "{err}")?,
+            DeserializeErrorKind::Unexpected { expected, got } => {
+                write!(f, "Expected {expected}, got {got}")?
+            }
+            DeserializeErrorKind::Mismatch { got } => {
+                write!(f, "Can't build a {shape} out of {got}")?
+            }
+            DeserializeErrorKind::InvalidValue(message) => write!(f, "Invalid {shape}: {message}")?,
+            DeserializeErrorKind::UnknownField(name) => {
+                write!(f, "Unknown field `{name}` for {shape}")?
+            }
+            DeserializeErrorKind::MissingField(name) => {
+                write!(f, "Missing field `{name}` for {shape}")?
+            }
+            DeserializeErrorKind::UnknownVariant(name) => {
+                write!(f, "Unknown variant `{name}` for {shape}")?
+            }
+            DeserializeErrorKind::Unsupported => write!(f, "Can't deserialize a {shape}")?,
+        }
+        if !self.path.is_root() {
+            write!(f, " at `{}`", self.path)?;
+        }
+        Ok(())
+    }
+}
+
+/// Builds a `T` out of the events read from `deserializer`
+///
+/// # Errors
+///
+/// Returns the first error returned by the deserializer, or an error if the events don't
+/// describe a `T`. Whatever was built so far is dropped.
+pub fn deserialize<'de, T: Facet, D: Deserializer<'de>>(
+    deserializer: &mut D,
+) -> Result<T, DeserializeError<D::Error>> {
+    let (poke, _guard) = Poke::alloc::<T>();
+    let opaque = deserialize_opaque(poke, deserializer)?;
+    Ok(unsafe { opaque.read::<T>() })
+}
+
+/// Builds the value `poke` points to out of the events read from `deserializer`
+///
+/// # Errors
+///
+/// Same as [`deserialize`]: on error, `poke` is left uninitialized.
+pub fn deserialize_opaque<'de, 'mem, D: Deserializer<'de>>(
+    poke: Poke<'mem>,
+    deserializer: &mut D,
+) -> Result<Opaque<'mem>, DeserializeError<D::Error>> {
+    let mut builder = Builder {
+        de: deserializer,
+        peeked: None,
+        path: Path::root(),
+        stack: Vec::new(),
+    };
+    builder.run(poke)
+}
+
+/// Where an enum's variant is, once its name was read
+enum VariantForm {
+    /// The value of the only field
+    Newtype { started: bool },
+    /// A sequence of fields
+    Tuple { next: usize },
+    /// A map of fields, with the field being built
+    Struct { current: usize },
+}
+
+/// A value whose children are being built
+enum Frame<'mem> {
+    /// A struct, along with the structs flattened into it: each of those has the index of
+    /// its parent in the list, and the index of its field in that parent. `current` is
+    /// the field being built, as a (struct, field) pair.
+    Struct {
+        structs: Vec<(PokeStruct<'mem>, Option<(usize, usize)>)>,
+        current: (usize, usize),
+    },
+    /// A tuple or tuple struct, whose fields come in order
+    Tuple { ps: PokeStruct<'mem>, next: usize },
+    /// A tuple struct with a single field, built as that field
+    Newtype { ps: PokeStruct<'mem>, started: bool },
+    /// A list, with the item being built
+    List {
+        pl: PokeList<'mem>,
+        item: Option<Scratch>,
+    },
+    /// A map, with the entry being built
+    Map {
+        pm: PokeMap<'mem>,
+        entry: Option<(String, Scratch)>,
+    },
+    /// An option that holds a value, once it's started
+    Option {
+        po: PokeOption<'mem>,
+        value: Option<Scratch>,
+    },
+    /// An enum whose variant has fields: the map holding the variant name is closed
+    /// once they're built
+    Enum {
+        pe: PokeEnum<'mem>,
+        fields: &'static [Field],
+        form: VariantForm,
+    },
+}
+
+/// Returns true if `field` holds a struct whose fields are read inline
+fn is_flattened(field: &Field) -> bool {
+    field.flags.contains(FieldFlags::FLATTEN)
+        && matches!(field.shape.def, Def::Struct(def) if def.kind == StructKind::Struct)
+}
+
+/// Builds a value with an explicit stack, so that deeply nested input doesn't overflow
+/// the call stack
+struct Builder<'d, 'de, 'mem, D: Deserializer<'de>> {
+    de: &'d mut D,
+    /// An event that was read, but belongs to the next value
+    peeked: Option<Event<'de>>,
+    path: Path,
+    /// Values being built, each with the length of its path
+    stack: Vec<(Frame<'mem>, usize)>,
+}
+
+impl<'de, D: Deserializer<'de>> Drop for Builder<'_, 'de, '_, D> {
+    fn drop(&mut self) {
+        // children live in memory their parents own (a field, or a scratch allocation),
+        // so they must go first
+        while let Some(frame) = self.stack.pop() {
+            drop(frame);
+        }
+    }
+}
+
+type Error<'de, D> = DeserializeError<<D as Deserializer<'de>>::Error>;
+
+impl<'de, 'mem, D: Deserializer<'de>> Builder<'_, 'de, 'mem, D> {
+    fn run(&mut self, root: Poke<'mem>) -> Result<Opaque<'mem>, Error<'de, D>> {
+        let mut done = self.start(root)?;
+        loop {
+            if let Some(value) = done.take() {
+                if self.stack.is_empty() {
+                    return Ok(value);
+                }
+                self.complete_child()?;
+            }
+            done = match self.next_child()? {
+                Some(poke) => self.start(poke)?,
+                None => {
+                    let (frame, _) = self.stack.pop().unwrap();
+                    Some(self.finish(frame)?)
+                }
+            };
+        }
+    }
+
+    fn read(&mut self, shape: &'static Shape) -> Result<Event<'de>, Error<'de, D>> {
+        match self.peeked.take() {
+            Some(event) => Ok(event),
+            None => self
+                .de
+                .next()
+                .map_err(|err| self.error(shape, DeserializeErrorKind::Format(err))),
+        }
+    }
+
+    fn error(&self, shape: &'static Shape, kind: DeserializeErrorKind<D::Error>) -> Error<'de, D> {
+        DeserializeError {
+            path: self.path.clone(),
+            shape,
+            kind,
+        }
+    }
+
+    fn unexpected(
+        &self,
+        shape: &'static Shape,
+        expected: &'static str,
+        got: &Event,
+    ) -> Error<'de, D> {
+        let got = got.describe();
+        self.error(shape, DeserializeErrorKind::Unexpected { expected, got })
+    }
+
+    /// Reads an event that must be `expected`
+    fn expect(
+        &mut self,
+        shape: &'static Shape,
+        expected: Event<'static>,
+    ) -> Result<(), Error<'de, D>> {
+        let event = self.read(shape)?;
+        if event == expected {
+            Ok(())
+        } else {
+            Err(self.unexpected(shape, expected.describe(), &event))
+        }
+    }
+
+    /// Starts building a value: returns it if it's already built, or pushes it on the stack
+    fn start(&mut self, poke: Poke<'mem>) -> Result<Option<Opaque<'mem>>, Error<'de, D>> {
+        let shape = poke.shape();
+        let event = self.read(shape)?;
+        let expect = |builder: &Self, expected: Event<'static>| {
+            if event == expected {
+                Ok(())
+            } else {
+                Err(builder.unexpected(shape, expected.describe(), &event))
+            }
+        };
+
+        let frame = match poke {
+            Poke::Scalar(pv) => {
+                return match scalar(pv, event) {
+                    Ok(value) => Ok(Some(value)),
+                    Err(kind) => Err(self.error(shape, kind)),
+                };
+            }
+            Poke::Option(po) => {
+                if event == Event::Unit {
+                    return Ok(Some(po.init_none()));
+                }
+                self.peeked = Some(event);
+                Frame::Option { po, value: None }
+            }
+            // structs that parse from a string, through their `parse` vtable entry
+            Poke::Struct(ps) if shape.vtable.parse.is_some() && matches!(event, Event::Str(_)) => {
+                let Event::Str(s) = &event else {
+                    unreachable!("checked above")
+                };
+                return match ps.into_value().parse(s) {
+                    Ok(value) => Ok(Some(value)),
+                    Err((_, err)) => {
+                        let kind = DeserializeErrorKind::InvalidValue(err.to_string());
+                        Err(self.error(shape, kind))
+                    }
+                };
+            }
+            Poke::Struct(ps) => match ps.def().kind {
+                StructKind::Unit => {
+                    expect(self, Event::Unit)?;
+                    return Ok(Some(ps.build_in_place()));
+                }
+                StructKind::TupleStruct if ps.def().fields.len() == 1 => {
+                    self.peeked = Some(event);
+                    Frame::Newtype { ps, started: false }
+                }
+                StructKind::Tuple | StructKind::TupleStruct => {
+                    expect(self, Event::StartSeq)?;
+                    Frame::Tuple { ps, next: 0 }
+                }
+                _ => {
+                    expect(self, Event::StartMap)?;
+                    Frame::Struct {
+                        structs: open_flattened(ps),
+                        current: (0, 0),
+                    }
+                }
+            },
+            Poke::List(pl) => {
+                expect(self, Event::StartSeq)?;
+                let pl = pl
+                    .init(None)
+                    .map_err(|_| self.error(shape, DeserializeErrorKind::Unsupported))?;
+                Frame::List { pl, item: None }
+            }
+            Poke::Map(pm) => {
+                expect(self, Event::StartMap)?;
+                let pm = pm
+                    .init(None)
+                    .map_err(|_| self.error(shape, DeserializeErrorKind::Unsupported))?;
+                Frame::Map { pm, entry: None }
+            }
+            Poke::Enum(pe) => match self.start_enum(pe, event)? {
+                Ok(value) => return Ok(Some(value)),
+                Err(frame) => frame,
+            },
+            _ => return Err(self.error(shape, DeserializeErrorKind::Unsupported)),
+        };
+        self.stack.push((frame, self.path.segments().len()));
+        Ok(None)
+    }
+
+    /// Selects the variant named by `event`: returns the enum if it's a unit variant, or a
+    /// frame to build its fields
+    #[allow(clippy::type_complexity)]
+    fn start_enum(
+        &mut self,
+        pe: PokeEnumNoVariant<'mem>,
+        event: Event<'de>,
+    ) -> Result<Result<Opaque<'mem>, Frame<'mem>>, Error<'de, D>> {
+        let shape = pe.shape();
+        let Def::Enum(def) = shape.def else {
+            unreachable!("enum pokes have an enum shape")
+        };
+
+        let (name, tagged) = match event {
+            Event::Str(name) => (name, false),
+            Event::StartMap => match self.read(shape)? {
+                Event::Key(name) => (name, true),
+                event => return Err(self.unexpected(shape, "a variant name", &event)),
+            },
+            event => {
+                return Err(self.unexpected(shape, "a variant name or a single-key map", &event));
+            }
+        };
+        let Some(index) = def.variants.iter().position(|v| v.name == name) else {
+            let name = name.into_owned();
+            return Err(self.error(shape, DeserializeErrorKind::UnknownVariant(name)));
+        };
+        let pe = pe
+            .set_variant_by_index(index)
+            .expect("variant index is in bounds");
+
+        let (fields, form, start) = match def.variants[index].kind {
+            VariantKind::Tuple { fields } if fields.len() == 1 => {
+                (fields, VariantForm::Newtype { started: false }, None)
+            }
+            VariantKind::Tuple { fields } => (
+                fields,
+                VariantForm::Tuple { next: 0 },
+                Some(Event::StartSeq),
+            ),
+            VariantKind::Struct { fields } => (
+                fields,
+                VariantForm::Struct { current: 0 },
+                Some(Event::StartMap),
+            ),
+            _ => {
+                if tagged {
+                    self.expect(shape, Event::Unit)?;
+                    self.expect(shape, Event::EndMap)?;
+                }
+                return Ok(Ok(pe.build_in_place()));
+            }
+        };
+        if !tagged {
+            let kind = DeserializeErrorKind::Unexpected {
+                expected: "a single-key map",
+                got: "a string",
+            };
+            return Err(self.error(shape, kind));
+        }
+        if let Some(start) = start {
+            self.expect(shape, start)?;
+        }
+        Ok(Err(Frame::Enum { pe, fields, form }))
+    }
+
+    /// Returns the next child of the value on top of the stack, or `None` if it's complete
+    fn next_child(&mut self) -> Result<Option<Poke<'mem>>, Error<'de, D>> {
+        let (frame, _) = self.stack.last_mut().unwrap();
+        let shape = frame_shape(frame);
+
+        // frames whose children come without an event of their own
+        match frame {
+            Frame::Newtype { ps, started } => {
+                if core::mem::replace(started, true) {
+                    return Ok(None);
+                }
+                self.path.push(PathSegment::Field(String::from("0")));
+                return Ok(Some(ps.field(0).expect("newtypes have a field")));
+            }
+            Frame::Option { po, value } => {
+                if value.is_some() {
+                    return Ok(None);
+                }
+                let (poke, scratch) = Scratch::alloc(po.def().t);
+                *value = Some(scratch);
+                return Ok(Some(poke));
+            }
+            Frame::Enum {
+                pe,
+                form: VariantForm::Newtype { started },
+                ..
+            } => {
+                if core::mem::replace(started, true) {
+                    return Ok(None);
+                }
+                self.path.push(PathSegment::Field(String::from("0")));
+                return Ok(Some(pe.variant_field(0).expect("newtypes have a field")));
+            }
+            _ => {}
+        }
+
+        let event = self.read(shape)?;
+        let (frame, _) = self.stack.last_mut().unwrap();
+        let (segments, poke) = match (frame, event) {
+            (Frame::Struct { .. } | Frame::Enum { .. } | Frame::Map { .. }, Event::EndMap)
+            | (Frame::Tuple { .. } | Frame::Enum { .. } | Frame::List { .. }, Event::EndSeq) => {
+                return Ok(None);
+            }
+            (Frame::Struct { structs, current }, Event::Key(name)) => {
+                let Some(found) = find_field(structs, &name) else {
+                    let name = name.into_owned();
+                    return Err(self.error(shape, DeserializeErrorKind::UnknownField(name)));
+                };
+                *current = found;
+                let segments = field_path(structs, found);
+                let poke = structs[found.0]
+                    .0
+                    .field(found.1)
+                    .expect("field is in bounds");
+                (segments, poke)
+            }
+            (
+                Frame::Enum {
+                    pe,
+                    fields,
+                    form: VariantForm::Struct { current },
+                },
+                Event::Key(name),
+            ) => {
+                let Some(index) = fields.iter().position(|f| f.name == name) else {
+                    let name = name.into_owned();
+                    return Err(self.error(shape, DeserializeErrorKind::UnknownField(name)));
+                };
+                *current = index;
+                let segment = PathSegment::Field(String::from(fields[index].name));
+                let poke = pe.variant_field(index).expect("field is in bounds");
+                (Vec::from([segment]), poke)
+            }
+            (Frame::Tuple { ps, next }, event) => {
+                if *next >= ps.def().fields.len() {
+                    return Err(self.unexpected(shape, "the end of a sequence", &event));
+                }
+                self.peeked = Some(event);
+                let segment = PathSegment::Field(next.to_string());
+                let poke = ps.field(*next).expect("field is in bounds");
+                (Vec::from([segment]), poke)
+            }
+            (
+                Frame::Enum {
+                    pe,
+                    fields,
+                    form: VariantForm::Tuple { next },
+                },
+                event,
+            ) => {
+                if *next >= fields.len() {
+                    return Err(self.unexpected(shape, "the end of a sequence", &event));
+                }
+                self.peeked = Some(event);
+                let segment = PathSegment::Field(next.to_string());
+                let poke = pe.variant_field(*next).expect("field is in bounds");
+                (Vec::from([segment]), poke)
+            }
+            (Frame::List { pl, item }, event) => {
+                self.peeked = Some(event);
+                let segment = PathSegment::Index(pl.len());
+                let (poke, scratch) = Scratch::alloc(pl.def().t);
+                *item = Some(scratch);
+                (Vec::from([segment]), poke)
+            }
+            (Frame::Map { pm, entry }, Event::Key(key)) => {
+                let segment = PathSegment::Key(key.to_string());
+                let (poke, scratch) = Scratch::alloc(pm.def().v);
+                *entry = Some((key.into_owned(), scratch));
+                (Vec::from([segment]), poke)
+            }
+            (Frame::Struct { .. } | Frame::Enum { .. } | Frame::Map { .. }, event) => {
+                return Err(self.unexpected(shape, "a map key or the end of a map", &event));
+            }
+            (Frame::Newtype { .. } | Frame::Option { .. }, _) => {
+                unreachable!("handled above")
+            }
+        };
+        for segment in segments {
+            self.path.push(segment);
+        }
+        Ok(Some(poke))
+    }
+
+    /// Records that the child returned by [`Self::next_child`] was built
+    fn complete_child(&mut self) -> Result<(), Error<'de, D>> {
+        let (frame, path_len) = self.stack.last_mut().unwrap();
+        let path_len = *path_len;
+        match frame {
+            Frame::Struct {
+                structs,
+                current: (index, field),
+            } => unsafe { structs[*index].0.mark_initialized(*field) },
+            Frame::Tuple { ps, next } => {
+                unsafe { ps.mark_initialized(*next) };
+                *next += 1;
+            }
+            Frame::Newtype { ps, .. } => unsafe { ps.mark_initialized(0) },
+            Frame::List { pl, item } => {
+                let mut item = item.take().unwrap();
+                unsafe { item.mark_initialized() };
+                // the item is moved into the list, the scratch only frees its allocation
+                unsafe { pl.push(item.take()) };
+            }
+            Frame::Map { pm, entry } => {
+                let (key, mut value) = entry.take().unwrap();
+                unsafe { value.mark_initialized() };
+
+                let key_shape = pm.def().k;
+                let (key_poke, mut key_scratch) = Scratch::alloc(key_shape);
+                if let Err(kind) = scalar(key_poke.into_value(), Event::Str(Cow::Owned(key))) {
+                    return Err(self.error(key_shape, kind));
+                }
+                unsafe { key_scratch.mark_initialized() };
+                // both are moved into the map, the scratches only free their allocations
+                unsafe { pm.insert(key_scratch.take(), value.take()) };
+            }
+            Frame::Option { value, .. } => unsafe { value.as_mut().unwrap().mark_initialized() },
+            Frame::Enum { pe, form, .. } => match form {
+                VariantForm::Newtype { .. } => unsafe { pe.mark_field_as_initialized(0) },
+                VariantForm::Tuple { next } => {
+                    unsafe { pe.mark_field_as_initialized(*next) };
+                    *next += 1;
+                }
+                VariantForm::Struct { current } => unsafe {
+                    pe.mark_field_as_initialized(*current)
+                },
+            },
+        }
+        while self.path.segments().len() > path_len {
+            self.path.pop();
+        }
+        Ok(())
+    }
+
+    /// Fills in the missing fields of a complete frame and builds its value
+    fn finish(&mut self, frame: Frame<'mem>) -> Result<Opaque<'mem>, Error<'de, D>> {
+        let shape = frame_shape(&frame);
+        match frame {
+            Frame::Struct { mut structs, .. } => {
+                // flattened structs come after the struct holding them
+                while let Some((mut ps, parent)) = structs.pop() {
+                    while let Some(field) = ps.first_uninitialized_field() {
+                        let index = ps
+                            .def()
+                            .fields
+                            .iter()
+                            .position(|f| core::ptr::eq(f, field))
+                            .unwrap();
+                        let poke = ps.field(index).unwrap();
+                        if !fill_missing(field, poke) {
+                            let kind = DeserializeErrorKind::MissingField(field.name);
+                            return Err(self.error(ps.shape(), kind));
+                        }
+                        unsafe { ps.mark_initialized(index) };
+                    }
+                    let value = ps.build_in_place();
+                    match parent {
+                        Some((parent, index)) => unsafe {
+                            structs[parent].0.mark_initialized(index)
+                        },
+                        None => return Ok(value),
+                    }
+                }
+                unreachable!("struct frames hold at least their own struct")
+            }
+            Frame::Tuple { ps, .. } => match ps.first_uninitialized_field() {
+                Some(field) => {
+                    Err(self.error(shape, DeserializeErrorKind::MissingField(field.name)))
+                }
+                None => Ok(ps.build_in_place()),
+            },
+            Frame::Newtype { ps, .. } => Ok(ps.build_in_place()),
+            Frame::List { pl, .. } => Ok(pl.build_in_place()),
+            Frame::Map { pm, .. } => Ok(pm.build_in_place()),
+            Frame::Option { po, value } => {
+                let mut value = value.unwrap();
+                Ok(unsafe { po.init_some(value.take()) })
+            }
+            Frame::Enum { mut pe, fields, .. } => {
+                while let Some(field) = pe.first_uninitialized_field() {
+                    let index = fields.iter().position(|f| core::ptr::eq(f, field)).unwrap();
+                    let poke = pe.variant_field(index).unwrap();
+                    if !fill_missing(field, poke) {
+                        let kind = DeserializeErrorKind::MissingField(field.name);
+                        return Err(self.error(shape, kind));
+                    }
+                    unsafe { pe.mark_field_as_initialized(index) };
+                }
+                // close the map holding the variant name
+                self.expect(shape, Event::EndMap)?;
+                Ok(pe.build_in_place())
+            }
+        }
+    }
+}
+
+fn frame_shape(frame: &Frame<'_>) -> &'static Shape {
+    match frame {
+        Frame::Struct { structs, .. } => structs[0].0.shape(),
+        Frame::Tuple { ps, .. } | Frame::Newtype { ps, .. } => ps.shape(),
+        Frame::List { pl, .. } => pl.shape(),
+        Frame::Map { pm, .. } => pm.shape(),
+        Frame::Option { po, .. } => po.shape(),
+        Frame::Enum { pe, .. } => pe.shape(),
+    }
+}
+
+/// Opens the structs flattened into `ps`, recursively
+fn open_flattened(ps: PokeStruct<'_>) -> Vec<(PokeStruct<'_>, Option<(usize, usize)>)> {
+    let mut structs = Vec::from([(ps, None)]);
+    let mut index = 0;
+    while index < structs.len() {
+        let fields = structs[index].0.def().fields;
+        for (field, _) in fields.iter().enumerate().filter(|(_, f)| is_flattened(f)) {
+            let inner = structs[index].0.field(field).unwrap().into_struct();
+            structs.push((inner, Some((index, field))));
+        }
+        index += 1;
+    }
+    structs
+}
+
+/// Finds the field named `name` in a struct or the structs flattened into it
+fn find_field(
+    structs: &[(PokeStruct<'_>, Option<(usize, usize)>)],
+    name: &str,
+) -> Option<(usize, usize)> {
+    structs.iter().enumerate().find_map(|(index, (ps, _))| {
+        ps.def()
+            .fields
+            .iter()
+            .position(|f| f.name == name && !is_flattened(f))
+            .map(|field| (index, field))
+    })
+}
+
+/// Returns the path from a struct to one of its fields, through flattened structs
+fn field_path(
+    structs: &[(PokeStruct<'_>, Option<(usize, usize)>)],
+    (mut index, field): (usize, usize),
+) -> Vec<PathSegment> {
+    let name = |index: usize, field: usize| {
+        PathSegment::Field(String::from(structs[index].0.def().fields[field].name))
+    };
+    let mut segments = Vec::from([name(index, field)]);
+    while let Some((parent, field)) = structs[index].1 {
+        segments.push(name(parent, field));
+        index = parent;
+    }
+    segments.reverse();
+    segments
+}
+
+/// Initializes a field that's missing from the input, if it has a default: `None` for
+/// options, and the type's default for `#[facet(default)]` fields
+fn fill_missing(field: &Field, poke: Poke<'_>) -> bool {
+    match poke {
+        Poke::Option(po) => {
+            po.init_none();
+            true
+        }
+        poke if field.flags.contains(FieldFlags::DEFAULT) => {
+            poke.into_value().default_in_place().is_ok()
+        }
+        _ => false,
+    }
+}
+
+/// Builds a scalar out of an event, converting it if needed
+fn scalar<'mem, E>(
+    pv: PokeValue<'mem>,
+    event: Event<'_>,
+) -> Result<Opaque<'mem>, DeserializeErrorKind<E>> {
+    let shape = pv.shape();
+    let mismatch = |event: &Event| DeserializeErrorKind::Mismatch {
+        got: event.describe(),
+    };
+    match event {
+        Event::Unit if shape.is_type::<()>() => Ok(unsafe { pv.put(OpaqueConst::from_ref(&())) }),
+        Event::Bool(b) if shape.is_type::<bool>() => {
+            Ok(unsafe { pv.put(OpaqueConst::from_ref(&b)) })
+        }
+        Event::Str(s) if shape.is_type::<String>() => {
+            let s = s.into_owned();
+            let data = unsafe { pv.put(OpaqueConst::from_ref(&s)) };
+            core::mem::forget(s);
+            Ok(data)
+        }
+        Event::Str(s) => pv
+            .parse(&s)
+            .map_err(|(_, err)| DeserializeErrorKind::InvalidValue(err.to_string())),
+        Event::U64(n) => number(pv, n, n as f64),
+        Event::I64(n) => number(pv, n, n as f64),
+        Event::F64(n) if shape.is_type::<f64>() => Ok(unsafe { pv.put(OpaqueConst::from_ref(&n)) }),
+        Event::F64(n) if shape.is_type::<f32>() => {
+            Ok(unsafe { pv.put(OpaqueConst::from_ref(&(n as f32))) })
+        }
+        event => Err(mismatch(&event)),
+    }
+}
+
+/// Builds a number out of an integer, with the target's `try_from` vtable entry, or by
+/// parsing the integer's decimal representation if it has none (strings aren't numbers,
+/// so they're never built this way)
+fn number<N: Facet + core::fmt::Display, E>(
+    pv: PokeValue<'_>,
+    n: N,
+    float: f64,
+) -> Result<Opaque<'_>, DeserializeErrorKind<E>> {
+    let shape = pv.shape();
+    if shape.is_type::<String>() {
+        return Err(DeserializeErrorKind::Mismatch { got: "an integer" });
+    }
+    if shape.is_type::<N>() {
+        return Ok(unsafe { pv.put(OpaqueConst::from_ref(&n)) });
+    }
+    if shape.is_type::<f64>() {
+        return Ok(unsafe { pv.put(OpaqueConst::from_ref(&float)) });
+    }
+    if shape.is_type::<f32>() {
+        return Ok(unsafe { pv.put(OpaqueConst::from_ref(&(float as f32))) });
+    }
+
+    let source = core::mem::ManuallyDrop::new(n);
+    match unsafe { pv.try_from(OpaqueConst::from_ref(&*source), N::SHAPE) } {
+        Ok(value) => Ok(value),
+        Err((pv, TryFromError::Unimplemented(_) | TryFromError::Incompatible { .. })) => pv
+            .parse(&source.to_string())
+            .map_err(|_| DeserializeErrorKind::Mismatch { got: "an integer" }),
+        Err((_, err)) => Err(DeserializeErrorKind::InvalidValue(err.to_string())),
+    }
+}
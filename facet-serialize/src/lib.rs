@@ -0,0 +1,30 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+#![warn(missing_docs)]
+#![doc = include_str!("../README.md")]
+
+//! Format-agnostic serialization and deserialization
+//!
+//! Format crates implement the token-level [`Serializer`] and [`Deserializer`] traits;
+//! [`serialize`] walks a value's shape to drive the former, and [`deserialize`] builds a
+//! value out of the events read from the latter.
+//!
+//! Both sides agree on a data model close to serde's:
+//!
+//!   * structs are maps keyed by field name (after `#[facet(rename = "...")]`), and the
+//!     fields of `#[facet(flatten)]` fields appear inline
+//!   * tuples and tuple structs are sequences, and single-field tuple structs are
+//!     transparent
+//!   * enums are externally tagged: unit variants are strings, other variants are maps
+//!     with a single key, the variant name
+//!   * `None` is a unit value, and `Some(value)` is just the value
+//!
+//! When deserializing, a missing `Option` field is `None`, and a missing
+//! `#[facet(default)]` field takes its type's default value.
+
+extern crate alloc;
+
+mod serialize;
+pub use serialize::*;
+
+mod deserialize;
+pub use deserialize::*;
@@ -0,0 +1,445 @@
+use alloc::borrow::Cow;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use facet_poke::{
+    Path, Peek, PeekEnum, PeekList, PeekMap, PeekOption, PeekStruct, PeekValue, VisitFlow, Visitor,
+};
+use facet_trait::{Def, Field, FieldFlags, Shape, ShapeExt as _, StructKind, VariantKind};
+
+/// The token-level half of a format: receives a value as a stream of calls, made by
+/// [`serialize`] as it walks the value.
+///
+/// Compound values are bracketed by `start_*` and `end_*` calls. Inside a struct, each
+/// field's value is preceded by a call to [`Serializer::field`]; inside a map, each
+/// value is preceded by a call to [`Serializer::map_key`].
+///
+/// Methods with a default implementation map their value onto the rest of the data
+/// model, e.g. `None` onto a unit value: override them if the format has a better
+/// representation.
+pub trait Serializer {
+    /// The error returned by the format, e.g. an I/O error
+    type Error;
+
+    /// Serializes a boolean
+    fn serialize_bool(&mut self, value: bool) -> Result<(), Self::Error>;
+
+    /// Serializes an unsigned integer (all unsigned types up to `u64`)
+    fn serialize_u64(&mut self, value: u64) -> Result<(), Self::Error>;
+
+    /// Serializes a signed integer (all signed types up to `i64`)
+    fn serialize_i64(&mut self, value: i64) -> Result<(), Self::Error>;
+
+    /// Serializes a `u128`: as a `u64` if it fits, as a string otherwise
+    fn serialize_u128(&mut self, value: u128) -> Result<(), Self::Error> {
+        match u64::try_from(value) {
+            Ok(value) => self.serialize_u64(value),
+            Err(_) => self.serialize_str(&value.to_string()),
+        }
+    }
+
+    /// Serializes an `i128`: as an `i64` if it fits, as a string otherwise
+    fn serialize_i128(&mut self, value: i128) -> Result<(), Self::Error> {
+        match i64::try_from(value) {
+            Ok(value) => self.serialize_i64(value),
+            Err(_) => self.serialize_str(&value.to_string()),
+        }
+    }
+
+    /// Serializes a floating-point number (`f32` or `f64`)
+    fn serialize_f64(&mut self, value: f64) -> Result<(), Self::Error>;
+
+    /// Serializes a string
+    fn serialize_str(&mut self, value: &str) -> Result<(), Self::Error>;
+
+    /// Serializes a scalar that the data model doesn't know about, like an IP address,
+    /// given its `Display` representation: as a string by default. Override this if the
+    /// format has a representation of its own for some types, like dates.
+    fn serialize_display(
+        &mut self,
+        _shape: &'static Shape,
+        value: &str,
+    ) -> Result<(), Self::Error> {
+        self.serialize_str(value)
+    }
+
+    /// Serializes `()` or a unit struct
+    fn serialize_unit(&mut self) -> Result<(), Self::Error>;
+
+    /// Serializes `None`, as a unit value by default
+    fn serialize_none(&mut self) -> Result<(), Self::Error> {
+        self.serialize_unit()
+    }
+
+    /// Starts a struct with `len` fields, flattened fields included
+    fn start_struct(&mut self, len: usize) -> Result<(), Self::Error>;
+
+    /// Announces the next field of the current struct: its value follows
+    fn field(&mut self, name: &'static str) -> Result<(), Self::Error>;
+
+    /// Ends the current struct
+    fn end_struct(&mut self) -> Result<(), Self::Error>;
+
+    /// Starts a list, tuple or tuple struct with `len` items
+    fn start_seq(&mut self, len: usize) -> Result<(), Self::Error>;
+
+    /// Ends the current sequence
+    fn end_seq(&mut self) -> Result<(), Self::Error>;
+
+    /// Starts a map with `len` entries
+    fn start_map(&mut self, len: usize) -> Result<(), Self::Error>;
+
+    /// Announces the next entry of the current map: its value follows. Keys are written
+    /// with their `Display` implementation.
+    fn map_key(&mut self, key: &str) -> Result<(), Self::Error>;
+
+    /// Ends the current map
+    fn end_map(&mut self) -> Result<(), Self::Error>;
+
+    /// Serializes a unit variant of an enum, as its name by default
+    fn serialize_unit_variant(&mut self, name: &'static str) -> Result<(), Self::Error> {
+        self.serialize_str(name)
+    }
+
+    /// Starts a variant with fields, whose payload follows: the value of its only field
+    /// for a newtype variant, a sequence for a tuple variant, and a struct for a struct
+    /// variant. By default, this starts a map with a single key, the variant name.
+    fn start_variant(&mut self, name: &'static str) -> Result<(), Self::Error> {
+        self.start_map(1)?;
+        self.map_key(name)
+    }
+
+    /// Ends the current variant
+    fn end_variant(&mut self) -> Result<(), Self::Error> {
+        self.end_map()
+    }
+}
+
+/// Returned by [`serialize`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct SerializeError<E> {
+    /// Where in the value the error happened
+    pub path: Path,
+    /// What went wrong
+    pub kind: SerializeErrorKind<E>,
+}
+
+/// What went wrong while serializing a value
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum SerializeErrorKind<E> {
+    /// The serializer returned an error
+    Format(E),
+    /// The value is a scalar the data model doesn't know about, and it doesn't
+    /// implement `Display`
+    Unsupported(&'static Shape),
+    /// The value contains itself
+    Cycle,
+}
+
+impl<E: core::fmt::Debug + core::fmt::Display> core::error::Error for SerializeError<E> {}
+
+impl<E: core::fmt::Display> core::fmt::Display for SerializeError<E> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match &self.kind {
+            SerializeErrorKind::Format(err) => write!(f, "{err}")?,
+            SerializeErrorKind::Unsupported(shape) => write!(f, "Can't serialize a {shape}")?,
+            SerializeErrorKind::Cycle => write!(f, "The value contains itself")?,
+        }
+        if !self.path.is_root() {
+            write!(f, " at `{}`", self.path)?;
+        }
+        Ok(())
+    }
+}
+
+/// Serializes `peek` into `serializer`, walking the value depth-first.
+///
+/// The walk doesn't recurse, so deeply nested values don't overflow the call stack.
+///
+/// # Errors
+///
+/// Returns the first error returned by the serializer, or an error if the value can't
+/// be represented in the data model.
+pub fn serialize<S: Serializer>(
+    peek: Peek<'_>,
+    serializer: &mut S,
+) -> Result<(), SerializeError<S::Error>> {
+    peek.visit(&mut Driver {
+        serializer,
+        scopes: Vec::new(),
+        flatten_next: false,
+    })
+}
+
+/// How the children of a compound value are written
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Scope {
+    /// Fields, each announced with [`Serializer::field`]
+    Struct,
+    /// Fields written inline, into the struct that holds them
+    Flattened,
+    /// Fields written in order, without their names
+    Seq,
+    /// The only field, written as if it were the value itself
+    Newtype,
+    /// No fields at all
+    Unit,
+}
+
+/// Adapts a [`Serializer`] into a [`Visitor`]
+struct Driver<'s, S> {
+    serializer: &'s mut S,
+    /// The scope of every compound value being visited, and whether it's an enum variant
+    scopes: Vec<(Scope, bool)>,
+    /// Set by a flattened field, for the struct it holds
+    flatten_next: bool,
+}
+
+/// Returns true if `field` holds a struct whose fields are written inline
+fn is_flattened(field: &Field) -> bool {
+    field.flags.contains(FieldFlags::FLATTEN)
+        && matches!(field.shape.def, Def::Struct(def) if def.kind == StructKind::Struct)
+}
+
+/// Counts the fields of a struct, replacing flattened fields with their own fields
+fn flattened_len(fields: &'static [Field]) -> usize {
+    let mut len = 0;
+    let mut pending = Vec::from([fields]);
+    while let Some(fields) = pending.pop() {
+        for field in fields {
+            match field.shape.def {
+                Def::Struct(def) if is_flattened(field) => pending.push(def.fields),
+                _ => len += 1,
+            }
+        }
+    }
+    len
+}
+
+impl<S: Serializer> Driver<'_, S> {
+    fn open(&mut self, scope: Scope, variant: bool) -> Result<VisitFlow, S::Error> {
+        self.scopes.push((scope, variant));
+        Ok(VisitFlow::Continue)
+    }
+
+    fn close(&mut self) -> Result<(), S::Error> {
+        let (scope, variant) = self.scopes.pop().expect("leaving a scope that was entered");
+        match scope {
+            Scope::Struct => self.serializer.end_struct()?,
+            Scope::Seq => self.serializer.end_seq()?,
+            Scope::Flattened | Scope::Newtype | Scope::Unit => {}
+        }
+        if variant && scope != Scope::Unit {
+            self.serializer.end_variant()?;
+        }
+        Ok(())
+    }
+
+    fn scalar(&mut self, value: PeekValue<'_>) -> Result<(), SerializeErrorKind<S::Error>> {
+        let shape = value.shape();
+        let data = value.data();
+        let s = &mut *self.serializer;
+
+        macro_rules! as_u64 {
+            ($($ty:ty),*) => {$(
+                if shape.is_type::<$ty>() {
+                    return Ok(s.serialize_u64(unsafe { *data.as_ref::<$ty>() } as u64)?);
+                }
+            )*};
+        }
+        macro_rules! as_i64 {
+            ($($ty:ty),*) => {$(
+                if shape.is_type::<$ty>() {
+                    return Ok(s.serialize_i64(unsafe { *data.as_ref::<$ty>() } as i64)?);
+                }
+            )*};
+        }
+        as_u64!(u8, u16, u32, u64);
+        as_i64!(i8, i16, i32, i64);
+
+        unsafe {
+            if shape.is_type::<()>() {
+                s.serialize_unit()?;
+            } else if shape.is_type::<bool>() {
+                s.serialize_bool(*data.as_ref::<bool>())?;
+            } else if shape.is_type::<u128>() {
+                s.serialize_u128(*data.as_ref::<u128>())?;
+            } else if shape.is_type::<i128>() {
+                s.serialize_i128(*data.as_ref::<i128>())?;
+            } else if shape.is_type::<f32>() {
+                s.serialize_f64(*data.as_ref::<f32>() as f64)?;
+            } else if shape.is_type::<f64>() {
+                s.serialize_f64(*data.as_ref::<f64>())?;
+            } else if shape.is_type::<String>() {
+                s.serialize_str(data.as_ref::<String>())?;
+            } else if shape.is_type::<&str>() {
+                s.serialize_str(data.as_ref::<&str>())?;
+            } else if shape.is_type::<Cow<'_, str>>() {
+                s.serialize_str(data.as_ref::<Cow<'_, str>>())?;
+            } else if value.vtable().display.is_some() {
+                s.serialize_display(shape, &value.to_string())?;
+            } else {
+                return Err(SerializeErrorKind::Unsupported(shape));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<E> From<E> for SerializeErrorKind<E> {
+    fn from(err: E) -> Self {
+        SerializeErrorKind::Format(err)
+    }
+}
+
+impl<'mem, S: Serializer> Visitor<'mem> for Driver<'_, S> {
+    type Error = SerializeError<S::Error>;
+
+    fn scalar(&mut self, path: &Path, value: PeekValue<'mem>) -> Result<(), Self::Error> {
+        Driver::scalar(self, value).map_err(|kind| error(path, kind))
+    }
+
+    fn enter_struct(
+        &mut self,
+        path: &Path,
+        value: PeekStruct<'mem>,
+    ) -> Result<VisitFlow, Self::Error> {
+        let def = value.def();
+        let s = &mut *self.serializer;
+        let scope = if core::mem::take(&mut self.flatten_next) {
+            Ok(Scope::Flattened)
+        } else {
+            match def.kind {
+                StructKind::Unit => s.serialize_unit().map(|()| Scope::Unit),
+                StructKind::TupleStruct if def.fields.len() == 1 => Ok(Scope::Newtype),
+                StructKind::Tuple | StructKind::TupleStruct => {
+                    s.start_seq(def.fields.len()).map(|()| Scope::Seq)
+                }
+                _ => s
+                    .start_struct(flattened_len(def.fields))
+                    .map(|()| Scope::Struct),
+            }
+        };
+        scope
+            .and_then(|scope| self.open(scope, false))
+            .map_err(|err| format_error(path, err))
+    }
+
+    fn leave_struct(&mut self, path: &Path, _value: PeekStruct<'mem>) -> Result<(), Self::Error> {
+        self.close().map_err(|err| format_error(path, err))
+    }
+
+    fn enter_field(
+        &mut self,
+        path: &Path,
+        field: &'static Field,
+        _value: Peek<'mem>,
+    ) -> Result<VisitFlow, Self::Error> {
+        match self.scopes.last() {
+            Some((Scope::Struct | Scope::Flattened, _)) if is_flattened(field) => {
+                self.flatten_next = true;
+            }
+            Some((Scope::Struct | Scope::Flattened, _)) => self
+                .serializer
+                .field(field.name)
+                .map_err(|err| format_error(path, err))?,
+            _ => {}
+        }
+        Ok(VisitFlow::Continue)
+    }
+
+    fn enter_list(&mut self, path: &Path, value: PeekList<'mem>) -> Result<VisitFlow, Self::Error> {
+        self.serializer
+            .start_seq(value.len())
+            .and_then(|()| self.open(Scope::Seq, false))
+            .map_err(|err| format_error(path, err))
+    }
+
+    fn leave_list(&mut self, path: &Path, _value: PeekList<'mem>) -> Result<(), Self::Error> {
+        self.close().map_err(|err| format_error(path, err))
+    }
+
+    fn enter_map(&mut self, path: &Path, value: PeekMap<'mem>) -> Result<VisitFlow, Self::Error> {
+        self.serializer
+            .start_map(value.len())
+            .map(|()| VisitFlow::Continue)
+            .map_err(|err| format_error(path, err))
+    }
+
+    fn leave_map(&mut self, path: &Path, _value: PeekMap<'mem>) -> Result<(), Self::Error> {
+        self.serializer
+            .end_map()
+            .map_err(|err| format_error(path, err))
+    }
+
+    fn enter_map_entry(
+        &mut self,
+        path: &Path,
+        key: Peek<'mem>,
+        _value: Peek<'mem>,
+    ) -> Result<VisitFlow, Self::Error> {
+        self.serializer
+            .map_key(&key.to_string())
+            .map(|()| VisitFlow::Continue)
+            .map_err(|err| format_error(path, err))
+    }
+
+    fn enter_variant(
+        &mut self,
+        path: &Path,
+        value: PeekEnum<'mem>,
+    ) -> Result<VisitFlow, Self::Error> {
+        let variant = value.variant();
+        let s = &mut *self.serializer;
+        let scope = match variant.kind {
+            VariantKind::Tuple { fields } if fields.len() == 1 => {
+                s.start_variant(variant.name).map(|()| Scope::Newtype)
+            }
+            VariantKind::Tuple { fields } => s
+                .start_variant(variant.name)
+                .and_then(|()| s.start_seq(fields.len()))
+                .map(|()| Scope::Seq),
+            VariantKind::Struct { fields } => s
+                .start_variant(variant.name)
+                .and_then(|()| s.start_struct(flattened_len(fields)))
+                .map(|()| Scope::Struct),
+            _ => s.serialize_unit_variant(variant.name).map(|()| Scope::Unit),
+        };
+        scope
+            .and_then(|scope| self.open(scope, true))
+            .map_err(|err| format_error(path, err))
+    }
+
+    fn leave_variant(&mut self, path: &Path, _value: PeekEnum<'mem>) -> Result<(), Self::Error> {
+        self.close().map_err(|err| format_error(path, err))
+    }
+
+    fn enter_option(
+        &mut self,
+        path: &Path,
+        value: PeekOption<'mem>,
+    ) -> Result<VisitFlow, Self::Error> {
+        if value.is_none() {
+            self.serializer
+                .serialize_none()
+                .map_err(|err| format_error(path, err))?;
+        }
+        Ok(VisitFlow::Continue)
+    }
+
+    fn cycle(&mut self, path: &Path, _value: Peek<'mem>) -> Result<(), Self::Error> {
+        Err(error(path, SerializeErrorKind::Cycle))
+    }
+}
+
+fn error<E>(path: &Path, kind: SerializeErrorKind<E>) -> SerializeError<E> {
+    SerializeError {
+        path: path.clone(),
+        kind,
+    }
+}
+
+fn format_error<E>(path: &Path, err: E) -> SerializeError<E> {
+    error(path, SerializeErrorKind::Format(err))
+}
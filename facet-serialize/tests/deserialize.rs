@@ -0,0 +1,386 @@
+use std::borrow::Cow;
+use std::collections::{HashMap, VecDeque};
+
+use facet_derive::Facet;
+use facet_poke::Peek;
+use facet_serialize::{
+    DeserializeErrorKind, Deserializer, Event, Serializer, deserialize, serialize,
+};
+use facet_testhelpers::Source;
+use facet_trait::Facet;
+
+use facet_trait as facet;
+
+/// Replays a list of events
+struct Events(VecDeque<Event<'static>>);
+
+impl Deserializer<'static> for Events {
+    type Error = &'static str;
+
+    fn next(&mut self) -> Result<Event<'static>, Self::Error> {
+        self.0.pop_front().ok_or("unexpected end of input")
+    }
+}
+
+/// Records events, leaving everything it can to the default methods
+#[derive(Default)]
+struct Recorder(VecDeque<Event<'static>>);
+
+impl Serializer for Recorder {
+    type Error = std::convert::Infallible;
+
+    fn serialize_bool(&mut self, value: bool) -> Result<(), Self::Error> {
+        self.0.push_back(Event::Bool(value));
+        Ok(())
+    }
+
+    fn serialize_u64(&mut self, value: u64) -> Result<(), Self::Error> {
+        self.0.push_back(Event::U64(value));
+        Ok(())
+    }
+
+    fn serialize_i64(&mut self, value: i64) -> Result<(), Self::Error> {
+        self.0.push_back(Event::I64(value));
+        Ok(())
+    }
+
+    fn serialize_f64(&mut self, value: f64) -> Result<(), Self::Error> {
+        self.0.push_back(Event::F64(value));
+        Ok(())
+    }
+
+    fn serialize_str(&mut self, value: &str) -> Result<(), Self::Error> {
+        self.0.push_back(Event::Str(Cow::Owned(value.to_string())));
+        Ok(())
+    }
+
+    fn serialize_unit(&mut self) -> Result<(), Self::Error> {
+        self.0.push_back(Event::Unit);
+        Ok(())
+    }
+
+    fn start_struct(&mut self, _len: usize) -> Result<(), Self::Error> {
+        self.0.push_back(Event::StartMap);
+        Ok(())
+    }
+
+    fn field(&mut self, name: &'static str) -> Result<(), Self::Error> {
+        self.0.push_back(Event::Key(Cow::Borrowed(name)));
+        Ok(())
+    }
+
+    fn end_struct(&mut self) -> Result<(), Self::Error> {
+        self.0.push_back(Event::EndMap);
+        Ok(())
+    }
+
+    fn start_seq(&mut self, _len: usize) -> Result<(), Self::Error> {
+        self.0.push_back(Event::StartSeq);
+        Ok(())
+    }
+
+    fn end_seq(&mut self) -> Result<(), Self::Error> {
+        self.0.push_back(Event::EndSeq);
+        Ok(())
+    }
+
+    fn start_map(&mut self, _len: usize) -> Result<(), Self::Error> {
+        self.0.push_back(Event::StartMap);
+        Ok(())
+    }
+
+    fn map_key(&mut self, key: &str) -> Result<(), Self::Error> {
+        self.0.push_back(Event::Key(Cow::Owned(key.to_string())));
+        Ok(())
+    }
+
+    fn end_map(&mut self) -> Result<(), Self::Error> {
+        self.0.push_back(Event::EndMap);
+        Ok(())
+    }
+}
+
+fn round_trip<T: Facet + PartialEq + std::fmt::Debug>(value: T) {
+    let mut recorder = Recorder::default();
+    serialize(Peek::new(&value), &mut recorder).unwrap();
+    let mut events = Events(recorder.0);
+    let back: T = deserialize(&mut events).unwrap();
+    assert_eq!(back, value);
+    assert!(events.0.is_empty(), "leftover events: {:?}", events.0);
+}
+
+/// Shorthand for a key
+fn key(name: &'static str) -> Event<'static> {
+    Event::Key(Cow::Borrowed(name))
+}
+
+/// Shorthand for a string
+fn str(value: &'static str) -> Event<'static> {
+    Event::Str(Cow::Borrowed(value))
+}
+
+fn events(events: impl IntoIterator<Item = Event<'static>>) -> Events {
+    Events(events.into_iter().collect())
+}
+
+#[derive(Debug, PartialEq, Facet)]
+struct Service {
+    #[facet(rename = "service-name")]
+    name: String,
+    port: u16,
+    weight: f32,
+    offset: i64,
+    tags: Vec<String>,
+    proxy: Option<String>,
+    #[facet(flatten)]
+    limits: Limits,
+    labels: HashMap<String, u64>,
+    source: Source,
+}
+
+#[derive(Debug, PartialEq, Facet)]
+struct Limits {
+    max_connections: u64,
+    burst: Option<u32>,
+}
+
+fn service() -> Service {
+    Service {
+        name: "api".to_string(),
+        port: 8080,
+        weight: 0.5,
+        offset: -3,
+        tags: vec!["a".to_string(), "b".to_string()],
+        proxy: None,
+        limits: Limits {
+            max_connections: 100,
+            burst: Some(10),
+        },
+        labels: HashMap::from([("tier".to_string(), 1), ("zone".to_string(), 2)]),
+        source: Source::Remote {
+            url: "https://example.com".to_string(),
+            retries: 3,
+        },
+    }
+}
+
+#[derive(Debug, PartialEq, Facet)]
+struct Meters(f64);
+
+#[derive(Debug, PartialEq, Facet)]
+struct Point(i32, i32);
+
+#[derive(Debug, PartialEq, Facet)]
+struct Marker;
+
+#[test]
+fn values_round_trip() {
+    round_trip(service());
+    round_trip(Source::Disabled);
+    round_trip(Source::File("/etc/app.toml".to_string()));
+    round_trip(Meters(1.5));
+    round_trip(Point(1, -2));
+    round_trip(Marker);
+    round_trip((7u8, "seven".to_string(), ()));
+    round_trip(vec![Some(1u8), None, Some(3)]);
+    round_trip(HashMap::from([(1u16, vec![true]), (2, vec![])]));
+    round_trip(Some(Some(u128::MAX)));
+}
+
+#[derive(Debug, PartialEq, Facet)]
+struct Version(u32, u32);
+
+impl core::str::FromStr for Version {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (major, minor) = s.split_once('.').ok_or("expected major.minor")?;
+        let number = |n: &str| n.parse().map_err(|_| format!("bad number '{n}'"));
+        Ok(Version(number(major)?, number(minor)?))
+    }
+}
+
+#[test]
+fn structs_parse_from_strings() {
+    assert_eq!(
+        deserialize::<Version, _>(&mut events([str("1.2")])),
+        Ok(Version(1, 2))
+    );
+    // they're still read from their fields as well
+    assert_eq!(
+        deserialize::<Version, _>(&mut events([
+            Event::StartSeq,
+            Event::U64(1),
+            Event::U64(2),
+            Event::EndSeq,
+        ])),
+        Ok(Version(1, 2))
+    );
+
+    let err = deserialize::<Version, _>(&mut events([str("1")])).unwrap_err();
+    assert!(matches!(err.kind, DeserializeErrorKind::InvalidValue(_)));
+}
+
+#[derive(Debug, PartialEq, Facet)]
+struct Settings {
+    name: String,
+    #[facet(default)]
+    retries: u32,
+    #[facet(default)]
+    tags: Vec<String>,
+    timeout: Option<u64>,
+}
+
+#[test]
+fn missing_fields_take_defaults() {
+    let settings: Settings = deserialize(&mut events([
+        Event::StartMap,
+        key("name"),
+        str("x"),
+        Event::EndMap,
+    ]))
+    .unwrap();
+    assert_eq!(
+        settings,
+        Settings {
+            name: "x".to_string(),
+            retries: 0,
+            tags: vec![],
+            timeout: None,
+        }
+    );
+
+    let err = deserialize::<Settings, _>(&mut events([
+        Event::StartMap,
+        key("retries"),
+        Event::U64(3),
+        Event::EndMap,
+    ]))
+    .unwrap_err();
+    assert_eq!(err.kind, DeserializeErrorKind::MissingField("name"));
+    assert_eq!(err.to_string(), "Missing field `name` for Settings");
+}
+
+#[test]
+fn scalars_are_converted() {
+    let events = |event| events([event]);
+
+    assert_eq!(deserialize::<u8, _>(&mut events(Event::I64(5))), Ok(5));
+    assert_eq!(deserialize::<i8, _>(&mut events(Event::U64(5))), Ok(5));
+    assert_eq!(deserialize::<u16, _>(&mut events(str("8080"))), Ok(8080));
+    assert_eq!(deserialize::<f64, _>(&mut events(Event::U64(2))), Ok(2.0));
+    assert_eq!(deserialize::<f32, _>(&mut events(Event::F64(0.5))), Ok(0.5));
+    assert_eq!(
+        deserialize::<core::net::SocketAddr, _>(&mut events(str("127.0.0.1:80"))),
+        Ok("127.0.0.1:80".parse().unwrap())
+    );
+
+    let err = deserialize::<u8, _>(&mut events(Event::U64(300))).unwrap_err();
+    assert!(matches!(err.kind, DeserializeErrorKind::InvalidValue(_)));
+
+    let err = deserialize::<bool, _>(&mut events(Event::U64(1))).unwrap_err();
+    assert_eq!(err.to_string(), "Can't build a bool out of an integer");
+}
+
+#[test]
+fn errors_have_paths() {
+    let err = deserialize::<Service, _>(&mut events([
+        Event::StartMap,
+        key("port"),
+        Event::U64(70000),
+    ]))
+    .unwrap_err();
+    assert!(matches!(err.kind, DeserializeErrorKind::InvalidValue(_)));
+    assert_eq!(err.path.to_string(), "port");
+
+    let err = deserialize::<Service, _>(&mut events([
+        Event::StartMap,
+        key("max_connections"),
+        Event::Bool(true),
+    ]))
+    .unwrap_err();
+    assert_eq!(err.path.to_string(), "limits.max_connections");
+
+    let err = deserialize::<Service, _>(&mut events([
+        Event::StartMap,
+        key("tags"),
+        Event::StartSeq,
+        str("a"),
+        Event::U64(1),
+    ]))
+    .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Can't build a String out of an integer at `tags[1]`"
+    );
+
+    let err = deserialize::<Service, _>(&mut events([
+        Event::StartMap,
+        key("source"),
+        Event::StartMap,
+        key("Remote"),
+        Event::StartMap,
+        key("host"),
+    ]))
+    .unwrap_err();
+    assert_eq!(
+        err.kind,
+        DeserializeErrorKind::UnknownField("host".to_string())
+    );
+    assert_eq!(
+        err.to_string(),
+        "Unknown field `host` for Source at `source`"
+    );
+
+    let err = deserialize::<Source, _>(&mut events([str("Nowhere")])).unwrap_err();
+    assert_eq!(
+        err.kind,
+        DeserializeErrorKind::UnknownVariant("Nowhere".to_string())
+    );
+
+    let err = deserialize::<Source, _>(&mut events([str("File")])).unwrap_err();
+    assert_eq!(err.to_string(), "Expected a single-key map, got a string");
+
+    let err = deserialize::<Service, _>(&mut events([Event::StartMap])).unwrap_err();
+    assert_eq!(
+        err.kind,
+        DeserializeErrorKind::Format("unexpected end of input")
+    );
+}
+
+#[derive(Debug, PartialEq, Facet)]
+struct Named {
+    name: String,
+}
+
+#[test]
+fn errors_inside_collections() {
+    // the first two items are complete and the third is partially built when the
+    // error hits; all of them are dropped along with the list
+    let err = deserialize::<Vec<Named>, _>(&mut events([
+        Event::StartSeq,
+        Event::StartMap,
+        key("name"),
+        str("a"),
+        Event::EndMap,
+        Event::StartMap,
+        key("name"),
+        str("b"),
+        Event::EndMap,
+        Event::StartMap,
+        key("name"),
+        Event::U64(3),
+    ]))
+    .unwrap_err();
+    assert_eq!(err.path.to_string(), "[2].name");
+
+    let err = deserialize::<HashMap<String, Named>, _>(&mut events([
+        Event::StartMap,
+        key("x"),
+        Event::StartMap,
+        Event::EndMap,
+    ]))
+    .unwrap_err();
+    assert_eq!(err.kind, DeserializeErrorKind::MissingField("name"));
+    assert_eq!(err.path.to_string(), r#"["x"]"#);
+}
@@ -0,0 +1,287 @@
+use std::collections::HashMap;
+
+use facet_derive::Facet;
+use facet_poke::Peek;
+use facet_serialize::{SerializeErrorKind, Serializer, serialize};
+use facet_testhelpers::Source;
+use facet_trait::Facet;
+
+use facet_trait as facet;
+
+/// Records every call as a token, failing at the token with index `fail_at`
+#[derive(Default)]
+struct Tokens {
+    tokens: Vec<Token>,
+    fail_at: Option<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Bool(bool),
+    U64(u64),
+    I64(i64),
+    F64(f64),
+    Str(String),
+    Unit,
+    None,
+    Struct(usize),
+    Field(&'static str),
+    EndStruct,
+    Seq(usize),
+    EndSeq,
+    Map(usize),
+    Key(String),
+    EndMap,
+    UnitVariant(&'static str),
+    Variant(&'static str),
+    EndVariant,
+}
+
+impl Tokens {
+    fn push(&mut self, token: Token) -> Result<(), String> {
+        if self.fail_at == Some(self.tokens.len()) {
+            return Err(format!("refusing {token:?}"));
+        }
+        self.tokens.push(token);
+        Ok(())
+    }
+}
+
+impl Serializer for Tokens {
+    type Error = String;
+
+    fn serialize_bool(&mut self, value: bool) -> Result<(), Self::Error> {
+        self.push(Token::Bool(value))
+    }
+
+    fn serialize_u64(&mut self, value: u64) -> Result<(), Self::Error> {
+        self.push(Token::U64(value))
+    }
+
+    fn serialize_i64(&mut self, value: i64) -> Result<(), Self::Error> {
+        self.push(Token::I64(value))
+    }
+
+    fn serialize_f64(&mut self, value: f64) -> Result<(), Self::Error> {
+        self.push(Token::F64(value))
+    }
+
+    fn serialize_str(&mut self, value: &str) -> Result<(), Self::Error> {
+        self.push(Token::Str(value.to_string()))
+    }
+
+    fn serialize_unit(&mut self) -> Result<(), Self::Error> {
+        self.push(Token::Unit)
+    }
+
+    fn serialize_none(&mut self) -> Result<(), Self::Error> {
+        self.push(Token::None)
+    }
+
+    fn start_struct(&mut self, len: usize) -> Result<(), Self::Error> {
+        self.push(Token::Struct(len))
+    }
+
+    fn field(&mut self, name: &'static str) -> Result<(), Self::Error> {
+        self.push(Token::Field(name))
+    }
+
+    fn end_struct(&mut self) -> Result<(), Self::Error> {
+        self.push(Token::EndStruct)
+    }
+
+    fn start_seq(&mut self, len: usize) -> Result<(), Self::Error> {
+        self.push(Token::Seq(len))
+    }
+
+    fn end_seq(&mut self) -> Result<(), Self::Error> {
+        self.push(Token::EndSeq)
+    }
+
+    fn start_map(&mut self, len: usize) -> Result<(), Self::Error> {
+        self.push(Token::Map(len))
+    }
+
+    fn map_key(&mut self, key: &str) -> Result<(), Self::Error> {
+        self.push(Token::Key(key.to_string()))
+    }
+
+    fn end_map(&mut self) -> Result<(), Self::Error> {
+        self.push(Token::EndMap)
+    }
+
+    fn serialize_unit_variant(&mut self, name: &'static str) -> Result<(), Self::Error> {
+        self.push(Token::UnitVariant(name))
+    }
+
+    fn start_variant(&mut self, name: &'static str) -> Result<(), Self::Error> {
+        self.push(Token::Variant(name))
+    }
+
+    fn end_variant(&mut self) -> Result<(), Self::Error> {
+        self.push(Token::EndVariant)
+    }
+}
+
+fn tokens<T: Facet>(value: &T) -> Vec<Token> {
+    let mut tokens = Tokens::default();
+    serialize(Peek::new(value), &mut tokens).unwrap();
+    tokens.tokens
+}
+
+#[derive(Facet)]
+struct Service {
+    #[facet(rename = "service-name")]
+    name: String,
+    port: u16,
+    weight: f32,
+    offset: i64,
+    tags: Vec<String>,
+    proxy: Option<String>,
+    #[facet(flatten)]
+    limits: Limits,
+    labels: HashMap<String, u64>,
+    source: Source,
+}
+
+#[derive(Facet)]
+struct Limits {
+    max_connections: u64,
+    burst: Option<u32>,
+}
+
+fn service() -> Service {
+    Service {
+        name: "api".to_string(),
+        port: 8080,
+        weight: 0.5,
+        offset: -3,
+        tags: vec!["a".to_string(), "b".to_string()],
+        proxy: None,
+        limits: Limits {
+            max_connections: 100,
+            burst: Some(10),
+        },
+        labels: HashMap::from([("tier".to_string(), 1)]),
+        source: Source::Remote {
+            url: "https://example.com".to_string(),
+            retries: 3,
+        },
+    }
+}
+
+#[test]
+fn structs_are_fields_in_order() {
+    use Token::*;
+
+    assert_eq!(
+        tokens(&service()),
+        [
+            Struct(10),
+            Field("service-name"),
+            Str("api".to_string()),
+            Field("port"),
+            U64(8080),
+            Field("weight"),
+            F64(0.5),
+            Field("offset"),
+            I64(-3),
+            Field("tags"),
+            Seq(2),
+            Str("a".to_string()),
+            Str("b".to_string()),
+            EndSeq,
+            Field("proxy"),
+            None,
+            Field("max_connections"),
+            U64(100),
+            Field("burst"),
+            U64(10),
+            Field("labels"),
+            Map(1),
+            Key("tier".to_string()),
+            U64(1),
+            EndMap,
+            Field("source"),
+            Variant("Remote"),
+            Struct(2),
+            Field("url"),
+            Str("https://example.com".to_string()),
+            Field("retries"),
+            U64(3),
+            EndStruct,
+            EndVariant,
+            EndStruct,
+        ]
+    );
+}
+
+#[test]
+fn enum_variants() {
+    use Token::*;
+
+    assert_eq!(tokens(&Source::Disabled), [UnitVariant("Disabled")]);
+    assert_eq!(
+        tokens(&Source::File("/etc/app.toml".to_string())),
+        [
+            Variant("File"),
+            Str("/etc/app.toml".to_string()),
+            EndVariant
+        ]
+    );
+}
+
+#[derive(Facet)]
+struct Meters(f64);
+
+#[derive(Facet)]
+struct Point(i32, i32);
+
+#[derive(Facet)]
+struct Marker;
+
+#[test]
+fn tuples_newtypes_and_units() {
+    use Token::*;
+
+    assert_eq!(tokens(&Meters(1.5)), [F64(1.5)]);
+    assert_eq!(tokens(&Point(1, -2)), [Seq(2), I64(1), I64(-2), EndSeq]);
+    assert_eq!(
+        tokens(&(7u8, "seven".to_string())),
+        [Seq(2), U64(7), Str("seven".to_string()), EndSeq]
+    );
+    assert_eq!(tokens(&Marker), [Unit]);
+    assert_eq!(tokens(&()), [Unit]);
+    assert_eq!(tokens(&Some(Some(true))), [Bool(true)]);
+}
+
+#[test]
+fn scalars_outside_the_data_model() {
+    use Token::*;
+
+    assert_eq!(tokens(&(u64::MAX as u128)), [U64(u64::MAX)]);
+    assert_eq!(
+        tokens(&u128::MAX),
+        [Str("340282366920938463463374607431768211455".to_string())]
+    );
+    assert_eq!(tokens(&-1i128), [I64(-1)]);
+
+    let addr: core::net::SocketAddr = "127.0.0.1:80".parse().unwrap();
+    assert_eq!(tokens(&addr), [Str("127.0.0.1:80".to_string())]);
+}
+
+#[test]
+fn errors_stop_serialization() {
+    let mut tokens = Tokens {
+        fail_at: Some(4),
+        ..Default::default()
+    };
+    let err = serialize(Peek::new(&service()), &mut tokens).unwrap_err();
+    assert_eq!(
+        err.kind,
+        SerializeErrorKind::Format("refusing U64(8080)".to_string())
+    );
+    assert_eq!(err.path.to_string(), "port");
+    assert_eq!(err.to_string(), "refusing U64(8080) at `port`");
+    assert_eq!(tokens.tokens.len(), 4);
+}
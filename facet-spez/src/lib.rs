@@ -1,8 +1,9 @@
+#![cfg_attr(not(feature = "std"), no_std)]
 #![warn(missing_docs)]
 #![doc = include_str!("../README.md")]
 pub use ::impls::impls;
 use core::fmt::{self, Debug};
-use facet_types::ParseError;
+use facet_types::{ErrorMessage, ParseError};
 
 use facet_opaque::{Opaque, OpaqueUninit};
 
@@ -152,20 +153,46 @@ impl<T> SpezCloneIntoNo for Spez<T> {
 pub trait SpezParseYes {
     /// Parses a string slice into the inner type.
     ///
-    /// This method is called when the wrapped type implements `FromStr`.
-    /// It attempts to parse the provided string and write the result into the target memory.
+    /// This method is called when the wrapped type implements `FromStr` and its error
+    /// implements `Display`. It attempts to parse the provided string and write the result
+    /// into the target memory, keeping the error message on failure.
     fn spez_parse(&self, s: &str, target: OpaqueUninit) -> Result<(), ParseError>;
 }
-impl<T: core::str::FromStr> SpezParseYes for &Spez<T> {
+impl<T> SpezParseYes for &&Spez<T>
+where
+    T: core::str::FromStr,
+    T::Err: core::fmt::Display,
+{
     fn spez_parse(&self, s: &str, target: OpaqueUninit) -> Result<(), ParseError> {
         match <T as core::str::FromStr>::from_str(s) {
             Ok(value) => {
                 unsafe { target.write(value) };
                 Ok(())
             }
-            Err(_) => Err(ParseError::Generic(
-                const { concat!("parse error for ", stringify!(T)) },
-            )),
+            Err(e) => Err(ParseError::from_display(&e)),
+        }
+    }
+}
+
+/// Specialization proxy for [`core::str::FromStr`]
+pub trait SpezParseOpaqueErr {
+    /// Parses a string slice into the inner type.
+    ///
+    /// This method is called when the wrapped type implements `FromStr` but its error
+    /// doesn't implement `Display`, so failures carry a generic message.
+    fn spez_parse(&self, s: &str, target: OpaqueUninit) -> Result<(), ParseError>;
+}
+impl<T> SpezParseOpaqueErr for &Spez<T>
+where
+    T: core::str::FromStr,
+{
+    fn spez_parse(&self, s: &str, target: OpaqueUninit) -> Result<(), ParseError> {
+        match <T as core::str::FromStr>::from_str(s) {
+            Ok(value) => {
+                unsafe { target.write(value) };
+                Ok(())
+            }
+            Err(_) => Err(ParseError::new("failed to parse string")),
         }
     }
 }
@@ -184,6 +211,34 @@ impl<T> SpezParseNo for Spez<T> {
     }
 }
 
+//////////////////////////////////////////////////////////////////////////////////////
+// Error message 💬❗
+//////////////////////////////////////////////////////////////////////////////////////
+
+/// Specialization proxy for rendering an error through [`core::fmt::Display`]
+pub trait SpezErrorMessageYes {
+    /// Renders the inner error into an [`ErrorMessage`].
+    ///
+    /// This method is called when the wrapped error implements `Display`.
+    fn spez_error_message(&self, fallback: &'static str) -> ErrorMessage;
+}
+impl<T: core::fmt::Display> SpezErrorMessageYes for &Spez<T> {
+    fn spez_error_message(&self, fallback: &'static str) -> ErrorMessage {
+        ErrorMessage::from_display(&self.0, fallback)
+    }
+}
+
+/// Specialization proxy for rendering an error through [`core::fmt::Display`]
+pub trait SpezErrorMessageNo {
+    /// Fallback when the wrapped error doesn't implement `Display`: returns `fallback`.
+    fn spez_error_message(&self, fallback: &'static str) -> ErrorMessage;
+}
+impl<T> SpezErrorMessageNo for Spez<T> {
+    fn spez_error_message(&self, fallback: &'static str) -> ErrorMessage {
+        ErrorMessage::Static(fallback)
+    }
+}
+
 //////////////////////////////////////////////////////////////////////////////////////
 // PartialEq 🟰🤝
 //////////////////////////////////////////////////////////////////////////////////////
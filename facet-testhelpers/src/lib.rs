@@ -0,0 +1,122 @@
+//! Types shared by the integration tests of the facet crates
+
+use facet_trait::{
+    Def, EnumDef, EnumRepr, Facet, Field, FieldFlags, ScalarDef, Shape, ValueVTable, Variant,
+    VariantKind,
+};
+
+use facet_trait as facet;
+
+/// The derive macro doesn't handle enums with fields yet, so the shape is written by hand.
+#[derive(Debug, PartialEq)]
+#[repr(u8)]
+pub enum Source {
+    Disabled,
+    File(String),
+    Remote { url: String, retries: u64 },
+}
+
+// A `#[repr(u8)]` enum is laid out as a union of `#[repr(C)]` structs, one per variant,
+// each starting with the tag.
+#[repr(C)]
+struct FileLayout {
+    _tag: u8,
+    path: String,
+}
+
+#[repr(C)]
+struct RemoteLayout {
+    _tag: u8,
+    url: String,
+    retries: u64,
+}
+
+unsafe impl Facet for Source {
+    const ARCHETYPE: Self = Source::Disabled;
+    const SHAPE: &'static Shape = &const {
+        Shape::builder()
+            .layout(core::alloc::Layout::new::<Self>())
+            .vtable(facet::value_vtable!(Source, |f, _opts| {
+                core::fmt::Write::write_str(f, "Source")
+            }))
+            .def(Def::Enum(
+                EnumDef::builder()
+                    .repr(EnumRepr::U8)
+                    .variants(
+                        &const {
+                            [
+                                Variant::builder()
+                                    .name("Disabled")
+                                    .discriminant(Some(0))
+                                    .kind(VariantKind::Unit)
+                                    .build(),
+                                Variant::builder()
+                                    .name("File")
+                                    .discriminant(Some(1))
+                                    .kind(VariantKind::Tuple {
+                                        fields: &const {
+                                            [Field::builder()
+                                                .name("0")
+                                                .shape(String::SHAPE)
+                                                .offset(core::mem::offset_of!(FileLayout, path))
+                                                .flags(FieldFlags::EMPTY)
+                                                .build()]
+                                        },
+                                    })
+                                    .build(),
+                                Variant::builder()
+                                    .name("Remote")
+                                    .discriminant(Some(2))
+                                    .kind(VariantKind::Struct {
+                                        fields: &const {
+                                            [
+                                                Field::builder()
+                                                    .name("url")
+                                                    .shape(String::SHAPE)
+                                                    .offset(core::mem::offset_of!(
+                                                        RemoteLayout,
+                                                        url
+                                                    ))
+                                                    .flags(FieldFlags::EMPTY)
+                                                    .build(),
+                                                Field::builder()
+                                                    .name("retries")
+                                                    .shape(u64::SHAPE)
+                                                    .offset(core::mem::offset_of!(
+                                                        RemoteLayout,
+                                                        retries
+                                                    ))
+                                                    .flags(FieldFlags::EMPTY)
+                                                    .build(),
+                                            ]
+                                        },
+                                    })
+                                    .build(),
+                            ]
+                        },
+                    )
+                    .build(),
+            ))
+            .build()
+    };
+}
+
+/// A scalar with neither `PartialEq` nor `Debug`
+pub struct Handle(pub u32);
+
+unsafe impl Facet for Handle {
+    const ARCHETYPE: Self = Handle(0);
+    const SHAPE: &'static Shape = &const {
+        Shape::builder()
+            .layout(core::alloc::Layout::new::<Self>())
+            .def(Def::Scalar(ScalarDef::of::<Self>()))
+            .vtable(
+                &const {
+                    ValueVTable::builder()
+                        .type_name(|f, _opts| write!(f, "Handle"))
+                        .build()
+                },
+            )
+            .build()
+    };
+}
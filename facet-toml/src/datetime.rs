@@ -0,0 +1,277 @@
+use core::alloc::Layout;
+use core::fmt;
+use core::str::FromStr;
+
+use facet_trait::{Def, Facet, ScalarDef, Shape, value_vtable};
+
+/// A TOML date, time, or both, with or without an offset
+///
+/// TOML has four kinds of datetimes, told apart by which parts are set:
+///
+///   * an offset datetime, like `1979-05-27T07:32:00Z`, has all three
+///   * a local datetime, like `1979-05-27T07:32:00`, has no offset
+///   * a local date, like `1979-05-27`, only has a date
+///   * a local time, like `07:32:00`, only has a time
+///
+/// It parses from and displays as RFC 3339, which is how it's read from and written to
+/// TOML documents — and how it goes through other formats, as a string.
+///
+/// ```rust
+/// use facet_toml::{Date, Datetime, Offset};
+///
+/// let datetime: Datetime = "1979-05-27 07:32:00.5-07:00".parse().unwrap();
+/// assert_eq!(datetime.date, Some(Date { year: 1979, month: 5, day: 27 }));
+/// assert_eq!(datetime.offset, Some(Offset::Custom { minutes: -420 }));
+/// assert_eq!(datetime.to_string(), "1979-05-27T07:32:00.5-07:00");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Datetime {
+    /// The date, unless this is a local time
+    pub date: Option<Date>,
+    /// The time, unless this is a local date
+    pub time: Option<Time>,
+    /// The offset from UTC, for offset datetimes
+    pub offset: Option<Offset>,
+}
+
+/// The date of a [`Datetime`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Date {
+    /// The year, from 0 to 9999
+    pub year: u16,
+    /// The month, from 1 to 12
+    pub month: u8,
+    /// The day of the month, from 1 to 31
+    pub day: u8,
+}
+
+/// The time of a [`Datetime`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Time {
+    /// The hour, from 0 to 23
+    pub hour: u8,
+    /// The minute, from 0 to 59
+    pub minute: u8,
+    /// The second, from 0 to 60 to allow for leap seconds
+    pub second: u8,
+    /// The fraction of the second, in nanoseconds: digits past the ninth are dropped
+    pub nanosecond: u32,
+}
+
+/// The offset from UTC of a [`Datetime`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Offset {
+    /// UTC, written `Z`
+    Z,
+    /// Any other offset, written `+hh:mm` or `-hh:mm`
+    Custom {
+        /// The offset in minutes, negative west of Greenwich
+        minutes: i16,
+    },
+}
+
+/// Returned when a string isn't an RFC 3339 date, time or datetime
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DatetimeParseError(pub(crate) &'static str);
+
+impl fmt::Display for DatetimeParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.0)
+    }
+}
+
+impl core::error::Error for DatetimeParseError {}
+
+impl FromStr for Datetime {
+    type Err = DatetimeParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut datetime = Datetime {
+            date: None,
+            time: None,
+            offset: None,
+        };
+        let mut rest = s.as_bytes();
+        if rest.len() >= 10 && rest[4] == b'-' {
+            datetime.date = Some(date(&rest[..10])?);
+            rest = &rest[10..];
+            match rest {
+                [] => return Ok(datetime),
+                [b'T' | b't' | b' ', time @ ..] => rest = time,
+                _ => return Err(DatetimeParseError("expected `T` between the date and time")),
+            }
+        }
+
+        if rest.len() < 8 {
+            return Err(DatetimeParseError("expected a time, as `hh:mm:ss`"));
+        }
+        let (mut time, after) = (time(&rest[..8])?, &rest[8..]);
+        rest = after;
+        if let [b'.', fraction @ ..] = rest {
+            let digits = fraction.iter().take_while(|b| b.is_ascii_digit()).count();
+            if digits == 0 {
+                return Err(DatetimeParseError(
+                    "expected digits after the decimal point",
+                ));
+            }
+            let mut scale = 100_000_000;
+            for digit in &fraction[..digits.min(9)] {
+                time.nanosecond += u32::from(digit - b'0') * scale;
+                scale /= 10;
+            }
+            rest = &fraction[digits..];
+        }
+        datetime.time = Some(time);
+
+        if rest.is_empty() {
+            return Ok(datetime);
+        }
+        if datetime.date.is_none() {
+            return Err(DatetimeParseError(
+                "a time without a date can't have an offset",
+            ));
+        }
+        datetime.offset = Some(match rest {
+            [b'Z' | b'z'] => Offset::Z,
+            [sign @ (b'+' | b'-'), h1, h2, b':', m1, m2] => {
+                let hours = two_digits(*h1, *h2).filter(|h| *h < 24);
+                let minutes = two_digits(*m1, *m2).filter(|m| *m < 60);
+                let (Some(hours), Some(minutes)) = (hours, minutes) else {
+                    return Err(DatetimeParseError("invalid offset"));
+                };
+                let minutes = i16::from(hours) * 60 + i16::from(minutes);
+                Offset::Custom {
+                    minutes: if *sign == b'-' { -minutes } else { minutes },
+                }
+            }
+            _ => return Err(DatetimeParseError("expected an offset, as `Z` or `+hh:mm`")),
+        });
+        Ok(datetime)
+    }
+}
+
+/// Parses two ASCII digits
+fn two_digits(tens: u8, units: u8) -> Option<u8> {
+    (tens.is_ascii_digit() && units.is_ascii_digit()).then(|| (tens - b'0') * 10 + units - b'0')
+}
+
+/// Parses `YYYY-MM-DD`
+fn date(s: &[u8]) -> Result<Date, DatetimeParseError> {
+    let invalid = DatetimeParseError("expected a date, as `YYYY-MM-DD`");
+    let [y1, y2, y3, y4, b'-', m1, m2, b'-', d1, d2] = *s else {
+        return Err(invalid);
+    };
+    let (Some(century), Some(year), Some(month), Some(day)) = (
+        two_digits(y1, y2),
+        two_digits(y3, y4),
+        two_digits(m1, m2),
+        two_digits(d1, d2),
+    ) else {
+        return Err(invalid);
+    };
+    let year = u16::from(century) * 100 + u16::from(year);
+    if !(1..=12).contains(&month) {
+        return Err(DatetimeParseError("the month must be between 01 and 12"));
+    }
+    let leap = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+    let days = match month {
+        2 if leap => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    };
+    if day == 0 || day > days {
+        return Err(DatetimeParseError("the day doesn't exist in that month"));
+    }
+    Ok(Date { year, month, day })
+}
+
+/// Parses `hh:mm:ss`
+fn time(s: &[u8]) -> Result<Time, DatetimeParseError> {
+    let invalid = DatetimeParseError("expected a time, as `hh:mm:ss`");
+    let [h1, h2, b':', m1, m2, b':', s1, s2] = *s else {
+        return Err(invalid);
+    };
+    let (Some(hour), Some(minute), Some(second)) =
+        (two_digits(h1, h2), two_digits(m1, m2), two_digits(s1, s2))
+    else {
+        return Err(invalid);
+    };
+    if hour > 23 || minute > 59 || second > 60 {
+        return Err(DatetimeParseError("the time is out of range"));
+    }
+    Ok(Time {
+        hour,
+        minute,
+        second,
+        nanosecond: 0,
+    })
+}
+
+impl fmt::Display for Datetime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(date) = &self.date {
+            write!(f, "{date}")?;
+            if self.time.is_some() {
+                f.write_str("T")?;
+            }
+        }
+        if let Some(time) = &self.time {
+            write!(f, "{time}")?;
+        }
+        if let Some(offset) = &self.offset {
+            write!(f, "{offset}")?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+impl fmt::Display for Time {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:02}:{:02}:{:02}", self.hour, self.minute, self.second)?;
+        if self.nanosecond > 0 {
+            let mut nanosecond = self.nanosecond;
+            let mut digits = 9;
+            while nanosecond % 10 == 0 {
+                nanosecond /= 10;
+                digits -= 1;
+            }
+            write!(f, ".{nanosecond:0digits$}")?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Offset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Offset::Z => f.write_str("Z"),
+            Offset::Custom { minutes } => {
+                let sign = if minutes < 0 { '-' } else { '+' };
+                let minutes = minutes.unsigned_abs();
+                write!(f, "{sign}{:02}:{:02}", minutes / 60, minutes % 60)
+            }
+        }
+    }
+}
+
+unsafe impl Facet for Datetime {
+    const ARCHETYPE: Self = Datetime {
+        date: None,
+        time: None,
+        offset: None,
+    };
+    const SHAPE: &'static Shape = &const {
+        Shape::builder()
+            .layout(Layout::new::<Self>())
+            .def(Def::Scalar(ScalarDef::of::<Self>()))
+            .vtable(value_vtable!(Datetime, |f, _opts| write!(f, "Datetime")))
+            .build()
+    };
+}
@@ -0,0 +1,81 @@
+use alloc::string::String;
+use core::convert::Infallible;
+use core::fmt;
+
+use facet_serialize::DeserializeError;
+
+/// Returned by [`crate::from_str`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReadError {
+    /// The line of the problem, counting from 1
+    pub line: usize,
+    /// The column of the problem, in characters, counting from 1
+    pub column: usize,
+    /// What went wrong
+    pub kind: ReadErrorKind,
+}
+
+/// What went wrong while reading a TOML document
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum ReadErrorKind {
+    /// The document isn't valid TOML
+    Syntax(&'static str),
+    /// A key is defined twice, or a table is defined again
+    DuplicateKey(String),
+    /// A number doesn't fit in TOML's 64-bit integers, or isn't a valid float
+    InvalidNumber(String),
+    /// The document is valid TOML, but it doesn't describe the type being built: the
+    /// error has the path to the offending field
+    Value(DeserializeError<Infallible>),
+}
+
+impl ReadError {
+    /// Locates the byte `offset` of `input`
+    pub(crate) fn new(input: &str, offset: usize, kind: ReadErrorKind) -> Self {
+        let before = &input[..offset.min(input.len())];
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+        Self {
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+            kind,
+        }
+    }
+}
+
+impl fmt::Display for ReadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}, column {}: ", self.line, self.column)?;
+        match &self.kind {
+            ReadErrorKind::Syntax(message) => write!(f, "{message}"),
+            ReadErrorKind::DuplicateKey(key) => write!(f, "`{key}` is defined twice"),
+            ReadErrorKind::InvalidNumber(number) => write!(f, "Invalid number `{number}`"),
+            ReadErrorKind::Value(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+impl core::error::Error for ReadError {}
+
+/// What went wrong while writing a TOML document, returned by [`crate::to_string`] along
+/// with the path to the offending value
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum WriteError {
+    /// A TOML document is a table, so only structs and maps can be written as one
+    NotATable,
+    /// TOML has no null: `None` and `()` can be left out of a table, but not out of an
+    /// array
+    NullInArray,
+}
+
+impl fmt::Display for WriteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WriteError::NotATable => write!(f, "A TOML document must be a table"),
+            WriteError::NullInArray => write!(f, "TOML arrays can't hold `None` or `()`"),
+        }
+    }
+}
+
+impl core::error::Error for WriteError {}
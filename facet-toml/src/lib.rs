@@ -0,0 +1,98 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+#![warn(missing_docs)]
+#![doc = include_str!("../README.md")]
+
+//! TOML documents, read into and written from typed values
+//!
+//! [`from_str`] and [`to_string`] go through the data model of [`facet_serialize`]: a
+//! document is a table, so the value is a struct or a map. Tables, arrays of tables,
+//! inline tables and dotted keys are all read into the same nested structs, and
+//! [`Datetime`] holds TOML's dates and times.
+//!
+//! TOML has no null: `None` fields are left out when writing, and missing `Option` fields
+//! are `None` when reading.
+
+extern crate alloc;
+
+use alloc::string::String;
+
+use facet_poke::Peek;
+use facet_serialize::{SerializeError, deserialize, serialize};
+use facet_trait::Facet;
+
+mod datetime;
+pub use datetime::*;
+
+mod error;
+pub use error::*;
+
+mod parse;
+mod read;
+mod write;
+
+/// Reads a `T` out of a TOML document
+///
+/// ```rust
+/// use facet_derive::Facet;
+/// use facet_trait::{self as facet, Facet};
+/// use facet_toml::Datetime;
+///
+/// #[derive(Debug, Facet)]
+/// struct Config {
+///     name: String,
+///     released: Datetime,
+///     server: Server,
+/// }
+///
+/// #[derive(Debug, Facet)]
+/// struct Server {
+///     host: String,
+///     port: u16,
+/// }
+///
+/// let config: Config = facet_toml::from_str(
+///     r#"
+///     name = "demo"
+///     released = 1979-05-27
+///
+///     [server]
+///     host = "localhost"
+///     port = 8080
+///     "#,
+/// )
+/// .unwrap();
+/// assert_eq!(config.server.port, 8080);
+/// assert_eq!(config.released.to_string(), "1979-05-27");
+///
+/// let toml = "name = \"demo\"\nreleased = 1979-05-27\nserver.host = \"localhost\"\nserver.port = 80000";
+/// let err = facet_toml::from_str::<Config>(toml).unwrap_err();
+/// assert_eq!((err.line, err.column), (4, 15));
+/// assert!(err.to_string().ends_with("at `server.port`"));
+/// ```
+///
+/// # Errors
+///
+/// Returns an error if the document isn't valid TOML, or if it doesn't describe a `T`,
+/// with the line and column of the offending value.
+pub fn from_str<T: Facet>(toml: &str) -> Result<T, ReadError> {
+    let root = parse::parse(toml).map_err(|(offset, kind)| ReadError::new(toml, offset, kind))?;
+    let mut events = read::Events::new(&root);
+    deserialize::<T, _>(&mut events)
+        .map_err(|err| ReadError::new(toml, events.offset, ReadErrorKind::Value(err)))
+}
+
+/// Writes `value` as a TOML document
+///
+/// Structs and maps become tables, with their scalar and array entries first, then a
+/// `[section]` for each of their tables and one `[[section]]` per item of their arrays
+/// of tables.
+///
+/// # Errors
+///
+/// Returns an error if the value isn't a table, or if an array holds a `None`, with the
+/// path to the offending value.
+pub fn to_string<T: Facet>(value: &T) -> Result<String, SerializeError<WriteError>> {
+    let mut builder = write::Builder::default();
+    serialize(Peek::new(value), &mut builder)?;
+    Ok(builder.finish())
+}
@@ -0,0 +1,649 @@
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use crate::{Datetime, ReadErrorKind};
+
+/// A value of a TOML document, along with the byte offset it starts at
+pub(crate) struct Item {
+    pub(crate) value: Value,
+    pub(crate) offset: usize,
+}
+
+pub(crate) enum Value {
+    String(String),
+    Integer(i64),
+    Float(f64),
+    Bool(bool),
+    Datetime(Datetime),
+    Array {
+        items: Vec<Item>,
+        /// The array was built by `[[headers]]`, and more tables can be appended to it
+        of_tables: bool,
+    },
+    Table(Table),
+}
+
+pub(crate) struct Table {
+    pub(crate) entries: Vec<(Key, Item)>,
+    kind: TableKind,
+}
+
+/// How a table was defined, which decides whether it can be extended further down the
+/// document
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum TableKind {
+    /// Created on the way to a `[header]`, it can still get a header of its own
+    Implicit,
+    /// Defined by a `[header]`
+    Header,
+    /// Created by a dotted key, more dotted keys can extend it
+    Dotted,
+    /// An inline table, which is complete
+    Inline,
+}
+
+#[derive(Clone)]
+pub(crate) struct Key {
+    pub(crate) name: String,
+    pub(crate) offset: usize,
+}
+
+/// An error, and the byte offset it's at
+pub(crate) type Error = (usize, ReadErrorKind);
+
+impl Table {
+    fn new(kind: TableKind) -> Self {
+        Self {
+            entries: Vec::new(),
+            kind,
+        }
+    }
+
+    fn position(&self, name: &str) -> Option<usize> {
+        self.entries.iter().position(|(key, _)| key.name == name)
+    }
+
+    /// Returns the table at `key`, creating it if needed, to define something under it:
+    /// a `[header]` can go through any table but inline ones, and through the last table
+    /// of an array of tables, while a dotted key can only go through tables made by
+    /// dotted keys
+    fn descend(&mut self, key: &Key, path: &[Key], header: bool) -> Result<&mut Table, Error> {
+        let index = match self.position(&key.name) {
+            Some(index) => index,
+            None => {
+                let kind = if header {
+                    TableKind::Implicit
+                } else {
+                    TableKind::Dotted
+                };
+                self.entries.push((
+                    key.clone(),
+                    Item {
+                        value: Value::Table(Table::new(kind)),
+                        offset: key.offset,
+                    },
+                ));
+                self.entries.len() - 1
+            }
+        };
+        match &mut self.entries[index].1.value {
+            Value::Table(table) if table.kind == TableKind::Dotted || header => {
+                if table.kind != TableKind::Inline {
+                    return Ok(table);
+                }
+            }
+            Value::Array {
+                items,
+                of_tables: true,
+            } if header => {
+                if let Some(Item {
+                    value: Value::Table(table),
+                    ..
+                }) = items.last_mut()
+                {
+                    return Ok(table);
+                }
+            }
+            _ => {}
+        }
+        Err(duplicate(key, path))
+    }
+
+    /// Inserts `item` at the dotted key `path`, relative to this table
+    fn insert(&mut self, path: &[Key], item: Item) -> Result<(), Error> {
+        let (last, parents) = path.split_last().expect("keys aren't empty");
+        let mut table = self;
+        for key in parents {
+            table = table.descend(key, path, false)?;
+        }
+        if table.position(&last.name).is_some() {
+            return Err(duplicate(last, path));
+        }
+        table.entries.push((last.clone(), item));
+        Ok(())
+    }
+
+    /// Returns the table of the `[header]` at `path`, which was defined before
+    fn at(&mut self, path: &[Key]) -> &mut Table {
+        let Some((first, rest)) = path.split_first() else {
+            return self;
+        };
+        let index = self
+            .position(&first.name)
+            .expect("headers define their tables");
+        match &mut self.entries[index].1.value {
+            Value::Table(table) => table.at(rest),
+            Value::Array { items, .. } => match items.last_mut() {
+                Some(Item {
+                    value: Value::Table(table),
+                    ..
+                }) => table.at(rest),
+                _ => unreachable!("arrays of tables hold tables"),
+            },
+            _ => unreachable!("headers define tables"),
+        }
+    }
+}
+
+fn duplicate(key: &Key, path: &[Key]) -> Error {
+    let path: Vec<&str> = path.iter().map(|key| key.name.as_str()).collect();
+    (key.offset, ReadErrorKind::DuplicateKey(path.join(".")))
+}
+
+/// Parses a TOML document into its root table
+pub(crate) fn parse(input: &str) -> Result<Table, Error> {
+    let mut parser = Parser { input, pos: 0 };
+    let mut root = Table::new(TableKind::Header);
+    let mut header = Vec::new();
+    loop {
+        parser.blank_lines();
+        match parser.peek() {
+            None => return Ok(root),
+            Some(b'[') => header = parser.header(&mut root)?,
+            Some(_) => {
+                let keys = parser.key()?;
+                parser.eat(b'=', "expected `=` after a key")?;
+                parser.spaces();
+                let item = parser.value()?;
+                root.at(&header).insert(&keys, item)?;
+            }
+        }
+        parser.end_of_line()?;
+    }
+}
+
+struct Parser<'input> {
+    input: &'input str,
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn error(&self, message: &'static str) -> Error {
+        (self.pos, ReadErrorKind::Syntax(message))
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.input.as_bytes().get(self.pos).copied()
+    }
+
+    fn rest(&self) -> &str {
+        &self.input[self.pos..]
+    }
+
+    fn spaces(&mut self) {
+        while matches!(self.peek(), Some(b' ' | b'\t')) {
+            self.pos += 1;
+        }
+    }
+
+    fn eat(&mut self, byte: u8, message: &'static str) -> Result<(), Error> {
+        self.spaces();
+        if self.peek() != Some(byte) {
+            return Err(self.error(message));
+        }
+        self.pos += 1;
+        Ok(())
+    }
+
+    /// Skips a comment, up to the end of the line
+    fn comment(&mut self) {
+        if self.peek() == Some(b'#') {
+            self.pos = self
+                .rest()
+                .find('\n')
+                .map_or(self.input.len(), |i| self.pos + i);
+        }
+    }
+
+    /// Skips a line break, returning false if there's none
+    fn newline(&mut self) -> bool {
+        if self.rest().starts_with('\n') {
+            self.pos += 1;
+        } else if self.rest().starts_with("\r\n") {
+            self.pos += 2;
+        } else {
+            return false;
+        }
+        true
+    }
+
+    /// Skips whitespace, comments and line breaks
+    fn blank_lines(&mut self) {
+        loop {
+            self.spaces();
+            self.comment();
+            if !self.newline() {
+                return;
+            }
+        }
+    }
+
+    fn end_of_line(&mut self) -> Result<(), Error> {
+        self.spaces();
+        self.comment();
+        if self.peek().is_some() && !self.newline() {
+            return Err(self.error("expected the end of the line"));
+        }
+        Ok(())
+    }
+
+    /// Parses a `[table]` or `[[array.of.tables]]` header, and creates what it defines
+    fn header(&mut self, root: &mut Table) -> Result<Vec<Key>, Error> {
+        self.pos += 1;
+        let array = self.peek() == Some(b'[');
+        if array {
+            self.pos += 1;
+        }
+        self.spaces();
+        let path = self.key()?;
+        if array {
+            self.eat(b']', "expected `]]` after the name of the array of tables")?;
+            if self.peek() != Some(b']') {
+                return Err(self.error("expected `]]` after the name of the array of tables"));
+            }
+            self.pos += 1;
+        } else {
+            self.eat(b']', "expected `]` after the name of the table")?;
+        }
+
+        let (last, parents) = path.split_last().expect("keys aren't empty");
+        let mut table = root;
+        for key in parents {
+            table = table.descend(key, &path, true)?;
+        }
+        let new_table = || Item {
+            value: Value::Table(Table::new(TableKind::Header)),
+            offset: last.offset,
+        };
+        match table.position(&last.name) {
+            None if array => table.entries.push((
+                last.clone(),
+                Item {
+                    value: Value::Array {
+                        items: Vec::from([new_table()]),
+                        of_tables: true,
+                    },
+                    offset: last.offset,
+                },
+            )),
+            None => table.entries.push((last.clone(), new_table())),
+            Some(index) => match &mut table.entries[index].1.value {
+                Value::Array {
+                    items,
+                    of_tables: true,
+                } if array => items.push(new_table()),
+                Value::Table(table) if !array && table.kind == TableKind::Implicit => {
+                    table.kind = TableKind::Header;
+                }
+                _ => return Err(duplicate(last, &path)),
+            },
+        }
+        Ok(path)
+    }
+
+    /// Parses a key, dotted or not, and the spaces after it
+    fn key(&mut self) -> Result<Vec<Key>, Error> {
+        let mut keys = Vec::new();
+        loop {
+            let offset = self.pos;
+            let name = match self.peek() {
+                Some(b'"') if self.rest().starts_with("\"\"\"") => {
+                    return Err(self.error("a key can't be a multi-line string"));
+                }
+                Some(b'\'') if self.rest().starts_with("'''") => {
+                    return Err(self.error("a key can't be a multi-line string"));
+                }
+                Some(b'"') => self.basic_string()?,
+                Some(b'\'') => self.literal_string()?,
+                _ => {
+                    let len = self
+                        .rest()
+                        .bytes()
+                        .take_while(|b| b.is_ascii_alphanumeric() || matches!(b, b'_' | b'-'))
+                        .count();
+                    if len == 0 {
+                        return Err(self.error("expected a key"));
+                    }
+                    self.pos += len;
+                    self.input[offset..self.pos].to_string()
+                }
+            };
+            keys.push(Key { name, offset });
+            self.spaces();
+            if self.peek() != Some(b'.') {
+                return Ok(keys);
+            }
+            self.pos += 1;
+            self.spaces();
+        }
+    }
+
+    fn value(&mut self) -> Result<Item, Error> {
+        let offset = self.pos;
+        let value = match self.peek() {
+            Some(b'"') => Value::String(self.basic_string()?),
+            Some(b'\'') => Value::String(self.literal_string()?),
+            Some(b'[') => {
+                self.pos += 1;
+                let mut items = Vec::new();
+                loop {
+                    self.blank_lines();
+                    if self.peek() == Some(b']') {
+                        self.pos += 1;
+                        break;
+                    }
+                    items.push(self.value()?);
+                    self.blank_lines();
+                    match self.peek() {
+                        Some(b',') => self.pos += 1,
+                        Some(b']') => {
+                            self.pos += 1;
+                            break;
+                        }
+                        _ => return Err(self.error("expected `,` or `]`")),
+                    }
+                }
+                Value::Array {
+                    items,
+                    of_tables: false,
+                }
+            }
+            Some(b'{') => {
+                self.pos += 1;
+                let mut table = Table::new(TableKind::Dotted);
+                self.spaces();
+                if self.peek() == Some(b'}') {
+                    self.pos += 1;
+                } else {
+                    loop {
+                        self.spaces();
+                        let keys = self.key()?;
+                        self.eat(b'=', "expected `=` after a key")?;
+                        self.spaces();
+                        let item = self.value()?;
+                        table.insert(&keys, item)?;
+                        self.spaces();
+                        match self.peek() {
+                            Some(b',') => self.pos += 1,
+                            Some(b'}') => {
+                                self.pos += 1;
+                                break;
+                            }
+                            _ => return Err(self.error("expected `,` or `}`")),
+                        }
+                    }
+                }
+                table.kind = TableKind::Inline;
+                Value::Table(table)
+            }
+            _ => self.scalar()?,
+        };
+        Ok(Item { value, offset })
+    }
+
+    /// Parses a boolean, a number or a datetime
+    fn scalar(&mut self) -> Result<Value, Error> {
+        let start = self.pos;
+        let token_len = |s: &str| {
+            s.bytes()
+                .take_while(|b| {
+                    b.is_ascii_alphanumeric() || matches!(b, b'_' | b'+' | b'-' | b'.' | b':')
+                })
+                .count()
+        };
+        self.pos += token_len(self.rest());
+        // A date and a time can be separated by a space rather than a `T`
+        let date = self.pos - start == 10 && self.input.as_bytes()[start + 4] == b'-';
+        let rest = self.rest().as_bytes();
+        if date && rest.len() > 3 && rest[0] == b' ' && rest[3] == b':' {
+            self.pos += 1;
+            self.pos += token_len(self.rest());
+        }
+        let token = &self.input[start..self.pos];
+        let bytes = token.as_bytes();
+
+        match token {
+            "" => Err(self.error("expected a value")),
+            "true" => Ok(Value::Bool(true)),
+            "false" => Ok(Value::Bool(false)),
+            _ if bytes[0].is_ascii_alphabetic() && !matches!(token, "inf" | "nan") => Err((
+                start,
+                ReadErrorKind::Syntax("expected a value: strings must be quoted"),
+            )),
+            _ if bytes.len() >= 3 && bytes[2] == b':'
+                || bytes.len() >= 10
+                    && bytes[4] == b'-'
+                    && bytes[..4].iter().all(u8::is_ascii_digit) =>
+            {
+                token
+                    .parse()
+                    .map(Value::Datetime)
+                    .map_err(|err: crate::DatetimeParseError| (start, ReadErrorKind::Syntax(err.0)))
+            }
+            _ => number(token)
+                .ok_or_else(|| (start, ReadErrorKind::InvalidNumber(token.to_string()))),
+        }
+    }
+
+    /// Parses a basic string, or a multi-line one
+    fn basic_string(&mut self) -> Result<String, Error> {
+        let multiline = self.rest().starts_with("\"\"\"");
+        self.pos += if multiline { 3 } else { 1 };
+        if multiline {
+            self.newline();
+        }
+        let mut s = String::new();
+        loop {
+            let Some(c) = self.rest().chars().next() else {
+                return Err(self.error("expected the end of the string"));
+            };
+            match c {
+                '"' if !multiline => {
+                    self.pos += 1;
+                    return Ok(s);
+                }
+                '"' if self.rest().starts_with("\"\"\"") => return self.close(s, b'"'),
+                '\\' => {
+                    self.pos += 1;
+                    match self.peek() {
+                        Some(b' ' | b'\t' | b'\r' | b'\n') if multiline => {
+                            // A backslash at the end of a line trims the whitespace after it
+                            self.spaces();
+                            if !self.newline() {
+                                return Err(self.error("invalid escape sequence"));
+                            }
+                            self.blank_lines_in_string();
+                        }
+                        _ => s.push(self.escape()?),
+                    }
+                }
+                '\n' | '\r' if multiline && self.newline() => s.push('\n'),
+                '\n' => return Err(self.error("expected the end of the string")),
+                c if c.is_control() && c != '\t' => {
+                    return Err(self.error("control characters must be escaped"));
+                }
+                c => {
+                    s.push(c);
+                    self.pos += c.len_utf8();
+                }
+            }
+        }
+    }
+
+    /// Skips whitespace and line breaks, after a line-ending backslash
+    fn blank_lines_in_string(&mut self) {
+        loop {
+            self.spaces();
+            if !self.newline() {
+                return;
+            }
+        }
+    }
+
+    /// Parses a literal string, or a multi-line one
+    fn literal_string(&mut self) -> Result<String, Error> {
+        let multiline = self.rest().starts_with("'''");
+        self.pos += if multiline { 3 } else { 1 };
+        if multiline {
+            self.newline();
+        }
+        let mut s = String::new();
+        loop {
+            let Some(c) = self.rest().chars().next() else {
+                return Err(self.error("expected the end of the string"));
+            };
+            match c {
+                '\'' if !multiline => {
+                    self.pos += 1;
+                    return Ok(s);
+                }
+                '\'' if self.rest().starts_with("'''") => return self.close(s, b'\''),
+                '\n' | '\r' if multiline && self.newline() => s.push('\n'),
+                '\n' => return Err(self.error("expected the end of the string")),
+                c if c.is_control() && c != '\t' => {
+                    return Err(self.error("control characters aren't allowed in strings"));
+                }
+                c => {
+                    s.push(c);
+                    self.pos += c.len_utf8();
+                }
+            }
+        }
+    }
+
+    /// Ends a multi-line string at its three closing quotes, keeping the one or two
+    /// quotes that can come right before them
+    fn close(&mut self, mut s: String, quote: u8) -> Result<String, Error> {
+        let quotes = self.rest().bytes().take_while(|b| *b == quote).count();
+        if quotes > 5 {
+            return Err(self.error("expected the end of the string"));
+        }
+        for _ in 3..quotes {
+            s.push(char::from(quote));
+        }
+        self.pos += quotes;
+        Ok(s)
+    }
+
+    /// Parses what follows a backslash in a basic string
+    fn escape(&mut self) -> Result<char, Error> {
+        let c = match self.peek() {
+            Some(b'b') => '\u{8}',
+            Some(b't') => '\t',
+            Some(b'n') => '\n',
+            Some(b'f') => '\u{c}',
+            Some(b'r') => '\r',
+            Some(b'"') => '"',
+            Some(b'\\') => '\\',
+            Some(b'u') => return self.unicode_escape(4),
+            Some(b'U') => return self.unicode_escape(8),
+            _ => return Err(self.error("invalid escape sequence")),
+        };
+        self.pos += 1;
+        Ok(c)
+    }
+
+    fn unicode_escape(&mut self, digits: usize) -> Result<char, Error> {
+        self.pos += 1;
+        let hex = self
+            .rest()
+            .get(..digits)
+            .filter(|hex| hex.bytes().all(|b| b.is_ascii_hexdigit()));
+        let Some(hex) = hex else {
+            return Err(self.error("expected hexadecimal digits in a unicode escape"));
+        };
+        let c = u32::from_str_radix(hex, 16).ok().and_then(char::from_u32);
+        let Some(c) = c else {
+            return Err(self.error("invalid unicode scalar value"));
+        };
+        self.pos += digits;
+        Ok(c)
+    }
+}
+
+/// Parses an integer or a float
+fn number(token: &str) -> Option<Value> {
+    match token {
+        "inf" | "+inf" => return Some(Value::Float(f64::INFINITY)),
+        "-inf" => return Some(Value::Float(f64::NEG_INFINITY)),
+        "nan" | "+nan" | "-nan" => return Some(Value::Float(f64::NAN)),
+        _ => {}
+    }
+    for (prefix, radix) in [("0x", 16), ("0o", 8), ("0b", 2)] {
+        if let Some(digits) = token.strip_prefix(prefix) {
+            let is_digit = |b: u8| char::from(b).is_digit(radix);
+            if !underscores_between(digits, is_digit) {
+                return None;
+            }
+            let digits = digits.replace('_', "");
+            return i64::from_str_radix(&digits, radix).ok().map(Value::Integer);
+        }
+    }
+
+    let unsigned = token.strip_prefix(['+', '-']).unwrap_or(token);
+    let integer_part = unsigned
+        .find(['.', 'e', 'E'])
+        .map_or(unsigned, |end| &unsigned[..end]);
+    let valid = underscores_between(integer_part, |b| b.is_ascii_digit())
+        && !(integer_part.len() > 1 && integer_part.starts_with('0'))
+        && unsigned.bytes().enumerate().all(|(i, b)| match b {
+            b'0'..=b'9' | b'_' | b'e' | b'E' => true,
+            // A decimal point needs digits on both sides
+            b'.' if i > 0 => {
+                let digit = |i: usize| unsigned.as_bytes().get(i).is_some_and(u8::is_ascii_digit);
+                digit(i - 1) && digit(i + 1)
+            }
+            b'+' | b'-' if i > 0 => matches!(unsigned.as_bytes()[i - 1], b'e' | b'E'),
+            _ => false,
+        })
+        && unsigned
+            .split('_')
+            .skip(1)
+            .all(|part| part.starts_with(|c: char| c.is_ascii_digit()))
+        && unsigned
+            .rsplit('_')
+            .skip(1)
+            .all(|part| part.ends_with(|c: char| c.is_ascii_digit()));
+    if !valid {
+        return None;
+    }
+    let cleaned = token.replace('_', "");
+    if integer_part.len() == unsigned.len() {
+        cleaned.parse().ok().map(Value::Integer)
+    } else {
+        cleaned.parse().ok().map(Value::Float)
+    }
+}
+
+/// Returns true if `digits` isn't empty, and each of its underscores is between two
+/// digits
+fn underscores_between(digits: &str, is_digit: impl Fn(u8) -> bool) -> bool {
+    let bytes = digits.as_bytes();
+    !bytes.is_empty()
+        && bytes.iter().enumerate().all(|(i, b)| match b {
+            b'_' => {
+                i > 0 && i + 1 < bytes.len() && is_digit(bytes[i - 1]) && is_digit(bytes[i + 1])
+            }
+            b => is_digit(*b),
+        })
+}
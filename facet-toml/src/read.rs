@@ -0,0 +1,95 @@
+use alloc::borrow::Cow;
+use alloc::string::ToString;
+use alloc::vec::Vec;
+use core::convert::Infallible;
+
+use facet_serialize::{Deserializer, Event};
+
+use crate::parse::{Item, Key, Table, Value};
+
+/// Reads a parsed document as a stream of events, keeping track of where in the document
+/// the last one came from
+pub(crate) struct Events<'t> {
+    pending: Vec<Pending<'t>>,
+    /// The byte offset of the last event
+    pub(crate) offset: usize,
+}
+
+enum Pending<'t> {
+    Root(&'t Table),
+    Item(&'t Item),
+    Items(core::slice::Iter<'t, Item>),
+    /// The entries of a table, and where the table starts
+    Entries(core::slice::Iter<'t, (Key, Item)>, usize),
+}
+
+impl<'t> Events<'t> {
+    pub(crate) fn new(root: &'t Table) -> Self {
+        Self {
+            pending: Vec::from([Pending::Root(root)]),
+            offset: 0,
+        }
+    }
+}
+
+impl<'t> Deserializer<'t> for Events<'t> {
+    type Error = Infallible;
+
+    fn next(&mut self) -> Result<Event<'t>, Self::Error> {
+        loop {
+            // `deserialize` reads a single value, so it never asks for more than the root
+            // table: should it, it would find the table closed
+            let Some(pending) = self.pending.pop() else {
+                return Ok(Event::EndMap);
+            };
+            match pending {
+                Pending::Root(table) => {
+                    self.pending.push(Pending::Entries(table.entries.iter(), 0));
+                    return Ok(Event::StartMap);
+                }
+                Pending::Item(item) => {
+                    self.offset = item.offset;
+                    return Ok(match &item.value {
+                        Value::String(s) => Event::Str(Cow::Borrowed(s)),
+                        Value::Integer(n) => match u64::try_from(*n) {
+                            Ok(n) => Event::U64(n),
+                            Err(_) => Event::I64(*n),
+                        },
+                        Value::Float(n) => Event::F64(*n),
+                        Value::Bool(b) => Event::Bool(*b),
+                        Value::Datetime(datetime) => Event::Str(Cow::Owned(datetime.to_string())),
+                        Value::Array { items, .. } => {
+                            self.pending.push(Pending::Items(items.iter()));
+                            Event::StartSeq
+                        }
+                        Value::Table(table) => {
+                            self.pending
+                                .push(Pending::Entries(table.entries.iter(), item.offset));
+                            Event::StartMap
+                        }
+                    });
+                }
+                Pending::Items(mut items) => match items.next() {
+                    Some(item) => {
+                        self.pending.push(Pending::Items(items));
+                        self.pending.push(Pending::Item(item));
+                    }
+                    None => return Ok(Event::EndSeq),
+                },
+                Pending::Entries(mut entries, start) => match entries.next() {
+                    Some((key, item)) => {
+                        self.pending.push(Pending::Entries(entries, start));
+                        self.pending.push(Pending::Item(item));
+                        self.offset = key.offset;
+                        return Ok(Event::Key(Cow::Borrowed(&key.name)));
+                    }
+                    None => {
+                        // Missing fields are reported at the start of their table
+                        self.offset = start;
+                        return Ok(Event::EndMap);
+                    }
+                },
+            }
+        }
+    }
+}
@@ -0,0 +1,281 @@
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt::Write as _;
+
+use facet_serialize::Serializer;
+use facet_trait::{Shape, ShapeExt as _};
+
+use crate::{Datetime, WriteError};
+
+/// A value being written, before it's laid out into sections
+enum Node {
+    /// A string, a number, a boolean or a datetime, as written in the document
+    Scalar(String),
+    Array(Vec<Node>),
+    Table(Vec<(String, Node)>),
+}
+
+impl Node {
+    /// Returns true if the node gets a `[header]` of its own, or a `[[header]]` per item
+    fn is_section(&self) -> bool {
+        match self {
+            Node::Scalar(_) => false,
+            Node::Array(items) => {
+                !items.is_empty() && items.iter().all(|item| matches!(item, Node::Table(_)))
+            }
+            Node::Table(_) => true,
+        }
+    }
+}
+
+/// Builds a TOML document out of a typed value being serialized
+#[derive(Default)]
+pub(crate) struct Builder {
+    /// The arrays and tables being built, with the key of the next entry of tables
+    open: Vec<(Node, Option<String>)>,
+    done: Option<Vec<(String, Node)>>,
+}
+
+impl Builder {
+    pub(crate) fn finish(self) -> String {
+        let entries = self.done.expect("the serializer emitted a whole value");
+        let mut out = String::new();
+        write_table(&mut out, "", &entries);
+        out
+    }
+
+    fn emit(&mut self, node: Node) -> Result<(), WriteError> {
+        match (self.open.last_mut(), node) {
+            (Some((Node::Array(items), _)), node) => items.push(node),
+            (Some((Node::Table(entries), key)), node) => {
+                entries.push((key.take().unwrap_or_default(), node));
+            }
+            (_, Node::Table(entries)) => self.done = Some(entries),
+            _ => return Err(WriteError::NotATable),
+        }
+        Ok(())
+    }
+
+    fn scalar(&mut self, value: impl ToString) -> Result<(), WriteError> {
+        self.emit(Node::Scalar(value.to_string()))
+    }
+
+    fn open(&mut self, node: Node) -> Result<(), WriteError> {
+        self.open.push((node, None));
+        Ok(())
+    }
+
+    fn close(&mut self) -> Result<(), WriteError> {
+        match self.open.pop() {
+            Some((node, _)) => self.emit(node),
+            None => Ok(()),
+        }
+    }
+
+    fn key(&mut self, key: &str) -> Result<(), WriteError> {
+        if let Some((_, next)) = self.open.last_mut() {
+            *next = Some(key.into());
+        }
+        Ok(())
+    }
+}
+
+impl Serializer for Builder {
+    type Error = WriteError;
+
+    fn serialize_bool(&mut self, value: bool) -> Result<(), Self::Error> {
+        self.scalar(value)
+    }
+
+    fn serialize_u64(&mut self, value: u64) -> Result<(), Self::Error> {
+        // TOML integers are 64-bit signed: larger ones are written as strings, like
+        // 128-bit integers that don't fit in 64 bits
+        match i64::try_from(value) {
+            Ok(value) => self.scalar(value),
+            Err(_) => self.serialize_str(&value.to_string()),
+        }
+    }
+
+    fn serialize_i64(&mut self, value: i64) -> Result<(), Self::Error> {
+        self.scalar(value)
+    }
+
+    fn serialize_f64(&mut self, value: f64) -> Result<(), Self::Error> {
+        if value.is_nan() {
+            self.scalar("nan")
+        } else if value.is_infinite() {
+            self.scalar(if value > 0.0 { "inf" } else { "-inf" })
+        } else {
+            // `Debug` always writes a decimal point or an exponent, so the number reads
+            // back as a float
+            self.scalar(format_args!("{value:?}"))
+        }
+    }
+
+    fn serialize_str(&mut self, value: &str) -> Result<(), Self::Error> {
+        let mut quoted = String::with_capacity(value.len() + 2);
+        write_string(&mut quoted, value);
+        self.emit(Node::Scalar(quoted))
+    }
+
+    fn serialize_display(&mut self, shape: &'static Shape, value: &str) -> Result<(), Self::Error> {
+        if shape.is_type::<Datetime>() {
+            self.scalar(value)
+        } else {
+            self.serialize_str(value)
+        }
+    }
+
+    fn serialize_unit(&mut self) -> Result<(), Self::Error> {
+        // TOML has no null: the entry is left out of its table
+        match self.open.last_mut() {
+            Some((Node::Table(_), key)) => {
+                key.take();
+                Ok(())
+            }
+            Some(_) => Err(WriteError::NullInArray),
+            None => Err(WriteError::NotATable),
+        }
+    }
+
+    fn start_struct(&mut self, _len: usize) -> Result<(), Self::Error> {
+        self.open(Node::Table(Vec::new()))
+    }
+
+    fn field(&mut self, name: &'static str) -> Result<(), Self::Error> {
+        self.key(name)
+    }
+
+    fn end_struct(&mut self) -> Result<(), Self::Error> {
+        self.close()
+    }
+
+    fn start_seq(&mut self, _len: usize) -> Result<(), Self::Error> {
+        self.open(Node::Array(Vec::new()))
+    }
+
+    fn end_seq(&mut self) -> Result<(), Self::Error> {
+        self.close()
+    }
+
+    fn start_map(&mut self, _len: usize) -> Result<(), Self::Error> {
+        self.open(Node::Table(Vec::new()))
+    }
+
+    fn map_key(&mut self, key: &str) -> Result<(), Self::Error> {
+        self.key(key)
+    }
+
+    fn end_map(&mut self) -> Result<(), Self::Error> {
+        self.close()
+    }
+}
+
+/// Writes the entries of the table at `path`: its keys and values first, then its
+/// subtables and arrays of tables, each in a section of its own
+fn write_table(out: &mut String, path: &str, entries: &[(String, Node)]) {
+    for (key, node) in entries {
+        if !node.is_section() {
+            write_key(out, key);
+            out.push_str(" = ");
+            write_inline(out, node);
+            out.push('\n');
+        }
+    }
+    for (key, node) in entries {
+        if !node.is_section() {
+            continue;
+        }
+        let mut path = path.to_string();
+        if !path.is_empty() {
+            path.push('.');
+        }
+        write_key(&mut path, key);
+        match node {
+            Node::Table(entries) => {
+                section(out, &path, false);
+                write_table(out, &path, entries);
+            }
+            Node::Array(items) => {
+                for item in items {
+                    if let Node::Table(entries) = item {
+                        section(out, &path, true);
+                        write_table(out, &path, entries);
+                    }
+                }
+            }
+            Node::Scalar(_) => {}
+        }
+    }
+}
+
+/// Starts a `[table]` or an `[[array.of.tables]]` item
+fn section(out: &mut String, path: &str, array: bool) {
+    if !out.is_empty() {
+        out.push('\n');
+    }
+    let (open, close) = if array { ("[[", "]]") } else { ("[", "]") };
+    writeln!(out, "{open}{path}{close}").unwrap();
+}
+
+/// Writes a value on a single line, tables as inline tables
+fn write_inline(out: &mut String, node: &Node) {
+    match node {
+        Node::Scalar(value) => out.push_str(value),
+        Node::Array(items) => {
+            out.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push_str(", ");
+                }
+                write_inline(out, item);
+            }
+            out.push(']');
+        }
+        Node::Table(entries) if entries.is_empty() => out.push_str("{}"),
+        Node::Table(entries) => {
+            out.push_str("{ ");
+            for (i, (key, node)) in entries.iter().enumerate() {
+                if i > 0 {
+                    out.push_str(", ");
+                }
+                write_key(out, key);
+                out.push_str(" = ");
+                write_inline(out, node);
+            }
+            out.push_str(" }");
+        }
+    }
+}
+
+/// Writes a key, bare if it can be
+fn write_key(out: &mut String, key: &str) {
+    let bare = !key.is_empty()
+        && key
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || matches!(b, b'_' | b'-'));
+    if bare {
+        out.push_str(key);
+    } else {
+        write_string(out, key);
+    }
+}
+
+/// Writes a basic string, with its quotes
+fn write_string(out: &mut String, s: &str) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            '\u{8}' => out.push_str("\\b"),
+            '\u{c}' => out.push_str("\\f"),
+            c if c.is_control() => write!(out, "\\u{:04X}", u32::from(c)).unwrap(),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
@@ -0,0 +1,324 @@
+use std::collections::HashMap;
+
+use facet_derive::Facet;
+use facet_serialize::DeserializeErrorKind;
+use facet_toml::{Date, Datetime, Offset, ReadErrorKind, Time, WriteError};
+use facet_trait::{self as facet, Facet};
+
+#[derive(Debug, PartialEq, Facet)]
+struct Config {
+    title: String,
+    owner: Owner,
+    database: Database,
+    servers: HashMap<String, Server>,
+}
+
+#[derive(Debug, PartialEq, Facet)]
+struct Owner {
+    name: String,
+    dob: Datetime,
+}
+
+#[derive(Debug, PartialEq, Facet)]
+struct Database {
+    enabled: bool,
+    ports: Vec<u16>,
+    data: Vec<Vec<String>>,
+    temp_targets: Limits,
+}
+
+#[derive(Debug, PartialEq, Facet)]
+struct Limits {
+    cpu: f64,
+    case: f32,
+}
+
+#[derive(Debug, PartialEq, Facet)]
+struct Server {
+    ip: String,
+    role: String,
+}
+
+#[test]
+fn reads_tables_inline_tables_and_dotted_keys() {
+    let toml = r#"
+# This is a TOML document
+title = "TOML Example"
+
+[owner]
+name = 'Tom Preston-Werner'
+dob = 1979-05-27T07:32:00-08:00
+
+[database]
+enabled = true
+ports = [ 8000, 8001, 0x1F_42 ]
+data = [ ["delta", "phi"], [
+  """
+multi\
+    line""",
+  '''raw \n''',
+] ]
+temp_targets = { cpu = 79.5, case = 72.0 }
+
+[servers]
+alpha.ip = "10.0.0.1"
+alpha.role = "frontend"
+
+[servers.beta]
+ip = "10.0.0.2" # trailing comment
+"role" = "backend"
+"#;
+    let config: Config = facet_toml::from_str(toml).unwrap();
+    assert_eq!(config.title, "TOML Example");
+    assert_eq!(config.owner.name, "Tom Preston-Werner");
+    assert_eq!(config.owner.dob.to_string(), "1979-05-27T07:32:00-08:00");
+    assert!(config.database.enabled);
+    assert_eq!(config.database.ports, [8000, 8001, 8002]);
+    assert_eq!(
+        config.database.data,
+        [vec!["delta", "phi"], vec!["multiline", "raw \\n"]]
+    );
+    assert_eq!(
+        config.database.temp_targets,
+        Limits {
+            cpu: 79.5,
+            case: 72.0
+        }
+    );
+    assert_eq!(config.servers["alpha"].role, "frontend");
+    assert_eq!(config.servers["beta"].ip, "10.0.0.2");
+    assert_eq!(config.servers["beta"].role, "backend");
+}
+
+#[derive(Debug, PartialEq, Facet)]
+struct Catalog {
+    name: String,
+    products: Vec<Product>,
+}
+
+#[derive(Debug, PartialEq, Facet)]
+struct Product {
+    name: String,
+    sku: Option<u64>,
+    price: f64,
+    variants: Vec<Variety>,
+}
+
+#[derive(Debug, PartialEq, Facet)]
+struct Variety {
+    color: String,
+}
+
+#[test]
+fn round_trips_arrays_of_tables() {
+    let catalog = Catalog {
+        name: "hardware".to_string(),
+        products: vec![
+            Product {
+                name: "Hammer".to_string(),
+                sku: Some(738594937),
+                price: 9.5,
+                variants: vec![],
+            },
+            Product {
+                name: "Nail \"box\"".to_string(),
+                sku: None,
+                price: 1.0,
+                variants: vec![
+                    Variety {
+                        color: "grey".to_string(),
+                    },
+                    Variety {
+                        color: "black".to_string(),
+                    },
+                ],
+            },
+        ],
+    };
+
+    let toml = facet_toml::to_string(&catalog).unwrap();
+    assert_eq!(
+        toml,
+        r#"name = "hardware"
+
+[[products]]
+name = "Hammer"
+sku = 738594937
+price = 9.5
+variants = []
+
+[[products]]
+name = "Nail \"box\""
+price = 1.0
+
+[[products.variants]]
+color = "grey"
+
+[[products.variants]]
+color = "black"
+"#
+    );
+    assert_eq!(facet_toml::from_str::<Catalog>(&toml).unwrap(), catalog);
+}
+
+#[derive(Debug, PartialEq, Facet)]
+struct Times {
+    offset: Datetime,
+    local: Datetime,
+    date: Datetime,
+    time: Datetime,
+    as_string: String,
+}
+
+#[test]
+fn datetimes() {
+    let times: Times = facet_toml::from_str(
+        "offset = 1979-05-27 00:32:00.999999Z\n\
+         local = 1979-05-27T07:32:00\n\
+         date = 2024-02-29\n\
+         time = 07:32:00.5\n\
+         as_string = 1979-05-27T07:32:00+05:30\n",
+    )
+    .unwrap();
+    assert_eq!(
+        times.offset,
+        Datetime {
+            date: Some(Date {
+                year: 1979,
+                month: 5,
+                day: 27,
+            }),
+            time: Some(Time {
+                hour: 0,
+                minute: 32,
+                second: 0,
+                nanosecond: 999_999_000,
+            }),
+            offset: Some(Offset::Z),
+        }
+    );
+    assert_eq!(times.local.offset, None);
+    assert_eq!(times.date.time, None);
+    assert_eq!(times.time.date, None);
+    assert_eq!(times.as_string, "1979-05-27T07:32:00+05:30");
+
+    // Datetimes are written bare, and come back as datetimes
+    let toml = facet_toml::to_string(&times).unwrap();
+    assert_eq!(
+        toml,
+        "offset = 1979-05-27T00:32:00.999999Z\n\
+         local = 1979-05-27T07:32:00\n\
+         date = 2024-02-29\n\
+         time = 07:32:00.5\n\
+         as_string = \"1979-05-27T07:32:00+05:30\"\n"
+    );
+    assert_eq!(facet_toml::from_str::<Times>(&toml).unwrap(), times);
+
+    assert!("2023-02-29".parse::<Datetime>().is_err());
+    assert!("07:32:00Z".parse::<Datetime>().is_err());
+    assert!("24:00:00".parse::<Datetime>().is_err());
+}
+
+#[derive(Debug, PartialEq, Facet)]
+struct Numbers {
+    small: u8,
+    big: u64,
+    negative: i64,
+    wide: u128,
+    floats: Vec<f64>,
+}
+
+#[test]
+fn numbers_outside_toml_integers() {
+    let numbers = Numbers {
+        small: 255,
+        big: u64::MAX,
+        negative: i64::MIN,
+        wide: u128::MAX,
+        floats: vec![-0.0, 1e300, f64::INFINITY, f64::NEG_INFINITY],
+    };
+    let toml = facet_toml::to_string(&numbers).unwrap();
+    assert_eq!(
+        toml,
+        "small = 255\n\
+         big = \"18446744073709551615\"\n\
+         negative = -9223372036854775808\n\
+         wide = \"340282366920938463463374607431768211455\"\n\
+         floats = [-0.0, 1e300, inf, -inf]\n"
+    );
+    assert_eq!(facet_toml::from_str::<Numbers>(&toml).unwrap(), numbers);
+
+    let err = facet_toml::from_str::<Numbers>("small = 9223372036854775808").unwrap_err();
+    assert_eq!(
+        err.kind,
+        ReadErrorKind::InvalidNumber("9223372036854775808".to_string())
+    );
+}
+
+#[test]
+fn read_errors_have_a_line_a_column_and_a_path() {
+    let err =
+        facet_toml::from_str::<Config>("title = \"x\"\n[owner]\nname = \"Tom\"\ndob = tomorrow\n")
+            .unwrap_err();
+    assert_eq!((err.line, err.column), (4, 7));
+    assert_eq!(
+        err.to_string(),
+        "line 4, column 7: expected a value: strings must be quoted"
+    );
+
+    let err = facet_toml::from_str::<Config>("[owner]\nname = \"a\"\n\n[owner]\n").unwrap_err();
+    assert_eq!((err.line, err.column), (4, 2));
+    assert_eq!(err.kind, ReadErrorKind::DuplicateKey("owner".to_string()));
+
+    let err = facet_toml::from_str::<Config>("a.b = 1\na.b.c = 2\n").unwrap_err();
+    assert_eq!((err.line, err.column), (2, 3));
+    assert_eq!(
+        err.to_string(),
+        "line 2, column 3: `a.b.c` is defined twice"
+    );
+
+    let toml = "title = \"x\"\n\
+                [database]\n\
+                enabled = true\n\
+                ports = [80, 443, 100000]\n";
+    let err = facet_toml::from_str::<Config>(toml).unwrap_err();
+    assert_eq!((err.line, err.column), (4, 19));
+    let ReadErrorKind::Value(value) = &err.kind else {
+        panic!("expected a value error, got {err}");
+    };
+    assert_eq!(value.path.to_string(), "database.ports[2]");
+    assert!(matches!(value.kind, DeserializeErrorKind::InvalidValue(_)));
+
+    // Missing fields are reported at the table they're missing from
+    let err =
+        facet_toml::from_str::<Config>("title = \"x\"\n\n[owner]\nname = \"Tom\"\n").unwrap_err();
+    assert_eq!((err.line, err.column), (3, 2));
+    assert_eq!(
+        err.to_string(),
+        "line 3, column 2: Missing field `dob` for Owner at `owner`"
+    );
+}
+
+#[derive(Debug, PartialEq, Facet)]
+struct Sparse {
+    values: Vec<Option<u32>>,
+}
+
+#[test]
+fn write_errors_have_a_path() {
+    let err = facet_toml::to_string(&Sparse {
+        values: vec![Some(1), None],
+    })
+    .unwrap_err();
+    assert_eq!(
+        err.kind,
+        facet_serialize::SerializeErrorKind::Format(WriteError::NullInArray)
+    );
+    assert_eq!(err.path.to_string(), "values[1]");
+
+    let err = facet_toml::to_string(&vec![1, 2]).unwrap_err();
+    assert_eq!(
+        err.kind,
+        facet_serialize::SerializeErrorKind::Format(WriteError::NotATable)
+    );
+}
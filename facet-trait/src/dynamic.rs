@@ -0,0 +1,488 @@
+//! Shapes assembled at runtime, for data whose schema is only known once the
+//! program is running (record layouts loaded from a config file, etc.)
+//!
+//! Every [`Shape`], [`Field`] and [`ValueVTable`] is `&'static`, so the builders
+//! in this module either leak what they build, or intern it into a [`ShapeArena`]
+//! which hands out the same `&'static Shape` for structurally equal requests.
+
+use core::alloc::Layout;
+use core::fmt;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use crate::{
+    Def, Facet, Field, FieldFlags, MarkerTraits, Shape, StructDef, StructKind, ValueVTable,
+};
+
+/// Errors encountered while assembling a dynamic shape
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum DynamicShapeError {
+    /// Two fields were declared with the same name
+    DuplicateField(String),
+
+    /// A field's shape needs to be dropped, which dynamic structs can't do
+    /// (their vtable has no way to find their fields again). Only `Copy`
+    /// shapes and other dynamic structs can be used as fields.
+    FieldNeedsDrop {
+        /// name of the offending field
+        field: String,
+        /// shape of the offending field
+        shape: &'static Shape,
+    },
+
+    /// A field was placed at an offset that isn't a multiple of its alignment
+    MisalignedField {
+        /// name of the offending field
+        field: String,
+        /// the requested offset
+        offset: usize,
+        /// the alignment required by the field's shape
+        align: usize,
+    },
+
+    /// A field was placed at an offset that overlaps with another field
+    OverlappingFields {
+        /// name of the field declared first
+        first: String,
+        /// name of the field declared second
+        second: String,
+    },
+
+    /// The requested size or alignment doesn't fit in a [`Layout`]
+    LayoutOverflow,
+
+    /// Lists and maps can only be assembled out of known scalar shapes, cf.
+    /// [`DynamicShapeBuilder::list`] and [`DynamicShapeBuilder::map`]
+    UnsupportedShape(&'static Shape),
+}
+
+impl fmt::Display for DynamicShapeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DynamicShapeError::DuplicateField(name) => write!(f, "Duplicate field: {}", name),
+            DynamicShapeError::FieldNeedsDrop { field, shape } => write!(
+                f,
+                "Field '{}' has shape {}, which needs to be dropped — dynamic structs only support Copy fields",
+                field, shape
+            ),
+            DynamicShapeError::MisalignedField {
+                field,
+                offset,
+                align,
+            } => write!(
+                f,
+                "Field '{}' is at offset {}, which is not a multiple of its alignment {}",
+                field, offset, align
+            ),
+            DynamicShapeError::OverlappingFields { first, second } => {
+                write!(f, "Fields '{}' and '{}' overlap", first, second)
+            }
+            DynamicShapeError::LayoutOverflow => write!(f, "Layout overflow"),
+            DynamicShapeError::UnsupportedShape(shape) => {
+                write!(f, "Unsupported shape for a dynamic list or map: {}", shape)
+            }
+        }
+    }
+}
+
+impl core::error::Error for DynamicShapeError {}
+
+/// Owns the shapes built by a [`DynamicShapeBuilder`], and deduplicates them.
+///
+/// Building the same layout twice through an arena returns the same
+/// `&'static Shape` and doesn't allocate again, so loading a config file
+/// repeatedly doesn't grow memory forever.
+///
+/// The arena itself must live for `'static` — typically in a `static`, or
+/// leaked once at startup.
+#[derive(Default)]
+pub struct ShapeArena {
+    shapes: Mutex<Vec<&'static Shape>>,
+    names: Mutex<Vec<&'static str>>,
+}
+
+impl ShapeArena {
+    /// Creates an empty arena
+    pub const fn new() -> Self {
+        Self {
+            shapes: Mutex::new(Vec::new()),
+            names: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Number of distinct shapes interned so far
+    pub fn len(&self) -> usize {
+        self.shapes.lock().unwrap().len()
+    }
+
+    /// Returns true if no shapes were interned yet
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn intern_name(&self, name: &str) -> &'static str {
+        let mut names = self.names.lock().unwrap();
+        if let Some(existing) = names.iter().find(|n| **n == name) {
+            return existing;
+        }
+        let leaked: &'static str = Box::leak(name.to_owned().into_boxed_str());
+        names.push(leaked);
+        leaked
+    }
+
+    fn intern_shape(&self, shape: Shape) -> &'static Shape {
+        let mut shapes = self.shapes.lock().unwrap();
+        if let Some(existing) = shapes.iter().find(|s| ***s == shape) {
+            return existing;
+        }
+        let leaked: &'static Shape = Box::leak(Box::new(shape));
+        shapes.push(leaked);
+        leaked
+    }
+}
+
+/// Assembles structs, lists and maps at runtime, out of existing shapes.
+///
+/// Struct layouts are computed like `#[repr(C)]` would (fields in declaration
+/// order, padded for alignment), unless fields are given explicit offsets.
+///
+/// # Limitations
+///
+/// Dynamic shapes don't nest arbitrarily:
+///
+///   * struct fields must be `Copy` (scalars, or other dynamic structs): a
+///     dynamic struct has no drop glue, so `String`s, lists and maps are
+///     rejected with [`DynamicShapeError::FieldNeedsDrop`]
+///   * lists and maps are the shapes of existing `Vec`s and `HashMap`s, so
+///     their elements must be one of a fixed set of scalar shapes — anything
+///     else, including dynamic structs and other lists or maps, is rejected
+///     with [`DynamicShapeError::UnsupportedShape`]
+///
+/// ```
+/// use facet_trait::{DynamicShapeBuilder, Facet};
+///
+/// let builder = DynamicShapeBuilder::new();
+/// let shape = builder
+///     .struct_()
+///     .field("id", u32::SHAPE)
+///     .field("score", f64::SHAPE)
+///     .build()
+///     .unwrap();
+/// assert_eq!(shape.layout.size(), 16);
+/// ```
+#[derive(Clone, Copy, Default)]
+pub struct DynamicShapeBuilder {
+    arena: Option<&'static ShapeArena>,
+}
+
+impl DynamicShapeBuilder {
+    /// Creates a builder which leaks every shape it builds
+    pub const fn new() -> Self {
+        Self { arena: None }
+    }
+
+    /// Creates a builder which interns every shape it builds into `arena`
+    pub const fn with_arena(arena: &'static ShapeArena) -> Self {
+        Self { arena: Some(arena) }
+    }
+
+    /// Starts assembling a struct with named fields
+    pub fn struct_(&self) -> DynamicStructBuilder {
+        DynamicStructBuilder {
+            builder: *self,
+            kind: StructKind::Struct,
+            fields: Vec::new(),
+        }
+    }
+
+    /// Starts assembling a tuple struct — fields are named after their index
+    pub fn tuple_struct(&self) -> DynamicStructBuilder {
+        DynamicStructBuilder {
+            builder: *self,
+            kind: StructKind::TupleStruct,
+            fields: Vec::new(),
+        }
+    }
+
+    /// Returns the shape of a list of `item` (a `Vec`), if `item` is `()`, `bool`,
+    /// `String`, or a primitive integer or float shape
+    ///
+    /// Returns [`DynamicShapeError::UnsupportedShape`] for any other shape.
+    pub fn list(&self, item: &'static Shape) -> Result<&'static Shape, DynamicShapeError> {
+        macro_rules! lists {
+            ($($t:ty),*) => {
+                $(
+                    if item == <$t as Facet>::SHAPE {
+                        return Ok(<Vec<$t> as Facet>::SHAPE);
+                    }
+                )*
+            };
+        }
+        lists!(
+            (),
+            bool,
+            String,
+            u8,
+            u16,
+            u32,
+            u64,
+            u128,
+            i8,
+            i16,
+            i32,
+            i64,
+            i128,
+            f32,
+            f64
+        );
+        Err(DynamicShapeError::UnsupportedShape(item))
+    }
+
+    /// Returns the shape of a map from `k` to `v` (a `HashMap`), if `k` is a
+    /// `String`, `u32`, `u64`, `i32` or `i64`, and `v` is one of the element
+    /// shapes accepted by [`Self::list`]
+    ///
+    /// Returns [`DynamicShapeError::UnsupportedShape`] with the offending shape
+    /// otherwise.
+    pub fn map(
+        &self,
+        k: &'static Shape,
+        v: &'static Shape,
+    ) -> Result<&'static Shape, DynamicShapeError> {
+        macro_rules! map_values {
+            ($k:ty; $($v:ty),*) => {
+                $(
+                    if v == <$v as Facet>::SHAPE {
+                        return Ok(<HashMap<$k, $v> as Facet>::SHAPE);
+                    }
+                )*
+            };
+        }
+        macro_rules! maps {
+            ($($k:ty),*) => {
+                $(
+                    if k == <$k as Facet>::SHAPE {
+                        map_values!(
+                            $k;
+                            (),
+                            bool,
+                            String,
+                            u8,
+                            u16,
+                            u32,
+                            u64,
+                            u128,
+                            i8,
+                            i16,
+                            i32,
+                            i64,
+                            i128,
+                            f32,
+                            f64
+                        );
+                        return Err(DynamicShapeError::UnsupportedShape(v));
+                    }
+                )*
+            };
+        }
+        maps!(String, u32, u64, i32, i64);
+        Err(DynamicShapeError::UnsupportedShape(k))
+    }
+
+    fn name(&self, name: &str) -> &'static str {
+        match self.arena {
+            Some(arena) => arena.intern_name(name),
+            None => Box::leak(name.to_owned().into_boxed_str()),
+        }
+    }
+
+    fn fields(&self, fields: Vec<Field>) -> &'static [Field] {
+        // fields are compared by value when interning the shape, so it's fine
+        // for the arena to leak a fresh slice before finding a match: it only
+        // happens once per distinct layout.
+        match self.arena {
+            Some(arena) => {
+                let shapes = arena.shapes.lock().unwrap();
+                for shape in shapes.iter() {
+                    if let Def::Struct(sd) = shape.def {
+                        if sd.fields == fields.as_slice() {
+                            return sd.fields;
+                        }
+                    }
+                }
+                Box::leak(fields.into_boxed_slice())
+            }
+            None => Box::leak(fields.into_boxed_slice()),
+        }
+    }
+
+    fn shape(&self, shape: Shape) -> &'static Shape {
+        match self.arena {
+            Some(arena) => arena.intern_shape(shape),
+            None => Box::leak(Box::new(shape)),
+        }
+    }
+}
+
+struct PendingField {
+    name: String,
+    shape: &'static Shape,
+    offset: Option<usize>,
+    flags: FieldFlags,
+}
+
+/// Assembles a struct shape at runtime, cf. [`DynamicShapeBuilder::struct_`]
+pub struct DynamicStructBuilder {
+    builder: DynamicShapeBuilder,
+    kind: StructKind,
+    fields: Vec<PendingField>,
+}
+
+impl DynamicStructBuilder {
+    /// Appends a field, placed right after the previous one (with padding)
+    pub fn field(self, name: impl Into<String>, shape: &'static Shape) -> Self {
+        self.push(name.into(), shape, None, FieldFlags::EMPTY)
+    }
+
+    /// Appends a field at an explicit offset, for describing existing buffers
+    pub fn field_at(self, name: impl Into<String>, shape: &'static Shape, offset: usize) -> Self {
+        self.push(name.into(), shape, Some(offset), FieldFlags::EMPTY)
+    }
+
+    /// Appends a field with the given flags (e.g. [`FieldFlags::SENSITIVE`])
+    pub fn field_with_flags(
+        self,
+        name: impl Into<String>,
+        shape: &'static Shape,
+        flags: FieldFlags,
+    ) -> Self {
+        self.push(name.into(), shape, None, flags)
+    }
+
+    /// Appends a tuple field, named after its index
+    pub fn element(self, shape: &'static Shape) -> Self {
+        let name = self.fields.len().to_string();
+        self.push(name, shape, None, FieldFlags::EMPTY)
+    }
+
+    fn push(
+        mut self,
+        name: String,
+        shape: &'static Shape,
+        offset: Option<usize>,
+        flags: FieldFlags,
+    ) -> Self {
+        self.fields.push(PendingField {
+            name,
+            shape,
+            offset,
+            flags,
+        });
+        self
+    }
+
+    /// Computes the layout and field offsets, and returns the finished shape
+    pub fn build(self) -> Result<&'static Shape, DynamicShapeError> {
+        let mut layout = Layout::from_size_align(0, 1).unwrap();
+        let mut placed: Vec<(usize, usize)> = Vec::with_capacity(self.fields.len());
+        let mut fields = Vec::with_capacity(self.fields.len());
+        let mut markers = MarkerTraits::SEND | MarkerTraits::SYNC | MarkerTraits::COPY;
+
+        for (index, pending) in self.fields.iter().enumerate() {
+            if self.fields[..index].iter().any(|f| f.name == pending.name) {
+                return Err(DynamicShapeError::DuplicateField(pending.name.clone()));
+            }
+
+            // dynamic structs are always `Copy`, so they're accepted here too
+            let shape = pending.shape;
+            if !shape.vtable.marker_traits.contains(MarkerTraits::COPY) {
+                return Err(DynamicShapeError::FieldNeedsDrop {
+                    field: pending.name.clone(),
+                    shape,
+                });
+            }
+            markers = markers.intersection(shape.vtable.marker_traits);
+
+            let offset = match pending.offset {
+                Some(offset) => {
+                    if offset % shape.layout.align() != 0 {
+                        return Err(DynamicShapeError::MisalignedField {
+                            field: pending.name.clone(),
+                            offset,
+                            align: shape.layout.align(),
+                        });
+                    }
+                    let end = offset
+                        .checked_add(shape.layout.size())
+                        .ok_or(DynamicShapeError::LayoutOverflow)?;
+                    let align = layout.align().max(shape.layout.align());
+                    layout = Layout::from_size_align(layout.size().max(end), align)
+                        .map_err(|_| DynamicShapeError::LayoutOverflow)?;
+                    offset
+                }
+                None => {
+                    let (extended, offset) = layout
+                        .extend(shape.layout)
+                        .map_err(|_| DynamicShapeError::LayoutOverflow)?;
+                    layout = extended;
+                    offset
+                }
+            };
+
+            let end = offset + shape.layout.size();
+            if shape.layout.size() > 0 {
+                if let Some(other) = placed
+                    .iter()
+                    .position(|&(start, stop)| start < stop && offset < stop && start < end)
+                {
+                    return Err(DynamicShapeError::OverlappingFields {
+                        first: self.fields[other].name.clone(),
+                        second: pending.name.clone(),
+                    });
+                }
+            }
+            placed.push((offset, end));
+
+            fields.push(
+                Field::builder()
+                    .name(self.builder.name(&pending.name))
+                    .shape(shape)
+                    .offset(offset)
+                    .flags(pending.flags)
+                    .build(),
+            );
+        }
+
+        let layout = layout.pad_to_align();
+        let fields = self.builder.fields(fields);
+
+        let shape = Shape::builder()
+            .layout(layout)
+            .vtable(&DYNAMIC_STRUCT_VTABLE_WITH_MARKERS[markers.bits() as usize])
+            .def(Def::Struct(
+                StructDef::builder().kind(self.kind).fields(fields).build(),
+            ))
+            .build();
+        Ok(self.builder.shape(shape))
+    }
+}
+
+const fn dynamic_struct_vtable(markers: u8) -> ValueVTable {
+    ValueVTable::builder()
+        .type_name(|f, _opts| write!(f, "DynamicStruct"))
+        .marker_traits(MarkerTraits::from_bits_truncate(markers))
+        .build()
+}
+
+/// One vtable per combination of marker traits, so that dynamic structs
+/// report the intersection of their fields' markers
+static DYNAMIC_STRUCT_VTABLE_WITH_MARKERS: [ValueVTable; 16] = {
+    let mut vtables = [dynamic_struct_vtable(0); 16];
+    let mut i = 0;
+    while i < 16 {
+        vtables[i] = dynamic_struct_vtable(i as u8);
+        i += 1;
+    }
+    vtables
+};
@@ -122,6 +122,14 @@ where
                             }
                             OpaqueConst::new_unchecked(ptr.as_ptr::<[T; 1]>())
                         })
+                        .get_item_ptr_mut(|ptr, index| unsafe {
+                            if index >= 1 {
+                                panic!(
+                                    "Index out of bounds: the len is 1 but the index is {index}"
+                                );
+                            }
+                            Opaque::new_unchecked(ptr.as_mut::<[T; 1]>().as_mut_ptr())
+                        })
                         .build()
                         },
                     )
@@ -18,12 +18,12 @@ unsafe impl<K, V, S> Facet for HashMap<K, V, S>
 where
     K: Facet + core::cmp::Eq + core::hash::Hash + 'static,
     V: Facet + 'static,
-    S: Facet + Default,
+    S: Facet + Default + core::hash::BuildHasher,
 {
     const ARCHETYPE: Self = HashMap::with_hasher(S::ARCHETYPE);
     const SHAPE: &'static Shape = &const {
         Shape::builder()
-            .layout(Layout::new::<HashMap<K, V>>())
+            .layout(Layout::new::<HashMap<K, V, S>>())
             .vtable(
                 &const {
                     let mut builder = ValueVTable::builder()
@@ -58,12 +58,12 @@ where
                             }
                         })
                         .drop_in_place(|value| unsafe {
-                            core::ptr::drop_in_place(value.as_mut::<HashMap<K, V>>());
+                            core::ptr::drop_in_place(value.as_mut::<HashMap<K, V, S>>());
                         });
 
                     if K::SHAPE.vtable.debug.is_some() && V::SHAPE.vtable.debug.is_some() {
                         builder = builder.debug(|value, f| unsafe {
-                            let value = value.as_ref::<HashMap<K, V>>();
+                            let value = value.as_ref::<HashMap<K, V, S>>();
                             let k_debug = K::SHAPE.vtable.debug.unwrap_unchecked();
                             let v_debug = V::SHAPE.vtable.debug.unwrap_unchecked();
                             write!(f, "{{")?;
@@ -82,13 +82,14 @@ where
                     builder =
                         builder.default_in_place(|target| unsafe { target.write(Self::default()) });
 
-                    builder = builder
-                        .clone_into(|src, dst| unsafe { dst.write(src.as_ref::<HashMap<K, V>>()) });
+                    builder = builder.clone_into(|src, dst| unsafe {
+                        dst.write(src.as_ref::<HashMap<K, V, S>>())
+                    });
 
                     if K::SHAPE.vtable.eq.is_some() && V::SHAPE.vtable.eq.is_some() {
                         builder = builder.eq(|a, b| unsafe {
-                            let a = a.as_ref::<HashMap<K, V>>();
-                            let b = b.as_ref::<HashMap<K, V>>();
+                            let a = a.as_ref::<HashMap<K, V, S>>();
+                            let b = b.as_ref::<HashMap<K, V, S>>();
                             let k_eq = K::SHAPE.vtable.eq.unwrap_unchecked();
                             let v_eq = V::SHAPE.vtable.eq.unwrap_unchecked();
                             a.len() == b.len()
@@ -109,7 +110,7 @@ where
                     if K::SHAPE.vtable.hash.is_some() && V::SHAPE.vtable.hash.is_some() {
                         builder = builder.hash(|value, hasher_this, hasher_write_fn| unsafe {
                             use crate::HasherProxy;
-                            let map = value.as_ref::<HashMap<K, V>>();
+                            let map = value.as_ref::<HashMap<K, V, S>>();
                             let k_hash = K::SHAPE.vtable.hash.unwrap_unchecked();
                             let v_hash = V::SHAPE.vtable.hash.unwrap_unchecked();
                             let mut hasher = HasherProxy::new(hasher_this, hasher_write_fn);
@@ -138,26 +139,38 @@ where
                                     )))
                                 })
                                 .insert(|ptr, key, value| unsafe {
-                                    let map = ptr.as_mut::<HashMap<K, V>>();
+                                    let map = ptr.as_mut::<HashMap<K, V, S>>();
                                     let key = key.read::<K>();
                                     let value = value.read::<V>();
                                     map.insert(key, value);
                                 })
                                 .len(|ptr| unsafe {
-                                    let map = ptr.as_ref::<HashMap<K, V>>();
+                                    let map = ptr.as_ref::<HashMap<K, V, S>>();
                                     map.len()
                                 })
                                 .contains_key(|ptr, key| unsafe {
-                                    let map = ptr.as_ref::<HashMap<K, V>>();
+                                    let map = ptr.as_ref::<HashMap<K, V, S>>();
                                     map.contains_key(key.as_ref())
                                 })
                                 .get_value_ptr(|ptr, key| unsafe {
-                                    let map = ptr.as_ref::<HashMap<K, V>>();
+                                    let map = ptr.as_ref::<HashMap<K, V, S>>();
                                     map.get(key.as_ref())
                                         .map(|v| OpaqueConst::new_unchecked(v as *const _))
                                 })
+                                .get_value_ptr_mut(|ptr, key| unsafe {
+                                    let map = ptr.as_mut::<HashMap<K, V, S>>();
+                                    map.get_mut(key.as_ref())
+                                        .map(|v| Opaque::new_unchecked(v as *mut _))
+                                })
+                                .remove(|ptr, key, value| unsafe {
+                                    let map = ptr.as_mut::<HashMap<K, V, S>>();
+                                    map.remove(key.as_ref::<K>()).map(|v| value.write(v))
+                                })
+                                .clear(|ptr| unsafe {
+                                    ptr.as_mut::<HashMap<K, V, S>>().clear();
+                                })
                                 .iter(|ptr| unsafe {
-                                    let map = ptr.as_ref::<HashMap<K, V>>();
+                                    let map = ptr.as_ref::<HashMap<K, V, S>>();
                                     let keys: VecDeque<&K> = map.keys().collect();
                                     let iter_state = Box::new(HashMapIterator { map: ptr, keys });
                                     Opaque::new_unchecked(Box::into_raw(iter_state) as *mut u8)
@@ -166,7 +179,7 @@ where
                                     MapIterVTable::builder()
                                         .next(|iter_ptr| unsafe {
                                             let state = iter_ptr.as_mut::<HashMapIterator<'_, K>>();
-                                            let map = state.map.as_ref::<HashMap<K, V>>();
+                                            let map = state.map.as_ref::<HashMap<K, V, S>>();
                                             while let Some(key) = state.keys.pop_front() {
                                                 if let Some(value) = map.get(key) {
                                                     return Some((
@@ -1,7 +1,9 @@
 mod array_impl;
 #[cfg(feature = "std")]
 mod hashmap_impl;
+mod option_impl;
 mod scalar_impls;
 mod slice_impl;
 mod tuples_impls;
+#[cfg(feature = "alloc")]
 mod vec_impl;
@@ -0,0 +1,120 @@
+use crate::*;
+use core::{alloc::Layout, hash::Hash as _};
+
+unsafe impl<T> Facet for Option<T>
+where
+    T: Facet,
+{
+    const ARCHETYPE: Self = None;
+    const SHAPE: &'static Shape = &const {
+        Shape::builder()
+            .layout(Layout::new::<Option<T>>())
+            .vtable(
+                &const {
+                    let mut builder = ValueVTable::builder()
+                        .type_name(|f, opts| {
+                            if let Some(opts) = opts.for_children() {
+                                write!(f, "Option<")?;
+                                (T::SHAPE.vtable.type_name)(f, opts)?;
+                                write!(f, ">")
+                            } else {
+                                write!(f, "Option<⋯>")
+                            }
+                        })
+                        .drop_in_place(|value| unsafe {
+                            core::ptr::drop_in_place(value.as_mut::<Option<T>>());
+                        })
+                        .default_in_place(|target| unsafe { target.write(None::<T>) });
+
+                    if T::SHAPE.vtable.debug.is_some() {
+                        builder = builder.debug(|value, f| unsafe {
+                            match value.as_ref::<Option<T>>() {
+                                Some(inner) => {
+                                    write!(f, "Some(")?;
+                                    (T::SHAPE.vtable.debug.unwrap_unchecked())(
+                                        OpaqueConst::from_ref(inner),
+                                        f,
+                                    )?;
+                                    write!(f, ")")
+                                }
+                                None => write!(f, "None"),
+                            }
+                        });
+                    }
+
+                    if T::SHAPE.vtable.eq.is_some() {
+                        builder = builder.eq(|a, b| unsafe {
+                            match (a.as_ref::<Option<T>>(), b.as_ref::<Option<T>>()) {
+                                (Some(a), Some(b)) => (T::SHAPE.vtable.eq.unwrap_unchecked())(
+                                    OpaqueConst::from_ref(a),
+                                    OpaqueConst::from_ref(b),
+                                ),
+                                (None, None) => true,
+                                _ => false,
+                            }
+                        });
+                    }
+
+                    if T::SHAPE.vtable.clone_into.is_some() {
+                        builder = builder.clone_into(|src, dst| unsafe {
+                            match src.as_ref::<Option<T>>() {
+                                Some(inner) => {
+                                    // clone into a temporary, then move it into the option
+                                    let mut value = core::mem::MaybeUninit::<T>::uninit();
+                                    (T::SHAPE.vtable.clone_into.unwrap_unchecked())(
+                                        OpaqueConst::from_ref(inner),
+                                        OpaqueUninit::new(value.as_mut_ptr()),
+                                    );
+                                    dst.write(Some(value.assume_init()))
+                                }
+                                None => dst.write(None::<T>),
+                            }
+                        });
+                    }
+
+                    if T::SHAPE.vtable.hash.is_some() {
+                        builder = builder.hash(|value, hasher_this, hasher_write_fn| unsafe {
+                            use crate::HasherProxy;
+                            let option = value.as_ref::<Option<T>>();
+                            let mut hasher = HasherProxy::new(hasher_this, hasher_write_fn);
+                            option.is_some().hash(&mut hasher);
+                            if let Some(inner) = option {
+                                (T::SHAPE.vtable.hash.unwrap_unchecked())(
+                                    OpaqueConst::from_ref(inner),
+                                    hasher_this,
+                                    hasher_write_fn,
+                                );
+                            }
+                        });
+                    }
+
+                    builder = builder.marker_traits(T::SHAPE.vtable.marker_traits);
+
+                    builder.build()
+                },
+            )
+            .def(Def::Option(
+                OptionDef::builder()
+                    .vtable(
+                        &const {
+                            OptionVTable::builder()
+                                .is_some(|option| unsafe { option.as_ref::<Option<T>>().is_some() })
+                                .get_value(|option| unsafe {
+                                    option
+                                        .as_ref::<Option<T>>()
+                                        .as_ref()
+                                        .map(|value| OpaqueConst::new_unchecked(value as *const T))
+                                })
+                                .init_some(|option, value| unsafe {
+                                    option.write(Some(value.read::<T>()))
+                                })
+                                .init_none(|option| unsafe { option.write(None::<T>) })
+                                .build()
+                        },
+                    )
+                    .t(T::SHAPE)
+                    .build(),
+            ))
+            .build()
+    };
+}
@@ -2,6 +2,9 @@ use crate::value_vtable;
 use crate::*;
 use core::alloc::Layout;
 
+#[cfg(feature = "alloc")]
+use alloc::string::String;
+
 unsafe impl Facet for () {
     const ARCHETYPE: Self = ();
     const SHAPE: &'static Shape = &const {
@@ -13,13 +16,18 @@ unsafe impl Facet for () {
     };
 }
 
+#[cfg(feature = "alloc")]
 unsafe impl Facet for String {
     const ARCHETYPE: Self = String::new();
     const SHAPE: &'static Shape = &const {
         Shape::builder()
             .layout(Layout::new::<Self>())
             .def(Def::Scalar(ScalarDef::of::<Self>()))
-            .vtable(value_vtable!(String, |f, _opts| write!(f, "String")))
+            .vtable(value_vtable!(
+                String,
+                |f, _opts| write!(f, "String"),
+                try_from = [&str]
+            ))
             .build()
     };
 }
@@ -35,17 +43,17 @@ unsafe impl Facet for &str {
     };
 }
 
-#[cfg(feature = "std")]
-unsafe impl Facet for std::borrow::Cow<'_, str> {
-    const ARCHETYPE: Self = std::borrow::Cow::Borrowed("");
+#[cfg(feature = "alloc")]
+unsafe impl Facet for alloc::borrow::Cow<'_, str> {
+    const ARCHETYPE: Self = alloc::borrow::Cow::Borrowed("");
     const SHAPE: &'static Shape = &const {
         Shape::builder()
             .layout(Layout::new::<Self>())
             .def(Def::Scalar(ScalarDef::of::<Self>()))
-            .vtable(value_vtable!(std::borrow::Cow<'_, str>, |f, _opts| write!(
-                f,
-                "Cow<'_, str>"
-            )))
+            .vtable(value_vtable!(
+                alloc::borrow::Cow<'_, str>,
+                |f, _opts| write!(f, "Cow<'_, str>")
+            ))
             .build()
     };
 }
@@ -69,10 +77,24 @@ macro_rules! impl_facet_for_integer {
                 Shape::builder()
                     .layout(Layout::new::<Self>())
                     .def(Def::Scalar(ScalarDef::of::<Self>()))
-                    .vtable(value_vtable!($type, |f, _opts| write!(
-                        f,
-                        stringify!($type)
-                    )))
+                    .vtable(
+                        &const {
+                            let mut vtable = *value_vtable!(
+                                $type,
+                                |f, _opts| write!(f, stringify!($type)),
+                                try_from = [u8, i8, u16, i16, u32, i32, u64, i64, u128, i128]
+                            );
+                            // same as the `FromStr` entry, but the error points at the
+                            // offending byte
+                            vtable.parse = Some(|s, target| match s.parse::<$type>() {
+                                Ok(value) => Ok(unsafe { target.write(value) }),
+                                Err(e) => Err(ParseError::from_display(&e)
+                                    .with_shape(Self::SHAPE)
+                                    .at_invalid_digit(s, <$type>::MIN != 0)),
+                            });
+                            vtable
+                        },
+                    )
                     .build()
             };
         }
@@ -111,17 +133,16 @@ macro_rules! impl_facet_for_float {
 impl_facet_for_float!(f32);
 impl_facet_for_float!(f64);
 
-#[cfg(feature = "std")]
-unsafe impl Facet for std::net::SocketAddr {
-    const ARCHETYPE: Self = std::net::SocketAddr::V4(std::net::SocketAddrV4::new(
-        std::net::Ipv4Addr::new(0, 0, 0, 0),
+unsafe impl Facet for core::net::SocketAddr {
+    const ARCHETYPE: Self = core::net::SocketAddr::V4(core::net::SocketAddrV4::new(
+        core::net::Ipv4Addr::new(0, 0, 0, 0),
         0,
     ));
     const SHAPE: &'static Shape = &const {
         Shape::builder()
             .layout(Layout::new::<Self>())
             .def(Def::Scalar(ScalarDef::of::<Self>()))
-            .vtable(value_vtable!(std::net::SocketAddr, |f, _opts| write!(
+            .vtable(value_vtable!(core::net::SocketAddr, |f, _opts| write!(
                 f,
                 "SocketAddr"
             )))
@@ -32,6 +32,10 @@ where
                             }
                             OpaqueConst::new_unchecked(slice.as_ptr().add(index))
                         })
+                        // `get_item_ptr_mut`, `pop`, `remove_at`, `clear`, `truncate` and
+                        // `reserve` stay `None`: the items are behind a shared reference,
+                        // so they can't be mutated in place, and a borrowed slice can't
+                        // change length.
                         .build()
                         },
                     )
@@ -1,4 +1,5 @@
 use crate::*;
+use alloc::vec::Vec;
 use core::{alloc::Layout, hash::Hash as _};
 
 unsafe impl<T> Facet for Vec<T>
@@ -120,6 +121,33 @@ where
                             }
                             OpaqueConst::new_unchecked(vec.as_ptr().add(index))
                         })
+                        .get_item_ptr_mut(|ptr, index| unsafe {
+                            let vec = ptr.as_mut::<Vec<T>>();
+                            let len = vec.len();
+                            if index >= len {
+                                panic!(
+                                    "Index out of bounds: the len is {len} but the index is {index}"
+                                );
+                            }
+                            Opaque::new_unchecked(vec.as_mut_ptr().add(index))
+                        })
+                        .pop(|ptr, item| unsafe {
+                            let vec = ptr.as_mut::<Vec<T>>();
+                            vec.pop().map(|value| item.write(value))
+                        })
+                        .remove_at(|ptr, index, item| unsafe {
+                            let vec = ptr.as_mut::<Vec<T>>();
+                            item.write(vec.remove(index))
+                        })
+                        .clear(|ptr| unsafe {
+                            ptr.as_mut::<Vec<T>>().clear();
+                        })
+                        .truncate(|ptr, len| unsafe {
+                            ptr.as_mut::<Vec<T>>().truncate(len);
+                        })
+                        .reserve(|ptr, additional| unsafe {
+                            ptr.as_mut::<Vec<T>>().reserve(additional);
+                        })
                         .build()
                         },
                     )
@@ -1,6 +1,10 @@
+#![cfg_attr(not(feature = "std"), no_std)]
 #![warn(missing_docs)]
 #![doc = include_str!("../README.md")]
 
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
 pub use facet_spez;
 
 pub use facet_opaque::*;
@@ -11,6 +15,11 @@ mod impls;
 mod macros;
 pub use macros::*;
 
+#[cfg(feature = "std")]
+mod dynamic;
+#[cfg(feature = "std")]
+pub use dynamic::*;
+
 /// Allows querying the [`Shape`] of a type, which in turn lets us inspect any fields, build a value of
 /// this type progressively, etc.
 ///
@@ -140,12 +140,14 @@ macro_rules! enum_variants {
 /// Creates a `ValueVTable` for a given type.
 ///
 /// This macro generates a `ValueVTable` with implementations for various traits
-/// (Display, Debug, PartialEq, Eq, PartialOrd, Ord, Hash) if they are implemented for the given type.
+/// (Display, Debug, FromStr, PartialEq, Eq, PartialOrd, Ord, Hash) if they are implemented for the given type.
 ///
 /// # Arguments
 ///
 /// * `$type_name:ty` - The type for which to create the `ValueVTable`.
 /// * `$type_name_fn:expr` - A function that writes the type name to a formatter.
+/// * `try_from = [$source:ty, ...]` (optional) - Source types the type implements `TryFrom` for.
+///   The generated `try_from` entry dispatches on the source shape.
 ///
 /// # Example
 ///
@@ -162,91 +164,130 @@ macro_rules! enum_variants {
 /// arrays, etc. — essentially, this macro is only useful for 1) scalars, 2) inside a derive macro
 #[macro_export]
 macro_rules! value_vtable {
-    ($type_name:ty, $type_name_fn:expr) => {
-        &const {
-            let mut builder = $crate::ValueVTable::builder()
-                .type_name($type_name_fn)
-                .drop_in_place(|data| unsafe { data.drop_in_place::<$type_name>() });
-
-            if $crate::facet_spez::impls!($type_name: core::fmt::Display) {
-                builder = builder.display(|data, f| {
-                    use $crate::facet_spez::*;
-                    (&&Spez(unsafe { data.as_ref::<$type_name>() })).spez_display(f)
-                });
-            }
+    (@builder $type_name:ty, $type_name_fn:expr) => {{
+        let mut builder = $crate::ValueVTable::builder()
+            .type_name($type_name_fn)
+            .drop_in_place(|data| unsafe { data.drop_in_place::<$type_name>() });
 
-            if $crate::facet_spez::impls!($type_name: core::fmt::Debug) {
-                builder = builder.debug(|data, f| {
-                    use $crate::facet_spez::*;
-                    (&&Spez(unsafe { data.as_ref::<$type_name>() })).spez_debug(f)
-                });
-            }
+        if $crate::facet_spez::impls!($type_name: core::fmt::Display) {
+            builder = builder.display(|data, f| {
+                use $crate::facet_spez::*;
+                (&&Spez(unsafe { data.as_ref::<$type_name>() })).spez_display(f)
+            });
+        }
 
-            if $crate::facet_spez::impls!($type_name: core::default::Default) {
-                builder = builder.default_in_place(|target| {
-                    use $crate::facet_spez::*;
-                    (&&Spez(<$type_name as $crate::Facet>::ARCHETYPE)).spez_default_in_place(target)
-                });
-            }
+        if $crate::facet_spez::impls!($type_name: core::fmt::Debug) {
+            builder = builder.debug(|data, f| {
+                use $crate::facet_spez::*;
+                (&&Spez(unsafe { data.as_ref::<$type_name>() })).spez_debug(f)
+            });
+        }
 
-            if $crate::facet_spez::impls!($type_name: core::clone::Clone) {
-                builder = builder.clone_into(|src, dst| {
-                    use $crate::facet_spez::*;
-                    (&&Spez(unsafe { src.as_ref::<$type_name>() })).spez_clone_into(dst)
-                });
-            }
+        if $crate::facet_spez::impls!($type_name: core::str::FromStr) {
+            builder = builder.parse(|s, target| {
+                use $crate::facet_spez::*;
+                (&&&Spez(<$type_name as $crate::Facet>::ARCHETYPE)).spez_parse(s, target)?;
+                Ok(unsafe { target.assume_init() })
+            });
+        }
 
-            {
-                let mut traits = $crate::MarkerTraits::empty();
-                if $crate::facet_spez::impls!($type_name: core::cmp::Eq) {
-                    traits = traits.union($crate::MarkerTraits::EQ);
-                }
-                if $crate::facet_spez::impls!($type_name: core::marker::Send) {
-                    traits = traits.union($crate::MarkerTraits::SEND);
-                }
-                if $crate::facet_spez::impls!($type_name: core::marker::Sync) {
-                    traits = traits.union($crate::MarkerTraits::SYNC);
-                }
-                if $crate::facet_spez::impls!($type_name: core::marker::Copy) {
-                    traits = traits.union($crate::MarkerTraits::COPY);
-                }
-                builder = builder.marker_traits(traits);
-            }
+        if $crate::facet_spez::impls!($type_name: core::default::Default) {
+            builder = builder.default_in_place(|target| {
+                use $crate::facet_spez::*;
+                (&&Spez(<$type_name as $crate::Facet>::ARCHETYPE)).spez_default_in_place(target)
+            });
+        }
 
-            if $crate::facet_spez::impls!($type_name: core::cmp::PartialEq) {
-                builder = builder.eq(|left, right| {
-                    use $crate::facet_spez::*;
-                    (&&Spez(unsafe { left.as_ref::<$type_name>() }))
-                        .spez_eq(&&Spez(unsafe { right.as_ref::<$type_name>() }))
-                });
-            }
+        if $crate::facet_spez::impls!($type_name: core::clone::Clone) {
+            builder = builder.clone_into(|src, dst| {
+                use $crate::facet_spez::*;
+                (&&Spez(unsafe { src.as_ref::<$type_name>() })).spez_clone_into(dst)
+            });
+        }
 
-            if $crate::facet_spez::impls!($type_name: core::cmp::PartialOrd) {
-                builder = builder.partial_ord(|left, right| {
-                    use $crate::facet_spez::*;
-                    (&&Spez(unsafe { left.as_ref::<$type_name>() }))
-                        .spez_partial_cmp(&&Spez(unsafe { right.as_ref::<$type_name>() }))
-                });
+        {
+            let mut traits = $crate::MarkerTraits::empty();
+            if $crate::facet_spez::impls!($type_name: core::cmp::Eq) {
+                traits = traits.union($crate::MarkerTraits::EQ);
             }
-
-            if $crate::facet_spez::impls!($type_name: core::cmp::Ord) {
-                builder = builder.ord(|left, right| {
-                    use $crate::facet_spez::*;
-                    (&&Spez(unsafe { left.as_ref::<$type_name>() }))
-                        .spez_cmp(&&Spez(unsafe { right.as_ref::<$type_name>() }))
-                });
+            if $crate::facet_spez::impls!($type_name: core::marker::Send) {
+                traits = traits.union($crate::MarkerTraits::SEND);
             }
-
-            if $crate::facet_spez::impls!($type_name: core::hash::Hash) {
-                builder = builder.hash(|value, hasher_this, hasher_write_fn| {
-                    use $crate::facet_spez::*;
-                    use $crate::HasherProxy;
-                    (&&Spez(unsafe { value.as_ref::<$type_name>() }))
-                        .spez_hash(&mut unsafe { HasherProxy::new(hasher_this, hasher_write_fn) })
-                });
+            if $crate::facet_spez::impls!($type_name: core::marker::Sync) {
+                traits = traits.union($crate::MarkerTraits::SYNC);
             }
+            if $crate::facet_spez::impls!($type_name: core::marker::Copy) {
+                traits = traits.union($crate::MarkerTraits::COPY);
+            }
+            builder = builder.marker_traits(traits);
+        }
+
+        if $crate::facet_spez::impls!($type_name: core::cmp::PartialEq) {
+            builder = builder.eq(|left, right| {
+                use $crate::facet_spez::*;
+                (&&Spez(unsafe { left.as_ref::<$type_name>() }))
+                    .spez_eq(&&Spez(unsafe { right.as_ref::<$type_name>() }))
+            });
+        }
+
+        if $crate::facet_spez::impls!($type_name: core::cmp::PartialOrd) {
+            builder = builder.partial_ord(|left, right| {
+                use $crate::facet_spez::*;
+                (&&Spez(unsafe { left.as_ref::<$type_name>() }))
+                    .spez_partial_cmp(&&Spez(unsafe { right.as_ref::<$type_name>() }))
+            });
+        }
 
-            builder.build()
+        if $crate::facet_spez::impls!($type_name: core::cmp::Ord) {
+            builder = builder.ord(|left, right| {
+                use $crate::facet_spez::*;
+                (&&Spez(unsafe { left.as_ref::<$type_name>() }))
+                    .spez_cmp(&&Spez(unsafe { right.as_ref::<$type_name>() }))
+            });
+        }
+
+        if $crate::facet_spez::impls!($type_name: core::hash::Hash) {
+            builder = builder.hash(|value, hasher_this, hasher_write_fn| {
+                use $crate::facet_spez::*;
+                use $crate::HasherProxy;
+                (&&Spez(unsafe { value.as_ref::<$type_name>() }))
+                    .spez_hash(&mut unsafe { HasherProxy::new(hasher_this, hasher_write_fn) })
+            });
+        }
+
+        builder
+    }};
+    ($type_name:ty, $type_name_fn:expr) => {
+        &const { $crate::value_vtable!(@builder $type_name, $type_name_fn).build() }
+    };
+    ($type_name:ty, $type_name_fn:expr, try_from = [$($source:ty),+ $(,)?]) => {
+        &const {
+            $crate::value_vtable!(@builder $type_name, $type_name_fn)
+                .try_from(|source, source_shape, target| {
+                    $(
+                        if source_shape == <$source as $crate::Facet>::SHAPE {
+                            let source = unsafe { core::ptr::read(source.as_ptr::<$source>()) };
+                            return match <$type_name as core::convert::TryFrom<$source>>::try_from(source) {
+                                Ok(value) => Ok(unsafe { target.write(value) }),
+                                // infallible conversions have an uninhabited error type
+                                #[allow(unreachable_code)]
+                                Err(err) => {
+                                    use $crate::facet_spez::*;
+                                    Err($crate::TryFromError::Failed {
+                                        message: (&&Spez(err)).spez_error_message("conversion failed"),
+                                        source: source_shape,
+                                        target: <$type_name as $crate::Facet>::SHAPE,
+                                    })
+                                }
+                            };
+                        }
+                    )+
+                    Err($crate::TryFromError::Incompatible {
+                        source: source_shape,
+                        target: <$type_name as $crate::Facet>::SHAPE,
+                    })
+                })
+                .build()
         }
     };
 }
@@ -1,3 +1,4 @@
+#![cfg_attr(not(feature = "std"), no_std)]
 #![warn(missing_docs)]
 #![doc = include_str!("../README.md")]
 
@@ -5,6 +6,9 @@
 
 // TODO: mark `non_exhaustive`, add `const fn` builder patterns
 
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
 use core::alloc::Layout;
 use core::fmt;
 
@@ -16,6 +20,9 @@ pub use list::*;
 mod map;
 pub use map::*;
 
+mod option;
+pub use option::*;
+
 mod value;
 pub use value::*;
 
@@ -33,6 +40,9 @@ pub struct Shape {
 
     /// Details/contents of the value
     pub def: Def,
+
+    /// Doc comment lines of the type, as written after `///` (leading space included)
+    pub doc: &'static [&'static str],
 }
 
 impl Shape {
@@ -127,6 +137,7 @@ pub struct ShapeBuilder {
     layout: Option<Layout>,
     vtable: Option<&'static ValueVTable>,
     def: Option<Def>,
+    doc: &'static [&'static str],
 }
 
 impl ShapeBuilder {
@@ -137,6 +148,7 @@ impl ShapeBuilder {
             layout: None,
             vtable: None,
             def: None,
+            doc: &[],
         }
     }
 
@@ -161,6 +173,13 @@ impl ShapeBuilder {
         self
     }
 
+    /// Sets the `doc` field of the `ShapeBuilder`.
+    #[inline]
+    pub const fn doc(mut self, doc: &'static [&'static str]) -> Self {
+        self.doc = doc;
+        self
+    }
+
     /// Builds a `Shape` from the `ShapeBuilder`.
     ///
     /// # Panics
@@ -172,6 +191,7 @@ impl ShapeBuilder {
             layout: self.layout.unwrap(),
             vtable: self.vtable.unwrap(),
             def: self.def.unwrap(),
+            doc: self.doc,
         }
     }
 }
@@ -215,10 +235,37 @@ impl core::fmt::Display for Shape {
 
 impl Shape {
     /// Heap-allocate a value of this shape
-    #[cfg(feature = "std")]
+    ///
+    /// Zero-sized shapes don't touch the allocator: they get a dangling pointer,
+    /// aligned for the shape.
+    #[cfg(feature = "alloc")]
     #[inline]
     pub fn allocate(&self) -> facet_opaque::OpaqueUninit<'static> {
-        facet_opaque::OpaqueUninit::new(unsafe { std::alloc::alloc(self.layout) })
+        if self.layout.size() == 0 {
+            return facet_opaque::OpaqueUninit::new(core::ptr::without_provenance_mut::<u8>(
+                self.layout.align(),
+            ));
+        }
+        let ptr = unsafe { alloc::alloc::alloc(self.layout) };
+        if ptr.is_null() {
+            alloc::alloc::handle_alloc_error(self.layout);
+        }
+        facet_opaque::OpaqueUninit::new(ptr)
+    }
+
+    /// Frees memory obtained from [`Shape::allocate`], without dropping its contents
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must have been returned by [`Shape::allocate`] on this same shape,
+    /// and must not be used afterwards.
+    #[cfg(feature = "alloc")]
+    #[inline]
+    pub unsafe fn deallocate(&self, ptr: facet_opaque::OpaqueUninit<'_>) {
+        if self.layout.size() == 0 {
+            return;
+        }
+        unsafe { alloc::alloc::dealloc(ptr.as_mut_ptr(), self.layout) }
     }
 }
 
@@ -323,6 +370,9 @@ pub enum StructKind {
 
     /// (T0, T1)
     Tuple,
+
+    /// struct UnitStruct;
+    Unit,
 }
 
 /// Describes a field in a struct or tuple
@@ -340,6 +390,12 @@ pub struct Field {
 
     /// flags for the field (e.g. sensitive, etc.)
     pub flags: FieldFlags,
+
+    /// doc comment lines of the field, as written after `///` (leading space included)
+    pub doc: &'static [&'static str],
+
+    /// for a list merged by key, the name of the item field that identifies items
+    pub merge_key: Option<&'static str>,
 }
 
 impl Field {
@@ -355,6 +411,8 @@ pub struct FieldBuilder {
     shape: Option<&'static Shape>,
     offset: Option<usize>,
     flags: Option<FieldFlags>,
+    doc: &'static [&'static str],
+    merge_key: Option<&'static str>,
 }
 
 impl FieldBuilder {
@@ -366,6 +424,8 @@ impl FieldBuilder {
             shape: None,
             offset: None,
             flags: None,
+            doc: &[],
+            merge_key: None,
         }
     }
 
@@ -393,6 +453,18 @@ impl FieldBuilder {
         self
     }
 
+    /// Sets the doc comment lines for the Field
+    pub const fn doc(mut self, doc: &'static [&'static str]) -> Self {
+        self.doc = doc;
+        self
+    }
+
+    /// Sets the name of the item field that identifies items, for a list merged by key
+    pub const fn merge_key(mut self, merge_key: &'static str) -> Self {
+        self.merge_key = Some(merge_key);
+        self
+    }
+
     /// Builds the Field
     pub const fn build(self) -> Field {
         Field {
@@ -403,6 +475,8 @@ impl FieldBuilder {
                 Some(flags) => flags,
                 None => FieldFlags::EMPTY,
             },
+            doc: self.doc,
+            merge_key: self.merge_key,
         }
     }
 }
@@ -416,6 +490,32 @@ bitflags::bitflags! {
 
         /// Flag indicating this field contains sensitive data that should not be displayed
         const SENSITIVE = 1 << 0;
+
+        /// Flag indicating this field takes its type's default value when it's missing
+        /// from the input, instead of causing an error
+        const DEFAULT = 1 << 1;
+
+        /// Flag indicating the fields of this (struct) field appear inline, as if they
+        /// were fields of the parent struct
+        const FLATTEN = 1 << 2;
+
+        /// Flag indicating a later layer's list or map replaces this (list or map) field
+        /// when layers are merged — the default for lists
+        const MERGE_REPLACE = 1 << 3;
+
+        /// Flag indicating a later layer's items are appended to this (list) field when
+        /// layers are merged, and its entries replace those with the same key in this
+        /// (map) field
+        const MERGE_APPEND = 1 << 4;
+
+        /// Flag indicating a later layer is merged into this (list or map) field item by
+        /// item, matching map entries by key and list items by their
+        /// [`merge_key`](Field::merge_key) field — the default for maps
+        const MERGE_BY_KEY = 1 << 5;
+
+        /// Flag indicating this field is read from a positional command-line argument
+        /// rather than a `--flag`
+        const POSITIONAL = 1 << 6;
     }
 }
 
@@ -435,9 +535,9 @@ impl core::fmt::Display for FieldFlags {
         // Define a vector of flag entries: (flag, name)
         let flags = [
             (FieldFlags::SENSITIVE, "sensitive"),
-            // Future flags can be easily added here:
-            // (FieldFlags::SOME_FLAG, "some_flag"),
-            // (FieldFlags::ANOTHER_FLAG, "another_flag"),
+            (FieldFlags::DEFAULT, "default"),
+            (FieldFlags::FLATTEN, "flatten"),
+            (FieldFlags::POSITIONAL, "positional"),
         ];
 
         // Write all active flags with proper separators
@@ -575,6 +675,60 @@ impl ListDefBuilder {
     }
 }
 
+/// Fields for option types
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+#[non_exhaustive]
+pub struct OptionDef {
+    /// vtable for interacting with the option
+    pub vtable: &'static OptionVTable,
+    /// shape of the value held by the option
+    pub t: &'static Shape,
+}
+
+impl OptionDef {
+    /// Returns a builder for OptionDef
+    pub const fn builder() -> OptionDefBuilder {
+        OptionDefBuilder::new()
+    }
+}
+
+/// Builder for OptionDef
+pub struct OptionDefBuilder {
+    vtable: Option<&'static OptionVTable>,
+    t: Option<&'static Shape>,
+}
+
+impl OptionDefBuilder {
+    /// Creates a new OptionDefBuilder
+    #[allow(clippy::new_without_default)]
+    pub const fn new() -> Self {
+        Self {
+            vtable: None,
+            t: None,
+        }
+    }
+
+    /// Sets the vtable for the OptionDef
+    pub const fn vtable(mut self, vtable: &'static OptionVTable) -> Self {
+        self.vtable = Some(vtable);
+        self
+    }
+
+    /// Sets the value shape for the OptionDef
+    pub const fn t(mut self, t: &'static Shape) -> Self {
+        self.t = Some(t);
+        self
+    }
+
+    /// Builds the OptionDef
+    pub const fn build(self) -> OptionDef {
+        OptionDef {
+            vtable: self.vtable.unwrap(),
+            t: self.t.unwrap(),
+        }
+    }
+}
+
 /// Fields for enum types
 #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
 #[non_exhaustive]
@@ -641,6 +795,9 @@ pub struct Variant {
 
     /// Kind of variant (unit, tuple, or struct)
     pub kind: VariantKind,
+
+    /// Doc comment lines of the variant, as written after `///` (leading space included)
+    pub doc: &'static [&'static str],
 }
 
 impl Variant {
@@ -655,6 +812,7 @@ pub struct VariantBuilder {
     name: Option<&'static str>,
     discriminant: Option<Option<i64>>,
     kind: Option<VariantKind>,
+    doc: &'static [&'static str],
 }
 
 impl VariantBuilder {
@@ -665,6 +823,7 @@ impl VariantBuilder {
             name: None,
             discriminant: None,
             kind: None,
+            doc: &[],
         }
     }
 
@@ -686,12 +845,19 @@ impl VariantBuilder {
         self
     }
 
+    /// Sets the doc comment lines for the Variant
+    pub const fn doc(mut self, doc: &'static [&'static str]) -> Self {
+        self.doc = doc;
+        self
+    }
+
     /// Builds the Variant
     pub const fn build(self) -> Variant {
         Variant {
             name: self.name.unwrap(),
             discriminant: self.discriminant.unwrap(),
             kind: self.kind.unwrap(),
+            doc: self.doc,
         }
     }
 }
@@ -823,6 +989,11 @@ pub enum Def {
     ///
     /// e.g. `enum Enum { Variant1, Variant2 }`
     Enum(EnumDef),
+
+    /// Optional value — not an [`Def::Enum`], since its layout is up to the compiler
+    ///
+    /// e.g. `Option<T>`
+    Option(OptionDef),
 }
 
 /// A characteristic a shape can have
@@ -33,6 +33,60 @@ pub type ListLenFn = unsafe fn(list: OpaqueConst) -> usize;
 /// The `list` parameter must point to aligned, initialized memory of the correct type.
 pub type ListGetItemPtrFn = unsafe fn(list: OpaqueConst, index: usize) -> OpaqueConst;
 
+/// Get a mutable pointer to the item at the given index. Panics if out of bounds.
+///
+/// # Safety
+///
+/// The `list` parameter must point to aligned, initialized memory of the correct type.
+pub type ListGetItemPtrMutFn = unsafe fn(list: Opaque, index: usize) -> Opaque;
+
+/// Remove the last item from the list, moving it into `item`.
+/// Returns `None` (and leaves `item` untouched) if the list is empty.
+///
+/// # Safety
+///
+/// The `list` parameter must point to aligned, initialized memory of the correct type.
+/// `item` must point to uninitialized memory large enough for an item.
+pub type ListPopFn = for<'list, 'item> unsafe fn(
+    list: Opaque<'list>,
+    item: OpaqueUninit<'item>,
+) -> Option<Opaque<'item>>;
+
+/// Remove the item at the given index, shifting the following items down,
+/// and move it into `item`. Panics if out of bounds.
+///
+/// # Safety
+///
+/// The `list` parameter must point to aligned, initialized memory of the correct type.
+/// `item` must point to uninitialized memory large enough for an item.
+pub type ListRemoveAtFn = for<'list, 'item> unsafe fn(
+    list: Opaque<'list>,
+    index: usize,
+    item: OpaqueUninit<'item>,
+) -> Opaque<'item>;
+
+/// Remove (and drop) every item of the list
+///
+/// # Safety
+///
+/// The `list` parameter must point to aligned, initialized memory of the correct type.
+pub type ListClearFn = unsafe fn(list: Opaque);
+
+/// Shorten the list to `len` items, dropping the rest. Does nothing if the list is
+/// already that short.
+///
+/// # Safety
+///
+/// The `list` parameter must point to aligned, initialized memory of the correct type.
+pub type ListTruncateFn = unsafe fn(list: Opaque, len: usize);
+
+/// Reserve capacity for at least `additional` more items
+///
+/// # Safety
+///
+/// The `list` parameter must point to aligned, initialized memory of the correct type.
+pub type ListReserveFn = unsafe fn(list: Opaque, additional: usize);
+
 /// Virtual table for a list-like type (like `Vec<T>`,
 /// but also `HashSet<T>`, etc.)
 #[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
@@ -49,6 +103,24 @@ pub struct ListVTable {
 
     /// cf. [`ListGetItemPtrFn`]
     pub get_item_ptr: ListGetItemPtrFn,
+
+    /// cf. [`ListGetItemPtrMutFn`] — `None` if items can't be mutated in place
+    pub get_item_ptr_mut: Option<ListGetItemPtrMutFn>,
+
+    /// cf. [`ListPopFn`] — `None` if the list can't shrink
+    pub pop: Option<ListPopFn>,
+
+    /// cf. [`ListRemoveAtFn`] — `None` if the list can't shrink
+    pub remove_at: Option<ListRemoveAtFn>,
+
+    /// cf. [`ListClearFn`] — `None` if the list can't shrink
+    pub clear: Option<ListClearFn>,
+
+    /// cf. [`ListTruncateFn`] — `None` if the list can't shrink
+    pub truncate: Option<ListTruncateFn>,
+
+    /// cf. [`ListReserveFn`] — `None` if the list has no notion of capacity
+    pub reserve: Option<ListReserveFn>,
 }
 
 impl ListVTable {
@@ -64,6 +136,12 @@ pub struct ListVTableBuilder {
     push: Option<ListPushFn>,
     len: Option<ListLenFn>,
     get_item_ptr: Option<ListGetItemPtrFn>,
+    get_item_ptr_mut: Option<ListGetItemPtrMutFn>,
+    pop: Option<ListPopFn>,
+    remove_at: Option<ListRemoveAtFn>,
+    clear: Option<ListClearFn>,
+    truncate: Option<ListTruncateFn>,
+    reserve: Option<ListReserveFn>,
 }
 
 impl ListVTableBuilder {
@@ -75,6 +153,12 @@ impl ListVTableBuilder {
             push: None,
             len: None,
             get_item_ptr: None,
+            get_item_ptr_mut: None,
+            pop: None,
+            remove_at: None,
+            clear: None,
+            truncate: None,
+            reserve: None,
         }
     }
 
@@ -102,6 +186,42 @@ impl ListVTableBuilder {
         self
     }
 
+    /// Sets the get_item_ptr_mut field
+    pub const fn get_item_ptr_mut(mut self, f: ListGetItemPtrMutFn) -> Self {
+        self.get_item_ptr_mut = Some(f);
+        self
+    }
+
+    /// Sets the pop field
+    pub const fn pop(mut self, f: ListPopFn) -> Self {
+        self.pop = Some(f);
+        self
+    }
+
+    /// Sets the remove_at field
+    pub const fn remove_at(mut self, f: ListRemoveAtFn) -> Self {
+        self.remove_at = Some(f);
+        self
+    }
+
+    /// Sets the clear field
+    pub const fn clear(mut self, f: ListClearFn) -> Self {
+        self.clear = Some(f);
+        self
+    }
+
+    /// Sets the truncate field
+    pub const fn truncate(mut self, f: ListTruncateFn) -> Self {
+        self.truncate = Some(f);
+        self
+    }
+
+    /// Sets the reserve field
+    pub const fn reserve(mut self, f: ListReserveFn) -> Self {
+        self.reserve = Some(f);
+        self
+    }
+
     /// Builds the [`ListVTable`] from the current state of the builder.
     ///
     /// # Panics
@@ -113,6 +233,12 @@ impl ListVTableBuilder {
             push: self.push.unwrap(),
             len: self.len.unwrap(),
             get_item_ptr: self.get_item_ptr.unwrap(),
+            get_item_ptr_mut: self.get_item_ptr_mut,
+            pop: self.pop,
+            remove_at: self.remove_at,
+            clear: self.clear,
+            truncate: self.truncate,
+            reserve: self.reserve,
         }
     }
 }
@@ -44,6 +44,34 @@ pub type MapGetValuePtrFn = for<'map, 'key> unsafe fn(
     key: OpaqueConst<'key>,
 ) -> Option<OpaqueConst<'map>>;
 
+/// Get a mutable pointer to a value for a given key, returns None if not found
+///
+/// # Safety
+///
+/// The `map` parameter must point to aligned, initialized memory of the correct type.
+pub type MapGetValuePtrMutFn =
+    for<'map, 'key> unsafe fn(map: Opaque<'map>, key: OpaqueConst<'key>) -> Option<Opaque<'map>>;
+
+/// Remove the entry for a given key, moving its value into `value`.
+/// Returns `None` (and leaves `value` untouched) if the key isn't in the map.
+///
+/// # Safety
+///
+/// The `map` parameter must point to aligned, initialized memory of the correct type.
+/// `value` must point to uninitialized memory large enough for a value.
+pub type MapRemoveFn = for<'map, 'key, 'value> unsafe fn(
+    map: Opaque<'map>,
+    key: OpaqueConst<'key>,
+    value: OpaqueUninit<'value>,
+) -> Option<Opaque<'value>>;
+
+/// Remove (and drop) every entry of the map
+///
+/// # Safety
+///
+/// The `map` parameter must point to aligned, initialized memory of the correct type.
+pub type MapClearFn = for<'map> unsafe fn(map: Opaque<'map>);
+
 /// Get an iterator over the map
 ///
 /// # Safety
@@ -148,6 +176,15 @@ pub struct MapVTable {
 
     /// Virtual table for map iterator operations
     pub iter_vtable: MapIterVTable,
+
+    /// cf. [`MapGetValuePtrMutFn`] — `None` if values can't be mutated in place
+    pub get_value_ptr_mut_fn: Option<MapGetValuePtrMutFn>,
+
+    /// cf. [`MapRemoveFn`] — `None` if entries can't be removed
+    pub remove_fn: Option<MapRemoveFn>,
+
+    /// cf. [`MapClearFn`] — `None` if entries can't be removed
+    pub clear_fn: Option<MapClearFn>,
 }
 
 impl MapVTable {
@@ -166,6 +203,9 @@ pub struct MapVTableBuilder {
     get_value_ptr_fn: Option<MapGetValuePtrFn>,
     iter_fn: Option<MapIterFn>,
     iter_vtable: Option<MapIterVTable>,
+    get_value_ptr_mut_fn: Option<MapGetValuePtrMutFn>,
+    remove_fn: Option<MapRemoveFn>,
+    clear_fn: Option<MapClearFn>,
 }
 
 impl MapVTableBuilder {
@@ -180,6 +220,9 @@ impl MapVTableBuilder {
             get_value_ptr_fn: None,
             iter_fn: None,
             iter_vtable: None,
+            get_value_ptr_mut_fn: None,
+            remove_fn: None,
+            clear_fn: None,
         }
     }
 
@@ -225,6 +268,24 @@ impl MapVTableBuilder {
         self
     }
 
+    /// Sets the get_value_ptr_mut_fn field
+    pub const fn get_value_ptr_mut(mut self, f: MapGetValuePtrMutFn) -> Self {
+        self.get_value_ptr_mut_fn = Some(f);
+        self
+    }
+
+    /// Sets the remove_fn field
+    pub const fn remove(mut self, f: MapRemoveFn) -> Self {
+        self.remove_fn = Some(f);
+        self
+    }
+
+    /// Sets the clear_fn field
+    pub const fn clear(mut self, f: MapClearFn) -> Self {
+        self.clear_fn = Some(f);
+        self
+    }
+
     /// Builds the [`MapVTable`] from the current state of the builder.
     ///
     /// # Panics
@@ -239,6 +300,9 @@ impl MapVTableBuilder {
             get_value_ptr_fn: self.get_value_ptr_fn.unwrap(),
             iter_fn: self.iter_fn.unwrap(),
             iter_vtable: self.iter_vtable.unwrap(),
+            get_value_ptr_mut_fn: self.get_value_ptr_mut_fn,
+            remove_fn: self.remove_fn,
+            clear_fn: self.clear_fn,
         }
     }
 }
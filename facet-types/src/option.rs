@@ -0,0 +1,116 @@
+use facet_opaque::{Opaque, OpaqueConst, OpaqueUninit};
+
+/// Check whether the option holds a value
+///
+/// # Safety
+///
+/// The `option` parameter must point to aligned, initialized memory of the correct type.
+pub type OptionIsSomeFn = unsafe fn(option: OpaqueConst) -> bool;
+
+/// Get a pointer to the value held by the option, or `None` if it holds none
+///
+/// # Safety
+///
+/// The `option` parameter must point to aligned, initialized memory of the correct type.
+pub type OptionGetValueFn =
+    for<'option> unsafe fn(option: OpaqueConst<'option>) -> Option<OpaqueConst<'option>>;
+
+/// Initialize the option with `Some(value)`
+///
+/// # Safety
+///
+/// The `option` parameter must point to uninitialized memory of sufficient size.
+/// `value` is moved out of (with [`core::ptr::read`]) — it should be deallocated
+/// afterwards but NOT dropped.
+pub type OptionInitSomeFn =
+    for<'option> unsafe fn(option: OpaqueUninit<'option>, value: Opaque<'_>) -> Opaque<'option>;
+
+/// Initialize the option with `None`
+///
+/// # Safety
+///
+/// The `option` parameter must point to uninitialized memory of sufficient size.
+pub type OptionInitNoneFn = unsafe fn(option: OpaqueUninit) -> Opaque;
+
+/// Virtual table for `Option<T>`
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct OptionVTable {
+    /// cf. [`OptionIsSomeFn`]
+    pub is_some: OptionIsSomeFn,
+
+    /// cf. [`OptionGetValueFn`]
+    pub get_value: OptionGetValueFn,
+
+    /// cf. [`OptionInitSomeFn`]
+    pub init_some: OptionInitSomeFn,
+
+    /// cf. [`OptionInitNoneFn`]
+    pub init_none: OptionInitNoneFn,
+}
+
+impl OptionVTable {
+    /// Returns a builder for OptionVTable
+    pub const fn builder() -> OptionVTableBuilder {
+        OptionVTableBuilder::new()
+    }
+}
+
+/// Builds an [`OptionVTable`]
+pub struct OptionVTableBuilder {
+    is_some: Option<OptionIsSomeFn>,
+    get_value: Option<OptionGetValueFn>,
+    init_some: Option<OptionInitSomeFn>,
+    init_none: Option<OptionInitNoneFn>,
+}
+
+impl OptionVTableBuilder {
+    /// Creates a new [`OptionVTableBuilder`] with all fields set to `None`.
+    #[allow(clippy::new_without_default)]
+    pub const fn new() -> Self {
+        Self {
+            is_some: None,
+            get_value: None,
+            init_some: None,
+            init_none: None,
+        }
+    }
+
+    /// Sets the is_some field
+    pub const fn is_some(mut self, f: OptionIsSomeFn) -> Self {
+        self.is_some = Some(f);
+        self
+    }
+
+    /// Sets the get_value field
+    pub const fn get_value(mut self, f: OptionGetValueFn) -> Self {
+        self.get_value = Some(f);
+        self
+    }
+
+    /// Sets the init_some field
+    pub const fn init_some(mut self, f: OptionInitSomeFn) -> Self {
+        self.init_some = Some(f);
+        self
+    }
+
+    /// Sets the init_none field
+    pub const fn init_none(mut self, f: OptionInitNoneFn) -> Self {
+        self.init_none = Some(f);
+        self
+    }
+
+    /// Builds the [`OptionVTable`] from the current state of the builder.
+    ///
+    /// # Panics
+    ///
+    /// This method will panic if any of the required fields are `None`.
+    pub const fn build(self) -> OptionVTable {
+        OptionVTable {
+            is_some: self.is_some.unwrap(),
+            get_value: self.get_value.unwrap(),
+            init_some: self.init_some.unwrap(),
+            init_none: self.init_none.unwrap(),
+        }
+    }
+}
@@ -127,28 +127,167 @@ pub type ParseFn =
     for<'mem> unsafe fn(s: &str, target: OpaqueUninit<'mem>) -> Result<Opaque<'mem>, ParseError>;
 
 /// Generates a [`ParseFn`] for a concrete type
-pub const fn parse_fn_for<T: core::str::FromStr>() -> Option<ParseFn> {
+///
+/// With the `alloc` feature, the error keeps the message of `T::Err`.
+pub const fn parse_fn_for<T>() -> Option<ParseFn>
+where
+    T: core::str::FromStr,
+    T::Err: core::fmt::Display,
+{
     Some(|s: &str, target: OpaqueUninit<'_>| unsafe {
         match s.parse::<T>() {
             Ok(value) => Ok(target.write(value)),
-            Err(_) => Err(ParseError::Generic("failed to parse string")),
+            Err(e) => Err(ParseError::from_display(&e)),
         }
     })
 }
 
+/// Message carried by [`ParseError`] and [`TryFromError`]
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ErrorMessage {
+    /// A message known at compile time
+    Static(&'static str),
+
+    /// A message built at runtime, typically from an underlying error
+    #[cfg(feature = "alloc")]
+    Owned(alloc::string::String),
+}
+
+impl ErrorMessage {
+    /// Returns the message as a string slice
+    pub fn as_str(&self) -> &str {
+        match self {
+            ErrorMessage::Static(msg) => msg,
+            #[cfg(feature = "alloc")]
+            ErrorMessage::Owned(msg) => msg,
+        }
+    }
+
+    /// Renders an underlying error into a message.
+    ///
+    /// Without the `alloc` feature, the underlying message is lost and `fallback` is used.
+    #[allow(unused_variables)]
+    pub fn from_display(err: &impl core::fmt::Display, fallback: &'static str) -> Self {
+        #[cfg(feature = "alloc")]
+        return ErrorMessage::Owned(alloc::string::ToString::to_string(err));
+        #[cfg(not(feature = "alloc"))]
+        return ErrorMessage::Static(fallback);
+    }
+}
+
+impl From<&'static str> for ErrorMessage {
+    fn from(msg: &'static str) -> Self {
+        ErrorMessage::Static(msg)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl From<alloc::string::String> for ErrorMessage {
+    fn from(msg: alloc::string::String) -> Self {
+        ErrorMessage::Owned(msg)
+    }
+}
+
+impl core::fmt::Display for ErrorMessage {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
 /// Error returned by [`ParseFn`]
 #[non_exhaustive]
-#[derive(Debug)]
-pub enum ParseError {
-    /// Generic error message
-    Generic(&'static str),
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    /// What went wrong
+    pub message: ErrorMessage,
+
+    /// Byte offset into the parsed string where the error occurred, if known
+    pub offset: Option<usize>,
+
+    /// The shape we were trying to parse into, if known
+    pub shape: Option<&'static Shape>,
+}
+
+impl ParseError {
+    /// Creates a parse error with the given message, no offset and no shape
+    pub const fn new(message: &'static str) -> Self {
+        Self {
+            message: ErrorMessage::Static(message),
+            offset: None,
+            shape: None,
+        }
+    }
+
+    /// Creates a parse error out of an underlying error (typically `FromStr::Err`).
+    ///
+    /// Without the `alloc` feature, the underlying message is lost.
+    pub fn from_display(err: &impl core::fmt::Display) -> Self {
+        Self {
+            message: ErrorMessage::from_display(err, "failed to parse string"),
+            offset: None,
+            shape: None,
+        }
+    }
+
+    /// Sets the byte offset at which the error occurred
+    pub const fn with_offset(mut self, offset: usize) -> Self {
+        self.offset = Some(offset);
+        self
+    }
+
+    /// Points the error at the first byte of `s` that can't be part of an integer
+    /// literal: anything but an ASCII digit, after an optional leading `+` (or `-` if
+    /// `signed`).
+    ///
+    /// Leaves the offset unset if every byte is a digit (empty input, overflow).
+    pub fn at_invalid_digit(self, s: &str, signed: bool) -> Self {
+        let sign = match s.as_bytes().first() {
+            Some(b'+') => 1,
+            Some(b'-') if signed => 1,
+            _ => 0,
+        };
+        match s.bytes().skip(sign).position(|b| !b.is_ascii_digit()) {
+            Some(index) => self.with_offset(sign + index),
+            None => self,
+        }
+    }
+
+    /// Sets the shape we were trying to parse into
+    pub const fn with_shape(mut self, shape: &'static Shape) -> Self {
+        self.shape = Some(shape);
+        self
+    }
+
+    /// Sets the shape we were trying to parse into, unless it was already set
+    pub const fn or_shape(self, shape: &'static Shape) -> Self {
+        match self.shape {
+            Some(_) => self,
+            None => self.with_shape(shape),
+        }
+    }
+
+    /// Shifts the offset (if any) by `base`, for when the parsed string was
+    /// a slice of a larger input
+    pub const fn offset_by(mut self, base: usize) -> Self {
+        if let Some(offset) = self.offset {
+            self.offset = Some(base + offset);
+        }
+        self
+    }
 }
 
 impl core::fmt::Display for ParseError {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-        match self {
-            ParseError::Generic(msg) => write!(f, "Parse failed: {}", msg),
+        write!(f, "Parse failed")?;
+        if let Some(shape) = self.shape {
+            write!(f, " for {}", shape)?;
+        }
+        write!(f, ": {}", self.message)?;
+        if let Some(offset) = self.offset {
+            write!(f, " (at byte {})", offset)?;
         }
+        Ok(())
     }
 }
 
@@ -158,17 +297,22 @@ impl core::error::Error for ParseError {}
 ///
 /// # Safety
 ///
-/// The `target` parameter has the correct layout and alignment, but points to
+/// The `source` parameter must point to an initialized value of the type described by
+/// `source_shape`. The `target` parameter has the correct layout and alignment, but points to
 /// uninitialized memory. If this function succeeds, it should return `Ok` with the
 /// same pointer wrapped in an [`Opaque`]. If conversion fails, it returns `Err` with an error.
+///
+/// Unless the error is [`TryFromError::Incompatible`] or [`TryFromError::Unimplemented`],
+/// the source value has been moved out of: it must be deallocated, but not dropped.
 pub type TryFromFn = for<'src, 'mem> unsafe fn(
     source: OpaqueConst<'src>,
+    source_shape: &'static Shape,
     target: OpaqueUninit<'mem>,
 ) -> Result<Opaque<'mem>, TryFromError>;
 
 /// Error type for TryFrom conversion failures
 #[non_exhaustive]
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum TryFromError {
     /// Generic conversion error
     Generic(&'static str),
@@ -181,6 +325,15 @@ pub enum TryFromError {
         /// The target shape that we tried to convert to
         target: &'static Shape,
     },
+    /// The conversion is supported, but rejected this particular source value
+    Failed {
+        /// Why the value was rejected (with `alloc`, the underlying error's message)
+        message: ErrorMessage,
+        /// The source shape that we tried to convert from
+        source: &'static Shape,
+        /// The target shape that we tried to convert to
+        target: &'static Shape,
+    },
 }
 
 impl core::fmt::Display for TryFromError {
@@ -197,6 +350,15 @@ impl core::fmt::Display for TryFromError {
                 "Conversion failed: Cannot convert from shape {} to shape {}",
                 source, target
             ),
+            TryFromError::Failed {
+                message,
+                source,
+                target,
+            } => write!(
+                f,
+                "Conversion failed: Cannot convert this {} to {}: {}",
+                source, target, message
+            ),
         }
     }
 }
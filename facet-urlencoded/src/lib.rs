@@ -1,8 +1,8 @@
 #![warn(missing_docs)]
 #![doc = include_str!("../README.md")]
 
-use facet_poke::Poke;
-use facet_trait::{Facet, Opaque, OpaqueConst, ShapeExt};
+use facet_poke::{Poke, PokeValue};
+use facet_trait::{Def, Facet, Opaque, OpaqueConst, ParseError, ShapeExt, StructKind};
 use log::*;
 
 #[cfg(test)]
@@ -192,6 +192,14 @@ fn deserialize_value<'mem>(
                                     ps.mark_initialized(index);
                                 }
                             }
+                            Poke::Option(po) if matches!(po.def().t.def, Def::Struct(_)) => {
+                                let (inner, _guard) = Poke::alloc_shape(po.def().t);
+                                let inner = deserialize_value(inner, nested_values)?;
+                                unsafe {
+                                    po.init_some(inner);
+                                    ps.mark_initialized(index);
+                                }
+                            }
                             _ => {
                                 return Err(UrlEncodedError::UnsupportedShape(format!(
                                     "Expected struct for nested field '{}'",
@@ -206,6 +214,19 @@ fn deserialize_value<'mem>(
                 }
             }
 
+            // Options missing from the form are `None`
+            for (index, field) in ps.def().fields.iter().enumerate() {
+                if matches!(field.shape.def, Def::Option(_))
+                    && values.get(field.name).is_none()
+                    && values.get_nested(field.name).is_none()
+                {
+                    if let Ok(Poke::Option(po)) = ps.field(index) {
+                        po.init_none();
+                        unsafe { ps.mark_initialized(index) };
+                    }
+                }
+            }
+
             trace!("Finished deserializing struct");
             Ok(ps.build_in_place())
         }
@@ -226,35 +247,53 @@ fn deserialize_scalar_field<'mem>(
     index: usize,
     ps: &mut facet_poke::PokeStruct<'mem>,
 ) -> Result<(), UrlEncodedError> {
-    match field_poke {
+    deserialize_scalar(key, value, field_poke)?;
+    unsafe { ps.mark_initialized(index) };
+    Ok(())
+}
+
+/// Fills a scalar, or an option holding one, from the value of the field `key`
+fn deserialize_scalar<'mem>(
+    key: &str,
+    value: &str,
+    poke: Poke<'mem>,
+) -> Result<Opaque<'mem>, UrlEncodedError> {
+    match poke {
         Poke::Scalar(ps_scalar) => {
-            if ps_scalar.shape().is_type::<String>() {
+            if ps_scalar.shape().is_type::<()>() {
+                // the key being present is all there is to it
+                Ok(unsafe { ps_scalar.put(OpaqueConst::from_ref(&())) })
+            } else if ps_scalar.shape().is_type::<String>() {
                 let s = value.to_string();
                 let opaque = OpaqueConst::from_ref(&s);
-                unsafe { ps_scalar.put(opaque) };
+                let data = unsafe { ps_scalar.put(opaque) };
                 core::mem::forget(s);
+                Ok(data)
             } else if ps_scalar.shape().is_type::<u64>() {
-                match value.parse::<u64>() {
-                    Ok(num) => {
-                        let opaque = OpaqueConst::from_ref(&num);
-                        unsafe { ps_scalar.put(opaque) };
-                    }
-                    Err(_) => {
-                        return Err(UrlEncodedError::InvalidNumber(
-                            key.to_string(),
-                            value.to_string(),
-                        ));
-                    }
-                }
+                ps_scalar.parse(value).map_err(|(_, error)| {
+                    UrlEncodedError::InvalidNumber(key.to_string(), value.to_string(), error)
+                })
+            } else if ps_scalar.shape().vtable.parse.is_some() {
+                parse_field(key, value, ps_scalar)
             } else {
                 warn!("Unsupported scalar type: {}", ps_scalar.shape());
-                return Err(UrlEncodedError::UnsupportedType(format!(
+                Err(UrlEncodedError::UnsupportedType(format!(
                     "{}",
                     ps_scalar.shape()
-                )));
+                )))
             }
-            unsafe { ps.mark_initialized(index) };
-            Ok(())
+        }
+        Poke::Struct(field_struct) if field_struct.def().kind == StructKind::Unit => {
+            Ok(field_struct.build_in_place())
+        }
+        Poke::Struct(field_struct) if field_struct.shape().vtable.parse.is_some() => {
+            parse_field(key, value, field_struct.into_value())
+        }
+        Poke::Option(po) => {
+            // the value is built on the side, then moved into the option
+            let (inner, _guard) = Poke::alloc_shape(po.def().t);
+            let inner = deserialize_scalar(key, value, inner)?;
+            Ok(unsafe { po.init_some(inner) })
         }
         _ => {
             error!("Expected scalar field");
@@ -266,12 +305,27 @@ fn deserialize_scalar_field<'mem>(
     }
 }
 
+/// Fills a field through its shape's `parse` vtable entry (typically its `FromStr` impl)
+fn parse_field<'mem>(
+    key: &str,
+    value: &str,
+    field_value: PokeValue<'mem>,
+) -> Result<Opaque<'mem>, UrlEncodedError> {
+    field_value.parse(value).map_err(|(_, error)| {
+        UrlEncodedError::InvalidValue(key.to_string(), value.to_string(), error)
+    })
+}
+
 /// Errors that can occur during URL encoded form data deserialization.
 #[derive(Debug)]
 #[non_exhaustive]
 pub enum UrlEncodedError {
-    /// The field value couldn't be parsed as a number.
-    InvalidNumber(String, String),
+    /// The field value couldn't be parsed as a number: field name, raw value,
+    /// and the underlying parse error.
+    InvalidNumber(String, String, ParseError),
+    /// The field value was rejected by the field type's parser: field name, raw value,
+    /// and the underlying parse error.
+    InvalidValue(String, String, ParseError),
     /// The shape is not supported for deserialization.
     UnsupportedShape(String),
     /// The type is not supported for deserialization.
@@ -281,8 +335,19 @@ pub enum UrlEncodedError {
 impl core::fmt::Display for UrlEncodedError {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         match self {
-            UrlEncodedError::InvalidNumber(field, value) => {
-                write!(f, "Invalid number for field '{}': '{}'", field, value)
+            UrlEncodedError::InvalidNumber(field, value, error) => {
+                write!(
+                    f,
+                    "Invalid number for field '{}': '{}': {}",
+                    field, value, error
+                )
+            }
+            UrlEncodedError::InvalidValue(field, value, error) => {
+                write!(
+                    f,
+                    "Invalid value for field '{}': '{}': {}",
+                    field, value, error
+                )
             }
             UrlEncodedError::UnsupportedShape(shape) => {
                 write!(f, "Unsupported shape: {}", shape)
@@ -1,6 +1,6 @@
 use crate::from_str;
 use facet_derive::Facet;
-use facet_trait::{self as facet, Facet};
+use facet_trait::{self as facet, Facet, ShapeExt as _};
 
 #[derive(Debug, Facet, PartialEq)]
 struct SearchParams {
@@ -89,7 +89,7 @@ fn test_invalid_number() {
     assert!(result.is_err());
     if let Err(err) = result {
         match err {
-            crate::UrlEncodedError::InvalidNumber(field, value) => {
+            crate::UrlEncodedError::InvalidNumber(field, value, _) => {
                 assert_eq!(field, "page");
                 assert_eq!(value, "not_a_number");
             }
@@ -98,6 +98,25 @@ fn test_invalid_number() {
     }
 }
 
+#[test]
+fn test_invalid_number_details() {
+    let query_string = "query=rust&page=12x";
+
+    let err = from_str::<SearchParams>(query_string).unwrap_err();
+    match &err {
+        crate::UrlEncodedError::InvalidNumber(_, _, error) => {
+            assert_eq!(error.message.as_str(), "invalid digit found in string");
+            assert_eq!(error.offset, Some(2));
+            assert!(error.shape.unwrap().is_type::<u64>());
+        }
+        _ => panic!("Expected InvalidNumber error"),
+    }
+    assert_eq!(
+        err.to_string(),
+        "Invalid number for field 'page': '12x': Parse failed for u64: invalid digit found in string (at byte 2)"
+    );
+}
+
 #[test]
 fn test_nested_struct() {
     let query_string = "user[name]=John+Doe&user[age]=30&user[address][street]=123+Main+St&user[address][city]=Anytown&user[address][zip]=12345&product_id=ABC123&quantity=2";
@@ -180,3 +199,105 @@ fn test_deep_nesting() {
         }
     );
 }
+
+#[derive(Debug, Facet, PartialEq)]
+struct Version(u32, u32);
+
+impl core::str::FromStr for Version {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (major, minor) = s
+            .split_once('.')
+            .ok_or_else(|| format!("expected major.minor, got '{s}'"))?;
+        Ok(Version(
+            major.parse().map_err(|_| "bad major".to_string())?,
+            minor.parse().map_err(|_| "bad minor".to_string())?,
+        ))
+    }
+}
+
+#[derive(Debug, Facet, PartialEq)]
+struct Package {
+    name: String,
+    version: Version,
+}
+
+#[test]
+fn test_from_str_field() {
+    let package: Package = from_str("name=facet&version=1.2").unwrap();
+    assert_eq!(
+        package,
+        Package {
+            name: "facet".to_string(),
+            version: Version(1, 2),
+        }
+    );
+
+    let err = from_str::<Package>("name=facet&version=1").unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Invalid value for field 'version': '1': Parse failed for Version: expected major.minor, got '1'"
+    );
+}
+
+#[derive(Debug, Facet, PartialEq)]
+struct Marker;
+
+#[derive(Debug, Facet, PartialEq)]
+struct Flagged {
+    name: String,
+    debug: Marker,
+    verbose: (),
+}
+
+#[test]
+fn test_zero_sized_fields() {
+    let flagged: Flagged = from_str("name=facet&debug&verbose=").unwrap();
+    assert_eq!(
+        flagged,
+        Flagged {
+            name: "facet".to_string(),
+            debug: Marker,
+            verbose: (),
+        }
+    );
+
+    assert_eq!(from_str::<Marker>("").unwrap(), Marker);
+}
+
+#[derive(Debug, Facet, PartialEq)]
+struct Filters {
+    query: Option<String>,
+    page: Option<u64>,
+    shipping: Option<Address>,
+}
+
+#[test]
+fn test_option_fields() {
+    let filters: Filters =
+        from_str("query=rust&shipping[street]=1+Main+St&shipping[city]=Anytown&shipping[zip]=1")
+            .unwrap();
+    assert_eq!(
+        filters,
+        Filters {
+            query: Some("rust".to_string()),
+            page: None,
+            shipping: Some(Address {
+                street: "1 Main St".to_string(),
+                city: "Anytown".to_string(),
+                zip: "1".to_string(),
+            }),
+        }
+    );
+
+    let filters: Filters = from_str("page=3").unwrap();
+    assert_eq!(
+        filters,
+        Filters {
+            query: None,
+            page: Some(3),
+            shipping: None,
+        }
+    );
+}
@@ -0,0 +1,78 @@
+use core::fmt;
+use std::convert::Infallible;
+
+use facet_serialize::DeserializeError;
+
+use crate::load::Node;
+
+/// Returned by [`crate::from_str`] and [`crate::from_str_multi`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReadError {
+    /// The line of the problem, counting from 1
+    pub line: usize,
+    /// The column of the problem, in characters, counting from 1
+    pub column: usize,
+    /// What went wrong
+    pub kind: ReadErrorKind,
+}
+
+/// What went wrong while reading a YAML document
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum ReadErrorKind {
+    /// The document isn't valid YAML
+    Syntax(String),
+    /// A merge key `<<` is given something else than a mapping or a sequence of mappings
+    InvalidMerge,
+    /// [`crate::from_str`] was given an empty stream
+    NoDocument,
+    /// [`crate::from_str`] was given several documents: read them with
+    /// [`crate::from_str_multi`]
+    MultipleDocuments,
+    /// A mapping key is a sequence or a mapping: keys must be scalars
+    ComplexKey,
+    /// The document is valid YAML, but it doesn't describe the type being built: the
+    /// error has the path to the offending field
+    Value(DeserializeError<Infallible>),
+}
+
+impl ReadError {
+    /// Reports a problem with `node`
+    pub(crate) fn at(node: &Node, kind: ReadErrorKind) -> Self {
+        Self {
+            line: node.line,
+            column: node.column,
+            kind,
+        }
+    }
+
+    /// Reports a syntax error
+    pub(crate) fn syntax(err: yaml_rust2::ScanError) -> Self {
+        Self {
+            line: err.marker().line(),
+            column: err.marker().col() + 1,
+            kind: ReadErrorKind::Syntax(err.info().to_string()),
+        }
+    }
+}
+
+impl fmt::Display for ReadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}, column {}: ", self.line, self.column)?;
+        match &self.kind {
+            ReadErrorKind::Syntax(message) => write!(f, "{message}"),
+            ReadErrorKind::InvalidMerge => write!(
+                f,
+                "`<<` merges a mapping or a sequence of mappings, nothing else"
+            ),
+            ReadErrorKind::NoDocument => write!(f, "Expected a YAML document, got nothing"),
+            ReadErrorKind::MultipleDocuments => {
+                write!(f, "Expected a single YAML document, got several")
+            }
+            ReadErrorKind::ComplexKey => write!(f, "Mapping keys must be scalars"),
+            ReadErrorKind::Value(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+impl std::error::Error for ReadError {}
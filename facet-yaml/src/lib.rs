@@ -1,125 +1,114 @@
 #![warn(missing_docs)]
 #![doc = include_str!("../README.md")]
 
-use facet_poke::Poke;
-use facet_trait::{Facet, Opaque, OpaqueConst, ShapeExt};
-use yaml_rust2::{Yaml, YamlLoader};
+use core::marker::PhantomData;
 
-#[cfg(test)]
-mod tests;
+use facet_serialize::deserialize;
+use facet_trait::Facet;
+use yaml_rust2::parser::Parser;
 
-/// Deserializes a YAML string into a value of type `T` that implements `Facet`.
-pub fn from_str<T: Facet>(yaml: &str) -> Result<T, AnyErr> {
-    let (poke, _guard) = Poke::alloc::<T>();
-    let opaque = from_str_opaque(poke, yaml)?;
-    Ok(unsafe { opaque.read::<T>() })
-}
+mod error;
+pub use error::*;
 
-/// Any error
-#[derive(Debug, Clone)]
-pub struct AnyErr(String);
+mod load;
+use load::Node;
 
-impl core::fmt::Display for AnyErr {
-    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-        write!(f, "{}", self.0)
-    }
-}
+mod read;
 
-impl std::error::Error for AnyErr {}
+mod serialize;
+pub use serialize::{Style, to_string, to_string_with_style};
 
-impl From<String> for AnyErr {
-    fn from(s: String) -> Self {
-        Self(s)
-    }
-}
+#[cfg(test)]
+mod tests;
 
-impl From<&str> for AnyErr {
-    fn from(s: &str) -> Self {
-        Self(s.to_string())
+/// Deserializes a YAML string into a value of type `T` that implements `Facet`.
+///
+/// Aliases are replaced by the node their anchor names, and merge keys (`<<: *defaults`)
+/// by the entries of the mappings they merge. The string must hold a single document:
+/// read streams of documents with [`from_str_multi`].
+///
+/// # Errors
+///
+/// Returns an error if the string isn't valid YAML, or if it doesn't describe a `T`, with
+/// the line and column of the offending node and the path to the offending value.
+pub fn from_str<T: Facet>(yaml: &str) -> Result<T, ReadError> {
+    let mut parser = Parser::new_from_str(yaml);
+    let Some(document) = load::next_document(&mut parser)? else {
+        return Err(ReadError {
+            line: 1,
+            column: 1,
+            kind: ReadErrorKind::NoDocument,
+        });
+    };
+    if let Some(next) = load::next_document(&mut parser)? {
+        return Err(ReadError::at(&next, ReadErrorKind::MultipleDocuments));
     }
+    from_node(&document)
 }
 
-fn yaml_type(ty: &Yaml) -> &'static str {
-    match ty {
-        Yaml::Real(_) => "real number",
-        Yaml::Integer(_) => "integer",
-        Yaml::String(_) => "string",
-        Yaml::Boolean(_) => "boolean",
-        Yaml::Array(_) => "array",
-        Yaml::Hash(_) => "hash/map",
-        Yaml::Alias(_) => "alias",
-        Yaml::Null => "null",
-        Yaml::BadValue => "bad value",
+/// Deserializes each document of a YAML stream, separated by `---`, into a `T`
+///
+/// ```rust
+/// use facet_derive::Facet;
+/// use facet_trait::{self as facet, Facet};
+///
+/// #[derive(Debug, PartialEq, Facet)]
+/// struct Service {
+///     name: String,
+///     replicas: u64,
+/// }
+///
+/// let yaml = "name: web\nreplicas: 3\n---\nname: worker\nreplicas: lots\n";
+/// let mut services = facet_yaml::from_str_multi::<Service>(yaml);
+/// assert_eq!(services.next().unwrap().unwrap().name, "web");
+/// let err = services.next().unwrap().unwrap_err();
+/// assert_eq!((err.line, err.column), (5, 11));
+/// assert!(services.next().is_none());
+/// ```
+///
+/// Documents are read lazily: a document that doesn't describe a `T` is an error, and the
+/// next one is read all the same, but a syntax error ends the stream.
+pub fn from_str_multi<T: Facet>(yaml: &str) -> Documents<'_, T> {
+    Documents {
+        parser: Parser::new_from_str(yaml),
+        done: false,
+        _t: PhantomData,
     }
 }
 
-fn yaml_to_u64(ty: &Yaml) -> Result<u64, AnyErr> {
-    match ty {
-        Yaml::Real(r) => r
-            .parse::<u64>()
-            .map_err(|_| AnyErr("Failed to parse real as u64".into())),
-        Yaml::Integer(i) => Ok(*i as u64),
-        Yaml::String(s) => s
-            .parse::<u64>()
-            .map_err(|_| AnyErr("Failed to parse string as u64".into())),
-        Yaml::Boolean(b) => Ok(if *b { 1 } else { 0 }),
-        _ => Err(AnyErr(format!("Cannot convert {} to u64", yaml_type(ty)))),
-    }
+/// The documents of a YAML stream, returned by [`from_str_multi`]
+pub struct Documents<'a, T> {
+    parser: Parser<core::str::Chars<'a>>,
+    done: bool,
+    _t: PhantomData<fn() -> T>,
 }
 
-fn from_str_opaque<'mem>(poke: Poke<'mem>, yaml: &str) -> Result<Opaque<'mem>, AnyErr> {
-    let docs = YamlLoader::load_from_str(yaml).map_err(|e| e.to_string())?;
-    if docs.len() != 1 {
-        return Err("Expected exactly one YAML document".into());
-    }
-    deserialize_value(poke, &docs[0])
-}
+impl<T: Facet> Iterator for Documents<'_, T> {
+    type Item = Result<T, ReadError>;
 
-fn deserialize_value<'mem>(poke: Poke<'mem>, value: &Yaml) -> Result<Opaque<'mem>, AnyErr> {
-    let opaque = match poke {
-        Poke::Scalar(ps) => {
-            if ps.shape().is_type::<u64>() {
-                let u = yaml_to_u64(value)?;
-                let opaque = OpaqueConst::from_ref(&u);
-                unsafe { ps.put(opaque) }
-            } else if ps.shape().is_type::<String>() {
-                let s = value
-                    .as_str()
-                    .ok_or_else(|| AnyErr(format!("Expected string, got: {}", yaml_type(value))))?
-                    .to_string();
-                let opaque = OpaqueConst::from_ref(&s);
-                let res = unsafe { ps.put(opaque) };
-                core::mem::forget(s);
-                res
-            } else {
-                return Err(format!("Unsupported scalar type: {}", ps.shape()).into());
-            }
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
         }
-        Poke::List(_) => todo!(),
-        Poke::Map(_) => todo!(),
-        Poke::Struct(mut ps) => match value {
-            Yaml::Hash(hash) => {
-                for (k, v) in hash {
-                    let k = k
-                        .as_str()
-                        .ok_or_else(|| format!("Expected string key, got: {}", yaml_type(k)))?;
-                    let (index, field_poke) = ps
-                        .field_by_name(k)
-                        .map_err(|e| format!("Field '{}' error: {}", k, e))?;
-                    let _v = deserialize_value(field_poke, v)
-                        .map_err(|e| format!("Error deserializing field '{}': {}", k, e))?;
-                    unsafe {
-                        ps.mark_initialized(index);
-                    }
-                }
-                ps.build_in_place()
+        match load::next_document(&mut self.parser) {
+            Ok(Some(document)) => Some(from_node(&document)),
+            Ok(None) => {
+                self.done = true;
+                None
             }
-            _ => {
-                return Err(format!("Expected a YAML hash, got: {:?}", value).into());
+            Err(err) => {
+                self.done = true;
+                Some(Err(err))
             }
-        },
-        Poke::Enum(_) => todo!(),
-        _ => todo!("unsupported poke type"),
-    };
-    Ok(opaque)
+        }
+    }
+}
+
+fn from_node<T: Facet>(node: &Node) -> Result<T, ReadError> {
+    let mut events = read::Events::new(node);
+    deserialize::<T, _>(&mut events).map_err(|err| ReadError {
+        line: events.line,
+        column: events.column,
+        kind: ReadErrorKind::Value(err),
+    })
 }
@@ -0,0 +1,220 @@
+use std::collections::HashMap;
+
+use yaml_rust2::Yaml;
+use yaml_rust2::parser::{Event, MarkedEventReceiver, Parser, Tag};
+use yaml_rust2::scanner::{Marker, TScalarStyle};
+
+use crate::{ReadError, ReadErrorKind};
+
+/// A node of a YAML document, with its aliases and merge keys resolved, and where it
+/// starts
+#[derive(Debug, Clone)]
+pub(crate) struct Node {
+    pub(crate) value: Value,
+    /// The line of the node, counting from 1
+    pub(crate) line: usize,
+    /// The column of the node, in characters, counting from 1
+    pub(crate) column: usize,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) enum Value {
+    /// A null, a boolean, a number or a string
+    Scalar(Yaml),
+    Seq(Vec<Node>),
+    Map(Vec<(Node, Node)>),
+    /// The `<<` key of a merge, until the mapping it's in is complete
+    MergeKey,
+}
+
+impl Node {
+    fn new(value: Value, mark: Marker) -> Self {
+        Self {
+            value,
+            line: mark.line(),
+            column: mark.col() + 1,
+        }
+    }
+
+    /// Returns true if both nodes are the same scalar, so the same key of a mapping
+    fn same_key(&self, other: &Node) -> bool {
+        matches!((&self.value, &other.value), (Value::Scalar(a), Value::Scalar(b)) if a == b)
+    }
+}
+
+/// Reads the next document of the stream `parser` reads, or `None` at the end of the
+/// stream
+pub(crate) fn next_document(
+    parser: &mut Parser<core::str::Chars<'_>>,
+) -> Result<Option<Node>, ReadError> {
+    let mut loader = Loader::default();
+    parser.load(&mut loader, false).map_err(ReadError::syntax)?;
+    match loader.error {
+        Some(err) => Err(err),
+        None => Ok(loader.document),
+    }
+}
+
+/// Builds the nodes of a document out of the parser's events
+#[derive(Default)]
+struct Loader {
+    /// The sequences and mappings being built, with their anchor and the key of the next
+    /// entry of mappings
+    open: Vec<(Node, usize, Option<Node>)>,
+    /// The anchored nodes, by anchor
+    anchors: HashMap<usize, Node>,
+    document: Option<Node>,
+    error: Option<ReadError>,
+}
+
+impl Loader {
+    /// Returns true if the next node is the key of an entry of a mapping
+    fn expects_key(&self) -> bool {
+        matches!(
+            self.open.last(),
+            Some((
+                Node {
+                    value: Value::Map(_),
+                    ..
+                },
+                _,
+                None
+            ))
+        )
+    }
+
+    fn insert(&mut self, node: Node, anchor: usize) {
+        if anchor > 0 {
+            self.anchors.insert(anchor, node.clone());
+        }
+        match self.open.last_mut() {
+            Some((
+                Node {
+                    value: Value::Seq(items),
+                    ..
+                },
+                _,
+                _,
+            )) => items.push(node),
+            Some((
+                Node {
+                    value: Value::Map(entries),
+                    ..
+                },
+                _,
+                key,
+            )) => match key.take() {
+                Some(key) => entries.push((key, node)),
+                None if matches!(node.value, Value::Seq(_) | Value::Map(_)) => {
+                    self.error = Some(ReadError::at(&node, ReadErrorKind::ComplexKey));
+                }
+                None => *key = Some(node),
+            },
+            _ => self.document = Some(node),
+        }
+    }
+}
+
+impl MarkedEventReceiver for Loader {
+    fn on_event(&mut self, event: Event, mark: Marker) {
+        if self.error.is_some() {
+            return;
+        }
+        match event {
+            Event::Scalar(text, style, anchor, tag) => {
+                let value = if style == TScalarStyle::Plain && text == "<<" && self.expects_key() {
+                    Value::MergeKey
+                } else {
+                    Value::Scalar(scalar(text, style, tag))
+                };
+                self.insert(Node::new(value, mark), anchor);
+            }
+            Event::SequenceStart(anchor, _) => {
+                self.open
+                    .push((Node::new(Value::Seq(Vec::new()), mark), anchor, None));
+            }
+            Event::MappingStart(anchor, _) => {
+                self.open
+                    .push((Node::new(Value::Map(Vec::new()), mark), anchor, None));
+            }
+            Event::SequenceEnd | Event::MappingEnd => {
+                let (mut node, anchor, _) = self.open.pop().expect("the parser balances events");
+                if let Value::Map(entries) = &mut node.value {
+                    if let Err(err) = merge(entries) {
+                        self.error = Some(err);
+                        return;
+                    }
+                }
+                self.insert(node, anchor);
+            }
+            Event::Alias(anchor) => match self.anchors.get(&anchor).cloned() {
+                Some(node) => self.insert(node, 0),
+                None => {
+                    self.error = Some(ReadError::at(
+                        &Node::new(Value::Scalar(Yaml::BadValue), mark),
+                        ReadErrorKind::Syntax("unknown anchor".to_string()),
+                    ));
+                }
+            },
+            Event::DocumentEnd if self.document.is_none() => {
+                self.document = Some(Node::new(Value::Scalar(Yaml::Null), mark));
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Resolves a scalar: quoted scalars and scalars tagged `!!str` are strings, others can
+/// be nulls, booleans or numbers
+fn scalar(text: String, style: TScalarStyle, tag: Option<Tag>) -> Yaml {
+    let is_str = tag.is_some_and(|tag| tag.handle == "tag:yaml.org,2002:" && tag.suffix == "str");
+    if style != TScalarStyle::Plain || is_str {
+        Yaml::String(text)
+    } else {
+        Yaml::from_str(&text)
+    }
+}
+
+/// Replaces the `<<` entries of a mapping with the entries of the mappings they merge,
+/// except for the keys the mapping has already: earlier mappings of a sequence win over
+/// later ones
+fn merge(entries: &mut Vec<(Node, Node)>) -> Result<(), ReadError> {
+    if !entries
+        .iter()
+        .any(|(key, _)| matches!(key.value, Value::MergeKey))
+    {
+        return Ok(());
+    }
+    let (merges, mut own): (Vec<_>, Vec<_>) = core::mem::take(entries)
+        .into_iter()
+        .partition(|(key, _)| matches!(key.value, Value::MergeKey));
+    for (_, merged) in merges {
+        let sources = match merged {
+            Node {
+                value: Value::Map(entries),
+                ..
+            } => vec![entries],
+            Node {
+                value: Value::Seq(items),
+                ..
+            } => items
+                .into_iter()
+                .map(|item| match item {
+                    Node {
+                        value: Value::Map(entries),
+                        ..
+                    } => Ok(entries),
+                    item => Err(ReadError::at(&item, ReadErrorKind::InvalidMerge)),
+                })
+                .collect::<Result<_, _>>()?,
+            node => return Err(ReadError::at(&node, ReadErrorKind::InvalidMerge)),
+        };
+        for (key, value) in sources.into_iter().flatten() {
+            if !own.iter().any(|(own_key, _)| own_key.same_key(&key)) {
+                own.push((key, value));
+            }
+        }
+    }
+    *entries = own;
+    Ok(())
+}
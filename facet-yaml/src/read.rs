@@ -0,0 +1,138 @@
+use std::borrow::Cow;
+use std::convert::Infallible;
+
+use facet_serialize::{Deserializer, Event};
+use yaml_rust2::Yaml;
+
+use crate::load::{Node, Value};
+
+/// Reads a loaded document as a stream of events, keeping track of where in the document
+/// the last one came from
+pub(crate) struct Events<'n> {
+    pending: Vec<Pending<'n>>,
+    /// The line of the last event, counting from 1
+    pub(crate) line: usize,
+    /// The column of the last event, in characters, counting from 1
+    pub(crate) column: usize,
+}
+
+enum Pending<'n> {
+    Node(&'n Node),
+    /// The items of a sequence, and the sequence
+    Items(core::slice::Iter<'n, Node>, &'n Node),
+    /// The entries of a mapping, and the mapping
+    Entries(core::slice::Iter<'n, (Node, Node)>, &'n Node),
+}
+
+impl<'n> Events<'n> {
+    pub(crate) fn new(document: &'n Node) -> Self {
+        Self {
+            pending: Vec::from([Pending::Node(document)]),
+            line: document.line,
+            column: document.column,
+        }
+    }
+
+    fn locate(&mut self, node: &Node) {
+        self.line = node.line;
+        self.column = node.column;
+    }
+}
+
+impl<'n> Deserializer<'n> for Events<'n> {
+    type Error = Infallible;
+
+    fn next(&mut self) -> Result<Event<'n>, Self::Error> {
+        loop {
+            // `deserialize` reads a single value, so it never asks for more than the
+            // document: should it, it would find a null
+            let Some(pending) = self.pending.pop() else {
+                return Ok(Event::Unit);
+            };
+            match pending {
+                Pending::Node(node) => {
+                    self.locate(node);
+                    return Ok(match &node.value {
+                        Value::Scalar(scalar) => scalar_event(scalar),
+                        Value::Seq(items) => {
+                            self.pending.push(Pending::Items(items.iter(), node));
+                            Event::StartSeq
+                        }
+                        Value::Map(entries) => {
+                            self.pending.push(Pending::Entries(entries.iter(), node));
+                            Event::StartMap
+                        }
+                        Value::MergeKey => unreachable!("merge keys are resolved by the loader"),
+                    });
+                }
+                Pending::Items(mut items, seq) => match items.next() {
+                    Some(item) => {
+                        self.pending.push(Pending::Items(items, seq));
+                        self.pending.push(Pending::Node(item));
+                    }
+                    None => {
+                        // Missing tuple fields are reported at the start of their sequence
+                        self.locate(seq);
+                        return Ok(Event::EndSeq);
+                    }
+                },
+                Pending::Entries(mut entries, map) => match entries.next() {
+                    Some((key, value)) => {
+                        self.pending.push(Pending::Entries(entries, map));
+                        self.pending.push(Pending::Node(value));
+                        self.locate(key);
+                        return Ok(Event::Key(key_text(key)));
+                    }
+                    None => {
+                        // Missing fields are reported at the start of their mapping
+                        self.locate(map);
+                        return Ok(Event::EndMap);
+                    }
+                },
+            }
+        }
+    }
+}
+
+fn scalar_event(scalar: &Yaml) -> Event<'_> {
+    match scalar {
+        Yaml::Null => Event::Unit,
+        Yaml::Boolean(b) => Event::Bool(*b),
+        Yaml::Integer(i) => match u64::try_from(*i) {
+            Ok(n) => Event::U64(n),
+            Err(_) => Event::I64(*i),
+        },
+        // integers that don't fit in an `i64` are resolved as floats: they're read from
+        // their text, so that they fit 128-bit integers
+        Yaml::Real(text) if is_integer(text) => Event::Str(Cow::Borrowed(text)),
+        Yaml::Real(_) => Event::F64(scalar.as_f64().expect("reals are resolved as floats")),
+        Yaml::String(s) => Event::Str(Cow::Borrowed(s)),
+        _ => unreachable!("the loader only resolves nulls, booleans, numbers and strings"),
+    }
+}
+
+fn is_integer(text: &str) -> bool {
+    let digits = text.strip_prefix(['-', '+']).unwrap_or(text);
+    !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit())
+}
+
+/// Returns the text of a mapping key: keys are read from their text, so that `1: one`
+/// fits a map keyed by strings as well as one keyed by integers.
+///
+/// YAML spells infinities and NaN `.inf`, `-.inf` and `.nan`: they're translated to what
+/// Rust's float parsing understands.
+fn key_text(key: &Node) -> Cow<'_, str> {
+    match &key.value {
+        Value::Scalar(Yaml::Real(r)) => match r.to_ascii_lowercase().as_str() {
+            ".inf" | "+.inf" => Cow::Borrowed("inf"),
+            "-.inf" => Cow::Borrowed("-inf"),
+            ".nan" => Cow::Borrowed("NaN"),
+            _ => Cow::Borrowed(r),
+        },
+        Value::Scalar(Yaml::String(s)) => Cow::Borrowed(s),
+        Value::Scalar(Yaml::Integer(i)) => Cow::Owned(i.to_string()),
+        Value::Scalar(Yaml::Boolean(b)) => Cow::Owned(b.to_string()),
+        Value::Scalar(Yaml::Null) => Cow::Borrowed("null"),
+        _ => unreachable!("the loader rejects keys that aren't scalars"),
+    }
+}
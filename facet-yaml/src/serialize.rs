@@ -0,0 +1,389 @@
+use core::convert::Infallible;
+use core::fmt::Write as _;
+
+use facet_poke::Peek;
+use facet_serialize::{SerializeError, Serializer, serialize};
+use facet_trait::Facet;
+
+/// How collections are laid out by [`to_string_with_style`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Style {
+    /// One entry per line, nested collections indented under their key
+    #[default]
+    Block,
+    /// Everything on a single line, `{key: value}` and `[item, item]`
+    Flow,
+}
+
+/// Serializes a value implementing `Facet` into a YAML string, in block style
+///
+/// ```rust
+/// use facet_derive::Facet;
+/// use facet_trait::{self as facet, Facet};
+///
+/// #[derive(Facet)]
+/// struct Person {
+///     name: String,
+///     langs: Vec<String>,
+/// }
+///
+/// let person = Person {
+///     name: "Ferris".to_string(),
+///     langs: vec!["rust".to_string(), "yes".to_string()],
+/// };
+/// assert_eq!(
+///     facet_yaml::to_string(&person).unwrap(),
+///     "name: Ferris\nlangs:\n  - rust\n  - \"yes\"\n"
+/// );
+/// ```
+///
+/// # Errors
+///
+/// Returns an error if the value holds something that can't be serialized, with the path
+/// to it.
+pub fn to_string<T: Facet>(value: &T) -> Result<String, SerializeError<Infallible>> {
+    to_string_with_style(value, Style::Block)
+}
+
+/// Serializes a value implementing `Facet` into a YAML string, in the given style
+///
+/// # Errors
+///
+/// Same as [`to_string`].
+pub fn to_string_with_style<T: Facet>(
+    value: &T,
+    style: Style,
+) -> Result<String, SerializeError<Infallible>> {
+    let mut builder = Builder::default();
+    serialize(Peek::new(value), &mut builder)?;
+    let node = builder.finish();
+    let mut out = String::new();
+    match style {
+        Style::Block => write_block(&mut out, &node, 0),
+        Style::Flow => {
+            write_flow(&mut out, &node);
+            out.push('\n');
+        }
+    }
+    Ok(out)
+}
+
+/// A value being written, before it's laid out
+enum Node {
+    /// A null, a boolean or a number, as written in the document
+    Scalar(String),
+    /// A string, quoted when it's written if it has to be
+    Str(String),
+    Seq(Vec<Node>),
+    Map(Vec<(String, Node)>),
+}
+
+impl Node {
+    /// Returns true for collections that have entries, which are laid out on lines of
+    /// their own in block style
+    fn is_nested(&self) -> bool {
+        match self {
+            Node::Seq(items) => !items.is_empty(),
+            Node::Map(entries) => !entries.is_empty(),
+            _ => false,
+        }
+    }
+}
+
+/// Builds a YAML node out of a typed value being serialized
+#[derive(Default)]
+struct Builder {
+    /// The sequences and mappings being built, with the key of the next entry of mappings
+    open: Vec<(Node, Option<String>)>,
+    done: Option<Node>,
+}
+
+impl Builder {
+    fn finish(self) -> Node {
+        self.done.expect("the serializer emitted a whole value")
+    }
+
+    fn emit(&mut self, node: Node) -> Result<(), Infallible> {
+        match self.open.last_mut() {
+            Some((Node::Seq(items), _)) => items.push(node),
+            Some((Node::Map(entries), key)) => {
+                entries.push((key.take().unwrap_or_default(), node));
+            }
+            _ => self.done = Some(node),
+        }
+        Ok(())
+    }
+
+    fn scalar(&mut self, value: impl ToString) -> Result<(), Infallible> {
+        self.emit(Node::Scalar(value.to_string()))
+    }
+
+    fn open(&mut self, node: Node) -> Result<(), Infallible> {
+        self.open.push((node, None));
+        Ok(())
+    }
+
+    fn close(&mut self) -> Result<(), Infallible> {
+        match self.open.pop() {
+            Some((node, _)) => self.emit(node),
+            None => Ok(()),
+        }
+    }
+
+    fn key(&mut self, key: &str) -> Result<(), Infallible> {
+        if let Some((_, next)) = self.open.last_mut() {
+            *next = Some(key.into());
+        }
+        Ok(())
+    }
+}
+
+impl Serializer for Builder {
+    type Error = Infallible;
+
+    fn serialize_bool(&mut self, value: bool) -> Result<(), Self::Error> {
+        self.scalar(value)
+    }
+
+    fn serialize_u64(&mut self, value: u64) -> Result<(), Self::Error> {
+        self.scalar(value)
+    }
+
+    fn serialize_i64(&mut self, value: i64) -> Result<(), Self::Error> {
+        self.scalar(value)
+    }
+
+    fn serialize_u128(&mut self, value: u128) -> Result<(), Self::Error> {
+        self.scalar(value)
+    }
+
+    fn serialize_i128(&mut self, value: i128) -> Result<(), Self::Error> {
+        self.scalar(value)
+    }
+
+    fn serialize_f64(&mut self, value: f64) -> Result<(), Self::Error> {
+        if value.is_nan() {
+            self.scalar(".nan")
+        } else if value.is_infinite() {
+            self.scalar(if value > 0.0 { ".inf" } else { "-.inf" })
+        } else {
+            // `Debug` always writes a decimal point or an exponent, so the number reads
+            // back as a float
+            self.scalar(format_args!("{value:?}"))
+        }
+    }
+
+    fn serialize_str(&mut self, value: &str) -> Result<(), Self::Error> {
+        self.emit(Node::Str(value.into()))
+    }
+
+    fn serialize_unit(&mut self) -> Result<(), Self::Error> {
+        self.scalar("null")
+    }
+
+    fn start_struct(&mut self, _len: usize) -> Result<(), Self::Error> {
+        self.open(Node::Map(Vec::new()))
+    }
+
+    fn field(&mut self, name: &'static str) -> Result<(), Self::Error> {
+        self.key(name)
+    }
+
+    fn end_struct(&mut self) -> Result<(), Self::Error> {
+        self.close()
+    }
+
+    fn start_seq(&mut self, _len: usize) -> Result<(), Self::Error> {
+        self.open(Node::Seq(Vec::new()))
+    }
+
+    fn end_seq(&mut self) -> Result<(), Self::Error> {
+        self.close()
+    }
+
+    fn start_map(&mut self, _len: usize) -> Result<(), Self::Error> {
+        self.open(Node::Map(Vec::new()))
+    }
+
+    fn map_key(&mut self, key: &str) -> Result<(), Self::Error> {
+        self.key(key)
+    }
+
+    fn end_map(&mut self) -> Result<(), Self::Error> {
+        self.close()
+    }
+}
+
+/// Writes a node in block style, starting at the current position of `out` and
+/// indenting its nested lines by `indent`, then ends the line
+fn write_block(out: &mut String, node: &Node, indent: usize) {
+    match node {
+        Node::Seq(items) if !items.is_empty() => {
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    push_indent(out, indent);
+                }
+                // nested collections start on the line of their `- `
+                out.push_str("- ");
+                write_block(out, item, indent + 2);
+            }
+        }
+        Node::Map(entries) if !entries.is_empty() => {
+            for (i, (key, value)) in entries.iter().enumerate() {
+                if i > 0 {
+                    push_indent(out, indent);
+                }
+                write_string(out, key, false);
+                out.push(':');
+                if value.is_nested() {
+                    out.push('\n');
+                    push_indent(out, indent + 2);
+                    write_block(out, value, indent + 2);
+                } else {
+                    out.push(' ');
+                    write_block(out, value, indent + 2);
+                }
+            }
+        }
+        Node::Str(s) if literal_chomping(s).is_some() => write_literal(out, s, indent),
+        Node::Str(s) => {
+            write_string(out, s, false);
+            out.push('\n');
+        }
+        node => {
+            write_flow(out, node);
+            out.push('\n');
+        }
+    }
+}
+
+/// Writes a multi-line string in literal style, `|` then its lines, indented
+fn write_literal(out: &mut String, s: &str, indent: usize) {
+    let chomping = literal_chomping(s).expect("the string can be written as a literal");
+    out.push('|');
+    out.push_str(chomping);
+    out.push('\n');
+    for line in s.trim_end_matches('\n').split('\n') {
+        if !line.is_empty() {
+            push_indent(out, indent);
+            out.push_str(line);
+        }
+        out.push('\n');
+    }
+    // the trailing newlines kept by `|+`
+    for _ in 1..s.len() - s.trim_end_matches('\n').len() {
+        out.push('\n');
+    }
+}
+
+/// Returns the chomping indicator a string needs to be written in literal style, or
+/// `None` if it can't be written in literal style
+fn literal_chomping(s: &str) -> Option<&'static str> {
+    let body = s.trim_end_matches('\n');
+    let writable = body.contains('\n')
+        && !body.starts_with([' ', '\t'])
+        && !body
+            .chars()
+            .any(|c| c != '\n' && c != '\t' && is_special(c))
+        && body
+            .split('\n')
+            .all(|line| line.is_empty() || !line.trim().is_empty());
+    if !writable {
+        return None;
+    }
+    Some(match s.len() - body.len() {
+        0 => "-",
+        1 => "",
+        _ => "+",
+    })
+}
+
+/// Writes a node on a single line, `{key: value}` and `[item, item]`
+fn write_flow(out: &mut String, node: &Node) {
+    match node {
+        Node::Scalar(value) => out.push_str(value),
+        Node::Str(s) => write_string(out, s, true),
+        Node::Seq(items) => {
+            out.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push_str(", ");
+                }
+                write_flow(out, item);
+            }
+            out.push(']');
+        }
+        Node::Map(entries) => {
+            out.push('{');
+            for (i, (key, value)) in entries.iter().enumerate() {
+                if i > 0 {
+                    out.push_str(", ");
+                }
+                write_string(out, key, true);
+                out.push_str(": ");
+                write_flow(out, value);
+            }
+            out.push('}');
+        }
+    }
+}
+
+fn push_indent(out: &mut String, indent: usize) {
+    out.extend(core::iter::repeat_n(' ', indent));
+}
+
+/// Writes a string, plain if it reads back as the same string, double-quoted otherwise
+fn write_string(out: &mut String, s: &str, flow: bool) {
+    if is_plain(s, flow) {
+        out.push_str(s);
+        return;
+    }
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            '\0' => out.push_str("\\0"),
+            c if is_special(c) => write!(out, "\\u{:04X}", u32::from(c)).unwrap(),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+/// Returns true for characters that are escaped in double-quoted strings
+fn is_special(c: char) -> bool {
+    c.is_control() || matches!(c, '\u{85}' | '\u{2028}' | '\u{2029}' | '\u{feff}')
+}
+
+/// Returns true if a string can be written without quotes: it doesn't start with an
+/// indicator, doesn't hold anything that ends it early, and doesn't read back as a null,
+/// a boolean or a number
+fn is_plain(s: &str, flow: bool) -> bool {
+    let Some(first) = s.chars().next() else {
+        return false;
+    };
+    if s.trim() != s
+        || "-?:,[]{}#&*!|>'\"%@`".contains(first)
+        || s.starts_with("...")
+        || s.contains(": ")
+        || s.contains(" #")
+        || s.ends_with(':')
+        || (flow && s.contains([',', '[', ']', '{', '}']))
+        || s.chars().any(is_special)
+    {
+        return false;
+    }
+    let lower = s.to_ascii_lowercase();
+    let resolves = matches!(
+        lower.as_str(),
+        "null" | "~" | "true" | "false" | "y" | "n" | "yes" | "no" | "on" | "off"
+    ) || matches!(lower.trim_start_matches(['-', '+']), ".inf" | ".nan")
+        || lower.parse::<f64>().is_ok()
+        || lower.trim_start_matches(['-', '+']).starts_with("0x")
+        || lower.trim_start_matches(['-', '+']).starts_with("0o");
+    !resolves
+}
@@ -1,5 +1,6 @@
 use super::*;
 use facet_derive::Facet;
+use facet_serialize::DeserializeErrorKind;
 use facet_trait as facet;
 
 #[derive(Debug, Facet, PartialEq)]
@@ -24,3 +25,486 @@ fn test_deserialize_person() {
         }
     );
 }
+
+#[test]
+fn test_invalid_number_details() {
+    let yaml = r#"
+            name: Alice
+            age: "12x"
+        "#;
+
+    let err = from_str::<Person>(yaml).unwrap_err().to_string();
+    assert!(
+        err.contains("Parse failed for u64: invalid digit found in string (at byte 2)"),
+        "{err}"
+    );
+}
+
+#[derive(Debug, Facet, PartialEq)]
+struct Version(u32, u32);
+
+impl core::str::FromStr for Version {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (major, minor) = s
+            .split_once('.')
+            .ok_or_else(|| format!("expected major.minor, got '{s}'"))?;
+        Ok(Version(
+            major.parse().map_err(|_| "bad major".to_string())?,
+            minor.parse().map_err(|_| "bad minor".to_string())?,
+        ))
+    }
+}
+
+#[derive(Debug, Facet, PartialEq)]
+struct Package {
+    name: String,
+    version: Version,
+    port: u16,
+}
+
+#[test]
+fn test_from_str_scalars() {
+    let yaml = r#"
+            name: facet
+            version: "1.2"
+            port: 8080
+        "#;
+
+    let package: Package = from_str(yaml).unwrap();
+    assert_eq!(
+        package,
+        Package {
+            name: "facet".to_string(),
+            version: Version(1, 2),
+            port: 8080,
+        }
+    );
+
+    let yaml = r#"
+            name: facet
+            version: "1"
+            port: 8080
+        "#;
+    let err = from_str::<Package>(yaml).unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("Parse failed for Version: expected major.minor, got '1'"),
+        "{err}"
+    );
+}
+
+#[derive(Debug, Facet, PartialEq)]
+struct Marker;
+
+#[derive(Debug, Facet, PartialEq)]
+struct Flags {
+    marker: Marker,
+    nothing: (),
+    name: String,
+}
+
+#[test]
+fn test_zero_sized_types() {
+    let yaml = r#"
+            marker: ~
+            nothing:
+            name: facet
+        "#;
+
+    let flags: Flags = from_str(yaml).unwrap();
+    assert_eq!(
+        flags,
+        Flags {
+            marker: Marker,
+            nothing: (),
+            name: "facet".to_string(),
+        }
+    );
+
+    assert_eq!(from_str::<Marker>("null").unwrap(), Marker);
+    from_str::<()>("~").unwrap();
+    assert!(from_str::<Marker>("marker: 1").is_err());
+}
+
+#[derive(Debug, Facet, PartialEq)]
+struct Inventory {
+    owner: Option<String>,
+    tags: Vec<String>,
+    counts: std::collections::HashMap<String, u64>,
+    people: Vec<Person>,
+    note: Option<String>,
+}
+
+#[test]
+fn test_collections_and_options() {
+    let yaml = r#"
+            owner: ~
+            tags: [a, "b c"]
+            counts:
+              apples: 3
+              7: 1
+            people:
+              - name: Alice
+                age: 30
+              - {name: Bob, age: 4}
+        "#;
+
+    let inventory: Inventory = from_str(yaml).unwrap();
+    assert_eq!(inventory.owner, None);
+    assert_eq!(inventory.tags, ["a", "b c"]);
+    assert_eq!(inventory.counts["apples"], 3);
+    assert_eq!(inventory.counts["7"], 1);
+    assert_eq!(inventory.people[1].name, "Bob");
+    // missing options are `None`
+    assert_eq!(inventory.note, None);
+
+    let err = from_str::<Inventory>("tags: [a]\ncounts: {}\npeople: [{name: Eve}]").unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "line 3, column 10: Missing field `age` for Person at `people[0]`"
+    );
+
+    let err = from_str::<Vec<u64>>("[1, two]").unwrap_err();
+    assert_eq!((err.line, err.column), (1, 5));
+    let ReadErrorKind::Value(value) = &err.kind else {
+        panic!("expected a value error, got {err}");
+    };
+    assert_eq!(value.path.to_string(), "[1]");
+    assert!(matches!(value.kind, DeserializeErrorKind::InvalidValue(_)));
+}
+
+#[test]
+fn test_to_string_styles() {
+    let inventory = Inventory {
+        owner: Some("Ferris".to_string()),
+        tags: vec![],
+        counts: [("apples".to_string(), 3)].into_iter().collect(),
+        people: vec![
+            Person {
+                name: "Alice".to_string(),
+                age: 30,
+            },
+            Person {
+                name: "Bob, Jr.".to_string(),
+                age: 4,
+            },
+        ],
+        note: None,
+    };
+
+    let block = to_string(&inventory).unwrap();
+    assert_eq!(
+        block,
+        "owner: Ferris\n\
+         tags: []\n\
+         counts:\n  \
+           apples: 3\n\
+         people:\n  \
+           - name: Alice\n    \
+             age: 30\n  \
+           - name: Bob, Jr.\n    \
+             age: 4\n\
+         note: null\n"
+    );
+    assert_eq!(from_str::<Inventory>(&block).unwrap(), inventory);
+
+    let flow = to_string_with_style(&inventory, Style::Flow).unwrap();
+    assert_eq!(
+        flow,
+        "{owner: Ferris, tags: [], counts: {apples: 3}, \
+         people: [{name: Alice, age: 30}, {name: \"Bob, Jr.\", age: 4}], note: null}\n"
+    );
+    assert_eq!(from_str::<Inventory>(&flow).unwrap(), inventory);
+}
+
+#[test]
+fn test_strings_are_quoted_only_when_needed() {
+    let strings = [
+        "plain text",
+        "",
+        " padded",
+        "yes",
+        "Null",
+        "~",
+        "12",
+        "1e3",
+        ".inf",
+        "0x1F",
+        "- dash",
+        "key: value",
+        "a # comment",
+        "trailing:",
+        "*alias",
+        "tab\there",
+        "quote \" and \\ backslash",
+        "bell \u{7}",
+        "C:\\path",
+    ];
+    let yaml = to_string(&strings.map(String::from).to_vec()).unwrap();
+    assert_eq!(
+        yaml,
+        r#"- plain text
+- ""
+- " padded"
+- "yes"
+- "Null"
+- "~"
+- "12"
+- "1e3"
+- ".inf"
+- "0x1F"
+- "- dash"
+- "key: value"
+- "a # comment"
+- "trailing:"
+- "*alias"
+- "tab\there"
+- quote " and \ backslash
+- "bell \u0007"
+- C:\path
+"#
+    );
+    assert_eq!(from_str::<Vec<String>>(&yaml).unwrap(), strings);
+}
+
+#[test]
+fn test_multi_line_strings() {
+    let strings = [
+        "first\nsecond",
+        "first\n\nthird\n",
+        "kept\nlines\n\n",
+        " indented\nsecond",
+    ];
+    let yaml = to_string(&strings.map(String::from).to_vec()).unwrap();
+    assert_eq!(
+        yaml,
+        "- |-\n  first\n  second\n\
+         - |\n  first\n\n  third\n\
+         - |+\n  kept\n  lines\n\n\
+         - \" indented\\nsecond\"\n"
+    );
+    assert_eq!(from_str::<Vec<String>>(&yaml).unwrap(), strings);
+
+    let package = Person {
+        name: "line one\nline two\n".to_string(),
+        age: 1,
+    };
+    let yaml = to_string(&package).unwrap();
+    assert_eq!(yaml, "name: |\n  line one\n  line two\nage: 1\n");
+    assert_eq!(from_str::<Person>(&yaml).unwrap(), package);
+
+    let text = "top\nlevel".to_string();
+    assert_eq!(
+        from_str::<String>(&to_string(&text).unwrap()).unwrap(),
+        text
+    );
+}
+
+#[test]
+fn test_numbers_round_trip() {
+    let floats = vec![0.5, -0.0, 1e300, f64::INFINITY, f64::NEG_INFINITY];
+    let yaml = to_string(&floats).unwrap();
+    assert_eq!(yaml, "- 0.5\n- -0.0\n- 1e300\n- .inf\n- -.inf\n");
+    assert_eq!(from_str::<Vec<f64>>(&yaml).unwrap(), floats);
+    assert!(from_str::<f64>(".nan").unwrap().is_nan());
+
+    assert!(from_str::<u64>("-1").is_err());
+}
+
+#[derive(Debug, Facet, PartialEq)]
+struct Labeled {
+    name: String,
+    #[facet(flatten)]
+    inner: Labels,
+}
+
+#[derive(Debug, Facet, PartialEq)]
+struct Labels {
+    a: u32,
+    b: String,
+}
+
+#[test]
+fn test_flatten_round_trip() {
+    let labeled: Labeled = from_str("name: x\na: 1\nb: \"y\"\n").unwrap();
+    assert_eq!(
+        labeled,
+        Labeled {
+            name: "x".to_string(),
+            inner: Labels {
+                a: 1,
+                b: "y".to_string(),
+            },
+        }
+    );
+    // `y` is a boolean in YAML 1.1, so it's quoted
+    assert_eq!(to_string(&labeled).unwrap(), "name: x\na: 1\nb: \"y\"\n");
+
+    // flattened fields are missing from the mapping they're written in
+    let err = from_str::<Labeled>("name: x\na: 1\n").unwrap_err();
+    let ReadErrorKind::Value(value) = &err.kind else {
+        panic!("expected a value error, got {err}");
+    };
+    assert_eq!(value.kind, DeserializeErrorKind::MissingField("b"));
+    assert_eq!(value.shape, Labels::SHAPE);
+}
+
+#[derive(Debug, Facet, PartialEq)]
+struct Deployment {
+    name: String,
+    image: String,
+    replicas: u64,
+    ports: Vec<u64>,
+}
+
+#[derive(Debug, Facet, PartialEq)]
+struct Manifest {
+    shared: Vec<u64>,
+    services: Vec<Deployment>,
+}
+
+#[test]
+fn test_anchors_aliases_and_merge_keys() {
+    let yaml = r#"
+shared: &ports [80, 443]
+services:
+  - &web
+    name: web
+    image: nginx
+    replicas: 2
+    ports: *ports
+  - <<: *web
+    name: web-canary
+    replicas: 1
+  - <<: [{name: first, image: a}, {name: second, image: b, replicas: 9}]
+    ports: []
+"#;
+    let manifest: Manifest = from_str(yaml).unwrap();
+    assert_eq!(manifest.shared, [80, 443]);
+    assert_eq!(
+        manifest.services,
+        [
+            Deployment {
+                name: "web".to_string(),
+                image: "nginx".to_string(),
+                replicas: 2,
+                ports: vec![80, 443],
+            },
+            // the mapping's own keys win over merged ones
+            Deployment {
+                name: "web-canary".to_string(),
+                image: "nginx".to_string(),
+                replicas: 1,
+                ports: vec![80, 443],
+            },
+            // earlier mappings win over later ones
+            Deployment {
+                name: "first".to_string(),
+                image: "a".to_string(),
+                replicas: 9,
+                ports: vec![],
+            },
+        ]
+    );
+
+    // a quoted `<<` is a plain key
+    let err = from_str::<Deployment>("\"<<\": {name: x}").unwrap_err();
+    let ReadErrorKind::Value(value) = &err.kind else {
+        panic!("expected a value error, got {err}");
+    };
+    assert_eq!(
+        value.kind,
+        DeserializeErrorKind::UnknownField("<<".to_string())
+    );
+
+    let err = from_str::<Deployment>("name: x\n<<: [1, 2]\n").unwrap_err();
+    assert_eq!((err.line, err.column), (2, 6));
+    assert_eq!(err.kind, ReadErrorKind::InvalidMerge);
+
+    let err = from_str::<Deployment>("name: *nowhere").unwrap_err();
+    assert!(matches!(err.kind, ReadErrorKind::Syntax(_)), "{err}");
+}
+
+#[test]
+fn test_multiple_documents() {
+    let yaml = "\
+name: web
+image: nginx
+replicas: 2
+ports: [80]
+---
+name: worker
+image: busybox
+replicas: -1
+ports: []
+---
+name: cron
+image: busybox
+replicas: 1
+ports: []
+";
+    let documents: Vec<_> = from_str_multi::<Deployment>(yaml).collect();
+    assert_eq!(documents.len(), 3);
+    assert_eq!(documents[0].as_ref().unwrap().name, "web");
+    let err = documents[1].as_ref().unwrap_err();
+    assert_eq!((err.line, err.column), (8, 11));
+    let ReadErrorKind::Value(value) = &err.kind else {
+        panic!("expected a value error, got {err}");
+    };
+    assert_eq!(value.path.to_string(), "replicas");
+    assert!(matches!(value.kind, DeserializeErrorKind::InvalidValue(_)));
+    assert_eq!(documents[2].as_ref().unwrap().name, "cron");
+
+    // anchors don't carry over from one document to the next
+    let mut documents = from_str_multi::<Vec<u64>>("- &n 1\n- *n\n---\n- *n\n");
+    assert_eq!(documents.next().unwrap().unwrap(), [1, 1]);
+    assert!(documents.next().unwrap().is_err());
+    assert!(documents.next().is_none());
+
+    let err = from_str::<Deployment>(yaml).unwrap_err();
+    assert_eq!(err.kind, ReadErrorKind::MultipleDocuments);
+    assert_eq!(
+        from_str::<Deployment>("").unwrap_err().kind,
+        ReadErrorKind::NoDocument
+    );
+    assert_eq!(from_str_multi::<Deployment>("").count(), 0);
+}
+
+#[test]
+fn test_errors_have_spans_and_paths() {
+    let yaml = "\
+services:
+  - name: web
+    image: nginx
+    replicas: 2
+    ports: [80, 4x3]
+shared: []
+";
+    let err = from_str::<Manifest>(yaml).unwrap_err();
+    assert_eq!((err.line, err.column), (5, 17));
+    assert_eq!(
+        err.to_string(),
+        "line 5, column 17: Invalid u64: Parse failed for u64: invalid digit found in string \
+         (at byte 1) at `services[0].ports[1]`"
+    );
+
+    let err = from_str::<Manifest>("shared: []\nservices: {}\n").unwrap_err();
+    assert_eq!((err.line, err.column), (2, 11));
+    let ReadErrorKind::Value(value) = &err.kind else {
+        panic!("expected a value error, got {err}");
+    };
+    assert_eq!(value.path.to_string(), "services");
+    assert_eq!(
+        value.kind,
+        DeserializeErrorKind::Unexpected {
+            expected: "a sequence",
+            got: "a map"
+        }
+    );
+
+    let err = from_str::<Manifest>("shared: [1,\n").unwrap_err();
+    assert!(matches!(err.kind, ReadErrorKind::Syntax(_)), "{err}");
+    assert_eq!(err.line, 2);
+}
@@ -67,6 +67,45 @@ fn struct_with_sensitive_field() {
     }
 }
 
+#[test]
+fn struct_with_field_attrs() {
+    #[derive(Debug, Default, Facet)]
+    struct Inner {
+        depth: u32,
+    }
+
+    #[derive(Debug, Facet)]
+    struct Blah {
+        #[facet(rename = "kind")]
+        r#type: String,
+        #[facet(default)]
+        #[facet(sensitive)]
+        token: String,
+        #[facet(flatten)]
+        inner: Inner,
+        #[facet(merge = "append")]
+        tags: Vec<String>,
+        #[facet(positional)]
+        path: String,
+        #[facet(merge = "by_key(depth)")]
+        items: Vec<Inner>,
+    }
+
+    let Def::Struct(StructDef { fields, .. }) = Blah::SHAPE.def else {
+        panic!("Expected Struct innards");
+    };
+    assert_eq!(fields[0].name, "kind");
+    assert_eq!(fields[0].flags, FieldFlags::EMPTY);
+    assert_eq!(fields[1].name, "token");
+    assert_eq!(fields[1].flags, FieldFlags::DEFAULT | FieldFlags::SENSITIVE);
+    assert_eq!(fields[2].flags, FieldFlags::FLATTEN);
+    assert_eq!(fields[3].flags, FieldFlags::MERGE_APPEND);
+    assert_eq!(fields[3].merge_key, None);
+    assert_eq!(fields[4].flags, FieldFlags::POSITIONAL);
+    assert_eq!(fields[5].flags, FieldFlags::MERGE_BY_KEY);
+    assert_eq!(fields[5].merge_key, Some("depth"));
+}
+
 #[test]
 fn struct_repr_c() {
     #[derive(Clone, Hash, PartialEq, Eq, ::facet::Facet)]
@@ -81,7 +120,17 @@ fn struct_repr_c() {
 fn struct_doc_comment() {
     #[derive(Clone, Hash, PartialEq, Eq, ::facet::Facet)]
     /// yes
-    struct Foo {}
+    struct Foo {
+        /// the bar
+        /// of foo
+        bar: u32,
+    }
+
+    assert_eq!(Foo::SHAPE.doc, [" yes"]);
+    let Def::Struct(StructDef { fields, .. }) = Foo::SHAPE.def else {
+        panic!("Expected Struct innards");
+    };
+    assert_eq!(fields[0].doc, [" the bar", " of foo"]);
 }
 
 #[test]
@@ -202,6 +251,145 @@ fn derive_real_life_cub_config() {
     }
 }
 
+#[test]
+fn derive_parse_from_str() {
+    #[derive(Debug, PartialEq, Facet)]
+    struct Version(u32, u32);
+
+    impl core::str::FromStr for Version {
+        type Err = &'static str;
+
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            let (major, minor) = s.split_once('.').ok_or("expected major.minor")?;
+            Ok(Version(
+                major.parse().map_err(|_| "bad major")?,
+                minor.parse().map_err(|_| "bad minor")?,
+            ))
+        }
+    }
+
+    let parse = Version::SHAPE
+        .vtable
+        .parse
+        .expect("FromStr should fill parse");
+    let mut slot = core::mem::MaybeUninit::<Version>::uninit();
+    let res = unsafe { parse("1.2", facet::OpaqueUninit::from_maybe_uninit(&mut slot)) };
+    assert!(res.is_ok());
+    assert_eq!(unsafe { slot.assume_init_read() }, Version(1, 2));
+
+    let err = unsafe { parse("1", facet::OpaqueUninit::from_maybe_uninit(&mut slot)) }
+        .err()
+        .expect("parse should fail");
+    assert_eq!(err.message.as_str(), "expected major.minor");
+
+    #[derive(Facet)]
+    struct NoParse {
+        foo: u32,
+    }
+    assert!(NoParse::SHAPE.vtable.parse.is_none());
+}
+
+#[test]
+fn derive_try_from_attr() {
+    #[derive(Debug, PartialEq, Facet)]
+    #[facet(try_from = u64)]
+    #[facet(try_from = &str)]
+    struct Port(u16);
+
+    impl TryFrom<u64> for Port {
+        type Error = core::num::TryFromIntError;
+
+        fn try_from(value: u64) -> Result<Self, Self::Error> {
+            Ok(Port(u16::try_from(value)?))
+        }
+    }
+
+    impl TryFrom<&str> for Port {
+        type Error = ();
+
+        fn try_from(value: &str) -> Result<Self, Self::Error> {
+            value.parse().map(Port).map_err(|_| ())
+        }
+    }
+
+    let try_from = Port::SHAPE
+        .vtable
+        .try_from
+        .expect("try_from attr should fill try_from");
+    let mut slot = core::mem::MaybeUninit::<Port>::uninit();
+
+    let source = 8080u64;
+    let res = unsafe {
+        try_from(
+            facet::OpaqueConst::from_ref(&source),
+            u64::SHAPE,
+            facet::OpaqueUninit::from_maybe_uninit(&mut slot),
+        )
+    };
+    assert!(res.is_ok());
+    assert_eq!(unsafe { slot.assume_init_read() }, Port(8080));
+
+    let source = "443";
+    let res = unsafe {
+        try_from(
+            facet::OpaqueConst::from_ref(&source),
+            <&str>::SHAPE,
+            facet::OpaqueUninit::from_maybe_uninit(&mut slot),
+        )
+    };
+    assert!(res.is_ok());
+    assert_eq!(unsafe { slot.assume_init_read() }, Port(443));
+
+    let source = 70000u64;
+    let err = unsafe {
+        try_from(
+            facet::OpaqueConst::from_ref(&source),
+            u64::SHAPE,
+            facet::OpaqueUninit::from_maybe_uninit(&mut slot),
+        )
+    }
+    .err()
+    .expect("conversion should fail");
+    assert!(matches!(
+        err,
+        facet::TryFromError::Failed { ref message, .. }
+            if message.as_str() == "out of range integral type conversion attempted"
+    ));
+
+    let source = "nope";
+    let err = unsafe {
+        try_from(
+            facet::OpaqueConst::from_ref(&source),
+            <&str>::SHAPE,
+            facet::OpaqueUninit::from_maybe_uninit(&mut slot),
+        )
+    }
+    .err()
+    .expect("conversion should fail");
+    assert!(matches!(
+        err,
+        facet::TryFromError::Failed { ref message, .. } if message.as_str() == "conversion failed"
+    ));
+
+    let source = 1.5f64;
+    let err = unsafe {
+        try_from(
+            facet::OpaqueConst::from_ref(&source),
+            f64::SHAPE,
+            facet::OpaqueUninit::from_maybe_uninit(&mut slot),
+        )
+    }
+    .err()
+    .expect("conversion should fail");
+    assert_eq!(
+        err,
+        facet::TryFromError::Incompatible {
+            source: f64::SHAPE,
+            target: Port::SHAPE,
+        }
+    );
+}
+
 // #[test]
 // fn struct_with_tuple() {
 //     #[derive(Debug, ::facet::Facet)]